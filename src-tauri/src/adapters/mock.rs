@@ -0,0 +1,244 @@
+//! In-memory adapters for unit tests
+//!
+//! Lets core logic that takes a `CredentialManager` or `RegistryAdapter` be
+//! tested without touching Windows Credential Manager or the registry, and
+//! records every credential save for assertions.
+
+use super::{CredentialManager, CredentialMetadata, CredentialPersistence, CredentialType, RegistryAdapter};
+use crate::errors::AppError;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// A recorded `save_with_persistence` call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveCall {
+    pub target: String,
+    pub username: String,
+    pub cred_type: CredentialType,
+    pub persistence: CredentialPersistence,
+}
+
+/// In-memory `CredentialManager` that records save calls
+#[derive(Default)]
+pub struct MockCredentialManager {
+    store: Mutex<HashMap<(String, u8), (String, String)>>,
+    saves: Mutex<Vec<SaveCall>>,
+    failing_targets: Mutex<HashSet<String>>,
+    failing_deletes: Mutex<HashSet<String>>,
+    last_written: Mutex<HashMap<(String, u8), u64>>,
+}
+
+/// Maps a credential type to a hashable key component
+fn type_key(cred_type: CredentialType) -> u8 {
+    match cred_type {
+        CredentialType::Generic => 0,
+        CredentialType::DomainPassword => 1,
+    }
+}
+
+impl MockCredentialManager {
+    /// Creates an empty mock credential manager
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns every save call made so far, in order
+    pub fn saves(&self) -> Vec<SaveCall> {
+        self.saves.lock().expect("mock lock poisoned").clone()
+    }
+
+    /// Makes every later save to `target` fail (the attempt is still recorded)
+    pub fn fail_saves_to(&self, target: &str) {
+        self.failing_targets
+            .lock()
+            .expect("mock lock poisoned")
+            .insert(target.to_string());
+    }
+
+    /// Sets the FILETIME reported as the credential's `LastWritten` (0 until set)
+    pub fn set_last_written(&self, target: &str, cred_type: CredentialType, filetime: u64) {
+        self.last_written
+            .lock()
+            .expect("mock lock poisoned")
+            .insert((target.to_string(), type_key(cred_type)), filetime);
+    }
+
+    /// Makes every later delete of `target` fail, leaving it stored
+    pub fn fail_deletes_to(&self, target: &str) {
+        self.failing_deletes
+            .lock()
+            .expect("mock lock poisoned")
+            .insert(target.to_string());
+    }
+}
+
+impl CredentialManager for MockCredentialManager {
+    fn save_with_persistence(
+        &self,
+        target: &str,
+        username: &str,
+        password: &str,
+        cred_type: CredentialType,
+        persistence: CredentialPersistence,
+    ) -> Result<(), AppError> {
+        self.saves.lock().expect("mock lock poisoned").push(SaveCall {
+            target: target.to_string(),
+            username: username.to_string(),
+            cred_type,
+            persistence,
+        });
+        if self.failing_targets.lock().expect("mock lock poisoned").contains(target) {
+            return Err(AppError::CredentialManagerError {
+                operation: format!("save {}", target),
+                source: None,
+            });
+        }
+        self.store.lock().expect("mock lock poisoned").insert(
+            (target.to_string(), type_key(cred_type)),
+            (username.to_string(), password.to_string()),
+        );
+        Ok(())
+    }
+
+    fn read_typed(
+        &self,
+        target: &str,
+        cred_type: CredentialType,
+    ) -> Result<Option<(String, String)>, AppError> {
+        Ok(self
+            .store
+            .lock()
+            .expect("mock lock poisoned")
+            .get(&(target.to_string(), type_key(cred_type)))
+            .cloned())
+    }
+
+    fn read_metadata_typed(
+        &self,
+        target: &str,
+        cred_type: CredentialType,
+    ) -> Result<Option<CredentialMetadata>, AppError> {
+        let key = (target.to_string(), type_key(cred_type));
+        let Some((username, _)) = self.store.lock().expect("mock lock poisoned").get(&key).cloned() else {
+            return Ok(None);
+        };
+        let last_written = self
+            .last_written
+            .lock()
+            .expect("mock lock poisoned")
+            .get(&key)
+            .copied()
+            .unwrap_or_default();
+        Ok(Some(CredentialMetadata {
+            username,
+            last_written,
+        }))
+    }
+
+    fn delete_typed(&self, target: &str, cred_type: CredentialType) -> Result<(), AppError> {
+        if self.failing_deletes.lock().expect("mock lock poisoned").contains(target) {
+            return Err(AppError::CredentialManagerError {
+                operation: format!("delete {}", target),
+                source: None,
+            });
+        }
+        self.store
+            .lock()
+            .expect("mock lock poisoned")
+            .remove(&(target.to_string(), type_key(cred_type)));
+        Ok(())
+    }
+
+    fn list_with_prefix_typed(
+        &self,
+        prefix: &str,
+        cred_type: CredentialType,
+    ) -> Result<Vec<String>, AppError> {
+        let mut targets: Vec<String> = self
+            .store
+            .lock()
+            .expect("mock lock poisoned")
+            .keys()
+            .filter(|(target, key)| *key == type_key(cred_type) && target.starts_with(prefix))
+            .map(|(target, _)| target.clone())
+            .collect();
+        targets.sort();
+        Ok(targets)
+    }
+}
+
+/// In-memory `RegistryAdapter` holding string and DWORD values per key
+///
+/// Values are kept in insertion order, like a freshly written registry key.
+#[derive(Default)]
+pub struct MockRegistry {
+    strings: Mutex<Vec<(String, String, String)>>,
+    dwords: Mutex<HashMap<(String, String), u32>>,
+}
+
+impl MockRegistry {
+    /// Creates an empty mock registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a DWORD value
+    pub fn set_dword(&self, key_path: &str, value_name: &str, value: u32) {
+        self.dwords
+            .lock()
+            .expect("mock lock poisoned")
+            .insert((key_path.to_string(), value_name.to_string()), value);
+    }
+}
+
+impl RegistryAdapter for MockRegistry {
+    fn read_string(&self, key_path: &str, value_name: &str) -> Result<Option<String>, AppError> {
+        Ok(self
+            .strings
+            .lock()
+            .expect("mock lock poisoned")
+            .iter()
+            .find(|(key, name, _)| key == key_path && name.eq_ignore_ascii_case(value_name))
+            .map(|(_, _, value)| value.clone()))
+    }
+
+    fn write_string(&self, key_path: &str, value_name: &str, value: &str) -> Result<(), AppError> {
+        let mut strings = self.strings.lock().expect("mock lock poisoned");
+        match strings
+            .iter_mut()
+            .find(|(key, name, _)| key == key_path && name.eq_ignore_ascii_case(value_name))
+        {
+            Some(entry) => entry.2 = value.to_string(),
+            None => strings.push((key_path.to_string(), value_name.to_string(), value.to_string())),
+        }
+        Ok(())
+    }
+
+    fn delete_value(&self, key_path: &str, value_name: &str) -> Result<(), AppError> {
+        self.strings
+            .lock()
+            .expect("mock lock poisoned")
+            .retain(|(key, name, _)| !(key == key_path && name.eq_ignore_ascii_case(value_name)));
+        Ok(())
+    }
+
+    fn read_dword(&self, key_path: &str, value_name: &str) -> Result<Option<u32>, AppError> {
+        Ok(self
+            .dwords
+            .lock()
+            .expect("mock lock poisoned")
+            .get(&(key_path.to_string(), value_name.to_string()))
+            .copied())
+    }
+
+    fn list_string_values(&self, key_path: &str) -> Result<Vec<(String, String)>, AppError> {
+        Ok(self
+            .strings
+            .lock()
+            .expect("mock lock poisoned")
+            .iter()
+            .filter(|(key, _, _)| key == key_path)
+            .map(|(_, name, value)| (name.clone(), value.clone()))
+            .collect())
+    }
+}
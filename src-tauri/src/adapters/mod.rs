@@ -5,3 +5,6 @@ pub mod windows;
 
 #[cfg(target_os = "windows")]
 pub use windows::*;
+
+#[cfg(all(test, target_os = "windows"))]
+pub mod mock;
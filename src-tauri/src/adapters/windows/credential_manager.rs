@@ -8,12 +8,70 @@ use crate::errors::AppError;
 use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
 use windows::core::{PCWSTR, PWSTR};
-use windows::Win32::Foundation::FILETIME;
+use windows::Win32::Foundation::{ERROR_NOT_FOUND, FILETIME};
 use windows::Win32::Security::Credentials::{
     CredDeleteW, CredReadW, CredWriteW, CREDENTIALW, CRED_ENUMERATE_FLAGS, CRED_FLAGS,
-    CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
+    CRED_PERSIST, CRED_PERSIST_LOCAL_MACHINE, CRED_PERSIST_SESSION, CRED_TYPE,
+    CRED_TYPE_DOMAIN_PASSWORD, CRED_TYPE_GENERIC,
 };
 
+/// Type of a Windows Credential Manager entry
+///
+/// QuickConnect stores its own credentials as generic credentials. Windows'
+/// own RDP client (the "Remember me" option in mstsc) stores `TERMSRV/*`
+/// entries as domain password credentials, so both types are needed to
+/// interoperate with native Windows credential storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CredentialType {
+    /// `CRED_TYPE_GENERIC` - readable by the application that stored it
+    #[default]
+    Generic,
+    /// `CRED_TYPE_DOMAIN_PASSWORD` - used by Windows for RDP/SMB logons
+    ///
+    /// The password blob of a domain credential is only readable by the
+    /// Windows logon components; reading one returns the username with an
+    /// empty password.
+    DomainPassword,
+}
+
+impl CredentialType {
+    /// Returns the Windows API constant for this credential type
+    fn as_raw(self) -> CRED_TYPE {
+        match self {
+            CredentialType::Generic => CRED_TYPE_GENERIC,
+            CredentialType::DomainPassword => CRED_TYPE_DOMAIN_PASSWORD,
+        }
+    }
+}
+
+/// How long a saved credential is kept by Windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CredentialPersistence {
+    /// `CRED_PERSIST_LOCAL_MACHINE` - survives logoff and reboot
+    #[default]
+    LocalMachine,
+    /// `CRED_PERSIST_SESSION` - removed when the user logs off
+    Session,
+}
+
+impl CredentialPersistence {
+    /// Returns the Windows API constant for this persistence
+    fn as_raw(self) -> CRED_PERSIST {
+        match self {
+            CredentialPersistence::LocalMachine => CRED_PERSIST_LOCAL_MACHINE,
+            CredentialPersistence::Session => CRED_PERSIST_SESSION,
+        }
+    }
+}
+
+/// A credential's details other than its password
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialMetadata {
+    pub username: String,
+    /// `LastWritten` as a FILETIME (100-nanosecond intervals since 1601-01-01 UTC)
+    pub last_written: u64,
+}
+
 /// Trait for credential storage operations
 ///
 /// This trait abstracts credential storage to enable:
@@ -21,39 +79,112 @@ use windows::Win32::Security::Credentials::{
 /// - Future support for other platforms (keyring on Linux, Keychain on macOS)
 /// - Easier reasoning about credential operations
 pub trait CredentialManager: Send + Sync {
-    /// Saves credentials to secure storage
+    /// Saves credentials of the given type and persistence to secure storage
     ///
     /// # Arguments
     /// * `target` - Unique identifier for the credentials (e.g., "QuickConnect" or "TERMSRV/hostname")
     /// * `username` - Username to store
     /// * `password` - Password to store securely
-    fn save(&self, target: &str, username: &str, password: &str) -> Result<(), AppError>;
-
-    /// Retrieves credentials from secure storage
+    /// * `cred_type` - Credential type to store the entry as
+    /// * `persistence` - How long Windows keeps the credential
+    fn save_with_persistence(
+        &self,
+        target: &str,
+        username: &str,
+        password: &str,
+        cred_type: CredentialType,
+        persistence: CredentialPersistence,
+    ) -> Result<(), AppError>;
+
+    /// Retrieves credentials of the given type from secure storage
     ///
     /// # Arguments
     /// * `target` - Unique identifier for the credentials
+    /// * `cred_type` - Credential type to look up
     ///
     /// # Returns
     /// * `Ok(Some((username, password)))` - If credentials exist
     /// * `Ok(None)` - If credentials don't exist
     /// * `Err(AppError)` - If an error occurred during retrieval
-    fn read(&self, target: &str) -> Result<Option<(String, String)>, AppError>;
+    fn read_typed(
+        &self,
+        target: &str,
+        cred_type: CredentialType,
+    ) -> Result<Option<(String, String)>, AppError>;
 
-    /// Deletes credentials from secure storage
+    /// Retrieves a credential's username and last-written time, never its password
     ///
     /// # Arguments
     /// * `target` - Unique identifier for the credentials
-    fn delete(&self, target: &str) -> Result<(), AppError>;
+    /// * `cred_type` - Credential type to look up
+    ///
+    /// # Returns
+    /// * `Ok(Some(CredentialMetadata))` - If the credential exists
+    /// * `Ok(None)` - If the credential doesn't exist
+    /// * `Err(AppError)` - If an error occurred during retrieval
+    fn read_metadata_typed(
+        &self,
+        target: &str,
+        cred_type: CredentialType,
+    ) -> Result<Option<CredentialMetadata>, AppError>;
 
-    /// Lists all credential targets matching a prefix
+    /// Deletes credentials of the given type from secure storage
+    ///
+    /// # Arguments
+    /// * `target` - Unique identifier for the credentials
+    /// * `cred_type` - Credential type to delete
+    fn delete_typed(&self, target: &str, cred_type: CredentialType) -> Result<(), AppError>;
+
+    /// Lists all credential targets of the given type matching a prefix
     ///
     /// # Arguments
     /// * `prefix` - Prefix to filter credentials (e.g., "TERMSRV/" for all RDP credentials)
+    /// * `cred_type` - Only targets of this credential type are returned
     ///
     /// # Returns
     /// * Vector of target names
-    fn list_with_prefix(&self, prefix: &str) -> Result<Vec<String>, AppError>;
+    fn list_with_prefix_typed(
+        &self,
+        prefix: &str,
+        cred_type: CredentialType,
+    ) -> Result<Vec<String>, AppError>;
+
+    /// Saves credentials of the given type, persisted on the local machine
+    fn save_typed(
+        &self,
+        target: &str,
+        username: &str,
+        password: &str,
+        cred_type: CredentialType,
+    ) -> Result<(), AppError> {
+        self.save_with_persistence(
+            target,
+            username,
+            password,
+            cred_type,
+            CredentialPersistence::LocalMachine,
+        )
+    }
+
+    /// Saves generic credentials to secure storage
+    fn save(&self, target: &str, username: &str, password: &str) -> Result<(), AppError> {
+        self.save_typed(target, username, password, CredentialType::Generic)
+    }
+
+    /// Retrieves generic credentials from secure storage
+    fn read(&self, target: &str) -> Result<Option<(String, String)>, AppError> {
+        self.read_typed(target, CredentialType::Generic)
+    }
+
+    /// Deletes generic credentials from secure storage
+    fn delete(&self, target: &str) -> Result<(), AppError> {
+        self.delete_typed(target, CredentialType::Generic)
+    }
+
+    /// Lists all generic credential targets matching a prefix
+    fn list_with_prefix(&self, prefix: &str) -> Result<Vec<String>, AppError> {
+        self.list_with_prefix_typed(prefix, CredentialType::Generic)
+    }
 }
 
 /// Windows implementation of CredentialManager
@@ -76,7 +207,14 @@ impl Default for WindowsCredentialManager {
 }
 
 impl CredentialManager for WindowsCredentialManager {
-    fn save(&self, target: &str, username: &str, password: &str) -> Result<(), AppError> {
+    fn save_with_persistence(
+        &self,
+        target: &str,
+        username: &str,
+        password: &str,
+        cred_type: CredentialType,
+        persistence: CredentialPersistence,
+    ) -> Result<(), AppError> {
         unsafe {
             // Convert strings to UTF-16 (wide) format required by Windows APIs
             // Windows uses UTF-16 internally, so all strings must be converted
@@ -96,17 +234,24 @@ impl CredentialManager for WindowsCredentialManager {
                 .chain(std::iter::once(0))
                 .collect();
 
+            // Domain credentials must not include the null terminator in the blob,
+            // otherwise Windows logon treats it as part of the password
+            let blob_chars = match cred_type {
+                CredentialType::Generic => password_wide.len(),
+                CredentialType::DomainPassword => password_wide.len() - 1,
+            };
+
             // Build CREDENTIALW structure for Windows Credential Manager
             // This structure defines all aspects of the stored credential
             let cred = CREDENTIALW {
                 Flags: CRED_FLAGS(0),  // No special flags
-                Type: CRED_TYPE_GENERIC,  // Generic credentials (not domain/cert-based)
+                Type: cred_type.as_raw(),  // Generic or domain password credentials
                 TargetName: PWSTR(target_name.as_ptr() as *mut u16),  // Unique identifier
                 Comment: PWSTR::null(),  // Optional comment field (unused)
                 LastWritten: FILETIME::default(),  // OS manages this timestamp
-                CredentialBlobSize: (password_wide.len() * 2) as u32,  // Size in bytes (u16 * 2)
+                CredentialBlobSize: (blob_chars * 2) as u32,  // Size in bytes (u16 * 2)
                 CredentialBlob: password_wide.as_ptr() as *mut u8,  // Password data
-                Persist: CRED_PERSIST_LOCAL_MACHINE,  // Persists across logins
+                Persist: persistence.as_raw(),  // Local machine (default) or logon session
                 AttributeCount: 0,  // No custom attributes
                 Attributes: std::ptr::null_mut(),  // No custom attributes
                 TargetAlias: PWSTR::null(),  // Optional alias (unused)
@@ -122,7 +267,11 @@ impl CredentialManager for WindowsCredentialManager {
         Ok(())
     }
 
-    fn read(&self, target: &str) -> Result<Option<(String, String)>, AppError> {
+    fn read_typed(
+        &self,
+        target: &str,
+        cred_type: CredentialType,
+    ) -> Result<Option<(String, String)>, AppError> {
         unsafe {
             let target_name: Vec<u16> = OsStr::new(target)
                 .encode_wide()
@@ -133,7 +282,7 @@ impl CredentialManager for WindowsCredentialManager {
 
             match CredReadW(
                 PCWSTR::from_raw(target_name.as_ptr()),
-                CRED_TYPE_GENERIC,
+                cred_type.as_raw(),
                 0,
                 &mut pcred,
             ) {
@@ -153,11 +302,16 @@ impl CredentialManager for WindowsCredentialManager {
                     };
 
                     // Extract password from credential blob
-                    // Password is stored as UTF-16 (wide string) in the blob
-                    let password_bytes = std::slice::from_raw_parts(
-                        cred.CredentialBlob,
-                        cred.CredentialBlobSize as usize,
-                    );
+                    // Password is stored as UTF-16 (wide string) in the blob.
+                    // Domain credentials come back with an empty blob.
+                    let password_bytes = if cred.CredentialBlob.is_null() {
+                        &[][..]
+                    } else {
+                        std::slice::from_raw_parts(
+                            cred.CredentialBlob,
+                            cred.CredentialBlobSize as usize,
+                        )
+                    };
 
                     // Convert byte pairs to u16 values (UTF-16 characters)
                     // Each UTF-16 character is 2 bytes in little-endian format
@@ -186,31 +340,92 @@ impl CredentialManager for WindowsCredentialManager {
         }
     }
 
-    fn delete(&self, target: &str) -> Result<(), AppError> {
+    fn read_metadata_typed(
+        &self,
+        target: &str,
+        cred_type: CredentialType,
+    ) -> Result<Option<CredentialMetadata>, AppError> {
+        use windows::Win32::Security::Credentials::CredFree;
+
         unsafe {
             let target_name: Vec<u16> = OsStr::new(target)
                 .encode_wide()
                 .chain(std::iter::once(0))
                 .collect();
 
-            // Call Windows CredDeleteW API to remove the credential
-            // If the credential doesn't exist, this returns an error,
-            // but we treat that as success (idempotent delete)
-            CredDeleteW(
+            let mut pcred = std::ptr::null_mut();
+            if CredReadW(
                 PCWSTR::from_raw(target_name.as_ptr()),
-                CRED_TYPE_GENERIC,  // Must match the type used when saving
-                0,  // Reserved parameter, must be 0
+                cred_type.as_raw(),
+                0,
+                &mut pcred,
             )
-            .map_err(|e| AppError::CredentialManagerError {
-                operation: format!("delete credentials for target '{}'", target),
+            .is_err()
+            {
+                // Credential not found is not an error, just return None
+                return Ok(None);
+            }
+
+            let cred = &*(pcred as *const CREDENTIALW);
+
+            // Only the username and timestamp are copied; the credential blob
+            // holding the password is never read
+            let username = if cred.UserName.is_null() {
+                Ok(String::new())
+            } else {
+                PWSTR::from_raw(cred.UserName.0).to_string()
+            };
+            let last_written = (u64::from(cred.LastWritten.dwHighDateTime) << 32)
+                | u64::from(cred.LastWritten.dwLowDateTime);
+
+            // Windows allocated the credential, so it must free it
+            CredFree(pcred as *const _);
+
+            let username = username.map_err(|e| AppError::CredentialManagerError {
+                operation: format!("decode username for target '{}'", target),
                 source: Some(e.into()),
             })?;
+            Ok(Some(CredentialMetadata {
+                username,
+                last_written,
+            }))
+        }
+    }
+
+    fn delete_typed(&self, target: &str, cred_type: CredentialType) -> Result<(), AppError> {
+        unsafe {
+            let target_name: Vec<u16> = OsStr::new(target)
+                .encode_wide()
+                .chain(std::iter::once(0))
+                .collect();
+
+            // Call Windows CredDeleteW API to remove the credential
+            // If the credential doesn't exist, this returns ERROR_NOT_FOUND,
+            // but we treat that as success (idempotent delete)
+            match CredDeleteW(
+                PCWSTR::from_raw(target_name.as_ptr()),
+                cred_type.as_raw(),  // Must match the type used when saving
+                0,  // Reserved parameter, must be 0
+            ) {
+                Ok(()) => {}
+                Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => {}
+                Err(e) => {
+                    return Err(AppError::CredentialManagerError {
+                        operation: format!("delete credentials for target '{}'", target),
+                        source: Some(e.into()),
+                    })
+                }
+            }
         }
 
         Ok(())
     }
 
-    fn list_with_prefix(&self, prefix: &str) -> Result<Vec<String>, AppError> {
+    fn list_with_prefix_typed(
+        &self,
+        prefix: &str,
+        cred_type: CredentialType,
+    ) -> Result<Vec<String>, AppError> {
         use windows::Win32::Security::Credentials::{CredEnumerateW, CredFree};
 
         unsafe {
@@ -242,6 +457,10 @@ impl CredentialManager for WindowsCredentialManager {
                     // Extract target name from each credential
                     for cred_ptr in credentials {
                         let cred = &**cred_ptr;  // Dereference twice: *CREDENTIALW* -> CREDENTIALW
+                        // CredEnumerateW returns every type, keep only the requested one
+                        if cred.Type != cred_type.as_raw() {
+                            continue;
+                        }
                         if !cred.TargetName.is_null() {
                             // Convert UTF-16 target name to Rust String
                             if let Ok(target_name) = PWSTR::from_raw(cred.TargetName.0).to_string()
@@ -265,3 +484,35 @@ impl CredentialManager for WindowsCredentialManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_password_save_read_roundtrip() {
+        let manager = WindowsCredentialManager::new();
+        let target = format!("TERMSRV/quickconnect-test-{}.invalid", std::process::id());
+
+        manager
+            .save_typed(&target, "CONTOSO\\testuser", "P@ssw0rd!", CredentialType::DomainPassword)
+            .expect("Saving a domain credential should succeed");
+
+        let result = manager.read_typed(&target, CredentialType::DomainPassword);
+        let generic = manager.read(&target);
+        let listed = manager.list_with_prefix_typed(&target, CredentialType::DomainPassword);
+        let _ = manager.delete_typed(&target, CredentialType::DomainPassword);
+
+        let (username, _password) = result
+            .expect("Reading a domain credential should succeed")
+            .expect("Domain credential should exist");
+        assert_eq!(username, "CONTOSO\\testuser");
+        // Domain credentials are not visible through the generic type
+        assert!(generic.expect("Generic read should succeed").is_none());
+        assert_eq!(listed.expect("Listing should succeed"), vec![target.clone()]);
+        assert!(manager
+            .read_typed(&target, CredentialType::DomainPassword)
+            .expect("Reading after delete should succeed")
+            .is_none());
+    }
+}
@@ -0,0 +1,38 @@
+//! Windows disk space adapter
+//!
+//! Wraps `GetDiskFreeSpaceExW` so the rest of the app can report free space
+//! on the data volume without unsafe code.
+
+use crate::errors::AppError;
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use windows::core::PCWSTR;
+use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+/// Returns the bytes free to the current user on the volume holding `path`
+///
+/// # Arguments
+/// * `path` - An existing directory on the volume
+///
+/// # Returns
+/// * `Ok(u64)` - Free bytes, honouring per-user disk quotas
+/// * `Err(AppError)` - The path doesn't exist or the query failed
+pub fn free_disk_space(path: &Path) -> Result<u64, AppError> {
+    let wide: Vec<u16> = OsStr::new(path)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut free_to_caller: u64 = 0;
+
+    // SAFETY: `wide` is a NUL-terminated UTF-16 string that outlives the call
+    let result = unsafe {
+        GetDiskFreeSpaceExW(PCWSTR(wide.as_ptr()), Some(&mut free_to_caller as *mut u64), None, None)
+    };
+    result.map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: std::io::Error::other(e.to_string()),
+    })?;
+
+    Ok(free_to_caller)
+}
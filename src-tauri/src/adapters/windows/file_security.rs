@@ -0,0 +1,72 @@
+//! Windows file security adapter
+//!
+//! Wraps `SetFileSecurityW` so a file can be locked down to its owner
+//! without unsafe code elsewhere.
+
+use crate::errors::AppError;
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{LocalFree, HLOCAL};
+use windows::Win32::Security::Authorization::{
+    ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
+use windows::Win32::Security::{
+    SetFileSecurityW, DACL_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION,
+    PSECURITY_DESCRIPTOR,
+};
+
+/// Protected DACL granting full access to the file's owner only
+const OWNER_ONLY_SDDL: &str = "D:P(A;;FA;;;OW)";
+
+fn wide(value: &OsStr) -> Vec<u16> {
+    value.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+/// Replaces a file's permissions so only its owner can open it
+///
+/// Inherited permissions (e.g. from the temp directory) are removed.
+///
+/// # Returns
+/// * `Ok(())` - The file is readable and writable by its owner only
+/// * `Err(AppError)` - The security descriptor could not be built or applied
+pub fn restrict_file_to_owner(path: &Path) -> Result<(), AppError> {
+    let io_error = |e: windows::core::Error| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: std::io::Error::other(e.to_string()),
+    };
+    let sddl = wide(OsStr::new(OWNER_ONLY_SDDL));
+    let file_name = wide(path.as_os_str());
+    let mut descriptor = PSECURITY_DESCRIPTOR::default();
+
+    // SAFETY: `sddl` is a NUL-terminated UTF-16 string that outlives the call;
+    // on success `descriptor` receives a LocalAlloc'd buffer freed below
+    unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            PCWSTR(sddl.as_ptr()),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            None,
+        )
+        .map_err(io_error)?;
+    }
+
+    // SAFETY: `file_name` is NUL-terminated and `descriptor` is the valid
+    // descriptor built above
+    let result = unsafe {
+        SetFileSecurityW(
+            PCWSTR(file_name.as_ptr()),
+            DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+            descriptor,
+        )
+        .ok()
+    };
+
+    // SAFETY: `descriptor` was allocated by the conversion above and is not used again
+    unsafe {
+        let _ = LocalFree(HLOCAL(descriptor.0));
+    }
+
+    result.map_err(io_error)
+}
@@ -4,7 +4,15 @@
 //! All Windows API calls are isolated here to enable future cross-platform support.
 
 pub mod credential_manager;
+pub mod disk;
+pub mod file_security;
+pub mod process;
 pub mod registry;
 
-pub use credential_manager::{CredentialManager, WindowsCredentialManager};
-pub use registry::{RegistryAdapter, WindowsRegistry};
+pub use credential_manager::{
+    CredentialManager, CredentialMetadata, CredentialPersistence, CredentialType, WindowsCredentialManager,
+};
+pub use disk::free_disk_space;
+pub use file_security::restrict_file_to_owner;
+pub use process::{process_is_input_idle, spawn_minimized, MinimizedProcess};
+pub use registry::{RegistryAdapter, RegistryHive, WindowsRegistry};
@@ -0,0 +1,101 @@
+//! Windows process adapter
+//!
+//! Wraps `CreateProcessW` to start a process minimized without taking focus,
+//! which `std::process::Command` has no option for.
+
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, STILL_ACTIVE};
+use windows::Win32::System::Threading::{
+    CreateProcessW, GetExitCodeProcess, PROCESS_CREATION_FLAGS, PROCESS_INFORMATION,
+    STARTF_USESHOWWINDOW, STARTUPINFOW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{WaitForInputIdle, SW_SHOWMINNOACTIVE};
+
+/// A process started by `spawn_minimized`, closed when dropped
+pub struct MinimizedProcess {
+    handle: HANDLE,
+}
+
+impl MinimizedProcess {
+    /// Returns the exit code if the process has already exited
+    pub fn try_exit_code(&self) -> Option<i32> {
+        let mut code = 0u32;
+        // SAFETY: `handle` is the open process handle returned by CreateProcessW
+        unsafe { GetExitCodeProcess(self.handle, &mut code) }.ok()?;
+        (code != STILL_ACTIVE.0 as u32).then_some(code as i32)
+    }
+
+    /// Whether the process has finished starting up and is waiting for input
+    pub fn is_input_idle(&self) -> bool {
+        is_input_idle(self.handle)
+    }
+}
+
+impl Drop for MinimizedProcess {
+    fn drop(&mut self) {
+        // SAFETY: the handle is owned by this value and not used after this
+        unsafe {
+            let _ = CloseHandle(self.handle);
+        }
+    }
+}
+
+/// Whether a GUI process has finished starting up and is waiting for input
+///
+/// Checks `WaitForInputIdle` without waiting. A process that has exited or
+/// has no message queue reports `false`.
+///
+/// # Arguments
+/// * `process` - Open handle of the process (e.g. from `Child::as_raw_handle`)
+pub fn process_is_input_idle(process: std::os::windows::io::RawHandle) -> bool {
+    is_input_idle(HANDLE(process as isize))
+}
+
+fn is_input_idle(handle: HANDLE) -> bool {
+    // SAFETY: `handle` is an open process handle owned by the caller
+    unsafe { WaitForInputIdle(handle, 0) == 0 }
+}
+
+/// Starts a process minimized to the taskbar without activating it
+///
+/// The command line is passed to `CreateProcessW` as-is (no shell), so
+/// characters such as `&`, `^` and `%` in arguments have no special meaning.
+///
+/// # Arguments
+/// * `command_line` - Program and arguments, quoted by Windows rules
+pub fn spawn_minimized(command_line: &str) -> std::io::Result<MinimizedProcess> {
+    // CreateProcessW may modify the command line buffer, so it must be mutable
+    let mut command_line: Vec<u16> = command_line.encode_utf16().chain(std::iter::once(0)).collect();
+    let startup_info = STARTUPINFOW {
+        cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+        dwFlags: STARTF_USESHOWWINDOW,
+        wShowWindow: SW_SHOWMINNOACTIVE.0 as u16,
+        ..Default::default()
+    };
+    let mut process_info = PROCESS_INFORMATION::default();
+
+    // SAFETY: `command_line` is a NUL-terminated UTF-16 buffer that outlives
+    // the call; `startup_info` and `process_info` are valid for the call
+    unsafe {
+        CreateProcessW(
+            None,
+            PWSTR(command_line.as_mut_ptr()),
+            None,
+            None,
+            false,
+            PROCESS_CREATION_FLAGS(0),
+            None,
+            None,
+            &startup_info,
+            &mut process_info,
+        )
+        .map_err(|e| std::io::Error::from_raw_os_error(e.code().0 & 0xFFFF))?;
+
+        // Only the process handle is needed
+        let _ = CloseHandle(process_info.hThread);
+    }
+
+    Ok(MinimizedProcess {
+        handle: process_info.hProcess,
+    })
+}
@@ -6,10 +6,11 @@
 use crate::errors::AppError;
 use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
-use windows::core::PCWSTR;
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::{ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS};
 use windows::Win32::System::Registry::{
-    RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY,
-    HKEY_CURRENT_USER, KEY_READ, KEY_WRITE, REG_SZ, REG_VALUE_TYPE,
+    RegCloseKey, RegDeleteValueW, RegEnumValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW,
+    HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_READ, KEY_WRITE, REG_EXPAND_SZ, REG_SZ, REG_VALUE_TYPE,
 };
 
 /// Trait for registry operations
@@ -56,15 +57,60 @@ pub trait RegistryAdapter: Send + Sync {
     /// * `Ok(None)` - If value doesn't exist
     /// * `Err(AppError)` - If an error occurred
     fn read_dword(&self, key_path: &str, value_name: &str) -> Result<Option<u32>, AppError>;
+
+    /// Lists the string values of a registry key
+    ///
+    /// # Arguments
+    /// * `key_path` - Registry key path
+    ///
+    /// # Returns
+    /// * `Ok(Vec<(name, value)>)` - String (`REG_SZ`/`REG_EXPAND_SZ`) values; empty if the key doesn't exist
+    /// * `Err(AppError)` - If an error occurred
+    fn list_string_values(&self, key_path: &str) -> Result<Vec<(String, String)>, AppError>;
+}
+
+/// Registry root key an adapter reads and writes under
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegistryHive {
+    /// `HKEY_CURRENT_USER`: per-user settings
+    #[default]
+    CurrentUser,
+    /// `HKEY_LOCAL_MACHINE`: machine-wide settings, e.g. pushed by Group Policy
+    LocalMachine,
+}
+
+impl RegistryHive {
+    fn root(self) -> HKEY {
+        match self {
+            RegistryHive::CurrentUser => HKEY_CURRENT_USER,
+            RegistryHive::LocalMachine => HKEY_LOCAL_MACHINE,
+        }
+    }
 }
 
 /// Windows implementation of RegistryAdapter
-pub struct WindowsRegistry;
+///
+/// Key paths are relative to the adapter's hive (`HKEY_CURRENT_USER` unless
+/// created with `for_hive`). Writing under `HKEY_LOCAL_MACHINE` needs
+/// administrator rights.
+pub struct WindowsRegistry {
+    hive: RegistryHive,
+}
 
 impl WindowsRegistry {
-    /// Creates a new Windows registry adapter instance
+    /// Creates a new Windows registry adapter instance for `HKEY_CURRENT_USER`
     pub fn new() -> Self {
-        WindowsRegistry
+        Self::for_hive(RegistryHive::CurrentUser)
+    }
+
+    /// Creates a Windows registry adapter for the given hive
+    pub fn for_hive(hive: RegistryHive) -> Self {
+        WindowsRegistry { hive }
+    }
+
+    /// Creates a Windows registry adapter for `HKEY_LOCAL_MACHINE`
+    pub fn local_machine() -> Self {
+        Self::for_hive(RegistryHive::LocalMachine)
     }
 }
 
@@ -87,10 +133,9 @@ impl RegistryAdapter for WindowsRegistry {
             // Handle to the opened registry key
             let mut hkey = HKEY::default();
 
-            // Open registry key under HKEY_CURRENT_USER with READ access
-            // HKEY_CURRENT_USER contains per-user settings
+            // Open registry key under the adapter's hive with READ access
             match RegOpenKeyExW(
-                HKEY_CURRENT_USER,
+                self.hive.root(),
                 PCWSTR::from_raw(key_path_wide.as_ptr()),
                 0,  // Reserved, must be 0
                 KEY_READ,  // Request read-only access
@@ -169,7 +214,7 @@ impl RegistryAdapter for WindowsRegistry {
 
             // Open or create registry key
             RegOpenKeyExW(
-                HKEY_CURRENT_USER,
+                self.hive.root(),
                 PCWSTR::from_raw(key_path_wide.as_ptr()),
                 0,
                 KEY_WRITE,
@@ -231,7 +276,7 @@ impl RegistryAdapter for WindowsRegistry {
 
             // Open registry key
             RegOpenKeyExW(
-                HKEY_CURRENT_USER,
+                self.hive.root(),
                 PCWSTR::from_raw(key_path_wide.as_ptr()),
                 0,
                 KEY_WRITE,
@@ -270,7 +315,7 @@ impl RegistryAdapter for WindowsRegistry {
             let mut hkey = HKEY::default();
 
             match RegOpenKeyExW(
-                HKEY_CURRENT_USER,
+                self.hive.root(),
                 PCWSTR::from_raw(key_path_wide.as_ptr()),
                 0,
                 KEY_READ,
@@ -308,4 +353,87 @@ impl RegistryAdapter for WindowsRegistry {
             }
         }
     }
+
+    fn list_string_values(&self, key_path: &str) -> Result<Vec<(String, String)>, AppError> {
+        unsafe {
+            let key_path_wide: Vec<u16> = OsStr::new(key_path)
+                .encode_wide()
+                .chain(std::iter::once(0))
+                .collect();
+
+            let mut hkey = HKEY::default();
+
+            // A missing key simply has no values
+            if RegOpenKeyExW(
+                self.hive.root(),
+                PCWSTR::from_raw(key_path_wide.as_ptr()),
+                0,
+                KEY_READ,
+                &mut hkey,
+            )
+            .is_err()
+            {
+                return Ok(Vec::new());
+            }
+
+            let mut values = Vec::new();
+            let mut index = 0;
+            // Value names are at most 16383 characters
+            let mut name = vec![0u16; 16384];
+            // Grown when a value doesn't fit (ERROR_MORE_DATA reports the size needed)
+            let mut data = vec![0u8; 4096];
+            loop {
+                let mut name_len = name.len() as u32;
+                let mut data_len = data.len() as u32;
+                let mut value_type = 0u32;
+
+                let result = RegEnumValueW(
+                    hkey,
+                    index,
+                    PWSTR::from_raw(name.as_mut_ptr()),
+                    &mut name_len,
+                    None,
+                    Some(&mut value_type),
+                    Some(data.as_mut_ptr()),
+                    Some(&mut data_len),
+                );
+                match result {
+                    Ok(()) => {}
+                    Err(e) if e.code() == ERROR_NO_MORE_ITEMS.to_hresult() => break,
+                    Err(e)
+                        if e.code() == ERROR_MORE_DATA.to_hresult() && data_len as usize > data.len() =>
+                    {
+                        // Retry the same value with a buffer big enough for it
+                        data.resize(data_len as usize, 0);
+                        continue;
+                    }
+                    Err(e) => {
+                        let _ = RegCloseKey(hkey);
+                        return Err(AppError::RegistryError {
+                            operation: format!("enumerate values of registry key '{}'", key_path),
+                            source: Some(e.into()),
+                        });
+                    }
+                }
+                index += 1;
+
+                if value_type != REG_SZ.0 && value_type != REG_EXPAND_SZ.0 {
+                    continue;
+                }
+
+                let name = String::from_utf16_lossy(&name[..name_len as usize]);
+                let wide: Vec<u16> = data[..data_len as usize]
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect();
+                let value = String::from_utf16_lossy(&wide)
+                    .trim_end_matches('\0')
+                    .to_string();
+                values.push((name, value));
+            }
+
+            let _ = RegCloseKey(hkey);
+            Ok(values)
+        }
+    }
 }
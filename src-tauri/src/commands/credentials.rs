@@ -3,9 +3,15 @@
 //! Thin command layer for credential operations.
 //! All business logic is delegated to the credential manager adapter.
 
-use crate::{Credentials, StoredCredentials};
-use crate::adapters::{CredentialManager, WindowsCredentialManager};
-use crate::infra::debug_log;
+use crate::{AppError, Credentials, Host, StoredCredentials};
+use crate::adapters::{CredentialManager, CredentialType, WindowsCredentialManager};
+use crate::core::credentials::{
+    CredentialInfo, CredentialMeta, CredentialTarget, CredentialTestResult, GATEWAY_CREDENTIAL_PREFIX,
+};
+use crate::core::rdp::{analyze_username, UsernameInfo};
+use crate::core::rdp_launcher::CredentialResolution;
+use crate::core::stats::DashboardStats;
+use crate::infra::{debug_log, get_credential_meta_path, get_migrated_credentials_path};
 
 /// Global credential manager instance using singleton pattern
 /// 
@@ -51,9 +57,29 @@ pub async fn save_credentials(credentials: Credentials) -> Result<(), String> {
         })?;
 
     debug_log("INFO", "CREDENTIALS", "Credentials saved successfully", None);
+
+    // Record when they were saved; the credentials themselves are already stored
+    if let Err(e) = record_credential_meta() {
+        debug_log(
+            "WARN",
+            "CREDENTIALS",
+            "Failed to record when credentials were saved",
+            Some(&e),
+        );
+    }
     Ok(())
 }
 
+/// Writes the credential metadata sidecar for credentials saved now
+fn record_credential_meta() -> Result<(), String> {
+    let max_age_days = crate::core::settings::load_settings()
+        .map(|settings| settings.credential_max_age_days)
+        .unwrap_or(0);
+    let meta = CredentialMeta::new(crate::core::idle::now_secs(), max_age_days);
+    let path = get_credential_meta_path()?;
+    crate::core::credentials::save_credential_meta_to(&path, &meta).map_err(|e| e.to_string())
+}
+
 /// Retrieves stored global QuickConnect credentials
 ///
 /// # Returns
@@ -97,6 +123,23 @@ pub async fn get_stored_credentials() -> Result<Option<StoredCredentials>, Strin
     }
 }
 
+/// Retrieves the username and last-written time of a stored credential, without its password
+///
+/// Thin wrapper that delegates to core::credentials::get_credential_info(), so
+/// the UI can show who the saved credentials are for and when they were saved.
+///
+/// # Arguments
+/// * `target` - Credential target, e.g. `QuickConnect` or `TERMSRV/server01.domain.com`
+///
+/// # Returns
+/// * `Ok(Some(info))` - The credential exists
+/// * `Ok(None)` - No credential is saved for `target`
+/// * `Err(String)` - Error message for frontend
+#[tauri::command]
+pub async fn get_credential_info(target: String) -> Result<Option<CredentialInfo>, String> {
+    crate::core::credentials::get_credential_info(&*CREDENTIAL_MANAGER, &target).map_err(|e| e.to_string())
+}
+
 /// Deletes stored global QuickConnect credentials
 ///
 /// # Returns
@@ -119,9 +162,71 @@ pub async fn delete_credentials() -> Result<(), String> {
         })?;
 
     debug_log("INFO", "CREDENTIALS", "Credentials deleted successfully", None);
+
+    if let Ok(path) = get_credential_meta_path() {
+        if path.exists() {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
     Ok(())
 }
 
+/// Gets how many days ago the global credentials were saved
+///
+/// Read-only; the stale-credential warning is decided by
+/// `check_credential_age_warning`.
+///
+/// # Returns
+/// * `Ok(Some(days))` - Days since the credentials were saved
+/// * `Ok(None)` - No save time recorded (no credentials, or saved by an older version)
+/// * `Err(String)` - The metadata file could not be read
+#[tauri::command]
+pub async fn get_credential_age() -> Result<Option<u64>, String> {
+    let path = get_credential_meta_path()?;
+    let meta = crate::core::credentials::load_credential_meta_from(&path).map_err(|e| e.to_string())?;
+
+    Ok(meta.map(|meta| meta.age_days(crate::core::idle::now_secs())))
+}
+
+/// Checks whether the global credentials are past their recorded expiry or
+/// the configured `credential_max_age_days`.
+///
+/// Called by the login window once it has loaded, so the warning reaches a
+/// page that can show it.
+///
+/// # Returns
+/// * `Ok(Some(days))` - The credentials are stale; the value is their age in days
+/// * `Ok(None)` - No warning is due (fresh, or no save time recorded)
+/// * `Err(String)` - The metadata file could not be read
+///
+/// # Side Effects
+/// - Logs a warning when the credentials are stale
+#[tauri::command]
+pub async fn check_credential_age_warning() -> Result<Option<u64>, String> {
+    let path = get_credential_meta_path()?;
+    let meta = match crate::core::credentials::load_credential_meta_from(&path).map_err(|e| e.to_string())? {
+        Some(meta) => meta,
+        None => return Ok(None),
+    };
+
+    let now = crate::core::idle::now_secs();
+    let max_age_days = crate::core::settings::load_settings()
+        .map(|settings| settings.credential_max_age_days)
+        .unwrap_or(0);
+    if !meta.should_warn(now, max_age_days) {
+        return Ok(None);
+    }
+
+    let age_days = meta.age_days(now);
+    debug_log(
+        "WARN",
+        "CREDENTIALS",
+        &format!("Stored credentials are {} day(s) old", age_days),
+        None,
+    );
+    Ok(Some(age_days))
+}
+
 /// Saves per-host credentials for RDP connections
 ///
 /// Stores credentials under TERMSRV/{hostname} for Windows RDP SSO.
@@ -153,19 +258,23 @@ pub async fn save_host_credentials(
     // TERMSRV/* naming convention enables Windows RDP Single Sign-On (SSO)
     // The Windows RDP client automatically uses credentials stored under
     // TERMSRV/{hostname} when connecting, eliminating manual login prompts
-    let target = format!("TERMSRV/{}", hostname);
-
-    CREDENTIAL_MANAGER
-        .save(&target, &credentials.username, &credentials.password)
-        .map_err(|e| {
-            debug_log(
-                "ERROR",
-                "HOST_CREDENTIALS",
-                &format!("Failed to save host credentials: {}", e),
-                None,
-            );
-            e.to_string()
-        })?;
+    let migrated_path = get_migrated_credentials_path()?;
+    crate::core::credentials::save_host_credential(
+        &*CREDENTIAL_MANAGER,
+        &migrated_path,
+        &hostname,
+        &credentials.username,
+        &credentials.password,
+    )
+    .map_err(|e| {
+        debug_log(
+            "ERROR",
+            "HOST_CREDENTIALS",
+            &format!("Failed to save host credentials: {}", e),
+            None,
+        );
+        e.to_string()
+    })?;
 
     debug_log(
         "INFO",
@@ -176,6 +285,83 @@ pub async fn save_host_credentials(
     Ok(())
 }
 
+/// Stores one set of credentials under TERMSRV/{hostname} for every host in a group
+///
+/// Every host is attempted even if some fail. The password is never logged.
+///
+/// # Arguments
+/// * `group` - Group name (case-insensitive)
+/// * `username` - Username in any supported format
+/// * `password` - Password to store
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts whose credentials were saved
+/// * `Err(String)` - Empty username, no hosts in the group, or the hosts that
+///   failed along with how many succeeded
+#[tauri::command]
+pub async fn set_group_credentials(group: String, username: String, password: String) -> Result<usize, String> {
+    if username.is_empty() {
+        return Err("Username cannot be empty".to_string());
+    }
+
+    let hosts = crate::core::hosts::get_all_hosts().map_err(|e| e.to_string())?;
+    let group_hosts = crate::core::hosts::hosts_in_group(&hosts, &group);
+    if group_hosts.is_empty() {
+        return Err(format!("No hosts in group '{}'", group));
+    }
+
+    debug_log(
+        "INFO",
+        "HOST_CREDENTIALS",
+        &format!("Saving credentials for {} host(s) in group '{}'", group_hosts.len(), group),
+        Some(&format!("Username: {}", username)),
+    );
+
+    let migrated_path = get_migrated_credentials_path()?;
+    let outcome = crate::core::credentials::save_credentials_for_hosts(
+        &*CREDENTIAL_MANAGER,
+        &migrated_path,
+        &group_hosts,
+        &username,
+        &password,
+    );
+
+    if outcome.failed.is_empty() {
+        debug_log(
+            "INFO",
+            "HOST_CREDENTIALS",
+            &format!("Saved credentials for all {} host(s) in group '{}'", outcome.saved, group),
+            None,
+        );
+        return Ok(outcome.saved);
+    }
+
+    let failures = outcome
+        .failed
+        .iter()
+        .map(|failure| format!("{} ({})", failure.hostname, failure.error))
+        .collect::<Vec<_>>()
+        .join(", ");
+    debug_log(
+        "ERROR",
+        "HOST_CREDENTIALS",
+        &format!(
+            "Saved credentials for {} of {} host(s) in group '{}'",
+            outcome.saved,
+            group_hosts.len(),
+            group
+        ),
+        Some(&format!("Failed: {}", failures)),
+    );
+    Err(format!(
+        "Saved credentials for {} of {} hosts in '{}'; failed: {}",
+        outcome.saved,
+        group_hosts.len(),
+        group,
+        failures
+    ))
+}
+
 /// Retrieves per-host credentials for RDP connections
 ///
 /// # Arguments
@@ -187,51 +373,240 @@ pub async fn save_host_credentials(
 /// * `Err(String)` - Error message for frontend
 #[tauri::command]
 pub async fn get_host_credentials(hostname: String) -> Result<Option<StoredCredentials>, String> {
-    let target = format!("TERMSRV/{}", hostname);
-
-    match CREDENTIAL_MANAGER.read(&target) {
-        Ok(Some((username, password))) => {
+    let migrated_path = get_migrated_credentials_path()?;
+    match crate::core::credentials::read_host_credential(&*CREDENTIAL_MANAGER, &migrated_path, &hostname) {
+        Ok(Some(credentials)) => {
             debug_log(
                 "INFO",
                 "HOST_CREDENTIALS",
                 &format!("Found per-host credentials for {}", hostname),
                 None,
             );
-            Ok(Some(StoredCredentials { username, password }))
+            Ok(Some(credentials))
         }
         Ok(None) => Ok(None),
         Err(e) => Err(e.to_string()),
     }
 }
 
+/// Explains which stored credential a connection to a host would use
+///
+/// For troubleshooting connections made with the wrong account. The password
+/// is never returned, only its length.
+///
+/// # Arguments
+/// * `hostname` - Server hostname (need not be in hosts.csv)
+///
+/// # Returns
+/// * `Ok(CredentialResolution)` - Per-host, global or none, with the resolved domain and username
+/// * `Err(String)` - Hosts or credentials could not be read
+#[tauri::command]
+pub async fn explain_credential_resolution(hostname: String) -> Result<CredentialResolution, String> {
+    let hosts = crate::core::hosts::get_all_hosts().map_err(|e| e.to_string())?;
+    let host = hosts
+        .into_iter()
+        .find(|host| host.hostname.eq_ignore_ascii_case(&hostname))
+        .unwrap_or_else(|| Host {
+            hostname: hostname.clone(),
+            ..Default::default()
+        });
+
+    crate::core::rdp_launcher::explain_credential_resolution(
+        &host,
+        |hostname| async move {
+            get_host_credentials(hostname)
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get host credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+        || async {
+            get_stored_credentials()
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get stored credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Converts per-host credentials saved as generic credentials to the domain type
+///
+/// Thin wrapper that delegates to core::credentials::migrate_credentials_to_domain_type().
+/// Safe to run repeatedly; already-migrated credentials are not touched.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of credentials migrated
+/// * `Err(String)` - Error message for frontend
+#[tauri::command]
+pub async fn migrate_credentials_to_domain_type() -> Result<usize, String> {
+    let migrated_path = get_migrated_credentials_path()?;
+    let migrated = crate::core::credentials::migrate_credentials_to_domain_type(&*CREDENTIAL_MANAGER, &migrated_path)
+        .map_err(|e| {
+            debug_log(
+                "ERROR",
+                "HOST_CREDENTIALS",
+                &format!("Failed to migrate credentials to domain type: {}", e),
+                None,
+            );
+            e.to_string()
+        })?;
+
+    debug_log(
+        "INFO",
+        "HOST_CREDENTIALS",
+        &format!("Migrated {} credential(s) to domain type", migrated),
+        None,
+    );
+    Ok(migrated)
+}
+
+/// Clears a host's cached `TERMSRV` credentials
+///
+/// Use after a password change so the next launch saves fresh credentials.
+/// Succeeds when nothing was stored.
+///
+/// # Arguments
+/// * `hostname` - Server hostname
+///
+/// # Returns
+/// * `Ok(())` - Credentials cleared, or none were stored
+/// * `Err(String)` - Error message for frontend
+#[tauri::command]
+pub async fn clear_host_credential(hostname: String) -> Result<(), String> {
+    let migrated_path = get_migrated_credentials_path()?;
+    let deleted = crate::core::credentials::clear_host_credential(&*CREDENTIAL_MANAGER, &migrated_path, &hostname)
+        .map_err(|e| {
+            debug_log(
+                "ERROR",
+                "HOST_CREDENTIALS",
+                &format!("Failed to clear credentials for {}: {}", hostname, e),
+                None,
+            );
+            e.to_string()
+        })?;
+
+    debug_log(
+        "INFO",
+        "HOST_CREDENTIALS",
+        &format!("Cleared {} cached credential(s) for {}", deleted, hostname),
+        None,
+    );
+    Ok(())
+}
+
 /// Deletes per-host credentials
 ///
+/// Same as `clear_host_credential`, kept under this name for existing callers.
+///
 /// # Arguments
 /// * `hostname` - Server hostname
 ///
 /// # Returns
-/// * `Ok(())` - Credentials deleted successfully
+/// * `Ok(())` - Credentials deleted, or none were stored
 /// * `Err(String)` - Error message for frontend
 #[tauri::command]
 pub async fn delete_host_credentials(hostname: String) -> Result<(), String> {
-    let target = format!("TERMSRV/{}", hostname);
+    clear_host_credential(hostname).await
+}
 
+/// Saves credentials for an RD Gateway under TERMSRV/GATEWAY/{gateway_host}
+///
+/// Used when the gateway needs different credentials from the session host.
+/// At launch they are copied to TERMSRV/{gateway_host} for mstsc.
+///
+/// # Arguments
+/// * `gateway_host` - Gateway hostname as configured in `gateway_hostname`
+/// * `credentials` - Username and password for the gateway
+///
+/// # Returns
+/// * `Ok(())` - Credentials saved successfully
+/// * `Err(String)` - Error message for frontend
+#[tauri::command]
+pub async fn save_gateway_credentials(
+    gateway_host: String,
+    credentials: Credentials,
+) -> Result<(), String> {
+    let gateway_host = gateway_host.trim().to_string();
+    if gateway_host.is_empty() {
+        return Err("Gateway hostname cannot be empty".to_string());
+    }
+    if credentials.username.is_empty() {
+        return Err("Username cannot be empty".to_string());
+    }
+
+    let target = format!("{}{}", GATEWAY_CREDENTIAL_PREFIX, gateway_host);
     CREDENTIAL_MANAGER
-        .delete(&target)
+        .save(&target, &credentials.username, &credentials.password)
         .map_err(|e| {
             debug_log(
                 "ERROR",
-                "HOST_CREDENTIALS",
-                &format!("Failed to delete host credentials: {}", e),
+                "GATEWAY_CREDENTIALS",
+                &format!("Failed to save gateway credentials: {}", e),
                 None,
             );
             e.to_string()
         })?;
 
+    // Drop any stale copy written for mstsc so the next launch re-saves it
+    let mstsc_target = format!("TERMSRV/{}", gateway_host);
+    if let Ok(Some(_)) = CREDENTIAL_MANAGER.read_typed(&mstsc_target, CredentialType::DomainPassword) {
+        let _ = CREDENTIAL_MANAGER.delete_typed(&mstsc_target, CredentialType::DomainPassword);
+    }
+
     debug_log(
         "INFO",
-        "HOST_CREDENTIALS",
-        &format!("Deleted per-host credentials for {}", hostname),
+        "GATEWAY_CREDENTIALS",
+        &format!("Saved credentials for gateway {}", gateway_host),
+        None,
+    );
+    Ok(())
+}
+
+/// Deletes the credentials saved for an RD Gateway
+///
+/// Also removes the copy written to TERMSRV/{gateway_host} for mstsc, so the
+/// gateway falls back to the session credentials.
+///
+/// # Returns
+/// * `Ok(())` - Credentials deleted (or none were stored)
+/// * `Err(String)` - Error message for frontend
+#[tauri::command]
+pub async fn delete_gateway_credentials(gateway_host: String) -> Result<(), String> {
+    let gateway_host = gateway_host.trim();
+    let target = format!("{}{}", GATEWAY_CREDENTIAL_PREFIX, gateway_host);
+    if CREDENTIAL_MANAGER.read(&target).map_err(|e| e.to_string())?.is_some() {
+        CREDENTIAL_MANAGER.delete(&target).map_err(|e| {
+            debug_log(
+                "ERROR",
+                "GATEWAY_CREDENTIALS",
+                &format!("Failed to delete gateway credentials: {}", e),
+                None,
+            );
+            e.to_string()
+        })?;
+    }
+
+    let mstsc_target = format!("TERMSRV/{}", gateway_host);
+    if let Ok(Some(_)) = CREDENTIAL_MANAGER.read_typed(&mstsc_target, CredentialType::DomainPassword) {
+        if let Err(e) = CREDENTIAL_MANAGER.delete_typed(&mstsc_target, CredentialType::DomainPassword) {
+            debug_log(
+                "WARN",
+                "GATEWAY_CREDENTIALS",
+                &format!("Failed to delete domain credentials for gateway {}: {}", gateway_host, e),
+                None,
+            );
+        }
+    }
+
+    debug_log(
+        "INFO",
+        "GATEWAY_CREDENTIALS",
+        &format!("Deleted credentials for gateway {}", gateway_host),
         None,
     );
     Ok(())
@@ -251,6 +626,7 @@ pub async fn list_hosts_with_credentials() -> Result<Vec<String>, String> {
             // e.g., "TERMSRV/server1.example.com" -> "server1.example.com"
             let hostnames: Vec<String> = targets
                 .iter()
+                .filter(|t| !t.starts_with(GATEWAY_CREDENTIAL_PREFIX))
                 .filter_map(|t| t.strip_prefix("TERMSRV/").map(|s| s.to_string()))
                 .collect();
             Ok(hostnames)
@@ -258,3 +634,135 @@ pub async fn list_hosts_with_credentials() -> Result<Vec<String>, String> {
         Err(e) => Err(e.to_string()),
     }
 }
+
+/// Lists all hosts with stored TERMSRV credentials for the audit view
+///
+/// # Returns
+/// * Bare hostnames (without the `TERMSRV/` prefix), sorted
+#[tauri::command]
+pub async fn list_credential_targets() -> Result<Vec<String>, String> {
+    crate::core::credentials::list_credential_targets(&*CREDENTIAL_MANAGER).map_err(|e| e.to_string())
+}
+
+/// Lists stored TERMSRV credentials, flagging those with no host in hosts.csv
+///
+/// # Returns
+/// * One entry per credential hostname, sorted, with `has_host: false` for orphans
+#[tauri::command]
+pub async fn audit_credential_targets() -> Result<Vec<CredentialTarget>, String> {
+    let hostnames = crate::core::credentials::list_credential_targets(&*CREDENTIAL_MANAGER)
+        .map_err(|e| e.to_string())?;
+    let hosts = crate::core::hosts::get_all_hosts().map_err(|e| e.to_string())?;
+    Ok(crate::core::credentials::flag_orphaned_targets(hostnames, &hosts))
+}
+
+/// Returns aggregate host statistics for the dashboard
+///
+/// Combines the hosts file with the stored `TERMSRV/` credentials; recency is
+/// measured against the local clock.
+#[tauri::command]
+pub async fn get_dashboard_stats() -> Result<DashboardStats, String> {
+    let hosts = crate::core::hosts::get_all_hosts().map_err(|e| e.to_string())?;
+    let credential_hostnames = crate::core::credentials::list_credential_targets(&*CREDENTIAL_MANAGER)
+        .map_err(|e| e.to_string())?;
+    Ok(crate::core::stats::compute_dashboard_stats(
+        &hosts,
+        &credential_hostnames,
+        chrono::Local::now().naive_local(),
+    ))
+}
+
+/// Checks that every host's stored credentials still authenticate
+///
+/// Each host is checked with its `TERMSRV/` credentials, or the global ones
+/// when it has none, by binding to a domain controller over LDAP. Each
+/// distinct set of credentials is bound once, and an account is not tried
+/// again after a failure, to avoid lockouts. Hosts with no credentials at all
+/// are skipped. Checks run a few at a time within
+/// `CREDENTIAL_TEST_BUDGET`.
+///
+/// # Arguments
+/// * `domain` - Domain to authenticate against (default: the credential's domain)
+/// * `server` - Domain controller to bind to (default: the domain name itself)
+///
+/// # Returns
+/// * `Ok(Vec<CredentialTestResult>)` - Pass/fail/skip per host, in hosts.csv order
+/// * `Err(String)` - Hosts or credentials could not be read
+#[tauri::command]
+pub async fn test_all_credentials(
+    domain: Option<String>,
+    server: Option<String>,
+) -> Result<Vec<CredentialTestResult>, String> {
+    let hosts = crate::core::hosts::get_all_hosts().map_err(|e| e.to_string())?;
+    let global = get_stored_credentials().await?;
+    let migrated_path = get_migrated_credentials_path()?;
+    let targets = crate::core::credentials::credential_test_targets(
+        &hosts,
+        |hostname| {
+            crate::core::credentials::read_host_credential(&*CREDENTIAL_MANAGER, &migrated_path, hostname)
+                .ok()
+                .flatten()
+        },
+        global.as_ref(),
+    );
+
+    let check = move |_hostname: &str, credentials: &StoredCredentials| {
+        let (credential_domain, _) = crate::core::rdp::parse_username(&credentials.username);
+        let domain = domain
+            .clone()
+            .filter(|domain| !domain.trim().is_empty())
+            .unwrap_or(credential_domain);
+        let server = server
+            .clone()
+            .filter(|server| !server.trim().is_empty())
+            .unwrap_or_else(|| domain.clone());
+        if !server.contains('.') {
+            return Err(format!(
+                "No domain controller to check against; pass a server or use a DNS domain (not '{}')",
+                server
+            ));
+        }
+
+        tauri::async_runtime::block_on(crate::core::ldap::test_ldap_connection(
+            &domain,
+            &server,
+            credentials,
+        ))
+        .map(|_| ())
+        .map_err(|e| e.user_message())
+    };
+
+    // Checks block on the network, keep them off the async runtime
+    let results = tauri::async_runtime::spawn_blocking(move || {
+        crate::core::credentials::test_credentials(
+            targets,
+            crate::core::credentials::CREDENTIAL_TEST_CONCURRENCY,
+            crate::core::credentials::CREDENTIAL_TEST_BUDGET,
+            check,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to test credentials: {}", e))?;
+
+    debug_log(
+        "INFO",
+        "CREDENTIALS",
+        &format!("Tested credentials for {} hosts", results.len()),
+        None,
+    );
+
+    Ok(results)
+}
+
+/// Checks a username's format so the login UI can show inline hints
+///
+/// # Arguments
+/// * `username` - Username as typed by the user (not trimmed)
+///
+/// # Returns
+/// * `Ok(UsernameInfo)` - Detected format (`netbios`/`upn`/`plain`), parsed
+///   domain and user, and any warnings
+#[tauri::command]
+pub async fn validate_username(username: String) -> Result<UsernameInfo, String> {
+    Ok(analyze_username(&username))
+}
@@ -3,10 +3,51 @@
 //! Thin command wrappers that delegate to core::hosts business logic.
 //! Commands validate inputs, call one core function, and emit UI events.
 
+use crate::core::clock::{Clock, SystemClock};
+use crate::core::status::{GroupedHosts, StatusCache, StatusSummary};
 use crate::core::types::Host;
 use crate::infra::debug_log;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tauri::{Emitter, Manager};
 
+/// How often the status poller checks whether a poll is due.
+const STATUS_POLL_TICK: Duration = Duration::from_secs(5);
+
+/// Shortest allowed interval between background status polls.
+const MIN_STATUS_POLL_INTERVAL_SECS: u32 = 10;
+
+/// Maximum time `get_hosts_grouped_by_status` waits for status probes.
+const GROUPED_STATUS_DEADLINE: Duration = Duration::from_secs(5);
+
+/// Maximum time `refresh_all_status` waits for status probes.
+const REFRESH_STATUS_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Recent status results, shared by `get_hosts_grouped_by_status` and the poller
+static STATUS_CACHE: once_cell::sync::Lazy<Mutex<StatusCache>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(StatusCache::new(crate::core::status::STATUS_CACHE_TTL)));
+
+/// Emits "hosts-updated" to the main and hosts windows.
+///
+/// The payload is the new host list version, so a window that has already
+/// loaded it (e.g. the one that made the change) can skip reloading.
+pub(crate) fn emit_hosts_updated(app_handle: &tauri::AppHandle) {
+    let version = crate::core::hosts::hosts_version();
+    if let Some(main_window) = app_handle.get_webview_window("main") {
+        let _ = main_window.emit("hosts-updated", version);
+    }
+    if let Some(hosts_window) = app_handle.get_webview_window("hosts") {
+        let _ = hosts_window.emit("hosts-updated", version);
+    }
+}
+
+/// Returns the current host list version (bumped on every hosts.csv write).
+#[tauri::command]
+pub fn get_hosts_version() -> u64 {
+    crate::core::hosts::hosts_version()
+}
+
 /// Reads hosts from the CSV file.
 ///
 /// Thin wrapper that delegates to core::hosts::get_all_hosts().
@@ -29,66 +70,302 @@ pub async fn search_hosts(query: String) -> Result<Vec<Host>, String> {
     crate::core::hosts::search_hosts(&query).map_err(|e| e.to_string())
 }
 
+/// Searches hosts with a regular expression (case-insensitive).
+///
+/// Thin wrapper that delegates to core::hosts::search_hosts_regex().
+/// Matches hostname and description; an invalid pattern returns an error.
+#[tauri::command]
+pub async fn search_hosts_regex(pattern: String) -> Result<Vec<Host>, String> {
+    crate::core::hosts::search_hosts_regex(&pattern).map_err(|e| e.to_string())
+}
+
+/// Finds hosts by tag.
+///
+/// Thin wrapper that delegates to core::hosts::search_hosts_by_tags().
+/// `match_all` requires every tag (AND); otherwise any tag matches (OR).
+/// An empty tag list returns all hosts.
+#[tauri::command]
+pub async fn search_hosts_by_tags(tags: Vec<String>, match_all: bool) -> Result<Vec<Host>, String> {
+    crate::core::hosts::search_hosts_by_tags(&tags, match_all).map_err(|e| e.to_string())
+}
+
+/// Lists the hosts that have never been connected to, sorted by hostname.
+///
+/// Thin wrapper that delegates to core::hosts::get_never_connected_hosts().
+/// Helps find dead entries to prune from the host list.
+#[tauri::command]
+pub async fn get_never_connected_hosts() -> Result<Vec<Host>, String> {
+    crate::core::hosts::get_never_connected_hosts().map_err(|e| e.to_string())
+}
+
 /// Saves or updates a host in the CSV file.
 ///
 /// Thin wrapper that:
 /// 1. Validates input (hostname not empty)
-/// 2. Calls core::hosts::upsert_host()
+/// 2. Calls core::hosts::save_host_checked()
 /// 3. Emits UI update events
+///
+/// A loopback hostname (`localhost`, `127.0.0.1`, `::1`, ...) is only saved
+/// with `confirm_loopback`; otherwise the outcome has `saved: false` and
+/// `loopback_warning: true` so the UI can ask first.
 #[tauri::command]
-pub fn save_host(app_handle: tauri::AppHandle, host: Host) -> Result<(), String> {
+pub fn save_host(
+    app_handle: tauri::AppHandle,
+    host: Host,
+    confirm_loopback: Option<bool>,
+) -> Result<crate::core::hosts::SaveHostOutcome, String> {
     // Delegate to core business logic
-    crate::core::hosts::upsert_host(host).map_err(|e| e.to_string())?;
+    let outcome = crate::core::hosts::save_host_checked(host, confirm_loopback.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
 
-    // Emit event to notify all windows that hosts list has been updated
-    if let Some(main_window) = app_handle.get_webview_window("main") {
-        let _ = main_window.emit("hosts-updated", ());
+    // Notify all windows that the hosts list has been updated
+    if outcome.saved {
+        emit_hosts_updated(&app_handle);
     }
-    if let Some(hosts_window) = app_handle.get_webview_window("hosts") {
-        let _ = hosts_window.emit("hosts-updated", ());
+
+    Ok(outcome)
+}
+
+/// Adds a host for every usable address in an IPv4 CIDR range.
+///
+/// Thin wrapper that:
+/// 1. Calls core::cidr::hosts_from_cidr() (rejects ranges larger than /24)
+/// 2. Calls core::hosts::upsert_hosts() (one write for the whole range)
+/// 3. Emits UI update events
+///
+/// # Arguments
+/// * `cidr` - Range such as `10.0.5.0/28`
+/// * `description_template` - Description for each host; `{ip}` is replaced by its address
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts added or updated
+#[tauri::command]
+pub fn add_hosts_from_cidr(
+    app_handle: tauri::AppHandle,
+    cidr: String,
+    description_template: Option<String>,
+) -> Result<usize, String> {
+    let hosts = crate::core::cidr::hosts_from_cidr(&cidr, description_template.as_deref())
+        .map_err(|e| e.to_string())?;
+    let count = crate::core::hosts::upsert_hosts(hosts).map_err(|e| e.to_string())?;
+
+    emit_hosts_updated(&app_handle);
+
+    Ok(count)
+}
+
+/// Adds a host for every hostname in a numbered range pattern.
+///
+/// Thin wrapper that:
+/// 1. Calls core::host_pattern::hosts_from_pattern() (one range, validated hostnames)
+/// 2. Calls core::hosts::upsert_hosts() (one write for the whole range)
+/// 3. Emits UI update events
+///
+/// # Arguments
+/// * `pattern` - Hostname such as `web[01-08].prod.local`; without a range it adds one host
+/// * `description` - Description for each host
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts added or updated
+#[tauri::command]
+pub fn add_hosts_from_pattern(
+    app_handle: tauri::AppHandle,
+    pattern: String,
+    description: Option<String>,
+) -> Result<usize, String> {
+    let hosts = crate::core::host_pattern::hosts_from_pattern(&pattern, description.as_deref())
+        .map_err(|e| e.to_string())?;
+    let count = crate::core::hosts::upsert_hosts(hosts).map_err(|e| e.to_string())?;
+
+    emit_hosts_updated(&app_handle);
+
+    Ok(count)
+}
+
+/// Copies an existing host's settings to a new hostname.
+///
+/// Thin wrapper that:
+/// 1. Calls core::hosts::duplicate_host() (validates the name, rejects collisions)
+/// 2. Emits UI update events
+#[tauri::command]
+pub fn duplicate_host(
+    app_handle: tauri::AppHandle,
+    source: String,
+    new_hostname: String,
+) -> Result<(), String> {
+    crate::core::hosts::duplicate_host(&source, &new_hostname).map_err(|e| e.to_string())?;
+
+    // Notify all windows that the hosts list has been updated
+    emit_hosts_updated(&app_handle);
+
+    Ok(())
+}
+
+/// Imports hosts from a directory of `.rdp` files.
+///
+/// Thin wrapper that:
+/// 1. Calls core::hosts::import_rdp_directory() (malformed files are skipped)
+/// 2. Emits a UI update event if anything was imported
+#[tauri::command]
+pub fn import_rdp_directory(app_handle: tauri::AppHandle, dir: String) -> Result<usize, String> {
+    let imported = crate::core::hosts::import_rdp_directory(std::path::Path::new(&dir))
+        .map_err(|e| e.to_string())?;
+
+    if imported > 0 {
+        emit_hosts_updated(&app_handle);
+    }
+
+    Ok(imported)
+}
+
+/// Checks a CSV file before importing it, without changing any hosts.
+///
+/// Thin wrapper that delegates to core::csv_reader::validate_hosts_csv().
+#[tauri::command]
+pub fn validate_hosts_csv(src_path: String) -> Result<crate::core::csv_reader::CsvValidationReport, String> {
+    crate::core::csv_reader::validate_hosts_csv(std::path::Path::new(&src_path)).map_err(|e| e.to_string())
+}
+
+/// Converts the host list to hosts.json and switches to the JSON store.
+///
+/// Thin wrapper that delegates to core::hosts::convert_hosts_storage().
+/// hosts.csv is left in place as a backup.
+#[tauri::command]
+pub fn convert_hosts_to_json(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    let count = crate::core::hosts::convert_hosts_storage(crate::core::hosts_store::StorageFormat::Json)
+        .map_err(|e| e.to_string())?;
+    emit_hosts_updated(&app_handle);
+    Ok(count)
+}
+
+/// Converts the host list back to hosts.csv and switches to the CSV store.
+///
+/// Thin wrapper that delegates to core::hosts::convert_hosts_storage().
+/// hosts.json is left in place as a backup.
+#[tauri::command]
+pub fn convert_hosts_to_csv(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    let count = crate::core::hosts::convert_hosts_storage(crate::core::hosts_store::StorageFormat::Csv)
+        .map_err(|e| e.to_string())?;
+    emit_hosts_updated(&app_handle);
+    Ok(count)
+}
+
+/// Moves the listed hosts into a group, or clears their group when `None`.
+///
+/// Thin wrapper that:
+/// 1. Calls core::hosts::set_hosts_group() (one read-modify-write)
+/// 2. Emits a single UI update event if anything changed
+#[tauri::command]
+pub fn set_hosts_group(
+    app_handle: tauri::AppHandle,
+    hostnames: Vec<String>,
+    group: Option<String>,
+) -> Result<usize, String> {
+    let changed = crate::core::hosts::set_hosts_group(&hostnames, group.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    if changed > 0 {
+        emit_hosts_updated(&app_handle);
     }
 
+    Ok(changed)
+}
+
+/// Replaces a host's tags; an empty list removes them all.
+///
+/// Thin wrapper that:
+/// 1. Calls core::hosts::set_host_tags()
+/// 2. Emits a UI update event
+#[tauri::command]
+pub fn set_host_tags(
+    app_handle: tauri::AppHandle,
+    hostname: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let tags = crate::core::hosts::set_host_tags(&hostname, &tags).map_err(|e| e.to_string())?;
+
+    emit_hosts_updated(&app_handle);
+
+    Ok(tags)
+}
+
+/// Saves a manual host ordering (e.g. after drag and drop).
+///
+/// Thin wrapper that:
+/// 1. Calls core::hosts::reorder_hosts(); hosts not listed keep their
+///    relative order after the listed ones
+/// 2. Emits a UI update event
+#[tauri::command]
+pub fn reorder_hosts(app_handle: tauri::AppHandle, ordered_hostnames: Vec<String>) -> Result<(), String> {
+    crate::core::hosts::reorder_hosts(&ordered_hostnames).map_err(|e| e.to_string())?;
+    emit_hosts_updated(&app_handle);
     Ok(())
 }
 
+/// Merges hosts whose hostnames only differ in case or a trailing dot.
+///
+/// Thin wrapper that:
+/// 1. Calls core::hosts::deduplicate_hosts() (one read-modify-write)
+/// 2. Emits a UI update event
+///
+/// # Returns
+/// Number of duplicate entries merged
+#[tauri::command]
+pub fn deduplicate_hosts(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    let merged = crate::core::hosts::deduplicate_hosts().map_err(|e| e.to_string())?;
+    emit_hosts_updated(&app_handle);
+    Ok(merged)
+}
+
 /// Deletes a host from the CSV file.
 ///
 /// Thin wrapper that:
 /// 1. Calls core::hosts::delete_host()
-/// 2. Emits UI update events
+/// 2. Removes the host's entry from `rdp_files.json`; a failure there is only logged
+/// 3. Emits UI update events
 #[tauri::command]
 pub fn delete_host(app_handle: tauri::AppHandle, hostname: String) -> Result<(), String> {
     // Delegate to core business logic
     crate::core::hosts::delete_host(&hostname).map_err(|e| e.to_string())?;
 
-    // Emit event to notify all windows that hosts list have been updated
-    if let Some(main_window) = app_handle.get_webview_window("main") {
-        let _ = main_window.emit("hosts-updated", ());
-    }
-    if let Some(hosts_window) = app_handle.get_webview_window("hosts") {
-        let _ = hosts_window.emit("hosts-updated", ());
+    let forgotten = crate::infra::get_connections_dir().and_then(|dir| {
+        crate::core::rdp_file_names::forget_rdp_file_in(&dir, &hostname).map_err(|e| e.to_string())
+    });
+    if let Err(e) = forgotten {
+        debug_log(
+            "WARN",
+            "HOST_OPERATIONS",
+            &format!("Failed to forget the RDP file name of deleted host {}", hostname),
+            Some(&e),
+        );
     }
 
+    // Notify all windows that the hosts list has been updated
+    emit_hosts_updated(&app_handle);
+
     Ok(())
 }
 
+/// Issues a short-lived confirmation token required by `delete_all_hosts`.
+#[tauri::command]
+pub fn request_delete_all_token() -> String {
+    crate::core::hosts::request_delete_all_token()
+}
+
 /// Deletes all hosts from the CSV file.
 ///
 /// Thin wrapper that:
-/// 1. Calls core::hosts::delete_all_hosts()
+/// 1. Calls core::hosts::delete_all_hosts_confirmed() (validates `token`
+///    from `request_delete_all_token`; nothing is deleted if it doesn't match
+///    or has expired)
 /// 2. Emits UI update events
 #[tauri::command]
-pub async fn delete_all_hosts(app_handle: tauri::AppHandle) -> Result<(), String> {
+pub async fn delete_all_hosts(app_handle: tauri::AppHandle, token: String) -> Result<(), String> {
     // Delegate to core business logic
-    crate::core::hosts::delete_all_hosts().map_err(|e| e.to_string())?;
+    crate::core::hosts::delete_all_hosts_confirmed(&token).map_err(|e| e.to_string())?;
 
-    // Emit event to notify all windows that hosts list has been updated
-    if let Some(main_window) = app_handle.get_webview_window("main") {
-        let _ = main_window.emit("hosts-updated", ());
-    }
-    if let Some(hosts_window) = app_handle.get_webview_window("hosts") {
-        let _ = hosts_window.emit("hosts-updated", ());
-    }
+    // Notify all windows that the hosts list has been updated
+    emit_hosts_updated(&app_handle);
 
     Ok(())
 }
@@ -97,74 +374,347 @@ pub async fn delete_all_hosts(app_handle: tauri::AppHandle) -> Result<(), String
 ///
 /// Thin wrapper that delegates to core::hosts::update_last_connected().
 /// This is called automatically when launching an RDP connection.
+///
+/// The connection is also recorded in the rolling usage sidecar; failing to
+/// do so is only logged.
 pub fn update_last_connected(hostname: &str) -> Result<(), String> {
-    crate::core::hosts::update_last_connected(hostname).map_err(|e| e.to_string())
+    crate::core::hosts::update_last_connected(hostname).map_err(|e| e.to_string())?;
+
+    let recorded = crate::infra::get_connection_usage_path().and_then(|path| {
+        crate::core::usage::record_usage_at(&path, hostname, &SystemClock).map_err(|e| e.to_string())
+    });
+    if let Err(e) = recorded {
+        debug_log(
+            "WARN",
+            "USAGE",
+            &format!("Failed to record connection usage for {}: {}", hostname, e),
+            None,
+        );
+    }
+    Ok(())
+}
+
+/// Gets how many times a host was connected to in the last 30 days.
+///
+/// Unlike the lifetime `connect_count`, this reflects current usage. Only
+/// connections made since the usage sidecar was introduced are counted.
+///
+/// # Returns
+/// * `Ok(u32)` - Connections within `core::usage::USAGE_WINDOW_DAYS` days
+/// * `Err(String)` - If the usage file cannot be read
+///
+/// # Side Effects
+/// - Prunes expired times from connection_usage.json
+#[tauri::command]
+pub fn get_recent_usage(hostname: String) -> Result<u32, String> {
+    let path = crate::infra::get_connection_usage_path()?;
+    crate::core::usage::recent_usage_at(&path, &hostname, &SystemClock).map_err(|e| e.to_string())
 }
 
 /// Checks if a host is online by attempting to connect to RDP port 3389.
 ///
-/// Returns "online", "offline", or "unknown".
+/// Thin wrapper that delegates to core::status::probe_with_retries().
+/// Returns "online", "offline", or "unknown". When 3389 is offline, the
+/// `status_fallback_ports` setting lists other ports to try.
+///
+/// # Arguments
+/// * `hostname` - Host to probe
+/// * `retries` - Maximum number of probes, with backoff between failures
+///   (default 1, at most `core::status::MAX_PROBE_ATTEMPTS`)
 #[tauri::command]
-pub async fn check_host_status(hostname: String) -> Result<String, String> {
-    use std::net::{TcpStream, ToSocketAddrs};
-    use std::time::Duration;
+pub async fn check_host_status(hostname: String, retries: Option<u8>) -> Result<String, String> {
+    let settings = crate::core::settings::load_settings().unwrap_or_default();
+    let source_address = settings.status_probe_source();
+    let fallback_ports = settings.status_fallback_ports;
+
+    // Probing and the backoff sleeps block, keep them off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::core::status::probe_with_retries(
+            &hostname,
+            retries.unwrap_or(1),
+            |hostname| {
+                crate::core::status::probe_host_with_fallbacks(
+                    hostname,
+                    &fallback_ports,
+                    source_address,
+                    crate::core::status::PROBE_TIMEOUT,
+                )
+            },
+            std::thread::sleep,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to check host status: {}", e))
+}
+
+/// Detects which RDP security protocols a host accepts, including whether it requires NLA.
+///
+/// Thin wrapper that delegates to core::rdp_security::probe_rdp_security(),
+/// which performs only the initial negotiation handshake and never
+/// authenticates. A port in the host entry (`server01:3390`) is used instead of 3389.
+#[tauri::command]
+pub async fn probe_rdp_security(hostname: String) -> Result<crate::core::rdp_security::RdpSecurityInfo, String> {
+    // The negotiations block on sockets for up to NEGOTIATION_TIMEOUT each,
+    // keep them off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::core::rdp_security::probe_rdp_security(&hostname, crate::core::rdp_security::NEGOTIATION_TIMEOUT)
+    })
+    .await
+    .map_err(|e| format!("Failed to probe RDP security: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
+/// Returns all hosts bucketed into online, offline and unknown.
+///
+/// Probes run concurrently; hosts checked within `STATUS_CACHE_TTL` (by an
+/// earlier call or the background poller) reuse that result. Hosts that don't
+/// answer within `GROUPED_STATUS_DEADLINE` are reported as unknown.
+#[tauri::command]
+pub async fn get_hosts_grouped_by_status() -> Result<GroupedHosts, String> {
+    let hosts = crate::core::hosts::get_all_hosts().map_err(|e| e.to_string())?;
+    let settings = crate::core::settings::load_settings().unwrap_or_default();
+    let source_address = settings.status_probe_source();
+    let fallback_ports = settings.status_fallback_ports;
+
+    // Probing blocks on sockets, keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut cache = STATUS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        crate::core::status::group_hosts_by_status(
+            hosts,
+            &mut cache,
+            move |hostname: &str| {
+                crate::core::status::probe_host_with_fallbacks(
+                    hostname,
+                    &fallback_ports,
+                    source_address,
+                    crate::core::status::PROBE_TIMEOUT,
+                )
+            },
+            GROUPED_STATUS_DEADLINE,
+            &SystemClock,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to check host status: {}", e))
+}
+
+/// Probes every host now and returns how many are online, offline and unknown.
+///
+/// Cached results are ignored and replaced, at most
+/// `REFRESH_CONCURRENCY` probes run at once, and hosts that don't answer
+/// within `REFRESH_STATUS_DEADLINE` count as unknown.
+///
+/// # Side Effects
+/// - Updates the shared status cache used by the tray and `get_hosts_grouped_by_status`
+/// - Rebuilds the tray menu so status indicators show the new results
+#[tauri::command]
+pub async fn refresh_all_status(app_handle: tauri::AppHandle) -> Result<StatusSummary, String> {
+    let hostnames: Vec<String> = crate::core::hosts::get_all_hosts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|host| host.hostname)
+        .collect();
+    let settings = crate::core::settings::load_settings().unwrap_or_default();
+    let source_address = settings.status_probe_source();
+    let fallback_ports = settings.status_fallback_ports;
+
+    // Probing blocks on sockets, keep it off the async runtime
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        crate::core::status::refresh_statuses(
+            &hostnames,
+            &STATUS_CACHE,
+            |hostnames| {
+                crate::core::status::probe_hosts_bounded(
+                    hostnames,
+                    crate::core::status::REFRESH_CONCURRENCY,
+                    move |hostname: &str| {
+                        crate::core::status::probe_host_with_fallbacks(
+                            hostname,
+                            &fallback_ports,
+                            source_address,
+                            crate::core::status::PROBE_TIMEOUT,
+                        )
+                    },
+                    REFRESH_STATUS_DEADLINE,
+                )
+            },
+            &SystemClock,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to refresh host status: {}", e))?;
 
     debug_log(
-        "DEBUG",
+        "INFO",
         "STATUS_CHECK",
-        &format!("Checking status for host: {}", hostname),
+        &format!(
+            "Status refresh: {} online, {} offline, {} unknown",
+            summary.online, summary.offline, summary.unknown
+        ),
         None,
     );
+    super::system::refresh_tray_menu(&app_handle);
+    Ok(summary)
+}
 
-    // Resolve hostname to IP address for TCP connection
-    // Port 3389 is the standard RDP port
-    let addr = format!("{}:3389", hostname);
-    let socket_addrs: Vec<_> = match addr.to_socket_addrs() {
-        Ok(addrs) => addrs.collect(),
-        Err(e) => {
-            // DNS resolution failed - host doesn't exist or network issue
-            debug_log(
-                "DEBUG",
-                "STATUS_CHECK",
-                &format!("Failed to resolve hostname {}: {}", hostname, e),
-                Some(&e.to_string()),
-            );
-            return Ok("unknown".to_string());
-        }
+/// Looks up host statuses for the tray menu within `deadline`.
+///
+/// Reuses and refreshes the shared status cache. Returns no statuses when the
+/// cache is busy (e.g. a grouped status check is probing) rather than waiting.
+///
+/// # Returns
+/// Statuses keyed by lowercased hostname; hosts not checked in time are missing.
+pub(crate) fn tray_host_statuses(hostnames: &[String], deadline: Duration) -> HashMap<String, String> {
+    let Ok(mut cache) = STATUS_CACHE.try_lock() else {
+        return HashMap::new();
     };
+    let settings = crate::core::settings::load_settings().unwrap_or_default();
+    let source_address = settings.status_probe_source();
+    let fallback_ports = settings.status_fallback_ports;
 
-    if socket_addrs.is_empty() {
-        debug_log(
-            "DEBUG",
-            "STATUS_CHECK",
-            &format!("No addresses resolved for hostname: {}", hostname),
-            None,
-        );
-        return Ok("unknown".to_string());
-    }
+    crate::core::status::statuses_within(
+        hostnames,
+        &mut cache,
+        move |hostname: &str| {
+            crate::core::status::probe_host_with_fallbacks(
+                hostname,
+                &fallback_ports,
+                source_address,
+                crate::core::status::PROBE_TIMEOUT,
+            )
+        },
+        deadline,
+        &SystemClock,
+    )
+}
 
-    // Attempt TCP connection with 2-second timeout
-    // This checks if port 3389 is open and accepting connections
-    // Timeout prevents UI from hanging on unreachable hosts
-    let timeout = Duration::from_secs(2);
-    match TcpStream::connect_timeout(&socket_addrs[0], timeout) {
-        Ok(_) => {
-            debug_log(
-                "DEBUG",
-                "STATUS_CHECK",
-                &format!("Host {} is online (port 3389 open)", hostname),
-                None,
-            );
-            Ok("online".to_string())
-        }
-        Err(e) => {
-            debug_log(
-                "DEBUG",
-                "STATUS_CHECK",
-                &format!("Host {} is offline or unreachable: {}", hostname, e),
-                Some(&e.to_string()),
+/// Starts the background host status poller.
+///
+/// Every `STATUS_POLL_TICK` the `status_polling_enabled` and
+/// `status_poll_interval_secs` settings are re-read. While enabled, all hosts
+/// are probed once per interval, `REFRESH_CONCURRENCY` at a time, and a
+/// `host-status-changed` event (a `core::status::StatusChange`) is emitted to
+/// the main and hosts windows for each host whose status changed since the
+/// previous poll.
+///
+/// # Side Effects
+/// - Spawns a background thread for the lifetime of the app
+pub fn spawn_status_poller(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut known: HashMap<String, String> = HashMap::new();
+        let mut last_poll: Option<Instant> = None;
+
+        loop {
+            std::thread::sleep(STATUS_POLL_TICK);
+
+            let settings = crate::core::settings::load_settings().unwrap_or_default();
+            if !settings.status_polling_enabled {
+                // Start from a fresh baseline when polling is turned back on
+                known.clear();
+                last_poll = None;
+                continue;
+            }
+
+            let interval = Duration::from_secs(u64::from(
+                settings.status_poll_interval_secs.max(MIN_STATUS_POLL_INTERVAL_SECS),
+            ));
+            if last_poll.is_some_and(|at| at.elapsed() < interval) {
+                continue;
+            }
+            last_poll = Some(Instant::now());
+
+            let hostnames: Vec<String> = match crate::core::hosts::get_all_hosts() {
+                Ok(hosts) => hosts.into_iter().map(|host| host.hostname).collect(),
+                Err(e) => {
+                    debug_log(
+                        "WARN",
+                        "STATUS_CHECK",
+                        "Background status poll could not read hosts",
+                        Some(&e.to_string()),
+                    );
+                    continue;
+                }
+            };
+
+            let source_address = settings.status_probe_source();
+            let fallback_ports = settings.status_fallback_ports;
+            let statuses = crate::core::status::probe_hosts_bounded(
+                &hostnames,
+                crate::core::status::REFRESH_CONCURRENCY,
+                move |hostname: &str| {
+                    crate::core::status::probe_host_with_fallbacks(
+                        hostname,
+                        &fallback_ports,
+                        source_address,
+                        crate::core::status::PROBE_TIMEOUT,
+                    )
+                },
+                interval,
             );
-            Ok("offline".to_string())
+            if let Ok(mut cache) = STATUS_CACHE.lock() {
+                let now = SystemClock.now();
+                for (hostname, status) in hostnames.iter().zip(&statuses) {
+                    if let Some(status) = status {
+                        cache.insert(hostname, status, now);
+                    }
+                }
+            }
+            // Hosts not probed within the interval keep their last known status
+            let results = hostnames
+                .into_iter()
+                .zip(statuses)
+                .filter_map(|(hostname, status)| {
+                    status
+                        .or_else(|| known.get(&hostname).cloned())
+                        .map(|status| (hostname, status))
+                })
+                .collect();
+            let changes = crate::core::status::diff_statuses(&mut known, results);
+
+            for change in changes {
+                debug_log(
+                    "INFO",
+                    "STATUS_CHECK",
+                    &format!(
+                        "Host {} changed from {} to {}",
+                        change.hostname, change.previous, change.status
+                    ),
+                    None,
+                );
+                for label in ["main", "hosts"] {
+                    if let Some(window) = app_handle.get_webview_window(label) {
+                        let _ = window.emit("host-status-changed", &change);
+                    }
+                }
+            }
         }
+    });
+}
+
+/// Turns background status polling on.
+///
+/// # Arguments
+/// * `interval_secs` - Seconds between polls (minimum 10); keeps the saved interval if omitted
+///
+/// # Side Effects
+/// - Writes settings.json; the poller starts on its next tick
+#[tauri::command]
+pub fn start_status_polling(interval_secs: Option<u32>) -> Result<(), String> {
+    let mut settings = crate::core::settings::load_settings().map_err(|e| e.to_string())?;
+    settings.status_polling_enabled = true;
+    if let Some(interval_secs) = interval_secs {
+        settings.status_poll_interval_secs = interval_secs.max(MIN_STATUS_POLL_INTERVAL_SECS);
     }
+    crate::core::settings::save_settings(&settings).map_err(|e| e.to_string())
+}
+
+/// Turns background status polling off.
+///
+/// # Side Effects
+/// - Writes settings.json; the poller stops on its next tick
+#[tauri::command]
+pub fn stop_status_polling() -> Result<(), String> {
+    let mut settings = crate::core::settings::load_settings().map_err(|e| e.to_string())?;
+    settings.status_polling_enabled = false;
+    crate::core::settings::save_settings(&settings).map_err(|e| e.to_string())
 }
@@ -5,6 +5,7 @@
 
 pub mod credentials;
 pub mod hosts;
+pub mod settings;
 pub mod system;
 pub mod theme;
 pub mod windows;
@@ -0,0 +1,240 @@
+//! Settings commands
+//!
+//! Thin wrappers exposing persisted application settings to the frontend.
+
+use crate::core::rdp::{suggest_resolution, MonitorArea, RdpOptions};
+use crate::core::settings::{load_settings, save_settings, AppSettings, UiPreferences};
+
+/// Gets all persisted application settings.
+///
+/// # Returns
+/// * `Ok(AppSettings)` - The stored settings (defaults for anything not set)
+/// * `Err(String)` - If the settings file cannot be read
+#[tauri::command]
+pub fn get_settings() -> Result<AppSettings, String> {
+    load_settings().map_err(|e| e.to_string())
+}
+
+/// Replaces all persisted application settings.
+///
+/// Individual features read their values from these settings; the narrower
+/// get/set commands below remain for callers that only change one value.
+///
+/// # Side Effects
+/// - Writes settings.json
+/// - Restarts the idle countdown so a new auto-hide timeout counts from now
+#[tauri::command]
+pub fn set_settings(settings: AppSettings) -> Result<(), String> {
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    super::windows::touch_activity();
+    Ok(())
+}
+
+/// Gets the RDP options configured for a host, or the global options.
+///
+/// # Arguments
+/// * `hostname` - Host to read overrides for; `None` returns the global options
+///
+/// # Returns
+/// * `Ok(RdpOptions)` - The stored options (unset fields use built-in defaults)
+/// * `Err(String)` - If the settings file cannot be read
+#[tauri::command]
+pub fn get_rdp_options(hostname: Option<String>) -> Result<RdpOptions, String> {
+    let settings = load_settings().map_err(|e| e.to_string())?;
+
+    Ok(match hostname {
+        Some(hostname) => settings.host_rdp.get(&hostname).cloned().unwrap_or_default(),
+        None => settings.rdp,
+    })
+}
+
+/// Saves RDP options for a host, or the global options.
+///
+/// Saving empty options for a host removes its overrides so that it falls back
+/// to the global options. An RD Gateway that is not a valid host address is
+/// rejected.
+///
+/// # Arguments
+/// * `hostname` - Host to store overrides for; `None` updates the global options
+/// * `options` - The options to store
+///
+/// # Side Effects
+/// - Writes settings.json
+#[tauri::command]
+pub fn set_rdp_options(hostname: Option<String>, options: RdpOptions) -> Result<(), String> {
+    options.validate().map_err(|e| e.to_string())?;
+    let mut settings = load_settings().map_err(|e| e.to_string())?;
+
+    match hostname {
+        Some(hostname) if options == RdpOptions::default() => {
+            settings.host_rdp.remove(&hostname);
+        }
+        Some(hostname) => {
+            settings.host_rdp.insert(hostname, options);
+        }
+        None => settings.rdp = options,
+    }
+
+    save_settings(&settings).map_err(|e| e.to_string())
+}
+
+/// Suggests a session size for the "match my screen" display option.
+///
+/// Fits the configured desktop size (the host's effective options, or the
+/// global ones) to the primary monitor's work area; see
+/// `core::rdp::suggest_resolution`.
+///
+/// # Arguments
+/// * `hostname` - Host whose configured size to fit; `None` uses the global options
+///
+/// # Returns
+/// * `Ok((width, height))` - Suggested size in physical pixels
+/// * `Err(String)` - If the settings or monitors cannot be read
+#[tauri::command]
+pub fn suggest_rdp_resolution(
+    app_handle: tauri::AppHandle,
+    hostname: Option<String>,
+) -> Result<(u32, u32), String> {
+    let settings = load_settings().map_err(|e| e.to_string())?;
+    let options = match hostname {
+        Some(hostname) => settings.rdp_options_for(&hostname),
+        None => settings.rdp,
+    };
+
+    let monitors: Vec<MonitorArea> = match app_handle.primary_monitor().map_err(|e| e.to_string())? {
+        Some(monitor) => vec![MonitorArea {
+            width: monitor.work_area().size.width,
+            height: monitor.work_area().size.height,
+            primary: true,
+        }],
+        None => app_handle
+            .available_monitors()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|monitor| MonitorArea {
+                width: monitor.work_area().size.width,
+                height: monitor.work_area().size.height,
+                primary: false,
+            })
+            .collect(),
+    };
+
+    Ok(suggest_resolution(
+        &monitors,
+        options.desktop_width,
+        options.desktop_height,
+    ))
+}
+
+/// Exports the theme and other look-and-feel preferences as JSON.
+///
+/// Unlike the full settings, the result holds nothing host- or
+/// credential-related, so it can be shared on its own.
+///
+/// # Returns
+/// * `Ok(String)` - JSON for `import_ui_preferences`
+/// * `Err(String)` - If the settings file cannot be read
+#[tauri::command]
+pub fn export_ui_preferences(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let settings = load_settings().map_err(|e| e.to_string())?;
+    let theme = super::theme::saved_theme_preference(&app_handle);
+
+    UiPreferences::from_settings(&settings, theme)
+        .to_json()
+        .map_err(|e| e.to_string())
+}
+
+/// Applies preferences exported by `export_ui_preferences`.
+///
+/// Anything missing from `json` is reset to its default; a missing theme
+/// means following the Windows theme.
+///
+/// # Returns
+/// * `Ok(UiPreferences)` - The preferences now in effect
+/// * `Err(String)` - Invalid JSON or values; nothing is changed
+///
+/// # Side Effects
+/// - Writes settings.json and the saved theme
+/// - Emits `theme-changed` and rebuilds the tray menu
+#[tauri::command]
+pub fn import_ui_preferences(app_handle: tauri::AppHandle, json: String) -> Result<UiPreferences, String> {
+    let preferences = UiPreferences::from_json(&json).map_err(|e| e.to_string())?;
+
+    let mut settings = load_settings().map_err(|e| e.to_string())?;
+    preferences.apply_to(&mut settings);
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    match &preferences.theme {
+        Some(theme) => super::theme::set_theme(app_handle, theme.clone())?,
+        None => super::theme::clear_theme(&app_handle)?,
+    }
+    Ok(preferences)
+}
+
+/// Gets the defaults IT has set in the registry (see `core::registry_defaults`).
+///
+/// Lets the scan dialog pre-fill the domain and domain controller. Values set
+/// for the user win over machine-wide ones; unset values are `null`.
+#[tauri::command]
+pub fn get_registry_defaults() -> crate::core::registry_defaults::RegistryDefaults {
+    crate::core::registry_defaults::registry_defaults().clone()
+}
+
+/// Gets the idle auto-hide timeout for the main window in minutes (0 = disabled).
+#[tauri::command]
+pub fn get_auto_hide_minutes() -> Result<u32, String> {
+    let settings = load_settings().map_err(|e| e.to_string())?;
+    Ok(settings.auto_hide_minutes)
+}
+
+/// Sets the idle auto-hide timeout for the main window in minutes (0 = disabled).
+///
+/// # Side Effects
+/// - Writes settings.json; the idle timer picks the new value up on its next check
+#[tauri::command]
+pub fn set_auto_hide_minutes(minutes: u32) -> Result<(), String> {
+    let mut settings = load_settings().map_err(|e| e.to_string())?;
+    settings.auto_hide_minutes = minutes;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    // Start counting from now rather than from the last interaction
+    super::windows::touch_activity();
+    Ok(())
+}
+
+/// Marks a host as stable, or clears the mark.
+///
+/// Stable hosts launch their saved .rdp file without rewriting credentials.
+///
+/// # Side Effects
+/// - Writes settings.json
+#[tauri::command]
+pub fn set_host_stable(hostname: String, stable: bool) -> Result<(), String> {
+    let mut settings = load_settings().map_err(|e| e.to_string())?;
+    settings
+        .stable_hosts
+        .retain(|existing| !existing.eq_ignore_ascii_case(&hostname));
+    if stable {
+        settings.stable_hosts.push(hostname);
+    }
+    save_settings(&settings).map_err(|e| e.to_string())
+}
+
+/// Gets whether launches reuse an existing, possibly hand-edited, .rdp file.
+#[tauri::command]
+pub fn get_preserve_rdp_edits() -> Result<bool, String> {
+    let settings = load_settings().map_err(|e| e.to_string())?;
+    Ok(settings.preserve_rdp_edits)
+}
+
+/// Sets whether launches reuse an existing .rdp file instead of regenerating it.
+///
+/// # Side Effects
+/// - Writes settings.json
+#[tauri::command]
+pub fn set_preserve_rdp_edits(preserve: bool) -> Result<(), String> {
+    let mut settings = load_settings().map_err(|e| e.to_string())?;
+    settings.preserve_rdp_edits = preserve;
+    save_settings(&settings).map_err(|e| e.to_string())
+}
@@ -3,17 +3,21 @@
 //! Handles system-level operations including autostart, application reset,
 //! RDP connections, domain scanning, and tray menu management.
 
-use crate::{AppError, Host, RecentConnection, RecentConnections};
-use crate::adapters::{CredentialManager, RegistryAdapter, WindowsCredentialManager, WindowsRegistry};
+use crate::{AppError, Host, HostErrorTracker, HostLastError, RecentConnection, RecentConnectionStatus, RecentConnections};
+use crate::adapters::{RegistryAdapter, WindowsCredentialManager, WindowsRegistry};
 use crate::commands;
 use crate::core;
+use crate::core::autostart::{APP_NAME, REGISTRY_RUN_KEY};
 use crate::infra::debug_log;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Mutex;
 use tauri::{Emitter, Manager};
 use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
 
-const REGISTRY_RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
-const APP_NAME: &str = "QuickConnect";
+/// Last launch error per host, kept in memory for the lifetime of the app.
+static HOST_ERRORS: once_cell::sync::Lazy<Mutex<HostErrorTracker>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HostErrorTracker::new()));
 
 /// Gets the full path to the recent connections JSON file.
 ///
@@ -50,21 +54,64 @@ fn save_recent_connections(recent: &RecentConnections) -> Result<(), String> {
 /// Loads recent connections from disk.
 ///
 /// If the file doesn't exist, returns an empty RecentConnections structure.
-/// Otherwise, reads and deserializes the JSON file.
+/// A corrupt file is moved to `recent_connections.json.bak` and also yields
+/// an empty list (see core::recent).
 ///
 /// # Returns
-/// * `Ok(RecentConnections)` - The loaded connections (or empty if file doesn't exist)
-/// * `Err(String)` - If file read or JSON parsing fails
+/// * `Ok(RecentConnections)` - The loaded connections (or empty if missing/corrupt)
+/// * `Err(String)` - If the file can't be read
 fn load_recent_connections() -> Result<RecentConnections, String> {
     let file_path = get_recent_connections_file()?;
-    if !file_path.exists() {
-        return Ok(RecentConnections::new());
-    }
-    let json = std::fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read recent connections: {}", e))?;
-    let recent: RecentConnections = serde_json::from_str(&json)
-        .map_err(|e| format!("Failed to parse recent connections: {}", e))?;
-    Ok(recent)
+    core::recent::load_recent_connections_from(&file_path)
+        .map_err(|e| format!("Failed to read recent connections: {}", e))
+}
+
+/// Starts the background Connections directory cleanup.
+///
+/// Runs once at startup and then every `CONNECTIONS_CLEANUP_INTERVAL`. When
+/// the directory exceeds `connections_max_files` or `connections_max_bytes`,
+/// the least-recently-modified `.rdp` files are deleted, keeping the files of
+/// recent connections. Skipped while `preserve_rdp_edits` is on (see
+/// core::connections::cleanup_limits).
+///
+/// While `high_security_mode` is on every saved `.rdp` file is deleted
+/// instead, since launches only use temporary files then.
+///
+/// # Side Effects
+/// - Spawns a background thread for the lifetime of the app
+pub fn spawn_connections_cleanup() {
+    std::thread::spawn(|| loop {
+        let settings = core::settings::load_settings().unwrap_or_default();
+        if settings.high_security_mode {
+            let result = crate::infra::get_connections_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| core::connections::purge_rdp_files_in(&dir).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                debug_log("WARN", "RDP_LAUNCH", "Failed to delete saved RDP files", Some(&e));
+            }
+        } else if let Some((max_files, max_bytes)) = core::connections::cleanup_limits(&settings) {
+            let recent: Vec<String> = load_recent_connections()
+                .map(|recent| recent.connections.into_iter().map(|conn| conn.hostname).collect())
+                .unwrap_or_default();
+
+            let result = crate::infra::get_connections_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| {
+                    core::connections::cleanup_connections_dir(
+                        &dir,
+                        &recent,
+                        max_files,
+                        max_bytes,
+                    )
+                    .map_err(|e| e.to_string())
+                });
+            if let Err(e) = result {
+                debug_log("WARN", "RDP_LAUNCH", "Connections directory cleanup failed", Some(&e));
+            }
+        }
+
+        std::thread::sleep(core::connections::CONNECTIONS_CLEANUP_INTERVAL);
+    });
 }
 
 /// Tauri command to retrieve the recent connections list.
@@ -81,6 +128,167 @@ pub fn get_recent_connections() -> Result<Vec<RecentConnection>, String> {
     Ok(recent.connections)
 }
 
+/// Tauri command to retrieve recent connections made since a given time.
+///
+/// Lets the UI fetch only what changed since its last update.
+///
+/// # Arguments
+/// * `timestamp` - Unix timestamp (seconds); connections at or after it are returned
+///
+/// # Returns
+/// * `Ok(Vec<RecentConnection>)` - Matching connections, newest first
+/// * `Err(String)` - If loading from disk fails
+#[tauri::command]
+pub fn get_recent_connections_since(timestamp: u64) -> Result<Vec<RecentConnection>, String> {
+    let recent = load_recent_connections()?;
+    Ok(core::recent::connections_since(recent, timestamp))
+}
+
+/// Tauri command to search the connection history by hostname.
+///
+/// Searches `connection_history.jsonl` together with the recent connections
+/// list. A missing history file yields only the matching recents.
+///
+/// # Arguments
+/// * `query` - Hostname substring (case-insensitive; blank matches everything)
+/// * `limit` - Maximum number of results (0 = unlimited)
+///
+/// # Returns
+/// * `Ok(Vec<RecentConnection>)` - Matching connections, newest first
+/// * `Err(String)` - If a file can't be read
+#[tauri::command]
+pub fn search_connection_history(query: String, limit: usize) -> Result<Vec<RecentConnection>, String> {
+    let history_path = crate::infra::get_connection_history_path()?;
+    let history = core::history::load_connection_history_from(&history_path)
+        .map_err(|e| format!("Failed to read connection history: {}", e))?;
+    let recent = load_recent_connections()?;
+
+    Ok(core::history::search_connections(
+        history.into_iter().chain(recent.connections),
+        &query,
+        limit,
+    ))
+}
+
+/// Saves a recent connection as a permanent host.
+///
+/// Upserts the recent entry into the hosts file with the given metadata and
+/// notifies windows with `hosts-updated`.
+///
+/// # Arguments
+/// * `hostname` - Hostname from the recent connections list
+/// * `description` - Host description (the recent entry's when empty)
+/// * `group` - Optional group for the host
+///
+/// # Returns
+/// * `Ok(())` - Host saved
+/// * `Err(String)` - Not in recents, or the host couldn't be saved
+#[tauri::command]
+pub fn save_recent_as_host(
+    app_handle: tauri::AppHandle,
+    hostname: String,
+    description: String,
+    group: Option<String>,
+) -> Result<(), String> {
+    let recent = load_recent_connections()?;
+    let host = core::recent::host_from_recent(&recent, &hostname, &description, group.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    core::hosts::upsert_host(host).map_err(|e| e.to_string())?;
+    commands::hosts::emit_hosts_updated(&app_handle);
+
+    Ok(())
+}
+
+/// Reports where QuickConnect keeps its data and whether migration is pending.
+///
+/// # Returns
+/// * `Ok(StorageStatus)` - Data directory, hosts file, legacy `hosts.csv`,
+///   schema version and free space
+/// * `Err(String)` - The data directory couldn't be resolved or the hosts file read
+#[tauri::command]
+pub fn get_storage_status() -> Result<core::storage::StorageStatus, String> {
+    let data_dir = crate::infra::paths::current_data_dir()?;
+    let overridden = std::env::var(crate::infra::paths::DATA_DIR_ENV_VAR)
+        .is_ok_and(|dir| !dir.trim().is_empty());
+    let storage_format = core::settings::load_settings()
+        .unwrap_or_default()
+        .storage_format;
+    let legacy = std::env::current_dir()
+        .map(|dir| dir.join(core::hosts::LEGACY_HOSTS_CSV))
+        .unwrap_or_else(|_| PathBuf::from(core::hosts::LEGACY_HOSTS_CSV));
+
+    core::storage::storage_status_for(&data_dir, overridden, storage_format, &legacy, |dir| {
+        crate::adapters::free_disk_space(dir).ok()
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Sets the debug log threshold for one category (or a `PREFIX_*` pattern).
+///
+/// Lets e.g. `LDAP_*` log at DEBUG without turning on debug mode for
+/// everything. `level` is DEBUG, INFO, WARN, ERROR, or DEFAULT to clear it.
+/// Applies until the app exits.
+#[tauri::command]
+pub fn set_category_log_level(category: String, level: String) -> Result<(), String> {
+    crate::infra::set_category_log_level(&category, &level)
+}
+
+/// Most lines `get_log_tail` returns in one call.
+const MAX_LOG_TAIL_LINES: usize = 10_000;
+
+/// Returns the last lines of the debug log for in-app viewing.
+///
+/// # Arguments
+/// * `lines` - Number of lines to return (capped at `MAX_LOG_TAIL_LINES`)
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Lines, oldest first (empty if there is no log yet)
+/// * `Err(String)` - The log couldn't be read
+#[tauri::command]
+pub fn get_log_tail(lines: usize) -> Result<Vec<String>, String> {
+    let path = crate::infra::get_debug_log_path()?;
+    crate::infra::read_log_tail(&path, lines.min(MAX_LOG_TAIL_LINES))
+        .map_err(|e| format!("Failed to read debug log: {}", e))
+}
+
+/// Maximum time `get_recent_connections_with_status` waits for status probes.
+const RECENT_STATUS_DEADLINE: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Tauri command to retrieve the recent connections with their current status.
+///
+/// Probes all recent hosts concurrently. Hosts that don't answer within
+/// `RECENT_STATUS_DEADLINE` are reported as "unknown" so the call stays prompt.
+///
+/// # Returns
+/// * `Ok(Vec<RecentConnectionStatus>)` - Recent connections, most recent first
+/// * `Err(String)` - If loading from disk fails
+#[tauri::command]
+pub async fn get_recent_connections_with_status() -> Result<Vec<RecentConnectionStatus>, String> {
+    let recent = load_recent_connections()?;
+    let settings = core::settings::load_settings().unwrap_or_default();
+    let source_address = settings.status_probe_source();
+    let fallback_ports = settings.status_fallback_ports;
+
+    // Probing blocks on sockets, keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        core::status::recent_connections_with_status(
+            recent.connections,
+            move |hostname: &str| {
+                core::status::probe_host_with_fallbacks(
+                    hostname,
+                    &fallback_ports,
+                    source_address,
+                    core::status::PROBE_TIMEOUT,
+                )
+            },
+            RECENT_STATUS_DEADLINE,
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to check recent connection status: {}", e))
+}
+
 /// Tauri command to launch an RDP connection to a host.
 ///
 /// This is a thin wrapper that delegates to the core RDP launcher and handles UI events.
@@ -91,12 +299,21 @@ pub fn get_recent_connections() -> Result<Vec<RecentConnection>, String> {
 /// - Spawns mstsc.exe process
 /// - Updates recent connections list
 /// - Updates last connected timestamp in hosts.csv
+/// - Emits "rdp-launch-started" before spawning mstsc, then "rdp-launch-completed"
+///   or "rdp-launch-failed" (payload: hostname, and error on failure)
 /// - Emits "host-connected" event to refresh UI
 /// - Rebuilds system tray menu
+/// - Records the failure (or clears a previous one) in the per-host error tracker
+///
+/// `launch_mode` overrides the `rdp_launch_mode` setting for this launch.
 #[tauri::command]
-pub async fn launch_rdp(app_handle: tauri::AppHandle, host: Host) -> Result<(), String> {
+pub async fn launch_rdp(
+    app_handle: tauri::AppHandle,
+    host: Host,
+    launch_mode: Option<core::rdp_launcher::LaunchMode>,
+) -> Result<(), String> {
     // Call the core RDP launcher using function injection for testability
-    core::rdp_launcher::launch_rdp_connection(
+    let launch_result = core::rdp_launcher::launch_rdp_connection(
         &host,
         |hostname| async move {
             commands::get_host_credentials(hostname)
@@ -114,9 +331,27 @@ pub async fn launch_rdp(app_handle: tauri::AppHandle, host: Host) -> Result<(),
                     source: Some(anyhow::anyhow!(e)),
                 })
         },
+        launch_mode,
+        |event| {
+            let _ = app_handle.emit(event.event_name(), &event);
+        },
     )
-    .await
-    .map_err(|e| e.to_string())?;
+    .await;
+
+    if let Ok(mut tracker) = HOST_ERRORS.lock() {
+        match &launch_result {
+            Ok(_) => tracker.record_success(&host.hostname),
+            Err(e) => {
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                tracker.record_failure(&host.hostname, e.category(), &e.to_string(), timestamp);
+            }
+        }
+    }
+
+    launch_result.map_err(|e| e.to_string())?;
 
     // Update last connected timestamp and emit UI events
     if let Err(e) = commands::hosts::update_last_connected(&host.hostname) {
@@ -133,90 +368,437 @@ pub async fn launch_rdp(app_handle: tauri::AppHandle, host: Host) -> Result<(),
         }
 
         // Rebuild tray menu to update recent connections
-        if let Some(tray) = app_handle.tray_by_id("main") {
-            let current_theme = super::theme::get_theme_or_default(app_handle.clone());
-            if let Ok(new_menu) = build_tray_menu(&app_handle, &current_theme) {
-                let _ = tray.set_menu(Some(new_menu));
-            }
-        }
+        refresh_tray_menu(&app_handle);
     }
 
     Ok(())
 }
 
-/// Tauri command to scan Active Directory for Windows Servers via LDAP.
+/// Tauri command to open RDP sessions to every host in a group.
 ///
-/// This is a thin wrapper that delegates to the core LDAP scanner and handles CSV writing and UI events.
+/// Hosts are launched one at a time with a short stagger so mstsc instances
+/// don't race on credential writes. Groups larger than
+/// `core::hosts::GROUP_CONNECT_LIMIT` are refused unless `force` is set.
 ///
-/// # Side Effects
-/// - Connects to LDAP server (port 389)
-/// - Authenticates with stored credentials
-/// - Searches Active Directory
-/// - Writes results to hosts.csv
-/// - Emits "hosts-updated" event to refresh UI
-/// - Sets hosts window to always-on-top during scan
+/// # Arguments
+/// * `group` - Group name (case-insensitive)
+/// * `force` - Connect even if the group exceeds the safety cap
+///
+/// # Returns
+/// * `Ok(usize)` - Number of sessions launched
+/// * `Err(String)` - Hosts could not be loaded or the group is over the cap
 #[tauri::command]
-pub async fn scan_domain(
+pub async fn connect_group(
     app_handle: tauri::AppHandle,
-    domain: String,
-    server: String,
-) -> Result<String, String> {
+    group: String,
+    force: Option<bool>,
+) -> Result<usize, String> {
+    let hosts = core::hosts::get_all_hosts().map_err(|e| e.to_string())?;
+    let selected = core::hosts::select_group_hosts(&hosts, &group, force.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
+
+    debug_log(
+        "INFO",
+        "RDP_LAUNCH",
+        &format!("Connecting to {} hosts in group '{}'", selected.len(), group),
+        None,
+    );
+
+    Ok(core::rdp_launcher::launch_hosts_staggered(
+        selected,
+        |host| launch_rdp(app_handle.clone(), host, None),
+        core::rdp_launcher::BATCH_LAUNCH_STAGGER,
+    )
+    .await)
+}
+
+/// Tauri command to open a host's .rdp file for hand editing.
+///
+/// Generates the file first if it doesn't exist, then reveals it in Explorer.
+/// Does not start a connection.
+///
+/// # Arguments
+/// * `hostname` - Host whose RDP file to open (need not be in hosts.csv)
+#[tauri::command]
+pub async fn edit_rdp_file(hostname: String) -> Result<(), String> {
+    let host = find_host_or_default(&hostname);
+
+    core::rdp_launcher::open_rdp_file_for_edit(
+        &host,
+        |hostname| async move {
+            commands::get_host_credentials(hostname)
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get host credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+        || async {
+            commands::get_stored_credentials()
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get stored credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+    )
+    .await
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+/// Tauri command to delete a host's generated .rdp file.
+///
+/// The next launch regenerates it with the current RDP options, e.g. after
+/// display settings change. Saved credentials are kept, and a host without a
+/// file succeeds.
+///
+/// # Arguments
+/// * `hostname` - Host whose RDP file to delete (need not be in hosts.csv)
+#[tauri::command]
+pub fn clear_rdp_file(hostname: String) -> Result<(), String> {
+    let dir = crate::infra::get_connections_dir()?;
+
+    core::connections::clear_rdp_file_in(&dir, &hostname)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the saved host with this hostname, or a bare host if it isn't in hosts.csv.
+fn find_host_or_default(hostname: &str) -> Host {
+    core::hosts::get_all_hosts()
+        .ok()
+        .and_then(|hosts| {
+            hosts
+                .into_iter()
+                .find(|h| h.hostname.eq_ignore_ascii_case(hostname))
+        })
+        .unwrap_or_else(|| Host {
+            hostname: hostname.to_string(),
+            ..Default::default()
+        })
+}
+
+/// Tauri command to show the RDP file content a launch would produce for a host.
+///
+/// For previewing and debugging. Does not save TERMSRV credentials, write a
+/// file, or start mstsc. Without stored credentials the username and domain
+/// are left empty and the output starts with a note saying so.
+///
+/// # Arguments
+/// * `hostname` - Host to preview (need not be in hosts.csv)
+#[tauri::command]
+pub async fn preview_rdp_content(hostname: String) -> Result<String, String> {
+    let host = find_host_or_default(&hostname);
+    let options = core::rdp_launcher::rdp_options_for_host(&host.hostname);
+
+    core::rdp_launcher::preview_rdp_content(
+        &WindowsCredentialManager::new(),
+        &host,
+        &options,
+        |hostname| async move {
+            commands::get_host_credentials(hostname)
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get host credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+        || async {
+            commands::get_stored_credentials()
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get stored credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Tauri command to check the RDP file a launch would produce for a host.
+///
+/// Generates the file through the preview path (nothing is written or
+/// launched) and parses it back: required settings, a well-formed address,
+/// no repeated keys or control characters. Intended for automated checks.
+///
+/// # Arguments
+/// * `hostname` - Host to check (need not be in hosts.csv)
+#[tauri::command]
+pub async fn validate_generated_rdp(hostname: String) -> Result<core::rdp::RdpValidation, String> {
+    let host = find_host_or_default(&hostname);
+    let options = core::rdp_launcher::rdp_options_for_host(&host.hostname);
+
+    core::rdp_launcher::validate_generated_rdp(
+        &WindowsCredentialManager::new(),
+        &host,
+        &options,
+        |hostname| async move {
+            commands::get_host_credentials(hostname)
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get host credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+        || async {
+            commands::get_stored_credentials()
+                .await
+                .map_err(|e| AppError::CredentialManagerError {
+                    operation: "get stored credentials".to_string(),
+                    source: Some(anyhow::anyhow!(e)),
+                })
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Tauri command to get the last launch error recorded for a host.
+///
+/// # Returns
+/// * `Ok(Some(HostLastError))` - The host's most recent launch failure
+/// * `Ok(None)` - No failure recorded since the last successful launch
+#[tauri::command]
+pub fn get_host_last_error(hostname: String) -> Result<Option<HostLastError>, String> {
+    let tracker = HOST_ERRORS
+        .lock()
+        .map_err(|e| format!("Failed to lock host error tracker: {}", e))?;
+    Ok(tracker.last_error(&hostname).cloned())
+}
+
+/// Takes the domain scan lock, failing if another scan is running.
+///
+/// The lock is released when the returned guard is dropped.
+fn acquire_scan_lock() -> Result<core::ldap::ScanGuard<'static>, String> {
+    core::ldap::SCAN_LOCK.try_acquire().ok_or_else(|| {
+        debug_log(
+            "WARN",
+            "LDAP_SCAN",
+            "Rejected domain scan: another scan is already running",
+            None,
+        );
+        core::ldap::SCAN_ALREADY_RUNNING.to_string()
+    })
+}
+
+/// Runs an LDAP domain scan with the stored credentials.
+///
+/// Keeps the hosts window on top while the scan runs and never persists results.
+async fn run_domain_scan(
+    app_handle: &tauri::AppHandle,
+    domain: &str,
+    server: &str,
+    include_disabled: bool,
+) -> Result<core::ldap::DomainScanResult, String> {
     // Set hosts window to always on top during scan
     if let Some(hosts_window) = app_handle.get_webview_window("hosts") {
         let _ = hosts_window.set_always_on_top(true);
     }
 
     // Get credentials
-    let credentials = commands::get_stored_credentials().await?.ok_or_else(|| {
-        "No stored credentials found. Please save your domain credentials in the login window first."
-            .to_string()
-    })?;
+    let credentials = match commands::get_stored_credentials().await {
+        Ok(Some(credentials)) => Ok(credentials),
+        Ok(None) => Err(
+            "No stored credentials found. Please save your domain credentials in the login window first."
+                .to_string(),
+        ),
+        Err(e) => Err(e),
+    };
 
     // Perform LDAP scan using core module
-    let result = core::ldap::scan_domain_for_servers(&domain, &server, &credentials)
-        .await
-        .map_err(|e| e.to_string());
+    let result = match credentials {
+        Ok(credentials) => {
+            let follow_referrals = core::settings::load_settings()
+                .map(|settings| settings.ldap_follow_referrals)
+                .unwrap_or(false);
+            core::ldap::scan_domain_for_servers(domain, server, &credentials, include_disabled, follow_referrals)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => Err(e),
+    };
 
     // Reset window always on top
     if let Some(hosts_window) = app_handle.get_webview_window("hosts") {
         let _ = hosts_window.set_always_on_top(false);
     }
 
-    // Write results to CSV if successful
-    if let Ok(scan_result) = &result {
-        let csv_path = crate::infra::get_hosts_csv_path()?;
-        core::csv_writer::write_hosts_to_csv(&csv_path, &scan_result.hosts)
-            .map_err(|e| e.to_string())?;
+    result
+}
 
-        // Emit UI events
-        if let Some(main_window) = app_handle.get_webview_window("main") {
-            let _ = main_window.emit("hosts-updated", ());
-        }
-        if let Some(hosts_window) = app_handle.get_webview_window("hosts") {
-            let _ = hosts_window.emit("hosts-updated", ());
-        }
+/// Tauri command to check LDAP connectivity and credentials without scanning.
+///
+/// Connects to the domain controller, binds with the stored credentials, then
+/// unbinds. Lets admins verify the setup before scanning a large domain.
+///
+/// # Returns
+/// * `Ok(String)` - Success message
+/// * `Err(String)` - No stored credentials, or the connection or bind error
+#[tauri::command]
+pub async fn test_ldap_connection(domain: String, server: String) -> Result<String, String> {
+    let credentials = commands::get_stored_credentials().await?.ok_or_else(|| {
+        "No stored credentials found. Please save your domain credentials in the login window first."
+            .to_string()
+    })?;
 
-        Ok(format!(
-            "Successfully found {} Windows Server(s).",
-            scan_result.count
-        ))
-    } else {
-        result.map(|r| format!("Successfully found {} Windows Server(s).", r.count))
+    core::ldap::test_ldap_connection(&domain, &server, &credentials)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Tauri command to preview the name QuickConnect binds to LDAP with.
+///
+/// Uses the same domain normalization and `@domain` inference as a scan, so
+/// admins can check the bind string before scanning.
+///
+/// # Returns
+/// * `Ok(String)` - The bind name, e.g. `admin@contoso.com`
+/// * `Err(String)` - The username is empty
+#[tauri::command]
+pub fn preview_ldap_bind(domain: String, username: String) -> Result<String, String> {
+    if username.trim().is_empty() {
+        return Err("Username cannot be empty".to_string());
     }
+
+    let domain = core::ldap::normalize_domain(&domain);
+    Ok(core::ldap::build_bind_dn(&domain, &username))
+}
+
+/// Tauri command to scan Active Directory for Windows Servers without saving the results.
+///
+/// Lets the frontend show the discovered hosts for review before they are
+/// written with `commit_scanned_hosts`.
+///
+/// # Side Effects
+/// - Connects to LDAP server (port 389)
+/// - Authenticates with stored credentials
+/// - Searches Active Directory
+/// - Sets hosts window to always-on-top during scan
+///
+/// Disabled computer accounts are skipped unless `include_disabled` is true.
+#[tauri::command]
+pub async fn scan_domain_preview(
+    app_handle: tauri::AppHandle,
+    domain: String,
+    server: String,
+    include_disabled: Option<bool>,
+) -> Result<Vec<Host>, String> {
+    let _scan = acquire_scan_lock()?;
+    run_domain_scan(&app_handle, &domain, &server, include_disabled.unwrap_or(false))
+        .await
+        .map(|scan_result| scan_result.hosts)
+}
+
+/// Tauri command to scan Active Directory and return inventory details per host.
+///
+/// Like `scan_domain_preview` but each entry also carries the operatingSystem
+/// attribute and distinguished name. Nothing is persisted.
+#[tauri::command]
+pub async fn scan_domain_detailed(
+    app_handle: tauri::AppHandle,
+    domain: String,
+    server: String,
+    include_disabled: Option<bool>,
+) -> Result<Vec<core::ldap::ScannedHost>, String> {
+    let _scan = acquire_scan_lock()?;
+    run_domain_scan(&app_handle, &domain, &server, include_disabled.unwrap_or(false))
+        .await
+        .map(|scan_result| scan_result.details)
+}
+
+/// Tauri command to show what committing a scan would change.
+///
+/// Compares the scanned hosts with hosts.csv by normalized hostname. Hosts the
+/// scan didn't find are listed as `removed` for information only; nothing is
+/// written or deleted.
+///
+/// # Returns
+/// * `Ok(HostDiff)` - The `added`, `removed` and `unchanged` hosts
+/// * `Err(String)` - hosts.csv could not be read
+#[tauri::command]
+pub fn diff_scan_result(scanned: Vec<Host>) -> Result<core::hosts::HostDiff, String> {
+    core::hosts::diff_scan_result(scanned).map_err(|e| e.to_string())
+}
+
+/// Tauri command to save hosts selected from a scan preview.
+///
+/// # Arguments
+/// * `hosts` - The hosts to save (usually a subset of the preview)
+/// * `mode` - `"replace"` to overwrite hosts.csv or `"merge"` to add to it
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts in hosts.csv after the commit
+///
+/// # Side Effects
+/// - Writes hosts.csv
+/// - Emits "hosts-updated" event to refresh UI
+#[tauri::command]
+pub fn commit_scanned_hosts(
+    app_handle: tauri::AppHandle,
+    hosts: Vec<Host>,
+    mode: core::hosts::ScanCommitMode,
+) -> Result<usize, String> {
+    let count = core::hosts::commit_scanned_hosts(hosts, mode).map_err(|e| e.to_string())?;
+
+    // Emit UI events
+    commands::hosts::emit_hosts_updated(&app_handle);
+
+    Ok(count)
+}
+
+/// Tauri command to scan Active Directory for Windows Servers via LDAP.
+///
+/// Convenience wrapper that previews and then commits every discovered host,
+/// replacing the existing host list.
+///
+/// # Side Effects
+/// - Connects to LDAP server (port 389)
+/// - Authenticates with stored credentials
+/// - Searches Active Directory
+/// - Writes results to hosts.csv
+/// - Emits "hosts-updated" event to refresh UI
+/// - Sets hosts window to always-on-top during scan
+///
+/// Disabled computer accounts are skipped unless `include_disabled` is true.
+/// Fails without scanning while another scan is running.
+#[tauri::command]
+pub async fn scan_domain(
+    app_handle: tauri::AppHandle,
+    domain: String,
+    server: String,
+    include_disabled: Option<bool>,
+) -> Result<String, String> {
+    // Held until the results are written so a second scan can't interleave
+    let _scan = acquire_scan_lock()?;
+    let hosts = run_domain_scan(&app_handle, &domain, &server, include_disabled.unwrap_or(false))
+        .await?
+        .hosts;
+    let count = hosts.len();
+
+    commit_scanned_hosts(app_handle, hosts, core::hosts::ScanCommitMode::Replace)?;
+
+    Ok(format!("Successfully found {} Windows Server(s).", count))
 }
 
 /// Tauri command to reset the application to factory defaults.
 ///
 /// This is a thin wrapper that uses the WindowsCredentialManager adapter to delete credentials safely.
+/// Every step runs even when an earlier one fails; the returned summary records
+/// each step's outcome, whether all of them succeeded, and the report text to show.
 ///
 /// # Side Effects
 /// - Deletes all QuickConnect credentials from Windows Credential Manager
-/// - Deletes all TERMSRV/* credentials
-/// - Deletes all RDP files in %APPDATA%\QuickConnect\Connections
+/// - Deletes all TERMSRV/* credentials (generic and domain password types) and
+///   migrated_credentials.json
+/// - Deletes all RDP files in the Connections directory (honors QUICKCONNECT_DATA_DIR)
 /// - Clears hosts.csv
-/// - Deletes recent_connections.json
+/// - Deletes the hosts file of the storage format not in use (hosts.json or hosts.csv)
+/// - Deletes recent_connections.json, connection_usage.json and connection_history.jsonl
+/// - Deletes settings.json
 #[tauri::command]
-pub async fn reset_application(app_handle: tauri::AppHandle) -> Result<String, String> {
+pub async fn reset_application(app_handle: tauri::AppHandle) -> Result<core::reset::ResetSummary, String> {
     debug_log(
         "WARN",
         "RESET",
@@ -224,88 +806,121 @@ pub async fn reset_application(app_handle: tauri::AppHandle) -> Result<String, S
         None,
     );
 
-    let mut report = String::from("=== QuickConnect Application Reset ===\n\n");
+    let mut report = core::reset::ResetReport::default();
     let cred_manager = WindowsCredentialManager::new();
 
-    // 1. Delete global credentials
-    match commands::delete_credentials().await {
-        Ok(_) => {
-            report.push_str("✓ Deleted global QuickConnect credentials\n");
-            debug_log("INFO", "RESET", "Deleted global credentials", None);
-        }
-        Err(e) => {
-            report.push_str(&format!("✗ Failed to delete global credentials: {}\n", e));
-        }
+    // 1. Delete global credentials and their save time
+    core::reset::delete_global_credentials(&cred_manager, &mut report);
+    if let Ok(meta_file) = crate::infra::get_credential_meta_path() {
+        let _ = core::reset::remove_data_file(&meta_file);
     }
 
-    // 2. Delete all TERMSRV/* credentials using adapter
-    match cred_manager.list_with_prefix("TERMSRV/") {
-        Ok(targets) => {
-            let count = targets.len();
-            report.push_str(&format!("\nFound {} RDP host credentials:\n", count));
-            for target in &targets {
-                report.push_str(&format!("  - {}\n", target));
-                if let Err(e) = cred_manager.delete(target) {
-                    report.push_str(&format!("    ✗ Failed to delete: {}\n", e));
-                }
-            }
-            report.push_str(&format!("✓ Processed {} RDP host credentials\n", count));
-        }
-        Err(e) => {
-            report.push_str(&format!("✗ Failed to enumerate TERMSRV credentials: {}\n", e));
-        }
+    // 2. Delete all TERMSRV/* credentials using adapter (QuickConnect and Windows-saved),
+    //    and the record of which were migrated to the domain type
+    core::reset::delete_termsrv_credentials(&cred_manager, &mut report);
+    if let Ok(migrated_file) = crate::infra::get_migrated_credentials_path() {
+        let _ = core::reset::remove_data_file(&migrated_file);
     }
 
     // 3. Delete all RDP files
-    if let Ok(appdata_dir) = std::env::var("APPDATA") {
-        let connections_dir = PathBuf::from(appdata_dir)
-            .join("QuickConnect")
-            .join("Connections");
-        report.push_str(&format!("\nRDP Files in {:?}:\n", connections_dir));
-
-        if connections_dir.exists() {
-            match std::fs::read_dir(&connections_dir) {
-                Ok(entries) => {
-                    let mut deleted_count = 0;
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.extension().and_then(|s| s.to_str()) == Some("rdp")
-                            && std::fs::remove_file(&path).is_ok()
-                        {
-                            deleted_count += 1;
-                        }
-                    }
-                    report.push_str(&format!("✓ Deleted {} RDP files\n", deleted_count));
-                }
-                Err(e) => {
-                    report.push_str(&format!("✗ Failed to read connections directory: {}\n", e));
-                }
-            }
-        }
+    match crate::infra::get_connections_dir() {
+        Ok(connections_dir) => core::reset::delete_rdp_files_in(&connections_dir, &mut report),
+        Err(e) => report.errors.push(e),
     }
 
     // 4. Delete hosts.csv
-    match commands::delete_all_hosts(app_handle).await {
-        Ok(_) => report.push_str("\n✓ Cleared hosts.csv\n"),
-        Err(e) => report.push_str(&format!("\n✗ Failed to clear hosts.csv: {}\n", e)),
+    // The hosts file of the other storage format is deleted below; find it
+    // while the storage_format setting still exists
+    let inactive_hosts_path = core::hosts_store::active_hosts_path().and_then(|path| {
+        core::hosts_store::hosts_path_for(core::hosts_store::StorageFormat::from_path(&path).other())
+    });
+
+    // The reset itself is the confirmation, so this skips the delete-all token
+    match core::hosts::delete_all_hosts() {
+        Ok(_) => {
+            commands::hosts::emit_hosts_updated(&app_handle);
+            report.hosts_cleared = true;
+        }
+        Err(e) => report.errors.push(format!("Failed to clear hosts.csv: {}", e)),
     }
 
-    // 5. Delete recent_connections.json
-    if let Ok(appdata_dir) = std::env::var("APPDATA") {
-        let recent_file = PathBuf::from(appdata_dir)
-            .join("QuickConnect")
-            .join("recent_connections.json");
-        if recent_file.exists() {
-            match std::fs::remove_file(&recent_file) {
-                Ok(_) => report.push_str("✓ Deleted recent connections history\n"),
-                Err(e) => report.push_str(&format!("✗ Failed to delete recent connections: {}\n", e)),
-            }
+    // 5. Delete recent_connections.json, connection_usage.json,
+    //    connection_history.jsonl and the unused hosts file
+    // 6. Delete settings.json
+    let data_files = [
+        (crate::infra::get_recent_connections_path(), &mut report.recents_deleted),
+        (crate::infra::get_connection_usage_path(), &mut report.usage_deleted),
+        (crate::infra::get_connection_history_path(), &mut report.history_deleted),
+        (inactive_hosts_path.map_err(|e| e.to_string()), &mut report.inactive_hosts_deleted),
+        (crate::infra::get_settings_path(), &mut report.settings_deleted),
+    ];
+    let mut file_errors = Vec::new();
+    for (path, deleted) in data_files {
+        match path.and_then(|path| core::reset::remove_data_file(&path)) {
+            Ok(()) => *deleted = true,
+            Err(e) => file_errors.push(e),
         }
     }
+    report.errors.extend(file_errors);
+
+    // Recent connections are gone from the tray too
+    refresh_tray_menu(&app_handle);
+
+    let summary = core::reset::ResetSummary::from(report);
+    debug_log(
+        if summary.complete { "INFO" } else { "WARN" },
+        "RESET",
+        &format!("Application reset finished (complete: {})", summary.complete),
+        None,
+    );
+    Ok(summary)
+}
 
-    report.push_str("\n=== Reset Complete ===\n");
-    report.push_str("The application has been reset to its initial state.\n");
-    report.push_str("Please restart the application.\n");
+/// Tauri command to back up the data directory to a zip archive.
+///
+/// Thin wrapper that delegates to core::backup::backup_data_dir(). The
+/// archive holds the hosts file, recent connections, settings and the
+/// Connections directory; credentials can't be exported and are not included.
+///
+/// # Returns
+/// * `Ok(())` - Archive written to `dest_zip`
+/// * `Err(String)` - A file couldn't be read or the archive written
+#[tauri::command]
+pub fn backup_data(dest_zip: String) -> Result<(), String> {
+    let data_dir = crate::infra::paths::get_quick_connect_dir()?;
+    core::backup::backup_data_dir(&data_dir, std::path::Path::new(&dest_zip))
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Tauri command to restore the data directory from a zip archive.
+///
+/// Thin wrapper that delegates to core::backup::restore_data_dir(). Without
+/// `overwrite`, nothing is restored if any existing file would be replaced;
+/// the returned report lists them so the UI can confirm and call again with
+/// `overwrite: true`.
+///
+/// # Side Effects
+/// - Emits `hosts-updated` and rebuilds the tray menu when files were restored
+#[tauri::command]
+pub fn restore_data(
+    app_handle: tauri::AppHandle,
+    src_zip: String,
+    overwrite: Option<bool>,
+) -> Result<core::backup::RestoreReport, String> {
+    let data_dir = crate::infra::paths::get_quick_connect_dir()?;
+    let report = core::backup::restore_data_dir(
+        std::path::Path::new(&src_zip),
+        &data_dir,
+        overwrite.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())?;
+
+    if !report.restored.is_empty() {
+        core::hosts::mark_hosts_replaced();
+        commands::hosts::emit_hosts_updated(&app_handle);
+        refresh_tray_menu(&app_handle);
+    }
 
     Ok(report)
 }
@@ -380,82 +995,142 @@ fn disable_autostart() -> Result<(), String> {
     Ok(())
 }
 
+/// Path of the running executable, as written to the Run key.
+fn current_exe_string() -> Result<String, String> {
+    std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to get executable path: {}", e))
+}
+
+/// Tauri command to find duplicate or stale QuickConnect autostart entries.
+///
+/// Lists every Run value that starts a QuickConnect executable other than the
+/// current one (e.g. `QuickConnect (1)` left behind by a reinstall elsewhere).
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - One line per problem entry (empty when clean)
+/// * `Err(String)` - The Run key could not be read
+///
+/// # Side Effects
+/// - Emits `autostart-duplicates-detected` to the main window when problems are found
+#[tauri::command]
+pub fn audit_autostart(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let current_exe = current_exe_string()?;
+    let entries = core::autostart::audit_autostart_entries(&WindowsRegistry::new(), &current_exe, |path| {
+        std::path::Path::new(path).exists()
+    })
+    .map_err(|e| e.to_string())?;
+
+    let problems: Vec<String> = entries
+        .iter()
+        .filter(|entry| entry.kind != core::autostart::AutostartEntryKind::Current)
+        .map(|entry| entry.describe())
+        .collect();
+
+    if !problems.is_empty() {
+        debug_log(
+            "WARN",
+            "AUTOSTART",
+            &format!("Found {} duplicate or stale autostart entries", problems.len()),
+            Some(&problems.join("\n")),
+        );
+        if let Some(main_window) = app_handle.get_webview_window("main") {
+            let _ = main_window.emit("autostart-duplicates-detected", &problems);
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Tauri command to remove every QuickConnect autostart entry except the current exe's.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Names of the removed Run values
+/// * `Err(String)` - The Run key could not be read or written
+#[tauri::command]
+pub fn clean_autostart_duplicates(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let current_exe = current_exe_string()?;
+    let removed = core::autostart::clean_autostart_duplicates(&WindowsRegistry::new(), &current_exe, |path| {
+        std::path::Path::new(path).exists()
+    })
+    .map_err(|e| e.to_string())?;
+
+    if !removed.is_empty() {
+        debug_log(
+            "INFO",
+            "AUTOSTART",
+            &format!("Removed {} autostart entries", removed.len()),
+            Some(&removed.join(", ")),
+        );
+        // The current entry may have been among those removed
+        refresh_tray_menu(&app_handle);
+    }
+
+    Ok(removed)
+}
+
+/// Maximum time `build_tray_menu` waits for status probes when
+/// `tray_status_indicators` is on.
+const TRAY_STATUS_DEADLINE: std::time::Duration = std::time::Duration::from_secs(1);
+
 /// Helper function to build tray menu with theme awareness
+///
+/// The item set comes from `core::tray::tray_menu_layout`.
 pub fn build_tray_menu(
     app: &tauri::AppHandle,
     current_theme: &str,
 ) -> Result<Menu<tauri::Wry>, Box<dyn std::error::Error>> {
-    // Check autostart status
+    let recent_connections = load_recent_connections().unwrap_or_else(|_| RecentConnections::new());
     let autostart_enabled = check_autostart().unwrap_or(false);
-    let autostart_text = if autostart_enabled {
-        "✓ Autostart with Windows"
+    let settings = core::settings::load_settings().unwrap_or_default();
+    let statuses = if settings.tray_status_indicators {
+        let hostnames: Vec<String> = recent_connections
+            .connections
+            .iter()
+            .map(|conn| conn.hostname.clone())
+            .collect();
+        super::hosts::tray_host_statuses(&hostnames, TRAY_STATUS_DEADLINE)
     } else {
-        "✗ Autostart with Windows"
+        HashMap::new()
     };
-    let autostart_item =
-        MenuItem::with_id(app, "toggle_autostart", autostart_text, true, None::<&str>)?;
+    let layout = core::tray::tray_menu_layout(
+        &recent_connections.connections,
+        &settings.recent_label_format,
+        &statuses,
+        current_theme,
+        autostart_enabled,
+    );
 
-    // Create theme menu items with checkmarks
-    let theme_light = MenuItem::with_id(
-        app,
-        "theme_light",
-        if current_theme == "light" {
-            "✓ Light"
-        } else {
-            "✗ Light"
-        },
-        true,
-        None::<&str>,
-    )?;
-    let theme_dark = MenuItem::with_id(
-        app,
-        "theme_dark",
-        if current_theme == "dark" {
-            "✓ Dark"
-        } else {
-            "✗ Dark"
-        },
-        true,
-        None::<&str>,
-    )?;
+    let to_menu_items = |items: &[core::tray::TrayItem]| {
+        items
+            .iter()
+            .map(|item| MenuItem::with_id(app, &item.id, &item.label, item.enabled, None::<&str>))
+            .collect::<Result<Vec<_>, _>>()
+    };
 
-    let theme_submenu = Submenu::with_items(app, "Theme", true, &[&theme_light, &theme_dark])?;
+    let recent_items = to_menu_items(&layout.recent)?;
+    let recent_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = recent_items
+        .iter()
+        .map(|item| item as &dyn tauri::menu::IsMenuItem<tauri::Wry>)
+        .collect();
+    let recent_submenu = Submenu::with_items(app, "Recent Connections", true, &recent_refs)?;
 
-    // Create recent connections submenu
-    let recent_connections = load_recent_connections().unwrap_or_else(|_| RecentConnections::new());
+    let theme_items = to_menu_items(&layout.theme)?;
+    let theme_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = theme_items
+        .iter()
+        .map(|item| item as &dyn tauri::menu::IsMenuItem<tauri::Wry>)
+        .collect();
+    let theme_submenu = Submenu::with_items(app, "Theme", true, &theme_refs)?;
 
-    let recent_submenu = if recent_connections.connections.is_empty() {
-        let no_recent = MenuItem::with_id(
-            app,
-            "no_recent",
-            "No recent connections",
-            false,
-            None::<&str>,
-        )?;
-        Submenu::with_items(app, "Recent Connections", true, &[&no_recent])?
-    } else {
-        // Build submenu with actual recent items
-        let items: Vec<_> = recent_connections
-            .connections
-            .iter()
-            .map(|conn| {
-                let label = if conn.description.is_empty() {
-                    conn.hostname.clone()
-                } else {
-                    format!("{} - {}", conn.hostname, conn.description)
-                };
-                let menu_id = format!("recent_{}", conn.hostname);
-                MenuItem::with_id(app, &menu_id, &label, true, None::<&str>)
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let item_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = items
-            .iter()
-            .map(|item| item as &dyn tauri::menu::IsMenuItem<tauri::Wry>)
-            .collect();
-        Submenu::with_items(app, "Recent Connections", true, &item_refs)?
-    };
+    let autostart_item = MenuItem::with_id(
+        app,
+        &layout.autostart.id,
+        &layout.autostart.label,
+        layout.autostart.enabled,
+        None::<&str>,
+    )?;
 
+    let refresh_status_item = MenuItem::with_id(app, "refresh_status", "Refresh Status", true, None::<&str>)?;
     let about_item = MenuItem::with_id(app, "about", "About QuickConnect", true, None::<&str>)?;
     let separator = PredefinedMenuItem::separator(app)?;
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
@@ -466,6 +1141,7 @@ pub fn build_tray_menu(
             &recent_submenu,
             &theme_submenu,
             &autostart_item,
+            &refresh_status_item,
             &about_item,
             &separator,
             &quit_item,
@@ -473,3 +1149,34 @@ pub fn build_tray_menu(
     )
     .map_err(|e| e.into())
 }
+
+/// Rebuilds the tray menu from the current recents, theme and autostart state.
+///
+/// Call after anything the tray shows has changed.
+pub fn refresh_tray_menu(app_handle: &tauri::AppHandle) {
+    let Some(tray) = app_handle.tray_by_id("main") else {
+        return;
+    };
+
+    let current_theme = super::theme::get_theme_or_default(app_handle.clone());
+    match build_tray_menu(app_handle, &current_theme) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => debug_log(
+            "WARN",
+            "TRAY",
+            "Failed to rebuild tray menu",
+            Some(&e.to_string()),
+        ),
+    }
+}
+
+/// Tauri command to rebuild the tray menu on demand.
+///
+/// For use after changes made outside QuickConnect's own commands.
+#[tauri::command]
+pub fn refresh_tray(app_handle: tauri::AppHandle) -> Result<(), String> {
+    refresh_tray_menu(&app_handle);
+    Ok(())
+}
@@ -4,6 +4,9 @@
 //! theme persistence, and theme change event propagation.
 
 use crate::adapters::{RegistryAdapter, WindowsRegistry};
+use crate::core::theme::{effective_theme, AUTO_THEME_CHECK_INTERVAL, THEME_AUTO_TIME};
+use crate::infra::debug_log;
+use chrono::Timelike;
 use tauri::{Emitter, Manager};
 
 /// Tauri command to get the Windows system theme.
@@ -29,9 +32,12 @@ pub fn get_windows_theme() -> Result<String, String> {
 
 /// Sets the application theme and notifies all windows.
 ///
+/// `theme` is `"light"`, `"dark"` or `"auto_time"` (follow the time of day,
+/// see the `auto_theme_dark_hour` / `auto_theme_light_hour` settings).
+///
 /// Thin wrapper that:
 /// 1. Saves theme preference to disk
-/// 2. Emits theme-changed events to all windows
+/// 2. Emits theme-changed events (with the effective theme) to all windows
 /// 3. Rebuilds tray menu with new theme
 #[tauri::command]
 pub fn set_theme(app_handle: tauri::AppHandle, theme: String) -> Result<(), String> {
@@ -49,51 +55,133 @@ pub fn set_theme(app_handle: tauri::AppHandle, theme: String) -> Result<(), Stri
         .map_err(|e| format!("Failed to write theme preference: {}", e))?;
 
     // Emit an event to all windows to update their theme
-    for window_label in ["login", "main", "hosts", "about", "error"] {
-        if let Some(window) = app_handle.get_webview_window(window_label) {
-            let _ = window.emit("theme-changed", theme.clone());
-        }
-    }
+    emit_theme_changed(&app_handle, &resolve_theme(&theme));
 
     // Rebuild tray menu with new theme
-    if let Some(tray) = app_handle.tray_by_id("main") {
-        if let Ok(menu) = super::system::build_tray_menu(&app_handle, &theme) {
-            let _ = tray.set_menu(Some(menu));
+    super::system::refresh_tray_menu(&app_handle);
+
+    Ok(())
+}
+
+/// Sends the effective theme to every window
+fn emit_theme_changed(app_handle: &tauri::AppHandle, theme: &str) {
+    for window_label in ["login", "main", "hosts", "about", "error"] {
+        if let Some(window) = app_handle.get_webview_window(window_label) {
+            let _ = window.emit("theme-changed", theme.to_string());
         }
     }
+}
 
-    Ok(())
+/// Resolves a theme preference to "light" or "dark" using the local hour
+fn resolve_theme(preference: &str) -> String {
+    let settings = crate::core::settings::load_settings().unwrap_or_default();
+    effective_theme(
+        preference,
+        chrono::Local::now().hour(),
+        settings.auto_theme_dark_hour,
+        settings.auto_theme_light_hour,
+    )
 }
 
-/// Gets the current theme with a guaranteed fallback to "dark"
+/// Gets the saved theme preference (which may be "auto_time") with a guaranteed fallback
 ///
-/// This function ensures a theme is always returned, falling back to:
+/// This is what the tray checkmarks reflect. Falls back to:
 /// 1. Saved app preference
-/// 2. Windows system theme
-/// 3. "dark" as ultimate fallback
+/// 2. `Theme` registry default (see `core::registry_defaults`)
+/// 3. Windows system theme
+/// 4. "dark" as ultimate fallback
 pub fn get_theme_or_default(app_handle: tauri::AppHandle) -> String {
-    get_theme(app_handle).unwrap_or_else(|_| "dark".to_string())
+    get_theme_preference(&app_handle).unwrap_or_else(|_| "dark".to_string())
+}
+
+/// Reads the saved theme preference, falling back to the registry default
+/// and then the Windows system theme
+fn get_theme_preference(app_handle: &tauri::AppHandle) -> Result<String, String> {
+    match saved_theme_preference(app_handle)
+        .or_else(|| crate::core::registry_defaults::registry_defaults().theme.clone())
+    {
+        Some(theme) => Ok(theme),
+        None => get_windows_theme(),
+    }
+}
+
+/// Reads the saved theme preference
+///
+/// # Returns
+/// * `None` - No preference is saved (or it can't be read), so the Windows theme is followed
+pub(crate) fn saved_theme_preference(app_handle: &tauri::AppHandle) -> Option<String> {
+    let app_dir = app_handle.path().app_data_dir().ok()?;
+    std::fs::read_to_string(app_dir.join("theme.txt"))
+        .ok()
+        .map(|theme| theme.trim().to_string())
+}
+
+/// Removes the saved theme preference so the Windows theme is followed again
+///
+/// Windows and the tray are updated as for `set_theme`.
+pub(crate) fn clear_theme(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+
+    let theme_file = app_dir.join("theme.txt");
+    if theme_file.exists() {
+        std::fs::remove_file(&theme_file)
+            .map_err(|e| format!("Failed to remove theme preference: {}", e))?;
+    }
+
+    emit_theme_changed(app_handle, &resolve_theme(&get_theme_preference(app_handle)?));
+    super::system::refresh_tray_menu(app_handle);
+    Ok(())
 }
 
-/// Gets the currently saved theme preference.
+/// Gets the theme to display.
 ///
-/// Falls back to Windows system theme if no preference is saved.
+/// Falls back to Windows system theme if no preference is saved. An
+/// `"auto_time"` preference is resolved to "light" or "dark" for the current hour.
 #[tauri::command]
 pub fn get_theme(app_handle: tauri::AppHandle) -> Result<String, String> {
-    // Try to read the saved theme preference
-    let app_dir = match app_handle.path().app_data_dir() {
-        Ok(dir) => dir,
-        Err(_) => return get_windows_theme(), // Fallback to Windows theme
-    };
+    get_theme_preference(&app_handle).map(|preference| resolve_theme(&preference))
+}
 
-    let theme_file = app_dir.join("theme.txt");
+/// Re-applies the time-of-day theme in the background as the hour changes.
+///
+/// Checks every `AUTO_THEME_CHECK_INTERVAL`; does nothing unless the saved
+/// preference is `"auto_time"`. Windows get a `theme-changed` event when the
+/// effective theme flips.
+///
+/// # Side Effects
+/// - Spawns a background thread for the lifetime of the app
+pub fn spawn_auto_theme_timer(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        // Seeded with the theme the windows start with, so a flip before the
+        // first check is still announced
+        let preference = get_theme_or_default(app_handle.clone());
+        let mut applied: Option<String> =
+            (preference == THEME_AUTO_TIME).then(|| resolve_theme(&preference));
 
-    if theme_file.exists() {
-        match std::fs::read_to_string(&theme_file) {
-            Ok(theme) => Ok(theme.trim().to_string()),
-            Err(_) => get_windows_theme(), // Fallback to Windows theme
+        loop {
+            std::thread::sleep(AUTO_THEME_CHECK_INTERVAL);
+
+            let preference = get_theme_or_default(app_handle.clone());
+            if preference != THEME_AUTO_TIME {
+                applied = None;
+                continue;
+            }
+
+            let theme = resolve_theme(&preference);
+            // Right after switching to "auto_time" this only records the theme
+            if applied.as_ref().is_some_and(|previous| *previous != theme) {
+                debug_log(
+                    "INFO",
+                    "THEME",
+                    &format!("Time-of-day theme switched to {}", theme),
+                    None,
+                );
+                emit_theme_changed(&app_handle, &theme);
+            }
+            applied = Some(theme);
         }
-    } else {
-        get_windows_theme() // Fallback to Windows theme
-    }
+    });
 }
@@ -4,14 +4,103 @@
 //! Commands handle window show/hide operations and maintain window state tracking.
 
 use crate::ErrorPayload;
+use crate::core::idle::{now_secs, should_auto_hide};
+use crate::core::shortcuts::{ShortcutStatus, GLOBAL_SHORTCUTS};
 use crate::infra::debug_log;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
+use std::time::Duration;
 use tauri::{Emitter, Manager};
 
 /// Global state tracking the last hidden window for restoration purposes.
 /// Used by the system tray to restore the most recently hidden window.
 pub static LAST_HIDDEN_WINDOW: Mutex<String> = Mutex::new(String::new());
 
+/// Unix timestamp (seconds) of the last user interaction with the main window.
+/// Used by the idle auto-hide timer.
+pub static LAST_ACTIVITY: AtomicU64 = AtomicU64::new(0);
+
+/// Registration outcome of each global shortcut, recorded at startup.
+static SHORTCUT_STATUS: Mutex<Vec<ShortcutStatus>> = Mutex::new(Vec::new());
+
+/// Records whether a global shortcut registered, for `get_shortcut_status`.
+pub fn record_shortcut_result<E: std::fmt::Display>(accelerator: &str, result: Result<(), E>) {
+    if let Ok(mut statuses) = SHORTCUT_STATUS.lock() {
+        statuses.push(ShortcutStatus::from_result(accelerator, result));
+    }
+}
+
+/// Tauri command reporting which global shortcuts are active.
+///
+/// Lets the UI warn when a hotkey is held by another application.
+#[tauri::command]
+pub fn get_shortcut_status() -> Result<Vec<ShortcutStatus>, String> {
+    let recorded = SHORTCUT_STATUS.lock().map_err(|e| e.to_string())?;
+    Ok(crate::core::shortcuts::collect_shortcut_statuses(&GLOBAL_SHORTCUTS, &recorded))
+}
+
+/// How often the idle auto-hide timer checks for inactivity.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Marks the main window as active now, restarting the idle auto-hide timer.
+pub fn touch_activity() {
+    LAST_ACTIVITY.store(now_secs(), Ordering::Relaxed);
+}
+
+/// Tauri command to record user activity in the main window.
+///
+/// Called by the frontend on focus and search input so the idle auto-hide
+/// timer only fires after real inactivity.
+#[tauri::command]
+pub fn record_activity() {
+    touch_activity();
+}
+
+/// Starts the background idle auto-hide timer for the main window.
+///
+/// Every `IDLE_CHECK_INTERVAL` the configured `auto_hide_minutes` setting is
+/// re-read; when the main window is visible and has been idle for that long it
+/// is hidden to the tray, exactly as on a manual close.
+///
+/// # Side Effects
+/// - Spawns a background thread for the lifetime of the app
+/// - Hides the main window and sets `LAST_HIDDEN_WINDOW` to "main"
+pub fn start_idle_auto_hide(app_handle: tauri::AppHandle) {
+    touch_activity();
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(IDLE_CHECK_INTERVAL);
+
+        let timeout_minutes = crate::core::settings::load_settings()
+            .map(|settings| settings.auto_hide_minutes)
+            .unwrap_or(0);
+
+        let Some(window) = app_handle.get_webview_window("main") else {
+            continue;
+        };
+
+        // Idle time only counts while the window is on screen
+        if !window.is_visible().unwrap_or(false) {
+            touch_activity();
+            continue;
+        }
+
+        if should_auto_hide(timeout_minutes, LAST_ACTIVITY.load(Ordering::Relaxed), now_secs()) {
+            debug_log(
+                "INFO",
+                "WINDOW",
+                &format!("Main window idle for {} minute(s), hiding to tray", timeout_minutes),
+                None,
+            );
+            if let Ok(mut last_hidden) = LAST_HIDDEN_WINDOW.lock() {
+                *last_hidden = "main".to_string();
+            }
+            let _ = window.hide();
+            touch_activity();
+        }
+    });
+}
+
 /// Tauri command to exit the application gracefully.
 ///
 /// This command is typically called from the system tray menu or when the user
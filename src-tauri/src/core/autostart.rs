@@ -0,0 +1,309 @@
+//! Autostart (Run key) auditing
+//!
+//! Finds QuickConnect entries under `HKCU\...\CurrentVersion\Run` and prunes
+//! the ones that don't belong to the running executable.
+//!
+//! # Why this exists
+//! Reinstalling into a different folder can leave several Run entries
+//! (`QuickConnect`, `QuickConnect (1)`, ...) pointing at different copies,
+//! which starts two instances at logon.
+//!
+//! # Why separate
+//! Takes the registry adapter, executable path and file-existence check as
+//! parameters so detection and pruning can be tested against a mock registry.
+
+use crate::adapters::RegistryAdapter;
+use crate::AppError;
+use serde::Serialize;
+
+/// Registry key holding per-user autostart entries
+pub const REGISTRY_RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+/// Run value name QuickConnect writes when autostart is enabled
+pub const APP_NAME: &str = "QuickConnect";
+
+/// Executable name that identifies a QuickConnect entry, whatever its value name
+const EXE_NAME: &str = "quickconnect.exe";
+
+/// How a QuickConnect Run entry relates to the running executable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutostartEntryKind {
+    /// Starts the running executable; the entry to keep
+    Current,
+    /// Starts another copy that still exists, or repeats the current entry
+    Duplicate,
+    /// Starts an executable that no longer exists
+    Stale,
+}
+
+/// A Run entry that starts QuickConnect
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AutostartEntry {
+    /// Run value name
+    pub name: String,
+    /// Executable path from the value (quotes and arguments removed)
+    pub path: String,
+    pub kind: AutostartEntryKind,
+}
+
+impl AutostartEntry {
+    /// One-line description for the audit report
+    pub fn describe(&self) -> String {
+        let problem = match self.kind {
+            AutostartEntryKind::Current => "current",
+            AutostartEntryKind::Duplicate => "duplicate",
+            AutostartEntryKind::Stale => "stale: file not found",
+        };
+        format!("{}: {} ({})", self.name, self.path, problem)
+    }
+}
+
+/// Extracts the executable path from a Run command line
+///
+/// Handles quoted paths (`"C:\Program Files\QuickConnect.exe" --flag`) and
+/// unquoted paths followed by arguments.
+pub fn command_exe_path(command: &str) -> String {
+    let command = command.trim();
+    if let Some(rest) = command.strip_prefix('"') {
+        return rest.split('"').next().unwrap_or(rest).to_string();
+    }
+
+    match command.to_ascii_lowercase().find(".exe") {
+        Some(index) => command[..index + ".exe".len()].to_string(),
+        None => command.to_string(),
+    }
+}
+
+/// Returns true when a Run command starts a QuickConnect executable
+pub fn is_quickconnect_command(command: &str) -> bool {
+    let path = command_exe_path(command);
+    path.rsplit(['\\', '/'])
+        .next()
+        .is_some_and(|file| file.eq_ignore_ascii_case(EXE_NAME))
+}
+
+/// Compares Windows paths case-insensitively, ignoring separator style
+fn same_path(a: &str, b: &str) -> bool {
+    a.replace('/', "\\").eq_ignore_ascii_case(&b.replace('/', "\\"))
+}
+
+/// Lists the QuickConnect Run entries and classifies them
+///
+/// The entry to keep is the one starting `current_exe`, preferring the
+/// `QuickConnect` value name when several do.
+///
+/// # Arguments
+/// * `registry` - Registry adapter
+/// * `current_exe` - Path of the running executable
+/// * `exists` - Whether an executable path exists on disk
+pub fn audit_autostart_entries<R, E>(
+    registry: &R,
+    current_exe: &str,
+    exists: E,
+) -> Result<Vec<AutostartEntry>, AppError>
+where
+    R: RegistryAdapter,
+    E: Fn(&str) -> bool,
+{
+    let entries: Vec<(String, String)> = registry
+        .list_string_values(REGISTRY_RUN_KEY)?
+        .into_iter()
+        .filter(|(_, command)| is_quickconnect_command(command))
+        .map(|(name, command)| (name, command_exe_path(&command)))
+        .collect();
+
+    let keep = entries
+        .iter()
+        .position(|(name, path)| name == APP_NAME && same_path(path, current_exe))
+        .or_else(|| entries.iter().position(|(_, path)| same_path(path, current_exe)));
+
+    Ok(entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, path))| {
+            let kind = if Some(index) == keep {
+                AutostartEntryKind::Current
+            } else if !same_path(&path, current_exe) && !exists(&path) {
+                AutostartEntryKind::Stale
+            } else {
+                AutostartEntryKind::Duplicate
+            };
+            AutostartEntry { name, path, kind }
+        })
+        .collect())
+}
+
+/// Deletes every QuickConnect Run entry except the current executable's
+///
+/// A surviving entry under another value name (e.g. `QuickConnect (1)`) is
+/// moved to `QuickConnect`, so `check_autostart` and `disable_autostart`
+/// find it afterwards.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Names of the deleted values, including a renamed entry's old name
+/// * `Err(AppError)` - The registry could not be read or written
+pub fn clean_autostart_duplicates<R, E>(
+    registry: &R,
+    current_exe: &str,
+    exists: E,
+) -> Result<Vec<String>, AppError>
+where
+    R: RegistryAdapter,
+    E: Fn(&str) -> bool,
+{
+    let mut removed = Vec::new();
+    let mut rename = None;
+    for entry in audit_autostart_entries(registry, current_exe, exists)? {
+        if entry.kind != AutostartEntryKind::Current {
+            registry.delete_value(REGISTRY_RUN_KEY, &entry.name)?;
+            removed.push(entry.name);
+        } else if entry.name != APP_NAME {
+            rename = Some(entry.name);
+        }
+    }
+
+    if let Some(name) = rename {
+        // Write the canonical value before deleting the old one so autostart
+        // is never left disabled; the command keeps its arguments
+        if let Some(command) = registry.read_string(REGISTRY_RUN_KEY, &name)? {
+            registry.write_string(REGISTRY_RUN_KEY, APP_NAME, &command)?;
+            registry.delete_value(REGISTRY_RUN_KEY, &name)?;
+            removed.push(name);
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::mock::MockRegistry;
+
+    const CURRENT: &str = "C:\\Users\\me\\AppData\\Local\\QuickConnect\\QuickConnect.exe";
+    const OLD: &str = "C:\\Program Files\\QuickConnect\\QuickConnect.exe";
+    const GONE: &str = "D:\\Tools\\QuickConnect\\QuickConnect.exe";
+
+    fn exists(path: &str) -> bool {
+        path != GONE
+    }
+
+    fn registry_with(entries: &[(&str, &str)]) -> MockRegistry {
+        let registry = MockRegistry::new();
+        for (name, command) in entries {
+            registry
+                .write_string(REGISTRY_RUN_KEY, name, command)
+                .expect("Mock write should succeed");
+        }
+        registry
+    }
+
+    fn kinds(entries: &[AutostartEntry]) -> Vec<(&str, AutostartEntryKind)> {
+        entries.iter().map(|e| (e.name.as_str(), e.kind)).collect()
+    }
+
+    #[test]
+    fn test_command_exe_path() {
+        assert_eq!(command_exe_path(&format!("\"{}\" --minimized", OLD)), OLD);
+        assert_eq!(command_exe_path(&format!("{} --minimized", CURRENT)), CURRENT);
+        assert_eq!(command_exe_path(CURRENT), CURRENT);
+    }
+
+    #[test]
+    fn test_is_quickconnect_command() {
+        assert!(is_quickconnect_command(CURRENT));
+        assert!(is_quickconnect_command("\"C:\\Apps\\quickconnect.EXE\""));
+        assert!(!is_quickconnect_command("C:\\Program Files\\OneDrive\\OneDrive.exe /background"));
+        assert!(!is_quickconnect_command("C:\\Tools\\NotQuickConnect.exe"));
+    }
+
+    #[test]
+    fn test_audit_detects_duplicates_and_stale_entries() {
+        let registry = registry_with(&[
+            ("OneDrive", "C:\\OneDrive\\OneDrive.exe /background"),
+            ("QuickConnect", CURRENT),
+            ("QuickConnect (1)", &format!("\"{}\"", OLD)),
+            ("QuickConnect (2)", GONE),
+        ]);
+
+        let entries = audit_autostart_entries(&registry, CURRENT, exists).expect("Audit should succeed");
+
+        assert_eq!(
+            kinds(&entries),
+            vec![
+                ("QuickConnect", AutostartEntryKind::Current),
+                ("QuickConnect (1)", AutostartEntryKind::Duplicate),
+                ("QuickConnect (2)", AutostartEntryKind::Stale),
+            ]
+        );
+        assert_eq!(entries[1].path, OLD);
+        assert_eq!(entries[2].describe(), format!("QuickConnect (2): {} (stale: file not found)", GONE));
+    }
+
+    #[test]
+    fn test_audit_prefers_standard_name_for_current_exe() {
+        let registry = registry_with(&[("QuickConnect (1)", CURRENT), ("QuickConnect", CURRENT)]);
+
+        let entries = audit_autostart_entries(&registry, CURRENT, exists).expect("Audit should succeed");
+
+        assert_eq!(
+            kinds(&entries),
+            vec![
+                ("QuickConnect (1)", AutostartEntryKind::Duplicate),
+                ("QuickConnect", AutostartEntryKind::Current),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audit_single_entry_is_clean() {
+        let registry = registry_with(&[("QuickConnect", CURRENT)]);
+        let entries = audit_autostart_entries(&registry, &CURRENT.to_lowercase(), exists)
+            .expect("Audit should succeed");
+        assert_eq!(kinds(&entries), vec![("QuickConnect", AutostartEntryKind::Current)]);
+    }
+
+    #[test]
+    fn test_clean_keeps_only_current_entry() {
+        let registry = registry_with(&[
+            ("OneDrive", "C:\\OneDrive\\OneDrive.exe /background"),
+            ("QuickConnect (1)", OLD),
+            ("QuickConnect", CURRENT),
+            ("QuickConnect (2)", GONE),
+        ]);
+
+        let removed = clean_autostart_duplicates(&registry, CURRENT, exists).expect("Clean should succeed");
+
+        assert_eq!(removed, vec!["QuickConnect (1)", "QuickConnect (2)"]);
+        let remaining = registry.list_string_values(REGISTRY_RUN_KEY).expect("List should succeed");
+        assert_eq!(
+            remaining,
+            vec![
+                ("OneDrive".to_string(), "C:\\OneDrive\\OneDrive.exe /background".to_string()),
+                ("QuickConnect".to_string(), CURRENT.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clean_moves_current_entry_to_standard_name() {
+        let command = format!("\"{}\" --minimized", CURRENT);
+        let registry = registry_with(&[("QuickConnect (1)", &command), ("QuickConnect", OLD)]);
+
+        let removed = clean_autostart_duplicates(&registry, CURRENT, exists).expect("Clean should succeed");
+
+        assert_eq!(removed, vec!["QuickConnect", "QuickConnect (1)"]);
+        let remaining = registry.list_string_values(REGISTRY_RUN_KEY).expect("List should succeed");
+        assert_eq!(remaining, vec![("QuickConnect".to_string(), command)]);
+    }
+
+    #[test]
+    fn test_clean_without_current_entry_removes_all_copies() {
+        let registry = registry_with(&[("QuickConnect", OLD)]);
+
+        let removed = clean_autostart_duplicates(&registry, CURRENT, exists).expect("Clean should succeed");
+
+        assert_eq!(removed, vec!["QuickConnect"]);
+        assert!(registry.list_string_values(REGISTRY_RUN_KEY).expect("List should succeed").is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+//! Data directory backup and restore
+//!
+//! Packs the host list, recent connections, settings and the Connections
+//! directory into a single zip, and unpacks one back into a data directory.
+//!
+//! # Why this exists
+//! Moving QuickConnect to a new machine otherwise means finding and copying
+//! several files by hand. Credentials live in Windows Credential Manager and
+//! can't be exported this way, so they are not part of the archive.
+//!
+//! # Why separate
+//! Takes the data directory and archive paths as parameters so a backup and
+//! restore round trip can be unit tested against temporary directories.
+
+use crate::infra::debug_log;
+use crate::AppError;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// Files in the data directory included in a backup (when present)
+pub const BACKUP_FILES: [&str; 6] = [
+    "hosts.csv",
+    "hosts.json",
+    "recent_connections.json",
+    "settings.json",
+    "connection_history.jsonl",
+    "connection_usage.json",
+];
+
+/// Directory of `.rdp` files included in a backup
+pub const BACKUP_CONNECTIONS_DIR: &str = "Connections";
+
+/// Directory in the data directory a restore extracts into before moving the
+/// files into place
+const RESTORE_STAGING_DIR: &str = ".restore-staging";
+
+/// Outcome of restoring a backup
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RestoreReport {
+    /// Archive entries written to the data directory
+    pub restored: Vec<String>,
+    /// Entries that would replace an existing file; nothing is written while
+    /// this is non-empty and overwriting wasn't confirmed
+    pub conflicts: Vec<String>,
+    /// Archive entries that aren't QuickConnect data and were ignored
+    pub skipped: Vec<String>,
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> AppError {
+    AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source,
+    }
+}
+
+fn zip_error(path: &Path, e: zip::result::ZipError) -> AppError {
+    io_error(path, std::io::Error::other(e))
+}
+
+/// Zips the data directory's files into `dest_zip`
+///
+/// Missing files are left out. Only the files directly inside `Connections`
+/// are included.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of files archived
+/// * `Err(AppError)` - A file couldn't be read or the archive written
+///
+/// # Side Effects
+/// - Creates or overwrites `dest_zip`
+pub fn backup_data_dir(data_dir: &Path, dest_zip: &Path) -> Result<usize, AppError> {
+    let mut entries: Vec<(String, PathBuf)> = BACKUP_FILES
+        .iter()
+        .map(|name| (name.to_string(), data_dir.join(name)))
+        .filter(|(_, path)| path.is_file())
+        .collect();
+
+    let connections_dir = data_dir.join(BACKUP_CONNECTIONS_DIR);
+    if connections_dir.is_dir() {
+        let mut rdp_files = Vec::new();
+        for entry in std::fs::read_dir(&connections_dir).map_err(|e| io_error(&connections_dir, e))? {
+            let path = entry.map_err(|e| io_error(&connections_dir, e))?.path();
+            if let (true, Some(name)) = (path.is_file(), path.file_name().and_then(|name| name.to_str())) {
+                rdp_files.push((format!("{}/{}", BACKUP_CONNECTIONS_DIR, name), path.clone()));
+            }
+        }
+        rdp_files.sort();
+        entries.extend(rdp_files);
+    }
+
+    let file = File::create(dest_zip).map_err(|e| io_error(dest_zip, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, path) in &entries {
+        let contents = std::fs::read(path).map_err(|e| io_error(path, e))?;
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| zip_error(dest_zip, e))?;
+        zip.write_all(&contents).map_err(|e| io_error(dest_zip, e))?;
+    }
+    zip.finish().map_err(|e| zip_error(dest_zip, e))?;
+
+    debug_log(
+        "INFO",
+        "BACKUP",
+        &format!("Backed up {} files to {:?}", entries.len(), dest_zip),
+        None,
+    );
+    Ok(entries.len())
+}
+
+/// Returns where an archive entry is restored to, relative to the data
+/// directory, or `None` if it isn't QuickConnect data
+fn restore_target(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if !path.components().all(|component| matches!(component, Component::Normal(_))) {
+        return None;
+    }
+
+    let parts: Vec<&str> = name.split('/').collect();
+    match parts.as_slice() {
+        [file] if BACKUP_FILES.contains(file) => Some(PathBuf::from(file)),
+        [dir, file] if *dir == BACKUP_CONNECTIONS_DIR && !file.is_empty() && !file.contains('\\') => {
+            Some(Path::new(BACKUP_CONNECTIONS_DIR).join(file))
+        }
+        _ => None,
+    }
+}
+
+/// Extracts a backup made by [`backup_data_dir`] into `data_dir`
+///
+/// Unless `overwrite` is set, nothing is written when any file would be
+/// replaced; the report lists those conflicts so the caller can ask before
+/// trying again with `overwrite`. Files in the data directory that aren't in
+/// the archive are left alone.
+///
+/// Every entry is first extracted into a staging directory next to the data,
+/// so a corrupt archive or a full disk leaves the data directory as it was.
+/// Only then are the files moved into place; a rename failing part way
+/// through (rare on one volume) leaves the files moved so far restored.
+///
+/// # Returns
+/// * `Ok(RestoreReport)` - What was restored, or the conflicts that stopped it
+/// * `Err(AppError)` - The archive couldn't be read or a file written
+pub fn restore_data_dir(src_zip: &Path, data_dir: &Path, overwrite: bool) -> Result<RestoreReport, AppError> {
+    let file = File::open(src_zip).map_err(|e| io_error(src_zip, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| zip_error(src_zip, e))?;
+
+    let mut report = RestoreReport::default();
+    let mut targets = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(|e| zip_error(src_zip, e))?;
+        let name = entry.name().to_string();
+        match restore_target(&name).filter(|_| entry.is_file()) {
+            Some(relative) => {
+                if data_dir.join(&relative).exists() {
+                    report.conflicts.push(name.clone());
+                }
+                targets.push((index, name, relative));
+            }
+            None => report.skipped.push(name),
+        }
+    }
+
+    if !report.conflicts.is_empty() && !overwrite {
+        return Ok(report);
+    }
+
+    let staging = data_dir.join(RESTORE_STAGING_DIR);
+    if staging.exists() {
+        // Left behind by an interrupted restore
+        std::fs::remove_dir_all(&staging).map_err(|e| io_error(&staging, e))?;
+    }
+    let staged = extract_targets(&mut archive, src_zip, &staging, &targets);
+    if let Err(e) = staged {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    for (_, name, relative) in targets {
+        let target = data_dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        std::fs::rename(staging.join(&relative), &target).map_err(|e| io_error(&target, e))?;
+        report.restored.push(name);
+    }
+    let _ = std::fs::remove_dir_all(&staging);
+
+    debug_log(
+        "INFO",
+        "BACKUP",
+        &format!(
+            "Restored {} files from {:?} ({} replaced, {} skipped)",
+            report.restored.len(),
+            src_zip,
+            report.conflicts.len(),
+            report.skipped.len()
+        ),
+        None,
+    );
+    Ok(report)
+}
+
+/// Extracts the archive entries to restore into `staging`
+fn extract_targets(
+    archive: &mut zip::ZipArchive<File>,
+    src_zip: &Path,
+    staging: &Path,
+    targets: &[(usize, String, PathBuf)],
+) -> Result<(), AppError> {
+    for (index, _, relative) in targets {
+        let target = staging.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        let mut entry = archive.by_index(*index).map_err(|e| zip_error(src_zip, e))?;
+        let mut out = File::create(&target).map_err(|e| io_error(&target, e))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| io_error(&target, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn populated_data_dir() -> TempDir {
+        let data_dir = TempDir::new().expect("Failed to create temp dir");
+        let root = data_dir.path();
+        std::fs::write(root.join("hosts.csv"), "hostname,description\nweb01.domain.com,Web\n").unwrap();
+        std::fs::write(root.join("recent_connections.json"), r#"{"connections":[]}"#).unwrap();
+        std::fs::write(root.join("settings.json"), r#"{"theme":"dark"}"#).unwrap();
+        let connections = root.join(BACKUP_CONNECTIONS_DIR);
+        std::fs::create_dir(&connections).unwrap();
+        std::fs::write(connections.join("web01.domain.com.rdp"), "full address:s:web01.domain.com\r\n").unwrap();
+        std::fs::write(connections.join("sql01.domain.com.rdp"), "full address:s:sql01.domain.com\r\n").unwrap();
+        // Not QuickConnect data
+        std::fs::write(root.join("quickconnect.log"), "log").unwrap();
+        data_dir
+    }
+
+    const RESTORED_FILES: [&str; 5] = [
+        "hosts.csv",
+        "recent_connections.json",
+        "settings.json",
+        "Connections/web01.domain.com.rdp",
+        "Connections/sql01.domain.com.rdp",
+    ];
+
+    #[test]
+    fn test_backup_then_restore_reproduces_files() {
+        let source = populated_data_dir();
+        let archive_dir = TempDir::new().unwrap();
+        let archive = archive_dir.path().join("backup.zip");
+
+        let archived = backup_data_dir(source.path(), &archive).expect("Backup should succeed");
+        assert_eq!(archived, 5);
+
+        let fresh = TempDir::new().unwrap();
+        let report = restore_data_dir(&archive, fresh.path(), false).expect("Restore should succeed");
+
+        assert!(report.conflicts.is_empty());
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.restored.len(), 5);
+        for file in RESTORED_FILES {
+            assert_eq!(
+                std::fs::read(fresh.path().join(file)).unwrap(),
+                std::fs::read(source.path().join(file)).unwrap(),
+                "{} differs",
+                file
+            );
+        }
+        assert!(!fresh.path().join("quickconnect.log").exists());
+        assert!(!fresh.path().join("hosts.json").exists());
+    }
+
+    #[test]
+    fn test_restore_reports_conflicts_without_overwrite() {
+        let source = populated_data_dir();
+        let archive_dir = TempDir::new().unwrap();
+        let archive = archive_dir.path().join("backup.zip");
+        backup_data_dir(source.path(), &archive).unwrap();
+
+        let target = TempDir::new().unwrap();
+        std::fs::write(target.path().join("settings.json"), "{}").unwrap();
+
+        let report = restore_data_dir(&archive, target.path(), false).expect("Restore should succeed");
+
+        assert_eq!(report.conflicts, vec!["settings.json"]);
+        assert!(report.restored.is_empty());
+        assert_eq!(std::fs::read_to_string(target.path().join("settings.json")).unwrap(), "{}");
+        assert!(!target.path().join("hosts.csv").exists());
+
+        let report = restore_data_dir(&archive, target.path(), true).expect("Restore should succeed");
+
+        assert_eq!(report.restored.len(), 5);
+        assert_eq!(
+            std::fs::read_to_string(target.path().join("settings.json")).unwrap(),
+            r#"{"theme":"dark"}"#
+        );
+    }
+
+    #[test]
+    fn test_backup_of_empty_data_dir() {
+        let data_dir = TempDir::new().unwrap();
+        let archive = data_dir.path().join("backup.zip");
+
+        assert_eq!(backup_data_dir(data_dir.path(), &archive).unwrap(), 0);
+
+        let fresh = TempDir::new().unwrap();
+        let report = restore_data_dir(&archive, fresh.path(), false).unwrap();
+        assert_eq!(report, RestoreReport::default());
+    }
+
+    #[test]
+    fn test_restore_skips_foreign_and_unsafe_entries() {
+        let archive_dir = TempDir::new().unwrap();
+        let archive = archive_dir.path().join("foreign.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for name in ["../settings.json", "Connections/nested/x.rdp", "notes.txt", "hosts.csv"] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(b"data").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let fresh = TempDir::new().unwrap();
+        let report = restore_data_dir(&archive, fresh.path(), false).unwrap();
+
+        assert_eq!(report.restored, vec!["hosts.csv"]);
+        assert_eq!(
+            report.skipped,
+            vec!["../settings.json", "Connections/nested/x.rdp", "notes.txt"]
+        );
+        assert!(!fresh.path().parent().unwrap().join("settings.json").exists());
+        assert!(!fresh.path().join(RESTORE_STAGING_DIR).exists());
+    }
+
+    #[test]
+    fn test_restore_with_corrupt_entry_leaves_data_dir_unchanged() {
+        let archive_dir = TempDir::new().unwrap();
+        let archive = archive_dir.path().join("corrupt.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        // Stored, so the entry data can be found and damaged in the file
+        let options =
+            zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("hosts.csv", options).unwrap();
+        zip.write_all(b"hostname,description\nrestored.domain.com,Restored\n").unwrap();
+        zip.start_file("settings.json", options).unwrap();
+        zip.write_all(br#"{"theme":"light"}"#).unwrap();
+        zip.finish().unwrap();
+
+        // Damage the second entry's data so only it fails its checksum
+        let mut bytes = std::fs::read(&archive).unwrap();
+        let data_at = bytes
+            .windows(b"light".len())
+            .position(|window| window == b"light")
+            .unwrap();
+        bytes[data_at] = b'n';
+        std::fs::write(&archive, bytes).unwrap();
+
+        let data_dir = populated_data_dir();
+        let before = std::fs::read(data_dir.path().join("hosts.csv")).unwrap();
+
+        assert!(restore_data_dir(&archive, data_dir.path(), true).is_err());
+
+        assert_eq!(std::fs::read(data_dir.path().join("hosts.csv")).unwrap(), before);
+        assert_eq!(
+            std::fs::read_to_string(data_dir.path().join("settings.json")).unwrap(),
+            r#"{"theme":"dark"}"#
+        );
+        assert!(!data_dir.path().join(RESTORE_STAGING_DIR).exists());
+    }
+
+    #[test]
+    fn test_restore_target_accepts_only_known_paths() {
+        assert_eq!(restore_target("hosts.csv"), Some(PathBuf::from("hosts.csv")));
+        assert_eq!(
+            restore_target("Connections/web01.rdp"),
+            Some(Path::new("Connections").join("web01.rdp"))
+        );
+        assert_eq!(restore_target("/hosts.csv"), None);
+        assert_eq!(restore_target("Connections/"), None);
+        assert_eq!(restore_target("credentials.json"), None);
+    }
+}
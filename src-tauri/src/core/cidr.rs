@@ -0,0 +1,177 @@
+//! CIDR range expansion
+//!
+//! Turns an IPv4 range such as `10.0.5.0/28` into one host entry per usable
+//! address.
+//!
+//! # Why this exists
+//! Provisioning a subnet means adding every address in it; typing them in one
+//! by one is slow and error-prone.
+//!
+//! # Why separate
+//! Parsing and enumeration are pure functions, so prefix limits and the
+//! network/broadcast exclusions can be unit tested.
+
+use crate::{AppError, Host};
+use std::net::Ipv4Addr;
+
+/// Smallest prefix length accepted (a /24 expands to 254 hosts)
+pub const MIN_CIDR_PREFIX: u8 = 24;
+
+/// Token in a description template replaced by each host's address
+pub const IP_TOKEN: &str = "{ip}";
+
+fn invalid(cidr: &str, reason: String) -> AppError {
+    AppError::InvalidHostname {
+        hostname: cidr.to_string(),
+        reason,
+    }
+}
+
+/// Parses `a.b.c.d/len` into the address and prefix length
+fn parse_cidr(cidr: &str) -> Result<(Ipv4Addr, u8), AppError> {
+    let (address, prefix) = cidr
+        .split_once('/')
+        .ok_or_else(|| invalid(cidr, "Expected a range such as 10.0.5.0/28".to_string()))?;
+
+    let address: Ipv4Addr = address.trim().parse().map_err(|_| {
+        invalid(cidr, format!("'{}' is not an IPv4 address", address.trim()))
+    })?;
+    let prefix: u8 = prefix
+        .trim()
+        .parse()
+        .ok()
+        .filter(|prefix| *prefix <= 32)
+        .ok_or_else(|| invalid(cidr, format!("'{}' is not a prefix length between 0 and 32", prefix.trim())))?;
+
+    Ok((address, prefix))
+}
+
+/// Lists the usable addresses in an IPv4 CIDR range
+///
+/// The network and broadcast addresses are excluded, except for /31
+/// (point-to-point, both addresses usable) and /32 (a single host). Host bits
+/// set in the address are ignored, so `10.0.5.7/30` is `10.0.5.4/30`.
+///
+/// # Returns
+/// * `Ok(Vec<Ipv4Addr>)` - Addresses in ascending order
+/// * `Err(AppError::InvalidHostname)` - Malformed, or larger than /`MIN_CIDR_PREFIX`
+pub fn expand_cidr(cidr: &str) -> Result<Vec<Ipv4Addr>, AppError> {
+    let cidr = cidr.trim();
+    let (address, prefix) = parse_cidr(cidr)?;
+    if prefix < MIN_CIDR_PREFIX {
+        return Err(invalid(
+            cidr,
+            format!(
+                "/{} is too large a range; the largest allowed is /{}",
+                prefix, MIN_CIDR_PREFIX
+            ),
+        ));
+    }
+
+    let mask = u32::MAX << (32 - u32::from(prefix));
+    let network = u32::from(address) & mask;
+    let broadcast = network | !mask;
+
+    let (first, last) = if prefix >= 31 {
+        (network, broadcast)
+    } else {
+        (network + 1, broadcast - 1)
+    };
+    Ok((first..=last).map(Ipv4Addr::from).collect())
+}
+
+/// Builds a host entry for each usable address in a CIDR range
+///
+/// # Arguments
+/// * `cidr` - IPv4 range, e.g. `10.0.5.0/28`
+/// * `description_template` - Description for each host, with `{ip}` replaced
+///   by its address (empty when omitted)
+pub fn hosts_from_cidr(cidr: &str, description_template: Option<&str>) -> Result<Vec<Host>, AppError> {
+    let template = description_template.unwrap_or("");
+
+    Ok(expand_cidr(cidr)?
+        .into_iter()
+        .map(|ip| {
+            let ip = ip.to_string();
+            Host {
+                description: template.replace(IP_TOKEN, &ip),
+                hostname: ip,
+                ..Default::default()
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addresses(cidr: &str) -> Vec<String> {
+        expand_cidr(cidr)
+            .expect("Range should expand")
+            .into_iter()
+            .map(|ip| ip.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_cidr_slash_30() {
+        assert_eq!(addresses("10.0.5.0/30"), vec!["10.0.5.1", "10.0.5.2"]);
+    }
+
+    #[test]
+    fn test_expand_cidr_slash_28() {
+        let expanded = addresses("10.0.5.0/28");
+        assert_eq!(expanded.len(), 14);
+        assert_eq!(expanded.first().unwrap(), "10.0.5.1");
+        assert_eq!(expanded.last().unwrap(), "10.0.5.14");
+    }
+
+    #[test]
+    fn test_expand_cidr_ignores_host_bits() {
+        assert_eq!(addresses(" 10.0.5.7/30 "), vec!["10.0.5.5", "10.0.5.6"]);
+    }
+
+    #[test]
+    fn test_expand_cidr_point_to_point_and_single_host() {
+        assert_eq!(addresses("10.0.5.4/31"), vec!["10.0.5.4", "10.0.5.5"]);
+        assert_eq!(addresses("10.0.5.9/32"), vec!["10.0.5.9"]);
+    }
+
+    #[test]
+    fn test_expand_cidr_slash_24_is_allowed() {
+        assert_eq!(expand_cidr("192.168.1.0/24").unwrap().len(), 254);
+    }
+
+    #[test]
+    fn test_expand_cidr_rejects_oversized_prefix() {
+        let err = expand_cidr("10.0.0.0/23").expect_err("/23 should be rejected");
+        assert!(err.to_string().contains("/23 is too large"));
+
+        assert!(expand_cidr("10.0.0.0/8").is_err());
+        assert!(expand_cidr("0.0.0.0/0").is_err());
+    }
+
+    #[test]
+    fn test_expand_cidr_rejects_malformed_input() {
+        for cidr in ["10.0.5.0", "10.0.5/30", "10.0.5.0/33", "10.0.5.0/abc", "server01/30", "fe80::/120", ""] {
+            assert!(expand_cidr(cidr).is_err(), "input: {:?}", cidr);
+        }
+    }
+
+    #[test]
+    fn test_hosts_from_cidr_applies_template() {
+        let hosts = hosts_from_cidr("10.0.5.0/30", Some("Build agent {ip}")).unwrap();
+
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[0].hostname, "10.0.5.1");
+        assert_eq!(hosts[0].description, "Build agent 10.0.5.1");
+        assert_eq!(hosts[1].description, "Build agent 10.0.5.2");
+    }
+
+    #[test]
+    fn test_hosts_from_cidr_without_template() {
+        let hosts = hosts_from_cidr("10.0.5.0/30", None).unwrap();
+        assert!(hosts.iter().all(|host| host.description.is_empty()));
+    }
+}
@@ -0,0 +1,93 @@
+//! Wall-clock time source
+//!
+//! Timestamps on hosts and recent connections, and the freshness of cached
+//! status results, all depend on "now".
+//!
+//! # Why separate
+//! Taking the time through a `Clock` lets tests pin it with `MockClock` and
+//! assert exact timestamps and expiry boundaries instead of ranges.
+
+use chrono::{DateTime, Local};
+
+/// Source of the current local time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// A clock that only moves when told to
+#[cfg(test)]
+#[derive(Debug)]
+pub struct MockClock {
+    now: std::sync::Mutex<DateTime<Local>>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new(now: DateTime<Local>) -> Self {
+        Self {
+            now: std::sync::Mutex::new(now),
+        }
+    }
+
+    /// A clock fixed at a local wall time, e.g. `at("2026-03-15 12:00:00")`
+    pub fn at(local: &str) -> Self {
+        let naive = chrono::NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M:%S")
+            .expect("MockClock::at expects YYYY-MM-DD HH:MM:SS");
+        let now = naive
+            .and_local_timezone(Local)
+            .earliest()
+            .expect("MockClock::at given a time skipped by DST");
+        Self::new(now)
+    }
+
+    pub fn set(&self, now: DateTime<Local>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    pub fn advance(&self, by: chrono::Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Local> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_set_and_advance() {
+        let clock = MockClock::at("2026-03-15 12:00:00");
+        assert_eq!(clock.now().format("%d/%m/%Y %H:%M:%S").to_string(), "15/03/2026 12:00:00");
+
+        clock.advance(chrono::Duration::seconds(90));
+        assert_eq!(clock.now().format("%d/%m/%Y %H:%M:%S").to_string(), "15/03/2026 12:01:30");
+
+        let earlier = MockClock::at("2025-12-13 14:30:00").now();
+        clock.set(earlier);
+        assert_eq!(clock.now(), earlier);
+    }
+
+    #[test]
+    fn test_system_clock_is_current() {
+        let before = Local::now();
+        let now = SystemClock.now();
+        assert!(now >= before && now <= Local::now());
+    }
+}
@@ -0,0 +1,507 @@
+//! Connections directory cleanup
+//!
+//! Keeps the Connections directory within the configured file count and byte
+//! size by deleting the least-recently-modified `.rdp` files, and removes a
+//! single host's file on request.
+//!
+//! # Why this exists
+//! A `.rdp` file is written for every host that is connected to, and files for
+//! removed hosts are never cleaned up. On long-lived installs with thousands
+//! of hosts they add up.
+//!
+//! # Why separate
+//! The selection of files to delete is a pure function over file metadata, so
+//! the LRU ordering and limits can be unit tested without touching the disk.
+
+use crate::core::rdp_file_names::{
+    forget_rdp_file_in, forget_rdp_file_stems_in, load_rdp_file_map, recorded_rdp_file_stem_in,
+};
+use crate::core::settings::AppSettings;
+use crate::infra::debug_log;
+use crate::AppError;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// How often the background cleanup re-checks the Connections directory
+pub const CONNECTIONS_CLEANUP_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(60 * 60);
+
+/// A `.rdp` file in the Connections directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RdpFileInfo {
+    pub path: PathBuf,
+    /// File name without the `.rdp` extension
+    pub hostname: String,
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+/// Chooses which files to delete to get within the limits
+///
+/// Files are deleted oldest first (by modification time) until there are at
+/// most `max_files` files and at most `max_bytes` bytes in total. A limit of
+/// 0 means unlimited. Files for `protected` hostnames (compared
+/// case-insensitively, e.g. recent connections) are never chosen, but still
+/// count towards the limits.
+///
+/// # Returns
+/// Paths to delete, oldest first
+pub fn select_files_to_remove(
+    mut files: Vec<RdpFileInfo>,
+    protected: &HashSet<String>,
+    max_files: usize,
+    max_bytes: u64,
+) -> Vec<PathBuf> {
+    let mut count = files.len();
+    let mut total: u64 = files.iter().map(|file| file.size).sum();
+    let over_limit = |count: usize, total: u64| {
+        (max_files > 0 && count > max_files) || (max_bytes > 0 && total > max_bytes)
+    };
+
+    files.sort_by_key(|file| file.modified);
+
+    let mut remove = Vec::new();
+    for file in files {
+        if !over_limit(count, total) {
+            break;
+        }
+        if protected.contains(&file.hostname.to_lowercase()) {
+            continue;
+        }
+        count -= 1;
+        total = total.saturating_sub(file.size);
+        remove.push(file.path);
+    }
+    remove
+}
+
+/// Lists the `.rdp` files in a directory (empty if the directory doesn't exist)
+pub fn list_rdp_files(dir: &Path) -> Result<Vec<RdpFileInfo>, AppError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let io_error = |e: std::io::Error| AppError::IoError {
+        path: dir.to_string_lossy().to_string(),
+        source: e,
+    };
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        let is_rdp = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("rdp"));
+        if !is_rdp || !path.is_file() {
+            continue;
+        }
+
+        let metadata = std::fs::metadata(&path).map_err(io_error)?;
+        let hostname = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        files.push(RdpFileInfo {
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            size: metadata.len(),
+            hostname,
+            path,
+        });
+    }
+    Ok(files)
+}
+
+/// Returns the cleanup limits (`max_files`, `max_bytes`) to apply, or `None`
+/// when the Connections directory must not be cleaned up
+///
+/// Nothing is cleaned up when no limit is set, or when `preserve_rdp_edits`
+/// is on: the files may then hold hand edits that can't be regenerated.
+pub fn cleanup_limits(settings: &AppSettings) -> Option<(usize, u64)> {
+    if settings.preserve_rdp_edits
+        || (settings.connections_max_files == 0 && settings.connections_max_bytes == 0)
+    {
+        return None;
+    }
+    Some((settings.connections_max_files as usize, settings.connections_max_bytes))
+}
+
+/// Deletes the oldest `.rdp` files until the directory is within the limits
+///
+/// # Arguments
+/// * `dir` - The Connections directory
+/// * `protected_hostnames` - Hosts whose files must be kept (recent connections)
+/// * `max_files` / `max_bytes` - Limits (0 = unlimited)
+///
+/// # Returns
+/// * `Ok(usize)` - Number of files deleted
+/// * `Err(AppError)` - The directory could not be listed
+///
+/// # Side Effects
+/// - Deletes files; a file that can't be deleted is logged and skipped
+/// - Removes the deleted files from `rdp_files.json`
+pub fn cleanup_connections_dir(
+    dir: &Path,
+    protected_hostnames: &[String],
+    max_files: usize,
+    max_bytes: u64,
+) -> Result<usize, AppError> {
+    if max_files == 0 && max_bytes == 0 {
+        return Ok(0);
+    }
+
+    let file_map = load_rdp_file_map(dir).unwrap_or_default();
+    let protected: HashSet<String> = protected_hostnames
+        .iter()
+        .map(|hostname| file_map.stem_for(hostname).to_lowercase())
+        .collect();
+    let files = list_rdp_files(dir)?;
+
+    let mut removed_stems = Vec::new();
+    for path in select_files_to_remove(files, &protected, max_files, max_bytes) {
+        match std::fs::remove_file(&path) {
+            Ok(()) => removed_stems.extend(path.file_stem().map(|stem| stem.to_string_lossy().to_string())),
+            Err(e) => debug_log(
+                "WARN",
+                "RDP_LAUNCH",
+                &format!("Failed to delete old RDP file {:?}", path),
+                Some(&e.to_string()),
+            ),
+        }
+    }
+
+    let removed = removed_stems.len();
+    if removed > 0 {
+        // Deleted hosts' names would otherwise stay recorded forever
+        if let Err(e) = forget_rdp_file_stems_in(dir, &removed_stems) {
+            debug_log(
+                "WARN",
+                "RDP_LAUNCH",
+                "Failed to update the RDP file name map",
+                Some(&e.to_string()),
+            );
+        }
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("Deleted {} old RDP file(s) from {:?}", removed, dir),
+            None,
+        );
+    }
+    Ok(removed)
+}
+
+/// Deletes every `.rdp` file in the Connections directory
+///
+/// Used while `high_security_mode` is on: launches then only write temporary
+/// files, so files saved before the mode was turned on would otherwise keep
+/// usernames and connection details on disk indefinitely. The file name
+/// mapping is kept so hosts get the same names if the mode is turned off.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of files deleted
+/// * `Err(AppError)` - The directory could not be listed
+///
+/// # Side Effects
+/// - Deletes files; a file that can't be deleted is logged and skipped
+pub fn purge_rdp_files_in(dir: &Path) -> Result<usize, AppError> {
+    let mut removed = 0;
+    for file in list_rdp_files(dir)? {
+        match std::fs::remove_file(&file.path) {
+            Ok(()) => removed += 1,
+            Err(e) => debug_log(
+                "WARN",
+                "RDP_LAUNCH",
+                &format!("Failed to delete saved RDP file {:?}", file.path),
+                Some(&e.to_string()),
+            ),
+        }
+    }
+
+    if removed > 0 {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("Deleted {} saved RDP file(s) from {:?} (high-security mode)", removed, dir),
+            None,
+        );
+    }
+    Ok(removed)
+}
+
+/// Deletes the generated `.rdp` file for one host
+///
+/// The next launch writes a fresh file with the current options; saved
+/// credentials are not touched. A missing file is not an error. The host's
+/// entry in `rdp_files.json` is removed once the file is gone.
+///
+/// # Arguments
+/// * `dir` - The Connections directory
+/// * `hostname` - Host entry; mapped to its recorded file name (see `rdp_file_names`)
+///
+/// # Returns
+/// * `Ok(true)` - The file was deleted
+/// * `Ok(false)` - There was no file to delete
+/// * `Err(AppError)` - The hostname isn't a plain file name, or the delete failed
+pub fn clear_rdp_file_in(dir: &Path, hostname: &str) -> Result<bool, AppError> {
+    let stem = recorded_rdp_file_stem_in(dir, hostname);
+    if stem.is_empty() || stem.contains(['/', '\\']) || stem == "." || stem == ".." {
+        return Err(AppError::InvalidHostname {
+            hostname: hostname.to_string(),
+            reason: "Not a valid RDP file name".to_string(),
+        });
+    }
+
+    let path = dir.join(format!("{}.rdp", stem));
+    let deleted = match std::fs::remove_file(&path) {
+        Ok(()) => {
+            debug_log(
+                "INFO",
+                "RDP_LAUNCH",
+                &format!("Deleted RDP file {:?}", path),
+                None,
+            );
+            true
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(e) => {
+            return Err(AppError::IoError {
+                path: path.to_string_lossy().to_string(),
+                source: e,
+            })
+        }
+    };
+
+    forget_rdp_file_in(dir, hostname)?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn file(hostname: &str, age_secs: u64, size: u64) -> RdpFileInfo {
+        RdpFileInfo {
+            path: PathBuf::from(format!("{}.rdp", hostname)),
+            hostname: hostname.to_string(),
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 - age_secs),
+            size,
+        }
+    }
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(|name| PathBuf::from(format!("{}.rdp", name))).collect()
+    }
+
+    fn sample_files() -> Vec<RdpFileInfo> {
+        vec![
+            file("new", 10, 100),
+            file("oldest", 400, 100),
+            file("middle", 200, 100),
+            file("old", 300, 100),
+        ]
+    }
+
+    #[test]
+    fn test_select_files_to_remove_oldest_first_by_count() {
+        let remove = select_files_to_remove(sample_files(), &HashSet::new(), 2, 0);
+        assert_eq!(remove, paths(&["oldest", "old"]));
+    }
+
+    #[test]
+    fn test_select_files_to_remove_within_limit() {
+        assert!(select_files_to_remove(sample_files(), &HashSet::new(), 4, 0).is_empty());
+        assert!(select_files_to_remove(sample_files(), &HashSet::new(), 10, 0).is_empty());
+    }
+
+    #[test]
+    fn test_select_files_to_remove_unlimited() {
+        assert!(select_files_to_remove(sample_files(), &HashSet::new(), 0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_select_files_to_remove_skips_protected() {
+        let protected = HashSet::from(["oldest".to_string()]);
+        let remove = select_files_to_remove(sample_files(), &protected, 2, 0);
+        assert_eq!(remove, paths(&["old", "middle"]));
+    }
+
+    #[test]
+    fn test_select_files_to_remove_by_size() {
+        let files = vec![
+            file("big", 300, 500),
+            file("small", 400, 50),
+            file("recent", 10, 100),
+        ];
+        // 650 bytes total; deleting oldest first until at most 200
+        let remove = select_files_to_remove(files, &HashSet::new(), 0, 200);
+        assert_eq!(remove, paths(&["small", "big"]));
+    }
+
+    #[test]
+    fn test_select_files_to_remove_all_protected() {
+        let protected: HashSet<String> = ["new", "oldest", "middle", "old"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        assert!(select_files_to_remove(sample_files(), &protected, 1, 0).is_empty());
+    }
+
+    #[test]
+    fn test_cleanup_limits() {
+        let mut settings = AppSettings::default();
+        assert_eq!(cleanup_limits(&settings), None);
+
+        settings.connections_max_files = 100;
+        assert_eq!(cleanup_limits(&settings), Some((100, 0)));
+
+        // Hand-edited files are never deleted
+        settings.preserve_rdp_edits = true;
+        assert_eq!(cleanup_limits(&settings), None);
+    }
+
+    #[test]
+    fn test_cleanup_connections_dir_deletes_oldest() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        for name in ["a", "b", "c"] {
+            std::fs::write(temp_dir.path().join(format!("{}.rdp", name)), "full address:s:x")
+                .expect("Failed to write file");
+        }
+        std::fs::write(temp_dir.path().join("notes.txt"), "keep").expect("Failed to write file");
+
+        let removed = cleanup_connections_dir(temp_dir.path(), &["A".to_string()], 1, 0)
+            .expect("Cleanup should succeed");
+
+        assert_eq!(removed, 2);
+        assert!(temp_dir.path().join("a.rdp").exists());
+        assert!(temp_dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_cleanup_connections_dir_prunes_file_name_map() {
+        use crate::core::rdp_file_names::{load_rdp_file_map, rdp_file_stem_in, RdpFileMap};
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        for (index, name) in ["a", "b", "c"].iter().enumerate() {
+            let stem = rdp_file_stem_in(temp_dir.path(), name).expect("Name should be recorded");
+            let path = temp_dir.path().join(format!("{}.rdp", stem));
+            std::fs::write(&path, "full address:s:x").expect("Failed to write file");
+            // "a" is the oldest, "c" the newest
+            let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + index as u64);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(modified))
+                .expect("Failed to set modification time");
+        }
+
+        let removed = cleanup_connections_dir(temp_dir.path(), &[], 1, 0).expect("Cleanup should succeed");
+
+        assert_eq!(removed, 2);
+        let map = load_rdp_file_map(temp_dir.path()).expect("Map should load");
+        let mut expected = RdpFileMap::default();
+        expected.assign("c", |_| None);
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn test_cleanup_connections_dir_missing_dir() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let missing = temp_dir.path().join("Connections");
+        assert_eq!(cleanup_connections_dir(&missing, &[], 1, 0).expect("Cleanup should succeed"), 0);
+    }
+
+    #[test]
+    fn test_purge_rdp_files_in_deletes_only_rdp_files() {
+        use crate::core::rdp_file_names::{rdp_file_stem_in, RDP_FILE_MAP_NAME};
+
+        let temp_dir = TempDir::new().unwrap();
+        let stem = rdp_file_stem_in(temp_dir.path(), "web01.domain.com").unwrap();
+        for name in [stem.as_str(), "sql01.domain.com"] {
+            std::fs::write(temp_dir.path().join(format!("{}.rdp", name)), "full address:s:x").unwrap();
+        }
+        std::fs::write(temp_dir.path().join("notes.txt"), "keep").unwrap();
+
+        assert_eq!(purge_rdp_files_in(temp_dir.path()).unwrap(), 2);
+
+        assert!(list_rdp_files(temp_dir.path()).unwrap().is_empty());
+        assert!(temp_dir.path().join("notes.txt").exists());
+        assert!(temp_dir.path().join(RDP_FILE_MAP_NAME).exists());
+        // A missing directory has nothing to purge
+        assert_eq!(purge_rdp_files_in(&temp_dir.path().join("missing")).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_clear_rdp_file_in_removes_only_that_host() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["web01.domain.com", "sql01.domain.com", "app01_3390"] {
+            std::fs::write(temp_dir.path().join(format!("{}.rdp", name)), "full address:s:x").unwrap();
+        }
+
+        assert!(clear_rdp_file_in(temp_dir.path(), "web01.domain.com").unwrap());
+        assert!(clear_rdp_file_in(temp_dir.path(), "app01:3390").unwrap());
+
+        assert!(!temp_dir.path().join("web01.domain.com.rdp").exists());
+        assert!(!temp_dir.path().join("app01_3390.rdp").exists());
+        assert!(temp_dir.path().join("sql01.domain.com.rdp").exists());
+    }
+
+    #[test]
+    fn test_clear_rdp_file_in_uses_recorded_file_name() {
+        use crate::core::rdp_file_names::rdp_file_stem_in;
+
+        let temp_dir = TempDir::new().unwrap();
+        let first = rdp_file_stem_in(temp_dir.path(), "app01_3390").unwrap();
+        let second = rdp_file_stem_in(temp_dir.path(), "app01:3390").unwrap();
+        for stem in [&first, &second] {
+            std::fs::write(temp_dir.path().join(format!("{}.rdp", stem)), "full address:s:x").unwrap();
+        }
+
+        assert!(clear_rdp_file_in(temp_dir.path(), "app01:3390").unwrap());
+
+        assert!(!temp_dir.path().join(format!("{}.rdp", second)).exists());
+        assert!(temp_dir.path().join(format!("{}.rdp", first)).exists());
+    }
+
+    #[test]
+    fn test_clear_rdp_file_in_prunes_file_name_map() {
+        use crate::core::rdp_file_names::{load_rdp_file_map, rdp_file_stem_in, RdpFileMap};
+
+        let temp_dir = TempDir::new().unwrap();
+        let stem = rdp_file_stem_in(temp_dir.path(), "web01.domain.com").unwrap();
+        std::fs::write(temp_dir.path().join(format!("{}.rdp", stem)), "full address:s:x").unwrap();
+        // A recorded host whose file is already gone is pruned too
+        rdp_file_stem_in(temp_dir.path(), "sql01.domain.com").unwrap();
+
+        assert!(clear_rdp_file_in(temp_dir.path(), "web01.domain.com").unwrap());
+        assert!(!clear_rdp_file_in(temp_dir.path(), "sql01.domain.com").unwrap());
+
+        assert_eq!(load_rdp_file_map(temp_dir.path()).unwrap(), RdpFileMap::default());
+    }
+
+    #[test]
+    fn test_clear_rdp_file_in_missing_file_is_ok() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(!clear_rdp_file_in(temp_dir.path(), "web01.domain.com").unwrap());
+        assert!(!clear_rdp_file_in(temp_dir.path(), "web01.domain.com").unwrap());
+    }
+
+    #[test]
+    fn test_clear_rdp_file_in_rejects_paths() {
+        let temp_dir = TempDir::new().unwrap();
+
+        for hostname in ["", "..", "../settings", "nested\\web01"] {
+            assert!(
+                matches!(
+                    clear_rdp_file_in(temp_dir.path(), hostname),
+                    Err(AppError::InvalidHostname { .. })
+                ),
+                "{:?} should be rejected",
+                hostname
+            );
+        }
+    }
+}
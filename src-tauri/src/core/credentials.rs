@@ -0,0 +1,1229 @@
+//! Credential auditing
+//!
+//! Inspects the per-host `TERMSRV/*` credentials stored in Windows Credential
+//! Manager, tracks how old the global credentials are, and checks in bulk that
+//! stored credentials still authenticate.
+//!
+//! # Why this exists
+//! Admins want to see which hosts have stored RDP credentials, and spot ones
+//! left behind after a host was removed, without launching anything. Domain
+//! passwords rotate, and stale stored credentials cause lockouts.
+//!
+//! # Why separate
+//! Takes the credential manager (or metadata file path, or the per-host check)
+//! as a parameter so the listing, cross-referencing, age and aggregation logic
+//! can be unit tested.
+
+use crate::adapters::{CredentialManager, CredentialType};
+use crate::{AppError, Host, StoredCredentials};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Target name prefix used for per-host RDP credentials
+pub const TERMSRV_PREFIX: &str = "TERMSRV/";
+
+/// Target name prefix used for credentials saved for an RD Gateway
+pub const GATEWAY_CREDENTIAL_PREFIX: &str = "TERMSRV/GATEWAY/";
+
+/// A stored per-host credential, cross-referenced against hosts.csv
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CredentialTarget {
+    pub hostname: String,
+    /// `false` when no host in hosts.csv matches (an orphaned credential)
+    pub has_host: bool,
+}
+
+/// Lists the hostnames that have stored `TERMSRV/*` credentials
+///
+/// Includes both QuickConnect's generic credentials and the domain copies used
+/// for RDP single sign-on, so each host appears once. Gateway credentials
+/// (`TERMSRV/GATEWAY/*`) are not per-host and are left out.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Bare hostnames, sorted case-insensitively
+/// * `Err(AppError)` - Credential enumeration failed
+pub fn list_credential_targets<M: CredentialManager>(manager: &M) -> Result<Vec<String>, AppError> {
+    let mut hostnames = Vec::new();
+    for cred_type in [CredentialType::Generic, CredentialType::DomainPassword] {
+        for target in manager.list_with_prefix_typed(TERMSRV_PREFIX, cred_type)? {
+            if target.starts_with(GATEWAY_CREDENTIAL_PREFIX) {
+                continue;
+            }
+            if let Some(hostname) = target.strip_prefix(TERMSRV_PREFIX) {
+                hostnames.push(hostname.to_string());
+            }
+        }
+    }
+
+    hostnames.sort_by_key(|hostname| hostname.to_lowercase());
+    hostnames.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    Ok(hostnames)
+}
+
+/// Flags credential hostnames that have no matching host (case-insensitive)
+pub fn flag_orphaned_targets(hostnames: Vec<String>, hosts: &[Host]) -> Vec<CredentialTarget> {
+    hostnames
+        .into_iter()
+        .map(|hostname| {
+            let has_host = hosts
+                .iter()
+                .any(|host| host.hostname.eq_ignore_ascii_case(&hostname));
+            CredentialTarget { hostname, has_host }
+        })
+        .collect()
+}
+
+/// Returns the `TERMSRV/{hostname}` target holding a host's credentials
+pub fn host_credential_target(hostname: &str) -> String {
+    format!("{}{}", TERMSRV_PREFIX, hostname)
+}
+
+/// `TERMSRV/{hostname}` targets converted by `migrate_credentials_to_domain_type`
+///
+/// A domain copy alone doesn't mean a host was migrated: launches write one
+/// for whatever credentials they used, including the global ones. Only the
+/// targets recorded here are read and saved as the domain type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MigratedCredentials {
+    /// Lowercased, since Credential Manager targets are case-insensitive
+    targets: BTreeSet<String>,
+}
+
+impl MigratedCredentials {
+    /// Whether a target was migrated
+    pub fn contains(&self, target: &str) -> bool {
+        self.targets.contains(&target.to_lowercase())
+    }
+
+    /// Records a target as migrated; returns false if it already was
+    pub fn insert(&mut self, target: &str) -> bool {
+        self.targets.insert(target.to_lowercase())
+    }
+
+    /// Forgets a target; returns false if it wasn't recorded
+    pub fn remove(&mut self, target: &str) -> bool {
+        self.targets.remove(&target.to_lowercase())
+    }
+}
+
+/// Loads the migrated targets from the given file (empty if it doesn't exist)
+pub fn load_migrated_credentials_from(path: &Path) -> Result<MigratedCredentials, AppError> {
+    if !path.exists() {
+        return Ok(MigratedCredentials::default());
+    }
+
+    let json = std::fs::read_to_string(path).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+
+    serde_json::from_str(&json).map_err(|e| AppError::JsonError {
+        context: format!("Failed to parse migrated credentials {:?}", path),
+        source: e,
+    })
+}
+
+/// Saves the migrated targets to the given file
+pub fn save_migrated_credentials_to(path: &Path, migrated: &MigratedCredentials) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(migrated).map_err(|e| AppError::JsonError {
+        context: "Failed to serialize migrated credentials".to_string(),
+        source: e,
+    })?;
+
+    std::fs::write(path, json).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })
+}
+
+/// Reads a host's stored `TERMSRV/{hostname}` credentials
+///
+/// Hosts recorded in `migrated_path` are read from their domain copy, since
+/// `migrate_credentials_to_domain_type` left nothing else. Every other host
+/// is read from its generic credential; a domain copy written by a launch is
+/// ignored (Windows doesn't return its password, and it may hold the global
+/// credentials).
+///
+/// # Returns
+/// * `Ok(Some(StoredCredentials))` - The host has stored credentials
+/// * `Ok(None)` - The host has no stored credentials
+/// * `Err(AppError)` - Reading a credential or the migration record failed
+pub fn read_host_credential<M: CredentialManager>(
+    manager: &M,
+    migrated_path: &Path,
+    hostname: &str,
+) -> Result<Option<StoredCredentials>, AppError> {
+    let target = host_credential_target(hostname);
+    let cred_type = if load_migrated_credentials_from(migrated_path)?.contains(&target) {
+        CredentialType::DomainPassword
+    } else {
+        CredentialType::Generic
+    };
+
+    Ok(manager
+        .read_typed(&target, cred_type)?
+        .map(|(username, password)| StoredCredentials { username, password }))
+}
+
+/// Saves a host's `TERMSRV/{hostname}` credentials
+///
+/// A host recorded in `migrated_path` stays migrated: its domain copy is
+/// overwritten. Otherwise the generic credential is saved and any stale
+/// domain copy dropped, so the next launch re-saves it for mstsc.
+pub fn save_host_credential<M: CredentialManager>(
+    manager: &M,
+    migrated_path: &Path,
+    hostname: &str,
+    username: &str,
+    password: &str,
+) -> Result<(), AppError> {
+    let target = host_credential_target(hostname);
+    if load_migrated_credentials_from(migrated_path)?.contains(&target) {
+        return manager.save_typed(&target, username, password, CredentialType::DomainPassword);
+    }
+
+    manager.save(&target, username, password)?;
+    if let Ok(Some(_)) = manager.read_typed(&target, CredentialType::DomainPassword) {
+        let _ = manager.delete_typed(&target, CredentialType::DomainPassword);
+    }
+    Ok(())
+}
+
+/// Deletes a host's stored `TERMSRV/{hostname}` credentials
+///
+/// Removes both QuickConnect's generic credential and the domain copy used for
+/// RDP single sign-on, so the next launch saves fresh ones, and forgets that
+/// the host was migrated. Credentials that don't exist are skipped, making
+/// this safe to call repeatedly.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of credentials deleted (0 if none were stored)
+/// * `Err(AppError)` - Reading or deleting a credential, or updating the migration record, failed
+pub fn clear_host_credential<M: CredentialManager>(
+    manager: &M,
+    migrated_path: &Path,
+    hostname: &str,
+) -> Result<usize, AppError> {
+    let target = host_credential_target(hostname);
+    let mut deleted = 0;
+    for cred_type in [CredentialType::Generic, CredentialType::DomainPassword] {
+        if manager.read_typed(&target, cred_type)?.is_some() {
+            manager.delete_typed(&target, cred_type)?;
+            deleted += 1;
+        }
+    }
+
+    let mut migrated = load_migrated_credentials_from(migrated_path)?;
+    if migrated.remove(&target) {
+        save_migrated_credentials_to(migrated_path, &migrated)?;
+    }
+    Ok(deleted)
+}
+
+/// Converts generic `TERMSRV/{hostname}` credentials to the domain type
+///
+/// Older versions stored per-host credentials as generic credentials, which
+/// mstsc can't use for single sign-on. Each one is re-saved as a domain
+/// password credential (replacing any domain copy, since the generic entry is
+/// the one the user saved) and the generic entry is deleted. Each target is
+/// recorded in `migrated_path` before its generic entry goes, so later reads
+/// and saves know to use the domain type. Gateway credentials are left alone.
+/// Running it again finds nothing to migrate.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of credentials migrated
+/// * `Err(AppError)` - Enumerating, reading, saving or deleting failed; credentials
+///   migrated before the failure stay migrated
+pub fn migrate_credentials_to_domain_type<M: CredentialManager>(
+    manager: &M,
+    migrated_path: &Path,
+) -> Result<usize, AppError> {
+    let mut record = load_migrated_credentials_from(migrated_path)?;
+    let mut migrated = 0;
+    for target in manager.list_with_prefix_typed(TERMSRV_PREFIX, CredentialType::Generic)? {
+        if target.starts_with(GATEWAY_CREDENTIAL_PREFIX) {
+            continue;
+        }
+        let Some((username, password)) = manager.read_typed(&target, CredentialType::Generic)? else {
+            continue;
+        };
+
+        manager.save_typed(&target, &username, &password, CredentialType::DomainPassword)?;
+        if record.insert(&target) {
+            save_migrated_credentials_to(migrated_path, &record)?;
+        }
+        manager.delete_typed(&target, CredentialType::Generic)?;
+        migrated += 1;
+    }
+    Ok(migrated)
+}
+
+/// A host whose credential could not be saved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CredentialSaveFailure {
+    pub hostname: String,
+    pub error: String,
+}
+
+/// Outcome of saving one set of credentials for several hosts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BulkCredentialSave {
+    pub saved: usize,
+    pub failed: Vec<CredentialSaveFailure>,
+}
+
+/// Saves the same credentials as `TERMSRV/{hostname}` for each host
+///
+/// A failure for one host doesn't stop the rest. Each host is saved as by
+/// `save_host_credential`, so migrated hosts keep the domain type.
+pub fn save_credentials_for_hosts<M: CredentialManager>(
+    manager: &M,
+    migrated_path: &Path,
+    hosts: &[Host],
+    username: &str,
+    password: &str,
+) -> BulkCredentialSave {
+    let mut outcome = BulkCredentialSave::default();
+
+    for host in hosts {
+        match save_host_credential(manager, migrated_path, &host.hostname, username, password) {
+            Ok(()) => outcome.saved += 1,
+            Err(e) => outcome.failed.push(CredentialSaveFailure {
+                hostname: host.hostname.clone(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    outcome
+}
+
+/// Seconds from the FILETIME epoch (1601-01-01 UTC) to the Unix epoch
+const FILETIME_UNIX_EPOCH_SECS: u64 = 11_644_473_600;
+
+/// Converts a FILETIME (100-nanosecond intervals since 1601) to Unix seconds
+///
+/// Returns `None` for times before 1970, including an unset (zero) FILETIME.
+pub fn filetime_to_unix(filetime: u64) -> Option<u64> {
+    (filetime / 10_000_000).checked_sub(FILETIME_UNIX_EPOCH_SECS)
+}
+
+/// A stored credential without its password
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CredentialInfo {
+    pub target: String,
+    pub username: String,
+    /// Unix timestamp (seconds) the credential was last written, if Windows recorded one
+    pub last_written: Option<u64>,
+}
+
+/// Reads the username and last-written time of the credential saved as `target`
+///
+/// The generic credential QuickConnect saves is preferred; a domain password
+/// credential (saved by mstsc or migrated) is used when there is none. The
+/// password is never read.
+///
+/// # Returns
+/// * `Ok(Some(CredentialInfo))` - The credential exists
+/// * `Ok(None)` - No credential of either type is saved for `target`
+/// * `Err(AppError)` - Reading the credential failed
+pub fn get_credential_info<M: CredentialManager>(
+    manager: &M,
+    target: &str,
+) -> Result<Option<CredentialInfo>, AppError> {
+    for cred_type in [CredentialType::Generic, CredentialType::DomainPassword] {
+        if let Some(metadata) = manager.read_metadata_typed(target, cred_type)? {
+            return Ok(Some(CredentialInfo {
+                target: target.to_string(),
+                username: metadata.username,
+                last_written: filetime_to_unix(metadata.last_written),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// When the global credentials were saved
+///
+/// Stored in a sidecar JSON file rather than in the encrypted credential.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialMeta {
+    /// Unix timestamp (seconds) of the save
+    pub saved_at: u64,
+    /// Unix timestamp (seconds) after which the credentials are considered stale
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+impl CredentialMeta {
+    /// Metadata for credentials saved at `now`, expiring after `max_age_days` (0 = never)
+    pub fn new(now: u64, max_age_days: u32) -> Self {
+        Self {
+            saved_at: now,
+            expires_at: (max_age_days > 0).then(|| now + u64::from(max_age_days) * SECS_PER_DAY),
+        }
+    }
+
+    /// Whole days since the credentials were saved (0 if the clock went backwards)
+    pub fn age_days(&self, now: u64) -> u64 {
+        now.saturating_sub(self.saved_at) / SECS_PER_DAY
+    }
+
+    /// Decides whether to warn that the credentials are stale
+    ///
+    /// Warns once the recorded `expires_at` has passed, or once the age reaches
+    /// the currently configured `max_age_days` (0 = only `expires_at` applies).
+    pub fn should_warn(&self, now: u64, max_age_days: u32) -> bool {
+        let expired = self.expires_at.is_some_and(|expires_at| now >= expires_at);
+        let too_old = max_age_days > 0 && self.age_days(now) >= u64::from(max_age_days);
+        expired || too_old
+    }
+}
+
+/// Loads credential metadata from the given file.
+///
+/// # Returns
+/// * `Ok(Some(CredentialMeta))` - Metadata was recorded
+/// * `Ok(None)` - No metadata file (credentials saved by an older version, or none saved)
+/// * `Err(AppError)` - Failed to read or parse the file
+pub fn load_credential_meta_from(path: &Path) -> Result<Option<CredentialMeta>, AppError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let json = std::fs::read_to_string(path).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+
+    serde_json::from_str(&json).map(Some).map_err(|e| AppError::JsonError {
+        context: format!("Failed to parse credential metadata {:?}", path),
+        source: e,
+    })
+}
+
+/// Saves credential metadata to the given file.
+pub fn save_credential_meta_to(path: &Path, meta: &CredentialMeta) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(meta).map_err(|e| AppError::JsonError {
+        context: "Failed to serialize credential metadata".to_string(),
+        source: e,
+    })?;
+
+    std::fs::write(path, json).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })
+}
+
+/// How many credential checks run at once in `test_all_credentials`
+pub const CREDENTIAL_TEST_CONCURRENCY: usize = 4;
+
+/// Overall time allowed for `test_all_credentials`
+pub const CREDENTIAL_TEST_BUDGET: Duration = Duration::from_secs(60);
+
+/// Outcome of checking one host's credentials
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialTestStatus {
+    /// The credentials authenticated
+    Pass,
+    /// The credentials were rejected, or the check could not be made
+    Fail,
+    /// Not checked: no credentials, or the time budget ran out
+    Skip,
+}
+
+/// Result of checking one host's credentials
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CredentialTestResult {
+    pub hostname: String,
+    pub status: CredentialTestStatus,
+    /// `true` when the global credentials were checked (no `TERMSRV/` entry)
+    pub uses_global: bool,
+    pub message: String,
+}
+
+/// Credentials to check for one host
+#[derive(Debug, Clone)]
+pub struct CredentialTestTarget {
+    pub hostname: String,
+    /// `None` when the host has no per-host credentials and there are no global ones
+    pub credentials: Option<StoredCredentials>,
+    pub uses_global: bool,
+}
+
+/// Picks the credentials each host would connect with
+///
+/// Per-host (`TERMSRV/`) credentials win; otherwise the global credentials.
+pub fn credential_test_targets<F>(
+    hosts: &[Host],
+    per_host: F,
+    global: Option<&StoredCredentials>,
+) -> Vec<CredentialTestTarget>
+where
+    F: Fn(&str) -> Option<StoredCredentials>,
+{
+    hosts
+        .iter()
+        .map(|host| match per_host(&host.hostname) {
+            Some(credentials) => CredentialTestTarget {
+                hostname: host.hostname.clone(),
+                credentials: Some(credentials),
+                uses_global: false,
+            },
+            None => CredentialTestTarget {
+                hostname: host.hostname.clone(),
+                credentials: global.cloned(),
+                uses_global: global.is_some(),
+            },
+        })
+        .collect()
+}
+
+/// Checks each target's credentials, a few at a time, within a time budget
+///
+/// Each distinct set of credentials is checked once and the outcome applied
+/// to every host using it, so hosts sharing the global credentials cost one
+/// bind rather than one per host. Checks of the same account run one after
+/// another, and once one fails the account's remaining password variants are
+/// skipped, so a wrong password can't lock the account out.
+///
+/// Targets without credentials are skipped. Checks still running when the
+/// budget is spent are reported as skipped; their threads finish in the
+/// background.
+///
+/// # Arguments
+/// * `targets` - Hosts and the credentials to check (see `credential_test_targets`)
+/// * `concurrency` - Maximum checks at once (0 is treated as 1)
+/// * `budget` - Maximum time to wait for all results
+/// * `check` - Authenticates one set of credentials, given the first host
+///   using them, returning a failure reason
+///
+/// # Returns
+/// One result per target, in the same order.
+pub fn test_credentials<C>(
+    targets: Vec<CredentialTestTarget>,
+    concurrency: usize,
+    budget: Duration,
+    check: C,
+) -> Vec<CredentialTestResult>
+where
+    C: Fn(&str, &StoredCredentials) -> Result<(), String> + Send + Sync + 'static,
+{
+    let mut results: Vec<CredentialTestResult> = targets
+        .iter()
+        .map(|target| CredentialTestResult {
+            hostname: target.hostname.clone(),
+            status: CredentialTestStatus::Skip,
+            uses_global: target.uses_global,
+            message: if target.credentials.is_some() {
+                "Not checked within the time budget".to_string()
+            } else {
+                "No stored credentials".to_string()
+            },
+        })
+        .collect();
+
+    // Group the targets by account, then by the exact credentials, keeping
+    // the order hosts appear in
+    let mut accounts: Vec<(String, Vec<CredentialCheck>)> = Vec::new();
+    for (index, target) in targets.into_iter().enumerate() {
+        let Some(credentials) = target.credentials else {
+            continue;
+        };
+        let account = credentials.username.trim().to_lowercase();
+        let position = match accounts.iter().position(|(key, _)| *key == account) {
+            Some(position) => position,
+            None => {
+                accounts.push((account, Vec::new()));
+                accounts.len() - 1
+            }
+        };
+        let checks = &mut accounts[position].1;
+        match checks.iter_mut().find(|check| {
+            check.credentials.username.trim().eq_ignore_ascii_case(credentials.username.trim())
+                && check.credentials.password == credentials.password
+        }) {
+            Some(check) => check.indices.push(index),
+            None => checks.push(CredentialCheck {
+                hostname: target.hostname,
+                credentials,
+                indices: vec![index],
+            }),
+        }
+    }
+    let pending: usize = accounts.iter().map(|(_, checks)| checks.len()).sum();
+    if pending == 0 {
+        return results;
+    }
+
+    let worker_count = concurrency.clamp(1, accounts.len());
+    let queue = Arc::new(Mutex::new(accounts.into_iter().map(|(_, checks)| checks)));
+    let check = Arc::new(check);
+    let (tx, rx) = mpsc::channel();
+
+    for _ in 0..worker_count {
+        let queue = Arc::clone(&queue);
+        let check = Arc::clone(&check);
+        let tx = tx.clone();
+        std::thread::spawn(move || loop {
+            let next = queue.lock().map(|mut queue| queue.next()).unwrap_or(None);
+            let Some(checks) = next else {
+                break;
+            };
+            let mut failed = false;
+            for item in checks {
+                // `None` marks a check skipped after an earlier failure
+                let outcome = (!failed).then(|| check(&item.hostname, &item.credentials));
+                failed |= matches!(outcome, Some(Err(_)));
+                // The receiver is gone once the budget is spent; stop taking work
+                if tx.send((item.indices, outcome)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    drop(tx);
+
+    let started = Instant::now();
+    for _ in 0..pending {
+        let remaining = budget.saturating_sub(started.elapsed());
+        let Ok((indices, outcome)) = rx.recv_timeout(remaining) else {
+            break;
+        };
+        for index in indices {
+            let result = &mut results[index];
+            match &outcome {
+                Some(Ok(())) => {
+                    result.status = CredentialTestStatus::Pass;
+                    result.message = "Authenticated".to_string();
+                }
+                Some(Err(reason)) => {
+                    result.status = CredentialTestStatus::Fail;
+                    result.message = reason.clone();
+                }
+                None => {
+                    result.message =
+                        "Not checked: another password for this account was just rejected".to_string();
+                }
+            }
+        }
+    }
+
+    results
+}
+
+/// One distinct set of credentials and the targets using it
+struct CredentialCheck {
+    /// First host using these credentials, passed to the check
+    hostname: String,
+    credentials: StoredCredentials,
+    /// Positions of every target using these credentials
+    indices: Vec<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::mock::MockCredentialManager;
+    use tempfile::TempDir;
+
+    fn save(manager: &MockCredentialManager, target: &str, cred_type: CredentialType) {
+        manager
+            .save_typed(target, "user", "password", cred_type)
+            .expect("Mock save should succeed");
+    }
+
+    /// Temporary directory and the migration record path inside it
+    fn migrated_record() -> (TempDir, std::path::PathBuf) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join(crate::infra::paths::MIGRATED_CREDENTIALS_FILE_NAME);
+        (temp_dir, path)
+    }
+
+    #[test]
+    fn test_list_credential_targets_strips_prefix() {
+        let manager = MockCredentialManager::new();
+        save(&manager, "TERMSRV/server01.domain.com", CredentialType::Generic);
+        save(&manager, "QuickConnect", CredentialType::Generic);
+
+        let hostnames = list_credential_targets(&manager).expect("Listing should succeed");
+
+        assert_eq!(hostnames, vec!["server01.domain.com"]);
+    }
+
+    #[test]
+    fn test_list_credential_targets_skips_gateway_credentials() {
+        let manager = MockCredentialManager::new();
+        save(&manager, "TERMSRV/server01.domain.com", CredentialType::Generic);
+        save(&manager, "TERMSRV/GATEWAY/rdg.domain.com", CredentialType::Generic);
+
+        let hostnames = list_credential_targets(&manager).expect("Listing should succeed");
+
+        assert_eq!(hostnames, vec!["server01.domain.com"]);
+    }
+
+    #[test]
+    fn test_list_credential_targets_sorted_and_deduplicated() {
+        let manager = MockCredentialManager::new();
+        save(&manager, "TERMSRV/web.domain.com", CredentialType::Generic);
+        save(&manager, "TERMSRV/Beta.domain.com", CredentialType::Generic);
+        save(&manager, "TERMSRV/alpha.domain.com", CredentialType::DomainPassword);
+        save(&manager, "TERMSRV/web.domain.com", CredentialType::DomainPassword);
+
+        let hostnames = list_credential_targets(&manager).expect("Listing should succeed");
+
+        assert_eq!(
+            hostnames,
+            vec!["alpha.domain.com", "Beta.domain.com", "web.domain.com"]
+        );
+    }
+
+    #[test]
+    fn test_clear_host_credential_deletes_only_that_host() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        save(&manager, "TERMSRV/sql01.domain.com", CredentialType::Generic);
+        save(&manager, "TERMSRV/sql01.domain.com", CredentialType::DomainPassword);
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::Generic);
+        save(&manager, "QuickConnect", CredentialType::Generic);
+
+        let deleted = clear_host_credential(&manager, &record, "sql01.domain.com").expect("Clear should succeed");
+
+        assert_eq!(deleted, 2);
+        for cred_type in [CredentialType::Generic, CredentialType::DomainPassword] {
+            assert!(manager
+                .read_typed("TERMSRV/sql01.domain.com", cred_type)
+                .expect("Read should succeed")
+                .is_none());
+        }
+        assert!(manager.read("TERMSRV/web01.domain.com").expect("Read should succeed").is_some());
+        assert!(manager.read("QuickConnect").expect("Read should succeed").is_some());
+    }
+
+    #[test]
+    fn test_clear_host_credential_missing_is_ok() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::Generic);
+
+        let deleted =
+            clear_host_credential(&manager, &record, "sql01.domain.com").expect("Missing credential should be Ok");
+
+        assert_eq!(deleted, 0);
+        assert_eq!(list_credential_targets(&manager).expect("Listing should succeed"), vec!["web01.domain.com"]);
+    }
+
+    #[test]
+    fn test_migrate_credentials_to_domain_type() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        manager
+            .save("TERMSRV/web01.domain.com", "CONTOSO\\admin", "secret")
+            .expect("Mock save should succeed");
+        save(&manager, "TERMSRV/sql01.domain.com", CredentialType::Generic);
+        // A stale domain copy is replaced by the generic credential
+        manager
+            .save_typed("TERMSRV/sql01.domain.com", "old", "old", CredentialType::DomainPassword)
+            .expect("Mock save should succeed");
+        save(&manager, "TERMSRV/app01.domain.com", CredentialType::DomainPassword);
+        save(&manager, "TERMSRV/GATEWAY/rdg.domain.com", CredentialType::Generic);
+        save(&manager, "QuickConnect", CredentialType::Generic);
+
+        let migrated = migrate_credentials_to_domain_type(&manager, &record).expect("Migration should succeed");
+
+        assert_eq!(migrated, 2);
+        let recorded = load_migrated_credentials_from(&record).expect("Load should succeed");
+        assert!(recorded.contains("TERMSRV/web01.domain.com"));
+        assert!(recorded.contains("TERMSRV/SQL01.domain.com"));
+        // Only had a domain copy, so it wasn't migrated
+        assert!(!recorded.contains("TERMSRV/app01.domain.com"));
+        assert_eq!(
+            manager.list_with_prefix_typed(TERMSRV_PREFIX, CredentialType::Generic).unwrap(),
+            vec!["TERMSRV/GATEWAY/rdg.domain.com"]
+        );
+        assert_eq!(
+            manager
+                .read_typed("TERMSRV/web01.domain.com", CredentialType::DomainPassword)
+                .unwrap(),
+            Some(("CONTOSO\\admin".to_string(), "secret".to_string()))
+        );
+        assert_eq!(
+            manager
+                .read_typed("TERMSRV/sql01.domain.com", CredentialType::DomainPassword)
+                .unwrap(),
+            Some(("user".to_string(), "password".to_string()))
+        );
+        assert!(manager.read("QuickConnect").unwrap().is_some());
+        assert_eq!(
+            list_credential_targets(&manager).unwrap(),
+            vec!["app01.domain.com", "sql01.domain.com", "web01.domain.com"]
+        );
+    }
+
+    #[test]
+    fn test_migrate_credentials_to_domain_type_is_idempotent() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::Generic);
+
+        assert_eq!(migrate_credentials_to_domain_type(&manager, &record).unwrap(), 1);
+        let saves_after_first_run = manager.saves().len();
+
+        assert_eq!(migrate_credentials_to_domain_type(&manager, &record).unwrap(), 0);
+        assert_eq!(manager.saves().len(), saves_after_first_run);
+        assert!(manager
+            .read_typed("TERMSRV/web01.domain.com", CredentialType::DomainPassword)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_save_credentials_for_hosts_saves_each_host() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        save(&manager, "TERMSRV/web02.domain.com", CredentialType::DomainPassword);
+        let hosts = vec![host("web01.domain.com"), host("web02.domain.com")];
+
+        let outcome = save_credentials_for_hosts(&manager, &record, &hosts, "DOMAIN\\svc_rdp", "secret");
+
+        assert_eq!(outcome.saved, 2);
+        assert!(outcome.failed.is_empty());
+        let targets: Vec<String> = manager
+            .saves()
+            .into_iter()
+            .filter(|call| call.cred_type == CredentialType::Generic)
+            .map(|call| call.target)
+            .collect();
+        assert_eq!(targets, vec!["TERMSRV/web01.domain.com", "TERMSRV/web02.domain.com"]);
+        assert_eq!(
+            manager.read("TERMSRV/web01.domain.com").expect("Read should succeed"),
+            Some(("DOMAIN\\svc_rdp".to_string(), "secret".to_string()))
+        );
+        // The stale domain copy is dropped so the next launch re-saves it
+        assert!(manager
+            .read_typed("TERMSRV/web02.domain.com", CredentialType::DomainPassword)
+            .expect("Read should succeed")
+            .is_none());
+    }
+
+    #[test]
+    fn test_save_credentials_for_hosts_keeps_migrated_hosts_domain_type() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::Generic);
+        migrate_credentials_to_domain_type(&manager, &record).expect("Migration should succeed");
+
+        let outcome = save_credentials_for_hosts(&manager, &record, &[host("web01.domain.com")], "svc_rdp", "new");
+
+        assert_eq!(outcome.saved, 1);
+        assert!(manager.read("TERMSRV/web01.domain.com").unwrap().is_none());
+        assert_eq!(
+            manager
+                .read_typed("TERMSRV/web01.domain.com", CredentialType::DomainPassword)
+                .unwrap(),
+            Some(("svc_rdp".to_string(), "new".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_read_host_credential_ignores_launch_domain_copy() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        // A launch with the global credentials leaves only a domain copy,
+        // which Windows returns without its password
+        manager
+            .save_typed("TERMSRV/web01.domain.com", "DOMAIN\\global", "", CredentialType::DomainPassword)
+            .unwrap();
+        manager
+            .save_typed("TERMSRV/sql01.domain.com", "DOMAIN\\global", "", CredentialType::DomainPassword)
+            .unwrap();
+        manager.save("TERMSRV/sql01.domain.com", "admin", "secret").unwrap();
+
+        assert!(read_host_credential(&manager, &record, "web01.domain.com").unwrap().is_none());
+        let stored = read_host_credential(&manager, &record, "sql01.domain.com").unwrap().unwrap();
+        assert_eq!(stored.username, "admin");
+        assert_eq!(stored.password, "secret");
+    }
+
+    #[test]
+    fn test_save_host_credential_with_launch_domain_copy_saves_generic() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::DomainPassword);
+
+        save_host_credential(&manager, &record, "web01.domain.com", "admin", "secret").unwrap();
+
+        let stored = read_host_credential(&manager, &record, "web01.domain.com").unwrap().unwrap();
+        assert_eq!(stored.password, "secret");
+        assert!(manager
+            .read_typed("TERMSRV/web01.domain.com", CredentialType::DomainPassword)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_read_host_credential_after_migration() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        manager.save("TERMSRV/web01.domain.com", "DOMAIN\\admin", "secret").unwrap();
+        migrate_credentials_to_domain_type(&manager, &record).unwrap();
+
+        let stored = read_host_credential(&manager, &record, "web01.domain.com").unwrap().unwrap();
+        assert_eq!(stored.username, "DOMAIN\\admin");
+        assert!(read_host_credential(&manager, &record, "sql01.domain.com").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_clear_host_credential_after_migration() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::Generic);
+        migrate_credentials_to_domain_type(&manager, &record).unwrap();
+
+        assert_eq!(clear_host_credential(&manager, &record, "web01.domain.com").unwrap(), 1);
+        assert!(read_host_credential(&manager, &record, "web01.domain.com").unwrap().is_none());
+        assert!(!load_migrated_credentials_from(&record).unwrap().contains("TERMSRV/web01.domain.com"));
+    }
+
+    #[test]
+    fn test_save_credentials_for_hosts_reports_failures() {
+        let manager = MockCredentialManager::new();
+        let (_temp_dir, record) = migrated_record();
+        manager.fail_saves_to("TERMSRV/web02.domain.com");
+        let hosts = vec![host("web01.domain.com"), host("web02.domain.com"), host("web03.domain.com")];
+
+        let outcome = save_credentials_for_hosts(&manager, &record, &hosts, "svc_rdp", "secret");
+
+        // A failure doesn't stop the remaining hosts
+        assert_eq!(outcome.saved, 2);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(outcome.failed[0].hostname, "web02.domain.com");
+        assert!(!outcome.failed[0].error.contains("secret"));
+        assert!(manager.read("TERMSRV/web03.domain.com").expect("Read should succeed").is_some());
+        assert!(manager.read("TERMSRV/web02.domain.com").expect("Read should succeed").is_none());
+    }
+
+    #[test]
+    fn test_list_credential_targets_empty() {
+        let manager = MockCredentialManager::new();
+        let hostnames = list_credential_targets(&manager).expect("Listing should succeed");
+        assert!(hostnames.is_empty());
+    }
+
+    #[test]
+    fn test_flag_orphaned_targets() {
+        let hosts = vec![Host {
+            hostname: "SERVER01.domain.com".to_string(),
+            ..Default::default()
+        }];
+
+        let targets = flag_orphaned_targets(
+            vec!["server01.domain.com".to_string(), "old.domain.com".to_string()],
+            &hosts,
+        );
+
+        assert_eq!(
+            targets,
+            vec![
+                CredentialTarget {
+                    hostname: "server01.domain.com".to_string(),
+                    has_host: true,
+                },
+                CredentialTarget {
+                    hostname: "old.domain.com".to_string(),
+                    has_host: false,
+                },
+            ]
+        );
+    }
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn test_credential_age_days() {
+        let meta = CredentialMeta::new(NOW, 0);
+
+        assert_eq!(meta.age_days(NOW), 0);
+        assert_eq!(meta.age_days(NOW + SECS_PER_DAY - 1), 0);
+        assert_eq!(meta.age_days(NOW + 61 * SECS_PER_DAY), 61);
+        // Clock moved backwards
+        assert_eq!(meta.age_days(NOW - SECS_PER_DAY), 0);
+    }
+
+    #[test]
+    fn test_credential_meta_expiry() {
+        assert_eq!(CredentialMeta::new(NOW, 0).expires_at, None);
+        assert_eq!(
+            CredentialMeta::new(NOW, 60).expires_at,
+            Some(NOW + 60 * SECS_PER_DAY)
+        );
+    }
+
+    #[test]
+    fn test_should_warn_by_threshold() {
+        let meta = CredentialMeta::new(NOW, 0);
+
+        assert!(!meta.should_warn(NOW + 59 * SECS_PER_DAY, 60));
+        assert!(meta.should_warn(NOW + 60 * SECS_PER_DAY, 60));
+        assert!(meta.should_warn(NOW + 90 * SECS_PER_DAY, 60));
+        // Threshold disabled
+        assert!(!meta.should_warn(NOW + 365 * SECS_PER_DAY, 0));
+    }
+
+    #[test]
+    fn test_should_warn_by_recorded_expiry() {
+        let meta = CredentialMeta::new(NOW, 30);
+
+        assert!(!meta.should_warn(NOW + 29 * SECS_PER_DAY, 0));
+        assert!(meta.should_warn(NOW + 30 * SECS_PER_DAY, 0));
+        // The recorded expiry still applies when the threshold is raised later
+        assert!(meta.should_warn(NOW + 30 * SECS_PER_DAY, 90));
+    }
+
+    #[test]
+    fn test_credential_meta_roundtrip() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("credential_meta.json");
+        assert_eq!(load_credential_meta_from(&path).expect("Load should succeed"), None);
+
+        let meta = CredentialMeta::new(NOW, 60);
+        save_credential_meta_to(&path, &meta).expect("Save should succeed");
+
+        assert_eq!(load_credential_meta_from(&path).expect("Load should succeed"), Some(meta));
+    }
+
+    fn creds(username: &str, password: &str) -> StoredCredentials {
+        StoredCredentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    fn host(hostname: &str) -> Host {
+        Host {
+            hostname: hostname.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Passes only for the password "good"
+    fn check_password(_hostname: &str, credentials: &StoredCredentials) -> Result<(), String> {
+        if credentials.password == "good" {
+            Ok(())
+        } else {
+            Err("Invalid credentials".to_string())
+        }
+    }
+
+    #[test]
+    fn test_credential_test_targets_prefers_per_host() {
+        let hosts = vec![host("web01"), host("db01")];
+        let global = creds("CONTOSO\\admin", "good");
+        let per_host = |hostname: &str| (hostname == "db01").then(|| creds("dba", "secret"));
+
+        let targets = credential_test_targets(&hosts, per_host, Some(&global));
+
+        assert!(targets[0].uses_global);
+        assert_eq!(targets[0].credentials.as_ref().map(|c| c.username.as_str()), Some("CONTOSO\\admin"));
+        assert!(!targets[1].uses_global);
+        assert_eq!(targets[1].credentials.as_ref().map(|c| c.username.as_str()), Some("dba"));
+    }
+
+    #[test]
+    fn test_credential_test_targets_without_global() {
+        let targets = credential_test_targets(&[host("web01")], |_| None, None);
+        assert!(targets[0].credentials.is_none());
+        assert!(!targets[0].uses_global);
+    }
+
+    #[test]
+    fn test_test_credentials_aggregates_outcomes() {
+        let hosts = vec![host("web01"), host("db01"), host("app01")];
+        let per_host = |hostname: &str| match hostname {
+            "web01" => Some(creds("web", "good")),
+            "db01" => Some(creds("dba", "expired")),
+            _ => None,
+        };
+        let targets = credential_test_targets(&hosts, per_host, None);
+
+        let results = test_credentials(targets, 2, Duration::from_secs(5), check_password);
+
+        let statuses: Vec<_> = results.iter().map(|r| (r.hostname.as_str(), r.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("web01", CredentialTestStatus::Pass),
+                ("db01", CredentialTestStatus::Fail),
+                ("app01", CredentialTestStatus::Skip),
+            ]
+        );
+        assert_eq!(results[1].message, "Invalid credentials");
+        assert_eq!(results[2].message, "No stored credentials");
+    }
+
+    #[test]
+    fn test_test_credentials_uses_global_fallback() {
+        let global = creds("admin", "good");
+        let targets = credential_test_targets(&[host("web01")], |_| None, Some(&global));
+
+        let results = test_credentials(targets, 4, Duration::from_secs(5), check_password);
+
+        assert_eq!(results[0].status, CredentialTestStatus::Pass);
+        assert!(results[0].uses_global);
+    }
+
+    #[test]
+    fn test_test_credentials_respects_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let hosts: Vec<Host> = (0..8).map(|i| host(&format!("host{}", i))).collect();
+        let targets = credential_test_targets(&hosts, |hostname| Some(creds(hostname, "good")), None);
+
+        let (running_check, peak_check) = (Arc::clone(&running), Arc::clone(&peak));
+        let results = test_credentials(targets, 3, Duration::from_secs(10), move |_, _| {
+            let now = running_check.fetch_add(1, Ordering::SeqCst) + 1;
+            peak_check.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            running_check.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        });
+
+        assert!(results.iter().all(|r| r.status == CredentialTestStatus::Pass));
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn test_test_credentials_checks_shared_credentials_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        let global = creds("CONTOSO\\admin", "good");
+        let hosts: Vec<Host> = (0..5).map(|i| host(&format!("host{}", i))).collect();
+        let per_host = |hostname: &str| (hostname == "host4").then(|| creds("contoso\\ADMIN", "good"));
+        let targets = credential_test_targets(&hosts, per_host, Some(&global));
+
+        let counter = Arc::clone(&checks);
+        let results = test_credentials(targets, 4, Duration::from_secs(5), move |hostname, credentials| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            check_password(hostname, credentials)
+        });
+
+        assert_eq!(checks.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|r| r.status == CredentialTestStatus::Pass));
+    }
+
+    #[test]
+    fn test_test_credentials_stops_account_after_failure() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        let hosts = vec![host("web01"), host("web02"), host("db01"), host("app01")];
+        let per_host = |hostname: &str| match hostname {
+            "web01" => Some(creds("admin", "wrong")),
+            "web02" => Some(creds("admin", "good")),
+            "db01" => Some(creds("admin", "wrong")),
+            _ => Some(creds("svc", "good")),
+        };
+        let targets = credential_test_targets(&hosts, per_host, None);
+
+        let counter = Arc::clone(&checks);
+        let results = test_credentials(targets, 4, Duration::from_secs(5), move |hostname, credentials| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            check_password(hostname, credentials)
+        });
+
+        // One failed bind for "admin", one bind for "svc"
+        assert_eq!(checks.load(Ordering::SeqCst), 2);
+        let statuses: Vec<_> = results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                CredentialTestStatus::Fail,
+                CredentialTestStatus::Skip,
+                CredentialTestStatus::Fail,
+                CredentialTestStatus::Pass,
+            ]
+        );
+        assert!(results[1].message.contains("rejected"));
+    }
+
+    #[test]
+    fn test_test_credentials_budget_exhausted_skips() {
+        let global = creds("admin", "good");
+        let targets = credential_test_targets(&[host("slow01")], |_| None, Some(&global));
+
+        let results = test_credentials(targets, 1, Duration::from_millis(50), |_, _| {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(())
+        });
+
+        assert_eq!(results[0].status, CredentialTestStatus::Skip);
+        assert_eq!(results[0].message, "Not checked within the time budget");
+    }
+
+    #[test]
+    fn test_test_credentials_empty() {
+        assert!(test_credentials(Vec::new(), 4, Duration::from_secs(1), check_password).is_empty());
+    }
+
+    #[test]
+    fn test_filetime_to_unix() {
+        // 2024-01-01 00:00:00 UTC
+        assert_eq!(filetime_to_unix(133_485_408_000_000_000), Some(1_704_067_200));
+        assert_eq!(filetime_to_unix(116_444_736_000_000_000), Some(0));
+        assert_eq!(filetime_to_unix(0), None);
+        assert_eq!(filetime_to_unix(116_444_735_990_000_000), None);
+    }
+
+    #[test]
+    fn test_get_credential_info_returns_metadata_only() {
+        let manager = MockCredentialManager::new();
+        manager
+            .save("QuickConnect", "CONTOSO\\admin", "secret")
+            .expect("Mock save should succeed");
+        manager.set_last_written("QuickConnect", CredentialType::Generic, 133_485_408_000_000_000);
+
+        let info = get_credential_info(&manager, "QuickConnect").unwrap().unwrap();
+
+        assert_eq!(
+            info,
+            CredentialInfo {
+                target: "QuickConnect".to_string(),
+                username: "CONTOSO\\admin".to_string(),
+                last_written: Some(1_704_067_200),
+            }
+        );
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(!json.contains("secret"));
+    }
+
+    #[test]
+    fn test_get_credential_info_falls_back_to_domain_type() {
+        let manager = MockCredentialManager::new();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::DomainPassword);
+
+        let info = get_credential_info(&manager, "TERMSRV/web01.domain.com").unwrap().unwrap();
+
+        assert_eq!(info.username, "user");
+        // The mock reports no LastWritten until one is set
+        assert_eq!(info.last_written, None);
+    }
+
+    #[test]
+    fn test_get_credential_info_not_found() {
+        let manager = MockCredentialManager::new();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::Generic);
+
+        assert_eq!(get_credential_info(&manager, "QuickConnect").unwrap(), None);
+        assert_eq!(get_credential_info(&manager, "TERMSRV/sql01.domain.com").unwrap(), None);
+    }
+}
@@ -15,8 +15,183 @@
 //! - Clear separation between I/O and command handling
 
 use crate::{Host, AppError};
+use csv::StringRecord;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::BufReader;
 use std::path::Path;
 
+/// Maps hosts.csv columns to Host fields by position
+///
+/// Built from the header row so columns can appear in any order, with unknown
+/// columns carried through in `Host::extra_columns`.
+struct ColumnMap {
+    hostname: usize,
+    description: Option<usize>,
+    last_connected: Option<usize>,
+    connect_username: Option<usize>,
+    group: Option<usize>,
+    connect_count: Option<usize>,
+    tags: Option<usize>,
+    sort_order: Option<usize>,
+    /// (index, header name) of columns that aren't Host fields
+    extra: Vec<(usize, String)>,
+    /// Rows with fewer fields than this are skipped
+    min_fields: usize,
+}
+
+impl ColumnMap {
+    /// Builds the mapping from a header row
+    ///
+    /// Returns `None` when there is no `hostname` column, i.e. the first row is
+    /// data from a legacy headerless file.
+    fn from_header(header: &StringRecord) -> Option<Self> {
+        let mut map = ColumnMap {
+            hostname: usize::MAX,
+            description: None,
+            last_connected: None,
+            connect_username: None,
+            group: None,
+            connect_count: None,
+            tags: None,
+            sort_order: None,
+            extra: Vec::new(),
+            min_fields: 1,
+        };
+
+        for (index, name) in header.iter().enumerate() {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "hostname" => map.hostname = index,
+                "description" => map.description = Some(index),
+                "last_connected" => map.last_connected = Some(index),
+                "connect_username" => map.connect_username = Some(index),
+                "group" => map.group = Some(index),
+                "connect_count" => map.connect_count = Some(index),
+                "tags" => map.tags = Some(index),
+                "sort_order" => map.sort_order = Some(index),
+                "" => {}
+                _ => map.extra.push((index, name.trim().to_string())),
+            }
+        }
+
+        (map.hostname != usize::MAX).then_some(map)
+    }
+
+    /// Positional mapping used by headerless files written before v1.2.0
+    fn legacy() -> Self {
+        ColumnMap {
+            hostname: 0,
+            description: Some(1),
+            last_connected: Some(2),
+            connect_username: Some(3),
+            group: None,
+            connect_count: None,
+            tags: None,
+            sort_order: None,
+            extra: Vec::new(),
+            // Legacy rows need at least hostname and description
+            min_fields: 2,
+        }
+    }
+
+    /// Converts a record into a Host, skipping rows without a hostname
+    ///
+    /// A blank hostname (e.g. a stray `,,,` line at the end of a hand-edited
+    /// file) would otherwise surface as a host that fails validation on launch.
+    fn to_host(&self, record: &StringRecord) -> Option<Host> {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|i| record.get(i))
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+
+        let hostname = record
+            .get(self.hostname)
+            .filter(|hostname| !hostname.trim().is_empty())?
+            .to_string();
+        let extra_columns = self
+            .extra
+            .iter()
+            .filter_map(|(index, name)| {
+                record
+                    .get(*index)
+                    .filter(|value| !value.is_empty())
+                    .map(|value| (name.clone(), value.to_string()))
+            })
+            .collect();
+
+        Some(Host {
+            hostname,
+            description: field(self.description).unwrap_or_default(),
+            last_connected: field(self.last_connected),
+            connect_username: field(self.connect_username),
+            group: field(self.group).map(|group| group.trim().to_string()),
+            // A hand-edited count that isn't a number is treated as no connections
+            connect_count: field(self.connect_count)
+                .and_then(|count| count.trim().parse().ok())
+                .unwrap_or(0),
+            tags: field(self.tags)
+                .map(|tags| crate::core::hosts::parse_tags(&tags))
+                .unwrap_or_default(),
+            sort_order: field(self.sort_order).and_then(|order| order.trim().parse().ok()),
+            extra_columns,
+        })
+    }
+}
+
+/// Version of the hosts.csv layout this build writes
+///
+/// 1. Headerless rows written before v1.2.0
+/// 2. Header row with hostname, description, last_connected and connect_username
+/// 3. Adds the group, connect_count and tags columns
+/// 4. Adds the sort_order column
+///
+/// Older files are still read, and are upgraded on the next write.
+pub const HOSTS_CSV_SCHEMA_VERSION: u32 = 4;
+
+/// Detects the layout version of a hosts.csv file from its first row
+///
+/// # Returns
+/// * `Ok(Some(version))` - See `HOSTS_CSV_SCHEMA_VERSION`
+/// * `Ok(None)` - The file doesn't exist or is empty
+/// * `Err(AppError)` - The file couldn't be read
+pub fn detect_csv_schema_version(csv_path: &Path) -> Result<Option<u32>, AppError> {
+    if !csv_path.exists() {
+        return Ok(None);
+    }
+
+    let file = std::fs::File::open(csv_path).map_err(|e| AppError::IoError {
+        path: csv_path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(BufReader::new(file));
+
+    let Some(first) = reader.records().next() else {
+        return Ok(None);
+    };
+    let first = first.map_err(|e| AppError::CsvError {
+        operation: "parse CSV header".to_string(),
+        source: e,
+    })?;
+
+    let version = match ColumnMap::from_header(&first) {
+        None => 1,
+        Some(map) if map.group.is_some() && map.connect_count.is_some() && map.tags.is_some() => {
+            if map.sort_order.is_some() {
+                4
+            } else {
+                3
+            }
+        }
+        Some(_) => 2,
+    };
+    Ok(Some(version))
+}
+
 /// Reads hosts from a CSV file
 ///
 /// # Why this exists
@@ -31,20 +206,25 @@ use std::path::Path;
 /// * `Err(AppError)` - Failed to read or parse CSV
 ///
 /// # Side Effects
-/// - Reads file from disk at `csv_path`
+/// - Streams the file at `csv_path` through a buffered reader, one record at a time
 ///
 /// # Failure Modes
 /// - File cannot be read (permission denied, etc.)
 /// - CSV is malformed (invalid format, missing columns)
 /// - Records cannot be parsed into Host structs
 ///
+/// Blank lines and rows with an empty hostname are skipped.
+///
 /// # CSV Format
-/// Expected format with optional last_connected column:
+/// Columns are matched by header name, in any order. Only `hostname` is
+/// required; unknown columns are kept in `Host::extra_columns`:
 /// ```csv
-/// hostname,description,last_connected
-/// server01.domain.com,Web Server,13/12/2025 14:30:00
-/// server02.domain.com,Database Server,
+/// hostname,description,last_connected,connect_username,group,connect_count,tags,sort_order
+/// server01.domain.com,Web Server,13/12/2025 14:30:00,,Web,12,prod;iis,1
+/// server02.domain.com,Database Server,,.\localadmin,,0,,
 /// ```
+/// Files without a header row are read positionally
+/// (hostname, description, last_connected, connect_username).
 pub fn read_hosts_from_csv(csv_path: &Path) -> Result<Vec<Host>, AppError> {
     use tracing::{debug, error};
 
@@ -62,7 +242,9 @@ pub fn read_hosts_from_csv(csv_path: &Path) -> Result<Vec<Host>, AppError> {
         return Ok(Vec::new());
     }
 
-    let contents = std::fs::read_to_string(csv_path).map_err(|e| {
+    // Stream records from the file rather than loading it whole; inventories
+    // can run to tens of thousands of rows
+    let file = std::fs::File::open(csv_path).map_err(|e| {
         error!(
             path = ?csv_path,
             error = %e,
@@ -76,41 +258,40 @@ pub fn read_hosts_from_csv(csv_path: &Path) -> Result<Vec<Host>, AppError> {
 
     let mut hosts = Vec::new();
     let mut reader = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_reader(contents.as_bytes());
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(BufReader::new(file));
 
-    // Parse each CSV record into a Host struct
-    // CSV format: hostname, description, last_connected (optional, added in v1.2.0)
+    let mut columns: Option<ColumnMap> = None;
     for result in reader.records() {
-        match result {
-            Ok(record) => {
-                // Minimum 2 columns required (hostname, description)
-                if record.len() >= 2 {
-                    // last_connected column is optional for backwards compatibility
-                    // with v1.1.0 CSV files that didn't have this column
-                    let last_connected = if record.len() >= 3 && !record[2].is_empty() {
-                        Some(record[2].to_string())
-                    } else {
-                        None
-                    };
-                    hosts.push(Host {
-                        hostname: record[0].to_string(),
-                        description: record[1].to_string(),
-                        last_connected,
-                    });
-                }
+        let record = result.map_err(|e| {
+            error!(
+                path = ?csv_path,
+                error = %e,
+                "Failed to parse CSV record"
+            );
+            AppError::CsvError {
+                operation: "parse CSV record".to_string(),
+                source: e,
             }
-            Err(e) => {
-                error!(
-                    path = ?csv_path,
-                    error = %e,
-                    "Failed to parse CSV record"
-                );
-                return Err(AppError::CsvError {
-                    operation: "parse CSV record".to_string(),
-                    source: e,
-                });
+        })?;
+
+        // First row: a header names the columns; otherwise it's a legacy headerless file
+        if columns.is_none() {
+            if let Some(map) = ColumnMap::from_header(&record) {
+                columns = Some(map);
+                continue;
             }
+            debug!(path = ?csv_path, "CSV has no header row, reading columns by position");
+        }
+        let map = columns.get_or_insert_with(ColumnMap::legacy);
+
+        if record.len() < map.min_fields {
+            continue;
+        }
+
+        if let Some(host) = map.to_host(&record) {
+            hosts.push(host);
         }
     }
 
@@ -123,6 +304,130 @@ pub fn read_hosts_from_csv(csv_path: &Path) -> Result<Vec<Host>, AppError> {
     Ok(hosts)
 }
 
+/// Maximum number of problems listed in a `CsvValidationReport`
+pub const MAX_CSV_PROBLEM_SAMPLES: usize = 20;
+
+/// Why a row of an imported CSV can't be used as-is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvProblemKind {
+    InvalidHostname,
+    Duplicate,
+    Unparseable,
+}
+
+/// A problem row, by its line number in the file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CsvProblem {
+    pub line: u64,
+    pub kind: CsvProblemKind,
+    pub message: String,
+}
+
+/// Result of checking a CSV before importing it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CsvValidationReport {
+    /// Data rows read (the header row isn't counted)
+    pub total_rows: usize,
+    /// Rows that would import as a new, distinct host
+    pub valid_rows: usize,
+    /// Rows with an empty or invalid hostname
+    pub invalid_hostnames: usize,
+    /// Rows repeating a hostname seen earlier in the file (case and trailing dots ignored)
+    pub duplicates: usize,
+    /// Records that couldn't be parsed or have too few fields
+    pub unparseable: usize,
+    /// The first `MAX_CSV_PROBLEM_SAMPLES` problems, in file order
+    pub problems: Vec<CsvProblem>,
+}
+
+impl CsvValidationReport {
+    fn record(&mut self, line: u64, kind: CsvProblemKind, message: String) {
+        match kind {
+            CsvProblemKind::InvalidHostname => self.invalid_hostnames += 1,
+            CsvProblemKind::Duplicate => self.duplicates += 1,
+            CsvProblemKind::Unparseable => self.unparseable += 1,
+        }
+        if self.problems.len() < MAX_CSV_PROBLEM_SAMPLES {
+            self.problems.push(CsvProblem { line, kind, message });
+        }
+    }
+}
+
+/// Checks every row of a hosts CSV without importing anything
+///
+/// Parses the file the same way `read_hosts_from_csv` does, but keeps going
+/// past bad records and reports on each row instead of returning hosts.
+///
+/// # Returns
+/// * `Ok(CsvValidationReport)` - Row counts and a sample of problems
+/// * `Err(AppError)` - The file couldn't be opened
+pub fn validate_hosts_csv(csv_path: &Path) -> Result<CsvValidationReport, AppError> {
+    let file = std::fs::File::open(csv_path).map_err(|e| AppError::IoError {
+        path: csv_path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(BufReader::new(file));
+
+    let mut report = CsvValidationReport::default();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut columns: Option<ColumnMap> = None;
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                report.total_rows += 1;
+                let line = e.position().map(|position| position.line()).unwrap_or(0);
+                report.record(line, CsvProblemKind::Unparseable, e.to_string());
+                continue;
+            }
+        };
+        let line = record.position().map(|position| position.line()).unwrap_or(0);
+
+        if columns.is_none() {
+            if let Some(map) = ColumnMap::from_header(&record) {
+                columns = Some(map);
+                continue;
+            }
+        }
+        let map = columns.get_or_insert_with(ColumnMap::legacy);
+        report.total_rows += 1;
+
+        if record.len() < map.min_fields {
+            report.record(
+                line,
+                CsvProblemKind::Unparseable,
+                format!("Expected at least {} fields, found {}", map.min_fields, record.len()),
+            );
+            continue;
+        }
+
+        let Some(host) = map.to_host(&record) else {
+            report.record(line, CsvProblemKind::InvalidHostname, "Hostname is empty".to_string());
+            continue;
+        };
+        if let Err(e) = crate::core::hosts::validate_host_address(&host.hostname) {
+            report.record(line, CsvProblemKind::InvalidHostname, e.to_string());
+            continue;
+        }
+        if !seen.insert(crate::core::hosts::normalize_hostname(&host.hostname)) {
+            report.record(
+                line,
+                CsvProblemKind::Duplicate,
+                format!("{} appears earlier in the file", host.hostname),
+            );
+            continue;
+        }
+
+        report.valid_rows += 1;
+    }
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,4 +469,269 @@ mod tests {
         assert_eq!(hosts[0].hostname, "server01.local");
         assert_eq!(hosts[0].last_connected, None);
     }
+
+    #[test]
+    fn test_read_csv_with_connect_username_column() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description,last_connected,connect_username").unwrap();
+        writeln!(file, "server01.local,Web Server,,.\\localadmin").unwrap();
+        writeln!(file, "server02.local,DB Server,,").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts[0].connect_username, Some(".\\localadmin".to_string()));
+        assert_eq!(hosts[1].connect_username, None);
+    }
+
+    #[test]
+    fn test_read_csv_with_reordered_columns() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "description,connect_username,hostname,last_connected").unwrap();
+        writeln!(file, "Web Server,admin,server01.local,13/12/2025 14:30:00").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].hostname, "server01.local");
+        assert_eq!(hosts[0].description, "Web Server");
+        assert_eq!(hosts[0].last_connected, Some("13/12/2025 14:30:00".to_string()));
+        assert_eq!(hosts[0].connect_username, Some("admin".to_string()));
+    }
+
+    #[test]
+    fn test_read_csv_keeps_unknown_columns() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,owner,description,rack").unwrap();
+        writeln!(file, "server01.local,ops,Web Server,R12").unwrap();
+        writeln!(file, "server02.local,,DB Server,R07").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[0].description, "Web Server");
+        assert_eq!(hosts[0].extra_columns.get("owner"), Some(&"ops".to_string()));
+        assert_eq!(hosts[0].extra_columns.get("rack"), Some(&"R12".to_string()));
+        assert_eq!(hosts[1].extra_columns.get("owner"), None);
+        assert_eq!(hosts[1].extra_columns.get("rack"), Some(&"R07".to_string()));
+    }
+
+    #[test]
+    fn test_read_csv_with_missing_optional_columns() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname").unwrap();
+        writeln!(file, "server01.local").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].hostname, "server01.local");
+        assert_eq!(hosts[0].description, "");
+        assert_eq!(hosts[0].last_connected, None);
+        assert_eq!(hosts[0].connect_username, None);
+    }
+
+    #[test]
+    fn test_read_csv_tolerates_short_rows() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description,last_connected,connect_username").unwrap();
+        writeln!(file, "server01.local,Web Server").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].description, "Web Server");
+        assert_eq!(hosts[0].last_connected, None);
+    }
+
+    #[test]
+    fn test_read_legacy_headerless_csv() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "server01.local,Web Server").unwrap();
+        writeln!(file, "server02.local,DB Server,13/12/2025 14:30:00").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[0].hostname, "server01.local");
+        assert_eq!(hosts[1].last_connected, Some("13/12/2025 14:30:00".to_string()));
+    }
+
+    #[test]
+    fn test_read_csv_with_group_column() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description,group").unwrap();
+        writeln!(file, "server01.local,Web Server,Patch Tuesday").unwrap();
+        writeln!(file, "server02.local,DB Server,").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts[0].group, Some("Patch Tuesday".to_string()));
+        assert_eq!(hosts[1].group, None);
+        assert!(hosts[0].extra_columns.is_empty());
+    }
+
+    #[test]
+    fn test_read_csv_skips_trailing_blank_line() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description").unwrap();
+        writeln!(file, "server01.local,Web Server").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "   ").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].hostname, "server01.local");
+    }
+
+    #[test]
+    fn test_read_csv_skips_empty_hostname_records() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description,last_connected,connect_username").unwrap();
+        writeln!(file, "server01.local,Web Server,,").unwrap();
+        writeln!(file, ",Orphaned description,,").unwrap();
+        writeln!(file, ",,,").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].hostname, "server01.local");
+    }
+
+    #[test]
+    fn test_read_legacy_csv_skips_empty_hostname_records() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "server01.local,Web Server").unwrap();
+        writeln!(file, " ,").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+        assert_eq!(hosts.len(), 1);
+    }
+
+    #[test]
+    fn test_read_large_csv_streams_all_rows() {
+        let mut file = std::io::BufWriter::new(NamedTempFile::new().unwrap());
+        writeln!(file, "hostname,description,last_connected,connect_username,group,connect_count,tags").unwrap();
+        for i in 0..60_000 {
+            writeln!(
+                file,
+                "server{:05}.domain.com,\"Server {}, rack {}\",13/12/2025 14:30:00,,Group {},{},prod;web",
+                i,
+                i,
+                i % 40,
+                i % 10,
+                i
+            )
+            .unwrap();
+        }
+        let file = file.into_inner().unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+
+        assert_eq!(hosts.len(), 60_000);
+        assert_eq!(hosts[0].hostname, "server00000.domain.com");
+        assert_eq!(hosts[0].description, "Server 0, rack 0");
+        let last = &hosts[59_999];
+        assert_eq!(last.hostname, "server59999.domain.com");
+        assert_eq!(last.description, "Server 59999, rack 39");
+        assert_eq!(last.last_connected, Some("13/12/2025 14:30:00".to_string()));
+        assert_eq!(last.group, Some("Group 9".to_string()));
+        assert_eq!(last.connect_count, 59_999);
+        assert_eq!(last.tags, vec!["prod".to_string(), "web".to_string()]);
+    }
+
+    #[test]
+    fn test_read_sort_order_column() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description,sort_order").unwrap();
+        writeln!(file, "server01.local,Web,2").unwrap();
+        writeln!(file, "server02.local,DB,").unwrap();
+        writeln!(file, "server03.local,Mail,first").unwrap();
+
+        let hosts = read_hosts_from_csv(file.path()).unwrap();
+
+        assert_eq!(hosts[0].sort_order, Some(2));
+        assert_eq!(hosts[1].sort_order, None);
+        // A hand-edited value that isn't a number is treated as unset
+        assert_eq!(hosts[2].sort_order, None);
+    }
+
+    #[test]
+    fn test_detect_csv_schema_version() {
+        let cases = [
+            ("server01.local,Web Server\n", Some(1)),
+            ("hostname,description,last_connected,connect_username\nserver01.local,Web,,\n", Some(2)),
+            ("hostname,description,last_connected,connect_username,group,connect_count,tags\n", Some(3)),
+            ("tags,hostname,connect_count,group,rack\n", Some(3)),
+            ("hostname,description,last_connected,connect_username,group,connect_count,tags,sort_order\n", Some(4)),
+            ("", None),
+        ];
+        for (contents, expected) in cases {
+            let mut file = NamedTempFile::new().unwrap();
+            write!(file, "{}", contents).unwrap();
+            assert_eq!(detect_csv_schema_version(file.path()).unwrap(), expected, "contents: {:?}", contents);
+        }
+
+        assert_eq!(detect_csv_schema_version(Path::new("nonexistent_file.csv")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_hosts_csv_counts_problems() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description,group").unwrap();
+        writeln!(file, "server01.domain.com,Web,Web").unwrap();
+        writeln!(file, "bad host!,Typo,").unwrap();
+        writeln!(file, "server02.domain.com,DB,").unwrap();
+        writeln!(file, "SERVER01.domain.com.,Web again,").unwrap();
+        writeln!(file, ",No hostname,").unwrap();
+        file.write_all(b"server03.domain.com,\xff\xfe,\n").unwrap();
+
+        let report = validate_hosts_csv(file.path()).unwrap();
+
+        assert_eq!(report.total_rows, 6);
+        assert_eq!(report.valid_rows, 2);
+        assert_eq!(report.invalid_hostnames, 2);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.unparseable, 1);
+
+        let kinds: Vec<(u64, CsvProblemKind)> = report.problems.iter().map(|p| (p.line, p.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (3, CsvProblemKind::InvalidHostname),
+                (5, CsvProblemKind::Duplicate),
+                (6, CsvProblemKind::InvalidHostname),
+                (7, CsvProblemKind::Unparseable),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_hosts_csv_does_not_modify_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "server01.domain.com,Web").unwrap();
+        writeln!(file, "server01.domain.com,Web").unwrap();
+        writeln!(file, "lonely").unwrap();
+        let before = std::fs::read(file.path()).unwrap();
+
+        let report = validate_hosts_csv(file.path()).unwrap();
+
+        // Headerless files are read positionally, needing hostname and description
+        assert_eq!(report.total_rows, 3);
+        assert_eq!(report.valid_rows, 1);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.unparseable, 1);
+        assert_eq!(std::fs::read(file.path()).unwrap(), before);
+    }
+
+    #[test]
+    fn test_validate_hosts_csv_caps_problem_sample() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "hostname,description").unwrap();
+        for _ in 0..(MAX_CSV_PROBLEM_SAMPLES + 5) {
+            writeln!(file, "bad host,Typo").unwrap();
+        }
+
+        let report = validate_hosts_csv(file.path()).unwrap();
+
+        assert_eq!(report.invalid_hostnames, MAX_CSV_PROBLEM_SAMPLES + 5);
+        assert_eq!(report.problems.len(), MAX_CSV_PROBLEM_SAMPLES);
+    }
+
+    #[test]
+    fn test_validate_hosts_csv_missing_file_is_error() {
+        let result = validate_hosts_csv(Path::new("does_not_exist_for_validation.csv"));
+        assert!(matches!(result, Err(AppError::IoError { .. })));
+    }
 }
@@ -4,6 +4,7 @@
 //! Isolated from command layer to enable testing and reuse.
 
 use crate::{Host, AppError};
+use std::collections::BTreeSet;
 use std::path::Path;
 
 /// Writes a list of hosts to a CSV file
@@ -19,12 +20,14 @@ use std::path::Path;
 /// # Side Effects
 /// - Creates or overwrites the file at `csv_path`
 /// - Creates parent directories if they don't exist
+/// - Hosts with a blank hostname are left out, so no empty record is written
 ///
 /// # CSV Format
+/// Known columns come first, followed by any `extra_columns` (sorted by name):
 /// ```csv
-/// hostname,description,last_connected
-/// server01.domain.com,Web Server,13/12/2025 14:30:00
-/// server02.domain.com,Database Server,
+/// hostname,description,last_connected,connect_username,group,connect_count,tags,sort_order
+/// server01.domain.com,Web Server,13/12/2025 14:30:00,,Web,12,prod;iis,1
+/// server02.domain.com,Database Server,,.\localadmin,,0,,
 /// ```
 pub fn write_hosts_to_csv(csv_path: &Path, hosts: &[Host]) -> Result<(), AppError> {
     use tracing::{debug, error};
@@ -49,8 +52,26 @@ pub fn write_hosts_to_csv(csv_path: &Path, hosts: &[Host]) -> Result<(), AppErro
             }
         })?;
 
+    // Unknown columns read from the file are written back after the known ones
+    let extra_names: BTreeSet<&str> = hosts
+        .iter()
+        .flat_map(|host| host.extra_columns.keys().map(String::as_str))
+        .collect();
+
     // Write header (includes last_connected for v1.2.0+ compatibility)
-    wtr.write_record(["hostname", "description", "last_connected"]).map_err(|e| {
+    let header = [
+        "hostname",
+        "description",
+        "last_connected",
+        "connect_username",
+        "group",
+        "connect_count",
+        "tags",
+        "sort_order",
+    ]
+        .into_iter()
+        .chain(extra_names.iter().copied());
+    wtr.write_record(header).map_err(|e| {
         error!(
             path = ?csv_path,
             error = %e,
@@ -63,12 +84,27 @@ pub fn write_hosts_to_csv(csv_path: &Path, hosts: &[Host]) -> Result<(), AppErro
     })?;
 
     // Write records (includes last_connected timestamp)
-    for host in hosts {
-        wtr.write_record([
-            &host.hostname,
-            &host.description,
-            host.last_connected.as_deref().unwrap_or(""),
-        ])
+    for host in hosts.iter().filter(|host| !host.hostname.trim().is_empty()) {
+        let connect_count = host.connect_count.to_string();
+        let tags = host.tags.join(";");
+        let sort_order = host.sort_order.map(|order| order.to_string()).unwrap_or_default();
+        let extra_values = extra_names
+            .iter()
+            .map(|name| host.extra_columns.get(*name).map(String::as_str).unwrap_or(""));
+        wtr.write_record(
+            [
+                host.hostname.as_str(),
+                host.description.as_str(),
+                host.last_connected.as_deref().unwrap_or(""),
+                host.connect_username.as_deref().unwrap_or(""),
+                host.group.as_deref().unwrap_or(""),
+                connect_count.as_str(),
+                tags.as_str(),
+                sort_order.as_str(),
+            ]
+            .into_iter()
+            .chain(extra_values),
+        )
         .map_err(|e| {
             error!(
                 path = ?csv_path,
@@ -119,11 +155,13 @@ mod tests {
                 hostname: "server01.domain.com".to_string(),
                 description: "Web Server".to_string(),
                 last_connected: None,
+                ..Default::default()
             },
             Host {
                 hostname: "server02.domain.com".to_string(),
                 description: "Database Server".to_string(),
                 last_connected: None,
+                ..Default::default()
             },
         ];
 
@@ -146,7 +184,7 @@ mod tests {
         assert!(result.is_ok());
 
         let content = std::fs::read_to_string(&csv_path).unwrap();
-        assert_eq!(content.trim(), "hostname,description,last_connected");
+        assert_eq!(content.trim(), "hostname,description,last_connected,connect_username,group,connect_count,tags,sort_order");
     }
 
     #[test]
@@ -158,6 +196,7 @@ mod tests {
             hostname: "server-01.domain.com".to_string(),
             description: "Server with \"quotes\" and, commas".to_string(),
             last_connected: None,
+            ..Default::default()
         }];
 
         let result = write_hosts_to_csv(&csv_path, &hosts);
@@ -168,4 +207,80 @@ mod tests {
         // CSV library should properly escape the description
         assert!(content.contains("Server with"));
     }
+
+    #[test]
+    fn test_write_preserves_extra_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("hosts.csv");
+
+        let mut tagged = Host {
+            hostname: "server01.domain.com".to_string(),
+            description: "Web Server".to_string(),
+            ..Default::default()
+        };
+        tagged.extra_columns.insert("rack".to_string(), "R12".to_string());
+        let plain = Host {
+            hostname: "server02.domain.com".to_string(),
+            description: "DB Server".to_string(),
+            ..Default::default()
+        };
+
+        write_hosts_to_csv(&csv_path, &[tagged, plain]).unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.starts_with("hostname,description,last_connected,connect_username,group,connect_count,tags,sort_order,rack\n"));
+        assert!(content.contains("server01.domain.com,Web Server,,,,0,,,R12"));
+        assert!(content.contains("server02.domain.com,DB Server,,,,0,,,\n"));
+
+        let loaded = crate::core::csv_reader::read_hosts_from_csv(&csv_path).unwrap();
+        assert_eq!(loaded[0].extra_columns.get("rack"), Some(&"R12".to_string()));
+        assert!(loaded[1].extra_columns.is_empty());
+    }
+
+    #[test]
+    fn test_write_and_read_multiple_tags() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("hosts.csv");
+
+        let hosts = vec![Host {
+            hostname: "sql01.domain.com".to_string(),
+            description: "SQL".to_string(),
+            tags: vec!["prod".to_string(), "sql".to_string(), "dmz".to_string()],
+            ..Default::default()
+        }];
+
+        write_hosts_to_csv(&csv_path, &hosts).unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains("sql01.domain.com,SQL,,,,0,prod;sql;dmz,\n"));
+
+        let loaded = crate::core::csv_reader::read_hosts_from_csv(&csv_path).unwrap();
+        assert_eq!(loaded[0].tags, vec!["prod", "sql", "dmz"]);
+    }
+
+    #[test]
+    fn test_write_skips_empty_hostname() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("hosts.csv");
+
+        let hosts = vec![
+            Host {
+                hostname: "server01.domain.com".to_string(),
+                description: "Web Server".to_string(),
+                ..Default::default()
+            },
+            Host {
+                hostname: "  ".to_string(),
+                description: "Blank".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        write_hosts_to_csv(&csv_path, &hosts).unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("Blank"));
+        assert!(content.ends_with("server01.domain.com,Web Server,,,,0,,\n"));
+    }
 }
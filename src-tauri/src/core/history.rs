@@ -0,0 +1,187 @@
+//! Connection history search
+//!
+//! Answers "when did I last connect to X" from `connection_history.jsonl`
+//! (one connection per line, in the same shape as a recent connection)
+//! together with the recent connections list.
+//!
+//! # Why this exists
+//! The recents list only keeps the last few connections, and the hosts list
+//! only the latest connection per saved host. The history keeps every
+//! connection, including ones to hosts since removed.
+//!
+//! # Why separate
+//! Takes the file path as a parameter so loading and matching can be unit
+//! tested against temporary files.
+
+use crate::infra::debug_log;
+use crate::{AppError, RecentConnection};
+use std::path::Path;
+
+/// Loads every entry from a connection history file
+///
+/// Blank lines are ignored and lines that can't be parsed (e.g. one cut
+/// short by a crash) are skipped with a warning.
+///
+/// # Returns
+/// * `Ok(Vec<RecentConnection>)` - Entries in file order (empty if the file is missing)
+/// * `Err(AppError)` - Failed to read the file
+pub fn load_connection_history_from(path: &Path) -> Result<Vec<RecentConnection>, AppError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = std::fs::read_to_string(path).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+
+    let mut entries = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RecentConnection>(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => debug_log(
+                "WARN",
+                "CONNECTION_HISTORY",
+                &format!("Skipping unparseable history line {} in {:?}", index + 1, path),
+                Some(&e.to_string()),
+            ),
+        }
+    }
+    Ok(entries)
+}
+
+/// Finds the connections whose hostname contains `query`
+///
+/// Matching is case-insensitive; a blank query matches everything. The same
+/// connection (hostname and timestamp) listed in more than one source is
+/// returned once.
+///
+/// # Arguments
+/// * `limit` - Maximum number of results (0 = unlimited)
+///
+/// # Returns
+/// Matching entries, newest first.
+pub fn search_connections(
+    entries: impl IntoIterator<Item = RecentConnection>,
+    query: &str,
+    limit: usize,
+) -> Vec<RecentConnection> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<RecentConnection> = Vec::new();
+    for entry in entries {
+        if !entry.hostname.to_lowercase().contains(&query) {
+            continue;
+        }
+        let duplicate = matches.iter().any(|seen| {
+            seen.timestamp == entry.timestamp && seen.hostname.eq_ignore_ascii_case(&entry.hostname)
+        });
+        if !duplicate {
+            matches.push(entry);
+        }
+    }
+
+    matches.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    if limit > 0 {
+        matches.truncate(limit);
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn entry(hostname: &str, timestamp: u64) -> RecentConnection {
+        RecentConnection {
+            hostname: hostname.to_string(),
+            description: String::new(),
+            timestamp,
+        }
+    }
+
+    fn write_history(lines: &[&str]) -> (TempDir, PathBuf) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("connection_history.jsonl");
+        std::fs::write(&path, lines.join("\n")).expect("Failed to write history");
+        (temp_dir, path)
+    }
+
+    fn hostnames(entries: &[RecentConnection]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.hostname.as_str()).collect()
+    }
+
+    #[test]
+    fn test_load_missing_history_is_empty() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("connection_history.jsonl");
+
+        let entries = load_connection_history_from(&path).expect("Load should succeed");
+
+        assert!(entries.is_empty());
+        assert!(search_connections(entries, "server", 10).is_empty());
+    }
+
+    #[test]
+    fn test_load_skips_blank_and_unparseable_lines() {
+        let (_temp_dir, path) = write_history(&[
+            r#"{"hostname":"web01.domain.com","description":"Web","timestamp":100}"#,
+            "",
+            r#"{"hostname":"sql01.dom"#,
+            r#"{"hostname":"sql01.domain.com","description":"","timestamp":200}"#,
+        ]);
+
+        let entries = load_connection_history_from(&path).expect("Load should succeed");
+
+        assert_eq!(hostnames(&entries), vec!["web01.domain.com", "sql01.domain.com"]);
+        assert_eq!(entries[0].description, "Web");
+    }
+
+    #[test]
+    fn test_search_matches_hostname_substring_case_insensitively() {
+        let (_temp_dir, path) = write_history(&[
+            r#"{"hostname":"web01.domain.com","description":"","timestamp":100}"#,
+            r#"{"hostname":"SQL01.domain.com","description":"","timestamp":200}"#,
+            r#"{"hostname":"web02.domain.com","description":"","timestamp":300}"#,
+        ]);
+        let entries = load_connection_history_from(&path).expect("Load should succeed");
+
+        let results = search_connections(entries.clone(), "sql", 0);
+        assert_eq!(hostnames(&results), vec!["SQL01.domain.com"]);
+
+        assert_eq!(search_connections(entries.clone(), "  ", 0).len(), 3);
+        assert!(search_connections(entries, "dc01", 0).is_empty());
+    }
+
+    #[test]
+    fn test_search_orders_newest_first_and_limits() {
+        let entries = vec![
+            entry("web01.domain.com", 100),
+            entry("web01.domain.com", 300),
+            entry("web02.domain.com", 200),
+        ];
+
+        let results = search_connections(entries.clone(), "web", 0);
+        let timestamps: Vec<u64> = results.iter().map(|entry| entry.timestamp).collect();
+        assert_eq!(timestamps, vec![300, 200, 100]);
+
+        let limited = search_connections(entries, "web", 2);
+        assert_eq!(hostnames(&limited), vec!["web01.domain.com", "web02.domain.com"]);
+        assert_eq!(limited[0].timestamp, 300);
+    }
+
+    #[test]
+    fn test_search_returns_connection_in_both_sources_once() {
+        let history = vec![entry("web01.domain.com", 100), entry("web01.domain.com", 200)];
+        let recents = vec![entry("WEB01.domain.com", 200)];
+
+        let results = search_connections(history.into_iter().chain(recents), "web01", 0);
+
+        assert_eq!(results.len(), 2);
+    }
+}
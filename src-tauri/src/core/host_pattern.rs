@@ -0,0 +1,205 @@
+//! Hostname range patterns
+//!
+//! Expands a hostname with a bracketed numeric range, such as
+//! `web[01-08].prod.local`, into one host entry per number.
+//!
+//! # Why this exists
+//! Server farms are usually numbered; adding `web01` to `web08` one at a
+//! time is slow and error-prone.
+//!
+//! # Why separate
+//! Parsing and expansion are pure functions, so zero-padding and the range
+//! checks can be unit tested.
+
+use crate::core::hosts::validate_host_address;
+use crate::{AppError, Host};
+
+/// Most hosts a single pattern may expand to
+pub const MAX_PATTERN_HOSTS: u64 = 256;
+
+fn invalid(pattern: &str, reason: String) -> AppError {
+    AppError::InvalidHostname {
+        hostname: pattern.to_string(),
+        reason,
+    }
+}
+
+/// Parses one bound of a range, e.g. `01`
+fn parse_bound(pattern: &str, bound: &str) -> Result<u64, AppError> {
+    if bound.is_empty() || !bound.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid(
+            pattern,
+            format!("'{}' is not a number; expected a range such as [01-08]", bound),
+        ));
+    }
+    bound
+        .parse()
+        .map_err(|_| invalid(pattern, format!("'{}' is too large", bound)))
+}
+
+/// Expands a hostname pattern into hostnames
+///
+/// The range `[start-end]` is replaced by each number from `start` to `end`,
+/// padded with zeros to the width of `start` (`[01-10]` gives `01` to `10`,
+/// `[8-12]` gives `8` to `12`). Only one range is allowed per pattern. A
+/// pattern without a range, or a bracketed IPv6 address such as
+/// `[fe80::1]:3390`, is a single host. Every resulting hostname is validated.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Hostnames in ascending order
+/// * `Err(AppError::InvalidHostname)` - Malformed range, more than one range,
+///   more than `MAX_PATTERN_HOSTS` hosts, or an invalid resulting hostname
+pub fn expand_host_pattern(pattern: &str) -> Result<Vec<String>, AppError> {
+    let pattern = pattern.trim();
+    let is_ipv6_literal = pattern.starts_with('[')
+        && pattern
+            .split_once(']')
+            .is_some_and(|(address, _)| address.contains(':'));
+
+    let Some(open) = pattern.find('[').filter(|_| !is_ipv6_literal) else {
+        if pattern.contains(']') && !is_ipv6_literal {
+            return Err(invalid(pattern, "']' without a matching '['".to_string()));
+        }
+        validate_host_address(pattern)?;
+        return Ok(vec![pattern.to_string()]);
+    };
+
+    let close = pattern[open..]
+        .find(']')
+        .map(|offset| open + offset)
+        .ok_or_else(|| invalid(pattern, "'[' without a matching ']'".to_string()))?;
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    if suffix.contains('[') || suffix.contains(']') || prefix.contains(']') {
+        return Err(invalid(
+            pattern,
+            "Only one [start-end] range is supported per pattern".to_string(),
+        ));
+    }
+
+    let range = &pattern[open + 1..close];
+    let (start, end) = range.split_once('-').ok_or_else(|| {
+        invalid(pattern, format!("'[{}]' is not a range; expected [start-end] such as [01-08]", range))
+    })?;
+    let width = start.len();
+    let (start, end) = (parse_bound(pattern, start)?, parse_bound(pattern, end)?);
+    if start > end {
+        return Err(invalid(
+            pattern,
+            format!("Range start {} is greater than its end {}", start, end),
+        ));
+    }
+    if end - start >= MAX_PATTERN_HOSTS {
+        return Err(invalid(
+            pattern,
+            format!(
+                "The range expands to {} hosts; the most allowed is {}",
+                end - start + 1,
+                MAX_PATTERN_HOSTS
+            ),
+        ));
+    }
+
+    (start..=end)
+        .map(|number| {
+            let hostname = format!("{}{:0width$}{}", prefix, number, suffix, width = width);
+            validate_host_address(&hostname)?;
+            Ok(hostname)
+        })
+        .collect()
+}
+
+/// Builds a host entry for each hostname a pattern expands to
+///
+/// # Arguments
+/// * `pattern` - Hostname with an optional range, e.g. `web[01-08].prod.local`
+/// * `description` - Description given to every host (empty when omitted)
+pub fn hosts_from_pattern(pattern: &str, description: Option<&str>) -> Result<Vec<Host>, AppError> {
+    let description = description.unwrap_or("");
+
+    Ok(expand_host_pattern(pattern)?
+        .into_iter()
+        .map(|hostname| Host {
+            hostname,
+            description: description.to_string(),
+            ..Default::default()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_zero_padded_range() {
+        let hostnames = expand_host_pattern("web[01-08].prod.local").unwrap();
+
+        assert_eq!(hostnames.len(), 8);
+        assert_eq!(hostnames[0], "web01.prod.local");
+        assert_eq!(hostnames[7], "web08.prod.local");
+    }
+
+    #[test]
+    fn test_expand_pads_to_start_width() {
+        assert_eq!(
+            expand_host_pattern("web[08-11].prod.local").unwrap(),
+            vec!["web08.prod.local", "web09.prod.local", "web10.prod.local", "web11.prod.local"]
+        );
+        assert_eq!(
+            expand_host_pattern("app[8-10]").unwrap(),
+            vec!["app8", "app9", "app10"]
+        );
+        assert_eq!(expand_host_pattern("sql[007-008]").unwrap(), vec!["sql007", "sql008"]);
+    }
+
+    #[test]
+    fn test_expand_without_range_is_single_host() {
+        assert_eq!(
+            expand_host_pattern(" server01.domain.com ").unwrap(),
+            vec!["server01.domain.com"]
+        );
+        assert_eq!(expand_host_pattern("server01:3390").unwrap(), vec!["server01:3390"]);
+        assert_eq!(expand_host_pattern("[fe80::1]:3390").unwrap(), vec!["[fe80::1]:3390"]);
+        assert!(expand_host_pattern("bad host!").is_err());
+    }
+
+    #[test]
+    fn test_expand_rejects_invalid_ranges() {
+        for pattern in [
+            "web[08-01].prod.local",
+            "web[a-c].prod.local",
+            "web[01].prod.local",
+            "web[-5].prod.local",
+            "web[01-08.prod.local",
+            "web01-08].prod.local",
+            "web[1-1000].prod.local",
+        ] {
+            assert!(expand_host_pattern(pattern).is_err(), "pattern: {:?}", pattern);
+        }
+
+        let err = expand_host_pattern("web[08-01].prod.local").unwrap_err();
+        assert!(err.to_string().contains("web[08-01].prod.local"));
+    }
+
+    #[test]
+    fn test_expand_rejects_multiple_ranges() {
+        let err = expand_host_pattern("rack[1-2]-web[01-04].prod.local").unwrap_err();
+
+        match err {
+            AppError::InvalidHostname { reason, .. } => {
+                assert!(reason.contains("Only one [start-end] range"), "reason: {}", reason);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hosts_from_pattern_applies_description() {
+        let hosts = hosts_from_pattern("web[1-2].prod.local", Some("Web farm")).unwrap();
+
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[1].hostname, "web2.prod.local");
+        assert!(hosts.iter().all(|host| host.description == "Web farm"));
+        assert!(hosts_from_pattern("web1", None).unwrap()[0].description.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 //! Host operations - core business logic for host management
 //!
-//! Handles all host CRUD operations, delegating to CSV reader/writer for persistence.
+//! Handles all host CRUD operations, delegating persistence to the hosts store
+//! (`hosts.csv` or `hosts.json`, see `core::hosts_store`).
 //!
 //! # Why this exists
 //! Encapsulates host management business logic separate from the command layer.
@@ -14,32 +15,152 @@
 //! - Clear separation of concerns
 
 use crate::{Host, AppError};
-use crate::core::{csv_reader, csv_writer};
+use crate::core::clock::{Clock, SystemClock};
+use crate::core::hosts_store::{self, StorageFormat};
 use crate::infra::{debug_log, get_hosts_csv_path};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 
-/// Reads all hosts from the CSV file.
+/// Version of the host list, bumped on every write to hosts.csv
+///
+/// Sent with `hosts-updated` so windows that already loaded this version can
+/// skip reloading the CSV.
+static HOSTS_VERSION: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the current host list version.
+pub fn hosts_version() -> u64 {
+    HOSTS_VERSION.load(Ordering::SeqCst)
+}
+
+/// Serializes tests that write through `write_hosts`, so version assertions are exact
+///
+/// Every test in the crate that writes a hosts file must hold it, not only
+/// the ones in this module.
+#[cfg(test)]
+pub(crate) fn lock_version() -> std::sync::MutexGuard<'static, ()> {
+    static VERSION_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    VERSION_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Bumps the host list version after the hosts file was replaced wholesale
+/// (e.g. by restoring a backup) rather than through `write_hosts`.
+pub fn mark_hosts_replaced() {
+    HOSTS_VERSION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Reads the hosts file at `path`, in the format given by its extension.
+fn read_hosts(path: &Path) -> Result<Vec<Host>, AppError> {
+    hosts_store::store_for_path(path).load()
+}
+
+/// Writes the hosts file and bumps the host list version.
+///
+/// Every host mutation goes through here so the version can't be missed.
+fn write_hosts(path: &Path, hosts: &[Host]) -> Result<(), AppError> {
+    hosts_store::store_for_path(path).save(hosts)?;
+    HOSTS_VERSION.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Copies the host list into the given format and makes it the active store.
+///
+/// # Why this exists
+/// One-time conversion between `hosts.csv` and `hosts.json`. The file in the
+/// old format is left in place as a backup. Nothing is copied when `to` is
+/// already the active format, since `from` is then the stale backup.
+///
+/// # Arguments
+/// * `from` / `to` - Paths of the current and new hosts files
+/// * `settings_path` - settings.json, updated so `storage_format` matches `to`
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts converted
+/// * `Err(AppError)` - Read, write or settings errors
+pub fn convert_hosts_storage_at(from: &Path, to: &Path, settings_path: &Path) -> Result<usize, AppError> {
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        &format!("Converting hosts from {:?} to {:?}", from, to),
+        None,
+    );
+
+    let mut settings = crate::core::settings::load_settings_from(settings_path)?;
+    let target = StorageFormat::from_path(to);
+    if settings.storage_format == target {
+        debug_log(
+            "INFO",
+            "HOST_OPERATIONS",
+            &format!("Hosts are already stored as {:?}, nothing to convert", target),
+            None,
+        );
+        return Ok(read_hosts(to)?.len());
+    }
+
+    let count = hosts_store::copy_hosts(
+        hosts_store::store_for_path(from).as_ref(),
+        hosts_store::store_for_path(to).as_ref(),
+    )?;
+
+    settings.storage_format = target;
+    crate::core::settings::save_settings_to(settings_path, &settings)?;
+
+    HOSTS_VERSION.fetch_add(1, Ordering::SeqCst);
+    Ok(count)
+}
+
+/// Converts the host list to `format` and switches the `storage_format` setting.
+///
+/// See [`convert_hosts_storage_at`]. Hosts are read from the active hosts file.
+pub fn convert_hosts_storage(format: StorageFormat) -> Result<usize, AppError> {
+    let settings_path = crate::infra::get_settings_path().map_err(|e| AppError::Other {
+        message: format!("Failed to get settings path: {}", e),
+        source: None,
+    })?;
+
+    convert_hosts_storage_at(
+        &hosts_store::active_hosts_path()?,
+        &hosts_store::hosts_path_for(format)?,
+        &settings_path,
+    )
+}
+
+/// How scanned hosts are applied to hosts.csv
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanCommitMode {
+    /// Replace the whole host list with the scanned hosts
+    Replace,
+    /// Add scanned hosts to the existing list, updating hosts that already exist
+    Merge,
+}
+
+/// Reads all hosts from the active hosts file (hosts.csv or hosts.json).
 ///
 /// # Why this exists
-/// Provides a simple interface for retrieving all hosts. Delegates to csv_reader
-/// but adds logging and error handling appropriate for the core layer.
+/// Provides a simple interface for retrieving all hosts. Delegates to the
+/// hosts store but adds logging and error handling appropriate for the core layer.
 ///
 /// # Returns
-/// * `Ok(Vec<Host>)` - All hosts from CSV (empty vec if file doesn't exist)
+/// * `Ok(Vec<Host>)` - All hosts (empty vec if file doesn't exist)
 /// * `Err(AppError)` - Failed to read or parse CSV
 ///
+/// Hosts with a `sort_order` come first, in that order; the rest follow in
+/// file order. The order is for display only: code that writes the list back
+/// reads it with `read_hosts` so the file keeps its own order.
+///
 /// # Side Effects
 /// - Reads hosts.csv from disk
 pub fn get_all_hosts() -> Result<Vec<Host>, AppError> {
     debug_log("DEBUG", "HOST_OPERATIONS", "Reading all hosts", None);
     
-    let path = get_hosts_csv_path()
-        .map_err(|e| AppError::Other {
-            message: format!("Failed to get CSV path: {}", e),
-            source: None,
-        })?;
+    let path = hosts_store::active_hosts_path()?;
     
-    let hosts = csv_reader::read_hosts_from_csv(&path)?;
+    let mut hosts = read_hosts(&path)?;
+    sort_hosts_by_order(&mut hosts);
     
     debug_log(
         "DEBUG",
@@ -51,6 +172,67 @@ pub fn get_all_hosts() -> Result<Vec<Host>, AppError> {
     Ok(hosts)
 }
 
+/// Orders hosts by `sort_order`, keeping file order for ties and unset hosts
+pub fn sort_hosts_by_order(hosts: &mut [Host]) {
+    // Stable sort: `None` sorts after every `Some`
+    hosts.sort_by_key(|host| (host.sort_order.is_none(), host.sort_order));
+}
+
+/// Applies a manual ordering to the hosts in the given file.
+///
+/// The listed hosts come first, in the given order, followed by the rest in
+/// their current order; every host is then numbered from 0. Hostnames match
+/// case-insensitively and repeats are ignored. Only `sort_order` changes, the
+/// file keeps its row order.
+///
+/// # Returns
+/// * `Ok(())` - Order saved
+/// * `Err(AppError::HostNotFound)` - A listed hostname isn't in the file
+/// * `Err(AppError)` - CSV read/write errors
+pub fn reorder_hosts_at(path: &Path, ordered_hostnames: &[String]) -> Result<(), AppError> {
+    let mut hosts = read_hosts(path)?;
+
+    let mut current: Vec<usize> = (0..hosts.len()).collect();
+    current.sort_by_key(|&index| (hosts[index].sort_order.is_none(), hosts[index].sort_order));
+
+    let mut order: Vec<usize> = Vec::with_capacity(hosts.len());
+    for hostname in ordered_hostnames {
+        let index = hosts
+            .iter()
+            .position(|host| host.hostname.eq_ignore_ascii_case(hostname.trim()))
+            .ok_or_else(|| AppError::HostNotFound {
+                hostname: hostname.to_string(),
+            })?;
+        if !order.contains(&index) {
+            order.push(index);
+        }
+    }
+    let listed = order.clone();
+    order.extend(current.into_iter().filter(|index| !listed.contains(index)));
+
+    for (position, index) in order.into_iter().enumerate() {
+        hosts[index].sort_order = Some(position as u32);
+    }
+
+    write_hosts(path, &hosts)
+}
+
+/// Applies a manual ordering to the host list.
+///
+/// # Side Effects
+/// - Reads and rewrites the hosts file
+pub fn reorder_hosts(ordered_hostnames: &[String]) -> Result<(), AppError> {
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        &format!("Reordering hosts ({} listed first)", ordered_hostnames.len()),
+        None,
+    );
+
+    let path = hosts_store::active_hosts_path()?;
+    reorder_hosts_at(&path, ordered_hostnames)
+}
+
 /// Searches hosts by hostname or description (case-insensitive).
 ///
 /// # Why this exists
@@ -81,6 +263,182 @@ pub fn search_hosts(query: &str) -> Result<Vec<Host>, AppError> {
     Ok(filtered)
 }
 
+/// Normalizes tags: trimmed, lowercase, no empties or duplicates (first occurrence kept).
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// What to do with a host description longer than `description_max_length`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DescriptionOverflow {
+    /// Cut the description down and end it with an ellipsis
+    #[default]
+    Truncate,
+    /// Refuse to save the host
+    Reject,
+}
+
+/// Cleans up a host description before it is saved.
+///
+/// Newlines are kept (a `\r\n` pair becomes `\n`), each run of other control
+/// whitespace such as tabs becomes a single space, and remaining control
+/// characters are removed. Descriptions longer than `max_length` characters are
+/// truncated to fit, ending in `…`, or rejected, depending on `overflow`.
+///
+/// # Arguments
+/// * `max_length` - Maximum length in characters (0 = unlimited)
+///
+/// # Returns
+/// * `Ok(String)` - The description to store
+/// * `Err(AppError)` - Too long and `overflow` is `Reject`
+pub fn sanitize_description(
+    description: &str,
+    max_length: u32,
+    overflow: DescriptionOverflow,
+) -> Result<String, AppError> {
+    let mut cleaned = String::with_capacity(description.len());
+    let mut chars = description.chars().peekable();
+    let mut in_whitespace_run = false;
+    while let Some(c) = chars.next() {
+        let is_newline = c == '\n' || (c == '\r' && chars.peek() == Some(&'\n'));
+        if c.is_control() && c.is_whitespace() && !is_newline {
+            if !in_whitespace_run {
+                cleaned.push(' ');
+            }
+            in_whitespace_run = true;
+            continue;
+        }
+
+        in_whitespace_run = false;
+        if c == '\n' || !c.is_control() {
+            cleaned.push(c);
+        }
+    }
+
+    let max_length = max_length as usize;
+    let length = cleaned.chars().count();
+    if max_length == 0 || length <= max_length {
+        return Ok(cleaned);
+    }
+
+    match overflow {
+        DescriptionOverflow::Truncate => {
+            let mut truncated: String = cleaned.chars().take(max_length - 1).collect();
+            truncated.push('…');
+            Ok(truncated)
+        }
+        DescriptionOverflow::Reject => Err(AppError::Other {
+            message: format!(
+                "Description is {} characters long; the maximum is {}",
+                length, max_length
+            ),
+            source: None,
+        }),
+    }
+}
+
+/// Parses the semicolon-delimited hosts.csv `tags` column.
+pub fn parse_tags(value: &str) -> Vec<String> {
+    let tags: Vec<String> = value.split(';').map(str::to_string).collect();
+    normalize_tags(&tags)
+}
+
+/// Filters hosts by tag (case-insensitive).
+///
+/// # Arguments
+/// * `tags` - Tags to look for; an empty list matches every host
+/// * `match_all` - `true` requires every tag (AND), `false` any of them (OR)
+pub fn filter_hosts_by_tags(hosts: Vec<Host>, tags: &[String], match_all: bool) -> Vec<Host> {
+    let wanted = normalize_tags(tags);
+    if wanted.is_empty() {
+        return hosts;
+    }
+
+    hosts
+        .into_iter()
+        .filter(|host| {
+            let has = |tag: &String| host.tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+            if match_all {
+                wanted.iter().all(has)
+            } else {
+                wanted.iter().any(has)
+            }
+        })
+        .collect()
+}
+
+/// Filters hosts whose hostname or description matches a regular expression.
+///
+/// The pattern is case-insensitive unless it turns that off itself (`(?-i)`).
+///
+/// # Returns
+/// * `Ok(Vec<Host>)` - Matching hosts, in their original order
+/// * `Err(AppError::Other)` - The pattern isn't a valid regular expression
+pub fn filter_hosts_by_regex(hosts: Vec<Host>, pattern: &str) -> Result<Vec<Host>, AppError> {
+    let regex = regex::RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| AppError::Other {
+            message: format!("Invalid search pattern '{}': {}", pattern, e),
+            source: None,
+        })?;
+
+    Ok(hosts
+        .into_iter()
+        .filter(|host| regex.is_match(&host.hostname) || regex.is_match(&host.description))
+        .collect())
+}
+
+/// Searches hosts with a regular expression.
+///
+/// See [`filter_hosts_by_regex`].
+pub fn search_hosts_regex(pattern: &str) -> Result<Vec<Host>, AppError> {
+    let hosts = get_all_hosts()?;
+    filter_hosts_by_regex(hosts, pattern)
+}
+
+/// Searches hosts.csv for hosts carrying the given tags.
+///
+/// See [`filter_hosts_by_tags`].
+pub fn search_hosts_by_tags(tags: &[String], match_all: bool) -> Result<Vec<Host>, AppError> {
+    let hosts = get_all_hosts()?;
+    Ok(filter_hosts_by_tags(hosts, tags, match_all))
+}
+
+/// Returns the hosts that have never been connected to, sorted by hostname.
+///
+/// A host qualifies when it has no (non-blank) `last_connected` time and a
+/// `connect_count` of 0. Sorting ignores case.
+pub fn never_connected_hosts(hosts: Vec<Host>) -> Vec<Host> {
+    let mut never = Vec::new();
+    for host in hosts {
+        let connected = host
+            .last_connected
+            .as_deref()
+            .is_some_and(|value| !value.trim().is_empty());
+        if !connected && host.connect_count == 0 {
+            never.push(host);
+        }
+    }
+    never.sort_by_cached_key(|host| host.hostname.to_lowercase());
+    never
+}
+
+/// Lists the hosts in hosts.csv that have never been connected to.
+///
+/// See [`never_connected_hosts`].
+pub fn get_never_connected_hosts() -> Result<Vec<Host>, AppError> {
+    Ok(never_connected_hosts(get_all_hosts()?))
+}
+
 /// Saves or updates a host (upsert operation).
 ///
 /// # Why this exists
@@ -118,11 +476,206 @@ pub fn upsert_host(host: Host) -> Result<(), AppError> {
         });
     }
 
-    // Read existing hosts
-    let mut hosts = get_all_hosts()?;
+    let settings = crate::core::settings::load_settings().unwrap_or_default();
+    let host = prepare_host(host, &settings)?;
+
+    // Read existing hosts in file order; sorting is only for display
+    let path = hosts_store::active_hosts_path()?;
+    let mut hosts = read_hosts(&path)?;
+
+    merge_host(&mut hosts, host);
+
+    // Write back to CSV
+    write_hosts(&path, &hosts)?;
+
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        "Host upserted successfully",
+        None,
+    );
+
+    Ok(())
+}
+
+/// Splits an explicit port off a host entry
+///
+/// Accepts `host:port`, `[ipv6]:port` and `[ipv6]`. A value with more than one
+/// colon outside brackets is an IPv6 literal, so it has no port. A suffix that
+/// isn't a valid port (1-65535) is left as part of the host.
+///
+/// # Returns
+/// * `(host, port)` - The host without brackets, and the port if one was given
+pub fn split_host_port(value: &str) -> (&str, Option<u16>) {
+    let value = value.trim();
+    let parse_port = |port: &str| port.parse::<u16>().ok().filter(|port| *port > 0);
+
+    if let Some(rest) = value.strip_prefix('[') {
+        if let Some((host, after)) = rest.split_once(']') {
+            if after.is_empty() {
+                return (host, None);
+            }
+            if let Some(port) = after.strip_prefix(':').and_then(parse_port) {
+                return (host, Some(port));
+            }
+        }
+        return (value, None);
+    }
+
+    match value.split_once(':') {
+        Some((host, port)) if !port.contains(':') => match parse_port(port) {
+            Some(port) => (host, Some(port)),
+            None => (value, None),
+        },
+        _ => (value, None),
+    }
+}
+
+/// Formats a host and optional port as an address
+///
+/// IPv6 literals are bracketed when a port follows, so the result can be
+/// split again with [`split_host_port`].
+pub fn format_host_port(host: &str, port: Option<u16>) -> String {
+    match port {
+        Some(port) if host.contains(':') => format!("[{}]:{}", host, port),
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+/// Returns whether a hostname points back at the local machine
+///
+/// Matches `localhost` (and `*.localhost`), the 127.0.0.0/8 range, `::1`
+/// (bracketed or IPv4-mapped too) and the unspecified addresses `0.0.0.0`
+/// and `::`. Connecting to any of these loops RDP back to this computer.
+pub fn is_loopback_hostname(hostname: &str) -> bool {
+    let name = normalize_hostname(hostname);
+    let (name, _) = split_host_port(&name);
+
+    if name == "localhost" || name.ends_with(".localhost") || name == "localhost.localdomain" {
+        return true;
+    }
+
+    match name.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(ip)) => ip.is_loopback() || ip.is_unspecified(),
+        Ok(std::net::IpAddr::V6(ip)) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.to_ipv4_mapped().is_some_and(|ip| ip.is_loopback())
+        }
+        Err(_) => false,
+    }
+}
+
+/// Outcome of saving a single host from the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SaveHostOutcome {
+    /// Whether the host was written
+    pub saved: bool,
+    /// The hostname is a loopback address (see [`is_loopback_hostname`])
+    pub loopback_warning: bool,
+}
+
+/// Saves a host, holding back loopback hostnames until confirmed
+///
+/// A loopback hostname is usually saved by accident, so without
+/// `confirm_loopback` nothing is written and the outcome carries the warning
+/// for the UI to confirm. Other hosts are saved as by [`upsert_host`].
+pub fn save_host_checked(host: Host, confirm_loopback: bool) -> Result<SaveHostOutcome, AppError> {
+    let loopback = is_loopback_hostname(&host.hostname);
+    if loopback && !confirm_loopback {
+        debug_log(
+            "WARN",
+            "HOST_OPERATIONS",
+            &format!("Not saving loopback host {} until confirmed", host.hostname),
+            None,
+        );
+        return Ok(SaveHostOutcome {
+            saved: false,
+            loopback_warning: true,
+        });
+    }
+
+    upsert_host(host)?;
+    Ok(SaveHostOutcome {
+        saved: true,
+        loopback_warning: loopback,
+    })
+}
+
+/// Normalizes tags and sanitizes the description before a host is saved
+fn prepare_host(mut host: Host, settings: &crate::core::settings::AppSettings) -> Result<Host, AppError> {
+    host.tags = normalize_tags(&host.tags);
+    host.description = sanitize_description(
+        &host.description,
+        settings.description_max_length,
+        settings.description_overflow,
+    )?;
+    Ok(host)
+}
+
+/// Adds or updates several hosts with a single write to the given file.
+///
+/// Each host goes through the same checks as [`upsert_host`]; nothing is
+/// written if any of them fails.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts added or updated
+/// * `Err(AppError)` - A host failed validation, or the file couldn't be read or written
+pub fn upsert_hosts_at(
+    path: &Path,
+    hosts: Vec<Host>,
+    settings: &crate::core::settings::AppSettings,
+) -> Result<usize, AppError> {
+    let mut prepared = Vec::with_capacity(hosts.len());
+    for host in hosts {
+        if host.hostname.trim().is_empty() {
+            return Err(AppError::InvalidHostname {
+                hostname: host.hostname,
+                reason: "Hostname cannot be empty".to_string(),
+            });
+        }
+        prepared.push(prepare_host(host, settings)?);
+    }
+
+    let count = prepared.len();
+    let mut existing = read_hosts(path)?;
+    for host in prepared {
+        merge_host(&mut existing, host);
+    }
+    write_hosts(path, &existing)?;
+
+    Ok(count)
+}
+
+/// Adds or updates several hosts in the active hosts file.
+///
+/// See [`upsert_hosts_at`].
+pub fn upsert_hosts(hosts: Vec<Host>) -> Result<usize, AppError> {
+    let settings = crate::core::settings::load_settings().unwrap_or_default();
+    let path = hosts_store::active_hosts_path()?;
+
+    upsert_hosts_at(&path, hosts, &settings)
+}
+
+/// Takes an optional text field from an edit: `None` (not sent) keeps the
+/// existing value, and an empty value clears it
+fn merge_optional_text(incoming: Option<String>, existing: &mut Option<String>) -> Option<String> {
+    match incoming {
+        None => existing.take(),
+        Some(value) if value.trim().is_empty() => None,
+        Some(value) => Some(value),
+    }
+}
 
-    // Upsert logic: update existing host or add new one
-    // Hostname is the unique identifier for deduplication
+/// Updates the host with the same hostname, or appends it
+///
+/// Hostname is the unique identifier for deduplication. Fields the incoming
+/// host doesn't send are kept from the existing entry, while an explicitly
+/// empty `connect_username`, `group` or extra column value clears it. Usage
+/// tracking and ordering (`last_connected`, `connect_count`, `sort_order`)
+/// are never sent by the editor and are always kept when missing.
+fn merge_host(hosts: &mut Vec<Host>, mut host: Host) {
     if let Some(idx) = hosts.iter().position(|h| h.hostname == host.hostname) {
         debug_log(
             "DEBUG",
@@ -130,6 +683,32 @@ pub fn upsert_host(host: Host) -> Result<(), AppError> {
             &format!("Updating existing host: {}", host.hostname),
             None,
         );
+        // The edit form only sends hostname and description; keep fields it doesn't know about
+        let existing = &mut hosts[idx];
+        let mut extra_columns = std::mem::take(&mut existing.extra_columns);
+        for (column, value) in std::mem::take(&mut host.extra_columns) {
+            if value.is_empty() {
+                extra_columns.remove(&column);
+            } else {
+                extra_columns.insert(column, value);
+            }
+        }
+        host.extra_columns = extra_columns;
+        if host.last_connected.is_none() {
+            host.last_connected = existing.last_connected.take();
+        }
+        host.connect_username = merge_optional_text(host.connect_username, &mut existing.connect_username);
+        host.group = merge_optional_text(host.group, &mut existing.group);
+        if host.connect_count == 0 {
+            host.connect_count = existing.connect_count;
+        }
+        // The editor doesn't send tags; they are changed with `set_host_tags`
+        if host.tags.is_empty() {
+            host.tags = std::mem::take(&mut existing.tags);
+        }
+        if host.sort_order.is_none() {
+            host.sort_order = existing.sort_order;
+        }
         hosts[idx] = host;
     } else {
         debug_log(
@@ -140,24 +719,95 @@ pub fn upsert_host(host: Host) -> Result<(), AppError> {
         );
         hosts.push(host);
     }
+}
 
-    // Write back to CSV
-    let path = get_hosts_csv_path()
-        .map_err(|e| AppError::Other {
-            message: format!("Failed to get CSV path: {}", e),
-            source: None,
-        })?;
-    
-    csv_writer::write_hosts_to_csv(&path, &hosts)?;
+/// Imports hosts from a directory of `.rdp` files into the given CSV file.
+///
+/// # Why this exists
+/// Lets users migrating from a folder of saved connections bring them in
+/// without retyping. Path-based so it can be tested against temporary files.
+///
+/// # Arguments
+/// * `path` - Path to hosts.csv
+/// * `dir` - Directory to scan (not recursive)
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts imported (added or updated)
+/// * `Err(AppError)` - The directory or CSV could not be read or written
+///
+/// # Side Effects
+/// - Writes hosts.csv once, if anything was imported
+/// - Logs a warning for each `.rdp` file that can't be read or parsed
+pub fn import_rdp_directory_to(path: &Path, dir: &Path) -> Result<usize, AppError> {
+    let io_error = |e: std::io::Error| AppError::IoError {
+        path: dir.to_string_lossy().to_string(),
+        source: e,
+    };
+
+    let mut rdp_files: Vec<_> = std::fs::read_dir(dir)
+        .map_err(io_error)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| {
+            file.is_file()
+                && file
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("rdp"))
+        })
+        .collect();
+    rdp_files.sort();
+
+    let mut hosts = read_hosts(path)?;
+    let mut imported = 0;
+    for file in rdp_files {
+        let name = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let parsed = std::fs::read(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| {
+                let content = crate::core::rdp::decode_rdp_bytes(&bytes);
+                crate::core::rdp::host_from_rdp_content(&content, &name).map_err(|e| match e {
+                    AppError::RdpFileError { reason, .. } => reason,
+                    other => other.to_string(),
+                })
+            });
+
+        match parsed {
+            Ok(host) => {
+                merge_host(&mut hosts, host);
+                imported += 1;
+            }
+            Err(reason) => debug_log(
+                "WARN",
+                "HOST_OPERATIONS",
+                &format!("Skipping RDP file {:?}", file),
+                Some(&reason),
+            ),
+        }
+    }
+
+    if imported > 0 {
+        write_hosts(path, &hosts)?;
+    }
 
     debug_log(
         "INFO",
         "HOST_OPERATIONS",
-        "Host upserted successfully",
+        &format!("Imported {} hosts from {:?}", imported, dir),
         None,
     );
 
-    Ok(())
+    Ok(imported)
+}
+
+/// Imports hosts from a directory of `.rdp` files into hosts.csv.
+///
+/// See [`import_rdp_directory_to`].
+pub fn import_rdp_directory(dir: &Path) -> Result<usize, AppError> {
+    let path = hosts_store::active_hosts_path()?;
+
+    import_rdp_directory_to(&path, dir)
 }
 
 /// Deletes a host by hostname.
@@ -188,20 +838,15 @@ pub fn delete_host(hostname: &str) -> Result<(), AppError> {
         None,
     );
 
-    // Read all hosts and filter out the one to delete
-    let hosts: Vec<Host> = get_all_hosts()?
+    // Read all hosts in file order and filter out the one to delete
+    let path = hosts_store::active_hosts_path()?;
+    let hosts: Vec<Host> = read_hosts(&path)?
         .into_iter()
         .filter(|h| h.hostname != hostname)
         .collect();
 
     // Write back to CSV
-    let path = get_hosts_csv_path()
-        .map_err(|e| AppError::Other {
-            message: format!("Failed to get CSV path: {}", e),
-            source: None,
-        })?;
-    
-    csv_writer::write_hosts_to_csv(&path, &hosts)?;
+    write_hosts(&path, &hosts)?;
 
     debug_log(
         "INFO",
@@ -213,14 +858,105 @@ pub fn delete_host(hostname: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Deletes all hosts.
-///
-/// # Why this exists
-/// Provides a safe way to clear all hosts. Used during application reset.
+/// Seconds a delete-all confirmation token stays valid
+pub const DELETE_ALL_TOKEN_TTL_SECS: u64 = 60;
+
+/// Single-use confirmation token guarding `delete_all_hosts_confirmed`
 ///
-/// # Returns
-/// * `Ok(())` - All hosts deleted successfully
-/// * `Err(AppError)` - Persistence error
+/// The frontend must request a token and pass it back, so a stray
+/// `delete_all_hosts` call can't wipe the list on its own.
+#[derive(Debug, Default)]
+pub struct DeleteAllGuard {
+    /// Outstanding token and the time (Unix seconds) it was issued
+    pending: Option<(String, u64)>,
+}
+
+impl DeleteAllGuard {
+    /// Stores `token` as the outstanding token, replacing any earlier one
+    pub fn issue(&mut self, token: String, now: u64) -> String {
+        self.pending = Some((token.clone(), now));
+        token
+    }
+
+    /// Checks and consumes the outstanding token
+    ///
+    /// The token is used up even when the check fails, so a wrong guess
+    /// requires requesting a new token.
+    ///
+    /// # Returns
+    /// * `Ok(())` - `token` matches and hasn't expired
+    /// * `Err(AppError::Other)` - No token requested, token mismatch, or expired
+    pub fn redeem(&mut self, token: &str, now: u64) -> Result<(), AppError> {
+        let reject = |message: &str| {
+            Err(AppError::Other {
+                message: format!("Delete all hosts not confirmed: {}", message),
+                source: None,
+            })
+        };
+
+        let Some((expected, issued_at)) = self.pending.take() else {
+            return reject("no confirmation token was requested");
+        };
+        if expected != token {
+            return reject("confirmation token does not match");
+        }
+        if now.saturating_sub(issued_at) > DELETE_ALL_TOKEN_TTL_SECS {
+            return reject("confirmation token has expired");
+        }
+
+        Ok(())
+    }
+}
+
+static DELETE_ALL_GUARD: Mutex<DeleteAllGuard> = Mutex::new(DeleteAllGuard { pending: None });
+
+/// Generates an unpredictable token from the process-random hasher keys
+fn new_delete_all_token() -> String {
+    use std::hash::{BuildHasher, Hasher};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::SeqCst));
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    format!("{:016x}", hasher.finish())
+}
+
+/// Issues a new delete-all confirmation token, valid for `DELETE_ALL_TOKEN_TTL_SECS`.
+pub fn request_delete_all_token() -> String {
+    let mut guard = DELETE_ALL_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    guard.issue(new_delete_all_token(), crate::core::idle::now_secs())
+}
+
+/// Deletes all hosts after validating a token from `request_delete_all_token`.
+///
+/// # Returns
+/// * `Ok(())` - All hosts deleted
+/// * `Err(AppError)` - Token missing, mismatched or expired (nothing deleted), or persistence error
+pub fn delete_all_hosts_confirmed(token: &str) -> Result<(), AppError> {
+    DELETE_ALL_GUARD
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .redeem(token, crate::core::idle::now_secs())
+        .inspect_err(|e| {
+            debug_log("WARN", "HOST_OPERATIONS", "Refused to delete all hosts", Some(&e.to_string()));
+        })?;
+
+    delete_all_hosts()
+}
+
+/// Deletes all hosts.
+///
+/// # Why this exists
+/// Provides a safe way to clear all hosts. Used during application reset.
+///
+/// # Returns
+/// * `Ok(())` - All hosts deleted successfully
+/// * `Err(AppError)` - Persistence error
 ///
 /// # Side Effects
 /// - Writes empty hosts.csv (with header only)
@@ -232,14 +968,10 @@ pub fn delete_all_hosts() -> Result<(), AppError> {
         None,
     );
 
-    let path = get_hosts_csv_path()
-        .map_err(|e| AppError::Other {
-            message: format!("Failed to get CSV path: {}", e),
-            source: None,
-        })?;
+    let path = hosts_store::active_hosts_path()?;
     
     // Write empty CSV (just header)
-    csv_writer::write_hosts_to_csv(&path, &[])?;
+    write_hosts(&path, &[])?;
 
     debug_log(
         "INFO",
@@ -251,620 +983,2560 @@ pub fn delete_all_hosts() -> Result<(), AppError> {
     Ok(())
 }
 
-/// Updates the last_connected timestamp for a host.
+/// Merges scanned hosts into an existing host list.
 ///
 /// # Why this exists
-/// Encapsulates the timestamp update logic. Called automatically after
-/// successful RDP connections to track usage.
+/// A domain scan only knows hostnames and AD descriptions. Merging must not
+/// throw away what the user already has, so existing hosts keep their position
+/// and only pick up a non-empty scanned description.
+///
+/// Usage tracking (`last_connected`, `connect_count`) belongs to the existing
+/// host: it is only taken from the scanned entry when the existing host has
+/// none, so a scan never wipes a recorded or hand-edited timestamp.
+///
+/// # Returns
+/// The merged list: existing hosts first (in their original order), followed
+/// by newly discovered hosts in scan order.
+pub fn merge_scanned_hosts(existing: Vec<Host>, scanned: Vec<Host>) -> Vec<Host> {
+    let mut merged = existing;
+
+    for host in scanned {
+        match merged.iter_mut().find(|h| h.hostname == host.hostname) {
+            Some(current) => {
+                if !host.description.trim().is_empty() {
+                    current.description = host.description;
+                }
+                if current.last_connected.is_none() {
+                    current.last_connected = host.last_connected;
+                }
+                if current.connect_count == 0 {
+                    current.connect_count = host.connect_count;
+                }
+            }
+            None => merged.push(host),
+        }
+    }
+
+    merged
+}
+
+/// Applies scanned hosts to the given CSV file.
+///
+/// # Why this exists
+/// Lets the user review scan results before anything is persisted; this is the
+/// commit half of the preview/commit split. Path-based so it can be tested
+/// against a temporary file.
 ///
 /// # Arguments
-/// * `hostname` - The hostname to update
+/// * `path` - Path to hosts.csv
+/// * `hosts` - The hosts selected from the scan preview
+/// * `mode` - Whether to replace the list or merge into it
 ///
 /// # Returns
-/// * `Ok(())` - Timestamp updated successfully
-/// * `Err(AppError)` - Host not found or persistence error
+/// * `Ok(usize)` - Number of hosts in the CSV after the commit
+/// * `Err(AppError)` - CSV read/write errors
 ///
 /// # Side Effects
-/// - Reads hosts.csv
-/// - Writes updated hosts.csv with new timestamp
-///
-/// # Failure Modes
-/// - Host not found in CSV
-/// - CSV read/write errors
-pub fn update_last_connected(hostname: &str) -> Result<(), AppError> {
-    use chrono::Local;
-
-    // Generate timestamp in UK date format: DD/MM/YYYY HH:MM:SS
-    // This format is used consistently across the application
-    let timestamp = Local::now().format("%d/%m/%Y %H:%M:%S").to_string();
-
+/// - Reads hosts.csv (merge mode only)
+/// - Writes hosts.csv
+pub fn commit_scanned_hosts_to(
+    path: &Path,
+    hosts: Vec<Host>,
+    mode: ScanCommitMode,
+) -> Result<usize, AppError> {
     debug_log(
         "INFO",
         "HOST_OPERATIONS",
-        &format!("Updating last connected for {} to {}", hostname, timestamp),
+        &format!("Committing {} scanned hosts ({:?})", hosts.len(), mode),
         None,
     );
 
-    // Read all hosts
-    let mut hosts = get_all_hosts()?;
+    // Blank hostnames can't be connected to, drop them rather than persisting junk
+    let hosts: Vec<Host> = hosts
+        .into_iter()
+        .filter(|h| !h.hostname.trim().is_empty())
+        .collect();
+
+    let hosts = match mode {
+        ScanCommitMode::Replace => hosts,
+        ScanCommitMode::Merge => {
+            let existing = read_hosts(path)?;
+            merge_scanned_hosts(existing, hosts)
+        }
+    };
+
+    write_hosts(path, &hosts)?;
+
+    Ok(hosts.len())
+}
+
+/// Applies scanned hosts to hosts.csv.
+///
+/// See [`commit_scanned_hosts_to`].
+pub fn commit_scanned_hosts(hosts: Vec<Host>, mode: ScanCommitMode) -> Result<usize, AppError> {
+    let path = hosts_store::active_hosts_path()?;
+
+    commit_scanned_hosts_to(&path, hosts, mode)
+}
+
+/// What committing a scan would change in the host list
+///
+/// Hostnames are compared with [`normalize_hostname`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HostDiff {
+    /// Scanned hosts that aren't in the list yet, in scan order
+    pub added: Vec<Host>,
+    /// Listed hosts the scan didn't find, in list order
+    ///
+    /// Informational only: committing a scan in merge mode never deletes them.
+    pub removed: Vec<Host>,
+    /// Listed hosts the scan found again, as currently stored
+    pub unchanged: Vec<Host>,
+}
+
+/// Compares scanned hosts with the current host list
+///
+/// Blank scanned hostnames are ignored and a host scanned twice is only
+/// counted once.
+pub fn diff_scanned_hosts(existing: Vec<Host>, scanned: Vec<Host>) -> HostDiff {
+    let mut scanned_keys = std::collections::HashSet::new();
+    let mut diff = HostDiff::default();
+    let existing_keys: std::collections::HashSet<String> = existing
+        .iter()
+        .map(|host| normalize_hostname(&host.hostname))
+        .collect();
 
-    // Find and update the host
-    let mut found = false;
-    for host in &mut hosts {
-        if host.hostname == hostname {
-            host.last_connected = Some(timestamp.clone());
-            found = true;
-            break;
+    for host in scanned {
+        let key = normalize_hostname(&host.hostname);
+        if key.is_empty() || !scanned_keys.insert(key.clone()) {
+            continue;
+        }
+        if !existing_keys.contains(&key) {
+            diff.added.push(host);
         }
     }
 
-    if !found {
-        return Err(AppError::HostNotFound {
-            hostname: hostname.to_string(),
-        });
+    for host in existing {
+        if scanned_keys.contains(&normalize_hostname(&host.hostname)) {
+            diff.unchanged.push(host);
+        } else {
+            diff.removed.push(host);
+        }
     }
 
-    // Write back to CSV
-    let path = get_hosts_csv_path()
-        .map_err(|e| AppError::Other {
-            message: format!("Failed to get CSV path: {}", e),
-            source: None,
-        })?;
-    
-    csv_writer::write_hosts_to_csv(&path, &hosts)?;
+    diff
+}
+
+/// Compares scanned hosts with the saved host list, without changing it
+///
+/// See [`diff_scanned_hosts`].
+pub fn diff_scan_result(scanned: Vec<Host>) -> Result<HostDiff, AppError> {
+    diff_scan_result_at(&hosts_store::active_hosts_path()?, scanned)
+}
+
+/// Compares scanned hosts with the hosts in the given file, without changing it
+pub fn diff_scan_result_at(path: &Path, scanned: Vec<Host>) -> Result<HostDiff, AppError> {
+    let mut existing = read_hosts(path)?;
+    sort_hosts_by_order(&mut existing);
+    let diff = diff_scanned_hosts(existing, scanned);
 
     debug_log(
         "INFO",
         "HOST_OPERATIONS",
-        &format!("Successfully updated last connected for {}", hostname),
+        &format!(
+            "Scan diff: {} added, {} not found by scan, {} unchanged",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.unchanged.len()
+        ),
         None,
     );
 
-    Ok(())
+    Ok(diff)
 }
 
-/// Migrates hosts.csv from old location (working directory) to new location (AppData).
-///
-/// # Why this exists
-/// Handles data migration for users upgrading from v1.0.0 to v1.1.0+.
-/// This was added when the storage location changed from the application
-/// directory to the proper AppData location.
-///
-/// # Why separate
-/// Migration logic is infrastructure/deployment concern, but operates on
-/// host data, so it lives in the core::hosts module where it has access
-/// to the necessary operations.
+/// Checks that a hostname is usable as a host entry.
 ///
-/// # Side Effects
-/// - Checks if hosts.csv exists in working directory
-/// - Copies to AppData location if not already there
-/// - Deletes old file after successful copy
+/// Accepts DNS names and IP addresses: letters, digits, `.`, `-` and `_`,
+/// up to 253 characters.
 ///
-/// # Failure Modes
-/// - Logs errors but doesn't fail - migration is best-effort
-pub fn migrate_hosts_csv_if_needed() {
-    let old_path = Path::new("hosts.csv");
+/// # Returns
+/// * `Ok(())` - Hostname is valid
+/// * `Err(AppError::InvalidHostname)` - Empty, too long, or contains invalid characters
+pub fn validate_hostname(hostname: &str) -> Result<(), AppError> {
+    let invalid = |reason: &str| AppError::InvalidHostname {
+        hostname: hostname.to_string(),
+        reason: reason.to_string(),
+    };
 
-    if !old_path.exists() {
-        return;
+    if hostname.trim().is_empty() {
+        return Err(invalid("Hostname cannot be empty"));
+    }
+    if hostname.len() > 253 {
+        return Err(invalid("Hostname cannot be longer than 253 characters"));
+    }
+    if !hostname
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return Err(invalid(
+            "Hostname may only contain letters, digits, '.', '-' and '_'",
+        ));
     }
 
-    let new_path = match get_hosts_csv_path() {
-        Ok(path) => path,
-        Err(e) => {
-            debug_log(
-                "ERROR",
-                "MIGRATION",
-                &format!("Failed to get new CSV path: {}", e),
-                None,
-            );
-            return;
+    Ok(())
+}
+
+/// Validates a host entry that may carry a port or be an IP literal
+///
+/// Accepts what [`split_host_port`] understands: a hostname checked by
+/// [`validate_hostname`] or an IPv4/IPv6 address, optionally with a port.
+///
+/// # Returns
+/// * `Ok(())` - The entry is usable
+/// * `Err(AppError::InvalidHostname)` - Invalid host, or a port outside 1-65535
+pub fn validate_host_address(value: &str) -> Result<(), AppError> {
+    let (host, port) = split_host_port(value);
+    if port.is_none() && !host.starts_with('[') && host.matches(':').count() == 1 {
+        if let Some((_, suffix)) = host.split_once(':') {
+            if !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit()) {
+                return Err(AppError::InvalidHostname {
+                    hostname: value.to_string(),
+                    reason: "Port must be between 1 and 65535".to_string(),
+                });
+            }
         }
-    };
+    }
 
-    if new_path.exists() {
-        debug_log(
-            "INFO",
-            "MIGRATION",
-            "hosts.csv already exists in AppData, skipping migration",
-            None,
-        );
-        return;
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return Ok(());
     }
+    validate_hostname(host)
+}
 
-    if let Err(e) = std::fs::copy(old_path, &new_path) {
-        debug_log(
-            "ERROR",
-            "MIGRATION",
-            &format!("Failed to migrate hosts.csv to AppData: {}", e),
-            None,
-        );
-        return;
+/// Creates a copy of an existing host under a new hostname.
+///
+/// All settings are copied; usage tracking (`last_connected`, `connect_count`)
+/// starts fresh, and the copy has no manual position until reordered.
+///
+/// # Returns
+/// * `Ok(Host)` - The new host (not yet saved)
+/// * `Err(AppError::HostNotFound)` - No host named `source`
+/// * `Err(AppError::InvalidHostname)` - New hostname invalid or already in use
+pub fn duplicate_host_entry(hosts: &[Host], source: &str, new_hostname: &str) -> Result<Host, AppError> {
+    let new_hostname = new_hostname.trim();
+    validate_host_address(new_hostname)?;
+
+    if hosts
+        .iter()
+        .any(|h| h.hostname.eq_ignore_ascii_case(new_hostname))
+    {
+        return Err(AppError::InvalidHostname {
+            hostname: new_hostname.to_string(),
+            reason: "A host with this name already exists".to_string(),
+        });
     }
 
+    let source_host = hosts
+        .iter()
+        .find(|h| h.hostname.eq_ignore_ascii_case(source.trim()))
+        .ok_or_else(|| AppError::HostNotFound {
+            hostname: source.to_string(),
+        })?;
+
+    Ok(Host {
+        hostname: new_hostname.to_string(),
+        last_connected: None,
+        connect_count: 0,
+        sort_order: None,
+        ..source_host.clone()
+    })
+}
+
+/// Duplicates a host in the given CSV file.
+///
+/// See [`duplicate_host_entry`].
+pub fn duplicate_host_at(path: &Path, source: &str, new_hostname: &str) -> Result<Host, AppError> {
+    let mut hosts = read_hosts(path)?;
+    let duplicate = duplicate_host_entry(&hosts, source, new_hostname)?;
+
+    hosts.push(duplicate.clone());
+    write_hosts(path, &hosts)?;
+
+    Ok(duplicate)
+}
+
+/// Duplicates a host in hosts.csv.
+///
+/// # Side Effects
+/// - Reads and rewrites hosts.csv
+pub fn duplicate_host(source: &str, new_hostname: &str) -> Result<Host, AppError> {
     debug_log(
         "INFO",
-        "MIGRATION",
-        &format!("Successfully migrated hosts.csv to {}", new_path.display()),
+        "HOST_OPERATIONS",
+        &format!("Duplicating host {} as {}", source, new_hostname),
         None,
     );
 
-    if let Err(e) = std::fs::remove_file(old_path) {
-        debug_log(
-            "WARN",
-            "MIGRATION",
-            &format!("Failed to delete old hosts.csv: {}", e),
-            None,
-        );
-    }
+    let path = hosts_store::active_hosts_path()?;
+
+    duplicate_host_at(&path, source, new_hostname)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::PathBuf;
-    use tempfile::TempDir;
+/// Maximum number of hosts `connect_group` opens at once unless forced
+pub const GROUP_CONNECT_LIMIT: usize = 20;
 
-    /// Helper to set up a test environment with a temporary CSV file
-    fn setup_test_env() -> (TempDir, PathBuf) {
-        let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        let csv_path = temp_dir.path().join("hosts.csv");
-        (temp_dir, csv_path)
+/// Returns the hosts in a group (trimmed, case-insensitive), in list order.
+pub fn hosts_in_group(hosts: &[Host], group: &str) -> Vec<Host> {
+    let group = group.trim();
+    hosts
+        .iter()
+        .filter(|host| {
+            host.group
+                .as_deref()
+                .is_some_and(|g| g.trim().eq_ignore_ascii_case(group))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Selects the hosts to connect to for a group, enforcing the safety cap.
+///
+/// # Arguments
+/// * `hosts` - All hosts
+/// * `group` - Group name to match
+/// * `force` - Allow more than `GROUP_CONNECT_LIMIT` hosts
+///
+/// # Returns
+/// * `Ok(Vec<Host>)` - Hosts in the group (may be empty)
+/// * `Err(AppError)` - The group is larger than the cap and `force` is false
+pub fn select_group_hosts(hosts: &[Host], group: &str, force: bool) -> Result<Vec<Host>, AppError> {
+    let selected = hosts_in_group(hosts, group);
+
+    if selected.len() > GROUP_CONNECT_LIMIT && !force {
+        return Err(AppError::Other {
+            message: format!(
+                "Group '{}' has {} hosts, more than the limit of {}. Connect with force to open them all.",
+                group.trim(),
+                selected.len(),
+                GROUP_CONNECT_LIMIT
+            ),
+            source: None,
+        });
     }
 
-    /// Helper to create test hosts
-    fn create_test_host(hostname: &str, description: &str) -> Host {
-        Host {
-            hostname: hostname.to_string(),
-            description: description.to_string(),
-            last_connected: None,
+    Ok(selected)
+}
+
+/// Sets the group on each listed host in the given CSV file.
+///
+/// Hostnames match case-insensitively; ones not in the file are skipped. A
+/// `None` or blank group clears it. The file is only rewritten if something
+/// changed.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts whose group changed
+/// * `Err(AppError)` - CSV read/write errors
+pub fn set_hosts_group_at(path: &Path, hostnames: &[String], group: Option<&str>) -> Result<usize, AppError> {
+    let group = group.map(str::trim).filter(|g| !g.is_empty());
+    let mut hosts = read_hosts(path)?;
+
+    let mut changed = 0;
+    for host in hosts.iter_mut() {
+        let listed = hostnames
+            .iter()
+            .any(|name| name.trim().eq_ignore_ascii_case(&host.hostname));
+        if listed && host.group.as_deref() != group {
+            host.group = group.map(str::to_string);
+            changed += 1;
         }
     }
 
-    #[test]
-    fn test_search_hosts_by_hostname() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let hosts = vec![
-            create_test_host("server01.domain.com", "Web Server"),
-            create_test_host("server02.domain.com", "Database Server"),
-            create_test_host("workstation01.domain.com", "Dev Machine"),
-        ];
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Mock get_hosts_csv_path by testing csv_reader directly
-        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        // Test search logic
-        let query = "server";
-        let filtered: Vec<Host> = loaded_hosts
-            .into_iter()
-            .filter(|host| {
-                host.hostname.to_lowercase().contains(&query.to_lowercase())
-                    || host.description.to_lowercase().contains(&query.to_lowercase())
-            })
-            .collect();
-        
-        assert_eq!(filtered.len(), 2);
-        assert!(filtered.iter().any(|h| h.hostname == "server01.domain.com"));
-        assert!(filtered.iter().any(|h| h.hostname == "server02.domain.com"));
+    if changed > 0 {
+        write_hosts(path, &hosts)?;
     }
 
-    #[test]
-    fn test_search_hosts_by_description() {
-        let (_temp_dir, csv_path) = setup_test_env();
+    Ok(changed)
+}
+
+/// Moves the listed hosts into `group` (or clears it) in hosts.csv.
+///
+/// # Side Effects
+/// - Reads hosts.csv, rewrites it once if any host changed
+pub fn set_hosts_group(hostnames: &[String], group: Option<&str>) -> Result<usize, AppError> {
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        &format!(
+            "Setting group of {} host(s) to {}",
+            hostnames.len(),
+            group.unwrap_or("(none)")
+        ),
+        None,
+    );
+
+    let path = hosts_store::active_hosts_path()?;
+
+    set_hosts_group_at(&path, hostnames, group)
+}
+
+/// Replaces a host's tags in the given hosts file.
+///
+/// The given list is authoritative: tags not in it are removed, and an empty
+/// list clears them all. Tags are normalized with `normalize_tags`.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - The tags saved
+/// * `Err(AppError::HostNotFound)` - The hostname isn't in the file
+/// * `Err(AppError)` - CSV read/write errors
+pub fn set_host_tags_at(path: &Path, hostname: &str, tags: &[String]) -> Result<Vec<String>, AppError> {
+    let mut hosts = read_hosts(path)?;
+    let host = hosts
+        .iter_mut()
+        .find(|host| host.hostname.eq_ignore_ascii_case(hostname.trim()))
+        .ok_or_else(|| AppError::HostNotFound {
+            hostname: hostname.to_string(),
+        })?;
+
+    let tags = normalize_tags(tags);
+    if host.tags != tags {
+        host.tags = tags.clone();
+        write_hosts(path, &hosts)?;
+    }
+
+    Ok(tags)
+}
+
+/// Replaces a host's tags in the host list.
+///
+/// # Side Effects
+/// - Reads the hosts file, rewrites it if the tags changed
+pub fn set_host_tags(hostname: &str, tags: &[String]) -> Result<Vec<String>, AppError> {
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        &format!("Setting {} tag(s) on {}", tags.len(), hostname),
+        None,
+    );
+
+    let path = hosts_store::active_hosts_path()?;
+
+    set_host_tags_at(&path, hostname, tags)
+}
+
+/// Canonical form of a hostname for duplicate detection
+///
+/// Lowercased, trimmed, with trailing dots removed (`Server01.Domain.com.`
+/// becomes `server01.domain.com`).
+pub fn normalize_hostname(hostname: &str) -> String {
+    hostname.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// Merges `duplicate` into `kept`, keeping the best metadata from each
+///
+/// - Description: the longer one (the kept host's on a tie)
+/// - `last_connected`: the newer one; a parseable timestamp beats an unparseable one
+/// - `connect_count`: summed
+/// - Tags: combined
+/// - Other fields: the kept host's, filled from the duplicate where empty
+fn absorb_duplicate(kept: &mut Host, duplicate: Host) {
+    if duplicate.description.trim().chars().count() > kept.description.trim().chars().count() {
+        kept.description = duplicate.description;
+    }
+
+    let newer = match (
+        kept.last_connected.as_deref().map(crate::core::stats::parse_last_connected),
+        duplicate.last_connected.as_deref().map(crate::core::stats::parse_last_connected),
+    ) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(None), Some(theirs)) => theirs.is_some(),
+        (Some(Some(ours)), Some(theirs)) => theirs.is_some_and(|theirs| theirs > ours),
+    };
+    if newer {
+        kept.last_connected = duplicate.last_connected;
+    }
+
+    kept.connect_count = kept.connect_count.saturating_add(duplicate.connect_count);
+    kept.tags = normalize_tags(&[std::mem::take(&mut kept.tags), duplicate.tags].concat());
+
+    if kept.connect_username.is_none() {
+        kept.connect_username = duplicate.connect_username;
+    }
+    if kept.group.is_none() {
+        kept.group = duplicate.group;
+    }
+    if kept.sort_order.is_none() {
+        kept.sort_order = duplicate.sort_order;
+    }
+    for (column, value) in duplicate.extra_columns {
+        kept.extra_columns.entry(column).or_insert(value);
+    }
+}
+
+/// Merges entries whose hostnames are equal once normalized
+///
+/// Each merged host keeps its first entry's hostname as written and stays
+/// where that entry was; the normalized form is only the grouping key, so
+/// per-host settings and credentials keyed by the hostname stay attached.
+///
+/// # Returns
+/// * The cleaned list and the number of duplicate entries merged away
+pub fn deduplicate_host_list(hosts: Vec<Host>) -> (Vec<Host>, usize) {
+    let mut deduplicated: Vec<Host> = Vec::with_capacity(hosts.len());
+    // Normalized hostname to its index in `deduplicated`
+    let mut positions: HashMap<String, usize> = HashMap::with_capacity(hosts.len());
+    let mut merged = 0;
+
+    for host in hosts {
+        let key = normalize_hostname(&host.hostname);
+        match positions.get(&key) {
+            Some(&index) => {
+                absorb_duplicate(&mut deduplicated[index], host);
+                merged += 1;
+            }
+            None => {
+                positions.insert(key, deduplicated.len());
+                deduplicated.push(host);
+            }
+        }
+    }
+
+    (deduplicated, merged)
+}
+
+/// Deduplicates the hosts in the given file.
+///
+/// The file is only rewritten if duplicates were merged.
+///
+/// # Returns
+/// * `Ok(usize)` - Number of duplicate entries merged
+/// * `Err(AppError)` - CSV read/write errors
+pub fn deduplicate_hosts_at(path: &Path) -> Result<usize, AppError> {
+    let (hosts, merged) = deduplicate_host_list(read_hosts(path)?);
+    if merged > 0 {
+        write_hosts(path, &hosts)?;
+    }
+
+    Ok(merged)
+}
+
+/// Deduplicates the whole host list.
+///
+/// # Side Effects
+/// - Reads the hosts file, rewrites it once if anything was merged
+pub fn deduplicate_hosts() -> Result<usize, AppError> {
+    let path = hosts_store::active_hosts_path()?;
+    let merged = deduplicate_hosts_at(&path)?;
+
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        &format!("Merged {} duplicate host(s)", merged),
+        None,
+    );
+
+    Ok(merged)
+}
+
+/// Updates the last_connected timestamp for a host in the given file.
+///
+/// The timestamp is taken from `clock` in UK date format
+/// (`DD/MM/YYYY HH:MM:SS`), and the host's connect count is incremented.
+///
+/// # Returns
+/// * `Ok(())` - Timestamp updated successfully
+/// * `Err(AppError)` - Host not found or persistence error
+pub fn update_last_connected_at(path: &Path, hostname: &str, clock: &dyn Clock) -> Result<(), AppError> {
+    // Generate timestamp in UK date format: DD/MM/YYYY HH:MM:SS
+    // This format is used consistently across the application
+    let timestamp = clock.now().format("%d/%m/%Y %H:%M:%S").to_string();
+
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        &format!("Updating last connected for {} to {}", hostname, timestamp),
+        None,
+    );
+
+    let mut hosts = read_hosts(path)?;
+
+    let host = hosts
+        .iter_mut()
+        .find(|host| host.hostname == hostname)
+        .ok_or_else(|| AppError::HostNotFound {
+            hostname: hostname.to_string(),
+        })?;
+    host.last_connected = Some(timestamp);
+    host.connect_count = host.connect_count.saturating_add(1);
+
+    write_hosts(path, &hosts)?;
+
+    debug_log(
+        "INFO",
+        "HOST_OPERATIONS",
+        &format!("Successfully updated last connected for {}", hostname),
+        None,
+    );
+
+    Ok(())
+}
+
+/// Updates the last_connected timestamp for a host.
+///
+/// # Why this exists
+/// Encapsulates the timestamp update logic. Called automatically after
+/// successful RDP connections to track usage.
+///
+/// # Arguments
+/// * `hostname` - The hostname to update
+///
+/// # Returns
+/// * `Ok(())` - Timestamp updated successfully
+/// * `Err(AppError)` - Host not found or persistence error
+///
+/// # Side Effects
+/// - Reads hosts.csv
+/// - Writes updated hosts.csv with new timestamp
+///
+/// # Failure Modes
+/// - Host not found in CSV
+/// - CSV read/write errors
+pub fn update_last_connected(hostname: &str) -> Result<(), AppError> {
+    let path = hosts_store::active_hosts_path()?;
+    update_last_connected_at(&path, hostname, &SystemClock)
+}
+
+/// hosts.csv location used before v1.1.0, relative to the working directory
+pub const LEGACY_HOSTS_CSV: &str = "hosts.csv";
+
+/// Migrates hosts.csv from old location (working directory) to new location (AppData).
+///
+/// # Why this exists
+/// Handles data migration for users upgrading from v1.0.0 to v1.1.0+.
+/// This was added when the storage location changed from the application
+/// directory to the proper AppData location.
+///
+/// # Why separate
+/// Migration logic is infrastructure/deployment concern, but operates on
+/// host data, so it lives in the core::hosts module where it has access
+/// to the necessary operations.
+///
+/// # Side Effects
+/// - Checks if hosts.csv exists in working directory
+/// - Copies to AppData location if not already there
+/// - Deletes old file after successful copy
+///
+/// # Failure Modes
+/// - Logs errors but doesn't fail - migration is best-effort
+pub fn migrate_hosts_csv_if_needed() {
+    let old_path = Path::new(LEGACY_HOSTS_CSV);
+
+    if !old_path.exists() {
+        return;
+    }
+
+    let new_path = match get_hosts_csv_path() {
+        Ok(path) => path,
+        Err(e) => {
+            debug_log(
+                "ERROR",
+                "MIGRATION",
+                &format!("Failed to get new CSV path: {}", e),
+                None,
+            );
+            return;
+        }
+    };
+
+    if new_path.exists() {
+        debug_log(
+            "INFO",
+            "MIGRATION",
+            "hosts.csv already exists in AppData, skipping migration",
+            None,
+        );
+        return;
+    }
+
+    if let Err(e) = std::fs::copy(old_path, &new_path) {
+        debug_log(
+            "ERROR",
+            "MIGRATION",
+            &format!("Failed to migrate hosts.csv to AppData: {}", e),
+            None,
+        );
+        return;
+    }
+
+    debug_log(
+        "INFO",
+        "MIGRATION",
+        &format!("Successfully migrated hosts.csv to {}", new_path.display()),
+        None,
+    );
+
+    if let Err(e) = std::fs::remove_file(old_path) {
+        debug_log(
+            "WARN",
+            "MIGRATION",
+            &format!("Failed to delete old hosts.csv: {}", e),
+            None,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::hosts_store::HostsStore;
+    use crate::core::{csv_reader, csv_writer};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    /// Helper to set up a test environment with a temporary CSV file
+    fn setup_test_env() -> (TempDir, PathBuf) {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let csv_path = temp_dir.path().join("hosts.csv");
+        (temp_dir, csv_path)
+    }
+
+    /// Helper to create test hosts
+    fn create_test_host(hostname: &str, description: &str) -> Host {
+        Host {
+            hostname: hostname.to_string(),
+            description: description.to_string(),
+            last_connected: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(split_host_port("server01:3390"), ("server01", Some(3390)));
+        assert_eq!(split_host_port(" server01.domain.com "), ("server01.domain.com", None));
+        assert_eq!(split_host_port("10.0.0.5"), ("10.0.0.5", None));
+        assert_eq!(split_host_port("10.0.0.5:3390"), ("10.0.0.5", Some(3390)));
+        assert_eq!(split_host_port("[fe80::1]:3390"), ("fe80::1", Some(3390)));
+        assert_eq!(split_host_port("[fe80::1]"), ("fe80::1", None));
+        // Without brackets every colon belongs to the IPv6 literal
+        assert_eq!(split_host_port("fe80::1"), ("fe80::1", None));
+        assert_eq!(split_host_port("2001:db8::3390"), ("2001:db8::3390", None));
+        assert_eq!(split_host_port("::1"), ("::1", None));
+    }
+
+    #[test]
+    fn test_never_connected_hosts_returns_unused_sorted() {
+        let connected = |hostname: &str, last_connected: Option<&str>, connect_count: u32| Host {
+            last_connected: last_connected.map(str::to_string),
+            connect_count,
+            ..create_test_host(hostname, "")
+        };
+        let hosts = vec![
+            create_test_host("web02.domain.com", "Never used"),
+            connected("sql01.domain.com", Some("01/03/2026 09:00:00"), 4),
+            create_test_host("App01.domain.com", ""),
+            // Counted by an older version that didn't record the time
+            connected("old01.domain.com", None, 2),
+            // Time recorded without a count
+            connected("web01.domain.com", Some("15/02/2026 17:30:00"), 0),
+            connected("blank01.domain.com", Some("  "), 0),
+        ];
+
+        let never = never_connected_hosts(hosts);
+
+        let names: Vec<&str> = never.iter().map(|host| host.hostname.as_str()).collect();
+        assert_eq!(names, vec!["App01.domain.com", "blank01.domain.com", "web02.domain.com"]);
+        assert_eq!(never[2].description, "Never used");
+        assert!(never_connected_hosts(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_split_host_port_leaves_invalid_ports_on_host() {
+        for value in ["server01:", "server01:rdp", "server01:0", "server01:70000", "[fe80::1]:x", "[fe80::1"] {
+            assert_eq!(split_host_port(value), (value, None), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_validate_host_address() {
+        for value in ["server01", "server01:3390", "10.0.0.5", "10.0.0.5:3390", "fe80::1", "[fe80::1]:3390", "[::1]"] {
+            assert!(validate_host_address(value).is_ok(), "{:?} should be valid", value);
+        }
+        for value in ["", "server01:70000", "server01:0", "server01:rdp", "bad host", "[server01]:3390x"] {
+            assert!(
+                matches!(validate_host_address(value), Err(AppError::InvalidHostname { .. })),
+                "{:?} should be invalid",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_host_port_roundtrips() {
+        assert_eq!(format_host_port("server01", Some(3390)), "server01:3390");
+        assert_eq!(format_host_port("server01", None), "server01");
+        assert_eq!(format_host_port("fe80::1", Some(3390)), "[fe80::1]:3390");
+        assert_eq!(format_host_port("fe80::1", None), "fe80::1");
+
+        for value in ["server01:3390", "10.0.0.5", "[fe80::1]:3390", "fe80::1"] {
+            let (host, port) = split_host_port(value);
+            assert_eq!(format_host_port(host, port), value);
+        }
+    }
+
+    #[test]
+    fn test_is_loopback_hostname_detects_loopback_names() {
+        for hostname in [
+            "localhost",
+            "LOCALHOST.",
+            " localhost ",
+            "app.localhost",
+            "127.0.0.1",
+            "127.1.2.3",
+            "0.0.0.0",
+            "::1",
+            "[::1]",
+            "::",
+            "::ffff:127.0.0.1",
+            "localhost:3390",
+            "[::1]:3390",
+        ] {
+            assert!(is_loopback_hostname(hostname), "{:?} should be loopback", hostname);
+        }
+    }
+
+    #[test]
+    fn test_is_loopback_hostname_allows_normal_hosts() {
+        for hostname in [
+            "server01.domain.com",
+            "localhost-dev.domain.com",
+            "mylocalhost",
+            "128.0.0.1",
+            "10.0.0.5",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+            "",
+        ] {
+            assert!(!is_loopback_hostname(hostname), "{:?} should not be loopback", hostname);
+        }
+    }
+
+    #[test]
+    fn test_save_host_checked_holds_back_unconfirmed_loopback() {
+        let outcome = save_host_checked(create_test_host("127.0.0.1", "Test"), false)
+            .expect("An unconfirmed loopback host is not an error");
+
+        assert_eq!(
+            outcome,
+            SaveHostOutcome {
+                saved: false,
+                loopback_warning: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_search_hosts_by_hostname() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Web Server"),
+            create_test_host("server02.domain.com", "Database Server"),
+            create_test_host("workstation01.domain.com", "Dev Machine"),
+        ];
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Mock get_hosts_csv_path by testing csv_reader directly
+        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        // Test search logic
+        let query = "server";
+        let filtered: Vec<Host> = loaded_hosts
+            .into_iter()
+            .filter(|host| {
+                host.hostname.to_lowercase().contains(&query.to_lowercase())
+                    || host.description.to_lowercase().contains(&query.to_lowercase())
+            })
+            .collect();
+        
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered.iter().any(|h| h.hostname == "server01.domain.com"));
+        assert!(filtered.iter().any(|h| h.hostname == "server02.domain.com"));
+    }
+
+    #[test]
+    fn test_search_hosts_by_description() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Web Server"),
+            create_test_host("server02.domain.com", "Database Server"),
+            create_test_host("server03.domain.com", "Mail Server"),
+        ];
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        // Test search logic
+        let query = "database";
+        let filtered: Vec<Host> = loaded_hosts
+            .into_iter()
+            .filter(|host| {
+                host.hostname.to_lowercase().contains(&query.to_lowercase())
+                    || host.description.to_lowercase().contains(&query.to_lowercase())
+            })
+            .collect();
+        
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].hostname, "server02.domain.com");
+    }
+
+    #[test]
+    fn test_search_hosts_case_insensitive() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("SERVER01.DOMAIN.COM", "Web Server"),
+            create_test_host("server02.domain.com", "Database"),
+        ];
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        // Test case-insensitive search
+        let query = "server01";
+        let filtered: Vec<Host> = loaded_hosts
+            .into_iter()
+            .filter(|host| {
+                host.hostname.to_lowercase().contains(&query.to_lowercase())
+                    || host.description.to_lowercase().contains(&query.to_lowercase())
+            })
+            .collect();
+        
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].hostname.to_lowercase(), "server01.domain.com");
+    }
+
+    #[test]
+    fn test_search_hosts_empty_query_returns_all() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Web Server"),
+            create_test_host("server02.domain.com", "Database"),
+        ];
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        // Empty query should match all hosts
+        let query = "";
+        let filtered: Vec<Host> = loaded_hosts
+            .into_iter()
+            .filter(|host| {
+                host.hostname.to_lowercase().contains(query)
+                    || host.description.to_lowercase().contains(query)
+            })
+            .collect();
+        
+        assert_eq!(filtered.len(), 2);
+    }
+
+    #[test]
+    fn test_search_hosts_no_matches() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Web Server"),
+            create_test_host("server02.domain.com", "Database"),
+        ];
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        let query = "nonexistent";
+        let filtered: Vec<Host> = loaded_hosts
+            .into_iter()
+            .filter(|host| {
+                host.hostname.to_lowercase().contains(query)
+                    || host.description.to_lowercase().contains(query)
+            })
+            .collect();
+        
+        assert!(filtered.is_empty());
+    }
+
+    #[test]
+    fn test_upsert_host_insert_new() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        // Start with empty hosts
+        csv_writer::write_hosts_to_csv(&csv_path, &[]).expect("Failed to write CSV");
+        
+        let new_host = create_test_host("server01.domain.com", "Test Server");
+        
+        // Simulate upsert logic
+        let mut hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        // Validate hostname
+        assert!(!new_host.hostname.trim().is_empty());
+        
+        // Check if host exists
+        if !hosts.iter().any(|h| h.hostname == new_host.hostname) {
+            hosts.push(new_host.clone());
+        }
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Verify
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].hostname, "server01.domain.com");
+        assert_eq!(loaded[0].description, "Test Server");
+    }
+
+    #[test]
+    fn test_upsert_host_update_existing() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        // Start with one host
+        let initial_hosts = vec![
+            create_test_host("server01.domain.com", "Old Description"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &initial_hosts).expect("Failed to write CSV");
+        
+        // Update with same hostname, different description
+        let updated_host = Host {
+            hostname: "server01.domain.com".to_string(),
+            description: "New Description".to_string(),
+            last_connected: Some("14/12/2025 10:30:00".to_string()),
+            ..Default::default()
+        };
+        
+        // Simulate upsert logic
+        let mut hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        if let Some(idx) = hosts.iter().position(|h| h.hostname == updated_host.hostname) {
+            hosts[idx] = updated_host.clone();
+        } else {
+            hosts.push(updated_host.clone());
+        }
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Verify
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].hostname, "server01.domain.com");
+        assert_eq!(loaded[0].description, "New Description");
+        assert_eq!(loaded[0].last_connected, Some("14/12/2025 10:30:00".to_string()));
+    }
+
+    #[test]
+    fn test_sanitize_description_truncates_over_length() {
+        let description = "a".repeat(20);
+
+        let result = sanitize_description(&description, 10, DescriptionOverflow::Truncate)
+            .expect("Truncate should not fail");
+
+        assert_eq!(result.chars().count(), 10);
+        assert_eq!(result, format!("{}…", "a".repeat(9)));
+    }
+
+    #[test]
+    fn test_sanitize_description_rejects_over_length() {
+        let description = "a".repeat(20);
+
+        let err = sanitize_description(&description, 10, DescriptionOverflow::Reject)
+            .expect_err("Over-length description should be rejected");
+
+        assert!(err.to_string().contains("20 characters"));
+        assert!(err.to_string().contains("maximum is 10"));
+    }
+
+    #[test]
+    fn test_sanitize_description_within_limit_unchanged() {
+        assert_eq!(
+            sanitize_description("Web Server", 10, DescriptionOverflow::Reject).unwrap(),
+            "Web Server"
+        );
+        // Counted in characters, not bytes
+        assert_eq!(
+            sanitize_description("Büro-Server", 11, DescriptionOverflow::Reject).unwrap(),
+            "Büro-Server"
+        );
+        // 0 disables the limit
+        let long = "x".repeat(5000);
+        assert_eq!(sanitize_description(&long, 0, DescriptionOverflow::Reject).unwrap(), long);
+    }
+
+    #[test]
+    fn test_sanitize_description_strips_control_characters() {
+        let result = sanitize_description(
+            "Line one\r\nLine\ttwo\u{0}\u{1b}[31m\u{7f}",
+            0,
+            DescriptionOverflow::Truncate,
+        )
+        .unwrap();
+
+        assert_eq!(result, "Line one\nLine two[31m");
+    }
+
+    #[test]
+    fn test_sanitize_description_replaces_control_whitespace_with_space() {
+        let result = sanitize_description("Line\ttwo", 0, DescriptionOverflow::Truncate).unwrap();
+        assert_eq!(result, "Line two");
+
+        let result = sanitize_description("Rack\t\t\u{b}4\rU12", 0, DescriptionOverflow::Truncate).unwrap();
+        assert_eq!(result, "Rack 4 U12");
+    }
+
+    #[test]
+    fn test_sanitize_description_strips_before_measuring() {
+        // 10 visible characters plus control characters fits a limit of 10
+        let result = sanitize_description("abcde\u{0}\u{0}fghij", 10, DescriptionOverflow::Reject)
+            .expect("Control characters should not count towards the limit");
+
+        assert_eq!(result, "abcdefghij");
+    }
+
+    #[test]
+    fn test_upsert_hosts_at_adds_cidr_range() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(
+            &csv_path,
+            &[Host {
+                hostname: "10.0.5.1".to_string(),
+                description: "Existing".to_string(),
+                connect_count: 4,
+                ..Default::default()
+            }],
+        )
+        .expect("Failed to write CSV");
+
+        let hosts = crate::core::cidr::hosts_from_cidr("10.0.5.0/30", Some("Agent {ip}")).unwrap();
+        let count = upsert_hosts_at(&csv_path, hosts, &crate::core::settings::AppSettings::default())
+            .expect("Upsert should succeed");
+
+        assert_eq!(count, 2);
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].hostname, "10.0.5.1");
+        assert_eq!(loaded[0].description, "Agent 10.0.5.1");
+        assert_eq!(loaded[0].connect_count, 4);
+        assert_eq!(loaded[1].hostname, "10.0.5.2");
+    }
+
+    #[test]
+    fn test_upsert_hosts_at_writes_nothing_on_invalid_host() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[create_test_host("server01.domain.com", "Keep")])
+            .expect("Failed to write CSV");
+        let before = std::fs::read(&csv_path).expect("Failed to read CSV bytes");
+
+        let hosts = vec![create_test_host("server02.domain.com", "New"), create_test_host(" ", "Blank")];
+        let result = upsert_hosts_at(&csv_path, hosts, &crate::core::settings::AppSettings::default());
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read(&csv_path).expect("Failed to read CSV bytes"), before);
+    }
+
+    #[test]
+    fn test_upsert_host_rejects_empty_hostname() {
+        let host = Host {
+            hostname: "".to_string(),
+            description: "Test".to_string(),
+            last_connected: None,
+            ..Default::default()
+        };
+        
+        // Validate hostname
+        assert!(host.hostname.trim().is_empty());
+    }
+
+    #[test]
+    fn test_upsert_host_trims_whitespace_hostname() {
+        let host = Host {
+            hostname: "  server01.domain.com  ".to_string(),
+            description: "Test".to_string(),
+            last_connected: None,
+            ..Default::default()
+        };
+        
+        // Validate that trimmed hostname is not empty
+        assert!(!host.hostname.trim().is_empty());
+        assert_eq!(host.hostname.trim(), "server01.domain.com");
+    }
+
+    #[test]
+    fn test_delete_host_removes_correct_host() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Server 1"),
+            create_test_host("server02.domain.com", "Server 2"),
+            create_test_host("server03.domain.com", "Server 3"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Simulate delete logic
+        let hostname_to_delete = "server02.domain.com";
+        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        loaded_hosts.retain(|h| h.hostname != hostname_to_delete);
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
+        
+        // Verify
+        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(final_hosts.len(), 2);
+        assert!(final_hosts.iter().any(|h| h.hostname == "server01.domain.com"));
+        assert!(final_hosts.iter().any(|h| h.hostname == "server03.domain.com"));
+        assert!(!final_hosts.iter().any(|h| h.hostname == "server02.domain.com"));
+    }
+
+    #[test]
+    fn test_delete_host_idempotent_when_not_exists() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Server 1"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Try to delete non-existent host
+        let hostname_to_delete = "nonexistent.domain.com";
+        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        let before_count = loaded_hosts.len();
+        loaded_hosts.retain(|h| h.hostname != hostname_to_delete);
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
+        
+        // Verify no change
+        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(final_hosts.len(), before_count);
+        assert_eq!(final_hosts[0].hostname, "server01.domain.com");
+    }
+
+    #[test]
+    fn test_delete_all_hosts_clears_list() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Server 1"),
+            create_test_host("server02.domain.com", "Server 2"),
+            create_test_host("server03.domain.com", "Server 3"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Delete all
+        csv_writer::write_hosts_to_csv(&csv_path, &[]).expect("Failed to write empty CSV");
+        
+        // Verify
+        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert!(final_hosts.is_empty());
+    }
+
+    #[test]
+    fn test_update_last_connected_updates_timestamp() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Server 1"),
+            create_test_host("server02.domain.com", "Server 2"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Simulate timestamp update
+        let hostname_to_update = "server01.domain.com";
+        let timestamp = "14/12/2025 15:45:30";
+        
+        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        let mut found = false;
+        for host in &mut loaded_hosts {
+            if host.hostname == hostname_to_update {
+                host.last_connected = Some(timestamp.to_string());
+                found = true;
+                break;
+            }
+        }
+        
+        assert!(found, "Host should be found");
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
+        
+        // Verify
+        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        let updated_host = final_hosts.iter().find(|h| h.hostname == hostname_to_update).expect("Host should exist");
+        assert_eq!(updated_host.last_connected, Some(timestamp.to_string()));
+    }
+
+    #[test]
+    fn test_update_last_connected_returns_error_if_not_found() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Server 1"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Try to update non-existent host
+        let hostname_to_update = "nonexistent.domain.com";
+        let timestamp = "14/12/2025 15:45:30";
+        
+        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        let mut found = false;
+        for host in &mut loaded_hosts {
+            if host.hostname == hostname_to_update {
+                host.last_connected = Some(timestamp.to_string());
+                found = true;
+                break;
+            }
+        }
+        
+        assert!(!found, "Host should not be found");
+    }
+
+    #[test]
+    fn test_update_last_connected_preserves_other_fields() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        let original_description = "Important Server";
+        let hosts = vec![
+            Host {
+                hostname: "server01.domain.com".to_string(),
+                description: original_description.to_string(),
+                last_connected: Some("13/12/2025 10:00:00".to_string()),
+                ..Default::default()
+            },
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        // Update timestamp
+        let hostname_to_update = "server01.domain.com";
+        let new_timestamp = "14/12/2025 15:45:30";
+        
+        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        for host in &mut loaded_hosts {
+            if host.hostname == hostname_to_update {
+                host.last_connected = Some(new_timestamp.to_string());
+                break;
+            }
+        }
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
+        
+        // Verify other fields preserved
+        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        let updated_host = &final_hosts[0];
+        assert_eq!(updated_host.hostname, "server01.domain.com");
+        assert_eq!(updated_host.description, original_description);
+        assert_eq!(updated_host.last_connected, Some(new_timestamp.to_string()));
+    }
+
+    #[test]
+    fn test_update_last_connected_at_uses_clock() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        let hosts = vec![
+            create_test_host("server01.domain.com", "Server 1"),
+            create_test_host("server02.domain.com", "Server 2"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        let clock = crate::core::clock::MockClock::at("2025-12-14 15:45:30");
+
+        update_last_connected_at(&csv_path, "server01.domain.com", &clock).expect("Update should succeed");
+        clock.advance(chrono::Duration::seconds(75));
+        update_last_connected_at(&csv_path, "server01.domain.com", &clock).expect("Update should succeed");
+
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded[0].last_connected, Some("14/12/2025 15:46:45".to_string()));
+        assert_eq!(loaded[0].connect_count, 2);
+        assert_eq!(loaded[0].description, "Server 1");
+        assert_eq!(loaded[1].last_connected, None);
+        assert_eq!(loaded[1].connect_count, 0);
+    }
+
+    #[test]
+    fn test_update_last_connected_at_unknown_host() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[create_test_host("server01.domain.com", "Server 1")])
+            .expect("Failed to write CSV");
+        let clock = crate::core::clock::MockClock::at("2025-12-14 15:45:30");
+
+        let result = update_last_connected_at(&csv_path, "nonexistent.domain.com", &clock);
+
+        assert!(matches!(result, Err(AppError::HostNotFound { .. })));
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded[0].last_connected, None);
+    }
+
+    #[test]
+    fn test_upsert_multiple_hosts_maintains_order() {
+        let (_temp_dir, csv_path) = setup_test_env();
+        
+        csv_writer::write_hosts_to_csv(&csv_path, &[]).expect("Failed to write CSV");
+        
+        let hosts_to_add = vec![
+            create_test_host("alpha.domain.com", "Alpha"),
+            create_test_host("beta.domain.com", "Beta"),
+            create_test_host("gamma.domain.com", "Gamma"),
+        ];
+        
+        // Add hosts one by one
+        let mut current_hosts = Vec::new();
+        for host in hosts_to_add {
+            current_hosts.push(host);
+            csv_writer::write_hosts_to_csv(&csv_path, &current_hosts).expect("Failed to write CSV");
+        }
+        
+        // Verify order preserved
+        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(final_hosts.len(), 3);
+        assert_eq!(final_hosts[0].hostname, "alpha.domain.com");
+        assert_eq!(final_hosts[1].hostname, "beta.domain.com");
+        assert_eq!(final_hosts[2].hostname, "gamma.domain.com");
+    }
+
+    #[test]
+    fn test_search_with_special_characters() {
+        let (_temp_dir, csv_path) = setup_test_env();
         
         let hosts = vec![
+            create_test_host("server-01.domain.com", "Server with dash"),
+            create_test_host("server_02.domain.com", "Server with underscore"),
+            create_test_host("server.03.domain.com", "Server with dots"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+        
+        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        
+        // Search for dash
+        let query = "server-";
+        let filtered: Vec<Host> = loaded_hosts.clone()
+            .into_iter()
+            .filter(|host| {
+                host.hostname.to_lowercase().contains(query)
+                    || host.description.to_lowercase().contains(query)
+            })
+            .collect();
+        
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].hostname, "server-01.domain.com");
+    }
+
+    #[test]
+    fn test_diff_scanned_hosts_buckets() {
+        let existing = vec![
+            create_test_host("server01.domain.com", "Kept"),
+            create_test_host("Server02.domain.com", "Renamed in AD"),
+            create_test_host("retired.domain.com", "Decommissioned"),
+        ];
+        let scanned = vec![
+            create_test_host("server01.domain.com", "From AD"),
+            create_test_host("server02.DOMAIN.com.", ""),
+            create_test_host("new01.domain.com", "New"),
+            create_test_host("NEW01.domain.com", "Duplicate"),
+            create_test_host("  ", ""),
+        ];
+
+        let diff = diff_scanned_hosts(existing, scanned);
+
+        let hostnames = |hosts: &[Host]| hosts.iter().map(|h| h.hostname.clone()).collect::<Vec<_>>();
+        assert_eq!(hostnames(&diff.added), vec!["new01.domain.com"]);
+        assert_eq!(hostnames(&diff.removed), vec!["retired.domain.com"]);
+        assert_eq!(
+            hostnames(&diff.unchanged),
+            vec!["server01.domain.com", "Server02.domain.com"]
+        );
+        // Unchanged hosts are reported as stored, not as scanned
+        assert_eq!(diff.unchanged[0].description, "Kept");
+    }
+
+    #[test]
+    fn test_diff_scanned_hosts_empty_list_adds_everything() {
+        let diff = diff_scanned_hosts(Vec::new(), vec![create_test_host("server01", "")]);
+
+        assert_eq!(diff.added.len(), 1);
+        assert!(diff.removed.is_empty());
+        assert!(diff.unchanged.is_empty());
+    }
+
+    #[test]
+    fn test_commit_scanned_hosts_replace_overwrites_csv() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[create_test_host("old.domain.com", "Old")])
+            .expect("Failed to write CSV");
+
+        let scanned = vec![
             create_test_host("server01.domain.com", "Web Server"),
             create_test_host("server02.domain.com", "Database Server"),
-            create_test_host("server03.domain.com", "Mail Server"),
         ];
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        // Test search logic
-        let query = "database";
-        let filtered: Vec<Host> = loaded_hosts
-            .into_iter()
-            .filter(|host| {
-                host.hostname.to_lowercase().contains(&query.to_lowercase())
-                    || host.description.to_lowercase().contains(&query.to_lowercase())
-            })
-            .collect();
-        
-        assert_eq!(filtered.len(), 1);
-        assert_eq!(filtered[0].hostname, "server02.domain.com");
+        let count = commit_scanned_hosts_to(&csv_path, scanned, ScanCommitMode::Replace)
+            .expect("Commit should succeed");
+
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(count, 2);
+        assert_eq!(loaded.len(), 2);
+        assert!(!loaded.iter().any(|h| h.hostname == "old.domain.com"));
+    }
+
+    #[test]
+    fn test_commit_scanned_hosts_merge_keeps_existing() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        let existing = vec![
+            Host {
+                hostname: "server01.domain.com".to_string(),
+                description: "My notes".to_string(),
+                last_connected: Some("15/01/2024 10:30:00".to_string()),
+                ..Default::default()
+            },
+            create_test_host("manual.domain.com", "Added by hand"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &existing).expect("Failed to write CSV");
+
+        let scanned = vec![
+            create_test_host("server01.domain.com", ""),
+            create_test_host("server02.domain.com", "Database Server"),
+        ];
+        let count = commit_scanned_hosts_to(&csv_path, scanned, ScanCommitMode::Merge)
+            .expect("Commit should succeed");
+
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(count, 3);
+        assert_eq!(loaded[0].hostname, "server01.domain.com");
+        assert_eq!(loaded[0].description, "My notes");
+        assert_eq!(loaded[0].last_connected, Some("15/01/2024 10:30:00".to_string()));
+        assert_eq!(loaded[1].hostname, "manual.domain.com");
+        assert_eq!(loaded[2].hostname, "server02.domain.com");
+    }
+
+    #[test]
+    fn test_commit_scanned_hosts_applies_only_selected_hosts() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+
+        // The frontend sends only the hosts the user ticked in the preview
+        let selected = vec![create_test_host("server02.domain.com", "Database Server")];
+        commit_scanned_hosts_to(&csv_path, selected, ScanCommitMode::Replace)
+            .expect("Commit should succeed");
+
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].hostname, "server02.domain.com");
+    }
+
+    #[test]
+    fn test_commit_scanned_hosts_skips_blank_hostnames() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+
+        let scanned = vec![
+            create_test_host("  ", "Blank"),
+            create_test_host("server01.domain.com", "Web Server"),
+        ];
+        let count = commit_scanned_hosts_to(&csv_path, scanned, ScanCommitMode::Replace)
+            .expect("Commit should succeed");
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_merge_scanned_hosts_updates_description_when_present() {
+        let existing = vec![create_test_host("server01.domain.com", "Old")];
+        let scanned = vec![create_test_host("server01.domain.com", "From AD")];
+
+        let merged = merge_scanned_hosts(existing, scanned);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].description, "From AD");
+    }
+
+    #[test]
+    fn test_merge_scanned_hosts_preserves_usage_tracking() {
+        let existing = vec![Host {
+            hostname: "server01.domain.com".to_string(),
+            description: "Old".to_string(),
+            last_connected: Some("14/12/2025 10:30:00".to_string()),
+            connect_count: 7,
+            ..Default::default()
+        }];
+        // Scanned entries carry no usage tracking
+        let scanned = vec![create_test_host("server01.domain.com", "From AD")];
+
+        let merged = merge_scanned_hosts(existing, scanned);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].description, "From AD");
+        assert_eq!(merged[0].last_connected, Some("14/12/2025 10:30:00".to_string()));
+        assert_eq!(merged[0].connect_count, 7);
+    }
+
+    #[test]
+    fn test_merge_scanned_hosts_fills_missing_usage_tracking() {
+        let existing = vec![create_test_host("server01.domain.com", "Old")];
+        let scanned = vec![Host {
+            hostname: "server01.domain.com".to_string(),
+            last_connected: Some("01/01/2026 09:00:00".to_string()),
+            connect_count: 2,
+            ..Default::default()
+        }];
+
+        let merged = merge_scanned_hosts(existing, scanned);
+
+        assert_eq!(merged[0].last_connected, Some("01/01/2026 09:00:00".to_string()));
+        assert_eq!(merged[0].connect_count, 2);
+        // Blank scanned description keeps the existing one
+        assert_eq!(merged[0].description, "Old");
+    }
+
+    #[test]
+    fn test_merge_scanned_hosts_new_host_has_no_timestamp() {
+        let existing = vec![Host {
+            hostname: "server01.domain.com".to_string(),
+            last_connected: Some("14/12/2025 10:30:00".to_string()),
+            connect_count: 3,
+            ..Default::default()
+        }];
+        let scanned = vec![create_test_host("server02.domain.com", "New Server")];
+
+        let merged = merge_scanned_hosts(existing, scanned);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1].hostname, "server02.domain.com");
+        assert_eq!(merged[1].last_connected, None);
+        assert_eq!(merged[1].connect_count, 0);
+    }
+
+    #[test]
+    fn test_scan_preview_does_not_modify_csv() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[create_test_host("server01.domain.com", "Keep")])
+            .expect("Failed to write CSV");
+        let before = std::fs::read(&csv_path).expect("Failed to read CSV bytes");
+
+        // The preview is the parsed search results; building it touches no file
+        let preview: Vec<Host> = [
+            ("CN=SERVER02,OU=Servers,DC=domain,DC=com", "server02.domain.com", "Database Server"),
+            ("CN=SERVER03,OU=Servers,DC=domain,DC=com", "server03.domain.com", "File Server"),
+        ]
+        .iter()
+        .filter_map(|(dn, hostname, description)| {
+            crate::core::ldap::parse_search_entry(&ldap3::SearchEntry {
+                dn: dn.to_string(),
+                attrs: [("dNSHostName", hostname), ("description", description)]
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), vec![value.to_string()]))
+                    .collect(),
+                bin_attrs: std::collections::HashMap::new(),
+            })
+        })
+        .map(|scanned| scanned.to_host())
+        .collect();
+        assert_eq!(preview.len(), 2);
+        assert_eq!(std::fs::read(&csv_path).expect("Failed to read CSV bytes"), before);
+
+        // Only committing the selection writes the file
+        let count = commit_scanned_hosts_to(&csv_path, preview[..1].to_vec(), ScanCommitMode::Merge)
+            .expect("Commit should succeed");
+
+        assert_eq!(count, 2);
+        assert_ne!(std::fs::read(&csv_path).expect("Failed to read CSV bytes"), before);
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        let hostnames: Vec<&str> = loaded.iter().map(|h| h.hostname.as_str()).collect();
+        assert_eq!(hostnames, vec!["server01.domain.com", "server02.domain.com"]);
+        assert_eq!(loaded[0].description, "Keep");
+    }
+
+    fn grouped_host(hostname: &str, group: Option<&str>) -> Host {
+        Host {
+            hostname: hostname.to_string(),
+            group: group.map(|g| g.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_hosts_in_group_matches_case_insensitively() {
+        let hosts = vec![
+            grouped_host("web01.domain.com", Some("Patch Tuesday")),
+            grouped_host("db01.domain.com", Some("Databases")),
+            grouped_host("web02.domain.com", Some("patch tuesday ")),
+            grouped_host("misc.domain.com", None),
+        ];
+
+        let selected = hosts_in_group(&hosts, " PATCH TUESDAY");
+
+        let names: Vec<&str> = selected.iter().map(|h| h.hostname.as_str()).collect();
+        assert_eq!(names, vec!["web01.domain.com", "web02.domain.com"]);
+    }
+
+    #[test]
+    fn test_select_group_hosts_unknown_group_is_empty() {
+        let hosts = vec![grouped_host("web01.domain.com", Some("Web"))];
+        let selected = select_group_hosts(&hosts, "Nope", false).expect("Selection should succeed");
+        assert!(selected.is_empty());
+    }
+
+    #[test]
+    fn test_select_group_hosts_enforces_cap() {
+        let hosts: Vec<Host> = (0..=GROUP_CONNECT_LIMIT)
+            .map(|i| grouped_host(&format!("server{:02}.domain.com", i), Some("Big")))
+            .collect();
+
+        let result = select_group_hosts(&hosts, "Big", false);
+        assert!(matches!(result, Err(AppError::Other { .. })));
+
+        let forced = select_group_hosts(&hosts, "Big", true).expect("Forced selection should succeed");
+        assert_eq!(forced.len(), GROUP_CONNECT_LIMIT + 1);
+    }
+
+    #[test]
+    fn test_select_group_hosts_at_cap_is_allowed() {
+        let hosts: Vec<Host> = (0..GROUP_CONNECT_LIMIT)
+            .map(|i| grouped_host(&format!("server{:02}.domain.com", i), Some("Big")))
+            .collect();
+
+        let selected = select_group_hosts(&hosts, "Big", false).expect("Selection at the cap should succeed");
+        assert_eq!(selected.len(), GROUP_CONNECT_LIMIT);
+    }
+
+    fn source_host() -> Host {
+        let mut host = Host {
+            hostname: "web01.domain.com".to_string(),
+            description: "Web Server".to_string(),
+            last_connected: Some("15/01/2024 10:30:00".to_string()),
+            connect_username: Some(".\\localadmin".to_string()),
+            group: Some("Web".to_string()),
+            connect_count: 7,
+            ..Default::default()
+        };
+        host.extra_columns.insert("rack".to_string(), "R12".to_string());
+        host
+    }
+
+    #[test]
+    fn test_duplicate_host_copies_fields_and_resets_usage() {
+        let hosts = vec![source_host()];
+
+        let copy = duplicate_host_entry(&hosts, "web01.domain.com", " web02.domain.com ")
+            .expect("Duplicate should succeed");
+
+        assert_eq!(copy.hostname, "web02.domain.com");
+        assert_eq!(copy.description, "Web Server");
+        assert_eq!(copy.connect_username, Some(".\\localadmin".to_string()));
+        assert_eq!(copy.group, Some("Web".to_string()));
+        assert_eq!(copy.extra_columns.get("rack"), Some(&"R12".to_string()));
+        assert_eq!(copy.last_connected, None);
+        assert_eq!(copy.connect_count, 0);
+    }
+
+    #[test]
+    fn test_duplicate_host_rejects_collision() {
+        let hosts = vec![source_host()];
+
+        let result = duplicate_host_entry(&hosts, "web01.domain.com", "WEB01.domain.com");
+
+        assert!(matches!(result, Err(AppError::InvalidHostname { .. })));
+    }
+
+    #[test]
+    fn test_duplicate_host_rejects_invalid_name() {
+        let hosts = vec![source_host()];
+
+        for name in ["", "   ", "web 02.domain.com", "web02;rm"] {
+            let result = duplicate_host_entry(&hosts, "web01.domain.com", name);
+            assert!(
+                matches!(result, Err(AppError::InvalidHostname { .. })),
+                "'{}' should be rejected",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_host_missing_source() {
+        let result = duplicate_host_entry(&[], "missing.domain.com", "web02.domain.com");
+        assert!(matches!(result, Err(AppError::HostNotFound { .. })));
+    }
+
+    #[test]
+    fn test_duplicate_host_at_persists_copy() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+
+        duplicate_host_at(&csv_path, "web01.domain.com", "web02.domain.com")
+            .expect("Duplicate should succeed");
+
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].connect_count, 7);
+        assert_eq!(loaded[1].hostname, "web02.domain.com");
+        assert_eq!(loaded[1].group, Some("Web".to_string()));
+        assert_eq!(loaded[1].connect_count, 0);
+    }
+
+    #[test]
+    fn test_hosts_version_increments_on_each_mutation() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+
+        let start = hosts_version();
+        commit_scanned_hosts_to(&csv_path, vec![source_host()], ScanCommitMode::Replace)
+            .expect("Commit should succeed");
+        assert_eq!(hosts_version(), start + 1);
+
+        duplicate_host_at(&csv_path, "web01.domain.com", "web02.domain.com")
+            .expect("Duplicate should succeed");
+        assert_eq!(hosts_version(), start + 2);
+
+        commit_scanned_hosts_to(
+            &csv_path,
+            vec![create_test_host("web03.domain.com", "Web 3")],
+            ScanCommitMode::Merge,
+        )
+        .expect("Commit should succeed");
+        assert_eq!(hosts_version(), start + 3);
+    }
+
+    #[test]
+    fn test_hosts_version_unchanged_by_reads() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+
+        let start = hosts_version();
+        let hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        let _ = hosts_in_group(&hosts, "Web");
+        let _ = duplicate_host_entry(&hosts, "web01.domain.com", "web02.domain.com");
+
+        assert_eq!(hosts_version(), start);
+    }
+
+    #[test]
+    fn test_failed_mutation_does_not_bump_version() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+
+        let start = hosts_version();
+        let result = duplicate_host_at(&csv_path, "web01.domain.com", "web01.domain.com");
+
+        assert!(result.is_err());
+        assert_eq!(hosts_version(), start);
+    }
+
+    fn names(hostnames: &[&str]) -> Vec<String> {
+        hostnames.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn test_set_hosts_group_moves_several_hosts() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        let hosts = vec![
+            create_test_host("web01.domain.com", "Web 1"),
+            create_test_host("web02.domain.com", "Web 2"),
+            create_test_host("db01.domain.com", "DB"),
+        ];
+        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
+
+        let changed = set_hosts_group_at(
+            &csv_path,
+            &names(&["WEB01.domain.com", "web02.domain.com"]),
+            Some(" Web "),
+        )
+        .expect("Group update should succeed");
+
+        assert_eq!(changed, 2);
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded[0].group, Some("Web".to_string()));
+        assert_eq!(loaded[1].group, Some("Web".to_string()));
+        assert_eq!(loaded[2].group, None);
+    }
+
+    #[test]
+    fn test_set_hosts_group_none_clears_group() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+
+        let changed = set_hosts_group_at(&csv_path, &names(&["web01.domain.com"]), None)
+            .expect("Group update should succeed");
+
+        assert_eq!(changed, 1);
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded[0].group, None);
+        assert_eq!(loaded[0].connect_count, 7);
+    }
+
+    #[test]
+    fn test_set_hosts_group_skips_unknown_hostnames() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+
+        let start = hosts_version();
+        let changed = set_hosts_group_at(
+            &csv_path,
+            &names(&["missing.domain.com", "web01.domain.com"]),
+            Some("Web"),
+        )
+        .expect("Unknown hostnames should not be an error");
+
+        // web01 is already in "Web", so nothing changed and nothing was written
+        assert_eq!(changed, 0);
+        assert_eq!(hosts_version(), start);
+    }
+
+    #[test]
+    fn test_set_host_tags_replaces_and_removes_tags() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        let mut host = source_host();
+        host.tags = names(&["prod", "web", "legacy"]);
+        csv_writer::write_hosts_to_csv(&csv_path, &[host]).expect("Failed to write CSV");
+
+        let saved = set_host_tags_at(&csv_path, "WEB01.domain.com", &names(&[" Prod ", "web"]))
+            .expect("Tag update should succeed");
+        assert_eq!(saved, names(&["prod", "web"]));
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(loaded[0].tags, names(&["prod", "web"]));
+
+        set_host_tags_at(&csv_path, "web01.domain.com", &[]).expect("Clearing tags should succeed");
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert!(loaded[0].tags.is_empty());
+        // Other fields are untouched
+        assert_eq!(loaded[0].connect_count, 7);
+        assert_eq!(loaded[0].group, Some("Web".to_string()));
+    }
+
+    #[test]
+    fn test_set_host_tags_unknown_host() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+
+        let result = set_host_tags_at(&csv_path, "missing.domain.com", &names(&["prod"]));
+        assert!(matches!(result, Err(AppError::HostNotFound { .. })));
+    }
+
+    fn ordered_hostnames(hosts: &[Host]) -> Vec<&str> {
+        hosts.iter().map(|h| h.hostname.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_hosts_by_order_puts_unset_last() {
+        let mut hosts = vec![
+            create_test_host("a.domain.com", ""),
+            Host { sort_order: Some(1), ..create_test_host("b.domain.com", "") },
+            create_test_host("c.domain.com", ""),
+            Host { sort_order: Some(0), ..create_test_host("d.domain.com", "") },
+        ];
+
+        sort_hosts_by_order(&mut hosts);
+
+        assert_eq!(
+            ordered_hostnames(&hosts),
+            vec!["d.domain.com", "b.domain.com", "a.domain.com", "c.domain.com"]
+        );
     }
 
     #[test]
-    fn test_search_hosts_case_insensitive() {
+    fn test_reorder_hosts_at_persists_order() {
+        let _version = lock_version();
         let (_temp_dir, csv_path) = setup_test_env();
-        
         let hosts = vec![
-            create_test_host("SERVER01.DOMAIN.COM", "Web Server"),
-            create_test_host("server02.domain.com", "Database"),
+            create_test_host("a.domain.com", "A"),
+            create_test_host("b.domain.com", "B"),
+            create_test_host("c.domain.com", "C"),
+            create_test_host("d.domain.com", "D"),
         ];
-        
         csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        // Test case-insensitive search
-        let query = "server01";
-        let filtered: Vec<Host> = loaded_hosts
-            .into_iter()
-            .filter(|host| {
-                host.hostname.to_lowercase().contains(&query.to_lowercase())
-                    || host.description.to_lowercase().contains(&query.to_lowercase())
-            })
-            .collect();
-        
-        assert_eq!(filtered.len(), 1);
-        assert_eq!(filtered[0].hostname.to_lowercase(), "server01.domain.com");
+
+        reorder_hosts_at(&csv_path, &names(&["C.domain.com", "a.domain.com", "c.domain.com"]))
+            .expect("Reorder should succeed");
+
+        let mut loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        // Row order in the file is unchanged
+        assert_eq!(
+            ordered_hostnames(&loaded),
+            vec!["a.domain.com", "b.domain.com", "c.domain.com", "d.domain.com"]
+        );
+        let orders: Vec<Option<u32>> = loaded.iter().map(|h| h.sort_order).collect();
+        assert_eq!(orders, vec![Some(1), Some(2), Some(0), Some(3)]);
+
+        // Listed hosts first, then the rest in their previous order
+        sort_hosts_by_order(&mut loaded);
+        assert_eq!(
+            ordered_hostnames(&loaded),
+            vec!["c.domain.com", "a.domain.com", "b.domain.com", "d.domain.com"]
+        );
     }
 
     #[test]
-    fn test_search_hosts_empty_query_returns_all() {
+    fn test_reorder_hosts_at_keeps_previous_order_of_unlisted() {
+        let _version = lock_version();
         let (_temp_dir, csv_path) = setup_test_env();
-        
         let hosts = vec![
-            create_test_host("server01.domain.com", "Web Server"),
-            create_test_host("server02.domain.com", "Database"),
+            create_test_host("a.domain.com", "A"),
+            create_test_host("b.domain.com", "B"),
+            create_test_host("c.domain.com", "C"),
         ];
-        
         csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        // Empty query should match all hosts
-        let query = "";
-        let filtered: Vec<Host> = loaded_hosts
-            .into_iter()
-            .filter(|host| {
-                host.hostname.to_lowercase().contains(query)
-                    || host.description.to_lowercase().contains(query)
-            })
-            .collect();
-        
-        assert_eq!(filtered.len(), 2);
+        reorder_hosts_at(&csv_path, &names(&["c.domain.com", "b.domain.com", "a.domain.com"]))
+            .expect("Reorder should succeed");
+
+        reorder_hosts_at(&csv_path, &names(&["a.domain.com"])).expect("Reorder should succeed");
+
+        let mut loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        sort_hosts_by_order(&mut loaded);
+        assert_eq!(
+            ordered_hostnames(&loaded),
+            vec!["a.domain.com", "c.domain.com", "b.domain.com"]
+        );
     }
 
     #[test]
-    fn test_search_hosts_no_matches() {
+    fn test_reorder_hosts_at_unknown_host() {
+        let _version = lock_version();
         let (_temp_dir, csv_path) = setup_test_env();
-        
+        csv_writer::write_hosts_to_csv(&csv_path, &[create_test_host("a.domain.com", "A")])
+            .expect("Failed to write CSV");
+        let version = hosts_version();
+
+        let result = reorder_hosts_at(&csv_path, &names(&["missing.domain.com"]));
+
+        assert!(matches!(result, Err(AppError::HostNotFound { .. })));
+        assert_eq!(hosts_version(), version);
+    }
+
+    #[test]
+    fn test_upsert_and_delete_keep_file_order() {
+        let _version = lock_version();
+        let env = crate::infra::paths::EnvGuard::lock();
+        let data_dir = TempDir::new().expect("Failed to create temp dir");
+        env.set(crate::infra::paths::DATA_DIR_ENV_VAR, data_dir.path());
+        let path = hosts_store::active_hosts_path().expect("Hosts path should resolve");
         let hosts = vec![
-            create_test_host("server01.domain.com", "Web Server"),
-            create_test_host("server02.domain.com", "Database"),
+            create_test_host("a.domain.com", "A"),
+            Host { sort_order: Some(0), ..create_test_host("b.domain.com", "B") },
+            create_test_host("c.domain.com", "C"),
         ];
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        let query = "nonexistent";
-        let filtered: Vec<Host> = loaded_hosts
+        write_hosts(&path, &hosts).expect("Failed to write hosts");
+
+        // Displayed with the ordered host first
+        let displayed: Vec<String> = get_all_hosts().unwrap().into_iter().map(|h| h.hostname).collect();
+        assert_eq!(displayed, vec!["b.domain.com", "a.domain.com", "c.domain.com"]);
+
+        upsert_host(create_test_host("d.domain.com", "D")).expect("Upsert should succeed");
+        delete_host("c.domain.com").expect("Delete should succeed");
+
+        let saved: Vec<String> = read_hosts(&path).unwrap().into_iter().map(|h| h.hostname).collect();
+        assert_eq!(saved, vec!["a.domain.com", "b.domain.com", "d.domain.com"]);
+    }
+
+    #[test]
+    fn test_upsert_keeps_sort_order() {
+        let mut hosts = vec![Host { sort_order: Some(4), ..create_test_host("a.domain.com", "A") }];
+
+        merge_host(&mut hosts, create_test_host("a.domain.com", "Renamed"));
+
+        assert_eq!(hosts[0].description, "Renamed");
+        assert_eq!(hosts[0].sort_order, Some(4));
+    }
+
+    #[test]
+    fn test_merge_host_keeps_fields_not_sent() {
+        let mut hosts = vec![Host {
+            connect_username: Some("CONTOSO\\ops".to_string()),
+            group: Some("Web".to_string()),
+            extra_columns: [("site".to_string(), "London".to_string())].into_iter().collect(),
+            ..create_test_host("a.domain.com", "A")
+        }];
+
+        merge_host(&mut hosts, create_test_host("a.domain.com", "Renamed"));
+
+        assert_eq!(hosts[0].connect_username.as_deref(), Some("CONTOSO\\ops"));
+        assert_eq!(hosts[0].group.as_deref(), Some("Web"));
+        assert_eq!(hosts[0].extra_columns.get("site").map(String::as_str), Some("London"));
+    }
+
+    #[test]
+    fn test_merge_host_clears_explicitly_empty_fields() {
+        let mut hosts = vec![Host {
+            connect_username: Some("CONTOSO\\ops".to_string()),
+            group: Some("Web".to_string()),
+            extra_columns: [
+                ("site".to_string(), "London".to_string()),
+                ("rack".to_string(), "R1".to_string()),
+            ]
             .into_iter()
-            .filter(|host| {
-                host.hostname.to_lowercase().contains(query)
-                    || host.description.to_lowercase().contains(query)
-            })
-            .collect();
-        
-        assert!(filtered.is_empty());
+            .collect(),
+            last_connected: Some("01/01/2026 09:00:00".to_string()),
+            connect_count: 3,
+            ..create_test_host("a.domain.com", "A")
+        }];
+
+        merge_host(
+            &mut hosts,
+            Host {
+                connect_username: Some(String::new()),
+                group: Some(" ".to_string()),
+                extra_columns: [("site".to_string(), String::new())].into_iter().collect(),
+                ..create_test_host("a.domain.com", "A")
+            },
+        );
+
+        assert_eq!(hosts[0].connect_username, None);
+        assert_eq!(hosts[0].group, None);
+        assert!(!hosts[0].extra_columns.contains_key("site"));
+        assert_eq!(hosts[0].extra_columns.get("rack").map(String::as_str), Some("R1"));
+        // Fields the editor never sends are kept
+        assert_eq!(hosts[0].last_connected.as_deref(), Some("01/01/2026 09:00:00"));
+        assert_eq!(hosts[0].connect_count, 3);
     }
 
     #[test]
-    fn test_upsert_host_insert_new() {
+    fn test_normalize_hostname() {
+        assert_eq!(normalize_hostname(" Server01.Domain.COM. "), "server01.domain.com");
+        assert_eq!(normalize_hostname("server01.domain.com.."), "server01.domain.com");
+        assert_eq!(normalize_hostname("server01"), "server01");
+    }
+
+    #[test]
+    fn test_deduplicate_host_list_merges_case_variants() {
+        let hosts = vec![
+            create_test_host("Server01.Domain.com", "Web"),
+            create_test_host("db01.domain.com", "Database"),
+            create_test_host("server01.domain.com.", "Web"),
+            create_test_host("SERVER01.DOMAIN.COM", "Web"),
+        ];
+
+        let (deduplicated, merged) = deduplicate_host_list(hosts);
+
+        assert_eq!(merged, 2);
+        let hostnames: Vec<&str> = deduplicated.iter().map(|h| h.hostname.as_str()).collect();
+        // The first entry's hostname is kept as written
+        assert_eq!(hostnames, vec!["Server01.Domain.com", "db01.domain.com"]);
+    }
+
+    #[test]
+    fn test_deduplicate_host_list_keeps_best_metadata() {
+        let hosts = vec![
+            Host {
+                hostname: "server01.domain.com".to_string(),
+                description: "Web".to_string(),
+                last_connected: Some("01/03/2026 09:00:00".to_string()),
+                connect_count: 3,
+                tags: vec!["prod".to_string()],
+                ..Default::default()
+            },
+            Host {
+                hostname: "Server01.domain.com.".to_string(),
+                description: "Primary web server (IIS)".to_string(),
+                last_connected: Some("not a date".to_string()),
+                connect_count: 4,
+                group: Some("Web".to_string()),
+                tags: vec!["iis".to_string(), "prod".to_string()],
+                ..Default::default()
+            },
+            Host {
+                hostname: "SERVER01.domain.com".to_string(),
+                description: "".to_string(),
+                last_connected: Some("2026-03-10T08:30:00".to_string()),
+                connect_count: 5,
+                group: Some("Other".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let (deduplicated, merged) = deduplicate_host_list(hosts);
+
+        assert_eq!(merged, 2);
+        assert_eq!(deduplicated.len(), 1);
+        let host = &deduplicated[0];
+        assert_eq!(host.description, "Primary web server (IIS)");
+        assert_eq!(host.last_connected, Some("2026-03-10T08:30:00".to_string()));
+        assert_eq!(host.connect_count, 12);
+        // The first group found is kept
+        assert_eq!(host.group, Some("Web".to_string()));
+        assert_eq!(host.tags, vec!["prod".to_string(), "iis".to_string()]);
+    }
+
+    #[test]
+    fn test_deduplicate_hosts_at_writes_cleaned_list() {
+        let _version = lock_version();
         let (_temp_dir, csv_path) = setup_test_env();
-        
-        // Start with empty hosts
-        csv_writer::write_hosts_to_csv(&csv_path, &[]).expect("Failed to write CSV");
-        
-        let new_host = create_test_host("server01.domain.com", "Test Server");
-        
-        // Simulate upsert logic
-        let mut hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        // Validate hostname
-        assert!(!new_host.hostname.trim().is_empty());
-        
-        // Check if host exists
-        if !hosts.iter().any(|h| h.hostname == new_host.hostname) {
-            hosts.push(new_host.clone());
-        }
-        
+        let hosts = vec![
+            create_test_host("Server01.domain.com", "Web"),
+            create_test_host("server01.domain.com", "Web server"),
+        ];
         csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Verify
+
+        let merged = deduplicate_hosts_at(&csv_path).expect("Deduplication should succeed");
+
+        assert_eq!(merged, 1);
         let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
         assert_eq!(loaded.len(), 1);
-        assert_eq!(loaded[0].hostname, "server01.domain.com");
-        assert_eq!(loaded[0].description, "Test Server");
+        assert_eq!(loaded[0].hostname, "Server01.domain.com");
+        assert_eq!(loaded[0].description, "Web server");
     }
 
     #[test]
-    fn test_upsert_host_update_existing() {
+    fn test_deduplicate_hosts_at_leaves_clean_file_untouched() {
+        let _version = lock_version();
         let (_temp_dir, csv_path) = setup_test_env();
-        
-        // Start with one host
-        let initial_hosts = vec![
-            create_test_host("server01.domain.com", "Old Description"),
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &initial_hosts).expect("Failed to write CSV");
-        
-        // Update with same hostname, different description
-        let updated_host = Host {
-            hostname: "server01.domain.com".to_string(),
-            description: "New Description".to_string(),
-            last_connected: Some("14/12/2025 10:30:00".to_string()),
-        };
-        
-        // Simulate upsert logic
-        let mut hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        if let Some(idx) = hosts.iter().position(|h| h.hostname == updated_host.hostname) {
-            hosts[idx] = updated_host.clone();
-        } else {
-            hosts.push(updated_host.clone());
+        csv_writer::write_hosts_to_csv(&csv_path, &[create_test_host("server01.domain.com", "Web")])
+            .expect("Failed to write CSV");
+        let version = hosts_version();
+
+        let merged = deduplicate_hosts_at(&csv_path).expect("Deduplication should succeed");
+
+        assert_eq!(merged, 0);
+        assert_eq!(hosts_version(), version);
+    }
+
+    #[test]
+    fn test_deduplicate_hosts_at_keeps_hostname_case_without_duplicates() {
+        let _version = lock_version();
+        let (_temp_dir, csv_path) = setup_test_env();
+        csv_writer::write_hosts_to_csv(
+            &csv_path,
+            &[
+                create_test_host("Server01.Domain.com", "Web"),
+                create_test_host("db01.domain.com.", "Database"),
+            ],
+        )
+        .expect("Failed to write CSV");
+        let version = hosts_version();
+
+        let merged = deduplicate_hosts_at(&csv_path).expect("Deduplication should succeed");
+
+        assert_eq!(merged, 0);
+        assert_eq!(hosts_version(), version);
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        let hostnames: Vec<&str> = loaded.iter().map(|h| h.hostname.as_str()).collect();
+        assert_eq!(hostnames, vec!["Server01.Domain.com", "db01.domain.com."]);
+    }
+
+    fn tagged_host(hostname: &str, tags: &[&str]) -> Host {
+        Host {
+            hostname: hostname.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
         }
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Verify
+    }
+
+    fn tagged_hosts() -> Vec<Host> {
+        vec![
+            tagged_host("sql01", &["prod", "sql"]),
+            tagged_host("web01", &["prod", "dmz"]),
+            tagged_host("sql02", &["test", "sql"]),
+            tagged_host("misc01", &[]),
+        ]
+    }
+
+    fn hostnames(hosts: &[Host]) -> Vec<&str> {
+        hosts.iter().map(|h| h.hostname.as_str()).collect()
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = names(&[" Prod ", "SQL", "", "prod", "dmz"]);
+        assert_eq!(normalize_tags(&tags), vec!["prod", "sql", "dmz"]);
+        assert_eq!(parse_tags("Prod; sql;;DMZ"), vec!["prod", "sql", "dmz"]);
+        assert!(parse_tags("").is_empty());
+    }
+
+    #[test]
+    fn test_filter_hosts_by_tags_match_all() {
+        let found = filter_hosts_by_tags(tagged_hosts(), &names(&["prod", "SQL"]), true);
+        assert_eq!(hostnames(&found), vec!["sql01"]);
+    }
+
+    #[test]
+    fn test_filter_hosts_by_tags_match_any() {
+        let found = filter_hosts_by_tags(tagged_hosts(), &names(&["dmz", "test"]), false);
+        assert_eq!(hostnames(&found), vec!["web01", "sql02"]);
+    }
+
+    #[test]
+    fn test_filter_hosts_by_tags_empty_list_matches_all() {
+        for match_all in [true, false] {
+            let found = filter_hosts_by_tags(tagged_hosts(), &[], match_all);
+            assert_eq!(found.len(), 4);
+
+            let found = filter_hosts_by_tags(tagged_hosts(), &names(&["  "]), match_all);
+            assert_eq!(found.len(), 4);
+        }
+    }
+
+    #[test]
+    fn test_filter_hosts_by_tags_no_match() {
+        let found = filter_hosts_by_tags(tagged_hosts(), &names(&["missing"]), false);
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_delete_all_guard_accepts_matching_token() {
+        let mut guard = DeleteAllGuard::default();
+        let token = guard.issue("abc123".to_string(), 1_000);
+
+        assert!(guard.redeem(&token, 1_000 + DELETE_ALL_TOKEN_TTL_SECS).is_ok());
+    }
+
+    #[test]
+    fn test_delete_all_guard_token_is_single_use() {
+        let mut guard = DeleteAllGuard::default();
+        let token = guard.issue("abc123".to_string(), 1_000);
+
+        assert!(guard.redeem(&token, 1_001).is_ok());
+        assert!(guard.redeem(&token, 1_002).is_err());
+    }
+
+    #[test]
+    fn test_delete_all_guard_rejects_mismatch() {
+        let mut guard = DeleteAllGuard::default();
+        guard.issue("abc123".to_string(), 1_000);
+
+        let result = guard.redeem("wrong", 1_001);
+
+        assert!(matches!(result, Err(AppError::Other { ref message, .. }) if message.contains("does not match")));
+        // A failed attempt uses up the token
+        assert!(guard.redeem("abc123", 1_002).is_err());
+    }
+
+    #[test]
+    fn test_delete_all_guard_rejects_expired() {
+        let mut guard = DeleteAllGuard::default();
+        let token = guard.issue("abc123".to_string(), 1_000);
+
+        let result = guard.redeem(&token, 1_000 + DELETE_ALL_TOKEN_TTL_SECS + 1);
+
+        assert!(matches!(result, Err(AppError::Other { ref message, .. }) if message.contains("expired")));
+    }
+
+    #[test]
+    fn test_delete_all_guard_rejects_without_request() {
+        let mut guard = DeleteAllGuard::default();
+        assert!(guard.redeem("", 1_000).is_err());
+    }
+
+    #[test]
+    fn test_delete_all_guard_reissue_replaces_token() {
+        let mut guard = DeleteAllGuard::default();
+        let first = guard.issue("first".to_string(), 1_000);
+        guard.issue("second".to_string(), 1_010);
+
+        assert!(guard.redeem(&first, 1_011).is_err());
+    }
+
+    #[test]
+    fn test_new_delete_all_tokens_differ() {
+        assert_ne!(new_delete_all_token(), new_delete_all_token());
+    }
+
+    fn write_rdp_file(dir: &Path, name: &str, content: &str) {
+        std::fs::write(dir.join(format!("{}.rdp", name)), content).expect("Failed to write RDP file");
+    }
+
+    #[test]
+    fn test_import_rdp_directory_adds_and_updates_hosts() {
+        let _version = lock_version();
+        let (temp_dir, csv_path) = setup_test_env();
+        let rdp_dir = temp_dir.path().join("rdp");
+        std::fs::create_dir(&rdp_dir).expect("Failed to create dir");
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+
+        write_rdp_file(&rdp_dir, "Web Server", "full address:s:web01.domain.com\r\n");
+        write_rdp_file(
+            &rdp_dir,
+            "SQL",
+            "full address:s:sql01.domain.com\r\nusername:s:dba\r\ndomain:s:CONTOSO\r\n",
+        );
+        std::fs::write(rdp_dir.join("notes.txt"), "full address:s:ignored").expect("Failed to write file");
+
+        let imported = import_rdp_directory_to(&csv_path, &rdp_dir).expect("Import should succeed");
+
+        assert_eq!(imported, 2);
         let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        assert_eq!(loaded.len(), 1);
-        assert_eq!(loaded[0].hostname, "server01.domain.com");
-        assert_eq!(loaded[0].description, "New Description");
-        assert_eq!(loaded[0].last_connected, Some("14/12/2025 10:30:00".to_string()));
+        assert_eq!(hostnames(&loaded), vec!["web01.domain.com", "sql01.domain.com"]);
+        // Existing host keeps what the file doesn't say
+        assert_eq!(loaded[0].description, "Web Server");
+        assert_eq!(loaded[0].connect_count, 7);
+        assert_eq!(loaded[0].last_connected, Some("15/01/2024 10:30:00".to_string()));
+        assert_eq!(loaded[1].connect_username, Some("CONTOSO\\dba".to_string()));
     }
 
     #[test]
-    fn test_upsert_host_rejects_empty_hostname() {
-        let host = Host {
-            hostname: "".to_string(),
-            description: "Test".to_string(),
-            last_connected: None,
-        };
-        
-        // Validate hostname
-        assert!(host.hostname.trim().is_empty());
+    fn test_import_rdp_directory_skips_malformed_files() {
+        let _version = lock_version();
+        let (temp_dir, csv_path) = setup_test_env();
+        write_rdp_file(temp_dir.path(), "broken", "screen mode id:i:2\r\n");
+        write_rdp_file(temp_dir.path(), "good", "full address:s:app01\r\n");
+
+        let imported = import_rdp_directory_to(&csv_path, temp_dir.path()).expect("Import should succeed");
+
+        assert_eq!(imported, 1);
+        let loaded = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
+        assert_eq!(hostnames(&loaded), vec!["app01"]);
     }
 
     #[test]
-    fn test_upsert_host_trims_whitespace_hostname() {
-        let host = Host {
-            hostname: "  server01.domain.com  ".to_string(),
-            description: "Test".to_string(),
-            last_connected: None,
-        };
-        
-        // Validate that trimmed hostname is not empty
-        assert!(!host.hostname.trim().is_empty());
-        assert_eq!(host.hostname.trim(), "server01.domain.com");
+    fn test_import_rdp_directory_nothing_to_import() {
+        let _version = lock_version();
+        let (temp_dir, csv_path) = setup_test_env();
+        let before = hosts_version();
+
+        let imported = import_rdp_directory_to(&csv_path, temp_dir.path()).expect("Import should succeed");
+
+        assert_eq!(imported, 0);
+        assert!(!csv_path.exists());
+        assert_eq!(hosts_version(), before);
     }
 
     #[test]
-    fn test_delete_host_removes_correct_host() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let hosts = vec![
-            create_test_host("server01.domain.com", "Server 1"),
-            create_test_host("server02.domain.com", "Server 2"),
-            create_test_host("server03.domain.com", "Server 3"),
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Simulate delete logic
-        let hostname_to_delete = "server02.domain.com";
-        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        loaded_hosts.retain(|h| h.hostname != hostname_to_delete);
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
-        
-        // Verify
-        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        assert_eq!(final_hosts.len(), 2);
-        assert!(final_hosts.iter().any(|h| h.hostname == "server01.domain.com"));
-        assert!(final_hosts.iter().any(|h| h.hostname == "server03.domain.com"));
-        assert!(!final_hosts.iter().any(|h| h.hostname == "server02.domain.com"));
+    fn test_import_rdp_directory_missing_dir() {
+        let (temp_dir, csv_path) = setup_test_env();
+        let result = import_rdp_directory_to(&csv_path, &temp_dir.path().join("missing"));
+        assert!(matches!(result, Err(AppError::IoError { .. })));
     }
 
     #[test]
-    fn test_delete_host_idempotent_when_not_exists() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let hosts = vec![
-            create_test_host("server01.domain.com", "Server 1"),
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Try to delete non-existent host
-        let hostname_to_delete = "nonexistent.domain.com";
-        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        let before_count = loaded_hosts.len();
-        loaded_hosts.retain(|h| h.hostname != hostname_to_delete);
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
-        
-        // Verify no change
-        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        assert_eq!(final_hosts.len(), before_count);
-        assert_eq!(final_hosts[0].hostname, "server01.domain.com");
+    fn test_json_backend_supports_path_operations() {
+        let _version = lock_version();
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let json_path = temp_dir.path().join("hosts.json");
+        let store = hosts_store::JsonHostsStore::new(&json_path);
+        store
+            .save(&[source_host(), create_test_host("db01.domain.com", "DB")])
+            .expect("Save should succeed");
+
+        let changed = set_hosts_group_at(&json_path, &names(&["db01.domain.com"]), Some("Data"))
+            .expect("Group update should succeed");
+        duplicate_host_at(&json_path, "web01.domain.com", "web02.domain.com")
+            .expect("Duplicate should succeed");
+
+        assert_eq!(changed, 1);
+        let loaded = store.load().expect("Load should succeed");
+        assert_eq!(hostnames(&loaded), vec!["web01.domain.com", "db01.domain.com", "web02.domain.com"]);
+        assert_eq!(loaded[1].group, Some("Data".to_string()));
+        assert!(!temp_dir.path().join("hosts.csv").exists());
     }
 
     #[test]
-    fn test_delete_all_hosts_clears_list() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let hosts = vec![
-            create_test_host("server01.domain.com", "Server 1"),
-            create_test_host("server02.domain.com", "Server 2"),
-            create_test_host("server03.domain.com", "Server 3"),
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Delete all
-        csv_writer::write_hosts_to_csv(&csv_path, &[]).expect("Failed to write empty CSV");
-        
-        // Verify
-        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        assert!(final_hosts.is_empty());
+    fn test_convert_hosts_storage_switches_format() {
+        let _version = lock_version();
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let csv_path = temp_dir.path().join("hosts.csv");
+        let json_path = temp_dir.path().join("hosts.json");
+        let settings_path = temp_dir.path().join("settings.json");
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+        let before = hosts_version();
+
+        let count = convert_hosts_storage_at(&csv_path, &json_path, &settings_path)
+            .expect("Conversion should succeed");
+
+        assert_eq!(count, 1);
+        assert!(hosts_version() > before);
+        assert!(csv_path.exists(), "the old file is kept as a backup");
+        let settings = crate::core::settings::load_settings_from(&settings_path).expect("Load should succeed");
+        assert_eq!(settings.storage_format, StorageFormat::Json);
+        let loaded = read_hosts(&json_path).expect("Load should succeed");
+        assert_eq!(loaded[0].connect_count, 7);
+        assert_eq!(loaded[0].extra_columns.get("rack"), Some(&"R12".to_string()));
+
+        convert_hosts_storage_at(&json_path, &csv_path, &settings_path).expect("Conversion should succeed");
+        let settings = crate::core::settings::load_settings_from(&settings_path).expect("Load should succeed");
+        assert_eq!(settings.storage_format, StorageFormat::Csv);
     }
 
     #[test]
-    fn test_update_last_connected_updates_timestamp() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let hosts = vec![
-            create_test_host("server01.domain.com", "Server 1"),
-            create_test_host("server02.domain.com", "Server 2"),
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Simulate timestamp update
-        let hostname_to_update = "server01.domain.com";
-        let timestamp = "14/12/2025 15:45:30";
-        
-        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        let mut found = false;
-        for host in &mut loaded_hosts {
-            if host.hostname == hostname_to_update {
-                host.last_connected = Some(timestamp.to_string());
-                found = true;
-                break;
-            }
-        }
-        
-        assert!(found, "Host should be found");
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
-        
-        // Verify
-        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        let updated_host = final_hosts.iter().find(|h| h.hostname == hostname_to_update).expect("Host should exist");
-        assert_eq!(updated_host.last_connected, Some(timestamp.to_string()));
+    fn test_convert_hosts_storage_twice_keeps_live_hosts() {
+        let _version = lock_version();
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let csv_path = temp_dir.path().join("hosts.csv");
+        let json_path = temp_dir.path().join("hosts.json");
+        let settings_path = temp_dir.path().join("settings.json");
+        csv_writer::write_hosts_to_csv(&csv_path, &[source_host()]).expect("Failed to write CSV");
+        convert_hosts_storage_at(&csv_path, &json_path, &settings_path).expect("Conversion should succeed");
+
+        // A host added after the switch only exists in hosts.json
+        let mut live = read_hosts(&json_path).expect("Load should succeed");
+        live.push(create_test_host("new.domain.com", "Added later"));
+        write_hosts(&json_path, &live).expect("Save should succeed");
+
+        let count = convert_hosts_storage_at(&csv_path, &json_path, &settings_path)
+            .expect("Converting to the active format should succeed");
+
+        assert_eq!(count, 2);
+        let loaded = read_hosts(&json_path).expect("Load should succeed");
+        assert_eq!(hostnames(&loaded), vec!["web01.domain.com", "new.domain.com"]);
+    }
+
+    fn regex_sample_hosts() -> Vec<Host> {
+        vec![
+            create_test_host("web01.prod.domain.com", "IIS"),
+            create_test_host("WEB02.prod.domain.com", "IIS"),
+            create_test_host("web03.test.domain.com", "IIS"),
+            create_test_host("sql01.prod.domain.com", "Web database"),
+        ]
     }
 
     #[test]
-    fn test_update_last_connected_returns_error_if_not_found() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let hosts = vec![
-            create_test_host("server01.domain.com", "Server 1"),
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Try to update non-existent host
-        let hostname_to_update = "nonexistent.domain.com";
-        let timestamp = "14/12/2025 15:45:30";
-        
-        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        let mut found = false;
-        for host in &mut loaded_hosts {
-            if host.hostname == hostname_to_update {
-                host.last_connected = Some(timestamp.to_string());
-                found = true;
-                break;
-            }
-        }
-        
-        assert!(!found, "Host should not be found");
+    fn test_filter_hosts_by_regex_matches_subset() {
+        let matched = filter_hosts_by_regex(regex_sample_hosts(), r"web\d+\.prod")
+            .expect("Pattern should compile");
+        assert_eq!(hostnames(&matched), vec!["web01.prod.domain.com", "WEB02.prod.domain.com"]);
     }
 
     #[test]
-    fn test_update_last_connected_preserves_other_fields() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let original_description = "Important Server";
-        let hosts = vec![
-            Host {
-                hostname: "server01.domain.com".to_string(),
-                description: original_description.to_string(),
-                last_connected: Some("13/12/2025 10:00:00".to_string()),
-            },
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        // Update timestamp
-        let hostname_to_update = "server01.domain.com";
-        let new_timestamp = "14/12/2025 15:45:30";
-        
-        let mut loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        for host in &mut loaded_hosts {
-            if host.hostname == hostname_to_update {
-                host.last_connected = Some(new_timestamp.to_string());
-                break;
-            }
-        }
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &loaded_hosts).expect("Failed to write CSV");
-        
-        // Verify other fields preserved
-        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        let updated_host = &final_hosts[0];
-        assert_eq!(updated_host.hostname, "server01.domain.com");
-        assert_eq!(updated_host.description, original_description);
-        assert_eq!(updated_host.last_connected, Some(new_timestamp.to_string()));
+    fn test_filter_hosts_by_regex_anchored() {
+        let matched = filter_hosts_by_regex(regex_sample_hosts(), r"^web")
+            .expect("Pattern should compile");
+        // The description "Web database" matches too
+        assert_eq!(
+            hostnames(&matched),
+            vec![
+                "web01.prod.domain.com",
+                "WEB02.prod.domain.com",
+                "web03.test.domain.com",
+                "sql01.prod.domain.com",
+            ]
+        );
+
+        let matched = filter_hosts_by_regex(regex_sample_hosts(), r"^sql\d+\.prod\.domain\.com$")
+            .expect("Pattern should compile");
+        assert_eq!(hostnames(&matched), vec!["sql01.prod.domain.com"]);
     }
 
     #[test]
-    fn test_upsert_multiple_hosts_maintains_order() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        csv_writer::write_hosts_to_csv(&csv_path, &[]).expect("Failed to write CSV");
-        
-        let hosts_to_add = vec![
-            create_test_host("alpha.domain.com", "Alpha"),
-            create_test_host("beta.domain.com", "Beta"),
-            create_test_host("gamma.domain.com", "Gamma"),
-        ];
-        
-        // Add hosts one by one
-        let mut current_hosts = Vec::new();
-        for host in hosts_to_add {
-            current_hosts.push(host);
-            csv_writer::write_hosts_to_csv(&csv_path, &current_hosts).expect("Failed to write CSV");
-        }
-        
-        // Verify order preserved
-        let final_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        assert_eq!(final_hosts.len(), 3);
-        assert_eq!(final_hosts[0].hostname, "alpha.domain.com");
-        assert_eq!(final_hosts[1].hostname, "beta.domain.com");
-        assert_eq!(final_hosts[2].hostname, "gamma.domain.com");
+    fn test_filter_hosts_by_regex_case_sensitive_opt_out() {
+        let matched = filter_hosts_by_regex(regex_sample_hosts(), r"(?-i)^WEB")
+            .expect("Pattern should compile");
+        assert_eq!(hostnames(&matched), vec!["WEB02.prod.domain.com"]);
     }
 
     #[test]
-    fn test_search_with_special_characters() {
-        let (_temp_dir, csv_path) = setup_test_env();
-        
-        let hosts = vec![
-            create_test_host("server-01.domain.com", "Server with dash"),
-            create_test_host("server_02.domain.com", "Server with underscore"),
-            create_test_host("server.03.domain.com", "Server with dots"),
-        ];
-        csv_writer::write_hosts_to_csv(&csv_path, &hosts).expect("Failed to write CSV");
-        
-        let loaded_hosts = csv_reader::read_hosts_from_csv(&csv_path).expect("Failed to read CSV");
-        
-        // Search for dash
-        let query = "server-";
-        let filtered: Vec<Host> = loaded_hosts.clone()
-            .into_iter()
-            .filter(|host| {
-                host.hostname.to_lowercase().contains(query)
-                    || host.description.to_lowercase().contains(query)
-            })
-            .collect();
-        
-        assert_eq!(filtered.len(), 1);
-        assert_eq!(filtered[0].hostname, "server-01.domain.com");
+    fn test_filter_hosts_by_regex_invalid_pattern() {
+        let result = filter_hosts_by_regex(regex_sample_hosts(), r"web(\d+");
+        match result {
+            Err(AppError::Other { message, .. }) => assert!(message.contains("Invalid search pattern")),
+            other => panic!("Expected an invalid pattern error, got {:?}", other.map(|h| h.len())),
+        }
     }
 }
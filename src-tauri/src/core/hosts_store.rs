@@ -0,0 +1,341 @@
+//! Host list persistence backends
+//!
+//! Hosts are stored either in `hosts.csv` (the default) or in `hosts.json`,
+//! chosen by the `storage_format` setting.
+//!
+//! # Why this exists
+//! CSV is easy to edit by hand but awkward for richer fields such as tags.
+//! JSON stores every field natively. `core::hosts` goes through `HostsStore`
+//! so its operations don't depend on the format.
+//!
+//! # Why separate
+//! Each store only knows how to load and save a whole host list at a path,
+//! so both backends can be tested against temporary files.
+
+use crate::core::{csv_reader, csv_writer};
+use crate::infra::{get_hosts_csv_path, get_hosts_json_path};
+use crate::{AppError, Host};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File format the host list is persisted in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// `hosts.csv`
+    #[default]
+    Csv,
+    /// `hosts.json`
+    Json,
+}
+
+impl StorageFormat {
+    /// Returns the format for a hosts file path (`.json` is JSON, anything else CSV)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => StorageFormat::Json,
+            _ => StorageFormat::Csv,
+        }
+    }
+
+    /// Returns the other format
+    pub fn other(self) -> Self {
+        match self {
+            StorageFormat::Csv => StorageFormat::Json,
+            StorageFormat::Json => StorageFormat::Csv,
+        }
+    }
+}
+
+/// Loads and saves the whole host list
+pub trait HostsStore {
+    /// Path of the file backing this store
+    fn path(&self) -> &Path;
+
+    /// Loads all hosts (empty if the file doesn't exist)
+    fn load(&self) -> Result<Vec<Host>, AppError>;
+
+    /// Replaces the stored hosts, creating the file if needed
+    fn save(&self, hosts: &[Host]) -> Result<(), AppError>;
+}
+
+/// `hosts.csv` backend (see `csv_reader` / `csv_writer`)
+pub struct CsvHostsStore {
+    path: PathBuf,
+}
+
+impl CsvHostsStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl HostsStore for CsvHostsStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<Vec<Host>, AppError> {
+        csv_reader::read_hosts_from_csv(&self.path)
+    }
+
+    fn save(&self, hosts: &[Host]) -> Result<(), AppError> {
+        csv_writer::write_hosts_to_csv(&self.path, hosts)
+    }
+}
+
+/// `hosts.json` backend: a pretty-printed array of hosts
+pub struct JsonHostsStore {
+    path: PathBuf,
+}
+
+impl JsonHostsStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl HostsStore for JsonHostsStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<Vec<Host>, AppError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let json = std::fs::read_to_string(&self.path).map_err(|e| AppError::IoError {
+            path: self.path.to_string_lossy().to_string(),
+            source: e,
+        })?;
+
+        let hosts: Vec<Host> = serde_json::from_str(&json).map_err(|e| AppError::JsonError {
+            context: format!("Failed to parse hosts file {:?}", self.path),
+            source: e,
+        })?;
+
+        // Same rule as the CSV reader: a host without a hostname can't be used
+        Ok(hosts
+            .into_iter()
+            .filter(|host| !host.hostname.trim().is_empty())
+            .collect())
+    }
+
+    fn save(&self, hosts: &[Host]) -> Result<(), AppError> {
+        let hosts: Vec<&Host> = hosts
+            .iter()
+            .filter(|host| !host.hostname.trim().is_empty())
+            .collect();
+        let json = serde_json::to_string_pretty(&hosts).map_err(|e| AppError::JsonError {
+            context: "Failed to serialize hosts".to_string(),
+            source: e,
+        })?;
+
+        std::fs::write(&self.path, json).map_err(|e| AppError::IoError {
+            path: self.path.to_string_lossy().to_string(),
+            source: e,
+        })
+    }
+}
+
+/// Returns the store for a hosts file, chosen by its extension
+pub fn store_for_path(path: &Path) -> Box<dyn HostsStore> {
+    match StorageFormat::from_path(path) {
+        StorageFormat::Csv => Box::new(CsvHostsStore::new(path)),
+        StorageFormat::Json => Box::new(JsonHostsStore::new(path)),
+    }
+}
+
+/// Returns the path of the hosts file for a storage format
+pub fn hosts_path_for(format: StorageFormat) -> Result<PathBuf, AppError> {
+    let path = match format {
+        StorageFormat::Csv => get_hosts_csv_path(),
+        StorageFormat::Json => get_hosts_json_path(),
+    };
+
+    path.map_err(|e| AppError::Other {
+        message: format!("Failed to get hosts file path: {}", e),
+        source: None,
+    })
+}
+
+/// Returns the path of the hosts file for a storage format in a given data
+/// directory, without creating anything
+///
+/// Agrees with [`hosts_path_for`] for the resolved data directory.
+pub fn hosts_path_in(data_dir: &Path, format: StorageFormat) -> PathBuf {
+    data_dir.join(match format {
+        StorageFormat::Csv => "hosts.csv",
+        StorageFormat::Json => "hosts.json",
+    })
+}
+
+/// Returns the path of the hosts file selected by the `storage_format` setting
+pub fn active_hosts_path() -> Result<PathBuf, AppError> {
+    let format = crate::core::settings::load_settings()
+        .map(|settings| settings.storage_format)
+        .unwrap_or_default();
+    hosts_path_for(format)
+}
+
+/// Copies every host from one store to another, replacing its contents
+///
+/// # Returns
+/// * `Ok(usize)` - Number of hosts copied
+/// * `Err(AppError)` - The source couldn't be read or the target written
+pub fn copy_hosts(from: &dyn HostsStore, to: &dyn HostsStore) -> Result<usize, AppError> {
+    let hosts = from.load()?;
+    to.save(&hosts)?;
+    Ok(hosts.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hosts_path_in_matches_hosts_path_for() {
+        let env = crate::infra::paths::EnvGuard::lock();
+        let data_dir = TempDir::new().expect("Failed to create temp dir");
+        env.set(crate::infra::paths::DATA_DIR_ENV_VAR, data_dir.path());
+
+        for format in [StorageFormat::Csv, StorageFormat::Json] {
+            assert_eq!(
+                hosts_path_for(format).expect("Path should resolve"),
+                hosts_path_in(data_dir.path(), format)
+            );
+        }
+    }
+
+    fn full_host() -> Host {
+        let mut host = Host {
+            hostname: "sql01.domain.com".to_string(),
+            description: "SQL, primary".to_string(),
+            last_connected: Some("15/01/2024 10:30:00".to_string()),
+            connect_username: Some("CONTOSO\\dba".to_string()),
+            group: Some("Databases".to_string()),
+            connect_count: 12,
+            tags: vec!["prod".to_string(), "sql".to_string()],
+            ..Default::default()
+        };
+        host.extra_columns.insert("rack".to_string(), "R12".to_string());
+        host
+    }
+
+    fn bare_host(hostname: &str) -> Host {
+        Host {
+            hostname: hostname.to_string(),
+            description: "Bare".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn as_json(hosts: &[Host]) -> serde_json::Value {
+        serde_json::to_value(hosts).expect("Hosts should serialize")
+    }
+
+    #[test]
+    fn test_storage_format_from_path() {
+        assert_eq!(StorageFormat::from_path(Path::new("hosts.json")), StorageFormat::Json);
+        assert_eq!(StorageFormat::from_path(Path::new("HOSTS.JSON")), StorageFormat::Json);
+        assert_eq!(StorageFormat::from_path(Path::new("hosts.csv")), StorageFormat::Csv);
+        assert_eq!(StorageFormat::from_path(Path::new("hosts")), StorageFormat::Csv);
+    }
+
+    #[test]
+    fn test_json_store_missing_file_is_empty() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let store = JsonHostsStore::new(temp_dir.path().join("hosts.json"));
+        assert!(store.load().expect("Load should succeed").is_empty());
+    }
+
+    #[test]
+    fn test_json_store_crud() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let store = JsonHostsStore::new(temp_dir.path().join("hosts.json"));
+
+        // Create
+        store
+            .save(&[full_host(), bare_host("web01.domain.com")])
+            .expect("Save should succeed");
+        let mut hosts = store.load().expect("Load should succeed");
+        assert_eq!(as_json(&hosts), as_json(&[full_host(), bare_host("web01.domain.com")]));
+
+        // Update
+        hosts[1].description = "Web".to_string();
+        hosts[1].tags = vec!["iis".to_string()];
+        store.save(&hosts).expect("Save should succeed");
+        let hosts = store.load().expect("Load should succeed");
+        assert_eq!(hosts[1].description, "Web");
+        assert_eq!(hosts[1].tags, vec!["iis"]);
+
+        // Delete
+        store.save(&hosts[..1]).expect("Save should succeed");
+        let hosts = store.load().expect("Load should succeed");
+        assert_eq!(as_json(&hosts), as_json(&[full_host()]));
+    }
+
+    #[test]
+    fn test_json_store_skips_blank_hostnames() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("hosts.json");
+        std::fs::write(&path, r#"[{"hostname":" ","description":"x","last_connected":null}]"#)
+            .expect("Failed to write file");
+
+        assert!(JsonHostsStore::new(&path).load().expect("Load should succeed").is_empty());
+    }
+
+    #[test]
+    fn test_json_store_corrupt_file_is_error() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("hosts.json");
+        std::fs::write(&path, "[{").expect("Failed to write file");
+
+        let result = JsonHostsStore::new(&path).load();
+        assert!(matches!(result, Err(AppError::JsonError { .. })));
+    }
+
+    #[test]
+    fn test_convert_csv_to_json_preserves_all_fields() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let csv = CsvHostsStore::new(temp_dir.path().join("hosts.csv"));
+        let json = JsonHostsStore::new(temp_dir.path().join("hosts.json"));
+        let hosts = vec![full_host(), bare_host("web01.domain.com")];
+        csv.save(&hosts).expect("Save should succeed");
+
+        let copied = copy_hosts(&csv, &json).expect("Conversion should succeed");
+
+        assert_eq!(copied, 2);
+        assert_eq!(as_json(&json.load().expect("Load should succeed")), as_json(&hosts));
+    }
+
+    #[test]
+    fn test_convert_json_to_csv_preserves_all_fields() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let json = JsonHostsStore::new(temp_dir.path().join("hosts.json"));
+        let csv = CsvHostsStore::new(temp_dir.path().join("hosts.csv"));
+        let hosts = vec![bare_host("web01.domain.com"), full_host()];
+        json.save(&hosts).expect("Save should succeed");
+
+        let copied = copy_hosts(&json, &csv).expect("Conversion should succeed");
+
+        assert_eq!(copied, 2);
+        assert_eq!(as_json(&csv.load().expect("Load should succeed")), as_json(&hosts));
+    }
+
+    #[test]
+    fn test_store_for_path_picks_backend() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let json_path = temp_dir.path().join("hosts.json");
+
+        store_for_path(&json_path)
+            .save(&[full_host()])
+            .expect("Save should succeed");
+
+        let content = std::fs::read_to_string(&json_path).expect("Failed to read file");
+        assert!(content.trim_start().starts_with('['));
+        assert_eq!(store_for_path(&json_path).path(), json_path.as_path());
+    }
+}
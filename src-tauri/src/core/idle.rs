@@ -0,0 +1,64 @@
+//! Idle auto-hide decision logic
+//!
+//! Decides when the main window should be hidden to the tray after a period
+//! without user interaction.
+//!
+//! # Why separate
+//! The timer itself runs in the command layer against real windows; keeping the
+//! decision pure makes it testable without a Tauri runtime.
+
+/// Returns the current time as seconds since the Unix epoch.
+pub fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Decides whether the main window should be auto-hidden.
+///
+/// # Arguments
+/// * `timeout_minutes` - Configured idle timeout (0 = auto-hide disabled)
+/// * `last_activity` - Unix timestamp (seconds) of the last user interaction
+/// * `now` - Current Unix timestamp (seconds)
+///
+/// # Returns
+/// `true` once at least `timeout_minutes` have passed since the last activity.
+/// A `last_activity` in the future (clock changes) never triggers a hide.
+pub fn should_auto_hide(timeout_minutes: u32, last_activity: u64, now: u64) -> bool {
+    if timeout_minutes == 0 {
+        return false;
+    }
+
+    now.saturating_sub(last_activity) >= u64::from(timeout_minutes) * 60
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_never_hides() {
+        assert!(!should_auto_hide(0, 0, 1_000_000));
+    }
+
+    #[test]
+    fn test_hides_after_timeout_elapsed() {
+        let last_activity = 1_705_312_200;
+        assert!(should_auto_hide(5, last_activity, last_activity + 300));
+        assert!(should_auto_hide(5, last_activity, last_activity + 3_600));
+    }
+
+    #[test]
+    fn test_does_not_hide_before_timeout() {
+        let last_activity = 1_705_312_200;
+        assert!(!should_auto_hide(5, last_activity, last_activity));
+        assert!(!should_auto_hide(5, last_activity, last_activity + 299));
+    }
+
+    #[test]
+    fn test_activity_in_future_does_not_hide() {
+        let now = 1_705_312_200;
+        assert!(!should_auto_hide(1, now + 600, now));
+    }
+}
@@ -6,19 +6,107 @@
 use crate::{Host, StoredCredentials, AppError};
 use crate::infra::debug_log;
 use ldap3::{LdapConnAsync, Scope, SearchEntry};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Result of a domain scan operation
 pub struct DomainScanResult {
     pub hosts: Vec<Host>,
     pub count: usize,
+    /// Per-host inventory details, in the same order as `hosts`
+    pub details: Vec<ScannedHost>,
 }
 
+/// A Windows Server discovered by a domain scan, with its directory details
+///
+/// Carries inventory information that is not persisted to hosts.csv.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScannedHost {
+    /// dNSHostName of the computer object
+    pub hostname: String,
+    /// description attribute (empty if not set)
+    pub description: String,
+    /// operatingSystem attribute, e.g. "Windows Server 2022 Standard"
+    pub operating_system: Option<String>,
+    /// Distinguished name of the computer object
+    pub distinguished_name: String,
+}
+
+impl ScannedHost {
+    /// Converts the scan record into a host entry for hosts.csv
+    pub fn to_host(&self) -> Host {
+        Host {
+            hostname: self.hostname.clone(),
+            description: self.description.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Allows only one domain scan at a time
+///
+/// Two scans racing to write hosts.csv (e.g. a double-clicked Scan button)
+/// would interleave their results.
+pub struct ScanLock {
+    running: AtomicBool,
+}
+
+/// Held while a scan runs; releases the `ScanLock` when dropped
+///
+/// Dropping covers every exit path: success, errors returned with `?`, and a
+/// cancelled scan future.
+pub struct ScanGuard<'a> {
+    lock: &'a ScanLock,
+}
+
+impl ScanLock {
+    pub const fn new() -> Self {
+        Self {
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// Takes the lock, or returns `None` if a scan is already running
+    pub fn try_acquire(&self) -> Option<ScanGuard<'_>> {
+        self.running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| ScanGuard { lock: self })
+    }
+
+    /// Returns true while a scan holds the lock
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+}
+
+impl Default for ScanLock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ScanGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.running.store(false, Ordering::Release);
+    }
+}
+
+/// The lock shared by every domain scan command
+pub static SCAN_LOCK: ScanLock = ScanLock::new();
+
+/// Error returned when a scan is started while another is running
+pub const SCAN_ALREADY_RUNNING: &str = "A domain scan is already running. Please wait for it to finish.";
+
 /// Scans an Active Directory domain for Windows Server computers
 ///
 /// # Arguments
 /// * `domain` - Domain name (e.g., "contoso.com")
 /// * `server` - Domain controller hostname/IP
 /// * `credentials` - Domain credentials for authentication
+/// * `include_disabled` - Also return disabled computer accounts
+/// * `follow_referrals` - Search the domains named in referrals instead of failing
 ///
 /// # Returns
 /// * `Ok(DomainScanResult)` - Successfully scanned domain
@@ -28,7 +116,7 @@ pub struct DomainScanResult {
 /// - None (read-only LDAP query)
 ///
 /// # LDAP Query Details
-/// - Filter: `(&(objectClass=computer)(operatingSystem=Windows Server*)(dNSHostName=*))`
+/// - Filter: see `build_search_filter`; disabled accounts are excluded unless requested
 /// - Attributes: dNSHostName, description, operatingSystem
 /// - Scope: Subtree (searches entire domain hierarchy)
 /// - Port: 389 (standard LDAP)
@@ -46,6 +134,8 @@ pub async fn scan_domain_for_servers(
     domain: &str,
     server: &str,
     credentials: &StoredCredentials,
+    include_disabled: bool,
+    follow_referrals: bool,
 ) -> Result<DomainScanResult, AppError> {
     debug_log(
         "INFO",
@@ -54,6 +144,10 @@ pub async fn scan_domain_for_servers(
         Some(&format!("Domain: {}, Server: {}", domain, server)),
     );
 
+    // Normalize pasted domain values (e.g. "ldap://contoso.com/") before use
+    let domain = normalize_domain(domain);
+    let domain = domain.as_str();
+
     // Validate inputs
     validate_inputs(domain, server)?;
 
@@ -64,15 +158,25 @@ pub async fn scan_domain_for_servers(
     ldap3::drive!(conn);
 
     // Authenticate with domain credentials
-    authenticate_ldap(&mut ldap, domain, credentials).await?;
+    authenticate_ldap(&mut ldap, server, domain, credentials).await?;
 
     // Search for Windows Server computers
-    let hosts = search_windows_servers(&mut ldap, domain).await?;
+    let base_dn = format_base_dn(domain);
+    let outcome = search_windows_servers(&mut ldap, server, &base_dn, include_disabled).await?;
 
     // Cleanup: unbind from LDAP
     let _ = ldap.unbind().await;
     debug_log("INFO", "LDAP_CONNECTION", "LDAP connection closed", None);
 
+    let details = match outcome.referrals {
+        Some(referrals) if follow_referrals => {
+            follow_search_referrals(&referrals, domain, credentials, include_disabled).await
+        }
+        Some(referrals) => return Err(referral_error(&base_dn, &referrals)),
+        None => outcome.hosts,
+    };
+    let hosts: Vec<Host> = details.iter().map(ScannedHost::to_host).collect();
+
     if hosts.is_empty() {
         debug_log(
             "ERROR",
@@ -81,7 +185,7 @@ pub async fn scan_domain_for_servers(
             Some("Search completed but no hosts matched filter"),
         );
         return Err(AppError::LdapSearchError {
-            base_dn: format_base_dn(domain),
+            base_dn,
             source: anyhow::anyhow!("No Windows Servers found matching search criteria"),
         });
     }
@@ -94,7 +198,233 @@ pub async fn scan_domain_for_servers(
         Some(&format!("Total hosts found: {}", count)),
     );
 
-    Ok(DomainScanResult { hosts, count })
+    Ok(DomainScanResult {
+        hosts,
+        count,
+        details,
+    })
+}
+
+/// LDAP result code for a referral to another server
+pub const LDAP_RC_REFERRAL: u32 = 10;
+
+/// Hosts from one search, plus the referrals it returned instead of results
+struct SearchOutcome {
+    hosts: Vec<ScannedHost>,
+    referrals: Option<Vec<String>>,
+}
+
+/// Decides whether a search result is really a referral elsewhere
+///
+/// Searching a parent domain in a multi-domain forest either fails with a
+/// referral result code or succeeds with only continuation references, which
+/// would otherwise look like "no servers found". Results that include entries
+/// are kept even when some references came back too.
+///
+/// # Returns
+/// * `Some(referrals)` - The search was referred elsewhere (URLs, possibly empty)
+/// * `None` - Normal results
+pub fn classify_search_result(
+    rc: u32,
+    result_refs: Vec<String>,
+    continuation_refs: Vec<String>,
+    entry_count: usize,
+) -> Option<Vec<String>> {
+    let referred = rc == LDAP_RC_REFERRAL || (entry_count == 0 && !continuation_refs.is_empty());
+    if !referred {
+        return None;
+    }
+
+    let mut referrals = result_refs;
+    for url in continuation_refs {
+        if !referrals.contains(&url) {
+            referrals.push(url);
+        }
+    }
+    Some(referrals)
+}
+
+/// A referral target: the server to contact and the base DN to search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdapReferral {
+    pub server: String,
+    pub base_dn: String,
+}
+
+/// Parses a referral URL such as `ldap://child.contoso.com/DC=child,DC=contoso,DC=com`
+///
+/// The port, and any `?attributes?scope?filter` suffix, are dropped.
+pub fn parse_referral_url(url: &str) -> Option<LdapReferral> {
+    let rest = url
+        .strip_prefix("ldap://")
+        .or_else(|| url.strip_prefix("LDAP://"))?;
+    let (authority, dn) = rest.split_once('/').unwrap_or((rest, ""));
+    let server = authority.split(':').next().unwrap_or(authority).trim();
+    if server.is_empty() {
+        return None;
+    }
+
+    let dn = dn.split('?').next().unwrap_or("").replace("%20", " ");
+    Some(LdapReferral {
+        server: server.to_string(),
+        base_dn: dn,
+    })
+}
+
+/// Converts a `DC=child,DC=contoso,DC=com` base DN back to `child.contoso.com`
+pub fn domain_from_base_dn(base_dn: &str) -> String {
+    base_dn
+        .split(',')
+        .filter_map(|part| {
+            let (key, value) = part.trim().split_once('=')?;
+            key.eq_ignore_ascii_case("DC").then(|| value.trim().to_string())
+        })
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+/// Builds the error for a search that only returned referrals
+///
+/// Names the referred domains so the user can scan the child domain directly.
+pub fn referral_error(base_dn: &str, referrals: &[String]) -> AppError {
+    let domains: Vec<String> = referrals
+        .iter()
+        .filter_map(|url| parse_referral_url(url))
+        .map(|referral| match domain_from_base_dn(&referral.base_dn) {
+            domain if domain.is_empty() => referral.server,
+            domain => domain,
+        })
+        .fold(Vec::new(), |mut domains, domain| {
+            if !domains.contains(&domain) {
+                domains.push(domain);
+            }
+            domains
+        });
+
+    let suggestion = match domains.as_slice() {
+        [] => "Scan the specific child domain that holds the servers".to_string(),
+        [domain] => format!("Scan the child domain '{}' directly", domain),
+        _ => format!("Scan one of the child domains directly ({})", domains.join(", ")),
+    };
+
+    AppError::LdapSearchError {
+        base_dn: base_dn.to_string(),
+        source: anyhow::anyhow!(
+            "The domain controller returned referrals to other domains instead of results. {}, or enable \"ldap_follow_referrals\" in settings.",
+            suggestion
+        ),
+    }
+}
+
+/// Searches each referred domain with the same credentials (one hop only)
+///
+/// A referral that can't be reached or searched is logged and skipped, and
+/// referrals returned by the referred servers are not followed further.
+async fn follow_search_referrals(
+    referrals: &[String],
+    domain: &str,
+    credentials: &StoredCredentials,
+    include_disabled: bool,
+) -> Vec<ScannedHost> {
+    let mut hosts: Vec<ScannedHost> = Vec::new();
+
+    for url in referrals {
+        let Some(referral) = parse_referral_url(url) else {
+            debug_log("WARN", "LDAP_SEARCH", &format!("Ignoring unsupported referral: {}", url), None);
+            continue;
+        };
+        debug_log(
+            "INFO",
+            "LDAP_SEARCH",
+            &format!("Following referral to {}", referral.server),
+            Some(&format!("Base DN: {}", referral.base_dn)),
+        );
+
+        let result = async {
+            let (conn, mut ldap) = connect_to_ldap(&referral.server).await?;
+            ldap3::drive!(conn);
+            authenticate_ldap(&mut ldap, &referral.server, domain, credentials).await?;
+            let outcome = search_windows_servers(&mut ldap, &referral.server, &referral.base_dn, include_disabled).await;
+            let _ = ldap.unbind().await;
+            outcome
+        }
+        .await;
+
+        match result {
+            Ok(outcome) => {
+                for host in outcome.hosts {
+                    if !hosts.iter().any(|h| h.hostname.eq_ignore_ascii_case(&host.hostname)) {
+                        hosts.push(host);
+                    }
+                }
+            }
+            Err(e) => debug_log(
+                "WARN",
+                "LDAP_SEARCH",
+                &format!("Failed to follow referral to {}", referral.server),
+                Some(&e.to_string()),
+            ),
+        }
+    }
+
+    hosts
+}
+
+/// Checks that a domain controller is reachable and the credentials bind
+///
+/// Runs the connect and bind steps of a scan, then unbinds without searching.
+///
+/// # Returns
+/// * `Ok(String)` - Success message for display
+/// * `Err(AppError::LdapConnectionError)` - Server unreachable
+/// * `Err(AppError::LdapBindError)` - Server reachable but the credentials were rejected
+pub async fn test_ldap_connection(
+    domain: &str,
+    server: &str,
+    credentials: &StoredCredentials,
+) -> Result<String, AppError> {
+    let domain = normalize_domain(domain);
+    validate_inputs(&domain, server)?;
+
+    let mut ldap = connect_and_bind(
+        server,
+        |server| async move {
+            let (conn, ldap) = connect_to_ldap(&server).await?;
+            ldap3::drive!(conn);
+            Ok(ldap)
+        },
+        |mut ldap| {
+            let domain = domain.clone();
+            async move {
+                authenticate_ldap(&mut ldap, server, &domain, credentials).await?;
+                Ok(ldap)
+            }
+        },
+    )
+    .await?;
+
+    let _ = ldap.unbind().await;
+    debug_log("INFO", "LDAP_CONNECTION", "LDAP connection test succeeded", None);
+
+    Ok(format!(
+        "Connected to {} and authenticated to {} successfully",
+        server, domain
+    ))
+}
+
+/// Connects, then binds, stopping at the first failure
+///
+/// Takes the connect and bind steps as parameters so the control flow can be
+/// tested without an LDAP server.
+pub async fn connect_and_bind<S, C, CF, B, BF>(server: &str, connect: C, bind: B) -> Result<S, AppError>
+where
+    C: FnOnce(String) -> CF,
+    CF: Future<Output = Result<S, AppError>>,
+    B: FnOnce(S) -> BF,
+    BF: Future<Output = Result<S, AppError>>,
+{
+    let session = connect(server.to_string()).await?;
+    bind(session).await
 }
 
 /// Validates domain and server inputs
@@ -128,6 +458,29 @@ fn validate_inputs(domain: &str, server: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Fills in the context an ldap3 error can't carry
+///
+/// `From<LdapError>` picks the variant from the error itself; the server, bind
+/// user and base DN are only known here.
+fn with_ldap_context(err: AppError, server: &str, username: Option<&str>, base_dn: Option<&str>) -> AppError {
+    match err {
+        AppError::LdapConnectionError { port, source, .. } => AppError::LdapConnectionError {
+            server: server.to_string(),
+            port,
+            source,
+        },
+        AppError::LdapBindError { username: unknown, source } => AppError::LdapBindError {
+            username: username.map(str::to_string).unwrap_or(unknown),
+            source,
+        },
+        AppError::LdapSearchError { base_dn: matched, source } => AppError::LdapSearchError {
+            base_dn: base_dn.map(str::to_string).unwrap_or(matched),
+            source,
+        },
+        other => other,
+    }
+}
+
 /// Connects to LDAP server
 async fn connect_to_ldap(
     server: &str,
@@ -153,11 +506,7 @@ async fn connect_to_ldap(
                     e
                 )),
             );
-            AppError::LdapConnectionError {
-                server: server.to_string(),
-                port: 389,
-                source: anyhow::Error::from(e),
-            }
+            with_ldap_context(AppError::from(e), server, None, None)
         })?;
 
     debug_log(
@@ -170,9 +519,30 @@ async fn connect_to_ldap(
     Ok((conn, ldap))
 }
 
+/// Builds the name sent in the LDAP simple bind
+///
+/// `DOMAIN\username` and `username@domain.com` are sent as entered; a bare
+/// username gets `@domain` appended. With no domain a bare username is sent
+/// alone rather than with a dangling `@`.
+///
+/// # Examples
+/// - ("contoso.com", "admin") -> "admin@contoso.com"
+/// - ("contoso.com", "CONTOSO\admin") -> "CONTOSO\admin"
+pub fn build_bind_dn(domain: &str, username: &str) -> String {
+    let username = username.trim();
+    let domain = domain.trim();
+
+    if username.contains('@') || username.contains('\\') || domain.is_empty() {
+        username.to_string()
+    } else {
+        format!("{}@{}", username, domain)
+    }
+}
+
 /// Authenticates with LDAP server using domain credentials
 async fn authenticate_ldap(
     ldap: &mut ldap3::Ldap,
+    server: &str,
     domain: &str,
     credentials: &StoredCredentials,
 ) -> Result<(), AppError> {
@@ -183,14 +553,7 @@ async fn authenticate_ldap(
         None,
     );
 
-    // Format username for LDAP binding
-    // Support multiple formats: username, DOMAIN\username, or username@domain.com
-    let bind_dn = if credentials.username.contains('@') || credentials.username.contains('\\') {
-        credentials.username.clone()
-    } else {
-        // If just username, append @domain
-        format!("{}@{}", credentials.username, domain)
-    };
+    let bind_dn = build_bind_dn(domain, &credentials.username);
 
     debug_log(
         "INFO",
@@ -200,8 +563,10 @@ async fn authenticate_ldap(
     );
 
     // Perform authenticated bind
+    // A rejected bind is an Ok result with a non-zero code, so check it with success()
     ldap.simple_bind(&bind_dn, &credentials.password)
         .await
+        .and_then(|result| result.success())
         .map_err(|e| {
             debug_log(
                 "ERROR",
@@ -212,10 +577,7 @@ async fn authenticate_ldap(
                     e
                 )),
             );
-            AppError::LdapBindError {
-                username: bind_dn.clone(),
-                source: anyhow::Error::from(e),
-            }
+            with_ldap_context(AppError::from(e), server, Some(&bind_dn), None)
         })?;
 
     debug_log(
@@ -228,27 +590,26 @@ async fn authenticate_ldap(
     Ok(())
 }
 
-/// Searches for Windows Server computers in the domain
+/// Searches for Windows Server computers beneath a base DN
+///
+/// Referrals (a referral result or continuation references) are returned
+/// alongside the hosts rather than treated as an error; see
+/// `classify_search_result`.
 async fn search_windows_servers(
     ldap: &mut ldap3::Ldap,
-    domain: &str,
-) -> Result<Vec<Host>, AppError> {
-    // Build the search base DN from domain
-    // e.g., "domain.com" -> "DC=domain,DC=com"
-    let base_dn = format_base_dn(domain);
+    server: &str,
+    base_dn: &str,
+    include_disabled: bool,
+) -> Result<SearchOutcome, AppError> {
+    // LDAP filter for Windows Server computers with DNS hostnames
+    let filter = build_search_filter(include_disabled);
 
     debug_log(
         "INFO",
         "LDAP_SEARCH",
         &format!("Searching base DN: {}", base_dn),
-        Some(&format!(
-            "Base DN: {}, Filter: (&(objectClass=computer)(operatingSystem=Windows Server*)(dNSHostName=*))",
-            base_dn
-        )),
+        Some(&format!("Base DN: {}, Filter: {}", base_dn, filter)),
     );
-
-    // LDAP filter for Windows Server computers with DNS hostnames
-    let filter = "(&(objectClass=computer)(operatingSystem=Windows Server*)(dNSHostName=*))";
     let attrs = vec!["dNSHostName", "description", "operatingSystem"];
 
     debug_log(
@@ -259,8 +620,8 @@ async fn search_windows_servers(
     );
 
     // Execute search
-    let (rs, _res) = ldap
-        .search(&base_dn, Scope::Subtree, filter, attrs)
+    let (rs, res) = ldap
+        .search(base_dn, Scope::Subtree, &filter, attrs)
         .await
         .map_err(|e| {
             debug_log(
@@ -269,12 +630,10 @@ async fn search_windows_servers(
                 "Failed to execute LDAP search",
                 Some(&format!("Search execution error: {:?}", e)),
             );
-            AppError::LdapSearchError {
-                base_dn: base_dn.clone(),
-                source: anyhow::Error::from(e),
-            }
+            with_ldap_context(AppError::from(e), server, None, Some(base_dn))
         })?
-        .success()
+        // Referral results (rc 10) are handled below rather than failing here
+        .non_error()
         .map_err(|e| {
             debug_log(
                 "ERROR",
@@ -282,10 +641,7 @@ async fn search_windows_servers(
                 "LDAP search returned error",
                 Some(&format!("Search result error: {:?}", e)),
             );
-            AppError::LdapSearchError {
-                base_dn: base_dn.clone(),
-                source: anyhow::Error::from(e),
-            }
+            with_ldap_context(AppError::from(e), server, None, Some(base_dn))
         })?;
 
     debug_log(
@@ -295,56 +651,139 @@ async fn search_windows_servers(
         Some(&format!("Entry count: {}", rs.len())),
     );
 
-    // Parse search results into Host objects
+    // Parse search results into scan records
     let mut hosts = Vec::new();
+    let mut continuation_refs = Vec::new();
     for entry in rs {
+        // Continuation references point into other domains of the forest
+        if entry.is_ref() {
+            continuation_refs.extend(ldap3::parse_refs(entry.0));
+            continue;
+        }
+        if entry.is_intermediate() {
+            continue;
+        }
         let search_entry = SearchEntry::construct(entry);
 
-        // Extract dNSHostName attribute
-        if let Some(hostname_values) = search_entry.attrs.get("dNSHostName") {
-            if let Some(hostname) = hostname_values.first() {
-                // Extract description if available
-                let description = search_entry
-                    .attrs
-                    .get("description")
-                    .and_then(|v| v.first())
-                    .map(|s| s.to_string())
-                    .unwrap_or_default();
-
+        match parse_search_entry(&search_entry) {
+            Some(scanned) => {
                 debug_log(
                     "INFO",
                     "LDAP_SEARCH",
-                    &format!("Found host: {} - {}", hostname, description),
-                    Some(&format!("Hostname: {}, Description: {}", hostname, description)),
+                    &format!("Found host: {} - {}", scanned.hostname, scanned.description),
+                    Some(&format!(
+                        "Hostname: {}, Description: {}, OS: {}, DN: {}",
+                        scanned.hostname,
+                        scanned.description,
+                        scanned.operating_system.as_deref().unwrap_or("(none)"),
+                        scanned.distinguished_name
+                    )),
+                );
+                hosts.push(scanned);
+            }
+            None => {
+                debug_log(
+                    "WARN",
+                    "LDAP_SEARCH",
+                    "LDAP entry found but missing dNSHostName attribute",
+                    Some(&format!("DN: {}", search_entry.dn)),
                 );
-
-                hosts.push(Host {
-                    hostname: hostname.to_string(),
-                    description,
-                    last_connected: None,
-                });
             }
-        } else {
-            debug_log(
-                "WARN",
-                "LDAP_SEARCH",
-                "LDAP entry found but missing dNSHostName attribute",
-                None,
-            );
         }
     }
 
-    Ok(hosts)
+    if !continuation_refs.is_empty() {
+        debug_log(
+            "WARN",
+            "LDAP_SEARCH",
+            &format!("Search returned {} continuation reference(s)", continuation_refs.len()),
+            Some(&continuation_refs.join(", ")),
+        );
+    }
+
+    let referrals = classify_search_result(res.rc, res.refs, continuation_refs, hosts.len());
+    Ok(SearchOutcome { hosts, referrals })
+}
+
+/// Matches computer accounts whose ACCOUNTDISABLE bit (0x2) is not set in
+/// userAccountControl (1.2.840.113556.1.4.803 is the bitwise AND rule)
+const ENABLED_ACCOUNTS_FILTER: &str = "(!(userAccountControl:1.2.840.113556.1.4.803:=2))";
+
+/// Builds the LDAP filter for Windows Server computers with DNS hostnames
+///
+/// Disabled computer objects are usually stale (the machine no longer
+/// exists), so they are excluded unless `include_disabled` is set.
+pub fn build_search_filter(include_disabled: bool) -> String {
+    let disabled_clause = if include_disabled { "" } else { ENABLED_ACCOUNTS_FILTER };
+    format!(
+        "(&(objectClass=computer)(operatingSystem=Windows Server*)(dNSHostName=*){})",
+        disabled_clause
+    )
+}
+
+/// Parses an LDAP search entry into a scan record
+///
+/// # Returns
+/// * `Some(ScannedHost)` - Entry has a dNSHostName
+/// * `None` - Entry has no (or an empty) dNSHostName and can't be connected to
+pub fn parse_search_entry(entry: &SearchEntry) -> Option<ScannedHost> {
+    let first_value = |name: &str| {
+        entry
+            .attrs
+            .get(name)
+            .and_then(|values| values.first())
+            .map(|value| value.to_string())
+    };
+
+    let hostname = first_value("dNSHostName").filter(|h| !h.trim().is_empty())?;
+
+    Some(ScannedHost {
+        hostname,
+        description: first_value("description").unwrap_or_default(),
+        operating_system: first_value("operatingSystem").filter(|os| !os.is_empty()),
+        distinguished_name: entry.dn.clone(),
+    })
+}
+
+/// Normalizes a user-entered domain name
+///
+/// Strips surrounding whitespace, an `ldap://` or `ldaps://` scheme, and any
+/// trailing slashes or dots so pasted values produce a valid base DN.
+///
+/// # Examples
+/// - "ldap://contoso.com/" -> "contoso.com"
+/// - " contoso.com. " -> "contoso.com"
+pub fn normalize_domain(domain: &str) -> String {
+    let mut normalized = domain.trim();
+
+    for scheme in ["ldaps://", "ldap://"] {
+        if normalized
+            .get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+        {
+            normalized = &normalized[scheme.len()..];
+            break;
+        }
+    }
+
+    normalized
+        .trim_end_matches(['/', '.'])
+        .trim()
+        .to_string()
 }
 
 /// Formats domain name into LDAP base DN
 ///
+/// The domain is normalized first and empty labels are skipped, so messy
+/// input never yields entries like `DC=` or `DC=ldap://contoso`.
+///
 /// # Examples
 /// - "contoso.com" -> "DC=contoso,DC=com"
 /// - "sub.domain.com" -> "DC=sub,DC=domain,DC=com"
 fn format_base_dn(domain: &str) -> String {
-    domain
+    normalize_domain(domain)
         .split('.')
+        .filter(|part| !part.is_empty())
         .map(|part| format!("DC={}", part))
         .collect::<Vec<String>>()
         .join(",")
@@ -363,4 +802,353 @@ mod tests {
         );
         assert_eq!(format_base_dn("local"), "DC=local");
     }
+
+    #[test]
+    fn test_format_base_dn_strips_scheme_and_trailing_slash() {
+        assert_eq!(format_base_dn("ldap://contoso.com/"), "DC=contoso,DC=com");
+        assert_eq!(format_base_dn("ldaps://contoso.com"), "DC=contoso,DC=com");
+        assert_eq!(format_base_dn("LDAP://sub.contoso.com//"), "DC=sub,DC=contoso,DC=com");
+    }
+
+    #[test]
+    fn test_format_base_dn_strips_trailing_dot_and_whitespace() {
+        assert_eq!(format_base_dn("contoso.com."), "DC=contoso,DC=com");
+        assert_eq!(format_base_dn("  contoso.com  "), "DC=contoso,DC=com");
+        assert_eq!(format_base_dn(" ldap://contoso.com./ "), "DC=contoso,DC=com");
+    }
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(normalize_domain("contoso.com"), "contoso.com");
+        assert_eq!(normalize_domain("ldap://contoso.com/"), "contoso.com");
+        assert_eq!(normalize_domain("ldap://"), "");
+        assert_eq!(normalize_domain("   "), "");
+        assert_eq!(normalize_domain("contosé"), "contosé");
+        assert_eq!(normalize_domain("ldapé.com"), "ldapé.com");
+    }
+
+    #[test]
+    fn test_build_bind_dn() {
+        // A bare username gets the domain appended
+        assert_eq!(build_bind_dn("contoso.com", "admin"), "admin@contoso.com");
+        assert_eq!(build_bind_dn(" contoso.com ", " admin "), "admin@contoso.com");
+        // Down-level and UPN names are sent as entered
+        assert_eq!(build_bind_dn("contoso.com", "CONTOSO\\admin"), "CONTOSO\\admin");
+        assert_eq!(build_bind_dn("contoso.com", "admin@fabrikam.com"), "admin@fabrikam.com");
+        // Without a domain nothing is appended
+        assert_eq!(build_bind_dn("", "admin"), "admin");
+        assert_eq!(build_bind_dn("  ", "CONTOSO\\admin"), "CONTOSO\\admin");
+        assert_eq!(build_bind_dn("", "admin@contoso.com"), "admin@contoso.com");
+    }
+
+    fn search_entry(dn: &str, attrs: &[(&str, &str)]) -> SearchEntry {
+        SearchEntry {
+            dn: dn.to_string(),
+            attrs: attrs
+                .iter()
+                .map(|(name, value)| (name.to_string(), vec![value.to_string()]))
+                .collect(),
+            bin_attrs: std::collections::HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_search_entry_extracts_os_and_dn() {
+        let entry = search_entry(
+            "CN=SERVER01,OU=Servers,DC=contoso,DC=com",
+            &[
+                ("dNSHostName", "server01.contoso.com"),
+                ("description", "Web Server"),
+                ("operatingSystem", "Windows Server 2022 Standard"),
+            ],
+        );
+
+        let scanned = parse_search_entry(&entry).expect("Entry should parse");
+
+        assert_eq!(scanned.hostname, "server01.contoso.com");
+        assert_eq!(scanned.description, "Web Server");
+        assert_eq!(
+            scanned.operating_system.as_deref(),
+            Some("Windows Server 2022 Standard")
+        );
+        assert_eq!(scanned.distinguished_name, "CN=SERVER01,OU=Servers,DC=contoso,DC=com");
+    }
+
+    #[test]
+    fn test_parse_search_entry_optional_attributes_missing() {
+        let entry = search_entry(
+            "CN=SERVER02,DC=contoso,DC=com",
+            &[("dNSHostName", "server02.contoso.com")],
+        );
+
+        let scanned = parse_search_entry(&entry).expect("Entry should parse");
+
+        assert_eq!(scanned.description, "");
+        assert_eq!(scanned.operating_system, None);
+        assert_eq!(scanned.to_host().hostname, "server02.contoso.com");
+    }
+
+    #[test]
+    fn test_parse_search_entry_without_hostname_is_skipped() {
+        let entry = search_entry(
+            "CN=SERVER03,DC=contoso,DC=com",
+            &[("operatingSystem", "Windows Server 2019")],
+        );
+        assert!(parse_search_entry(&entry).is_none());
+
+        let blank = search_entry("CN=SERVER04,DC=contoso,DC=com", &[("dNSHostName", " ")]);
+        assert!(parse_search_entry(&blank).is_none());
+    }
+
+    fn connection_error(server: &str) -> AppError {
+        AppError::LdapConnectionError {
+            server: server.to_string(),
+            port: 389,
+            source: anyhow::anyhow!("connection refused"),
+        }
+    }
+
+    fn bind_error() -> AppError {
+        AppError::LdapBindError {
+            username: "CONTOSO\\john.doe".to_string(),
+            source: anyhow::anyhow!("invalid credentials"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_bind_success() {
+        let result = connect_and_bind(
+            "dc01.contoso.com",
+            |server| async move { Ok(vec![server]) },
+            |mut session| async move {
+                session.push("bound".to_string());
+                Ok(session)
+            },
+        )
+        .await;
+
+        assert_eq!(
+            result.expect("Connect and bind should succeed"),
+            vec!["dc01.contoso.com", "bound"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_bind_connection_error_skips_bind() {
+        let bind_called = std::cell::Cell::new(false);
+
+        let result = connect_and_bind(
+            "dc01.contoso.com",
+            |server| async move { Err::<(), _>(connection_error(&server)) },
+            |session| {
+                bind_called.set(true);
+                async move { Ok(session) }
+            },
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::LdapConnectionError { ref server, .. }) if server == "dc01.contoso.com"
+        ));
+        assert!(!bind_called.get());
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_bind_surfaces_bind_error() {
+        let result = connect_and_bind(
+            "dc01.contoso.com",
+            |_| async { Ok(()) },
+            |_| async { Err::<(), _>(bind_error()) },
+        )
+        .await;
+
+        let error = result.expect_err("Bind failure should be returned");
+        assert!(matches!(error, AppError::LdapBindError { .. }));
+        assert_eq!(error.code(), "LDAP_BIND");
+    }
+
+    #[tokio::test]
+    async fn test_test_ldap_connection_validates_inputs_first() {
+        let credentials = StoredCredentials {
+            username: "john.doe".to_string(),
+            password: "password".to_string(),
+        };
+
+        let result = test_ldap_connection("ldap://", "dc01.contoso.com", &credentials).await;
+
+        assert!(matches!(result, Err(AppError::InvalidHostname { .. })));
+    }
+
+    #[test]
+    fn test_build_search_filter_excludes_disabled_by_default() {
+        assert_eq!(
+            build_search_filter(false),
+            "(&(objectClass=computer)(operatingSystem=Windows Server*)(dNSHostName=*)(!(userAccountControl:1.2.840.113556.1.4.803:=2)))"
+        );
+    }
+
+    #[test]
+    fn test_build_search_filter_include_disabled() {
+        let filter = build_search_filter(true);
+
+        assert_eq!(
+            filter,
+            "(&(objectClass=computer)(operatingSystem=Windows Server*)(dNSHostName=*))"
+        );
+        assert!(!filter.contains("userAccountControl"));
+    }
+
+    #[test]
+    fn test_build_search_filter_parentheses_balanced() {
+        for include_disabled in [false, true] {
+            let filter = build_search_filter(include_disabled);
+            assert_eq!(filter.matches('(').count(), filter.matches(')').count());
+        }
+    }
+
+    #[test]
+    fn test_scan_lock_rejects_second_scan_while_held() {
+        let lock = ScanLock::new();
+
+        let guard = lock.try_acquire().expect("First scan should get the lock");
+        assert!(lock.is_running());
+        assert!(lock.try_acquire().is_none());
+
+        drop(guard);
+        assert!(!lock.is_running());
+        assert!(lock.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_scan_lock_released_on_error_path() {
+        let lock = ScanLock::new();
+        let failing_scan = || -> Result<(), AppError> {
+            let _guard = lock.try_acquire().expect("Lock should be free");
+            Err(AppError::InvalidHostname {
+                hostname: String::new(),
+                reason: "failed".to_string(),
+            })?;
+            Ok(())
+        };
+
+        assert!(failing_scan().is_err());
+        assert!(!lock.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_scan_lock_concurrent_invocation_rejected() {
+        let lock = ScanLock::new();
+
+        // join! polls `first` until it yields while holding the lock, then `second`
+        let first = async {
+            let _guard = lock.try_acquire().ok_or(SCAN_ALREADY_RUNNING)?;
+            tokio::task::yield_now().await;
+            Ok::<(), &str>(())
+        };
+        let second = async { lock.try_acquire().map(|_| ()).ok_or(SCAN_ALREADY_RUNNING) };
+
+        let (first, second) = tokio::join!(first, second);
+
+        assert!(first.is_ok());
+        assert_eq!(second, Err(SCAN_ALREADY_RUNNING));
+        assert!(!lock.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_scan_lock_released_when_scan_cancelled() {
+        let lock = ScanLock::new();
+        let scan = async {
+            let _guard = lock.try_acquire().expect("Lock should be free");
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+        };
+
+        let result = tokio::time::timeout(std::time::Duration::from_millis(10), scan).await;
+
+        assert!(result.is_err());
+        assert!(!lock.is_running());
+    }
+
+    #[test]
+    fn test_classify_search_result_normal_results() {
+        assert_eq!(classify_search_result(0, vec![], vec![], 3), None);
+        assert_eq!(classify_search_result(0, vec![], vec![], 0), None);
+    }
+
+    #[test]
+    fn test_classify_search_result_referral_code() {
+        let refs = vec!["ldap://child.contoso.com/DC=child,DC=contoso,DC=com".to_string()];
+        assert_eq!(classify_search_result(LDAP_RC_REFERRAL, refs.clone(), vec![], 0), Some(refs));
+    }
+
+    #[test]
+    fn test_classify_search_result_only_continuation_references() {
+        let refs = vec![
+            "ldap://emea.contoso.com/DC=emea,DC=contoso,DC=com".to_string(),
+            "ldap://ForestDnsZones.contoso.com/DC=ForestDnsZones,DC=contoso,DC=com".to_string(),
+        ];
+        assert_eq!(classify_search_result(0, vec![], refs.clone(), 0), Some(refs));
+    }
+
+    #[test]
+    fn test_classify_search_result_partial_results_kept() {
+        let refs = vec!["ldap://emea.contoso.com/DC=emea,DC=contoso,DC=com".to_string()];
+        assert_eq!(classify_search_result(0, vec![], refs, 2), None);
+    }
+
+    #[test]
+    fn test_parse_referral_url() {
+        assert_eq!(
+            parse_referral_url("ldap://emea.contoso.com:389/DC=emea,DC=contoso,DC=com??sub"),
+            Some(LdapReferral {
+                server: "emea.contoso.com".to_string(),
+                base_dn: "DC=emea,DC=contoso,DC=com".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_referral_url("ldap://dc01/").map(|r| r.server),
+            Some("dc01".to_string())
+        );
+        assert_eq!(parse_referral_url("https://contoso.com"), None);
+        assert_eq!(parse_referral_url("ldap:///DC=contoso,DC=com"), None);
+    }
+
+    #[test]
+    fn test_domain_from_base_dn() {
+        assert_eq!(domain_from_base_dn("DC=emea,DC=contoso,DC=com"), "emea.contoso.com");
+        assert_eq!(domain_from_base_dn("OU=Servers,dc=contoso, dc=com"), "contoso.com");
+        assert_eq!(domain_from_base_dn(""), "");
+    }
+
+    #[test]
+    fn test_referral_error_suggests_child_domain() {
+        let refs = vec!["ldap://emea.contoso.com/DC=emea,DC=contoso,DC=com".to_string()];
+
+        let error = referral_error("DC=contoso,DC=com", &refs);
+
+        assert!(matches!(&error, AppError::LdapSearchError { base_dn, .. } if base_dn == "DC=contoso,DC=com"));
+        let message = error.to_string();
+        assert!(message.contains("referrals"), "{}", message);
+        assert!(message.contains("'emea.contoso.com'"), "{}", message);
+        assert!(message.contains("ldap_follow_referrals"), "{}", message);
+    }
+
+    #[test]
+    fn test_referral_error_lists_several_domains_once() {
+        let refs = vec![
+            "ldap://emea.contoso.com/DC=emea,DC=contoso,DC=com".to_string(),
+            "ldap://dc2.emea.contoso.com/DC=emea,DC=contoso,DC=com".to_string(),
+            "ldap://apac.contoso.com/DC=apac,DC=contoso,DC=com".to_string(),
+        ];
+
+        let message = referral_error("DC=contoso,DC=com", &refs).to_string();
+
+        assert!(message.contains("(emea.contoso.com, apac.contoso.com)"), "{}", message);
+    }
+
+    #[test]
+    fn test_referral_error_without_urls() {
+        let message = referral_error("DC=contoso,DC=com", &[]).to_string();
+        assert!(message.contains("Scan the specific child domain"), "{}", message);
+    }
 }
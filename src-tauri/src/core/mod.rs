@@ -1,11 +1,36 @@
 //! Core module - domain types and business logic
 
+pub mod autostart;
+pub mod backup;
+pub mod cidr;
+pub mod clock;
+pub mod connections;
+pub mod credentials;
 pub mod csv_reader;
 pub mod csv_writer;
+pub mod history;
+pub mod host_pattern;
 pub mod hosts;
+pub mod hosts_store;
+pub mod idle;
 pub mod ldap;
 pub mod rdp;
+pub mod rdp_file_names;
 pub mod rdp_launcher;
+pub mod rdp_security;
+pub mod recent;
+pub mod registry_defaults;
+pub mod reset;
+pub mod settings;
+pub mod shortcuts;
+pub mod single_instance;
+pub mod stats;
+pub mod status;
+pub mod storage;
+pub mod theme;
+pub mod tray;
 pub mod types;
+pub mod usage;
+pub mod window_close;
 
 pub use types::*;
@@ -4,6 +4,418 @@
 //! RDP files use a standard text format that works across platforms.
 
 use crate::core::Host;
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+
+/// Where Windows key combinations (Alt+Tab, Windows key, etc.) are applied
+///
+/// Maps directly to the `keyboardhook:i:` RDP setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardHook {
+    /// Key combinations stay on the local computer (`keyboardhook:i:0`)
+    Local,
+    /// Key combinations are sent to the remote session (`keyboardhook:i:1`)
+    Remote,
+    /// Key combinations go to the remote session only in full screen (`keyboardhook:i:2`)
+    FullscreenOnly,
+}
+
+impl KeyboardHook {
+    /// Returns the integer value used in the RDP file
+    pub fn rdp_value(self) -> u8 {
+        match self {
+            KeyboardHook::Local => 0,
+            KeyboardHook::Remote => 1,
+            KeyboardHook::FullscreenOnly => 2,
+        }
+    }
+}
+
+/// Network profile mstsc tunes the session experience for
+///
+/// Maps directly to the `connection type:i:` RDP setting. mstsc only honours
+/// a fixed profile when network auto-detection is off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionType {
+    /// Modem, 56 Kbps (`connection type:i:1`)
+    Modem,
+    /// Low-speed broadband, 256 Kbps - 2 Mbps (`connection type:i:2`)
+    LowSpeedBroadband,
+    /// Satellite, 2 - 16 Mbps with high latency (`connection type:i:3`)
+    Satellite,
+    /// High-speed broadband, 2 - 10 Mbps (`connection type:i:4`)
+    HighSpeedBroadband,
+    /// WAN, 10 Mbps or higher with high latency (`connection type:i:5`)
+    Wan,
+    /// LAN, 10 Mbps or higher (`connection type:i:6`)
+    Lan,
+    /// Let mstsc detect the connection quality (`connection type:i:7`)
+    AutoDetect,
+}
+
+impl ConnectionType {
+    /// Returns the integer value used in the RDP file
+    pub fn rdp_value(self) -> u8 {
+        match self {
+            ConnectionType::Modem => 1,
+            ConnectionType::LowSpeedBroadband => 2,
+            ConnectionType::Satellite => 3,
+            ConnectionType::HighSpeedBroadband => 4,
+            ConnectionType::Wan => 5,
+            ConnectionType::Lan => 6,
+            ConnectionType::AutoDetect => 7,
+        }
+    }
+}
+
+/// Optional overrides for the generated RDP file
+///
+/// Every field is optional; `None` keeps the built-in default so that an
+/// empty `RdpOptions` produces exactly the historical RDP file content.
+/// Options can be set globally and per host (see `core::settings`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RdpOptions {
+    /// Where Windows key combinations are applied (default: full screen only)
+    pub keyboard_hook: Option<KeyboardHook>,
+    /// Redirect the clipboard (default: on)
+    pub redirect_clipboard: Option<bool>,
+    /// Redirect local printers (default: on)
+    pub redirect_printers: Option<bool>,
+    /// Redirect smart card readers (default: on)
+    pub redirect_smartcards: Option<bool>,
+    /// Redirect COM ports (default: off)
+    pub redirect_comports: Option<bool>,
+    /// Redirect all local drives (default: not written, mstsc decides)
+    pub redirect_drives: Option<bool>,
+    /// Show the connection bar in full screen (default: not written, mstsc decides)
+    pub display_connection_bar: Option<bool>,
+    /// Keep the connection bar pinned instead of auto-hiding (default: not written, mstsc decides)
+    pub pin_connection_bar: Option<bool>,
+    /// Network profile for the session experience (default: low-speed broadband)
+    pub connection_type: Option<ConnectionType>,
+    /// Let mstsc measure the network (default: on, unless a fixed `connection_type` is set)
+    pub network_autodetect: Option<bool>,
+    /// Let mstsc measure the bandwidth (default: on)
+    pub bandwidth_autodetect: Option<bool>,
+    /// Reconnect attempts after a dropped connection (default: not written, mstsc uses 20)
+    pub autoreconnect_max_retries: Option<u32>,
+    /// RD Gateway to connect through (default: none, connect directly)
+    ///
+    /// Credentials saved for the gateway (`TERMSRV/GATEWAY/{gateway}`) are used
+    /// for it; without them the session credentials are used for both.
+    pub gateway_hostname: Option<String>,
+    /// Session width in pixels (default: 1920)
+    pub desktop_width: Option<u32>,
+    /// Session height in pixels (default: 1080)
+    pub desktop_height: Option<u32>,
+    /// Scale the session to fit the window instead of adding scroll bars
+    /// (default: not written, mstsc decides)
+    pub smart_sizing: Option<bool>,
+    /// Window position and size as mstsc's `winposstr` value, e.g.
+    /// `0,1,100,100,1380,900` (default: not written, mstsc decides)
+    pub window_position: Option<String>,
+    /// Keep the window position mstsc saved into the host's `.rdp` file when
+    /// the file is regenerated (default: off)
+    pub remember_window_size: Option<bool>,
+}
+
+impl RdpOptions {
+    /// Returns a copy of these options with every value set in `overrides` applied on top
+    ///
+    /// Used to layer per-host options over the global defaults.
+    pub fn overlay(&self, overrides: &RdpOptions) -> RdpOptions {
+        RdpOptions {
+            keyboard_hook: overrides.keyboard_hook.or(self.keyboard_hook),
+            redirect_clipboard: overrides.redirect_clipboard.or(self.redirect_clipboard),
+            redirect_printers: overrides.redirect_printers.or(self.redirect_printers),
+            redirect_smartcards: overrides.redirect_smartcards.or(self.redirect_smartcards),
+            redirect_comports: overrides.redirect_comports.or(self.redirect_comports),
+            redirect_drives: overrides.redirect_drives.or(self.redirect_drives),
+            display_connection_bar: overrides.display_connection_bar.or(self.display_connection_bar),
+            pin_connection_bar: overrides.pin_connection_bar.or(self.pin_connection_bar),
+            connection_type: overrides.connection_type.or(self.connection_type),
+            network_autodetect: overrides.network_autodetect.or(self.network_autodetect),
+            bandwidth_autodetect: overrides.bandwidth_autodetect.or(self.bandwidth_autodetect),
+            autoreconnect_max_retries: overrides
+                .autoreconnect_max_retries
+                .or(self.autoreconnect_max_retries),
+            gateway_hostname: overrides
+                .gateway_hostname
+                .clone()
+                .or_else(|| self.gateway_hostname.clone()),
+            desktop_width: overrides.desktop_width.or(self.desktop_width),
+            desktop_height: overrides.desktop_height.or(self.desktop_height),
+            smart_sizing: overrides.smart_sizing.or(self.smart_sizing),
+            window_position: overrides
+                .window_position
+                .clone()
+                .or_else(|| self.window_position.clone()),
+            remember_window_size: overrides.remember_window_size.or(self.remember_window_size),
+        }
+    }
+
+    /// Checks values that mstsc would reject or that are written into the
+    /// `.rdp` file as text
+    ///
+    /// # Returns
+    /// * `Ok(())` - The options can be saved
+    /// * `Err(AppError::InvalidHostname)` - The gateway is not a valid host address
+    /// * `Err(AppError::Other)` - The session size is outside
+    ///   [`MIN_DESKTOP_DIMENSION`]..=[`MAX_DESKTOP_DIMENSION`]
+    pub fn validate(&self) -> Result<(), AppError> {
+        let size_range = MIN_DESKTOP_DIMENSION..=MAX_DESKTOP_DIMENSION;
+        for (name, value) in [("width", self.desktop_width), ("height", self.desktop_height)] {
+            if value.is_some_and(|value| !size_range.contains(&value)) {
+                return Err(AppError::Other {
+                    message: format!(
+                        "Session {} must be between {} and {} pixels",
+                        name, MIN_DESKTOP_DIMENSION, MAX_DESKTOP_DIMENSION
+                    ),
+                    source: None,
+                });
+            }
+        }
+        self.validate_gateway()
+    }
+
+    /// Checks that the gateway, if set, is a valid host address
+    ///
+    /// A line break in it would otherwise add lines to the generated file.
+    pub fn validate_gateway(&self) -> Result<(), AppError> {
+        if let Some(gateway) = self
+            .gateway_hostname
+            .as_deref()
+            .map(str::trim)
+            .filter(|gateway| !gateway.is_empty())
+        {
+            crate::core::hosts::validate_host_address(gateway)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything that goes into a generated RDP file besides the host
+///
+/// Build with `RdpFileOptions::new()` and the setter methods; the default
+/// (no username or domain, default `RdpOptions`) produces the historical file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RdpFileOptions {
+    /// Username written to `username:s:` (without domain)
+    pub username: String,
+    /// Domain written to `domain:s:` (empty when none)
+    pub domain: String,
+    /// Display, redirection and experience settings
+    pub rdp: RdpOptions,
+    /// The gateway has its own saved credentials, so mstsc must not reuse the
+    /// session credentials for it (only used with `RdpOptions::gateway_hostname`)
+    pub separate_gateway_credentials: bool,
+}
+
+impl RdpFileOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = username.into();
+        self
+    }
+
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = domain.into();
+        self
+    }
+
+    /// Replaces all display, redirection and experience settings
+    pub fn options(mut self, options: RdpOptions) -> Self {
+        self.rdp = options;
+        self
+    }
+
+    pub fn keyboard_hook(mut self, keyboard_hook: KeyboardHook) -> Self {
+        self.rdp.keyboard_hook = Some(keyboard_hook);
+        self
+    }
+
+    pub fn redirect_clipboard(mut self, redirect: bool) -> Self {
+        self.rdp.redirect_clipboard = Some(redirect);
+        self
+    }
+
+    pub fn redirect_printers(mut self, redirect: bool) -> Self {
+        self.rdp.redirect_printers = Some(redirect);
+        self
+    }
+
+    pub fn redirect_smartcards(mut self, redirect: bool) -> Self {
+        self.rdp.redirect_smartcards = Some(redirect);
+        self
+    }
+
+    pub fn redirect_comports(mut self, redirect: bool) -> Self {
+        self.rdp.redirect_comports = Some(redirect);
+        self
+    }
+
+    pub fn redirect_drives(mut self, redirect: bool) -> Self {
+        self.rdp.redirect_drives = Some(redirect);
+        self
+    }
+
+    pub fn connection_bar(mut self, display: bool, pinned: bool) -> Self {
+        self.rdp.display_connection_bar = Some(display);
+        self.rdp.pin_connection_bar = Some(pinned);
+        self
+    }
+
+    pub fn connection_type(mut self, connection_type: ConnectionType) -> Self {
+        self.rdp.connection_type = Some(connection_type);
+        self
+    }
+
+    pub fn gateway(mut self, gateway_hostname: impl Into<String>) -> Self {
+        self.rdp.gateway_hostname = Some(gateway_hostname.into());
+        self
+    }
+
+    pub fn separate_gateway_credentials(mut self, separate: bool) -> Self {
+        self.separate_gateway_credentials = separate;
+        self
+    }
+
+    pub fn desktop_size(mut self, width: u32, height: u32) -> Self {
+        self.rdp.desktop_width = Some(width);
+        self.rdp.desktop_height = Some(height);
+        self
+    }
+
+    pub fn smart_sizing(mut self, smart_sizing: bool) -> Self {
+        self.rdp.smart_sizing = Some(smart_sizing);
+        self
+    }
+
+    pub fn window_position(mut self, winposstr: impl Into<String>) -> Self {
+        self.rdp.window_position = Some(winposstr.into());
+        self
+    }
+}
+
+/// Checks a `winposstr` value: six comma-separated integers
+/// (flags, show command, left, top, right, bottom)
+pub fn is_valid_window_position(winposstr: &str) -> bool {
+    let fields: Vec<&str> = winposstr.split(',').map(str::trim).collect();
+    fields.len() == 6 && fields.iter().all(|field| field.parse::<i32>().is_ok())
+}
+
+/// Reads the `winposstr:s:` value from existing RDP file content
+///
+/// mstsc writes the window position back into the `.rdp` file it opened
+/// when the session window closes.
+pub fn saved_window_position(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (key, rest) = line.trim().split_once(':')?;
+        let value = rest.strip_prefix("s:")?.trim();
+        (key.trim().eq_ignore_ascii_case("winposstr") && is_valid_window_position(value))
+            .then(|| value.to_string())
+    })
+}
+
+/// Carries the window position saved in a host's existing `.rdp` file over
+/// to the file about to replace it
+///
+/// Only applies when `remember_window_size` is on; the saved position wins
+/// over a configured `window_position`, since it is what the user last left.
+pub fn with_saved_window_position(
+    file_options: &RdpFileOptions,
+    existing_content: Option<&str>,
+) -> RdpFileOptions {
+    let mut file_options = file_options.clone();
+    if file_options.rdp.remember_window_size == Some(true) {
+        if let Some(saved) = existing_content.and_then(saved_window_position) {
+            file_options.rdp.window_position = Some(saved);
+        }
+    }
+    file_options
+}
+
+/// Returns the `.rdp` file name (without extension) for a host entry
+///
+/// Windows file names can't contain `:`, so the port separator and the
+/// colons and brackets of IPv6 literals become `_`.
+pub fn rdp_file_stem(hostname: &str) -> String {
+    hostname
+        .chars()
+        .map(|c| if matches!(c, ':' | '[' | ']') { '_' } else { c })
+        .collect()
+}
+
+/// Session size used when none is configured and no monitor is known
+pub const DEFAULT_DESKTOP_SIZE: (u32, u32) = (1920, 1080);
+
+/// Smallest session width or height mstsc accepts
+pub const MIN_DESKTOP_DIMENSION: u32 = 200;
+
+/// Largest session width or height mstsc accepts
+pub const MAX_DESKTOP_DIMENSION: u32 = 8192;
+
+/// Usable area of a monitor (excluding the taskbar), in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorArea {
+    pub width: u32,
+    pub height: u32,
+    /// Whether this is the primary monitor
+    pub primary: bool,
+}
+
+/// Picks a session size for "match my screen"
+///
+/// Uses the primary monitor's work area (or the first monitor's when none is
+/// marked primary). A requested width or height is kept when it fits and
+/// shrunk to the work area when it doesn't; a dimension that wasn't
+/// requested takes the work area's. The result is always within the range
+/// mstsc accepts.
+///
+/// # Arguments
+/// * `monitors` - Work areas of the connected monitors
+/// * `requested_width` / `requested_height` - Configured session size, if any
+///
+/// # Returns
+/// * `(width, height)` - Falls back to the requested size, or
+///   [`DEFAULT_DESKTOP_SIZE`], when there are no monitors
+pub fn suggest_resolution(
+    monitors: &[MonitorArea],
+    requested_width: Option<u32>,
+    requested_height: Option<u32>,
+) -> (u32, u32) {
+    let monitor = monitors
+        .iter()
+        .find(|monitor| monitor.primary)
+        .or_else(|| monitors.first());
+
+    let (width, height) = match monitor {
+        Some(monitor) => (
+            requested_width.map_or(monitor.width, |width| width.min(monitor.width)),
+            requested_height.map_or(monitor.height, |height| height.min(monitor.height)),
+        ),
+        None => (
+            requested_width.unwrap_or(DEFAULT_DESKTOP_SIZE.0),
+            requested_height.unwrap_or(DEFAULT_DESKTOP_SIZE.1),
+        ),
+    };
+
+    (
+        width.clamp(MIN_DESKTOP_DIMENSION, MAX_DESKTOP_DIMENSION),
+        height.clamp(MIN_DESKTOP_DIMENSION, MAX_DESKTOP_DIMENSION),
+    )
+}
+
+/// Converts a boolean setting into its RDP integer representation
+fn flag(value: bool) -> u8 {
+    u8::from(value)
+}
 
 /// Parses a username to extract domain and username components
 ///
@@ -45,14 +457,78 @@ pub fn parse_username(username: &str) -> (String, String) {
     }
 }
 
+/// Username format detected by `parse_username`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsernameFormat {
+    /// `DOMAIN\username`
+    Netbios,
+    /// `username@domain.com`
+    Upn,
+    /// `username` with no domain
+    Plain,
+}
+
+/// Result of checking a username before it is saved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UsernameInfo {
+    pub format: UsernameFormat,
+    pub domain: String,
+    pub username: String,
+    /// Problems likely to make authentication fail (empty if none)
+    pub warnings: Vec<String>,
+}
+
+/// Parses a username and reports its format plus anything likely to break binding
+///
+/// Uses the same rules as `parse_username`, so the reported domain and user are
+/// exactly what an RDP launch would use.
+pub fn analyze_username(username: &str) -> UsernameInfo {
+    let format = if username.contains('\\') {
+        UsernameFormat::Netbios
+    } else if username.contains('@') {
+        UsernameFormat::Upn
+    } else {
+        UsernameFormat::Plain
+    };
+    let (domain, user) = parse_username(username);
+
+    let mut warnings = Vec::new();
+    if username.trim().is_empty() {
+        warnings.push("Username is empty".to_string());
+    } else {
+        if username.trim() != username {
+            warnings.push("Username has leading or trailing whitespace".to_string());
+        } else if username.chars().any(char::is_whitespace) {
+            warnings.push("Username contains whitespace".to_string());
+        }
+        if username.contains("\\\\") {
+            warnings.push("Username contains a double backslash; use a single \\".to_string());
+        }
+        if format != UsernameFormat::Plain && domain.trim().is_empty() {
+            warnings.push("Domain part is empty".to_string());
+        }
+        if format != UsernameFormat::Plain && user.trim().is_empty() {
+            warnings.push("User part is empty".to_string());
+        }
+    }
+
+    UsernameInfo {
+        format,
+        domain,
+        username: user,
+        warnings,
+    }
+}
+
 /// Generates RDP file content for a host connection
 ///
 /// Creates a standard RDP file with optimal settings for Windows Server connections.
 ///
 /// # Arguments
 /// * `host` - The host to connect to
-/// * `username` - Username for authentication (without domain)
-/// * `domain` - Domain for authentication (empty string if none)
+/// * `file_options` - Username, domain and RDP settings; `RdpFileOptions::default()`
+///   yields the standard file with no credentials
 ///
 /// # Returns
 /// * RDP file content as a string
@@ -62,25 +538,68 @@ pub fn parse_username(username: &str) -> (String, String) {
 /// - Format: "key:type:value" where type is i (integer) or s (string)
 /// - Key settings:
 ///   - screen mode id:i:2 = Fullscreen mode
-///   - keyboardhook:i:2 = Send keyboard commands to remote session
+///   - keyboardhook:i:2 = Send Windows key combinations to remote session in full screen only
 ///   - prompt for credentials:i:0 = Use saved credentials (no prompt)
 ///   - enablecredsspsupport:i:1 = Enable CredSSP (Network Level Authentication)
 ///   - cert ignore:i:1 = Accept untrusted certificates
-pub fn generate_rdp_content(host: &Host, username: &str, domain: &str) -> String {
+pub fn generate_rdp_content(host: &Host, file_options: &RdpFileOptions) -> String {
+    let options = &file_options.rdp;
+    let keyboard_hook = options
+        .keyboard_hook
+        .unwrap_or(KeyboardHook::FullscreenOnly)
+        .rdp_value();
+    let redirect_printers = flag(options.redirect_printers.unwrap_or(true));
+    let redirect_comports = flag(options.redirect_comports.unwrap_or(false));
+    let redirect_smartcards = flag(options.redirect_smartcards.unwrap_or(true));
+    let redirect_clipboard = flag(options.redirect_clipboard.unwrap_or(true));
+    let connection_type = options
+        .connection_type
+        .unwrap_or(ConnectionType::LowSpeedBroadband);
+    // A fixed profile is ignored while auto-detection is on, so choosing one
+    // turns auto-detection off unless it was set explicitly
+    let network_autodetect = flag(options.network_autodetect.unwrap_or(
+        options.connection_type.is_none() || connection_type == ConnectionType::AutoDetect,
+    ));
+    let bandwidth_autodetect = flag(options.bandwidth_autodetect.unwrap_or(true));
+    // A hand-edited size outside mstsc's range would make it reject the file
+    let desktop_width = options
+        .desktop_width
+        .unwrap_or(DEFAULT_DESKTOP_SIZE.0)
+        .clamp(MIN_DESKTOP_DIMENSION, MAX_DESKTOP_DIMENSION);
+    let desktop_height = options
+        .desktop_height
+        .unwrap_or(DEFAULT_DESKTOP_SIZE.1)
+        .clamp(MIN_DESKTOP_DIMENSION, MAX_DESKTOP_DIMENSION);
+
+    // Without a gateway the historical "detect automatically" lines are kept.
+    // With one, mstsc always uses it and takes saved password credentials
+    // (source 0); prompting once means the session credentials cover both.
+    // A gateway with a line break would add its own lines to the file, so
+    // one with any control character is left out.
+    let gateway = options
+        .gateway_hostname
+        .as_deref()
+        .map(str::trim)
+        .filter(|gateway| !gateway.is_empty() && !gateway.chars().any(char::is_control));
+    let (gateway_usage, gateway_credentials_source, prompt_credential_once) = match gateway {
+        None => (4, 4, 1),
+        Some(_) => (1, 0, flag(!file_options.separate_gateway_credentials)),
+    };
+
     // Generate RDP file content with key settings for seamless connection
-    format!(
+    let mut content = format!(
         "screen mode id:i:2\r\n\
-desktopwidth:i:1920\r\n\
-desktopheight:i:1080\r\n\
+desktopwidth:i:{}\r\n\
+desktopheight:i:{}\r\n\
 session bpp:i:32\r\n\
 full address:s:{}\r\n\
 compression:i:1\r\n\
-keyboardhook:i:2\r\n\
+keyboardhook:i:{}\r\n\
 audiocapturemode:i:1\r\n\
 videoplaybackmode:i:1\r\n\
-connection type:i:2\r\n\
-networkautodetect:i:1\r\n\
-bandwidthautodetect:i:1\r\n\
+connection type:i:{}\r\n\
+networkautodetect:i:{}\r\n\
+bandwidthautodetect:i:{}\r\n\
 enableworkspacereconnect:i:1\r\n\
 disable wallpaper:i:0\r\n\
 allow desktop composition:i:0\r\n\
@@ -91,10 +610,10 @@ disable themes:i:0\r\n\
 disable cursor setting:i:0\r\n\
 bitmapcachepersistenable:i:1\r\n\
 audiomode:i:0\r\n\
-redirectprinters:i:1\r\n\
-redirectcomports:i:0\r\n\
-redirectsmartcards:i:1\r\n\
-redirectclipboard:i:1\r\n\
+redirectprinters:i:{}\r\n\
+redirectcomports:i:{}\r\n\
+redirectsmartcards:i:{}\r\n\
+redirectclipboard:i:{}\r\n\
 redirectposdevices:i:0\r\n\
 autoreconnection enabled:i:1\r\n\
 authentication level:i:0\r\n\
@@ -103,11 +622,11 @@ negotiate security layer:i:1\r\n\
 remoteapplicationmode:i:0\r\n\
 alternate shell:s:\r\n\
 shell working directory:s:\r\n\
-gatewayhostname:s:\r\n\
-gatewayusagemethod:i:4\r\n\
-gatewaycredentialssource:i:4\r\n\
-gatewayprofileusagemethod:i:0\r\n\
-promptcredentialonce:i:1\r\n\
+gatewayhostname:s:{}\r\n\
+gatewayusagemethod:i:{}\r\n\
+gatewaycredentialssource:i:{}\r\n\
+gatewayprofileusagemethod:i:{}\r\n\
+promptcredentialonce:i:{}\r\n\
 use redirection server name:i:0\r\n\
 rdgiskdcproxy:i:0\r\n\
 kdcproxyname:s:\r\n\
@@ -118,8 +637,213 @@ public mode:i:0\r\n\
 cert ignore:i:1\r\n\
 prompt for credentials on client:i:0\r\n\
 disableconnectionsharing:i:0\r\n",
-        host.hostname, username, domain
-    )
+        desktop_width,
+        desktop_height,
+        host.hostname,
+        keyboard_hook,
+        connection_type.rdp_value(),
+        network_autodetect,
+        bandwidth_autodetect,
+        redirect_printers,
+        redirect_comports,
+        redirect_smartcards,
+        redirect_clipboard,
+        gateway.unwrap_or(""),
+        gateway_usage,
+        gateway_credentials_source,
+        u8::from(gateway.is_some()),
+        prompt_credential_once,
+        file_options.username,
+        file_options.domain
+    );
+
+    // Drive redirection is only written when explicitly configured so the
+    // default file keeps leaving it to mstsc
+    if let Some(redirect_drives) = options.redirect_drives {
+        let drives = if redirect_drives { "*" } else { "" };
+        content.push_str(&format!("drivestoredirect:s:{}\r\n", drives));
+    }
+
+    // Connection bar lines are likewise only written when configured
+    if let Some(display) = options.display_connection_bar {
+        content.push_str(&format!("displayconnectionbar:i:{}\r\n", flag(display)));
+    }
+    if let Some(pin) = options.pin_connection_bar {
+        content.push_str(&format!("pinconnectionbar:i:{}\r\n", flag(pin)));
+    }
+    if let Some(retries) = options.autoreconnect_max_retries {
+        content.push_str(&format!("autoreconnect max retries:i:{}\r\n", retries));
+    }
+    if let Some(smart_sizing) = options.smart_sizing {
+        content.push_str(&format!("smart sizing:i:{}\r\n", flag(smart_sizing)));
+    }
+    // An invalid position would make mstsc ignore the file's window settings
+    if let Some(winposstr) = options
+        .window_position
+        .as_deref()
+        .map(str::trim)
+        .filter(|winposstr| is_valid_window_position(winposstr))
+    {
+        content.push_str(&format!("winposstr:s:{}\r\n", winposstr));
+    }
+
+    content
+}
+
+/// Decodes the bytes of a `.rdp` file
+///
+/// Files saved by mstsc are UTF-16LE with a byte order mark; hand-written
+/// ones are usually UTF-8.
+pub fn decode_rdp_bytes(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Builds a Host from the contents of an existing `.rdp` file
+///
+/// Reads `full address:s:` for the hostname (keeping a `:port` suffix) and
+/// `username:s:` / `domain:s:` for the connect username. The description is `name`, usually the file name.
+///
+/// # Returns
+/// * `Ok(Host)` - The parsed host
+/// * `Err(AppError::RdpFileError)` - No usable `full address` line
+pub fn host_from_rdp_content(content: &str, name: &str) -> Result<Host, AppError> {
+    let invalid = |reason: &str| AppError::RdpFileError {
+        hostname: name.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let setting = |key: &str| {
+        content.lines().find_map(|line| {
+            let (line_key, rest) = line.trim().split_once(':')?;
+            let value = rest.strip_prefix("s:")?;
+            line_key.trim().eq_ignore_ascii_case(key).then(|| value.trim().to_string())
+        })
+    };
+
+    let address = setting("full address")
+        .filter(|address| !address.is_empty())
+        .ok_or_else(|| invalid("No 'full address' line"))?;
+    crate::core::hosts::validate_host_address(&address).map_err(|e| invalid(&e.to_string()))?;
+    let (host, port) = crate::core::hosts::split_host_port(&address);
+    let hostname = crate::core::hosts::format_host_port(host, port);
+
+    let username = setting("username").filter(|username| !username.is_empty());
+    let domain = setting("domain").filter(|domain| !domain.is_empty());
+    let connect_username = match (username, domain) {
+        (Some(username), Some(domain)) if !username.contains('\\') && !username.contains('@') => {
+            Some(format!("{}\\{}", domain, username))
+        }
+        (username, _) => username,
+    };
+
+    Ok(Host {
+        hostname,
+        description: name.to_string(),
+        connect_username,
+        ..Default::default()
+    })
+}
+
+/// Settings every generated RDP file contains
+pub const REQUIRED_RDP_KEYS: [&str; 6] = [
+    "full address",
+    "screen mode id",
+    "desktopwidth",
+    "desktopheight",
+    "username",
+    "domain",
+];
+
+/// Outcome of checking RDP file content with [`validate_rdp_content`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RdpValidation {
+    /// No problems were found
+    pub valid: bool,
+    /// Why the content is invalid, one entry per problem (empty when valid)
+    pub problems: Vec<String>,
+    /// The `full address` value, when present
+    pub address: Option<String>,
+    /// Number of settings lines
+    pub settings: usize,
+}
+
+/// Checks that RDP file content is structurally sound
+///
+/// Every line other than blanks and `;` comments must be `key:type:value`
+/// with type `i` (integer value), `s` or `b`, keys must not repeat, the
+/// [`REQUIRED_RDP_KEYS`] must be present, no line may contain control
+/// characters, and `full address` must be a valid hostname with an optional
+/// port. Nothing is launched.
+pub fn validate_rdp_content(content: &str) -> RdpValidation {
+    let mut problems = Vec::new();
+    let mut keys: Vec<String> = Vec::new();
+    let mut address = None;
+
+    for (index, raw_line) in content.split('\n').enumerate() {
+        let number = index + 1;
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        if line.chars().any(char::is_control) {
+            problems.push(format!("Line {} contains a control character", number));
+            continue;
+        }
+        if line.trim().is_empty() || line.starts_with(';') {
+            continue;
+        }
+
+        let Some((key, kind, value)) = line
+            .split_once(':')
+            .and_then(|(key, rest)| rest.split_once(':').map(|(kind, value)| (key, kind, value)))
+        else {
+            problems.push(format!("Line {} is not key:type:value", number));
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        match kind {
+            "i" if value.trim().parse::<i64>().is_err() => {
+                problems.push(format!("'{}' has a non-integer value '{}'", key, value));
+            }
+            "i" | "s" | "b" => {}
+            _ => problems.push(format!("'{}' has unknown type '{}'", key, kind)),
+        }
+        if keys.contains(&key) {
+            problems.push(format!("'{}' is set more than once", key));
+        }
+        if key == "full address" {
+            address = Some(value.trim().to_string());
+        }
+        keys.push(key);
+    }
+
+    for required in REQUIRED_RDP_KEYS {
+        if !keys.iter().any(|key| key == required) {
+            problems.push(format!("Missing required setting '{}'", required));
+        }
+    }
+
+    if let Some(address) = &address {
+        if address.is_empty() {
+            problems.push("'full address' is empty".to_string());
+        } else if let Err(e) = crate::core::hosts::validate_host_address(address) {
+            problems.push(format!("'full address' is not valid: {}", e));
+        }
+    }
+
+    RdpValidation {
+        valid: problems.is_empty(),
+        problems,
+        address,
+        settings: keys.len(),
+    }
 }
 
 #[cfg(test)]
@@ -153,13 +877,667 @@ mod tests {
             hostname: "server.contoso.com".to_string(),
             description: "Test Server".to_string(),
             last_connected: None,
+            ..Default::default()
         };
 
-        let content = generate_rdp_content(&host, "john.doe", "CONTOSO");
+        let content = generate_rdp_content(&host, &john_doe());
 
         assert!(content.contains("full address:s:server.contoso.com"));
         assert!(content.contains("username:s:john.doe"));
         assert!(content.contains("domain:s:CONTOSO"));
         assert!(content.contains("\r\n")); // Windows line endings
     }
+
+    fn john_doe() -> RdpFileOptions {
+        RdpFileOptions::new().username("john.doe").domain("CONTOSO")
+    }
+
+    fn test_host() -> Host {
+        Host {
+            hostname: "server.contoso.com".to_string(),
+            description: "Test Server".to_string(),
+            last_connected: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rdp_file_options_default_output_matches_standard_file() {
+        let expected = [
+            "screen mode id:i:2",
+            "desktopwidth:i:1920",
+            "desktopheight:i:1080",
+            "session bpp:i:32",
+            "full address:s:server.contoso.com",
+            "compression:i:1",
+            "keyboardhook:i:2",
+            "audiocapturemode:i:1",
+            "videoplaybackmode:i:1",
+            "connection type:i:2",
+            "networkautodetect:i:1",
+            "bandwidthautodetect:i:1",
+            "enableworkspacereconnect:i:1",
+            "disable wallpaper:i:0",
+            "allow desktop composition:i:0",
+            "allow font smoothing:i:0",
+            "disable full window drag:i:1",
+            "disable menu anims:i:1",
+            "disable themes:i:0",
+            "disable cursor setting:i:0",
+            "bitmapcachepersistenable:i:1",
+            "audiomode:i:0",
+            "redirectprinters:i:1",
+            "redirectcomports:i:0",
+            "redirectsmartcards:i:1",
+            "redirectclipboard:i:1",
+            "redirectposdevices:i:0",
+            "autoreconnection enabled:i:1",
+            "authentication level:i:0",
+            "prompt for credentials:i:0",
+            "negotiate security layer:i:1",
+            "remoteapplicationmode:i:0",
+            "alternate shell:s:",
+            "shell working directory:s:",
+            "gatewayhostname:s:",
+            "gatewayusagemethod:i:4",
+            "gatewaycredentialssource:i:4",
+            "gatewayprofileusagemethod:i:0",
+            "promptcredentialonce:i:1",
+            "use redirection server name:i:0",
+            "rdgiskdcproxy:i:0",
+            "kdcproxyname:s:",
+            "username:s:john.doe",
+            "domain:s:CONTOSO",
+            "enablecredsspsupport:i:1",
+            "public mode:i:0",
+            "cert ignore:i:1",
+            "prompt for credentials on client:i:0",
+            "disableconnectionsharing:i:0",
+        ]
+        .iter()
+        .map(|line| format!("{}\r\n", line))
+        .collect::<String>();
+
+        let built = RdpFileOptions::new().username("john.doe").domain("CONTOSO");
+        let literal = RdpFileOptions {
+            username: "john.doe".to_string(),
+            domain: "CONTOSO".to_string(),
+            rdp: RdpOptions::default(),
+            separate_gateway_credentials: false,
+        };
+
+        assert_eq!(built, literal);
+        assert_eq!(generate_rdp_content(&test_host(), &built), expected);
+    }
+
+    #[test]
+    fn test_rdp_file_options_builder_sets_fields() {
+        let options = RdpFileOptions::new()
+            .username("admin")
+            .keyboard_hook(KeyboardHook::Local)
+            .redirect_clipboard(false)
+            .redirect_drives(true)
+            .connection_bar(true, false)
+            .connection_type(ConnectionType::Lan);
+
+        assert_eq!(options.domain, "");
+        assert_eq!(
+            options.rdp,
+            RdpOptions {
+                keyboard_hook: Some(KeyboardHook::Local),
+                redirect_clipboard: Some(false),
+                redirect_drives: Some(true),
+                display_connection_bar: Some(true),
+                pin_connection_bar: Some(false),
+                connection_type: Some(ConnectionType::Lan),
+                ..Default::default()
+            }
+        );
+
+        let content = generate_rdp_content(&test_host(), &options);
+        assert!(content.contains("keyboardhook:i:0\r\n"));
+        assert!(content.contains("redirectclipboard:i:0\r\n"));
+        assert!(content.contains("drivestoredirect:s:*\r\n"));
+        assert!(content.contains("connection type:i:6\r\n"));
+        assert!(content.contains("username:s:admin\r\ndomain:s:\r\n"));
+    }
+
+    #[test]
+    fn test_rdp_file_options_options_replaces_settings() {
+        let options = RdpFileOptions::new()
+            .redirect_printers(false)
+            .options(RdpOptions {
+                redirect_comports: Some(true),
+                ..Default::default()
+            });
+
+        assert_eq!(options.rdp.redirect_printers, None);
+        assert_eq!(options.rdp.redirect_comports, Some(true));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_default_options_unchanged() {
+        let content = generate_rdp_content(&test_host(), &john_doe());
+
+        assert!(content.contains("keyboardhook:i:2\r\n"));
+        assert!(content.contains("redirectprinters:i:1\r\n"));
+        assert!(content.contains("redirectcomports:i:0\r\n"));
+        assert!(content.contains("redirectsmartcards:i:1\r\n"));
+        assert!(content.contains("redirectclipboard:i:1\r\n"));
+        assert!(!content.contains("drivestoredirect"));
+        assert!(!content.contains("displayconnectionbar"));
+        assert!(!content.contains("pinconnectionbar"));
+        assert!(content.contains("connection type:i:2\r\n"));
+        assert!(content.contains("networkautodetect:i:1\r\n"));
+        assert!(content.contains("bandwidthautodetect:i:1\r\n"));
+        assert!(!content.contains("autoreconnect max retries"));
+        assert!(content.ends_with("disableconnectionsharing:i:0\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_keyboard_hook_values() {
+        let cases = [
+            (KeyboardHook::Local, "keyboardhook:i:0\r\n"),
+            (KeyboardHook::Remote, "keyboardhook:i:1\r\n"),
+            (KeyboardHook::FullscreenOnly, "keyboardhook:i:2\r\n"),
+        ];
+
+        for (hook, expected) in cases {
+            let options = RdpOptions {
+                keyboard_hook: Some(hook),
+                ..Default::default()
+            };
+            let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+            assert!(content.contains(expected), "missing {:?} for {:?}", expected, hook);
+            assert_eq!(content.matches("keyboardhook:i:").count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_generate_rdp_content_local_resource_overrides() {
+        let options = RdpOptions {
+            redirect_clipboard: Some(false),
+            redirect_printers: Some(false),
+            redirect_smartcards: Some(false),
+            redirect_comports: Some(true),
+            redirect_drives: Some(true),
+            ..Default::default()
+        };
+
+        let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+
+        assert!(content.contains("redirectclipboard:i:0\r\n"));
+        assert!(content.contains("redirectprinters:i:0\r\n"));
+        assert!(content.contains("redirectsmartcards:i:0\r\n"));
+        assert!(content.contains("redirectcomports:i:1\r\n"));
+        assert!(content.contains("drivestoredirect:s:*\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_connection_bar_enabled() {
+        let options = RdpOptions {
+            display_connection_bar: Some(true),
+            pin_connection_bar: Some(true),
+            ..Default::default()
+        };
+
+        let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+
+        assert!(content.contains("displayconnectionbar:i:1\r\n"));
+        assert!(content.contains("pinconnectionbar:i:1\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_connection_bar_disabled() {
+        let options = RdpOptions {
+            display_connection_bar: Some(false),
+            pin_connection_bar: Some(false),
+            ..Default::default()
+        };
+
+        let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+
+        assert!(content.contains("displayconnectionbar:i:0\r\n"));
+        assert!(content.contains("pinconnectionbar:i:0\r\n"));
+        assert_eq!(content.matches("connectionbar:i:").count(), 2);
+    }
+
+    #[test]
+    fn test_generate_rdp_content_connection_bar_unset_leaves_output_unchanged() {
+        let baseline = generate_rdp_content(&test_host(), &john_doe());
+        let options = RdpOptions {
+            pin_connection_bar: Some(false),
+            ..Default::default()
+        };
+
+        let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+
+        assert!(!content.contains("displayconnectionbar"));
+        assert_eq!(content, format!("{}pinconnectionbar:i:0\r\n", baseline));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_connection_type() {
+        let options = RdpOptions {
+            connection_type: Some(ConnectionType::Wan),
+            ..Default::default()
+        };
+
+        let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+
+        assert!(content.contains("connection type:i:5\r\n"));
+        assert_eq!(content.matches("connection type:i:").count(), 1);
+        // A fixed profile only applies with network auto-detection off
+        assert!(content.contains("networkautodetect:i:0\r\n"));
+        assert!(content.contains("bandwidthautodetect:i:1\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_experience_overrides() {
+        let options = RdpOptions {
+            connection_type: Some(ConnectionType::AutoDetect),
+            bandwidth_autodetect: Some(false),
+            autoreconnect_max_retries: Some(3),
+            ..Default::default()
+        };
+
+        let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+
+        assert!(content.contains("connection type:i:7\r\n"));
+        assert!(content.contains("networkautodetect:i:1\r\n"));
+        assert!(content.contains("bandwidthautodetect:i:0\r\n"));
+        assert!(content.ends_with("autoreconnect max retries:i:3\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_window_lines_absent_by_default() {
+        let content = generate_rdp_content(&test_host(), &john_doe());
+
+        assert!(!content.contains("smart sizing"));
+        assert!(!content.contains("winposstr"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_window_lines_use_configured_values() {
+        let file_options = john_doe()
+            .smart_sizing(true)
+            .window_position("0,1,100,50,1380,900");
+
+        let content = generate_rdp_content(&test_host(), &file_options);
+
+        assert!(content.contains("smart sizing:i:1\r\n"));
+        assert!(content.contains("winposstr:s:0,1,100,50,1380,900\r\n"));
+        assert_eq!(content.matches("winposstr:s:").count(), 1);
+
+        let content = generate_rdp_content(&test_host(), &john_doe().smart_sizing(false));
+        assert!(content.contains("smart sizing:i:0\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_skips_invalid_window_position() {
+        for winposstr in ["", "0,1,100,50", "0,1,a,50,1380,900", "0,1,100,50,1380,900,7"] {
+            let content =
+                generate_rdp_content(&test_host(), &john_doe().window_position(winposstr));
+            assert!(!content.contains("winposstr"), "winposstr: {:?}", winposstr);
+        }
+    }
+
+    #[test]
+    fn test_saved_window_position_reads_mstsc_line() {
+        let content = "full address:s:server01\r\nWinPosStr:s:0,3,-8,-8,1928,1048\r\n";
+
+        assert_eq!(
+            saved_window_position(content),
+            Some("0,3,-8,-8,1928,1048".to_string())
+        );
+        assert_eq!(saved_window_position("winposstr:s:garbage\r\n"), None);
+        assert_eq!(saved_window_position("full address:s:server01\r\n"), None);
+    }
+
+    #[test]
+    fn test_with_saved_window_position_requires_remember_window_size() {
+        let existing = "full address:s:server01\r\nwinposstr:s:0,1,10,20,810,620\r\n";
+        let configured = john_doe().window_position("0,1,0,0,800,600");
+
+        let unchanged = with_saved_window_position(&configured, Some(existing));
+        assert_eq!(unchanged, configured);
+
+        let mut remembering = configured.clone();
+        remembering.rdp.remember_window_size = Some(true);
+        let carried = with_saved_window_position(&remembering, Some(existing));
+        assert_eq!(carried.rdp.window_position.as_deref(), Some("0,1,10,20,810,620"));
+        assert!(generate_rdp_content(&test_host(), &carried)
+            .contains("winposstr:s:0,1,10,20,810,620\r\n"));
+
+        // Without a saved position the configured one is kept
+        let first_launch = with_saved_window_position(&remembering, None);
+        assert_eq!(first_launch.rdp.window_position.as_deref(), Some("0,1,0,0,800,600"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_explicit_network_autodetect_wins() {
+        let options = RdpOptions {
+            connection_type: Some(ConnectionType::Lan),
+            network_autodetect: Some(true),
+            ..Default::default()
+        };
+
+        let content = generate_rdp_content(&test_host(), &john_doe().options(options));
+
+        assert!(content.contains("connection type:i:6\r\n"));
+        assert!(content.contains("networkautodetect:i:1\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_gateway_shares_session_credentials() {
+        let content = generate_rdp_content(&test_host(), &john_doe().gateway(" rdg.domain.com "));
+
+        assert!(content.contains("gatewayhostname:s:rdg.domain.com\r\n"));
+        assert!(content.contains("gatewayusagemethod:i:1\r\n"));
+        assert!(content.contains("gatewaycredentialssource:i:0\r\n"));
+        assert!(content.contains("gatewayprofileusagemethod:i:1\r\n"));
+        assert!(content.contains("promptcredentialonce:i:1\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_drops_gateway_with_line_break() {
+        let content = generate_rdp_content(
+            &test_host(),
+            &john_doe().gateway("rdg.domain.com\r\nfull address:s:other.domain.com"),
+        );
+
+        assert!(content.contains("gatewayhostname:s:\r\n"));
+        assert!(content.contains("gatewayusagemethod:i:4\r\n"));
+        assert!(!content.contains("other.domain.com"));
+        assert!(validate_rdp_content(&content).valid);
+    }
+
+    #[test]
+    fn test_generate_rdp_content_clamps_desktop_size() {
+        let content = generate_rdp_content(&test_host(), &john_doe().desktop_size(0, 100_000));
+
+        assert!(content.contains(&format!("desktopwidth:i:{}\r\n", MIN_DESKTOP_DIMENSION)));
+        assert!(content.contains(&format!("desktopheight:i:{}\r\n", MAX_DESKTOP_DIMENSION)));
+    }
+
+    #[test]
+    fn test_rdp_options_validate_desktop_size() {
+        let sized = |width: u32, height: u32| RdpOptions {
+            desktop_width: Some(width),
+            desktop_height: Some(height),
+            ..Default::default()
+        };
+
+        assert!(sized(MIN_DESKTOP_DIMENSION, MAX_DESKTOP_DIMENSION).validate().is_ok());
+        assert!(sized(1280, 720).validate().is_ok());
+        assert!(sized(MIN_DESKTOP_DIMENSION - 1, 720).validate().is_err());
+        assert!(sized(1280, MAX_DESKTOP_DIMENSION + 1).validate().is_err());
+    }
+
+    #[test]
+    fn test_rdp_options_validate_gateway() {
+        let with_gateway = |gateway: &str| RdpOptions {
+            gateway_hostname: Some(gateway.to_string()),
+            ..Default::default()
+        };
+
+        assert!(RdpOptions::default().validate().is_ok());
+        assert!(with_gateway("").validate().is_ok());
+        assert!(with_gateway(" rdg.domain.com ").validate().is_ok());
+        assert!(with_gateway("rdg.domain.com:8443").validate().is_ok());
+        assert!(matches!(
+            with_gateway("rdg.domain.com\r\nfull address:s:other").validate(),
+            Err(AppError::InvalidHostname { .. })
+        ));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_gateway_with_separate_credentials() {
+        let options = john_doe()
+            .gateway("rdg.domain.com")
+            .separate_gateway_credentials(true);
+
+        let content = generate_rdp_content(&test_host(), &options);
+
+        assert!(content.contains("gatewaycredentialssource:i:0\r\n"));
+        assert!(content.contains("promptcredentialonce:i:0\r\n"));
+    }
+
+    #[test]
+    fn test_generate_rdp_content_blank_gateway_ignored() {
+        let options = john_doe().gateway("  ").separate_gateway_credentials(true);
+
+        let content = generate_rdp_content(&test_host(), &options);
+
+        assert!(content.contains("gatewayhostname:s:\r\n"));
+        assert!(content.contains("gatewayusagemethod:i:4\r\n"));
+        assert!(content.contains("gatewaycredentialssource:i:4\r\n"));
+        assert!(content.contains("promptcredentialonce:i:1\r\n"));
+    }
+
+    #[test]
+    fn test_rdp_options_overlay_prefers_overrides() {
+        let global = RdpOptions {
+            keyboard_hook: Some(KeyboardHook::Remote),
+            redirect_clipboard: Some(false),
+            ..Default::default()
+        };
+        let host = RdpOptions {
+            keyboard_hook: Some(KeyboardHook::Local),
+            ..Default::default()
+        };
+
+        let effective = global.overlay(&host);
+
+        assert_eq!(effective.keyboard_hook, Some(KeyboardHook::Local));
+        assert_eq!(effective.redirect_clipboard, Some(false));
+        assert_eq!(effective.redirect_printers, None);
+        assert_eq!(effective.gateway_hostname, None);
+
+        let per_host_gateway = RdpOptions {
+            gateway_hostname: Some("rdg.domain.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            global.overlay(&per_host_gateway).gateway_hostname.as_deref(),
+            Some("rdg.domain.com")
+        );
+
+        let per_host_window = RdpOptions {
+            remember_window_size: Some(true),
+            ..Default::default()
+        };
+        let global_window = RdpOptions {
+            smart_sizing: Some(true),
+            window_position: Some("0,1,0,0,800,600".to_string()),
+            ..Default::default()
+        };
+        let effective = global_window.overlay(&per_host_window);
+        assert_eq!(effective.smart_sizing, Some(true));
+        assert_eq!(effective.window_position.as_deref(), Some("0,1,0,0,800,600"));
+        assert_eq!(effective.remember_window_size, Some(true));
+    }
+
+    #[test]
+    fn test_desktop_size_is_written() {
+        let content = generate_rdp_content(&test_host(), &RdpFileOptions::new().desktop_size(2560, 1400));
+
+        assert!(content.contains("desktopwidth:i:2560\r\ndesktopheight:i:1400\r\n"));
+    }
+
+    fn monitor(width: u32, height: u32, primary: bool) -> MonitorArea {
+        MonitorArea { width, height, primary }
+    }
+
+    #[test]
+    fn test_suggest_resolution_uses_primary_work_area() {
+        let monitors = [monitor(1280, 984, false), monitor(2560, 1400, true)];
+
+        assert_eq!(suggest_resolution(&monitors, None, None), (2560, 1400));
+        // Without a primary the first monitor is used
+        assert_eq!(suggest_resolution(&monitors[..1], None, None), (1280, 984));
+    }
+
+    #[test]
+    fn test_suggest_resolution_shrinks_requests_to_fit() {
+        let monitors = [monitor(1920, 1040, true)];
+
+        assert_eq!(suggest_resolution(&monitors, Some(1600), Some(900)), (1600, 900));
+        assert_eq!(suggest_resolution(&monitors, Some(2560), Some(1440)), (1920, 1040));
+        assert_eq!(suggest_resolution(&monitors, Some(1024), None), (1024, 1040));
+        assert_eq!(suggest_resolution(&monitors, None, Some(1440)), (1920, 1040));
+    }
+
+    #[test]
+    fn test_suggest_resolution_without_monitors() {
+        assert_eq!(suggest_resolution(&[], None, None), DEFAULT_DESKTOP_SIZE);
+        assert_eq!(suggest_resolution(&[], Some(1280), Some(720)), (1280, 720));
+    }
+
+    #[test]
+    fn test_suggest_resolution_stays_within_mstsc_range() {
+        let tiny = [monitor(160, 120, true)];
+        assert_eq!(
+            suggest_resolution(&tiny, None, None),
+            (MIN_DESKTOP_DIMENSION, MIN_DESKTOP_DIMENSION)
+        );
+
+        let huge = [monitor(15360, 8640, true)];
+        assert_eq!(
+            suggest_resolution(&huge, None, None),
+            (MAX_DESKTOP_DIMENSION, MAX_DESKTOP_DIMENSION)
+        );
+        assert_eq!(suggest_resolution(&[], Some(10000), Some(50)), (MAX_DESKTOP_DIMENSION, MIN_DESKTOP_DIMENSION));
+    }
+
+    #[test]
+    fn test_validate_rdp_content_accepts_generated_file() {
+        let content = generate_rdp_content(
+            &test_host(),
+            &RdpFileOptions::new().username("admin").domain("CONTOSO").redirect_drives(true),
+        );
+
+        let validation = validate_rdp_content(&content);
+
+        assert_eq!(validation.problems, Vec::<String>::new());
+        assert!(validation.valid);
+        assert_eq!(validation.address.as_deref(), Some(test_host().hostname.as_str()));
+        assert_eq!(validation.settings, content.lines().count());
+    }
+
+    #[test]
+    fn test_validate_rdp_content_reports_each_problem() {
+        let content = "; comment\r\nfull address:s:server01:99999\r\nscreen mode id:i:full\r\nscreen mode id:i:2\r\nusername:s:a\x07b\r\nnot a setting\r\ndomain:x:\r\n";
+
+        let validation = validate_rdp_content(content);
+
+        assert!(!validation.valid);
+        assert_eq!(
+            validation.problems,
+            vec![
+                "'screen mode id' has a non-integer value 'full'",
+                "'screen mode id' is set more than once",
+                "Line 5 contains a control character",
+                "Line 6 is not key:type:value",
+                "'domain' has unknown type 'x'",
+                "Missing required setting 'desktopwidth'",
+                "Missing required setting 'desktopheight'",
+                "Missing required setting 'username'",
+                "'full address' is not valid: Invalid hostname 'server01:99999': Port must be between 1 and 65535",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_rdp_content_empty_address() {
+        let host = Host {
+            hostname: String::new(),
+            ..test_host()
+        };
+
+        let validation = validate_rdp_content(&generate_rdp_content(&host, &RdpFileOptions::new()));
+
+        assert!(!validation.valid);
+        assert_eq!(validation.problems, vec!["'full address' is empty"]);
+    }
+
+    #[test]
+    fn test_host_from_rdp_content() {
+        let content = "screen mode id:i:2\r\nfull address:s:server01.domain.com\r\nusername:s:admin\r\ndomain:s:CONTOSO\r\n";
+
+        let host = host_from_rdp_content(content, "Web Server").expect("Should parse");
+
+        assert_eq!(host.hostname, "server01.domain.com");
+        assert_eq!(host.description, "Web Server");
+        assert_eq!(host.connect_username, Some("CONTOSO\\admin".to_string()));
+    }
+
+    #[test]
+    fn test_host_from_rdp_content_roundtrips_generated_file() {
+        let content = generate_rdp_content(&test_host(), &RdpFileOptions::new().username("admin"));
+
+        let host = host_from_rdp_content(&content, "server01").expect("Should parse");
+
+        assert_eq!(host.hostname, test_host().hostname);
+        assert_eq!(host.connect_username, Some("admin".to_string()));
+    }
+
+    #[test]
+    fn test_host_from_rdp_content_username_variants() {
+        let parse = |lines: &str| {
+            host_from_rdp_content(&format!("full address:s:server01\n{}", lines), "x")
+                .expect("Should parse")
+                .connect_username
+        };
+
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("username:s:\ndomain:s:CONTOSO"), None);
+        assert_eq!(parse("username:s:admin@contoso.com\ndomain:s:CONTOSO"), Some("admin@contoso.com".to_string()));
+        assert_eq!(parse("username:s:CONTOSO\\admin\ndomain:s:OTHER"), Some("CONTOSO\\admin".to_string()));
+    }
+
+    #[test]
+    fn test_host_from_rdp_content_keeps_port() {
+        let host = host_from_rdp_content("full address:s:10.0.0.5:3390", "x").expect("Should parse");
+        assert_eq!(host.hostname, "10.0.0.5:3390");
+
+        let host = host_from_rdp_content("full address:s:[fe80::1]:3390", "x").expect("Should parse");
+        assert_eq!(host.hostname, "[fe80::1]:3390");
+
+        let host = host_from_rdp_content("full address:s:fe80::1", "x").expect("Should parse");
+        assert_eq!(host.hostname, "fe80::1");
+    }
+
+    #[test]
+    fn test_rdp_file_stem_is_a_valid_file_name() {
+        assert_eq!(rdp_file_stem("server01.domain.com"), "server01.domain.com");
+        assert_eq!(rdp_file_stem("server01:3390"), "server01_3390");
+        assert_eq!(rdp_file_stem("[fe80::1]:3390"), "_fe80__1__3390");
+    }
+
+    #[test]
+    fn test_host_from_rdp_content_missing_address() {
+        let result = host_from_rdp_content("screen mode id:i:2\r\nusername:s:admin\r\n", "broken");
+        assert!(matches!(result, Err(AppError::RdpFileError { .. })));
+
+        let result = host_from_rdp_content("full address:s:\r\n", "empty");
+        assert!(matches!(result, Err(AppError::RdpFileError { .. })));
+    }
+
+    #[test]
+    fn test_host_from_rdp_content_invalid_address() {
+        let result = host_from_rdp_content("full address:s:bad host!", "x");
+        assert!(matches!(result, Err(AppError::RdpFileError { .. })));
+    }
+
+    #[test]
+    fn test_decode_rdp_bytes_utf16_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "full address:s:server01".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        assert_eq!(decode_rdp_bytes(&bytes), "full address:s:server01");
+        assert_eq!(decode_rdp_bytes(b"full address:s:server01"), "full address:s:server01");
+    }
 }
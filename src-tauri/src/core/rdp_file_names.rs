@@ -0,0 +1,354 @@
+//! Unique RDP file names
+//!
+//! Maps each host to the name of its `.rdp` file in the Connections
+//! directory, recorded in `rdp_files.json` beside the files.
+//!
+//! # Why this exists
+//! File names come from hostnames via `rdp::rdp_file_stem`, which replaces
+//! characters Windows file names can't hold, so distinct hosts such as
+//! `server01:3390` and `server01_3390` map to the same name and would
+//! overwrite each other's file. The host that claims a name second gets a
+//! short hash of its hostname appended instead, and the recorded mapping
+//! keeps every host on the same file across runs.
+//!
+//! # Why separate
+//! Name assignment is a pure function of the mapping, so collisions and
+//! stability can be unit tested; the Connections directory is a parameter.
+
+use crate::core::hosts::normalize_hostname;
+use crate::core::rdp::{decode_rdp_bytes, host_from_rdp_content, rdp_file_stem};
+use crate::infra::debug_log;
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// File in the Connections directory recording each host's file name
+pub const RDP_FILE_MAP_NAME: &str = "rdp_files.json";
+
+/// Serializes load-assign-save so concurrent launches can't lose an entry
+static RDP_FILE_MAP_LOCK: Mutex<()> = Mutex::new(());
+
+/// Stable 8-digit hex hash of a hostname (32-bit FNV-1a)
+///
+/// Unlike `std`'s hasher the result never changes between builds, so file
+/// names stay the same after an upgrade.
+pub fn hostname_hash(hostname: &str) -> String {
+    let hash = hostname.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    format!("{:08x}", hash)
+}
+
+/// Key a hostname is recorded under (trimmed, lowercased)
+fn map_key(hostname: &str) -> String {
+    hostname.trim().to_lowercase()
+}
+
+/// Hostname to file name (without `.rdp`) for every host that has been given one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RdpFileMap {
+    files: BTreeMap<String, String>,
+}
+
+impl RdpFileMap {
+    /// Returns the recorded file name for a host, or the plain sanitized
+    /// hostname when none is recorded yet
+    pub fn stem_for(&self, hostname: &str) -> String {
+        self.files
+            .get(&map_key(hostname))
+            .cloned()
+            .unwrap_or_else(|| rdp_file_stem(hostname.trim()))
+    }
+
+    /// Returns the host's file name, choosing and recording one if needed
+    ///
+    /// The sanitized hostname is used unless another recorded host already
+    /// has that name (compared case-insensitively, like Windows file names)
+    /// or `file_owner` reports that an existing, unrecorded file of that name
+    /// belongs to a different host; then `-{hostname_hash}` is appended.
+    ///
+    /// # Arguments
+    /// * `file_owner` - Hostname an existing file with the given name was written for
+    pub fn assign<F>(&mut self, hostname: &str, file_owner: F) -> String
+    where
+        F: FnOnce(&str) -> Option<String>,
+    {
+        let key = map_key(hostname);
+        if let Some(stem) = self.files.get(&key) {
+            return stem.clone();
+        }
+
+        let slug = rdp_file_stem(hostname.trim());
+        let taken = self
+            .files
+            .values()
+            .any(|stem| stem.eq_ignore_ascii_case(&slug))
+            || file_owner(&slug)
+                .is_some_and(|owner| normalize_hostname(&owner) != normalize_hostname(hostname));
+        let stem = if taken {
+            format!("{}-{}", slug, hostname_hash(&key))
+        } else {
+            slug
+        };
+
+        self.files.insert(key, stem.clone());
+        stem
+    }
+
+    /// Forgets a host's recorded file name
+    ///
+    /// # Returns
+    /// * `Some(stem)` - The name that was recorded
+    /// * `None` - Nothing was recorded for the host
+    pub fn remove(&mut self, hostname: &str) -> Option<String> {
+        self.files.remove(&map_key(hostname))
+    }
+
+    /// Forgets every host recorded under one of the given file names
+    /// (compared case-insensitively)
+    ///
+    /// # Returns
+    /// Number of entries removed
+    pub fn remove_stems(&mut self, stems: &[String]) -> usize {
+        let before = self.files.len();
+        self.files
+            .retain(|_, stem| !stems.iter().any(|removed| removed.eq_ignore_ascii_case(stem)));
+        before - self.files.len()
+    }
+}
+
+/// Loads the file name mapping from a Connections directory
+///
+/// # Returns
+/// * `Ok(RdpFileMap)` - The mapping (empty when the file is missing or corrupt)
+/// * `Err(AppError)` - The file could not be read
+pub fn load_rdp_file_map(dir: &Path) -> Result<RdpFileMap, AppError> {
+    let path = dir.join(RDP_FILE_MAP_NAME);
+    if !path.exists() {
+        return Ok(RdpFileMap::default());
+    }
+
+    let json = std::fs::read_to_string(&path).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+
+    Ok(serde_json::from_str(&json).unwrap_or_else(|e| {
+        debug_log(
+            "WARN",
+            "RDP_LAUNCH",
+            &format!("RDP file name map {:?} is corrupt, starting empty", path),
+            Some(&e.to_string()),
+        );
+        RdpFileMap::default()
+    }))
+}
+
+/// Saves the file name mapping to a Connections directory
+pub fn save_rdp_file_map(dir: &Path, map: &RdpFileMap) -> Result<(), AppError> {
+    let path = dir.join(RDP_FILE_MAP_NAME);
+    let json = serde_json::to_string_pretty(map).map_err(|e| AppError::JsonError {
+        context: "Failed to serialize RDP file name map".to_string(),
+        source: e,
+    })?;
+
+    std::fs::write(&path, json).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })
+}
+
+/// Returns a host's `.rdp` file name (without extension) in `dir`, recording
+/// a new one when the host has none yet
+///
+/// An existing file that isn't recorded (written before names were recorded)
+/// is only taken over when its `full address` is this host.
+///
+/// # Side Effects
+/// - Writes `rdp_files.json` in `dir` when a name is assigned
+pub fn rdp_file_stem_in(dir: &Path, hostname: &str) -> Result<String, AppError> {
+    let _lock = RDP_FILE_MAP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut map = load_rdp_file_map(dir)?;
+    let before = map.files.len();
+
+    let stem = map.assign(hostname, |slug| {
+        let bytes = std::fs::read(dir.join(format!("{}.rdp", slug))).ok()?;
+        host_from_rdp_content(&decode_rdp_bytes(&bytes), slug)
+            .ok()
+            .map(|host| host.hostname)
+    });
+
+    if map.files.len() != before {
+        save_rdp_file_map(dir, &map)?;
+        debug_log(
+            "DEBUG",
+            "RDP_LAUNCH",
+            &format!("Recorded RDP file name {}.rdp for {}", stem, hostname),
+            None,
+        );
+    }
+    Ok(stem)
+}
+
+/// Removes a host's entry from the mapping in `dir`
+///
+/// Called once the host's file is gone, so the mapping doesn't keep growing
+/// with hosts that were deleted or whose file was cleared.
+///
+/// # Returns
+/// * `Ok(true)` - An entry was removed
+/// * `Ok(false)` - Nothing was recorded for the host
+/// * `Err(AppError)` - The mapping could not be read or written
+///
+/// # Side Effects
+/// - Rewrites `rdp_files.json` in `dir` when an entry is removed
+pub fn forget_rdp_file_in(dir: &Path, hostname: &str) -> Result<bool, AppError> {
+    let _lock = RDP_FILE_MAP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut map = load_rdp_file_map(dir)?;
+
+    let Some(stem) = map.remove(hostname) else {
+        return Ok(false);
+    };
+    save_rdp_file_map(dir, &map)?;
+    debug_log(
+        "DEBUG",
+        "RDP_LAUNCH",
+        &format!("Forgot RDP file name {}.rdp for {}", stem, hostname),
+        None,
+    );
+    Ok(true)
+}
+
+/// Removes the entries for deleted files from the mapping in `dir`
+///
+/// # Arguments
+/// * `stems` - Names of the deleted files, without `.rdp`
+///
+/// # Returns
+/// * `Ok(usize)` - Number of entries removed
+/// * `Err(AppError)` - The mapping could not be read or written
+///
+/// # Side Effects
+/// - Rewrites `rdp_files.json` in `dir` when an entry is removed
+pub fn forget_rdp_file_stems_in(dir: &Path, stems: &[String]) -> Result<usize, AppError> {
+    let _lock = RDP_FILE_MAP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut map = load_rdp_file_map(dir)?;
+
+    let removed = map.remove_stems(stems);
+    if removed > 0 {
+        save_rdp_file_map(dir, &map)?;
+    }
+    Ok(removed)
+}
+
+/// Returns a host's recorded `.rdp` file name in `dir` without recording anything
+///
+/// Falls back to the plain sanitized hostname when the mapping is missing or
+/// unreadable.
+pub fn recorded_rdp_file_stem_in(dir: &Path, hostname: &str) -> String {
+    load_rdp_file_map(dir).unwrap_or_default().stem_for(hostname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hostname_hash_is_stable() {
+        assert_eq!(hostname_hash("server01:3390"), "8a47a122");
+        assert_eq!(hostname_hash("server01_3390"), "82920c8d");
+        assert_eq!(hostname_hash(""), "811c9dc5");
+    }
+
+    #[test]
+    fn test_assign_keeps_plain_name_without_collision() {
+        let mut map = RdpFileMap::default();
+
+        assert_eq!(map.assign("server01.domain.com", |_| None), "server01.domain.com");
+        assert_eq!(map.assign("server01:3390", |_| None), "server01_3390");
+        assert_eq!(map.stem_for("SERVER01.domain.com"), "server01.domain.com");
+        assert_eq!(map.stem_for("unrecorded:3390"), "unrecorded_3390");
+    }
+
+    #[test]
+    fn test_assign_appends_hash_on_collision() {
+        let mut map = RdpFileMap::default();
+
+        let first = map.assign("server01_3390", |_| None);
+        let second = map.assign("server01:3390", |_| None);
+
+        assert_eq!(first, "server01_3390");
+        assert_eq!(second, "server01_3390-8a47a122");
+        // Repeated lookups return the recorded names
+        assert_eq!(map.assign("server01:3390", |_| None), second);
+        assert_eq!(map.assign("Server01_3390", |_| None), first);
+    }
+
+    #[test]
+    fn test_assign_avoids_unrecorded_file_of_another_host() {
+        let mut map = RdpFileMap::default();
+
+        let stem = map.assign("server01:3390", |_| Some("server01_3390".to_string()));
+        assert_eq!(stem, "server01_3390-8a47a122");
+
+        let mut map = RdpFileMap::default();
+        let stem = map.assign("server01:3390", |_| Some("SERVER01:3390".to_string()));
+        assert_eq!(stem, "server01_3390");
+    }
+
+    #[test]
+    fn test_rdp_file_stem_in_is_stable_across_runs() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+        let first = rdp_file_stem_in(temp_dir.path(), "server01:3390").unwrap();
+        let second = rdp_file_stem_in(temp_dir.path(), "server01_3390").unwrap();
+        assert_ne!(first.to_lowercase(), second.to_lowercase());
+
+        // A later run reads the recorded names back, whatever order hosts are launched in
+        assert_eq!(rdp_file_stem_in(temp_dir.path(), "server01_3390").unwrap(), second);
+        assert_eq!(rdp_file_stem_in(temp_dir.path(), "server01:3390").unwrap(), first);
+        assert_eq!(recorded_rdp_file_stem_in(temp_dir.path(), "server01_3390"), second);
+        assert!(temp_dir.path().join(RDP_FILE_MAP_NAME).exists());
+    }
+
+    #[test]
+    fn test_rdp_file_stem_in_checks_existing_file_owner() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        std::fs::write(
+            temp_dir.path().join("server01_3390.rdp"),
+            "full address:s:server01_3390\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            rdp_file_stem_in(temp_dir.path(), "server01:3390").unwrap(),
+            "server01_3390-8a47a122"
+        );
+    }
+
+    #[test]
+    fn test_forget_rdp_file_in_removes_only_that_host() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        rdp_file_stem_in(temp_dir.path(), "server01_3390").unwrap();
+        let second = rdp_file_stem_in(temp_dir.path(), "server01:3390").unwrap();
+
+        assert!(forget_rdp_file_in(temp_dir.path(), "SERVER01_3390").unwrap());
+        assert!(!forget_rdp_file_in(temp_dir.path(), "server01_3390").unwrap());
+
+        let map = load_rdp_file_map(temp_dir.path()).unwrap();
+        assert_eq!(map.files.len(), 1);
+        assert_eq!(map.stem_for("server01:3390"), second);
+    }
+
+    #[test]
+    fn test_load_rdp_file_map_tolerates_corrupt_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        std::fs::write(temp_dir.path().join(RDP_FILE_MAP_NAME), "not json").unwrap();
+
+        assert_eq!(load_rdp_file_map(temp_dir.path()).unwrap(), RdpFileMap::default());
+    }
+}
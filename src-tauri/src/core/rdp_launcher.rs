@@ -8,10 +8,17 @@
 //! - UI event emissions
 
 use crate::{Host, StoredCredentials, RecentConnections, AppError};
-use crate::adapters::{CredentialManager, WindowsCredentialManager};
-use crate::core::rdp::{parse_username, generate_rdp_content};
-use crate::infra::debug_log;
-use std::path::PathBuf;
+use crate::adapters::{
+    restrict_file_to_owner, CredentialManager, CredentialPersistence, CredentialType,
+    WindowsCredentialManager,
+};
+use crate::core::rdp::{parse_username, generate_rdp_content, validate_rdp_content, RdpFileOptions, RdpOptions, RdpValidation};
+use crate::core::credentials::GATEWAY_CREDENTIAL_PREFIX;
+use crate::core::settings;
+use crate::infra::{debug_log, get_connections_dir};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// Result of an RDP launch operation
 pub struct RdpLaunchResult {
@@ -19,35 +26,79 @@ pub struct RdpLaunchResult {
     pub hostname: String,
 }
 
+/// Progress of the mstsc launch, reported so the UI can show feedback
+///
+/// Serializes to the event payload: `{ "hostname": ... }`, plus `"error"` for
+/// `Failed`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum LaunchEvent {
+    /// mstsc is about to be started
+    Started { hostname: String },
+    /// mstsc started (and passed verification when `verify_rdp_launch` is on)
+    Completed { hostname: String },
+    /// mstsc could not be started or exited immediately
+    Failed { hostname: String, error: String },
+}
+
+impl LaunchEvent {
+    /// Name of the UI event carrying this payload
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            LaunchEvent::Started { .. } => "rdp-launch-started",
+            LaunchEvent::Completed { .. } => "rdp-launch-completed",
+            LaunchEvent::Failed { .. } => "rdp-launch-failed",
+        }
+    }
+}
+
 /// Launches an RDP connection to the specified host
 ///
 /// # Arguments
 /// * `host` - The host to connect to
 /// * `get_host_credentials_fn` - Async function to retrieve per-host credentials
 /// * `get_global_credentials_fn` - Async function to retrieve global credentials
+/// * `launch_mode` - Window state for mstsc (`None` = the `rdp_launch_mode` setting)
+/// * `on_event` - Receives `LaunchEvent::Started` right before mstsc is spawned,
+///   then `Completed` or `Failed`
 ///
 /// # Returns
 /// * `Ok(RdpLaunchResult)` - Connection launched successfully
 /// * `Err(AppError)` - Failed to launch connection
 ///
 /// # Side Effects
-/// - Creates TERMSRV/{hostname} credential if not exists (enables Windows RDP SSO)
+/// - Creates TERMSRV/{hostname} credential if not exists (enables Windows RDP SSO),
+///   unless `persist_rdp_credentials` is off
+/// - With an RD Gateway configured and credentials saved for it under
+///   TERMSRV/GATEWAY/{gateway}, also creates the TERMSRV/{gateway} credential mstsc
+///   uses for the gateway
+/// - Reads RDP options and credential persistence from settings.json (defaults if missing or invalid)
 /// - Writes RDP file to %APPDATA%/QuickConnect/Connections/{hostname}.rdp
+///   (beneath QUICKCONNECT_DATA_DIR instead when set)
+///   (an existing file is reused as-is when `preserve_rdp_edits` is on)
 /// - Updates recent_connections.json
-/// - Launches mstsc.exe process
+/// - Launches mstsc.exe process (and briefly watches it when `verify_rdp_launch` is on)
+/// - For hosts in `stable_hosts`, launches the existing RDP file without fetching
+///   or saving credentials when both the file and TERMSRV/{hostname} exist
+/// - With `high_security_mode` on, writes the RDP file to an owner-only temporary
+///   file instead, deletes it shortly after launch, and never uses the Connections
+///   directory (`stable_hosts` and `preserve_rdp_edits` don't apply)
 ///
 /// # Platform-Specific Behavior
 /// - Windows: Uses mstsc.exe as RDP client
 /// - Creates persistent RDP files for reuse
 /// - Stores credentials in Windows Credential Manager
-pub async fn launch_rdp_connection<F1, F2, Fut1, Fut2>(
+pub async fn launch_rdp_connection<F1, F2, Fut1, Fut2, E>(
     host: &Host,
     get_host_credentials_fn: F1,
     get_global_credentials_fn: F2,
+    launch_mode: Option<LaunchMode>,
+    on_event: E,
 ) -> Result<RdpLaunchResult, AppError>
 where
     F1: FnOnce(String) -> Fut1,
     F2: FnOnce() -> Fut2,
+    E: Fn(LaunchEvent),
     Fut1: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
     Fut2: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
 {
@@ -58,11 +109,43 @@ where
         None,
     );
 
-    // Step 1: Retrieve credentials (per-host first, then global fallback)
+    // Step 1: Load settings (RDP options and credential persistence)
+    let app_settings = load_launch_settings();
+    let launch_mode = launch_mode.unwrap_or(app_settings.rdp_launch_mode);
+
+    // Stable hosts launch their saved RDP file directly while it and the
+    // TERMSRV credential are both still there
+    if app_settings.is_stable_host(&host.hostname) && !app_settings.high_security_mode {
+        let rdp_path = recorded_rdp_file_path(&host.hostname)?;
+        let launched = launch_existing_rdp_file(
+            &WindowsCredentialManager::new(),
+            &host.hostname,
+            &rdp_path,
+            |path| {
+                launch_with_events(&host.hostname, &on_event, move || {
+                    launch_mstsc(
+                        path,
+                        launch_mode,
+                        app_settings.verify_rdp_launch,
+                        app_settings.mstsc_spawn_attempts,
+                    )
+                })
+            },
+        )
+        .await?;
+        if launched {
+            return Ok(RdpLaunchResult {
+                rdp_file_path: rdp_path,
+                hostname: host.hostname.clone(),
+            });
+        }
+    }
+
+    // Step 2: Retrieve credentials (per-host first, then global fallback)
     let credentials = get_credentials(host, get_host_credentials_fn, get_global_credentials_fn).await?;
 
-    // Step 2: Parse username to extract domain and username components
-    let (domain, username) = parse_username(&credentials.username);
+    // Step 3: Parse username (host override or credential username) into domain and username
+    let (domain, username) = resolve_connect_username(host, &credentials);
 
     debug_log(
         "INFO",
@@ -79,14 +162,58 @@ where
         )),
     );
 
-    // Step 3: Ensure TERMSRV credentials exist for RDP SSO
-    ensure_termsrv_credentials(host, &credentials, &domain, &username).await?;
-
-    // Step 4: Generate and write RDP file
-    let rdp_path = create_rdp_file(host, &username, &domain)?;
+    // Step 4: Ensure TERMSRV credentials exist for RDP SSO (unless disabled in settings)
+    let credential_manager = WindowsCredentialManager::new();
+    ensure_termsrv_credentials(
+        &credential_manager,
+        host,
+        &credentials,
+        &domain,
+        &username,
+        app_settings.termsrv_persistence(),
+    )
+    .await?;
+
+    // Resolve RDP options (global settings overlaid with per-host overrides)
+    let rdp_options = app_settings.rdp_options_for(&host.hostname);
+
+    // A gateway with its own saved credentials gets them written for mstsc too
+    let separate_gateway_credentials = match gateway_of(&rdp_options) {
+        Some(gateway) => ensure_gateway_credentials(
+            &credential_manager,
+            gateway,
+            app_settings.termsrv_persistence(),
+        )?,
+        None => false,
+    };
 
-    // Step 5: Launch mstsc.exe
-    launch_mstsc(&rdp_path)?;
+    let file_options = RdpFileOptions::new()
+        .username(username)
+        .domain(domain)
+        .options(rdp_options)
+        .separate_gateway_credentials(separate_gateway_credentials);
+
+    // Steps 5 and 6: Write the RDP file and launch mstsc.exe (per-launch mode
+    // overrides the setting)
+    let launch = |path: PathBuf| {
+        launch_with_events(&host.hostname, &on_event, move || {
+            launch_mstsc(
+                path,
+                launch_mode,
+                app_settings.verify_rdp_launch,
+                app_settings.mstsc_spawn_attempts,
+            )
+        })
+    };
+    let rdp_path = write_and_launch(
+        host,
+        &file_options,
+        &app_settings,
+        &std::env::temp_dir(),
+        EPHEMERAL_RDP_READ_TIMEOUT,
+        launch,
+    )
+    .await?;
 
     debug_log(
         "INFO",
@@ -104,42 +231,239 @@ where
     })
 }
 
-/// Retrieves credentials for RDP connection (per-host or global)
-async fn get_credentials<F1, F2, Fut1, Fut2>(
+/// Ensures a host's RDP file exists and reveals it in Explorer for hand editing
+///
+/// Does not launch mstsc. An existing file is never overwritten, so earlier
+/// edits survive; a missing file is generated from the current credentials and
+/// settings first.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path of the RDP file that was revealed
+/// * `Err(AppError)` - High-security mode is on, no credentials, file could not be
+///   written, or Explorer failed to start
+pub async fn open_rdp_file_for_edit<F1, F2, Fut1, Fut2>(
     host: &Host,
     get_host_credentials_fn: F1,
     get_global_credentials_fn: F2,
-) -> Result<StoredCredentials, AppError>
+) -> Result<PathBuf, AppError>
 where
     F1: FnOnce(String) -> Fut1,
     F2: FnOnce() -> Fut2,
     Fut1: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
     Fut2: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
 {
-    // Try per-host credentials first
-    if let Some(creds) = get_host_credentials_fn(host.hostname.clone()).await? {
-        debug_log(
-            "INFO",
-            "RDP_LAUNCH",
-            &format!("Using per-host credentials for {}", host.hostname),
-            None,
-        );
-        return Ok(creds);
+    if load_launch_settings().high_security_mode {
+        return Err(AppError::RdpFileError {
+            hostname: host.hostname.clone(),
+            reason: "RDP files are not kept on disk while high-security mode is on".to_string(),
+        });
+    }
+
+    let mut rdp_path = recorded_rdp_file_path(&host.hostname)?;
+
+    if !rdp_path.exists() {
+        let credentials =
+            get_credentials(host, get_host_credentials_fn, get_global_credentials_fn).await?;
+        let (domain, username) = resolve_connect_username(host, &credentials);
+        let rdp_options = load_launch_settings().rdp_options_for(&host.hostname);
+        let separate_gateway_credentials =
+            gateway_credentials_stored(&WindowsCredentialManager::new(), &rdp_options)?;
+        let file_options = RdpFileOptions::new()
+            .username(username)
+            .domain(domain)
+            .options(rdp_options)
+            .separate_gateway_credentials(separate_gateway_credentials);
+        rdp_path = create_rdp_file(host, &file_options)?;
     }
 
-    // Fall back to global credentials
     debug_log(
         "INFO",
         "RDP_LAUNCH",
-        &format!(
-            "No per-host credentials found for {}, using global credentials",
-            host.hostname
-        ),
+        &format!("Opening RDP file for editing: {:?}", rdp_path),
         None,
     );
 
-    match get_global_credentials_fn().await? {
-        Some(creds) => Ok(creds),
+    std::process::Command::new("explorer.exe")
+        .arg(format!("/select,{}", rdp_path.to_string_lossy()))
+        .spawn()
+        .map_err(|e| AppError::RdpFileError {
+            hostname: host.hostname.clone(),
+            reason: format!("Failed to open Explorer: {}", e),
+        })?;
+
+    Ok(rdp_path)
+}
+
+/// Line prepended to a preview generated without stored credentials
+pub const PREVIEW_NO_CREDENTIALS_NOTE: &str =
+    "; QuickConnect preview: no stored credentials found, username and domain are empty\r\n";
+
+/// Returns the RDP file content a launch would write for a host
+///
+/// Resolves credentials the same way as a launch (per-host, then global) to
+/// fill in username and domain. Nothing is written: no TERMSRV credential is
+/// saved, no RDP file is created, and mstsc is not started. Without stored
+/// credentials the content is generated with an empty username and domain,
+/// prefixed with `PREVIEW_NO_CREDENTIALS_NOTE`.
+///
+/// # Arguments
+/// * `credential_manager` - Read for the gateway's own credentials, never written
+/// * `host` - Host to preview
+/// * `options` - Effective RDP options for the host
+pub async fn preview_rdp_content<M, F1, F2, Fut1, Fut2>(
+    credential_manager: &M,
+    host: &Host,
+    options: &RdpOptions,
+    get_host_credentials_fn: F1,
+    get_global_credentials_fn: F2,
+) -> Result<String, AppError>
+where
+    M: CredentialManager,
+    F1: FnOnce(String) -> Fut1,
+    F2: FnOnce() -> Fut2,
+    Fut1: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+    Fut2: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+{
+    match get_credentials(host, get_host_credentials_fn, get_global_credentials_fn).await {
+        Ok(credentials) => {
+            let (domain, username) = resolve_connect_username(host, &credentials);
+            // A preview never fails over the gateway lookup; it falls back to shared credentials
+            let separate_gateway_credentials =
+                gateway_credentials_stored(credential_manager, options).unwrap_or(false);
+            let file_options = RdpFileOptions::new()
+                .username(username)
+                .domain(domain)
+                .options(options.clone())
+                .separate_gateway_credentials(separate_gateway_credentials);
+            Ok(generate_rdp_content(host, &file_options))
+        }
+        Err(AppError::CredentialsNotFound { .. }) => Ok(format!(
+            "{}{}",
+            PREVIEW_NO_CREDENTIALS_NOTE,
+            generate_rdp_content(host, &RdpFileOptions::new().options(options.clone()))
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+/// Generates a host's RDP file content as a launch would and checks it
+///
+/// Runs the same path as [`preview_rdp_content`], so nothing is written or
+/// launched, then parses the result back with `validate_rdp_content`. Used
+/// by automated checks to catch generation problems end to end.
+///
+/// # Returns
+/// * `Ok(RdpValidation)` - Whether the generated file is sound, and why not
+/// * `Err(AppError)` - Credentials couldn't be read
+pub async fn validate_generated_rdp<M, F1, F2, Fut1, Fut2>(
+    credential_manager: &M,
+    host: &Host,
+    options: &RdpOptions,
+    get_host_credentials_fn: F1,
+    get_global_credentials_fn: F2,
+) -> Result<RdpValidation, AppError>
+where
+    M: CredentialManager,
+    F1: FnOnce(String) -> Fut1,
+    F2: FnOnce() -> Fut2,
+    Fut1: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+    Fut2: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+{
+    let content =
+        preview_rdp_content(credential_manager, host, options, get_host_credentials_fn, get_global_credentials_fn)
+            .await?;
+    Ok(validate_rdp_content(&content))
+}
+
+/// Returns the effective RDP options for a host from settings.json (defaults if unreadable)
+pub fn rdp_options_for_host(hostname: &str) -> RdpOptions {
+    load_launch_settings().rdp_options_for(hostname)
+}
+
+/// Which stored credential a connection uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialSource {
+    /// The host's own `TERMSRV/{hostname}` credential
+    PerHost,
+    /// The global `QuickConnect` credential
+    Global,
+    /// Nothing stored; the launch would fail
+    None,
+}
+
+/// The credential a launch would use for a host, without the password
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CredentialResolution {
+    pub hostname: String,
+    pub source: CredentialSource,
+    /// Credential Manager target the credential was read from
+    pub target: Option<String>,
+    /// Domain mstsc would be given (empty when none)
+    pub domain: String,
+    /// Username mstsc would be given
+    pub username: String,
+    /// Whether the host's `connect_username` replaced the stored username
+    pub username_overridden: bool,
+    /// Length of the stored password; the password itself is never returned
+    pub password_length: usize,
+}
+
+/// Picks the credentials for a host: per-host first, then global
+async fn select_credentials<F1, F2, Fut1, Fut2>(
+    host: &Host,
+    get_host_credentials_fn: F1,
+    get_global_credentials_fn: F2,
+) -> Result<Option<(CredentialSource, StoredCredentials)>, AppError>
+where
+    F1: FnOnce(String) -> Fut1,
+    F2: FnOnce() -> Fut2,
+    Fut1: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+    Fut2: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+{
+    if let Some(creds) = get_host_credentials_fn(host.hostname.clone()).await? {
+        return Ok(Some((CredentialSource::PerHost, creds)));
+    }
+
+    Ok(get_global_credentials_fn()
+        .await?
+        .map(|creds| (CredentialSource::Global, creds)))
+}
+
+/// Retrieves credentials for RDP connection (per-host or global)
+async fn get_credentials<F1, F2, Fut1, Fut2>(
+    host: &Host,
+    get_host_credentials_fn: F1,
+    get_global_credentials_fn: F2,
+) -> Result<StoredCredentials, AppError>
+where
+    F1: FnOnce(String) -> Fut1,
+    F2: FnOnce() -> Fut2,
+    Fut1: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+    Fut2: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+{
+    match select_credentials(host, get_host_credentials_fn, get_global_credentials_fn).await? {
+        Some((CredentialSource::PerHost, creds)) => {
+            debug_log(
+                "INFO",
+                "RDP_LAUNCH",
+                &format!("Using per-host credentials for {}", host.hostname),
+                None,
+            );
+            Ok(creds)
+        }
+        Some((_, creds)) => {
+            debug_log(
+                "INFO",
+                "RDP_LAUNCH",
+                &format!(
+                    "No per-host credentials found for {}, using global credentials",
+                    host.hostname
+                ),
+                None,
+            );
+            Ok(creds)
+        }
         None => {
             let error = "No credentials found. Please save credentials in the login window first.";
             debug_log(
@@ -155,21 +479,114 @@ where
     }
 }
 
+/// Explains which credential a launch to `host` would use
+///
+/// Follows the same per-host-then-global order and `connect_username`
+/// override as `launch_rdp_connection`, but only reports the result. Finding
+/// no credentials is reported as `CredentialSource::None`, not an error.
+///
+/// # Returns
+/// * `Ok(CredentialResolution)` - Source, target and resolved domain/username
+/// * `Err(AppError)` - A credential lookup failed
+pub async fn explain_credential_resolution<F1, F2, Fut1, Fut2>(
+    host: &Host,
+    get_host_credentials_fn: F1,
+    get_global_credentials_fn: F2,
+) -> Result<CredentialResolution, AppError>
+where
+    F1: FnOnce(String) -> Fut1,
+    F2: FnOnce() -> Fut2,
+    Fut1: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+    Fut2: std::future::Future<Output = Result<Option<StoredCredentials>, AppError>>,
+{
+    let selected = select_credentials(host, get_host_credentials_fn, get_global_credentials_fn).await?;
+
+    let Some((source, credentials)) = selected else {
+        return Ok(CredentialResolution {
+            hostname: host.hostname.clone(),
+            source: CredentialSource::None,
+            target: None,
+            domain: String::new(),
+            username: String::new(),
+            username_overridden: false,
+            password_length: 0,
+        });
+    };
+
+    let target = match source {
+        CredentialSource::PerHost => format!("TERMSRV/{}", host.hostname),
+        _ => "QuickConnect".to_string(),
+    };
+    let (domain, username) = resolve_connect_username(host, &credentials);
+    let username_overridden = host
+        .connect_username
+        .as_deref()
+        .is_some_and(|username| !username.trim().is_empty());
+
+    Ok(CredentialResolution {
+        hostname: host.hostname.clone(),
+        source,
+        target: Some(target),
+        domain,
+        username,
+        username_overridden,
+        password_length: credentials.password.chars().count(),
+    })
+}
+
+/// Resolves the domain and username to connect with
+///
+/// A host's `connect_username` takes precedence over the username of the stored
+/// credentials, letting a host use a different account with the same password.
+/// Blank overrides are ignored.
+fn resolve_connect_username(host: &Host, credentials: &StoredCredentials) -> (String, String) {
+    match host.connect_username.as_deref().map(str::trim) {
+        Some(override_username) if !override_username.is_empty() => {
+            debug_log(
+                "INFO",
+                "RDP_LAUNCH",
+                &format!("Using connect username override for {}", host.hostname),
+                None,
+            );
+            parse_username(override_username)
+        }
+        _ => parse_username(&credentials.username),
+    }
+}
+
 /// Ensures TERMSRV/{hostname} credentials exist for Windows RDP SSO
 ///
-/// If per-host credentials don't exist, saves global credentials as TERMSRV/{hostname}.
-/// This enables Windows RDP client to automatically use saved credentials.
-async fn ensure_termsrv_credentials(
+/// If no domain password credential exists for TERMSRV/{hostname}, saves the resolved
+/// credentials under that target. The domain password type is what mstsc itself uses,
+/// so credentials saved by Windows are reused and ours are picked up by mstsc.
+///
+/// When `persistence` is `None` (the `persist_rdp_credentials` setting is off, e.g. on
+/// shared machines) nothing is written and mstsc prompts for credentials instead.
+async fn ensure_termsrv_credentials<M: CredentialManager>(
+    credential_manager: &M,
     host: &Host,
     credentials: &StoredCredentials,
     domain: &str,
     username: &str,
+    persistence: Option<CredentialPersistence>,
 ) -> Result<(), AppError> {
-    let credential_manager = WindowsCredentialManager::new();
     let target = format!("TERMSRV/{}", host.hostname);
 
-    // Check if TERMSRV credentials already exist
-    if credential_manager.read(&target)?.is_some() {
+    let Some(persistence) = persistence else {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("Credential persistence disabled, not saving {}", target),
+            None,
+        );
+        return Ok(());
+    };
+
+    // Check if TERMSRV credentials already exist (including ones saved by mstsc)
+    if credential_manager
+        .read_typed(&target, CredentialType::DomainPassword)?
+        .is_some()
+    {
         debug_log(
             "INFO",
             "RDP_LAUNCH",
@@ -193,7 +610,13 @@ async fn ensure_termsrv_credentials(
         username.to_string()
     };
 
-    credential_manager.save(&target, &termsrv_username, &credentials.password)?;
+    credential_manager.save_with_persistence(
+        &target,
+        &termsrv_username,
+        &credentials.password,
+        CredentialType::DomainPassword,
+        persistence,
+    )?;
 
     debug_log(
         "INFO",
@@ -205,41 +628,263 @@ async fn ensure_termsrv_credentials(
     Ok(())
 }
 
-/// Creates RDP file in AppData/QuickConnect/Connections directory
-fn create_rdp_file(host: &Host, username: &str, domain: &str) -> Result<PathBuf, AppError> {
-    // Get AppData directory
-    let appdata_dir = std::env::var("APPDATA")
-        .map_err(|_| AppError::IoError {
-            path: "APPDATA environment variable".to_string(),
-            source: std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "APPDATA environment variable not found",
-            ),
-        })?;
+/// Returns the configured RD Gateway, ignoring a blank value
+fn gateway_of(options: &RdpOptions) -> Option<&str> {
+    options
+        .gateway_hostname
+        .as_deref()
+        .map(str::trim)
+        .filter(|gateway| !gateway.is_empty())
+}
+
+/// Returns whether the configured gateway has its own saved credentials
+///
+/// `false` when no gateway is configured.
+fn gateway_credentials_stored<M: CredentialManager>(
+    credential_manager: &M,
+    options: &RdpOptions,
+) -> Result<bool, AppError> {
+    match gateway_of(options) {
+        Some(gateway) => Ok(credential_manager
+            .read(&format!("{}{}", GATEWAY_CREDENTIAL_PREFIX, gateway))?
+            .is_some()),
+        None => Ok(false),
+    }
+}
+
+/// Writes the gateway's saved credentials where mstsc looks for them
+///
+/// Credentials saved under `TERMSRV/GATEWAY/{gateway}` are copied to the
+/// `TERMSRV/{gateway}` domain credential unless one already exists. Nothing is
+/// written when `persistence` is `None`.
+///
+/// # Returns
+/// * `Ok(true)` - The gateway has its own credentials (distinct from the session's)
+/// * `Ok(false)` - None saved; mstsc reuses the session credentials for the gateway
+/// * `Err(AppError)` - Reading or saving a credential failed
+fn ensure_gateway_credentials<M: CredentialManager>(
+    credential_manager: &M,
+    gateway: &str,
+    persistence: Option<CredentialPersistence>,
+) -> Result<bool, AppError> {
+    let Some((gateway_username, gateway_password)) =
+        credential_manager.read(&format!("{}{}", GATEWAY_CREDENTIAL_PREFIX, gateway))?
+    else {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("No credentials saved for gateway {}, sharing the session credentials", gateway),
+            None,
+        );
+        return Ok(false);
+    };
+
+    let Some(persistence) = persistence else {
+        return Ok(true);
+    };
+
+    let target = format!("TERMSRV/{}", gateway);
+    if credential_manager
+        .read_typed(&target, CredentialType::DomainPassword)?
+        .is_none()
+    {
+        credential_manager.save_with_persistence(
+            &target,
+            &gateway_username,
+            &gateway_password,
+            CredentialType::DomainPassword,
+            persistence,
+        )?;
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("Saved gateway credentials to {} with username: {}", target, gateway_username),
+            None,
+        );
+    }
+
+    Ok(true)
+}
 
-    let connections_dir = PathBuf::from(&appdata_dir)
-        .join("QuickConnect")
-        .join("Connections");
+/// Loads the settings used while launching a connection
+///
+/// A missing or unreadable settings file must never block a connection, so
+/// failures are logged and the built-in defaults are used instead.
+fn load_launch_settings() -> settings::AppSettings {
+    settings::load_settings().unwrap_or_else(|e| {
+        debug_log(
+            "WARN",
+            "RDP_LAUNCH",
+            "Failed to load settings, using defaults",
+            Some(&format!("Error: {}", e)),
+        );
+        settings::AppSettings::default()
+    })
+}
+
+/// Decides whether a launch should (re)write the host's RDP file
+///
+/// Files are regenerated on every launch so settings changes take effect,
+/// unless the user has asked to keep manual edits and a file already exists.
+pub fn should_regenerate_rdp_file(preserve_manual_edits: bool, file_exists: bool) -> bool {
+    !(preserve_manual_edits && file_exists)
+}
+
+/// Returns the Connections directory, creating it if needed
+fn connections_dir() -> Result<PathBuf, AppError> {
+    let connections_dir = get_connections_dir().map_err(|e| AppError::IoError {
+        path: format!("Connections directory ({})", e),
+        source: std::io::Error::other(e),
+    })?;
 
     debug_log(
         "DEBUG",
         "RDP_LAUNCH",
         &format!("Connections directory: {:?}", connections_dir),
-        Some(&format!("AppData directory: {}", appdata_dir)),
+        None,
     );
+    Ok(connections_dir)
+}
 
-    // Create directory if it doesn't exist
-    std::fs::create_dir_all(&connections_dir).map_err(|e| AppError::IoError {
-        path: connections_dir.to_string_lossy().to_string(),
-        source: e,
-    })?;
+/// Returns the path of a host's existing RDP file without recording a name
+///
+/// For lookups only; [`create_rdp_file`] is the one place a name is assigned.
+fn recorded_rdp_file_path(hostname: &str) -> Result<PathBuf, AppError> {
+    let connections_dir = connections_dir()?;
+    let stem = crate::core::rdp_file_names::recorded_rdp_file_stem_in(&connections_dir, hostname);
+    Ok(connections_dir.join(format!("{}.rdp", stem)))
+}
+
+/// Returns the path to write a host's RDP file to, recording its name
+fn assign_rdp_file_path(hostname: &str) -> Result<PathBuf, AppError> {
+    let connections_dir = connections_dir()?;
+
+    // Generate RDP file path (hosts whose names sanitize alike get distinct files)
+    let stem = crate::core::rdp_file_names::rdp_file_stem_in(&connections_dir, hostname)?;
+    let rdp_filename = format!("{}.rdp", stem);
+    Ok(connections_dir.join(&rdp_filename))
+}
+
+/// Returns the RDP file to launch, reusing an existing file when manual edits are preserved
+fn prepare_rdp_file(
+    host: &Host,
+    file_options: &RdpFileOptions,
+    preserve_manual_edits: bool,
+) -> Result<PathBuf, AppError> {
+    let rdp_path = recorded_rdp_file_path(&host.hostname)?;
+
+    if !should_regenerate_rdp_file(preserve_manual_edits, rdp_path.exists()) {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("Reusing existing RDP file {:?} (manual edits preserved)", rdp_path),
+            None,
+        );
+        return Ok(rdp_path);
+    }
+
+    create_rdp_file(host, file_options)
+}
+
+/// Writes the RDP file for a launch and runs `launch` with it
+///
+/// In `high_security_mode` the file is a temporary one in `temp_dir` (see
+/// [`launch_ephemeral_rdp_file`]); otherwise it is the host's file in the
+/// Connections directory, reused when manual edits are preserved.
+async fn write_and_launch<L, Fut, P>(
+    host: &Host,
+    file_options: &RdpFileOptions,
+    app_settings: &settings::AppSettings,
+    temp_dir: &Path,
+    read_timeout: Duration,
+    launch: L,
+) -> Result<PathBuf, AppError>
+where
+    L: FnOnce(PathBuf) -> Fut,
+    Fut: std::future::Future<Output = Result<P, AppError>>,
+    P: LaunchedProcess,
+{
+    if app_settings.high_security_mode {
+        return launch_ephemeral_rdp_file(temp_dir, host, file_options, read_timeout, launch).await;
+    }
+
+    let rdp_path = prepare_rdp_file(host, file_options, app_settings.preserve_rdp_edits)?;
+    launch(rdp_path.clone()).await?;
+    Ok(rdp_path)
+}
+
+/// Launches a stable host's existing RDP file, skipping credential and file preparation
+///
+/// Only applies when the file exists and a TERMSRV/{hostname} credential is
+/// already saved; otherwise nothing is launched and the caller falls back to
+/// the full launch.
+///
+/// # Returns
+/// * `Ok(true)` - The existing file was launched
+/// * `Ok(false)` - File or credential missing, nothing launched
+/// * `Err(AppError)` - Credential lookup or launch failed
+async fn launch_existing_rdp_file<M, L, Fut, T>(
+    credential_manager: &M,
+    hostname: &str,
+    rdp_path: &Path,
+    launch: L,
+) -> Result<bool, AppError>
+where
+    M: CredentialManager,
+    L: FnOnce(PathBuf) -> Fut,
+    Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+    if !rdp_path.exists() {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("No saved RDP file for stable host {}, regenerating", hostname),
+            None,
+        );
+        return Ok(false);
+    }
+
+    let target = format!("TERMSRV/{}", hostname);
+    if credential_manager
+        .read_typed(&target, CredentialType::DomainPassword)?
+        .is_none()
+    {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("No {} credential for stable host, using full launch", target),
+            None,
+        );
+        return Ok(false);
+    }
+
+    debug_log(
+        "INFO",
+        "RDP_LAUNCH",
+        &format!("Launching saved RDP file {:?} for stable host {}", rdp_path, hostname),
+        None,
+    );
+    launch(rdp_path.to_path_buf()).await?;
+    Ok(true)
+}
 
-    // Generate RDP file path
-    let rdp_filename = format!("{}.rdp", host.hostname);
-    let rdp_path = connections_dir.join(&rdp_filename);
+/// Creates RDP file in the Connections directory (see `infra::paths::get_connections_dir`)
+fn create_rdp_file(host: &Host, file_options: &RdpFileOptions) -> Result<PathBuf, AppError> {
+    let rdp_path = assign_rdp_file_path(&host.hostname)?;
+
+    // mstsc saves the window position into the file on close; keep it when asked
+    let existing_content = if file_options.rdp.remember_window_size == Some(true) {
+        std::fs::read(&rdp_path)
+            .ok()
+            .map(|bytes| crate::core::rdp::decode_rdp_bytes(&bytes))
+    } else {
+        None
+    };
+    let file_options =
+        crate::core::rdp::with_saved_window_position(file_options, existing_content.as_deref());
 
     // Generate RDP content using core logic
-    let rdp_content = generate_rdp_content(host, username, domain);
+    let rdp_content = generate_rdp_content(host, &file_options);
 
     debug_log(
         "INFO",
@@ -276,34 +921,584 @@ fn create_rdp_file(host: &Host, username: &str, domain: &str) -> Result<PathBuf,
     Ok(rdp_path)
 }
 
-/// Launches mstsc.exe with the specified RDP file
-fn launch_mstsc(rdp_path: &PathBuf) -> Result<(), AppError> {
-    debug_log(
-        "INFO",
-        "RDP_LAUNCH",
-        "Attempting to launch mstsc.exe with RDP file",
-        Some(&format!("Target file: {:?}", rdp_path)),
-    );
+/// Longest a high-security launch waits for mstsc to read the temporary RDP
+/// file before deleting it anyway
+pub const EPHEMERAL_RDP_READ_TIMEOUT: Duration = Duration::from_secs(15);
 
-    std::process::Command::new("mstsc.exe")
-        .arg(rdp_path.to_string_lossy().as_ref())
-        .spawn()
-        .map_err(|e| {
-            debug_log(
-                "ERROR",
-                "RDP_LAUNCH",
-                &format!("Failed to launch mstsc.exe: {}", e),
-                Some(&format!("Failed to spawn mstsc.exe process: {:?}", e)),
-            );
-            AppError::RdpFileError {
-                hostname: rdp_path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("unknown")
-                    .to_string(),
-                reason: format!("Failed to launch mstsc.exe: {}", e),
-            }
+/// Interval between checks whether mstsc has read the temporary RDP file
+const EPHEMERAL_RDP_READ_POLL: Duration = Duration::from_millis(100);
+
+/// File name prefix of temporary RDP files
+const EPHEMERAL_RDP_PREFIX: &str = "QuickConnect-";
+
+/// Tries to delete a temporary RDP file before giving up (mstsc may still have it open)
+const EPHEMERAL_RDP_DELETE_ATTEMPTS: u32 = 5;
+
+/// Pause between attempts to delete a temporary RDP file
+const EPHEMERAL_RDP_DELETE_RETRY: Duration = Duration::from_millis(500);
+
+/// Distinguishes temporary RDP files created within the same nanosecond
+static EPHEMERAL_RDP_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Writes RDP content to a new owner-only file in `temp_dir`
+///
+/// The file name doesn't contain the hostname. Permissions are restricted
+/// before the content is written; on any failure the file is removed again.
+fn write_ephemeral_rdp_file(
+    temp_dir: &Path,
+    host: &Host,
+    file_options: &RdpFileOptions,
+) -> Result<PathBuf, AppError> {
+    use std::io::Write;
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let counter = EPHEMERAL_RDP_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let rdp_path = temp_dir.join(format!(
+        "{}{}-{:x}-{}.rdp",
+        EPHEMERAL_RDP_PREFIX,
+        std::process::id(),
+        nanos,
+        counter
+    ));
+    let io_error = |e: std::io::Error| AppError::IoError {
+        path: rdp_path.to_string_lossy().to_string(),
+        source: e,
+    };
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&rdp_path)
+        .map_err(io_error)?;
+
+    let written = restrict_file_to_owner(&rdp_path).and_then(|_| {
+        file.write_all(generate_rdp_content(host, file_options).as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(io_error)
+    });
+    drop(file);
+
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&rdp_path);
+        return Err(e);
+    }
+
+    debug_log(
+        "INFO",
+        "RDP_LAUNCH",
+        &format!("Wrote temporary RDP file {:?} for {} (high-security mode)", rdp_path, host.hostname),
+        None,
+    );
+
+    Ok(rdp_path)
+}
+
+/// Deletes a temporary RDP file, retrying while mstsc still has it open
+async fn remove_ephemeral_rdp_file(rdp_path: &Path) -> Result<(), AppError> {
+    let mut attempt = 1;
+    loop {
+        match std::fs::remove_file(rdp_path) {
+            Ok(()) => {
+                debug_log(
+                    "INFO",
+                    "RDP_LAUNCH",
+                    &format!("Deleted temporary RDP file {:?}", rdp_path),
+                    None,
+                );
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) if attempt < EPHEMERAL_RDP_DELETE_ATTEMPTS => {
+                debug_log(
+                    "WARN",
+                    "RDP_LAUNCH",
+                    &format!("Temporary RDP file {:?} not deleted yet, retrying: {}", rdp_path, e),
+                    None,
+                );
+                tokio::time::sleep(EPHEMERAL_RDP_DELETE_RETRY).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                debug_log(
+                    "ERROR",
+                    "RDP_LAUNCH",
+                    &format!("Failed to delete temporary RDP file {:?}: {}", rdp_path, e),
+                    None,
+                );
+                return Err(AppError::IoError {
+                    path: rdp_path.to_string_lossy().to_string(),
+                    source: e,
+                });
+            }
+        }
+    }
+}
+
+/// Waits until a freshly launched mstsc is done with its connection file
+///
+/// mstsc reads the file while it starts up, so this returns once the process
+/// has started (see [`LaunchedProcess::has_started`]) or exited, or after
+/// `timeout`.
+///
+/// # Returns
+/// * `true` - The process started or exited
+/// * `false` - Neither happened within `timeout`
+async fn wait_for_rdp_file_read<P: LaunchedProcess>(process: &mut P, timeout: Duration) -> bool {
+    let started = Instant::now();
+    loop {
+        if process.has_started() || process.try_exit_code().is_some() {
+            return true;
+        }
+        if started.elapsed() >= timeout {
+            return false;
+        }
+        tokio::time::sleep(EPHEMERAL_RDP_READ_POLL).await;
+    }
+}
+
+/// Launches a host from a temporary RDP file that is deleted afterwards
+///
+/// Writes the file to `temp_dir` (see [`write_ephemeral_rdp_file`]), runs
+/// `launch`, waits up to `read_timeout` for mstsc to read the file (see
+/// [`wait_for_rdp_file_read`]), then deletes it. The file is deleted even
+/// when the launch fails.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path the (now deleted) file had
+/// * `Err(AppError)` - The file could not be written, the launch failed, or
+///   the file could not be deleted
+async fn launch_ephemeral_rdp_file<L, Fut, P>(
+    temp_dir: &Path,
+    host: &Host,
+    file_options: &RdpFileOptions,
+    read_timeout: Duration,
+    launch: L,
+) -> Result<PathBuf, AppError>
+where
+    L: FnOnce(PathBuf) -> Fut,
+    Fut: std::future::Future<Output = Result<P, AppError>>,
+    P: LaunchedProcess,
+{
+    let rdp_path = write_ephemeral_rdp_file(temp_dir, host, file_options)?;
+
+    let mut launched = launch(rdp_path.clone()).await;
+    if let Ok(process) = &mut launched {
+        if !wait_for_rdp_file_read(process, read_timeout).await {
+            debug_log(
+                "WARN",
+                "RDP_LAUNCH",
+                &format!(
+                    "mstsc did not finish starting within {:?}, deleting temporary RDP file {:?} anyway",
+                    read_timeout, rdp_path
+                ),
+                None,
+            );
+        }
+    }
+    let removed = remove_ephemeral_rdp_file(&rdp_path).await;
+
+    launched?;
+    removed?;
+    Ok(rdp_path)
+}
+
+/// Deletes temporary RDP files left in `temp_dir` by earlier runs
+///
+/// A high-security launch deletes its file once mstsc has read it, but a
+/// crash or forced exit in between leaves it behind. Files written by the
+/// current process are kept.
+///
+/// # Returns
+/// * Number of files deleted
+///
+/// # Side Effects
+/// - Deletes `QuickConnect-*.rdp` files; a file that can't be deleted is logged and skipped
+pub fn sweep_ephemeral_rdp_files(temp_dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(temp_dir) else {
+        return 0;
+    };
+    let own_prefix = format!("{}{}-", EPHEMERAL_RDP_PREFIX, std::process::id());
+
+    let mut removed = 0;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !name.starts_with(EPHEMERAL_RDP_PREFIX)
+            || !name.ends_with(".rdp")
+            || name.starts_with(&own_prefix)
+            || !path.is_file()
+        {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => debug_log(
+                "WARN",
+                "RDP_LAUNCH",
+                &format!("Failed to delete stale temporary RDP file {:?}", path),
+                Some(&e.to_string()),
+            ),
+        }
+    }
+
+    if removed > 0 {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            &format!("Deleted {} stale temporary RDP file(s) from {:?}", removed, temp_dir),
+            None,
+        );
+    }
+    removed
+}
+
+/// How long verify-launch mode watches mstsc for an immediate failure
+pub const LAUNCH_VERIFY_WINDOW: Duration = Duration::from_millis(1500);
+
+/// Interval between exit checks while verifying a launch
+const LAUNCH_VERIFY_POLL: Duration = Duration::from_millis(100);
+
+/// Pause before trying to start mstsc again after a transient spawn failure
+pub const SPAWN_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Whether starting mstsc again might succeed after `error`
+///
+/// A missing executable, a policy block or a bad command line fails the same
+/// way every time; anything else (e.g. resource exhaustion under heavy load)
+/// is worth another attempt.
+fn is_retryable_spawn_error(error: &std::io::Error) -> bool {
+    !matches!(
+        error.kind(),
+        std::io::ErrorKind::NotFound
+            | std::io::ErrorKind::PermissionDenied
+            | std::io::ErrorKind::InvalidInput
+    )
+}
+
+/// A spawned RDP client process that can be checked for an early exit
+pub trait LaunchedProcess {
+    /// Returns the exit code if the process has already exited
+    fn try_exit_code(&mut self) -> Option<i32>;
+
+    /// Whether the process has finished starting up and is waiting for input
+    ///
+    /// mstsc has read its connection file by then. Processes that can't tell
+    /// report `false`.
+    fn has_started(&mut self) -> bool {
+        false
+    }
+}
+
+impl LaunchedProcess for Box<dyn LaunchedProcess + Send> {
+    fn try_exit_code(&mut self) -> Option<i32> {
+        (**self).try_exit_code()
+    }
+
+    fn has_started(&mut self) -> bool {
+        (**self).has_started()
+    }
+}
+
+impl LaunchedProcess for crate::adapters::MinimizedProcess {
+    fn try_exit_code(&mut self) -> Option<i32> {
+        crate::adapters::MinimizedProcess::try_exit_code(self)
+    }
+
+    fn has_started(&mut self) -> bool {
+        self.is_input_idle()
+    }
+}
+
+impl LaunchedProcess for std::process::Child {
+    fn try_exit_code(&mut self) -> Option<i32> {
+        self.try_wait()
+            .ok()
+            .flatten()
+            // Killed by a signal (non-Windows only) counts as a failure
+            .map(|status| status.code().unwrap_or(-1))
+    }
+
+    fn has_started(&mut self) -> bool {
+        child_has_started(self)
+    }
+}
+
+#[cfg(windows)]
+fn child_has_started(child: &std::process::Child) -> bool {
+    use std::os::windows::io::AsRawHandle;
+    crate::adapters::process_is_input_idle(child.as_raw_handle())
+}
+
+#[cfg(not(windows))]
+fn child_has_started(_child: &std::process::Child) -> bool {
+    false
+}
+
+/// Watches a freshly launched RDP client for an immediate non-zero exit
+///
+/// mstsc exits straight away when it rejects a connection file; a process
+/// still running after `window` is treated as a successful launch.
+///
+/// # Returns
+/// * `Ok(())` - Still running after `window`, or exited with code 0
+/// * `Err(AppError::RdpLaunchError)` - Exited early with a non-zero code
+pub fn verify_launch<P: LaunchedProcess>(
+    child: &mut P,
+    window: Duration,
+    poll: Duration,
+) -> Result<(), AppError> {
+    let started = Instant::now();
+
+    loop {
+        if let Some(code) = child.try_exit_code() {
+            if code == 0 {
+                return Ok(());
+            }
+            return Err(AppError::RdpLaunchError {
+                source: std::io::Error::other(format!(
+                    "mstsc.exe exited immediately with code {}; the connection file was rejected",
+                    code
+                )),
+            });
+        }
+
+        if started.elapsed() >= window {
+            return Ok(());
+        }
+        std::thread::sleep(poll);
+    }
+}
+
+/// Window state mstsc is started in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchMode {
+    /// Foreground window with focus
+    #[default]
+    Normal,
+    /// Minimized to the taskbar without stealing focus
+    Minimized,
+}
+
+/// Program, arguments and window state used to start mstsc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MstscSpawnConfig {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Windows process creation flags (0 = none)
+    pub creation_flags: u32,
+    /// Start minimized to the taskbar without taking focus
+    pub minimized: bool,
+}
+
+impl MstscSpawnConfig {
+    /// Builds the process command for this configuration
+    ///
+    /// `std::process::Command` can't set the initial window state, so a
+    /// `minimized` configuration is started with `spawn` instead.
+    pub fn command(&self) -> std::process::Command {
+        let mut command = std::process::Command::new(&self.program);
+        command.args(&self.args);
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(self.creation_flags);
+        }
+        command
+    }
+
+    /// Returns the Windows command line for this configuration
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|arg| quote_windows_arg(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Starts the process in the configured window state
+    pub fn spawn(&self) -> std::io::Result<Box<dyn LaunchedProcess + Send>> {
+        if self.minimized {
+            let process = crate::adapters::spawn_minimized(&self.command_line())?;
+            Ok(Box::new(process))
+        } else {
+            Ok(Box::new(self.command().spawn()?))
+        }
+    }
+}
+
+/// Quotes one argument the way the Windows C runtime splits command lines
+///
+/// No shell is involved, so only spaces, tabs and quotes need handling.
+fn quote_windows_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        // Backslashes only need escaping when a quote follows them
+        let escaped = if c == '"' { backslashes * 2 + 1 } else { backslashes };
+        quoted.extend(std::iter::repeat_n('\\', escaped));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Chooses how to start mstsc for a launch mode
+///
+/// mstsc has no switch for starting minimized, so `Minimized` asks Windows
+/// to show its first window minimized without activation. mstsc is started
+/// directly in both modes (no `cmd.exe`), so the file path is never parsed by
+/// a shell and `verify_rdp_launch` watches the real mstsc process.
+pub fn mstsc_spawn_config(rdp_path: &Path, mode: LaunchMode) -> MstscSpawnConfig {
+    MstscSpawnConfig {
+        program: "mstsc.exe".to_string(),
+        args: vec![rdp_path.to_string_lossy().to_string()],
+        creation_flags: 0,
+        minimized: mode == LaunchMode::Minimized,
+    }
+}
+
+/// Runs `launch`, reporting `Started` before it and `Completed`/`Failed` after
+async fn launch_with_events<E, L, Fut, T>(hostname: &str, on_event: &E, launch: L) -> Result<T, AppError>
+where
+    E: Fn(LaunchEvent),
+    L: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+    on_event(LaunchEvent::Started {
+        hostname: hostname.to_string(),
+    });
+
+    let result = launch().await;
+
+    on_event(match &result {
+        Ok(_) => LaunchEvent::Completed {
+            hostname: hostname.to_string(),
+        },
+        Err(e) => LaunchEvent::Failed {
+            hostname: hostname.to_string(),
+            error: e.to_string(),
+        },
+    });
+
+    result
+}
+
+/// Launches mstsc.exe with the specified RDP file
+///
+/// Fire-and-forget by default; with `verify` the process is watched briefly
+/// so an immediate rejection of the file is reported as an error. A spawn
+/// failure that may be transient is retried up to `spawn_attempts` times in
+/// total. Spawning, retry pauses and verification run on a blocking thread,
+/// not the async runtime.
+///
+/// # Returns
+/// * `Ok(process)` - The started mstsc process (dropping it doesn't stop mstsc)
+/// * `Err(AppError)` - mstsc could not be started, or exited immediately
+async fn launch_mstsc(
+    rdp_path: PathBuf,
+    mode: LaunchMode,
+    verify: bool,
+    spawn_attempts: u32,
+) -> Result<Box<dyn LaunchedProcess + Send>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        launch_mstsc_with(&rdp_path, mode, verify, spawn_attempts, SPAWN_RETRY_DELAY, |config| config.spawn())
+    })
+    .await
+    .map_err(|e| AppError::RdpLaunchError {
+        source: std::io::Error::other(e),
+    })?
+}
+
+/// Launches the RDP client through `spawn` (injectable for tests)
+fn launch_mstsc_with<S, P>(
+    rdp_path: &Path,
+    mode: LaunchMode,
+    verify: bool,
+    spawn_attempts: u32,
+    retry_delay: Duration,
+    mut spawn: S,
+) -> Result<P, AppError>
+where
+    S: FnMut(&MstscSpawnConfig) -> std::io::Result<P>,
+    P: LaunchedProcess,
+{
+    let config = mstsc_spawn_config(rdp_path, mode);
+    let spawn_attempts = spawn_attempts.max(1);
+    let mut attempt = 1;
+
+    let mut child = loop {
+        debug_log(
+            "INFO",
+            "RDP_LAUNCH",
+            "Attempting to launch mstsc.exe with RDP file",
+            Some(&format!(
+                "Target file: {:?}, mode: {:?}, attempt {} of {}",
+                rdp_path, mode, attempt, spawn_attempts
+            )),
+        );
+
+        match spawn(&config) {
+            Ok(child) => break child,
+            Err(e) if attempt < spawn_attempts && is_retryable_spawn_error(&e) => {
+                debug_log(
+                    "WARN",
+                    "RDP_LAUNCH",
+                    &format!(
+                        "Failed to launch mstsc.exe (attempt {} of {}), retrying: {}",
+                        attempt, spawn_attempts, e
+                    ),
+                    Some(&format!("Spawn error: {:?}, retrying in {:?}", e, retry_delay)),
+                );
+                std::thread::sleep(retry_delay);
+                attempt += 1;
+            }
+            Err(e) => {
+                debug_log(
+                    "ERROR",
+                    "RDP_LAUNCH",
+                    &format!("Failed to launch mstsc.exe: {}", e),
+                    Some(&format!(
+                        "Failed to spawn mstsc.exe process on attempt {} of {}: {:?}",
+                        attempt, spawn_attempts, e
+                    )),
+                );
+                return Err(AppError::RdpFileError {
+                    hostname: rdp_path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("unknown")
+                        .to_string(),
+                    reason: format!("Failed to launch mstsc.exe: {}", e),
+                });
+            }
+        }
+    };
+
+    if verify {
+        verify_launch(&mut child, LAUNCH_VERIFY_WINDOW, LAUNCH_VERIFY_POLL).inspect_err(|e| {
+            debug_log(
+                "ERROR",
+                "RDP_LAUNCH",
+                "mstsc.exe exited immediately after launch",
+                Some(&e.to_string()),
+            );
         })?;
+    }
 
     debug_log(
         "INFO",
@@ -312,7 +1507,52 @@ fn launch_mstsc(rdp_path: &PathBuf) -> Result<(), AppError> {
         None,
     );
 
-    Ok(())
+    Ok(child)
+}
+
+/// Delay between launches in a batch so mstsc instances don't race on TERMSRV credential writes
+pub const BATCH_LAUNCH_STAGGER: std::time::Duration = std::time::Duration::from_millis(750);
+
+/// Launches several hosts one after another, pausing between launches
+///
+/// A failed launch is logged and doesn't stop the remaining hosts.
+///
+/// # Arguments
+/// * `hosts` - Hosts to launch, in order
+/// * `launch` - Launches a single host
+/// * `stagger` - Pause between consecutive launches
+///
+/// # Returns
+/// * Number of hosts launched successfully
+pub async fn launch_hosts_staggered<F, Fut>(
+    hosts: Vec<Host>,
+    mut launch: F,
+    stagger: std::time::Duration,
+) -> usize
+where
+    F: FnMut(Host) -> Fut,
+    Fut: std::future::Future<Output = Result<(), String>>,
+{
+    let mut launched = 0;
+
+    for (index, host) in hosts.into_iter().enumerate() {
+        if index > 0 && !stagger.is_zero() {
+            tokio::time::sleep(stagger).await;
+        }
+
+        let hostname = host.hostname.clone();
+        match launch(host).await {
+            Ok(()) => launched += 1,
+            Err(e) => debug_log(
+                "WARN",
+                "RDP_LAUNCH",
+                &format!("Batch launch to {} failed", hostname),
+                Some(&e),
+            ),
+        }
+    }
+
+    launched
 }
 
 /// Updates recent connections tracking
@@ -326,6 +1566,8 @@ pub fn update_recent_connections(host: &Host, recent: &mut RecentConnections) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::adapters::mock::MockCredentialManager;
+    use crate::infra::paths::EnvGuard;
     use std::fs;
     use tempfile::TempDir;
 
@@ -334,6 +1576,7 @@ mod tests {
             hostname: hostname.to_string(),
             description: description.to_string(),
             last_connected: None,
+            ..Default::default()
         }
     }
 
@@ -400,15 +1643,16 @@ mod tests {
 
     #[test]
     fn test_create_rdp_file_generates_correct_path() {
+        let env = EnvGuard::lock();
         let host = create_test_host("server01.domain.com", "Test Server");
         let username = "testuser";
         let domain = "TESTDOMAIN";
 
         // Set APPDATA for test
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
 
-        let result = create_rdp_file(&host, username, domain);
+        let result = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain));
 
         assert!(result.is_ok());
         let rdp_path = result.expect("RDP file path should be created");
@@ -422,16 +1666,38 @@ mod tests {
         assert!(connections_dir.exists());
     }
 
+    #[test]
+    fn test_recorded_rdp_file_path_records_nothing() {
+        let env = EnvGuard::lock();
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        env.set("APPDATA", temp_dir.path());
+        let map_path = temp_dir
+            .path()
+            .join("QuickConnect")
+            .join("Connections")
+            .join(crate::core::rdp_file_names::RDP_FILE_MAP_NAME);
+
+        let looked_up = recorded_rdp_file_path("server01.domain.com").expect("Path should resolve");
+        assert!(looked_up.ends_with("server01.domain.com.rdp"));
+        assert!(!map_path.exists(), "a lookup must not record a file name");
+
+        let host = create_test_host("server01.domain.com", "Test Server");
+        let created = create_rdp_file(&host, &RdpFileOptions::new()).expect("RDP file should be created");
+        assert_eq!(created, looked_up);
+        assert!(map_path.exists());
+    }
+
     #[test]
     fn test_create_rdp_file_writes_valid_content() {
+        let env = EnvGuard::lock();
         let host = create_test_host("testserver.domain.com", "Test Server");
         let username = "testuser";
         let domain = "TESTDOMAIN";
 
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
 
-        let rdp_path = create_rdp_file(&host, username, domain).expect("RDP file should be created");
+        let rdp_path = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain)).expect("RDP file should be created");
 
         assert!(rdp_path.exists());
 
@@ -448,14 +1714,15 @@ mod tests {
 
     #[test]
     fn test_create_rdp_file_without_domain() {
+        let env = EnvGuard::lock();
         let host = create_test_host("server.local", "Local Server");
         let username = "localuser";
         let domain = ""; // No domain
 
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
 
-        let rdp_path = create_rdp_file(&host, username, domain).expect("RDP file should be created");
+        let rdp_path = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain)).expect("RDP file should be created");
 
         let content = fs::read_to_string(&rdp_path).expect("RDP file should be readable");
         
@@ -466,6 +1733,7 @@ mod tests {
 
     #[test]
     fn test_create_rdp_file_creates_directory_if_not_exists() {
+        let env = EnvGuard::lock();
         let host = create_test_host("newserver.domain.com", "New Server");
         let username = "user";
         let domain = "DOMAIN";
@@ -477,9 +1745,9 @@ mod tests {
         assert!(!connections_dir.exists());
         
         // Set APPDATA after capturing the path
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
 
-        let result = create_rdp_file(&host, username, domain);
+        let result = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain));
         
         assert!(result.is_ok());
         // Verify directory was created
@@ -488,20 +1756,21 @@ mod tests {
 
     #[test]
     fn test_create_rdp_file_overwrites_existing_file() {
+        let env = EnvGuard::lock();
         let host = create_test_host("server.domain.com", "Server");
         let username = "user1";
         let domain = "DOMAIN";
 
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
 
         // Create first file
-        let rdp_path1 = create_rdp_file(&host, username, domain).expect("First RDP file should be created");
+        let rdp_path1 = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain)).expect("First RDP file should be created");
         let content1 = fs::read_to_string(&rdp_path1).expect("First file should be readable");
 
         // Create second file with different username (same temp_dir, so APPDATA is still set)
         let username2 = "user2";
-        let rdp_path2 = create_rdp_file(&host, username2, domain).expect("Second RDP file should be created");
+        let rdp_path2 = create_rdp_file(&host, &RdpFileOptions::new().username(username2).domain(domain)).expect("Second RDP file should be created");
         let content2 = fs::read_to_string(&rdp_path2).expect("Second file should be readable");
 
         // Filenames should be the same (same hostname)
@@ -515,14 +1784,15 @@ mod tests {
 
     #[test]
     fn test_create_rdp_file_handles_special_characters_in_hostname() {
+        let env = EnvGuard::lock();
         let host = create_test_host("server-01_test.domain.com", "Special Server");
         let username = "testuser";
         let domain = "DOMAIN";
 
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
 
-        let result = create_rdp_file(&host, username, domain);
+        let result = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain));
         
         assert!(result.is_ok());
         let rdp_path = result.expect("RDP path should exist");
@@ -532,23 +1802,28 @@ mod tests {
     }
 
     #[test]
-    fn test_create_rdp_file_returns_error_when_appdata_missing() {
+    fn test_create_rdp_file_falls_back_when_appdata_missing() {
+        let env = EnvGuard::lock();
         let host = create_test_host("server.domain.com", "Server");
         let username = "user";
         let domain = "DOMAIN";
 
-        // Remove APPDATA environment variable
-        std::env::remove_var("APPDATA");
-
-        let result = create_rdp_file(&host, username, domain);
-        
-        assert!(result.is_err());
-        match result {
-            Err(AppError::IoError { path, .. }) => {
-                assert!(path.contains("APPDATA"));
-            }
-            _ => panic!("Expected IoError for missing APPDATA"),
-        }
+        // Remove APPDATA; LOCALAPPDATA is the next location tried
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        env.remove("APPDATA");
+        env.set("LOCALAPPDATA", temp_dir.path());
+
+        let result = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain));
+
+        let rdp_path = result.expect("RDP file should be created in the fallback location");
+        assert_eq!(
+            rdp_path,
+            temp_dir
+                .path()
+                .join("QuickConnect")
+                .join("Connections")
+                .join("server.domain.com.rdp")
+        );
     }
 
     #[tokio::test]
@@ -608,6 +1883,90 @@ mod tests {
         }
     }
 
+    /// Runs `explain_credential_resolution` against credentials in a mock manager
+    async fn explain_with(manager: &MockCredentialManager, host: &Host) -> CredentialResolution {
+        let read = |target: String| {
+            manager
+                .read(&target)
+                .map(|found| found.map(|(username, password)| StoredCredentials { username, password }))
+        };
+
+        explain_credential_resolution(
+            host,
+            |hostname| {
+                let result = read(format!("TERMSRV/{}", hostname));
+                async move { result }
+            },
+            || {
+                let result = read("QuickConnect".to_string());
+                async move { result }
+            },
+        )
+        .await
+        .expect("Resolution should succeed")
+    }
+
+    #[tokio::test]
+    async fn test_explain_credential_resolution_per_host() {
+        let manager = MockCredentialManager::new();
+        manager.save("TERMSRV/server.domain.com", "CONTOSO\\host_user", "secret").expect("Save");
+        manager.save("QuickConnect", "global_user@contoso.com", "other").expect("Save");
+
+        let resolution = explain_with(&manager, &create_test_host("server.domain.com", "Server")).await;
+
+        assert_eq!(resolution.source, CredentialSource::PerHost);
+        assert_eq!(resolution.target.as_deref(), Some("TERMSRV/server.domain.com"));
+        assert_eq!(resolution.domain, "CONTOSO");
+        assert_eq!(resolution.username, "host_user");
+        assert_eq!(resolution.password_length, 6);
+        assert!(!resolution.username_overridden);
+    }
+
+    #[tokio::test]
+    async fn test_explain_credential_resolution_global_fallback() {
+        let manager = MockCredentialManager::new();
+        manager.save("QuickConnect", "global_user@contoso.com", "password1").expect("Save");
+
+        let resolution = explain_with(&manager, &create_test_host("server.domain.com", "Server")).await;
+
+        assert_eq!(resolution.source, CredentialSource::Global);
+        assert_eq!(resolution.target.as_deref(), Some("QuickConnect"));
+        assert_eq!(resolution.domain, "contoso.com");
+        assert_eq!(resolution.username, "global_user");
+        assert_eq!(resolution.password_length, 9);
+    }
+
+    #[tokio::test]
+    async fn test_explain_credential_resolution_applies_connect_username() {
+        let manager = MockCredentialManager::new();
+        manager.save("QuickConnect", "CONTOSO\\global_user", "password1").expect("Save");
+        let mut host = create_test_host("server.domain.com", "Server");
+        host.connect_username = Some("FABRIKAM\\admin".to_string());
+
+        let resolution = explain_with(&manager, &host).await;
+
+        assert_eq!(resolution.source, CredentialSource::Global);
+        assert_eq!(resolution.domain, "FABRIKAM");
+        assert_eq!(resolution.username, "admin");
+        assert!(resolution.username_overridden);
+    }
+
+    #[tokio::test]
+    async fn test_explain_credential_resolution_none_found() {
+        let manager = MockCredentialManager::new();
+
+        let resolution = explain_with(&manager, &create_test_host("server.domain.com", "Server")).await;
+
+        assert_eq!(resolution.source, CredentialSource::None);
+        assert_eq!(resolution.target, None);
+        assert!(resolution.username.is_empty());
+        assert_eq!(resolution.password_length, 0);
+
+        let json = serde_json::to_string(&resolution).expect("Serialize");
+        assert!(json.contains("\"source\":\"none\""));
+        assert!(!json.contains("password\""));
+    }
+
     #[tokio::test]
     async fn test_ensure_termsrv_credentials_saves_with_domain() {
         let _host = create_test_host("server.domain.com", "Server");
@@ -642,6 +2001,192 @@ mod tests {
         assert_eq!(termsrv_username, "localuser");
     }
 
+    /// Mock manager holding a saved TERMSRV credential for `hostname`
+    fn manager_with_termsrv(hostname: &str) -> MockCredentialManager {
+        let manager = MockCredentialManager::new();
+        manager
+            .save_with_persistence(
+                &format!("TERMSRV/{}", hostname),
+                "DOMAIN\\user",
+                "secret",
+                CredentialType::DomainPassword,
+                CredentialPersistence::LocalMachine,
+            )
+            .expect("mock save should succeed");
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_launch_existing_rdp_file_skips_credential_save() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let rdp_path = temp_dir.path().join("server.domain.com.rdp");
+        fs::write(&rdp_path, "full address:s:server.domain.com\r\n").unwrap();
+        let manager = manager_with_termsrv("server.domain.com");
+        let saves_before = manager.saves().len();
+        let mut launched_path = None;
+
+        let launched = launch_existing_rdp_file(&manager, "server.domain.com", &rdp_path, |path| {
+            launched_path = Some(path);
+            std::future::ready(Ok(()))
+        })
+        .await
+        .expect("Fast path should succeed");
+
+        assert!(launched);
+        assert_eq!(launched_path, Some(rdp_path.clone()));
+        assert_eq!(manager.saves().len(), saves_before);
+        // The saved file is launched untouched
+        assert_eq!(fs::read_to_string(&rdp_path).unwrap(), "full address:s:server.domain.com\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_launch_existing_rdp_file_falls_back_when_file_missing() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let rdp_path = temp_dir.path().join("server.domain.com.rdp");
+        let manager = manager_with_termsrv("server.domain.com");
+        let mut launches = 0;
+
+        let launched = launch_existing_rdp_file(&manager, "server.domain.com", &rdp_path, |_| {
+            launches += 1;
+            std::future::ready(Ok(()))
+        })
+        .await
+        .expect("Missing file should not be an error");
+
+        assert!(!launched);
+        assert_eq!(launches, 0);
+    }
+
+    #[tokio::test]
+    async fn test_launch_existing_rdp_file_falls_back_when_credential_missing() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let rdp_path = temp_dir.path().join("server.domain.com.rdp");
+        fs::write(&rdp_path, "full address:s:server.domain.com\r\n").unwrap();
+        let manager = manager_with_termsrv("other.domain.com");
+        let mut launches = 0;
+
+        let launched = launch_existing_rdp_file(&manager, "server.domain.com", &rdp_path, |_| {
+            launches += 1;
+            std::future::ready(Ok(()))
+        })
+        .await
+        .expect("Missing credential should not be an error");
+
+        assert!(!launched);
+        assert_eq!(launches, 0);
+        assert_eq!(manager.saves().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_launch_existing_rdp_file_reports_launch_failure() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let rdp_path = temp_dir.path().join("server.domain.com.rdp");
+        fs::write(&rdp_path, "full address:s:server.domain.com\r\n").unwrap();
+        let manager = manager_with_termsrv("server.domain.com");
+
+        let result = launch_existing_rdp_file::<_, _, _, ()>(&manager, "server.domain.com", &rdp_path, |_| {
+            std::future::ready(Err(AppError::RdpLaunchError {
+                source: std::io::Error::other("mstsc.exe exited with code 1"),
+            }))
+        })
+        .await;
+
+        assert!(matches!(result, Err(AppError::RdpLaunchError { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_launch_with_events_reports_started_before_launch_then_completed() {
+        let log = std::cell::RefCell::new(Vec::new());
+        let on_event = |event: LaunchEvent| log.borrow_mut().push(event.event_name().to_string());
+
+        let result = launch_with_events("server.domain.com", &on_event, || {
+            log.borrow_mut().push("launch".to_string());
+            std::future::ready(Ok(()))
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            *log.borrow(),
+            vec!["rdp-launch-started", "launch", "rdp-launch-completed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_launch_with_events_reports_failure() {
+        let events = std::cell::RefCell::new(Vec::new());
+        let on_event = |event: LaunchEvent| events.borrow_mut().push(event);
+
+        let result = launch_with_events::<_, _, _, ()>("server.domain.com", &on_event, || {
+            std::future::ready(Err(AppError::RdpLaunchError {
+                source: std::io::Error::other("mstsc.exe not found"),
+            }))
+        })
+        .await;
+
+        assert!(matches!(result, Err(AppError::RdpLaunchError { .. })));
+        let events = events.borrow();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            LaunchEvent::Started {
+                hostname: "server.domain.com".to_string()
+            }
+        );
+        match &events[1] {
+            LaunchEvent::Failed { hostname, error } => {
+                assert_eq!(hostname, "server.domain.com");
+                assert!(error.contains("mstsc.exe not found"));
+            }
+            other => panic!("Expected a failed event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_launch_with_events_on_stable_fast_path() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let rdp_path = temp_dir.path().join("server.domain.com.rdp");
+        fs::write(&rdp_path, "full address:s:server.domain.com\r\n").unwrap();
+        let manager = manager_with_termsrv("server.domain.com");
+        let log = std::cell::RefCell::new(Vec::new());
+        let on_event = |event: LaunchEvent| log.borrow_mut().push(event.event_name().to_string());
+
+        let launched = launch_existing_rdp_file(&manager, "server.domain.com", &rdp_path, |_| {
+            launch_with_events("server.domain.com", &on_event, || {
+                log.borrow_mut().push("launch".to_string());
+                std::future::ready(Ok(()))
+            })
+        })
+        .await
+        .expect("Fast path should succeed");
+
+        assert!(launched);
+        assert_eq!(
+            *log.borrow(),
+            vec!["rdp-launch-started", "launch", "rdp-launch-completed"]
+        );
+    }
+
+    #[test]
+    fn test_launch_event_payloads() {
+        let started = LaunchEvent::Started {
+            hostname: "server.domain.com".to_string(),
+        };
+        let failed = LaunchEvent::Failed {
+            hostname: "server.domain.com".to_string(),
+            error: "boom".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&started).unwrap(),
+            serde_json::json!({ "hostname": "server.domain.com" })
+        );
+        assert_eq!(
+            serde_json::to_value(&failed).unwrap(),
+            serde_json::json!({ "hostname": "server.domain.com", "error": "boom" })
+        );
+    }
+
     #[test]
     fn test_rdp_launch_result_contains_correct_fields() {
         let rdp_path = PathBuf::from("C:\\Users\\Test\\AppData\\Roaming\\QuickConnect\\Connections\\server.rdp");
@@ -658,18 +2203,19 @@ mod tests {
 
     #[test]
     fn test_create_rdp_file_content_matches_rdp_module() {
+        let env = EnvGuard::lock();
         let host = create_test_host("testserver.com", "Test");
         let username = "user";
         let domain = "DOMAIN";
 
         // Generate content using both paths
-        let direct_content = generate_rdp_content(&host, username, domain);
+        let direct_content = generate_rdp_content(&host, &RdpFileOptions::new().username(username).domain(domain));
         
         // Create file and read content
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
         
-        let rdp_path = create_rdp_file(&host, username, domain).expect("RDP file should be created");
+        let rdp_path = create_rdp_file(&host, &RdpFileOptions::new().username(username).domain(domain)).expect("RDP file should be created");
         let file_content = fs::read_to_string(&rdp_path).expect("RDP file should be readable");
 
         // Both should match
@@ -678,16 +2224,17 @@ mod tests {
 
     #[test]
     fn test_multiple_rdp_files_can_coexist() {
+        let env = EnvGuard::lock();
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
-        std::env::set_var("APPDATA", temp_dir.path());
+        env.set("APPDATA", temp_dir.path());
 
         let host1 = create_test_host("server01.domain.com", "Server 1");
         let host2 = create_test_host("server02.domain.com", "Server 2");
         let host3 = create_test_host("server03.domain.com", "Server 3");
 
-        let rdp_path1 = create_rdp_file(&host1, "user", "DOMAIN").expect("File 1 should be created");
-        let rdp_path2 = create_rdp_file(&host2, "user", "DOMAIN").expect("File 2 should be created");
-        let rdp_path3 = create_rdp_file(&host3, "user", "DOMAIN").expect("File 3 should be created");
+        let rdp_path1 = create_rdp_file(&host1, &RdpFileOptions::new().username("user").domain("DOMAIN")).expect("File 1 should be created");
+        let rdp_path2 = create_rdp_file(&host2, &RdpFileOptions::new().username("user").domain("DOMAIN")).expect("File 2 should be created");
+        let rdp_path3 = create_rdp_file(&host3, &RdpFileOptions::new().username("user").domain("DOMAIN")).expect("File 3 should be created");
 
         // All files should exist
         assert!(rdp_path1.exists());
@@ -699,4 +2246,906 @@ mod tests {
         assert_ne!(rdp_path2.file_name(), rdp_path3.file_name());
         assert_ne!(rdp_path1.file_name(), rdp_path3.file_name());
     }
+
+    #[test]
+    fn test_resolve_connect_username_uses_override() {
+        let mut host = create_test_host("server01.contoso.com", "Test Server");
+        host.connect_username = Some(".\\localadmin".to_string());
+        let credentials = StoredCredentials {
+            username: "CONTOSO\\admin".to_string(),
+            password: "password".to_string(),
+        };
+
+        let (domain, username) = resolve_connect_username(&host, &credentials);
+
+        assert_eq!(domain, ".");
+        assert_eq!(username, "localadmin");
+    }
+
+    #[test]
+    fn test_resolve_connect_username_falls_back_to_credentials() {
+        let credentials = StoredCredentials {
+            username: "CONTOSO\\admin".to_string(),
+            password: "password".to_string(),
+        };
+
+        let mut host = create_test_host("server01.contoso.com", "Test Server");
+        assert_eq!(
+            resolve_connect_username(&host, &credentials),
+            ("CONTOSO".to_string(), "admin".to_string())
+        );
+
+        // A blank override is treated as absent
+        host.connect_username = Some("  ".to_string());
+        assert_eq!(
+            resolve_connect_username(&host, &credentials),
+            ("CONTOSO".to_string(), "admin".to_string())
+        );
+    }
+
+    #[test]
+    fn test_connect_username_override_appears_in_rdp_content() {
+        let env = EnvGuard::lock();
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        env.set("APPDATA", temp_dir.path());
+        let mut host = create_test_host("server01.contoso.com", "Test Server");
+        host.connect_username = Some("FABRIKAM\\operator".to_string());
+        let credentials = StoredCredentials {
+            username: "CONTOSO\\admin".to_string(),
+            password: "password".to_string(),
+        };
+
+        let (domain, username) = resolve_connect_username(&host, &credentials);
+        let rdp_path = create_rdp_file(&host, &RdpFileOptions::new().username(&username).domain(&domain))
+            .expect("RDP file should be created");
+        let content = std::fs::read_to_string(&rdp_path).expect("Should read RDP file");
+
+        assert!(content.contains("username:s:operator\r\n"));
+        assert!(content.contains("domain:s:FABRIKAM\r\n"));
+        assert!(!content.contains("username:s:admin"));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_termsrv_credentials_skips_save_when_persistence_disabled() {
+        let manager = MockCredentialManager::new();
+        let host = create_test_host("server01.contoso.com", "Test Server");
+        let credentials = create_test_credentials("CONTOSO\\admin", "password");
+
+        ensure_termsrv_credentials(&manager, &host, &credentials, "CONTOSO", "admin", None)
+            .await
+            .expect("Skipping the save should succeed");
+
+        assert!(manager.saves().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ensure_termsrv_credentials_saves_domain_credential_by_default() {
+        let manager = MockCredentialManager::new();
+        let host = create_test_host("server01.contoso.com", "Test Server");
+        let credentials = create_test_credentials("CONTOSO\\admin", "password");
+        let persistence = settings::AppSettings::default().termsrv_persistence();
+
+        ensure_termsrv_credentials(&manager, &host, &credentials, "CONTOSO", "admin", persistence)
+            .await
+            .expect("Saving should succeed");
+
+        let saves = manager.saves();
+        assert_eq!(saves.len(), 1);
+        assert_eq!(saves[0].target, "TERMSRV/server01.contoso.com");
+        assert_eq!(saves[0].username, "CONTOSO\\admin");
+        assert_eq!(saves[0].cred_type, CredentialType::DomainPassword);
+        assert_eq!(saves[0].persistence, CredentialPersistence::LocalMachine);
+    }
+
+    #[tokio::test]
+    async fn test_ensure_termsrv_credentials_uses_session_persistence() {
+        let manager = MockCredentialManager::new();
+        let host = create_test_host("server01.contoso.com", "Test Server");
+        let credentials = create_test_credentials("admin", "password");
+
+        ensure_termsrv_credentials(
+            &manager,
+            &host,
+            &credentials,
+            "",
+            "admin",
+            Some(CredentialPersistence::Session),
+        )
+        .await
+        .expect("Saving should succeed");
+
+        let saves = manager.saves();
+        assert_eq!(saves.len(), 1);
+        assert_eq!(saves[0].username, "admin");
+        assert_eq!(saves[0].persistence, CredentialPersistence::Session);
+    }
+
+    #[tokio::test]
+    async fn test_ensure_termsrv_credentials_keeps_existing_credential() {
+        let manager = MockCredentialManager::new();
+        manager
+            .save_typed(
+                "TERMSRV/server01.contoso.com",
+                "CONTOSO\\other",
+                "secret",
+                CredentialType::DomainPassword,
+            )
+            .expect("Seeding the mock should succeed");
+        let host = create_test_host("server01.contoso.com", "Test Server");
+        let credentials = create_test_credentials("CONTOSO\\admin", "password");
+
+        ensure_termsrv_credentials(
+            &manager,
+            &host,
+            &credentials,
+            "CONTOSO",
+            "admin",
+            Some(CredentialPersistence::LocalMachine),
+        )
+        .await
+        .expect("Existing credential should be accepted");
+
+        // Only the seeding save was recorded
+        assert_eq!(manager.saves().len(), 1);
+    }
+
+    /// Mock manager holding separate credentials for the gateway `gateway`
+    fn manager_with_gateway_credentials(gateway: &str) -> MockCredentialManager {
+        let manager = MockCredentialManager::new();
+        manager
+            .save(
+                &format!("{}{}", GATEWAY_CREDENTIAL_PREFIX, gateway),
+                "CONTOSO\\gwuser",
+                "gwsecret",
+            )
+            .expect("Seeding the mock should succeed");
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_gateway_with_distinct_credentials_saves_both_targets() {
+        let manager = manager_with_gateway_credentials("rdg.contoso.com");
+        let host = create_test_host("server01.contoso.com", "Test Server");
+        let credentials = create_test_credentials("CONTOSO\\admin", "password");
+        let persistence = Some(CredentialPersistence::LocalMachine);
+
+        ensure_termsrv_credentials(&manager, &host, &credentials, "CONTOSO", "admin", persistence)
+            .await
+            .expect("Saving session credentials should succeed");
+        let separate = ensure_gateway_credentials(&manager, "rdg.contoso.com", persistence)
+            .expect("Saving gateway credentials should succeed");
+
+        assert!(separate);
+        let domain_saves: Vec<(String, String)> = manager
+            .saves()
+            .into_iter()
+            .filter(|call| call.cred_type == CredentialType::DomainPassword)
+            .map(|call| (call.target, call.username))
+            .collect();
+        assert_eq!(
+            domain_saves,
+            vec![
+                ("TERMSRV/server01.contoso.com".to_string(), "CONTOSO\\admin".to_string()),
+                ("TERMSRV/rdg.contoso.com".to_string(), "CONTOSO\\gwuser".to_string()),
+            ]
+        );
+
+        // The .rdp tells mstsc to prompt for (and so look up) the gateway separately
+        let options = RdpOptions {
+            gateway_hostname: Some("rdg.contoso.com".to_string()),
+            ..Default::default()
+        };
+        let content = generate_rdp_content(
+            &host,
+            &RdpFileOptions::new()
+                .username("admin")
+                .domain("CONTOSO")
+                .options(options)
+                .separate_gateway_credentials(separate),
+        );
+        assert!(content.contains("gatewayhostname:s:rdg.contoso.com\r\n"));
+        assert!(content.contains("gatewaycredentialssource:i:0\r\n"));
+        assert!(content.contains("promptcredentialonce:i:0\r\n"));
+    }
+
+    #[test]
+    fn test_gateway_without_credentials_shares_session_credentials() {
+        let manager = MockCredentialManager::new();
+
+        let separate = ensure_gateway_credentials(
+            &manager,
+            "rdg.contoso.com",
+            Some(CredentialPersistence::LocalMachine),
+        )
+        .expect("Checking gateway credentials should succeed");
+
+        assert!(!separate);
+        assert!(manager.saves().is_empty());
+    }
+
+    #[test]
+    fn test_gateway_credentials_not_written_when_persistence_disabled() {
+        let manager = manager_with_gateway_credentials("rdg.contoso.com");
+
+        let separate = ensure_gateway_credentials(&manager, "rdg.contoso.com", None)
+            .expect("Checking gateway credentials should succeed");
+
+        assert!(separate);
+        // Only the seeding save was recorded
+        assert_eq!(manager.saves().len(), 1);
+    }
+
+    #[test]
+    fn test_gateway_credentials_keep_existing_domain_copy() {
+        let manager = manager_with_gateway_credentials("rdg.contoso.com");
+        manager
+            .save_typed(
+                "TERMSRV/rdg.contoso.com",
+                "CONTOSO\\gwuser",
+                "gwsecret",
+                CredentialType::DomainPassword,
+            )
+            .expect("Seeding the mock should succeed");
+
+        ensure_gateway_credentials(
+            &manager,
+            "rdg.contoso.com",
+            Some(CredentialPersistence::LocalMachine),
+        )
+        .expect("Existing credential should be accepted");
+
+        assert_eq!(manager.saves().len(), 2);
+    }
+
+    #[test]
+    fn test_gateway_credentials_stored_requires_gateway() {
+        let manager = manager_with_gateway_credentials("rdg.contoso.com");
+
+        assert!(!gateway_credentials_stored(&manager, &RdpOptions::default()).unwrap());
+
+        let blank = RdpOptions {
+            gateway_hostname: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert!(!gateway_credentials_stored(&manager, &blank).unwrap());
+
+        let configured = RdpOptions {
+            gateway_hostname: Some(" rdg.contoso.com ".to_string()),
+            ..Default::default()
+        };
+        assert!(gateway_credentials_stored(&manager, &configured).unwrap());
+    }
+
+    #[test]
+    fn test_should_regenerate_rdp_file_when_edits_not_preserved() {
+        assert!(should_regenerate_rdp_file(false, true));
+        assert!(should_regenerate_rdp_file(false, false));
+    }
+
+    #[test]
+    fn test_should_regenerate_rdp_file_when_missing() {
+        assert!(should_regenerate_rdp_file(true, false));
+    }
+
+    #[test]
+    fn test_should_reuse_existing_rdp_file_when_edits_preserved() {
+        assert!(!should_regenerate_rdp_file(true, true));
+    }
+
+    #[test]
+    fn test_create_rdp_file_writes_to_data_dir_override() {
+        let env = EnvGuard::lock();
+        let appdata_dir = TempDir::new().expect("Failed to create temp directory");
+        let data_dir = TempDir::new().expect("Failed to create temp directory");
+        env.set("APPDATA", appdata_dir.path());
+        env.set(crate::infra::paths::DATA_DIR_ENV_VAR, data_dir.path());
+
+        let host = create_test_host("server01.domain.com", "Test Server");
+        let result = create_rdp_file(&host, &RdpFileOptions::new().username("user").domain("DOMAIN"));
+
+        let rdp_path = result.expect("RDP file should be created");
+        assert_eq!(
+            rdp_path,
+            data_dir.path().join("Connections").join("server01.domain.com.rdp")
+        );
+        assert!(rdp_path.exists());
+        assert!(!appdata_dir.path().join("QuickConnect").join("Connections").exists());
+    }
+
+    #[tokio::test]
+    async fn test_launch_hosts_staggered_launches_in_order_and_counts_successes() {
+        let hosts = vec![
+            create_test_host("web01.domain.com", "Web 1"),
+            create_test_host("bad.domain.com", "Broken"),
+            create_test_host("web02.domain.com", "Web 2"),
+        ];
+        let invoked = std::sync::Mutex::new(Vec::new());
+
+        let launched = launch_hosts_staggered(
+            hosts,
+            |host| {
+                invoked
+                    .lock()
+                    .expect("lock poisoned")
+                    .push(host.hostname.clone());
+                async move {
+                    if host.hostname == "bad.domain.com" {
+                        Err("mstsc failed".to_string())
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+            std::time::Duration::ZERO,
+        )
+        .await;
+
+        assert_eq!(launched, 2);
+        assert_eq!(
+            *invoked.lock().expect("lock poisoned"),
+            vec!["web01.domain.com", "bad.domain.com", "web02.domain.com"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_launch_hosts_staggered_empty() {
+        let launched =
+            launch_hosts_staggered(Vec::new(), |_| async { Ok(()) }, BATCH_LAUNCH_STAGGER).await;
+        assert_eq!(launched, 0);
+    }
+
+    /// Fake RDP client process that exits after a number of checks
+    #[derive(Debug)]
+    struct FakeProcess {
+        checks_until_exit: usize,
+        exit_code: Option<i32>,
+    }
+
+    impl LaunchedProcess for FakeProcess {
+        fn try_exit_code(&mut self) -> Option<i32> {
+            if self.checks_until_exit == 0 {
+                self.exit_code
+            } else {
+                self.checks_until_exit -= 1;
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_launch_mstsc_verify_reports_fast_failing_child() {
+        let result = launch_mstsc_with(Path::new("server.rdp"), LaunchMode::Normal, true, 1, Duration::ZERO, |_| {
+            Ok(FakeProcess {
+                checks_until_exit: 0,
+                exit_code: Some(1),
+            })
+        });
+
+        match result {
+            Err(AppError::RdpLaunchError { source }) => {
+                assert!(source.to_string().contains("code 1"));
+            }
+            _ => panic!("Expected RdpLaunchError for fast-failing mstsc"),
+        }
+    }
+
+    #[test]
+    fn test_launch_mstsc_without_verify_ignores_exit() {
+        let result = launch_mstsc_with(Path::new("server.rdp"), LaunchMode::Normal, false, 1, Duration::ZERO, |_| {
+            Ok(FakeProcess {
+                checks_until_exit: 0,
+                exit_code: Some(1),
+            })
+        });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_verify_launch_running_child_succeeds() {
+        let mut child = FakeProcess {
+            checks_until_exit: usize::MAX,
+            exit_code: None,
+        };
+
+        let result = verify_launch(&mut child, Duration::from_millis(30), Duration::from_millis(5));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_verify_launch_late_failure_within_window_is_reported() {
+        let mut child = FakeProcess {
+            checks_until_exit: 2,
+            exit_code: Some(2),
+        };
+
+        let result = verify_launch(&mut child, Duration::from_secs(5), Duration::from_millis(1));
+
+        assert!(matches!(result, Err(AppError::RdpLaunchError { .. })));
+    }
+
+    #[test]
+    fn test_verify_launch_clean_exit_succeeds() {
+        let mut child = FakeProcess {
+            checks_until_exit: 0,
+            exit_code: Some(0),
+        };
+
+        assert!(verify_launch(&mut child, Duration::from_secs(5), Duration::from_millis(1)).is_ok());
+    }
+
+    #[test]
+    fn test_launch_mstsc_spawn_failure_is_rdp_file_error() {
+        let result = launch_mstsc_with::<_, FakeProcess>(Path::new("server.rdp"), LaunchMode::Normal, true, 1, Duration::ZERO, |_| {
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "mstsc.exe not found"))
+        });
+
+        assert!(matches!(result, Err(AppError::RdpFileError { .. })));
+    }
+
+    #[test]
+    fn test_launch_mstsc_retries_transient_spawn_failure() {
+        let mut spawns = 0;
+        let result = launch_mstsc_with(Path::new("server.rdp"), LaunchMode::Normal, false, 2, Duration::ZERO, |_| {
+            spawns += 1;
+            if spawns == 1 {
+                Err(std::io::Error::new(std::io::ErrorKind::OutOfMemory, "Not enough resources"))
+            } else {
+                Ok(FakeProcess {
+                    checks_until_exit: usize::MAX,
+                    exit_code: None,
+                })
+            }
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(spawns, 2);
+    }
+
+    #[test]
+    fn test_launch_mstsc_does_not_retry_missing_executable() {
+        let mut spawns = 0;
+        let result = launch_mstsc_with::<_, FakeProcess>(Path::new("server.rdp"), LaunchMode::Normal, false, 3, Duration::ZERO, |_| {
+            spawns += 1;
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "mstsc.exe not found"))
+        });
+
+        assert!(matches!(result, Err(AppError::RdpFileError { .. })));
+        assert_eq!(spawns, 1);
+    }
+
+    #[test]
+    fn test_launch_mstsc_gives_up_after_spawn_attempts() {
+        let mut spawns = 0;
+        let result = launch_mstsc_with::<_, FakeProcess>(Path::new("server.rdp"), LaunchMode::Normal, false, 2, Duration::ZERO, |_| {
+            spawns += 1;
+            Err(std::io::Error::other("The paging file is too small"))
+        });
+
+        match result {
+            Err(AppError::RdpFileError { reason, .. }) => assert!(reason.contains("paging file")),
+            other => panic!("Expected RdpFileError, got {:?}", other),
+        }
+        assert_eq!(spawns, 2);
+    }
+
+    #[tokio::test]
+    async fn test_ephemeral_rdp_file_is_written_launched_and_deleted() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let host = create_test_host("secret01.domain.com", "Classified");
+        let file_options = RdpFileOptions::new().username("john.doe").domain("CONTOSO");
+        let mut launched = None;
+
+        let rdp_path = launch_ephemeral_rdp_file(temp_dir.path(), &host, &file_options, Duration::ZERO, |path| {
+            let content = std::fs::read_to_string(&path).expect("File should exist at launch");
+            assert!(content.contains("full address:s:secret01.domain.com"));
+            assert!(content.contains("username:s:john.doe"));
+            launched = Some(path);
+            std::future::ready(Ok(FakeProcess {
+                checks_until_exit: usize::MAX,
+                exit_code: None,
+            }))
+        })
+        .await
+        .expect("Launch should succeed");
+
+        assert_eq!(launched.as_ref(), Some(&rdp_path));
+        assert_eq!(rdp_path.parent(), Some(temp_dir.path()));
+        assert!(!rdp_path.to_string_lossy().contains("secret01"));
+        assert!(!rdp_path.exists(), "Temporary RDP file should be deleted");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_ephemeral_rdp_file_is_deleted_when_launch_fails() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let host = create_test_host("secret01.domain.com", "Classified");
+
+        let result = launch_ephemeral_rdp_file::<_, _, FakeProcess>(temp_dir.path(), &host, &RdpFileOptions::new(), Duration::ZERO, |_| {
+            std::future::ready(Err(AppError::RdpFileError {
+                hostname: "secret01.domain.com".to_string(),
+                reason: "Failed to launch mstsc.exe".to_string(),
+            }))
+        })
+        .await;
+
+        assert!(matches!(result, Err(AppError::RdpFileError { .. })));
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    /// Process that finishes starting up after a number of checks
+    struct StartingProcess {
+        checks_until_started: usize,
+        checks: usize,
+    }
+
+    impl LaunchedProcess for StartingProcess {
+        fn try_exit_code(&mut self) -> Option<i32> {
+            None
+        }
+
+        fn has_started(&mut self) -> bool {
+            self.checks += 1;
+            self.checks > self.checks_until_started
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ephemeral_rdp_file_is_kept_until_mstsc_has_started() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let host = create_test_host("secret01.domain.com", "Classified");
+        let mut process = StartingProcess {
+            checks_until_started: 2,
+            checks: 0,
+        };
+
+        let started = Instant::now();
+        assert!(wait_for_rdp_file_read(&mut process, Duration::from_secs(60)).await);
+        // Returns on the signal, not after the timeout
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(process.checks, 3);
+
+        let rdp_path = launch_ephemeral_rdp_file(temp_dir.path(), &host, &RdpFileOptions::new(), Duration::from_secs(60), |path| {
+            assert!(path.exists());
+            std::future::ready(Ok(StartingProcess {
+                checks_until_started: 1,
+                checks: 0,
+            }))
+        })
+        .await
+        .expect("Launch should succeed");
+        assert!(!rdp_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_rdp_file_read_ends_on_exit_or_timeout() {
+        let mut exited = FakeProcess {
+            checks_until_exit: 1,
+            exit_code: Some(1),
+        };
+        assert!(wait_for_rdp_file_read(&mut exited, Duration::from_secs(60)).await);
+
+        let mut silent = FakeProcess {
+            checks_until_exit: usize::MAX,
+            exit_code: None,
+        };
+        assert!(!wait_for_rdp_file_read(&mut silent, Duration::from_millis(10)).await);
+    }
+
+    #[test]
+    fn test_sweep_ephemeral_rdp_files_removes_stale_files_only() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let stale = temp_dir.path().join("QuickConnect-1-18c2f3a-0.rdp");
+        let own = temp_dir
+            .path()
+            .join(format!("QuickConnect-{}-18c2f3a-1.rdp", std::process::id()));
+        let other_app = temp_dir.path().join("Other-1.rdp");
+        let notes = temp_dir.path().join("QuickConnect-notes.txt");
+        for path in [&stale, &own, &other_app, &notes] {
+            std::fs::write(path, "full address:s:x").unwrap();
+        }
+
+        assert_eq!(sweep_ephemeral_rdp_files(temp_dir.path()), 1);
+        assert!(!stale.exists());
+        assert!(own.exists());
+        assert!(other_app.exists());
+        assert!(notes.exists());
+        assert_eq!(sweep_ephemeral_rdp_files(&temp_dir.path().join("missing")), 0);
+    }
+
+    #[test]
+    fn test_high_security_launch_leaves_connections_dir_untouched() {
+        let env = EnvGuard::lock();
+        let appdata = TempDir::new().expect("Failed to create temp directory");
+        env.set("APPDATA", appdata.path());
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let host = create_test_host("secret02.domain.com", "Classified");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("Failed to build runtime");
+        let launch = |settings: &settings::AppSettings| {
+            runtime.block_on(write_and_launch(
+                &host,
+                &RdpFileOptions::new(),
+                settings,
+                temp_dir.path(),
+                Duration::ZERO,
+                |path| {
+                    assert!(path.exists());
+                    std::future::ready(Ok(FakeProcess {
+                        checks_until_exit: usize::MAX,
+                        exit_code: None,
+                    }))
+                },
+            ))
+        };
+        let connections_dir = appdata.path().join("QuickConnect").join("Connections");
+        let rdp_files = || {
+            std::fs::read_dir(&connections_dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rdp"))
+                        .count()
+                })
+                .unwrap_or(0)
+        };
+
+        let high_security = settings::AppSettings {
+            high_security_mode: true,
+            ..Default::default()
+        };
+        let rdp_path = launch(&high_security).expect("High-security launch should succeed");
+        assert!(rdp_path.starts_with(temp_dir.path()));
+        assert!(!rdp_path.exists());
+        assert_eq!(rdp_files(), 0);
+
+        // The normal path does write to the Connections directory
+        let rdp_path = launch(&settings::AppSettings::default()).expect("Launch should succeed");
+        assert!(rdp_path.starts_with(&connections_dir));
+        assert_eq!(rdp_files(), 1);
+    }
+
+    #[test]
+    fn test_mstsc_spawn_config_normal() {
+        let config = mstsc_spawn_config(Path::new("C:\\Connections\\server.rdp"), LaunchMode::Normal);
+
+        assert_eq!(config.program, "mstsc.exe");
+        assert_eq!(config.args, vec!["C:\\Connections\\server.rdp"]);
+        assert_eq!(config.creation_flags, 0);
+    }
+
+    #[test]
+    fn test_mstsc_spawn_config_minimized() {
+        let config = mstsc_spawn_config(Path::new("C:\\Connections\\server.rdp"), LaunchMode::Minimized);
+
+        // mstsc itself is started, never a shell
+        assert_eq!(config.program, "mstsc.exe");
+        assert_eq!(config.args, vec!["C:\\Connections\\server.rdp"]);
+        assert!(config.minimized);
+        assert!(!mstsc_spawn_config(Path::new("server.rdp"), LaunchMode::Normal).minimized);
+    }
+
+    #[test]
+    fn test_mstsc_command_line_keeps_shell_characters_literal() {
+        let path = Path::new("C:\\Data & Co\\100%\\a^b.rdp");
+        let config = mstsc_spawn_config(path, LaunchMode::Minimized);
+
+        assert_eq!(config.command_line(), "mstsc.exe \"C:\\Data & Co\\100%\\a^b.rdp\"");
+        assert_eq!(
+            mstsc_spawn_config(Path::new("C:\\Connections\\server.rdp"), LaunchMode::Minimized).command_line(),
+            "mstsc.exe C:\\Connections\\server.rdp"
+        );
+    }
+
+    #[test]
+    fn test_quote_windows_arg() {
+        assert_eq!(quote_windows_arg("plain"), "plain");
+        assert_eq!(quote_windows_arg(""), "\"\"");
+        assert_eq!(quote_windows_arg("C:\\My Files\\"), "\"C:\\My Files\\\\\"");
+        assert_eq!(quote_windows_arg("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn test_launch_mstsc_passes_mode_config_to_launcher() {
+        let mut seen = None;
+        let result = launch_mstsc_with(Path::new("server.rdp"), LaunchMode::Minimized, false, 1, Duration::ZERO, |config| {
+            seen = Some(config.clone());
+            Ok(FakeProcess {
+                checks_until_exit: usize::MAX,
+                exit_code: None,
+            })
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(seen, Some(mstsc_spawn_config(Path::new("server.rdp"), LaunchMode::Minimized)));
+    }
+
+    #[test]
+    fn test_launch_mode_serialization() {
+        assert_eq!(serde_json::to_string(&LaunchMode::Minimized).expect("Serialize"), "\"minimized\"");
+        let mode: LaunchMode = serde_json::from_str("\"normal\"").expect("Deserialize");
+        assert_eq!(mode, LaunchMode::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_preview_rdp_content_matches_generated_content() {
+        let host = create_test_host("server01.domain.com", "Server");
+        let options = RdpOptions {
+            redirect_clipboard: Some(false),
+            ..Default::default()
+        };
+        let global_creds = Some(create_test_credentials("CONTOSO\\john.doe", "pass"));
+
+        let preview = preview_rdp_content(
+            &MockCredentialManager::new(),
+            &host,
+            &options,
+            |_hostname: String| async { Ok(None) },
+            || async move { Ok(global_creds) },
+        )
+        .await
+        .expect("Preview should succeed");
+
+        assert_eq!(preview, generate_rdp_content(&host, &RdpFileOptions::new().username("john.doe").domain("CONTOSO").options(options.clone())));
+    }
+
+    #[tokio::test]
+    async fn test_preview_rdp_content_uses_connect_username_override() {
+        let host = Host {
+            connect_username: Some(".\\localadmin".to_string()),
+            ..create_test_host("server01.domain.com", "Server")
+        };
+        let host_creds = Some(create_test_credentials("CONTOSO\\john.doe", "pass"));
+
+        let preview = preview_rdp_content(
+            &MockCredentialManager::new(),
+            &host,
+            &RdpOptions::default(),
+            |_hostname: String| async move { Ok(host_creds) },
+            || async { Ok(None) },
+        )
+        .await
+        .expect("Preview should succeed");
+
+        assert!(preview.contains("username:s:localadmin\r\n"));
+        assert!(preview.contains("domain:s:.\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_rdp_content_without_credentials_notes_empty_username() {
+        let host = create_test_host("server01.domain.com", "Server");
+
+        let preview = preview_rdp_content(
+            &MockCredentialManager::new(),
+            &host,
+            &RdpOptions::default(),
+            |_hostname: String| async { Ok(None) },
+            || async { Ok(None) },
+        )
+        .await
+        .expect("Preview without credentials should still succeed");
+
+        assert!(preview.starts_with(PREVIEW_NO_CREDENTIALS_NOTE));
+        assert!(preview.ends_with(&generate_rdp_content(&host, &RdpFileOptions::new())));
+        assert!(preview.contains("username:s:\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_rdp_content_propagates_credential_errors() {
+        let host = create_test_host("server01.domain.com", "Server");
+
+        let result = preview_rdp_content(
+            &MockCredentialManager::new(),
+            &host,
+            &RdpOptions::default(),
+            |_hostname: String| async {
+                Err(AppError::CredentialManagerError {
+                    operation: "read".to_string(),
+                    source: None,
+                })
+            },
+            || async { Ok(None) },
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::CredentialManagerError { .. })));
+    }
+
+    #[test]
+    fn test_preview_rdp_content_writes_nothing() {
+        let env = EnvGuard::lock();
+        let data_dir = TempDir::new().expect("Failed to create temp directory");
+        env.set(crate::infra::paths::DATA_DIR_ENV_VAR, data_dir.path());
+
+        // Credentials and a gateway with its own credentials: a launch would
+        // save TERMSRV/{host}, copy the gateway credential and write the file
+        let manager = manager_with_gateway_credentials("rdg.contoso.com");
+        let seeded = manager.saves().len();
+        let host = create_test_host("server01.contoso.com", "Server");
+        let options = RdpOptions {
+            gateway_hostname: Some("rdg.contoso.com".to_string()),
+            ..Default::default()
+        };
+        let global_creds = Some(create_test_credentials("CONTOSO\\admin", "pass"));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("Failed to build runtime");
+        let preview = runtime
+            .block_on(preview_rdp_content(
+                &manager,
+                &host,
+                &options,
+                |_hostname: String| async { Ok(None) },
+                || async move { Ok(global_creds) },
+            ))
+            .expect("Preview should succeed");
+
+        // The full path ran: credentials resolved and the gateway lookup found its credentials
+        assert!(preview.contains("username:s:admin\r\n"));
+        assert!(preview.contains("gatewaycredentialssource:i:0\r\n"));
+        assert_eq!(manager.saves().len(), seeded, "preview must not save credentials");
+        let entries = fs::read_dir(data_dir.path())
+            .expect("Failed to read data dir")
+            .count();
+        assert_eq!(entries, 0, "preview must not create files");
+    }
+
+    #[tokio::test]
+    async fn test_validate_generated_rdp_accepts_normal_host() {
+        let host = create_test_host("server01.domain.com", "Server");
+        let global_creds = Some(create_test_credentials("CONTOSO\\john.doe", "pass"));
+
+        let validation = validate_generated_rdp(
+            &MockCredentialManager::new(),
+            &host,
+            &RdpOptions::default(),
+            |_hostname: String| async { Ok(None) },
+            || async move { Ok(global_creds) },
+        )
+        .await
+        .expect("Validation should run");
+
+        assert!(validation.valid, "{:?}", validation.problems);
+        assert_eq!(validation.address.as_deref(), Some("server01.domain.com"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_generated_rdp_reports_empty_address() {
+        let host = create_test_host("", "Server");
+
+        let validation = validate_generated_rdp(
+            &MockCredentialManager::new(),
+            &host,
+            &RdpOptions::default(),
+            |_hostname: String| async { Ok(None) },
+            || async { Ok(None) },
+        )
+        .await
+        .expect("Validation should run");
+
+        assert!(!validation.valid);
+        assert_eq!(validation.problems, vec!["'full address' is empty"]);
+    }
+
+    #[tokio::test]
+    async fn test_validate_generated_rdp_reports_injected_option() {
+        // A hand-edited hosts file can carry a line break in the hostname
+        let host = create_test_host("server01.domain.com\r\nfull address:s:other.domain.com", "Server");
+
+        let validation = validate_generated_rdp(
+            &MockCredentialManager::new(),
+            &host,
+            &RdpOptions::default(),
+            |_hostname: String| async { Ok(None) },
+            || async { Ok(None) },
+        )
+        .await
+        .expect("Validation should run");
+
+        assert!(!validation.valid);
+        assert_eq!(validation.problems, vec!["'full address' is set more than once"]);
+    }
 }
@@ -0,0 +1,396 @@
+//! RDP security negotiation probing
+//!
+//! Sends the first packet of an RDP connection (an X.224 Connection Request
+//! carrying an RDP Negotiation Request, MS-RDPBCGR 2.2.1.1) and reads the
+//! server's Connection Confirm to learn which security protocols it accepts,
+//! without going on to TLS or authentication.
+//!
+//! # Why this exists
+//! Admins want to know whether a host requires NLA before saving connection
+//! settings for it; a plain port probe can't tell.
+//!
+//! # Why separate
+//! Building the request and parsing the response are pure functions over
+//! bytes, so they can be unit tested against captured handshakes without
+//! touching the network.
+
+use crate::core::hosts::split_host_port;
+use crate::core::status::{connect_from, rdp_port_of};
+use crate::AppError;
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+
+/// Standard RDP security (no TLS)
+pub const PROTOCOL_RDP: u32 = 0x0000_0000;
+/// TLS
+pub const PROTOCOL_SSL: u32 = 0x0000_0001;
+/// CredSSP (Network Level Authentication)
+pub const PROTOCOL_HYBRID: u32 = 0x0000_0002;
+/// CredSSP with the Early User Authorization Result PDU
+pub const PROTOCOL_HYBRID_EX: u32 = 0x0000_0008;
+
+/// Failure code sent when the server only accepts CredSSP
+pub const HYBRID_REQUIRED_BY_SERVER: u32 = 0x0000_0005;
+
+const TPKT_VERSION: u8 = 0x03;
+const X224_CONNECTION_REQUEST: u8 = 0xE0;
+const X224_CONNECTION_CONFIRM: u8 = 0xD0;
+const TYPE_RDP_NEG_REQ: u8 = 0x01;
+const TYPE_RDP_NEG_RSP: u8 = 0x02;
+const TYPE_RDP_NEG_FAILURE: u8 = 0x03;
+
+/// Time allowed for connecting and for each read and write
+pub const NEGOTIATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest Connection Confirm accepted; real ones are 11 or 19 bytes
+const MAX_RESPONSE_LENGTH: usize = 256;
+
+/// How the server answered one negotiation request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NegotiationResponse {
+    /// The server picked this protocol from the requested ones
+    Selected(u32),
+    /// The server refused every requested protocol, with this failure code
+    Failure(u32),
+    /// No negotiation data: the server predates negotiation and only does
+    /// standard RDP security
+    Legacy,
+}
+
+/// Security protocols a host accepts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RdpSecurityInfo {
+    pub hostname: String,
+    pub port: u16,
+    /// Standard RDP security is accepted
+    pub standard_rdp: bool,
+    /// TLS without NLA is accepted
+    pub tls: bool,
+    /// CredSSP (NLA) is accepted
+    pub credssp: bool,
+    /// CredSSP with early user authorization is accepted
+    pub credssp_early_auth: bool,
+    /// NLA is the only way in: the server offered CredSSP and explicitly
+    /// refused TLS alone
+    pub nla_required: bool,
+}
+
+/// Builds a Connection Request asking for the `requested` protocols
+pub fn build_negotiation_request(requested: u32) -> Vec<u8> {
+    let mut packet = vec![
+        // TPKT header: version, reserved, total length (big-endian)
+        TPKT_VERSION,
+        0x00,
+        0x00,
+        19,
+        // X.224 Connection Request: length indicator, code, dst-ref, src-ref, class
+        14,
+        X224_CONNECTION_REQUEST,
+        0x00,
+        0x00,
+        0x00,
+        0x00,
+        0x00,
+        // RDP Negotiation Request: type, flags, length (little-endian)
+        TYPE_RDP_NEG_REQ,
+        0x00,
+        0x08,
+        0x00,
+    ];
+    packet.extend_from_slice(&requested.to_le_bytes());
+    packet
+}
+
+fn invalid_response(reason: &str) -> AppError {
+    AppError::Other {
+        message: format!("Invalid RDP negotiation response: {}", reason),
+        source: None,
+    }
+}
+
+/// Parses a Connection Confirm (TPKT header included)
+///
+/// # Returns
+/// * `Ok(NegotiationResponse)` - What the server selected or why it refused
+/// * `Err(AppError)` - The bytes aren't a Connection Confirm
+pub fn parse_negotiation_response(bytes: &[u8]) -> Result<NegotiationResponse, AppError> {
+    if bytes.len() < 11 {
+        return Err(invalid_response("too short"));
+    }
+    if bytes[0] != TPKT_VERSION {
+        return Err(invalid_response("not a TPKT packet"));
+    }
+    let length = u16::from_be_bytes([bytes[2], bytes[3]]) as usize;
+    if length != bytes.len() {
+        return Err(invalid_response("length doesn't match the TPKT header"));
+    }
+    if bytes[5] & 0xF0 != X224_CONNECTION_CONFIRM {
+        return Err(invalid_response("not an X.224 Connection Confirm"));
+    }
+
+    let negotiation = &bytes[11..];
+    if negotiation.is_empty() {
+        return Ok(NegotiationResponse::Legacy);
+    }
+    if negotiation.len() != 8 || u16::from_le_bytes([negotiation[2], negotiation[3]]) != 8 {
+        return Err(invalid_response("negotiation data isn't 8 bytes"));
+    }
+
+    let value = u32::from_le_bytes([negotiation[4], negotiation[5], negotiation[6], negotiation[7]]);
+    match negotiation[0] {
+        TYPE_RDP_NEG_RSP => Ok(NegotiationResponse::Selected(value)),
+        TYPE_RDP_NEG_FAILURE => Ok(NegotiationResponse::Failure(value)),
+        other => Err(invalid_response(&format!("unknown negotiation type {:#04x}", other))),
+    }
+}
+
+/// Combines the answers to the three probes `probe_rdp_security` makes
+///
+/// # Arguments
+/// * `all` - Answer when TLS, CredSSP and CredSSP with early auth were offered
+/// * `tls_only` - Answer when only TLS was offered (`None` if not asked or unreadable)
+/// * `rdp_only` - Answer when only standard RDP was offered (`None` likewise)
+pub fn summarize_negotiation(
+    hostname: &str,
+    port: u16,
+    all: NegotiationResponse,
+    tls_only: Option<NegotiationResponse>,
+    rdp_only: Option<NegotiationResponse>,
+) -> RdpSecurityInfo {
+    let mut info = RdpSecurityInfo {
+        hostname: hostname.to_string(),
+        port,
+        ..Default::default()
+    };
+
+    if all == NegotiationResponse::Legacy {
+        info.standard_rdp = true;
+        return info;
+    }
+
+    if let NegotiationResponse::Selected(protocol) = all {
+        info.credssp = protocol & (PROTOCOL_HYBRID | PROTOCOL_HYBRID_EX) != 0;
+        info.credssp_early_auth = protocol & PROTOCOL_HYBRID_EX != 0;
+        info.tls = protocol == PROTOCOL_SSL;
+    }
+    if let Some(NegotiationResponse::Selected(protocol)) = tls_only {
+        info.tls |= protocol == PROTOCOL_SSL;
+    }
+    info.standard_rdp = matches!(
+        rdp_only,
+        Some(NegotiationResponse::Selected(PROTOCOL_RDP) | NegotiationResponse::Legacy)
+    );
+    info.nla_required = info.credssp
+        && !info.tls
+        && !info.standard_rdp
+        && tls_only == Some(NegotiationResponse::Failure(HYBRID_REQUIRED_BY_SERVER));
+    info
+}
+
+/// Sends one negotiation request to `hostname` and reads the answer
+///
+/// `hostname` may carry a port (`server01:3390`); otherwise `RDP_PORT` is used.
+pub fn negotiate(hostname: &str, requested: u32, timeout: Duration) -> Result<NegotiationResponse, AppError> {
+    let (host, _) = split_host_port(hostname);
+    let port = rdp_port_of(hostname);
+    let io_error = |source: std::io::Error| AppError::IoError {
+        path: format!("{}:{}", host, port),
+        source,
+    };
+
+    let address = (host, port)
+        .to_socket_addrs()
+        .map_err(io_error)?
+        .next()
+        .ok_or_else(|| AppError::InvalidHostname {
+            hostname: hostname.to_string(),
+            reason: "Hostname did not resolve to any address".to_string(),
+        })?;
+
+    let mut stream = connect_from(address, None, timeout).map_err(io_error)?;
+    stream.set_read_timeout(Some(timeout)).map_err(io_error)?;
+    stream.set_write_timeout(Some(timeout)).map_err(io_error)?;
+    stream
+        .write_all(&build_negotiation_request(requested))
+        .map_err(io_error)?;
+
+    let mut response = vec![0u8; 4];
+    stream.read_exact(&mut response).map_err(io_error)?;
+    let length = u16::from_be_bytes([response[2], response[3]]) as usize;
+    if !(4..=MAX_RESPONSE_LENGTH).contains(&length) {
+        return Err(invalid_response("bad TPKT length"));
+    }
+    response.resize(length, 0);
+    stream.read_exact(&mut response[4..]).map_err(io_error)?;
+
+    parse_negotiation_response(&response)
+}
+
+/// Detects which security protocols `hostname` accepts
+///
+/// Makes up to three connections: one offering everything, then one offering
+/// only TLS and one offering only standard RDP. The first must succeed; the
+/// other two only refine the result, so failures there count as "not accepted".
+///
+/// # Returns
+/// * `Ok(RdpSecurityInfo)` - The accepted protocols
+/// * `Err(AppError)` - The host couldn't be reached or didn't answer as an RDP server
+pub fn probe_rdp_security(hostname: &str, timeout: Duration) -> Result<RdpSecurityInfo, AppError> {
+    let all = negotiate(
+        hostname,
+        PROTOCOL_SSL | PROTOCOL_HYBRID | PROTOCOL_HYBRID_EX,
+        timeout,
+    )?;
+
+    let (tls_only, rdp_only) = if all == NegotiationResponse::Legacy {
+        (None, None)
+    } else {
+        (
+            negotiate(hostname, PROTOCOL_SSL, timeout).ok(),
+            negotiate(hostname, PROTOCOL_RDP, timeout).ok(),
+        )
+    };
+
+    let (host, _) = split_host_port(hostname);
+    Ok(summarize_negotiation(host, rdp_port_of(hostname), all, tls_only, rdp_only))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Synthetic X.224 Connection Confirm whose RDP_NEG_RSP selects PROTOCOL_HYBRID (CredSSP)
+    const HYBRID_SELECTED: [u8; 19] = [
+        0x03, 0x00, 0x00, 0x13, 0x0E, 0xD0, 0x00, 0x00, 0x12, 0x34, 0x00, 0x02, 0x1F, 0x08, 0x00,
+        0x02, 0x00, 0x00, 0x00,
+    ];
+
+    fn confirm(neg_type: u8, value: u32) -> Vec<u8> {
+        let mut bytes = vec![0x03, 0x00, 0x00, 0x13, 0x0E, 0xD0, 0x00, 0x00, 0x12, 0x34, 0x00, neg_type, 0x00, 0x08, 0x00];
+        bytes.extend_from_slice(&value.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_build_negotiation_request() {
+        let request = build_negotiation_request(PROTOCOL_SSL | PROTOCOL_HYBRID | PROTOCOL_HYBRID_EX);
+
+        assert_eq!(
+            request,
+            vec![
+                0x03, 0x00, 0x00, 0x13, 0x0E, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x08,
+                0x00, 0x0B, 0x00, 0x00, 0x00
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_selected_protocol() {
+        assert_eq!(
+            parse_negotiation_response(&HYBRID_SELECTED).unwrap(),
+            NegotiationResponse::Selected(PROTOCOL_HYBRID)
+        );
+        assert_eq!(
+            parse_negotiation_response(&confirm(TYPE_RDP_NEG_RSP, PROTOCOL_SSL)).unwrap(),
+            NegotiationResponse::Selected(PROTOCOL_SSL)
+        );
+    }
+
+    #[test]
+    fn test_parse_failure_and_legacy() {
+        assert_eq!(
+            parse_negotiation_response(&confirm(TYPE_RDP_NEG_FAILURE, HYBRID_REQUIRED_BY_SERVER)).unwrap(),
+            NegotiationResponse::Failure(HYBRID_REQUIRED_BY_SERVER)
+        );
+
+        // Servers that predate negotiation send a bare Connection Confirm
+        let legacy = [0x03, 0x00, 0x00, 0x0B, 0x06, 0xD0, 0x00, 0x00, 0x12, 0x34, 0x00];
+        assert_eq!(parse_negotiation_response(&legacy).unwrap(), NegotiationResponse::Legacy);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_responses() {
+        assert!(parse_negotiation_response(&HYBRID_SELECTED[..10]).is_err());
+
+        let mut wrong_version = HYBRID_SELECTED;
+        wrong_version[0] = 0x02;
+        assert!(parse_negotiation_response(&wrong_version).is_err());
+
+        let mut wrong_length = HYBRID_SELECTED;
+        wrong_length[3] = 0x14;
+        assert!(parse_negotiation_response(&wrong_length).is_err());
+
+        // A Connection Request echoed back isn't a Connection Confirm
+        let mut request = HYBRID_SELECTED;
+        request[5] = X224_CONNECTION_REQUEST;
+        assert!(parse_negotiation_response(&request).is_err());
+
+        let mut unknown_type = HYBRID_SELECTED;
+        unknown_type[11] = 0x07;
+        assert!(parse_negotiation_response(&unknown_type).is_err());
+    }
+
+    #[test]
+    fn test_summarize_nla_required() {
+        let info = summarize_negotiation(
+            "server01.domain.com",
+            3389,
+            parse_negotiation_response(&HYBRID_SELECTED).unwrap(),
+            Some(NegotiationResponse::Failure(HYBRID_REQUIRED_BY_SERVER)),
+            Some(NegotiationResponse::Failure(HYBRID_REQUIRED_BY_SERVER)),
+        );
+
+        assert_eq!(
+            info,
+            RdpSecurityInfo {
+                hostname: "server01.domain.com".to_string(),
+                port: 3389,
+                standard_rdp: false,
+                tls: false,
+                credssp: true,
+                credssp_early_auth: false,
+                nla_required: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_summarize_nla_optional() {
+        let info = summarize_negotiation(
+            "server01.domain.com",
+            3390,
+            NegotiationResponse::Selected(PROTOCOL_HYBRID_EX),
+            Some(NegotiationResponse::Selected(PROTOCOL_SSL)),
+            Some(NegotiationResponse::Selected(PROTOCOL_RDP)),
+        );
+
+        assert!(info.credssp);
+        assert!(info.credssp_early_auth);
+        assert!(info.tls);
+        assert!(info.standard_rdp);
+        assert!(!info.nla_required);
+        assert_eq!(info.port, 3390);
+    }
+
+    #[test]
+    fn test_summarize_without_nla() {
+        // NLA turned off: the server picks TLS even though CredSSP was offered
+        let info = summarize_negotiation(
+            "server01.domain.com",
+            3389,
+            NegotiationResponse::Selected(PROTOCOL_SSL),
+            Some(NegotiationResponse::Selected(PROTOCOL_SSL)),
+            None,
+        );
+        assert!(info.tls);
+        assert!(!info.credssp);
+        assert!(!info.standard_rdp);
+        assert!(!info.nla_required);
+
+        let legacy = summarize_negotiation("old01", 3389, NegotiationResponse::Legacy, None, None);
+        assert!(legacy.standard_rdp);
+        assert!(!legacy.tls && !legacy.credssp && !legacy.nla_required);
+    }
+}
@@ -0,0 +1,312 @@
+//! Recent connections persistence
+//!
+//! Loads `recent_connections.json`, recovering from a corrupt file, and turns
+//! recent entries into saved hosts.
+//!
+//! # Why this exists
+//! The file can be truncated by a crash mid-write. The tray menu is built from
+//! it, so a parse error must not stop the menu from being built; the list is
+//! only a convenience and can start over.
+//!
+//! # Why separate
+//! Takes the file path as a parameter so recovery can be unit tested against
+//! temporary files.
+
+use crate::infra::debug_log;
+use crate::{AppError, Host, RecentConnection, RecentConnections};
+use std::path::{Path, PathBuf};
+
+/// Returns the path a corrupt recents file is moved to (`<file>.bak`).
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// Loads recent connections from the given file.
+///
+/// A file that can't be parsed is moved to `<file>.bak` (replacing any older
+/// backup) and an empty list is returned.
+///
+/// # Returns
+/// * `Ok(RecentConnections)` - Parsed connections (empty if the file is missing or corrupt)
+/// * `Err(AppError)` - Failed to read the file
+///
+/// # Side Effects
+/// - Renames a corrupt file to `<file>.bak`
+pub fn load_recent_connections_from(path: &Path) -> Result<RecentConnections, AppError> {
+    if !path.exists() {
+        return Ok(RecentConnections::new());
+    }
+
+    let json = std::fs::read_to_string(path).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+
+    match serde_json::from_str(&json) {
+        Ok(recent) => Ok(recent),
+        Err(parse_error) => {
+            let backup = backup_path(path);
+            let details = match std::fs::rename(path, &backup) {
+                Ok(()) => format!("{}; moved to {:?}", parse_error, backup),
+                Err(e) => format!("{}; backup to {:?} failed: {}", parse_error, backup, e),
+            };
+            debug_log(
+                "WARN",
+                "RECENT_CONNECTIONS",
+                &format!("Recent connections file {:?} is corrupt, starting empty", path),
+                Some(&details),
+            );
+            Ok(RecentConnections::new())
+        }
+    }
+}
+
+/// Builds a saved host from a recent connection
+///
+/// Used to keep a quick-connect target as a permanent host. The hostname is
+/// matched case-insensitively and kept as it appears in the recents list; the
+/// connection time becomes `last_connected`.
+///
+/// # Arguments
+/// * `description` - Description for the host (the recent entry's when empty)
+/// * `group` - Group for the host (blank means none)
+///
+/// # Returns
+/// * `Ok(Host)` - The host to upsert
+/// * `Err(AppError)` - The hostname isn't in the recents list
+pub fn host_from_recent(
+    recent: &RecentConnections,
+    hostname: &str,
+    description: &str,
+    group: Option<&str>,
+) -> Result<Host, AppError> {
+    let hostname = hostname.trim();
+    let entry = recent
+        .connections
+        .iter()
+        .find(|conn| conn.hostname.eq_ignore_ascii_case(hostname))
+        .ok_or_else(|| AppError::Other {
+            message: format!("'{}' is not in recent connections", hostname),
+            source: None,
+        })?;
+
+    let description = match description.trim() {
+        "" => entry.description.clone(),
+        description => description.to_string(),
+    };
+    let last_connected = chrono::DateTime::from_timestamp(entry.timestamp as i64, 0)
+        .map(|utc| {
+            utc.with_timezone(&chrono::Local)
+                .format("%d/%m/%Y %H:%M:%S")
+                .to_string()
+        });
+
+    Ok(Host {
+        hostname: entry.hostname.clone(),
+        description,
+        last_connected,
+        group: group
+            .map(str::trim)
+            .filter(|group| !group.is_empty())
+            .map(str::to_string),
+        ..Default::default()
+    })
+}
+
+/// Returns the recent connections made at or after `since`
+///
+/// # Arguments
+/// * `since` - Unix timestamp (seconds); entries with `timestamp >= since` are kept
+///
+/// # Returns
+/// Matching connections, newest first
+pub fn connections_since(recent: RecentConnections, since: u64) -> Vec<RecentConnection> {
+    let mut connections: Vec<RecentConnection> = recent
+        .connections
+        .into_iter()
+        .filter(|conn| conn.timestamp >= since)
+        .collect();
+
+    connections.sort_by_key(|conn| std::cmp::Reverse(conn.timestamp));
+    connections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::AppSettings;
+    use tempfile::TempDir;
+
+    fn write_recents(contents: &str) -> (TempDir, PathBuf) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("recent_connections.json");
+        std::fs::write(&path, contents).expect("Failed to write recents");
+        (temp_dir, path)
+    }
+
+    #[test]
+    fn test_load_valid_file() {
+        let mut recent = RecentConnections::new();
+        recent.add_connection("server01.domain.com".to_string(), "Web".to_string());
+        let json = serde_json::to_string_pretty(&recent).expect("Serialization should succeed");
+        let (_temp_dir, path) = write_recents(&json);
+
+        let loaded = load_recent_connections_from(&path).expect("Load should succeed");
+
+        assert_eq!(loaded.connections.len(), 1);
+        assert!(!backup_path(&path).exists());
+    }
+
+    #[test]
+    fn test_load_missing_file_returns_empty() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("recent_connections.json");
+
+        let loaded = load_recent_connections_from(&path).expect("Load should succeed");
+
+        assert!(loaded.connections.is_empty());
+        assert!(!backup_path(&path).exists());
+    }
+
+    #[test]
+    fn test_load_truncated_file_recovers() {
+        let (_temp_dir, path) =
+            write_recents(r#"{"connections":[{"hostname":"server01.domain.com","descr"#);
+
+        let loaded = load_recent_connections_from(&path).expect("Corrupt file should not be an error");
+
+        assert!(loaded.connections.is_empty());
+        let backup = backup_path(&path);
+        assert!(backup.exists());
+        assert!(!path.exists());
+        let saved = std::fs::read_to_string(&backup).expect("Failed to read backup");
+        assert!(saved.starts_with(r#"{"connections":"#));
+    }
+
+    #[test]
+    fn test_load_garbage_file_recovers() {
+        for garbage in ["not json at all", "", "[1, 2, 3]", "{\"connections\": 42}"] {
+            let (_temp_dir, path) = write_recents(garbage);
+
+            let loaded = load_recent_connections_from(&path).expect("Corrupt file should not be an error");
+
+            assert!(loaded.connections.is_empty(), "input: {:?}", garbage);
+            assert!(backup_path(&path).exists(), "input: {:?}", garbage);
+        }
+    }
+
+    #[test]
+    fn test_backup_path_appends_extension() {
+        let path = Path::new("data").join("recent_connections.json");
+        assert_eq!(
+            backup_path(&path),
+            Path::new("data").join("recent_connections.json.bak")
+        );
+    }
+
+    fn recents() -> RecentConnections {
+        let mut recent = RecentConnections::new();
+        recent.add_connection("server01.domain.com".to_string(), "Web".to_string());
+        recent.add_connection("quick.domain.com".to_string(), "".to_string());
+        recent
+    }
+
+    #[test]
+    fn test_host_from_recent_promotes_entry() {
+        let host = host_from_recent(&recents(), "QUICK.domain.com", "Jump box", Some(" Admin "))
+            .expect("Recent entry should be found");
+
+        assert_eq!(host.hostname, "quick.domain.com");
+        assert_eq!(host.description, "Jump box");
+        assert_eq!(host.group, Some("Admin".to_string()));
+        assert!(host.last_connected.is_some());
+        assert_eq!(host.connect_count, 0);
+    }
+
+    #[test]
+    fn test_host_from_recent_keeps_recent_description_when_blank() {
+        let host = host_from_recent(&recents(), "server01.domain.com", "  ", Some(""))
+            .expect("Recent entry should be found");
+
+        assert_eq!(host.description, "Web");
+        assert_eq!(host.group, None);
+    }
+
+    #[test]
+    fn test_host_from_recent_not_in_recents() {
+        let err = host_from_recent(&recents(), "unknown.domain.com", "Test", None)
+            .expect_err("Unknown hostname should be rejected");
+
+        assert!(err.to_string().contains("unknown.domain.com"));
+        assert!(err.to_string().contains("not in recent connections"));
+    }
+
+    #[test]
+    fn test_host_from_recent_upserts_into_hosts_file() {
+        let _version = crate::core::hosts::lock_version();
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let csv_path = temp_dir.path().join("hosts.csv");
+        let existing = vec![Host {
+            hostname: "other.domain.com".to_string(),
+            description: "Other".to_string(),
+            ..Default::default()
+        }];
+        crate::core::csv_writer::write_hosts_to_csv(&csv_path, &existing).expect("Failed to write CSV");
+
+        let host = host_from_recent(&recents(), "server01.domain.com", "Web Server", Some("Web")).unwrap();
+        let written = crate::core::hosts::upsert_hosts_at(&csv_path, vec![host], &AppSettings::default())
+            .expect("Upsert should succeed");
+
+        assert_eq!(written, 1);
+        let loaded = crate::core::csv_reader::read_hosts_from_csv(&csv_path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].hostname, "server01.domain.com");
+        assert_eq!(loaded[1].description, "Web Server");
+        assert_eq!(loaded[1].group, Some("Web".to_string()));
+
+        // Adding the same recent host again updates the entry in place
+        let host = host_from_recent(&recents(), "server01.domain.com", "Web Server 2", None).unwrap();
+        crate::core::hosts::upsert_hosts_at(&csv_path, vec![host], &AppSettings::default())
+            .expect("Upsert should succeed");
+        let loaded = crate::core::csv_reader::read_hosts_from_csv(&csv_path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].description, "Web Server 2");
+    }
+
+    fn recents_at(timestamps: &[(&str, u64)]) -> RecentConnections {
+        RecentConnections {
+            connections: timestamps
+                .iter()
+                .map(|(hostname, timestamp)| RecentConnection {
+                    hostname: hostname.to_string(),
+                    description: String::new(),
+                    timestamp: *timestamp,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_connections_since_filters_and_sorts_newest_first() {
+        let recent = recents_at(&[
+            ("server02", 1_700_000_200),
+            ("server01", 1_700_000_300),
+            ("server03", 1_700_000_100),
+            ("server04", 1_700_000_050),
+        ]);
+
+        let since = connections_since(recent, 1_700_000_100);
+
+        let hostnames: Vec<&str> = since.iter().map(|conn| conn.hostname.as_str()).collect();
+        assert_eq!(hostnames, vec!["server01", "server02", "server03"]);
+    }
+
+    #[test]
+    fn test_connections_since_newer_than_all_is_empty() {
+        let recent = recents_at(&[("server01", 1_700_000_300), ("server02", 1_700_000_200)]);
+
+        assert!(connections_since(recent, 1_700_000_301).is_empty());
+    }
+}
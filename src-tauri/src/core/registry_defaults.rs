@@ -0,0 +1,268 @@
+//! Enterprise defaults from the registry
+//!
+//! Reads default settings from `Software\QuickConnect` under
+//! `HKEY_CURRENT_USER` and `HKEY_LOCAL_MACHINE`. A value set for the user
+//! wins; the machine-wide value is used when the user has none.
+//!
+//! # Why this exists
+//! IT departments push configuration through Group Policy into the registry,
+//! usually machine-wide into `HKEY_LOCAL_MACHINE`. These are defaults, not
+//! enforced policy: anything the user saved in `settings.json` (or the theme
+//! menu) still takes precedence.
+//!
+//! # Why separate
+//! Takes one registry adapter per hive so the precedence rules can be tested
+//! against two mock registries.
+
+use crate::adapters::RegistryAdapter;
+use crate::core::theme::THEME_PREFERENCES;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Registry key read in both hives
+pub const DEFAULTS_KEY: &str = "Software\\QuickConnect";
+
+/// Default theme preference (`REG_SZ`: `light`, `dark` or `auto_time`)
+pub const THEME_VALUE: &str = "Theme";
+/// Default data directory (`REG_SZ`), used when `QUICKCONNECT_DATA_DIR` isn't set
+pub const DATA_DIR_VALUE: &str = "DataDir";
+/// Default domain controller for scans (`REG_SZ`)
+pub const LDAP_SERVER_VALUE: &str = "LdapServer";
+/// Default domain for scans (`REG_SZ`)
+pub const LDAP_DOMAIN_VALUE: &str = "LdapDomain";
+/// Default `auto_hide_minutes` setting (`REG_DWORD`)
+pub const AUTO_HIDE_MINUTES_VALUE: &str = "AutoHideMinutes";
+/// Default `status_poll_interval_secs` setting (`REG_DWORD`)
+pub const STATUS_POLL_INTERVAL_VALUE: &str = "StatusPollIntervalSecs";
+
+/// Defaults found in the registry; `None` where neither hive has a value
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RegistryDefaults {
+    pub theme: Option<String>,
+    pub data_dir: Option<String>,
+    pub ldap_server: Option<String>,
+    pub ldap_domain: Option<String>,
+    pub auto_hide_minutes: Option<u32>,
+    pub status_poll_interval_secs: Option<u32>,
+}
+
+impl RegistryDefaults {
+    /// Settings keys the registry can supply, with the value found (if any)
+    fn settings_values(&self) -> [(&'static str, Option<u32>); 2] {
+        [
+            ("auto_hide_minutes", self.auto_hide_minutes),
+            ("status_poll_interval_secs", self.status_poll_interval_secs),
+        ]
+    }
+
+    /// Fills settings missing from a parsed `settings.json` object
+    ///
+    /// Settings present in the file are left alone, so a value the user
+    /// saved always beats the registry default.
+    pub fn apply_to_settings_json(&self, settings: &mut Map<String, Value>) {
+        for (key, value) in self.settings_values() {
+            if let Some(value) = value {
+                settings.entry(key).or_insert_with(|| Value::from(value));
+            }
+        }
+    }
+
+    /// Removes registry-supplied settings from an object about to be saved
+    ///
+    /// A setting is dropped when `settings.json` didn't already contain it
+    /// and it still holds the registry value, so the file only records what
+    /// the user changed and later registry changes keep taking effect.
+    ///
+    /// # Arguments
+    /// * `settings` - Settings being saved
+    /// * `saved` - The `settings.json` object currently on disk
+    pub fn strip_from_settings_json(&self, settings: &mut Map<String, Value>, saved: &Map<String, Value>) {
+        for (key, value) in self.settings_values() {
+            let Some(value) = value else {
+                continue;
+            };
+            if !saved.contains_key(key) && settings.get(key) == Some(&Value::from(value)) {
+                settings.remove(key);
+            }
+        }
+    }
+}
+
+/// Reads a string value, preferring the user's hive
+///
+/// Blank values and read errors count as absent.
+pub fn read_string_default<U, M>(user: &U, machine: &M, value_name: &str) -> Option<String>
+where
+    U: RegistryAdapter,
+    M: RegistryAdapter,
+{
+    let read = |registry: &dyn RegistryAdapter| {
+        registry
+            .read_string(DEFAULTS_KEY, value_name)
+            .ok()
+            .flatten()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    read(user).or_else(|| read(machine))
+}
+
+/// Reads a DWORD value, preferring the user's hive
+///
+/// Read errors count as absent.
+pub fn read_dword_default<U, M>(user: &U, machine: &M, value_name: &str) -> Option<u32>
+where
+    U: RegistryAdapter,
+    M: RegistryAdapter,
+{
+    let read = |registry: &dyn RegistryAdapter| registry.read_dword(DEFAULTS_KEY, value_name).ok().flatten();
+
+    read(user).or_else(|| read(machine))
+}
+
+/// Reads every supported default from the user and machine hives
+///
+/// An unknown theme name is ignored.
+pub fn read_registry_defaults<U, M>(user: &U, machine: &M) -> RegistryDefaults
+where
+    U: RegistryAdapter,
+    M: RegistryAdapter,
+{
+    RegistryDefaults {
+        theme: read_string_default(user, machine, THEME_VALUE)
+            .map(|theme| theme.to_lowercase())
+            .filter(|theme| THEME_PREFERENCES.contains(&theme.as_str())),
+        data_dir: read_string_default(user, machine, DATA_DIR_VALUE),
+        ldap_server: read_string_default(user, machine, LDAP_SERVER_VALUE),
+        ldap_domain: read_string_default(user, machine, LDAP_DOMAIN_VALUE),
+        auto_hide_minutes: read_dword_default(user, machine, AUTO_HIDE_MINUTES_VALUE),
+        status_poll_interval_secs: read_dword_default(user, machine, STATUS_POLL_INTERVAL_VALUE),
+    }
+}
+
+/// Defaults read from the registry on first use
+static REGISTRY_DEFAULTS: once_cell::sync::Lazy<RegistryDefaults> =
+    once_cell::sync::Lazy::new(load_registry_defaults);
+
+/// Reads the defaults from this machine's registry
+pub fn load_registry_defaults() -> RegistryDefaults {
+    use crate::adapters::WindowsRegistry;
+
+    read_registry_defaults(&WindowsRegistry::new(), &WindowsRegistry::local_machine())
+}
+
+/// Returns the registry defaults, reading them once per run
+///
+/// Read at startup; Group Policy changes apply from the next start.
+pub fn registry_defaults() -> &'static RegistryDefaults {
+    &REGISTRY_DEFAULTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::mock::MockRegistry;
+
+    fn hives() -> (MockRegistry, MockRegistry) {
+        let machine = MockRegistry::new();
+        machine.write_string(DEFAULTS_KEY, THEME_VALUE, "light").unwrap();
+        machine.write_string(DEFAULTS_KEY, LDAP_SERVER_VALUE, "dc01.corp.local").unwrap();
+        machine.write_string(DEFAULTS_KEY, DATA_DIR_VALUE, "D:\\QuickConnect").unwrap();
+        machine.set_dword(DEFAULTS_KEY, AUTO_HIDE_MINUTES_VALUE, 15);
+        machine.set_dword(DEFAULTS_KEY, STATUS_POLL_INTERVAL_VALUE, 120);
+
+        let user = MockRegistry::new();
+        user.write_string(DEFAULTS_KEY, THEME_VALUE, "dark").unwrap();
+        user.set_dword(DEFAULTS_KEY, AUTO_HIDE_MINUTES_VALUE, 0);
+
+        (user, machine)
+    }
+
+    #[test]
+    fn test_user_values_override_machine_values() {
+        let (user, machine) = hives();
+
+        let defaults = read_registry_defaults(&user, &machine);
+
+        assert_eq!(defaults.theme.as_deref(), Some("dark"));
+        assert_eq!(defaults.auto_hide_minutes, Some(0));
+    }
+
+    #[test]
+    fn test_machine_values_fill_in_missing_user_values() {
+        let (user, machine) = hives();
+
+        let defaults = read_registry_defaults(&user, &machine);
+
+        assert_eq!(defaults.ldap_server.as_deref(), Some("dc01.corp.local"));
+        assert_eq!(defaults.data_dir.as_deref(), Some("D:\\QuickConnect"));
+        assert_eq!(defaults.status_poll_interval_secs, Some(120));
+        assert_eq!(defaults.ldap_domain, None);
+    }
+
+    #[test]
+    fn test_blank_user_string_falls_back_to_machine() {
+        let (user, machine) = hives();
+        user.write_string(DEFAULTS_KEY, LDAP_SERVER_VALUE, "  ").unwrap();
+
+        assert_eq!(
+            read_string_default(&user, &machine, LDAP_SERVER_VALUE).as_deref(),
+            Some("dc01.corp.local")
+        );
+    }
+
+    #[test]
+    fn test_unknown_theme_is_ignored() {
+        let user = MockRegistry::new();
+        user.write_string(DEFAULTS_KEY, THEME_VALUE, "Purple").unwrap();
+
+        assert_eq!(read_registry_defaults(&user, &MockRegistry::new()).theme, None);
+    }
+
+    #[test]
+    fn test_apply_to_settings_json_keeps_saved_values() {
+        let (user, machine) = hives();
+        let defaults = read_registry_defaults(&user, &machine);
+        let mut settings = serde_json::json!({ "status_poll_interval_secs": 30 })
+            .as_object()
+            .cloned()
+            .unwrap();
+
+        defaults.apply_to_settings_json(&mut settings);
+
+        assert_eq!(settings["status_poll_interval_secs"], 30);
+        assert_eq!(settings["auto_hide_minutes"], 0);
+    }
+
+    #[test]
+    fn test_strip_from_settings_json_drops_unchanged_registry_values() {
+        let defaults = RegistryDefaults {
+            auto_hide_minutes: Some(15),
+            status_poll_interval_secs: Some(120),
+            ..Default::default()
+        };
+        let saved = serde_json::json!({ "status_poll_interval_secs": 120 })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let mut settings = serde_json::json!({
+            "auto_hide_minutes": 15,
+            "status_poll_interval_secs": 120,
+        })
+        .as_object()
+        .cloned()
+        .unwrap();
+
+        defaults.strip_from_settings_json(&mut settings, &saved);
+
+        // Still the registry value and never saved: left to the registry
+        assert!(!settings.contains_key("auto_hide_minutes"));
+        // Saved by the user before: kept
+        assert_eq!(settings["status_poll_interval_secs"], 120);
+
+        let mut settings = serde_json::json!({ "auto_hide_minutes": 5 }).as_object().cloned().unwrap();
+        defaults.strip_from_settings_json(&mut settings, &Map::new());
+        assert_eq!(settings["auto_hide_minutes"], 5);
+    }
+}
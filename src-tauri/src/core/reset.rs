@@ -0,0 +1,400 @@
+//! Application reset
+//!
+//! Records what each step of `reset_application` did, so the frontend can
+//! tell a partial reset from a complete one instead of parsing report text.
+//!
+//! # Why separate
+//! The credential and file steps take the credential manager and paths as
+//! parameters so their outcomes can be unit tested with
+//! `MockCredentialManager` against temporary directories.
+
+use crate::adapters::{CredentialManager, CredentialType};
+use crate::core::credentials::TERMSRV_PREFIX;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// Target of the global QuickConnect credentials
+const GLOBAL_CREDENTIAL_TARGET: &str = "QuickConnect";
+
+/// A credential or file that could not be deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResetFailure {
+    pub target: String,
+    pub error: String,
+}
+
+/// Outcome of each reset step
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ResetReport {
+    /// Global QuickConnect credentials deleted
+    pub credentials_deleted: usize,
+    /// `TERMSRV/*` credentials deleted (generic and domain password)
+    pub termsrv_deleted: Vec<String>,
+    /// `TERMSRV/*` credentials that are still stored
+    pub termsrv_failed: Vec<ResetFailure>,
+    /// `.rdp` files deleted from the Connections directory
+    pub rdp_files_deleted: usize,
+    /// `.rdp` files that are still there
+    pub rdp_files_failed: Vec<ResetFailure>,
+    /// The hosts file was cleared
+    pub hosts_cleared: bool,
+    /// The recent connections file is gone (deleted, or there was none)
+    pub recents_deleted: bool,
+    /// The connection usage file is gone (deleted, or there was none)
+    pub usage_deleted: bool,
+    /// The connection history log is gone (deleted, or there was none)
+    pub history_deleted: bool,
+    /// The hosts file of the storage format not in use is gone (deleted, or there was none)
+    pub inactive_hosts_deleted: bool,
+    /// The settings file is gone (deleted, or there was none)
+    pub settings_deleted: bool,
+    /// Steps that failed as a whole, e.g. credentials that couldn't be listed
+    pub errors: Vec<String>,
+}
+
+impl ResetReport {
+    /// Whether every step succeeded
+    pub fn is_complete(&self) -> bool {
+        self.termsrv_failed.is_empty()
+            && self.rdp_files_failed.is_empty()
+            && self.hosts_cleared
+            && self.recents_deleted
+            && self.usage_deleted
+            && self.history_deleted
+            && self.inactive_hosts_deleted
+            && self.settings_deleted
+            && self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for ResetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = |done: bool| if done { "✓" } else { "✗" };
+
+        writeln!(f, "=== QuickConnect Application Reset ===\n")?;
+        writeln!(f, "✓ Deleted {} global QuickConnect credentials", self.credentials_deleted)?;
+        writeln!(f, "✓ Deleted {} RDP host credentials", self.termsrv_deleted.len())?;
+        for target in &self.termsrv_deleted {
+            writeln!(f, "  - {}", target)?;
+        }
+        for failure in &self.termsrv_failed {
+            writeln!(f, "✗ Failed to delete {}: {}", failure.target, failure.error)?;
+        }
+        writeln!(f, "✓ Deleted {} RDP files", self.rdp_files_deleted)?;
+        for failure in &self.rdp_files_failed {
+            writeln!(f, "✗ Failed to delete {}: {}", failure.target, failure.error)?;
+        }
+        writeln!(f, "{} Cleared hosts.csv", mark(self.hosts_cleared))?;
+        writeln!(f, "{} Deleted recent connections history", mark(self.recents_deleted))?;
+        writeln!(f, "{} Deleted connection usage", mark(self.usage_deleted))?;
+        writeln!(f, "{} Deleted connection history", mark(self.history_deleted))?;
+        writeln!(f, "{} Deleted unused hosts file", mark(self.inactive_hosts_deleted))?;
+        writeln!(f, "{} Deleted settings", mark(self.settings_deleted))?;
+        for error in &self.errors {
+            writeln!(f, "✗ {}", error)?;
+        }
+
+        if self.is_complete() {
+            writeln!(f, "\n=== Reset Complete ===")?;
+            writeln!(f, "The application has been reset to its initial state.")?;
+        } else {
+            writeln!(f, "\n=== Reset Incomplete ===")?;
+            writeln!(f, "Some items could not be deleted; see the errors above.")?;
+        }
+        write!(f, "Please restart the application.")
+    }
+}
+
+/// A `ResetReport` with its display text, as returned to the frontend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResetSummary {
+    #[serde(flatten)]
+    pub report: ResetReport,
+    /// Whether every step succeeded
+    pub complete: bool,
+    /// The report formatted for display
+    pub message: String,
+}
+
+impl From<ResetReport> for ResetSummary {
+    fn from(report: ResetReport) -> Self {
+        Self {
+            complete: report.is_complete(),
+            message: report.to_string(),
+            report,
+        }
+    }
+}
+
+/// Deletes the global QuickConnect credentials, counting them when they existed
+pub fn delete_global_credentials<M: CredentialManager>(manager: &M, report: &mut ResetReport) {
+    let existed = match manager.read(GLOBAL_CREDENTIAL_TARGET) {
+        Ok(credentials) => credentials.is_some(),
+        Err(e) => {
+            report.errors.push(format!("Failed to read global credentials: {}", e));
+            return;
+        }
+    };
+
+    match manager.delete(GLOBAL_CREDENTIAL_TARGET) {
+        Ok(()) if existed => report.credentials_deleted += 1,
+        Ok(()) => {}
+        Err(e) => report.errors.push(format!("Failed to delete global credentials: {}", e)),
+    }
+}
+
+/// Deletes every `TERMSRV/*` credential, both QuickConnect and Windows-saved
+///
+/// A failure for one credential doesn't stop the rest.
+pub fn delete_termsrv_credentials<M: CredentialManager>(manager: &M, report: &mut ResetReport) {
+    for cred_type in [CredentialType::Generic, CredentialType::DomainPassword] {
+        let targets = match manager.list_with_prefix_typed(TERMSRV_PREFIX, cred_type) {
+            Ok(targets) => targets,
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("Failed to enumerate TERMSRV credentials ({:?}): {}", cred_type, e));
+                continue;
+            }
+        };
+
+        for target in targets {
+            match manager.delete_typed(&target, cred_type) {
+                Ok(()) => report.termsrv_deleted.push(target),
+                Err(e) => report.termsrv_failed.push(ResetFailure {
+                    target,
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+}
+
+/// Deletes every `.rdp` file in `dir`, and the file name mapping beside them
+///
+/// A missing directory has nothing to delete.
+pub fn delete_rdp_files_in(dir: &Path, report: &mut ResetReport) {
+    if !dir.exists() {
+        return;
+    }
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            report
+                .errors
+                .push(format!("Failed to read connections directory {:?}: {}", dir, e));
+            return;
+        }
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|s| s.to_str()) != Some("rdp") {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => report.rdp_files_deleted += 1,
+            Err(e) => report.rdp_files_failed.push(ResetFailure {
+                target: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    if let Err(e) = remove_data_file(&dir.join(crate::core::rdp_file_names::RDP_FILE_MAP_NAME)) {
+        report.errors.push(e);
+    }
+}
+
+/// Deletes a data file if it exists
+///
+/// # Returns
+/// * `Ok(())` - The file is gone (deleted, or there was none)
+/// * `Err(String)` - The file couldn't be deleted
+pub fn remove_data_file(path: &Path) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to delete {:?}: {}", path, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::mock::MockCredentialManager;
+    use tempfile::TempDir;
+
+    fn save(manager: &MockCredentialManager, target: &str, cred_type: CredentialType) {
+        manager
+            .save_typed(target, "DOMAIN\\admin", "secret", cred_type)
+            .expect("Save should succeed");
+    }
+
+    fn complete_report() -> ResetReport {
+        ResetReport {
+            hosts_cleared: true,
+            recents_deleted: true,
+            usage_deleted: true,
+            history_deleted: true,
+            inactive_hosts_deleted: true,
+            settings_deleted: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_delete_global_credentials_counts_existing() {
+        let manager = MockCredentialManager::new();
+        let mut report = ResetReport::default();
+
+        delete_global_credentials(&manager, &mut report);
+        assert_eq!(report.credentials_deleted, 0);
+
+        save(&manager, "QuickConnect", CredentialType::Generic);
+        delete_global_credentials(&manager, &mut report);
+        assert_eq!(report.credentials_deleted, 1);
+        assert!(manager.read("QuickConnect").unwrap().is_none());
+        assert!(report.errors.is_empty());
+    }
+
+    #[test]
+    fn test_delete_global_credentials_records_failure() {
+        let manager = MockCredentialManager::new();
+        save(&manager, "QuickConnect", CredentialType::Generic);
+        manager.fail_deletes_to("QuickConnect");
+        let mut report = ResetReport::default();
+
+        delete_global_credentials(&manager, &mut report);
+
+        assert_eq!(report.credentials_deleted, 0);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].contains("global credentials"));
+    }
+
+    #[test]
+    fn test_delete_termsrv_credentials_records_successes_and_failures() {
+        let manager = MockCredentialManager::new();
+        save(&manager, "TERMSRV/web01.domain.com", CredentialType::Generic);
+        save(&manager, "TERMSRV/sql01.domain.com", CredentialType::DomainPassword);
+        save(&manager, "TERMSRV/locked.domain.com", CredentialType::DomainPassword);
+        save(&manager, "QuickConnect", CredentialType::Generic);
+        manager.fail_deletes_to("TERMSRV/locked.domain.com");
+        let mut report = ResetReport::default();
+
+        delete_termsrv_credentials(&manager, &mut report);
+
+        assert_eq!(
+            report.termsrv_deleted,
+            vec!["TERMSRV/web01.domain.com", "TERMSRV/sql01.domain.com"]
+        );
+        assert_eq!(report.termsrv_failed.len(), 1);
+        assert_eq!(report.termsrv_failed[0].target, "TERMSRV/locked.domain.com");
+        assert!(report.termsrv_failed[0].error.contains("delete"));
+        assert!(manager
+            .read_typed("TERMSRV/locked.domain.com", CredentialType::DomainPassword)
+            .unwrap()
+            .is_some());
+        // Only TERMSRV/* targets are touched
+        assert!(manager.read("QuickConnect").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_delete_rdp_files_in_counts_and_records_failures() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        std::fs::write(temp_dir.path().join("web01.domain.com.rdp"), "").unwrap();
+        std::fs::write(temp_dir.path().join("sql01.domain.com.rdp"), "").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+        std::fs::write(temp_dir.path().join("rdp_files.json"), "{}").unwrap();
+        // remove_file can't delete a directory
+        std::fs::create_dir(temp_dir.path().join("stuck.rdp")).unwrap();
+        let mut report = ResetReport::default();
+
+        delete_rdp_files_in(temp_dir.path(), &mut report);
+
+        assert_eq!(report.rdp_files_deleted, 2);
+        assert_eq!(report.rdp_files_failed.len(), 1);
+        assert_eq!(report.rdp_files_failed[0].target, "stuck.rdp");
+        assert!(temp_dir.path().join("notes.txt").exists());
+        assert!(!temp_dir.path().join("web01.domain.com.rdp").exists());
+        // The file name mapping goes with the files
+        assert!(!temp_dir.path().join("rdp_files.json").exists());
+        assert!(report.errors.is_empty());
+    }
+
+    #[test]
+    fn test_delete_rdp_files_in_missing_dir() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let mut report = ResetReport::default();
+
+        delete_rdp_files_in(&temp_dir.path().join("Connections"), &mut report);
+
+        assert_eq!(report, ResetReport::default());
+    }
+
+    #[test]
+    fn test_remove_data_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, "{}").unwrap();
+
+        assert!(remove_data_file(&path).is_ok());
+        assert!(!path.exists());
+        // Already gone counts as deleted
+        assert!(remove_data_file(&path).is_ok());
+        // A directory can't be removed as a file
+        assert!(remove_data_file(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_is_complete_and_display() {
+        let mut report = complete_report();
+        report.credentials_deleted = 1;
+        report.termsrv_deleted.push("TERMSRV/web01.domain.com".to_string());
+        report.rdp_files_deleted = 3;
+        assert!(report.is_complete());
+
+        let text = report.to_string();
+        assert!(text.contains("✓ Deleted 1 global QuickConnect credentials"));
+        assert!(text.contains("  - TERMSRV/web01.domain.com"));
+        assert!(text.contains("✓ Deleted 3 RDP files"));
+        assert!(text.contains("✓ Cleared hosts.csv"));
+        assert!(text.contains("✓ Deleted connection history"));
+        assert!(text.contains("✓ Deleted unused hosts file"));
+        assert!(text.contains("=== Reset Complete ==="));
+
+        report.history_deleted = false;
+        assert!(!report.is_complete());
+        assert!(report.to_string().contains("✗ Deleted connection history"));
+        report.history_deleted = true;
+
+        report.settings_deleted = false;
+        report.termsrv_failed.push(ResetFailure {
+            target: "TERMSRV/sql01.domain.com".to_string(),
+            error: "access denied".to_string(),
+        });
+        assert!(!report.is_complete());
+
+        let text = report.to_string();
+        assert!(text.contains("✗ Deleted settings"));
+        assert!(text.contains("✗ Failed to delete TERMSRV/sql01.domain.com: access denied"));
+        assert!(text.contains("=== Reset Incomplete ==="));
+    }
+
+    #[test]
+    fn test_summary_serializes_fields_and_message() {
+        let mut report = complete_report();
+        report.errors.push("Failed to clear hosts.csv".to_string());
+        report.hosts_cleared = false;
+
+        let json = serde_json::to_value(ResetSummary::from(report.clone())).unwrap();
+
+        assert_eq!(json["complete"], false);
+        assert_eq!(json["hosts_cleared"], false);
+        assert_eq!(json["settings_deleted"], true);
+        assert_eq!(json["credentials_deleted"], 0);
+        assert_eq!(json["termsrv_failed"], serde_json::json!([]));
+        assert_eq!(json["message"], report.to_string());
+    }
+}
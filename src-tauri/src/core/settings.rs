@@ -0,0 +1,760 @@
+//! Application settings - persisted user configuration
+//!
+//! Stores configuration that is not tied to a single host record, such as the
+//! global RDP file options and per-host RDP option overrides.
+//!
+//! # Why this exists
+//! Hosts are persisted in a flat CSV file that is shared and edited by hand, so
+//! richer configuration lives in a separate `settings.json` next to it.
+//!
+//! # Why separate
+//! Keeps settings I/O and resolution logic out of the command layer so it can be
+//! unit tested against temporary files and reused by the RDP launcher.
+
+use crate::adapters::CredentialPersistence;
+use crate::core::hosts::DescriptionOverflow;
+use crate::core::hosts_store::StorageFormat;
+use crate::core::rdp::RdpOptions;
+use crate::core::rdp_launcher::LaunchMode;
+use crate::core::registry_defaults::RegistryDefaults;
+use crate::core::single_instance::SingleInstanceTarget;
+use crate::core::window_close::CloseBehavior;
+use crate::infra::{debug_log, get_settings_path};
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Most times a launch tries to start mstsc (`mstsc_spawn_attempts` is clamped to this)
+pub const MAX_MSTSC_SPAWN_ATTEMPTS: u32 = 5;
+
+/// Persisted application settings
+///
+/// Every field has a default so that settings files written by older versions
+/// (or edited by hand) still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// RDP options applied to every connection
+    pub rdp: RdpOptions,
+    /// Per-host RDP option overrides, keyed by hostname
+    pub host_rdp: HashMap<String, RdpOptions>,
+    /// Minutes without interaction before the main window hides to the tray (0 = disabled)
+    pub auto_hide_minutes: u32,
+    /// Save credentials as TERMSRV/{hostname} for RDP single sign-on
+    ///
+    /// Turn off on shared/kiosk machines so credentials don't persist for the
+    /// next user; mstsc then prompts for credentials instead.
+    pub persist_rdp_credentials: bool,
+    /// Keep saved TERMSRV credentials only until the user logs off
+    pub rdp_credentials_session_only: bool,
+    /// Reuse an existing .rdp file on launch instead of regenerating it, so hand edits survive
+    pub preserve_rdp_edits: bool,
+    /// Watch mstsc briefly after launch and report an immediate non-zero exit as an error
+    pub verify_rdp_launch: bool,
+    /// Times to try starting mstsc when spawning it fails transiently (1 = no retry,
+    /// clamped to 1..=`MAX_MSTSC_SPAWN_ATTEMPTS` on load)
+    pub mstsc_spawn_attempts: u32,
+    /// Never keep `.rdp` files on disk: each launch writes an owner-only temporary
+    /// file and deletes it once mstsc has read it
+    pub high_security_mode: bool,
+    /// Probe all hosts in the background and push `host-status-changed` events
+    pub status_polling_enabled: bool,
+    /// Seconds between background status polls
+    pub status_poll_interval_secs: u32,
+    /// Warn when the global credentials are at least this many days old (0 = never)
+    pub credential_max_age_days: u32,
+    /// Tray label for recent connections, using `{hostname}`, `{description}` and `{alias}`
+    pub recent_label_format: String,
+    /// Prefix tray recent connections with a status glyph (probes hosts when the menu is built)
+    pub tray_status_indicators: bool,
+    /// Maximum number of `.rdp` files kept in the Connections directory (0 = unlimited)
+    pub connections_max_files: u32,
+    /// Maximum total size in bytes of the Connections directory (0 = unlimited)
+    pub connections_max_bytes: u64,
+    /// Ports to try when a host doesn't answer on 3389 during status checks
+    pub status_fallback_ports: Vec<u16>,
+    /// Local IP address status probes connect from, for multi-homed machines (blank = OS chooses)
+    pub status_probe_source_address: String,
+    /// Whether hosts are kept in hosts.csv or hosts.json (see `convert_hosts_to_json`)
+    pub storage_format: StorageFormat,
+    /// Default window state for mstsc (a launch may override it)
+    pub rdp_launch_mode: LaunchMode,
+    /// Search the child domains named in LDAP referrals during a scan instead of failing
+    pub ldap_follow_referrals: bool,
+    /// Hour (0-23) the `auto_time` theme switches to dark
+    pub auto_theme_dark_hour: u8,
+    /// Hour (0-23) the `auto_time` theme switches back to light
+    pub auto_theme_light_hour: u8,
+    /// Maximum length in characters of a host description (0 = unlimited)
+    pub description_max_length: u32,
+    /// Whether over-length descriptions are truncated or rejected on save
+    pub description_overflow: DescriptionOverflow,
+    /// Window shown when QuickConnect is launched while already running
+    pub single_instance_target: SingleInstanceTarget,
+    /// Hosts whose credentials rarely change; launches reuse their saved .rdp file
+    /// and existing TERMSRV credential instead of rewriting both
+    pub stable_hosts: Vec<String>,
+    /// Whether closing the login or main window hides it to the tray or quits
+    pub close_behavior: CloseBehavior,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            rdp: RdpOptions::default(),
+            host_rdp: HashMap::new(),
+            auto_hide_minutes: 0,
+            persist_rdp_credentials: true,
+            rdp_credentials_session_only: false,
+            preserve_rdp_edits: false,
+            verify_rdp_launch: false,
+            mstsc_spawn_attempts: 2,
+            high_security_mode: false,
+            status_polling_enabled: false,
+            status_poll_interval_secs: 60,
+            credential_max_age_days: 0,
+            recent_label_format: crate::core::tray::DEFAULT_RECENT_LABEL_FORMAT.to_string(),
+            tray_status_indicators: false,
+            connections_max_files: 0,
+            connections_max_bytes: 0,
+            status_fallback_ports: Vec::new(),
+            status_probe_source_address: String::new(),
+            storage_format: StorageFormat::Csv,
+            rdp_launch_mode: LaunchMode::Normal,
+            ldap_follow_referrals: false,
+            auto_theme_dark_hour: 18,
+            auto_theme_light_hour: 7,
+            description_max_length: 512,
+            description_overflow: DescriptionOverflow::Truncate,
+            single_instance_target: SingleInstanceTarget::LastHidden,
+            stable_hosts: Vec::new(),
+            close_behavior: CloseBehavior::HideToTray,
+        }
+    }
+}
+
+impl AppSettings {
+    /// Returns how TERMSRV credentials should be persisted, or `None` to skip saving them
+    pub fn termsrv_persistence(&self) -> Option<CredentialPersistence> {
+        if !self.persist_rdp_credentials {
+            None
+        } else if self.rdp_credentials_session_only {
+            Some(CredentialPersistence::Session)
+        } else {
+            Some(CredentialPersistence::LocalMachine)
+        }
+    }
+
+    /// Returns the local address status probes should connect from
+    ///
+    /// `None` when `status_probe_source_address` is blank or not an IP
+    /// address, so the OS picks the interface.
+    pub fn status_probe_source(&self) -> Option<std::net::IpAddr> {
+        self.status_probe_source_address.trim().parse().ok()
+    }
+
+    /// Returns whether a host is marked stable (case-insensitive)
+    pub fn is_stable_host(&self, hostname: &str) -> bool {
+        self.stable_hosts
+            .iter()
+            .any(|stable| stable.eq_ignore_ascii_case(hostname))
+    }
+
+    /// Returns the effective RDP options for a host
+    ///
+    /// Per-host values take precedence over the global options; anything left
+    /// unset falls back to the built-in RDP defaults.
+    pub fn rdp_options_for(&self, hostname: &str) -> RdpOptions {
+        match self.host_rdp.get(hostname) {
+            Some(host_options) => self.rdp.overlay(host_options),
+            None => self.rdp.clone(),
+        }
+    }
+}
+
+/// Look-and-feel preferences that can be shared between machines
+///
+/// Kept apart from the full settings so they can be exported on their own
+/// without host or credential options. Missing fields take the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiPreferences {
+    /// Saved theme preference (`"light"`, `"dark"` or `"auto_time"`);
+    /// `None` follows the Windows theme
+    pub theme: Option<String>,
+    /// See `AppSettings::auto_theme_dark_hour`
+    pub auto_theme_dark_hour: u8,
+    /// See `AppSettings::auto_theme_light_hour`
+    pub auto_theme_light_hour: u8,
+    /// See `AppSettings::recent_label_format`
+    pub recent_label_format: String,
+}
+
+impl Default for UiPreferences {
+    fn default() -> Self {
+        Self::from_settings(&AppSettings::default(), None)
+    }
+}
+
+impl UiPreferences {
+    /// Collects the preferences from settings and the saved theme preference
+    pub fn from_settings(settings: &AppSettings, theme: Option<String>) -> Self {
+        Self {
+            theme,
+            auto_theme_dark_hour: settings.auto_theme_dark_hour,
+            auto_theme_light_hour: settings.auto_theme_light_hour,
+            recent_label_format: settings.recent_label_format.clone(),
+        }
+    }
+
+    /// Writes the preferences other than the theme into `settings`
+    pub fn apply_to(&self, settings: &mut AppSettings) {
+        settings.auto_theme_dark_hour = self.auto_theme_dark_hour;
+        settings.auto_theme_light_hour = self.auto_theme_light_hour;
+        settings.recent_label_format = self.recent_label_format.clone();
+    }
+
+    /// Parses exported preferences, rejecting unknown themes and invalid hours
+    ///
+    /// # Returns
+    /// * `Ok(UiPreferences)` - Values from `json`, defaults for anything missing
+    /// * `Err(AppError)` - Malformed JSON or an invalid value
+    pub fn from_json(json: &str) -> Result<Self, AppError> {
+        let preferences: Self = serde_json::from_str(json).map_err(|e| AppError::JsonError {
+            context: "Failed to parse UI preferences".to_string(),
+            source: e,
+        })?;
+
+        let invalid = |message: String| AppError::Other { message, source: None };
+        if let Some(theme) = &preferences.theme {
+            if !crate::core::theme::THEME_PREFERENCES.contains(&theme.as_str()) {
+                return Err(invalid(format!("Unknown theme '{}'", theme)));
+            }
+        }
+        for hour in [preferences.auto_theme_dark_hour, preferences.auto_theme_light_hour] {
+            if hour > 23 {
+                return Err(invalid(format!("Invalid auto theme hour {}", hour)));
+            }
+        }
+        Ok(preferences)
+    }
+
+    /// Serializes the preferences for export
+    pub fn to_json(&self) -> Result<String, AppError> {
+        serde_json::to_string_pretty(self).map_err(|e| AppError::JsonError {
+            context: "Failed to serialize UI preferences".to_string(),
+            source: e,
+        })
+    }
+}
+
+/// Loads settings from the given file.
+///
+/// # Returns
+/// * `Ok(AppSettings)` - Parsed settings (defaults if the file doesn't exist)
+/// * `Err(AppError)` - Failed to read or parse the file
+pub fn load_settings_from(path: &Path) -> Result<AppSettings, AppError> {
+    load_settings_with_defaults_from(path, &RegistryDefaults::default())
+}
+
+/// Loads settings from the given file, taking settings it doesn't contain
+/// from the registry defaults before the built-in ones.
+///
+/// # Returns
+/// * `Ok(AppSettings)` - Parsed settings
+/// * `Err(AppError)` - Failed to read or parse the file
+pub fn load_settings_with_defaults_from(
+    path: &Path,
+    registry_defaults: &RegistryDefaults,
+) -> Result<AppSettings, AppError> {
+    let mut json = read_settings_json(path)?;
+
+    if let Some(settings) = json.as_object_mut() {
+        registry_defaults.apply_to_settings_json(settings);
+    }
+
+    let mut settings: AppSettings = serde_json::from_value(json).map_err(|e| AppError::JsonError {
+        context: format!("Failed to parse settings file {:?}", path),
+        source: e,
+    })?;
+
+    // A hand-edited value must not keep a launch retrying for minutes
+    settings.mstsc_spawn_attempts = settings.mstsc_spawn_attempts.clamp(1, MAX_MSTSC_SPAWN_ATTEMPTS);
+
+    // A hand-edited gateway must not add lines to the generated `.rdp` file
+    for options in std::iter::once(&mut settings.rdp).chain(settings.host_rdp.values_mut()) {
+        if let Err(e) = options.validate_gateway() {
+            debug_log(
+                "WARN",
+                "SETTINGS",
+                "Ignoring invalid RD Gateway in settings",
+                Some(&e.to_string()),
+            );
+            options.gateway_hostname = None;
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Reads the settings file as raw JSON (an empty object if it doesn't exist)
+fn read_settings_json(path: &Path) -> Result<serde_json::Value, AppError> {
+    if !path.exists() {
+        return Ok(serde_json::Value::Object(serde_json::Map::new()));
+    }
+
+    let contents = std::fs::read_to_string(path).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+    serde_json::from_str(&contents).map_err(|e| AppError::JsonError {
+        context: format!("Failed to parse settings file {:?}", path),
+        source: e,
+    })
+}
+
+/// Saves settings to the given file.
+///
+/// # Side Effects
+/// - Creates the parent directory if needed
+/// - Overwrites the settings file
+pub fn save_settings_to(path: &Path, settings: &AppSettings) -> Result<(), AppError> {
+    save_settings_with_defaults_to(path, settings, &RegistryDefaults::default())
+}
+
+/// Saves settings to the given file, leaving out values that only came from
+/// the registry defaults
+///
+/// The registry stays a separate layer: a default the user never changed is
+/// not written, so a later change to it in the registry still applies.
+///
+/// # Side Effects
+/// - Creates the parent directory if needed
+/// - Overwrites the settings file
+pub fn save_settings_with_defaults_to(
+    path: &Path,
+    settings: &AppSettings,
+    registry_defaults: &RegistryDefaults,
+) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| AppError::IoError {
+            path: parent.to_string_lossy().to_string(),
+            source: e,
+        })?;
+    }
+
+    let mut value = serde_json::to_value(settings).map_err(|e| AppError::JsonError {
+        context: "Failed to serialize settings".to_string(),
+        source: e,
+    })?;
+    if let Some(object) = value.as_object_mut() {
+        // An unreadable file counts as empty: it is about to be replaced
+        let saved = read_settings_json(path).unwrap_or_default();
+        let empty = serde_json::Map::new();
+        registry_defaults.strip_from_settings_json(object, saved.as_object().unwrap_or(&empty));
+    }
+
+    let json = serde_json::to_string_pretty(&value).map_err(|e| AppError::JsonError {
+        context: "Failed to serialize settings".to_string(),
+        source: e,
+    })?;
+
+    std::fs::write(path, json).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })
+}
+
+/// Loads settings from `%APPDATA%\QuickConnect\settings.json`.
+///
+/// Settings missing from the file come from the registry defaults
+/// (see `core::registry_defaults`) when IT has set them.
+pub fn load_settings() -> Result<AppSettings, AppError> {
+    let path = get_settings_path().map_err(|e| AppError::Other {
+        message: format!("Failed to get settings path: {}", e),
+        source: None,
+    })?;
+
+    debug_log(
+        "DEBUG",
+        "SETTINGS",
+        &format!("Loading settings from {:?}", path),
+        None,
+    );
+
+    load_settings_with_defaults_from(&path, crate::core::registry_defaults::registry_defaults())
+}
+
+/// Saves settings to `%APPDATA%\QuickConnect\settings.json`.
+pub fn save_settings(settings: &AppSettings) -> Result<(), AppError> {
+    let path = get_settings_path().map_err(|e| AppError::Other {
+        message: format!("Failed to get settings path: {}", e),
+        source: None,
+    })?;
+
+    debug_log(
+        "DEBUG",
+        "SETTINGS",
+        &format!("Saving settings to {:?}", path),
+        None,
+    );
+
+    save_settings_with_defaults_to(&path, settings, crate::core::registry_defaults::registry_defaults())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rdp::KeyboardHook;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_settings_missing_file_returns_defaults() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+
+        let settings = load_settings_from(&path).expect("Missing file should load defaults");
+
+        assert_eq!(settings, AppSettings::default());
+    }
+
+    #[test]
+    fn test_save_and_load_settings_roundtrip() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("nested").join("settings.json");
+
+        let mut settings = AppSettings::default();
+        settings.rdp.keyboard_hook = Some(KeyboardHook::Remote);
+        settings.host_rdp.insert(
+            "server01.contoso.com".to_string(),
+            RdpOptions {
+                redirect_clipboard: Some(false),
+                ..Default::default()
+            },
+        );
+
+        save_settings_to(&path, &settings).expect("Failed to save settings");
+        let loaded = load_settings_from(&path).expect("Failed to load settings");
+
+        assert_eq!(loaded, settings);
+    }
+
+    #[test]
+    fn test_default_settings_values() {
+        let settings = AppSettings::default();
+
+        assert_eq!(settings.rdp, RdpOptions::default());
+        assert!(settings.host_rdp.is_empty());
+        assert_eq!(settings.auto_hide_minutes, 0);
+        assert!(settings.persist_rdp_credentials);
+        assert!(!settings.rdp_credentials_session_only);
+        assert!(!settings.preserve_rdp_edits);
+        assert!(!settings.verify_rdp_launch);
+        assert_eq!(settings.mstsc_spawn_attempts, 2);
+        assert!(!settings.high_security_mode);
+        assert!(!settings.status_polling_enabled);
+        assert_eq!(settings.status_poll_interval_secs, 60);
+        assert_eq!(settings.credential_max_age_days, 0);
+        assert_eq!(settings.recent_label_format, "{hostname} - {description}");
+        assert!(!settings.tray_status_indicators);
+        assert_eq!(settings.connections_max_files, 0);
+        assert_eq!(settings.connections_max_bytes, 0);
+        assert!(settings.status_fallback_ports.is_empty());
+        assert_eq!(settings.storage_format, StorageFormat::Csv);
+        assert_eq!(settings.rdp_launch_mode, LaunchMode::Normal);
+        assert!(!settings.ldap_follow_referrals);
+        assert_eq!(settings.auto_theme_dark_hour, 18);
+        assert_eq!(settings.auto_theme_light_hour, 7);
+        assert_eq!(settings.description_max_length, 512);
+        assert_eq!(settings.description_overflow, DescriptionOverflow::Truncate);
+        assert_eq!(settings.single_instance_target, SingleInstanceTarget::LastHidden);
+        assert!(settings.status_probe_source_address.is_empty());
+        assert!(settings.stable_hosts.is_empty());
+        assert_eq!(settings.close_behavior, CloseBehavior::HideToTray);
+    }
+
+    #[test]
+    fn test_settings_json_roundtrip() {
+        let mut settings = AppSettings::default();
+        settings.rdp.pin_connection_bar = Some(true);
+        settings.auto_hide_minutes = 15;
+        settings.persist_rdp_credentials = false;
+
+        let json = serde_json::to_string(&settings).expect("Serialization should succeed");
+        let parsed: AppSettings = serde_json::from_str(&json).expect("Deserialization should succeed");
+
+        assert_eq!(parsed, settings);
+    }
+
+    #[test]
+    fn test_save_then_load_returns_updated_settings() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+        save_settings_to(&path, &AppSettings::default()).expect("Failed to save settings");
+
+        let updated = AppSettings {
+            auto_hide_minutes: 30,
+            rdp_credentials_session_only: true,
+            preserve_rdp_edits: true,
+            verify_rdp_launch: true,
+            ..AppSettings::default()
+        };
+        save_settings_to(&path, &updated).expect("Failed to save settings");
+
+        let loaded = load_settings_from(&path).expect("Failed to load settings");
+        assert_eq!(loaded, updated);
+    }
+
+    #[test]
+    fn test_load_settings_tolerates_missing_fields() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"rdp":{"keyboard_hook":"local"}}"#)
+            .expect("Failed to write settings");
+
+        let settings = load_settings_from(&path).expect("Partial settings should load");
+
+        assert_eq!(settings.rdp.keyboard_hook, Some(KeyboardHook::Local));
+        assert!(settings.host_rdp.is_empty());
+        assert_eq!(settings.auto_hide_minutes, 0);
+        assert!(settings.persist_rdp_credentials);
+        assert!(!settings.preserve_rdp_edits);
+    }
+
+    #[test]
+    fn test_load_settings_clamps_mstsc_spawn_attempts() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+
+        std::fs::write(&path, r#"{"mstsc_spawn_attempts":4000000000}"#).expect("Failed to write settings");
+        let settings = load_settings_from(&path).expect("Settings should load");
+        assert_eq!(settings.mstsc_spawn_attempts, MAX_MSTSC_SPAWN_ATTEMPTS);
+
+        std::fs::write(&path, r#"{"mstsc_spawn_attempts":0}"#).expect("Failed to write settings");
+        let settings = load_settings_from(&path).expect("Settings should load");
+        assert_eq!(settings.mstsc_spawn_attempts, 1);
+    }
+
+    #[test]
+    fn test_load_settings_drops_invalid_gateway() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{"rdp":{"gateway_hostname":"rdg.domain.com\r\nfull address:s:other"},"host_rdp":{"server01":{"gateway_hostname":"rdg.domain.com","desktop_width":0}}}"#,
+        )
+        .expect("Failed to write settings");
+
+        let settings = load_settings_from(&path).expect("Settings should load");
+        assert_eq!(settings.rdp.gateway_hostname, None);
+        // An out-of-range size is clamped when the file is generated, so the gateway stays
+        assert_eq!(
+            settings.host_rdp["server01"].gateway_hostname.as_deref(),
+            Some("rdg.domain.com")
+        );
+    }
+
+    #[test]
+    fn test_load_settings_registry_defaults_fill_missing_fields_only() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"status_poll_interval_secs":30}"#)
+            .expect("Failed to write settings");
+        let registry_defaults = RegistryDefaults {
+            auto_hide_minutes: Some(15),
+            status_poll_interval_secs: Some(300),
+            ..Default::default()
+        };
+
+        let settings = load_settings_with_defaults_from(&path, &registry_defaults)
+            .expect("Settings should load");
+        assert_eq!(settings.status_poll_interval_secs, 30);
+        assert_eq!(settings.auto_hide_minutes, 15);
+
+        let missing = temp_dir.path().join("missing.json");
+        let settings = load_settings_with_defaults_from(&missing, &registry_defaults)
+            .expect("Missing file should load defaults");
+        assert_eq!(settings.status_poll_interval_secs, 300);
+    }
+
+    #[test]
+    fn test_save_settings_does_not_persist_registry_defaults() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"status_poll_interval_secs":30}"#)
+            .expect("Failed to write settings");
+        let registry_defaults = RegistryDefaults {
+            auto_hide_minutes: Some(15),
+            status_poll_interval_secs: Some(300),
+            ..Default::default()
+        };
+
+        let mut settings = load_settings_with_defaults_from(&path, &registry_defaults)
+            .expect("Settings should load");
+        settings.preserve_rdp_edits = true;
+        save_settings_with_defaults_to(&path, &settings, &registry_defaults)
+            .expect("Settings should save");
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(saved.get("auto_hide_minutes").is_none());
+        assert_eq!(saved["status_poll_interval_secs"], 30);
+        assert_eq!(saved["preserve_rdp_edits"], true);
+
+        // A changed registry value is picked up on the next load
+        let registry_defaults = RegistryDefaults {
+            auto_hide_minutes: Some(45),
+            ..registry_defaults
+        };
+        let settings = load_settings_with_defaults_from(&path, &registry_defaults)
+            .expect("Settings should load");
+        assert_eq!(settings.auto_hide_minutes, 45);
+        assert!(settings.preserve_rdp_edits);
+
+        // A value the user changes is saved
+        let settings = AppSettings {
+            auto_hide_minutes: 5,
+            ..settings
+        };
+        save_settings_with_defaults_to(&path, &settings, &registry_defaults)
+            .expect("Settings should save");
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["auto_hide_minutes"], 5);
+    }
+
+    #[test]
+    fn test_load_settings_invalid_json_returns_error() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("settings.json");
+        std::fs::write(&path, "not json").expect("Failed to write settings");
+
+        let result = load_settings_from(&path);
+
+        assert!(matches!(result, Err(AppError::JsonError { .. })));
+    }
+
+    #[test]
+    fn test_rdp_options_for_host_overrides_global() {
+        let mut settings = AppSettings::default();
+        settings.rdp.keyboard_hook = Some(KeyboardHook::Remote);
+        settings.rdp.redirect_printers = Some(false);
+        settings.host_rdp.insert(
+            "server01".to_string(),
+            RdpOptions {
+                keyboard_hook: Some(KeyboardHook::Local),
+                ..Default::default()
+            },
+        );
+
+        let host_options = settings.rdp_options_for("server01");
+        assert_eq!(host_options.keyboard_hook, Some(KeyboardHook::Local));
+        assert_eq!(host_options.redirect_printers, Some(false));
+
+        let other_options = settings.rdp_options_for("server02");
+        assert_eq!(other_options.keyboard_hook, Some(KeyboardHook::Remote));
+    }
+
+    #[test]
+    fn test_termsrv_persistence_follows_settings() {
+        let mut settings = AppSettings::default();
+        assert_eq!(
+            settings.termsrv_persistence(),
+            Some(CredentialPersistence::LocalMachine)
+        );
+
+        settings.rdp_credentials_session_only = true;
+        assert_eq!(settings.termsrv_persistence(), Some(CredentialPersistence::Session));
+
+        settings.persist_rdp_credentials = false;
+        assert_eq!(settings.termsrv_persistence(), None);
+    }
+
+    #[test]
+    fn test_is_stable_host_ignores_case() {
+        let settings = AppSettings {
+            stable_hosts: vec!["Server01.domain.com".to_string()],
+            ..Default::default()
+        };
+
+        assert!(settings.is_stable_host("server01.DOMAIN.com"));
+        assert!(!settings.is_stable_host("server02.domain.com"));
+        assert!(!AppSettings::default().is_stable_host("server01.domain.com"));
+    }
+
+    #[test]
+    fn test_status_probe_source_parses_address() {
+        let with_source = |address: &str| AppSettings {
+            status_probe_source_address: address.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(AppSettings::default().status_probe_source(), None);
+        assert_eq!(
+            with_source(" 10.1.2.3 ").status_probe_source(),
+            Some("10.1.2.3".parse().unwrap())
+        );
+        assert_eq!(with_source("fe80::1").status_probe_source(), Some("fe80::1".parse().unwrap()));
+        assert_eq!(with_source("eth0").status_probe_source(), None);
+    }
+
+    #[test]
+    fn test_ui_preferences_roundtrip() {
+        let settings = AppSettings {
+            auto_theme_dark_hour: 20,
+            auto_theme_light_hour: 6,
+            recent_label_format: "{hostname}".to_string(),
+            auto_hide_minutes: 15,
+            ..AppSettings::default()
+        };
+        let exported = UiPreferences::from_settings(&settings, Some("auto_time".to_string()))
+            .to_json()
+            .expect("Export should succeed");
+
+        let imported = UiPreferences::from_json(&exported).expect("Import should succeed");
+        let mut target = AppSettings::default();
+        imported.apply_to(&mut target);
+
+        assert_eq!(imported.theme.as_deref(), Some("auto_time"));
+        assert_eq!(target.auto_theme_dark_hour, 20);
+        assert_eq!(target.auto_theme_light_hour, 6);
+        assert_eq!(target.recent_label_format, "{hostname}");
+        // Only look-and-feel preferences travel
+        assert_eq!(target.auto_hide_minutes, 0);
+        assert!(!exported.contains("auto_hide_minutes"));
+    }
+
+    #[test]
+    fn test_ui_preferences_import_overrides_defaults() {
+        let imported = UiPreferences::from_json(r#"{"theme":"light","auto_theme_dark_hour":21}"#)
+            .expect("Import should succeed");
+        let mut settings = AppSettings::default();
+        imported.apply_to(&mut settings);
+
+        assert_eq!(imported.theme.as_deref(), Some("light"));
+        assert_eq!(settings.auto_theme_dark_hour, 21);
+        assert_eq!(settings.auto_theme_light_hour, AppSettings::default().auto_theme_light_hour);
+        assert_eq!(settings.recent_label_format, AppSettings::default().recent_label_format);
+
+        let follows_windows = UiPreferences::from_json("{}").expect("Import should succeed");
+        assert_eq!(follows_windows, UiPreferences::default());
+        assert_eq!(follows_windows.theme, None);
+    }
+
+    #[test]
+    fn test_ui_preferences_import_rejects_invalid_values() {
+        assert!(matches!(
+            UiPreferences::from_json(r#"{"theme":"purple"}"#),
+            Err(AppError::Other { .. })
+        ));
+        assert!(matches!(
+            UiPreferences::from_json(r#"{"auto_theme_light_hour":24}"#),
+            Err(AppError::Other { .. })
+        ));
+        assert!(matches!(
+            UiPreferences::from_json("not json"),
+            Err(AppError::JsonError { .. })
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+//! Global shortcut registration status
+//!
+//! Global hotkeys can fail to register when another application already holds
+//! them, leaving the user with a hotkey that silently does nothing.
+//!
+//! # Why separate
+//! Registration happens through the Tauri plugin at startup; the outcomes are
+//! recorded here as plain results so the reporting can be unit tested without
+//! a running app.
+
+use serde::Serialize;
+
+/// Shows or hides the main window
+pub const SHOW_MAIN_SHORTCUT: &str = "Ctrl+Shift+R";
+
+/// Shows or hides the error window
+pub const TOGGLE_ERROR_SHORTCUT: &str = "Ctrl+Shift+E";
+
+/// Every global shortcut QuickConnect registers, in registration order
+pub const GLOBAL_SHORTCUTS: [&str; 2] = [SHOW_MAIN_SHORTCUT, TOGGLE_ERROR_SHORTCUT];
+
+/// Whether a global shortcut is active
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShortcutStatus {
+    pub accelerator: String,
+    pub active: bool,
+    /// Why registration failed; `None` when active
+    pub error: Option<String>,
+}
+
+impl ShortcutStatus {
+    /// Builds the status from a registration result
+    pub fn from_result<E: std::fmt::Display>(accelerator: &str, result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => ShortcutStatus {
+                accelerator: accelerator.to_string(),
+                active: true,
+                error: None,
+            },
+            Err(e) => ShortcutStatus {
+                accelerator: accelerator.to_string(),
+                active: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Reports every expected shortcut from the recorded registration results
+///
+/// The latest result for an accelerator wins (matched case-insensitively).
+/// Shortcuts with no recorded attempt are reported inactive.
+///
+/// # Returns
+/// One status per expected accelerator, in the same order.
+pub fn collect_shortcut_statuses(expected: &[&str], recorded: &[ShortcutStatus]) -> Vec<ShortcutStatus> {
+    expected
+        .iter()
+        .map(|accelerator| {
+            recorded
+                .iter()
+                .rev()
+                .find(|status| status.accelerator.eq_ignore_ascii_case(accelerator))
+                .cloned()
+                .unwrap_or_else(|| ShortcutStatus {
+                    accelerator: accelerator.to_string(),
+                    active: false,
+                    error: Some("Not registered".to_string()),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_reports_success_and_failure() {
+        let recorded = vec![
+            ShortcutStatus::from_result::<String>(SHOW_MAIN_SHORTCUT, Ok(())),
+            ShortcutStatus::from_result(TOGGLE_ERROR_SHORTCUT, Err("HotKey already registered")),
+        ];
+
+        let statuses = collect_shortcut_statuses(&GLOBAL_SHORTCUTS, &recorded);
+
+        assert_eq!(
+            statuses,
+            vec![
+                ShortcutStatus {
+                    accelerator: "Ctrl+Shift+R".to_string(),
+                    active: true,
+                    error: None,
+                },
+                ShortcutStatus {
+                    accelerator: "Ctrl+Shift+E".to_string(),
+                    active: false,
+                    error: Some("HotKey already registered".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_reports_unattempted_as_inactive() {
+        let statuses = collect_shortcut_statuses(&GLOBAL_SHORTCUTS, &[]);
+
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.iter().all(|status| !status.active));
+        assert_eq!(statuses[0].error.as_deref(), Some("Not registered"));
+    }
+
+    #[test]
+    fn test_collect_uses_latest_result() {
+        let recorded = vec![
+            ShortcutStatus::from_result(SHOW_MAIN_SHORTCUT, Err("in use")),
+            ShortcutStatus::from_result::<String>("ctrl+shift+r", Ok(())),
+        ];
+
+        let statuses = collect_shortcut_statuses(&[SHOW_MAIN_SHORTCUT], &recorded);
+
+        assert!(statuses[0].active);
+        assert_eq!(statuses[0].error, None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Single-instance activation
+//!
+//! Decides what the running instance does when QuickConnect is launched again.
+//!
+//! # Why this exists
+//! By default a second launch restores whichever window was hidden last. Users
+//! who mostly quick-connect want the search window instead, and a second launch
+//! with `--connect` should start that connection rather than show anything.
+//!
+//! # Why separate
+//! The decision only depends on the setting, the forwarded connect target and
+//! the last hidden window, so it is unit tested here; `run()` applies it.
+
+use serde::{Deserialize, Serialize};
+
+/// Window shown when a second instance is launched without `--connect`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SingleInstanceTarget {
+    /// Whichever window was hidden most recently
+    #[default]
+    LastHidden,
+    /// The main search window
+    Main,
+    /// The login window
+    Login,
+}
+
+/// What the running instance should do for a second launch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SingleInstanceAction {
+    /// Launch a connection to the host forwarded with `--connect`
+    Connect(String),
+    /// Restore and focus the window with this label
+    ShowWindow(String),
+}
+
+/// Chooses the action for a second launch
+///
+/// A forwarded `--connect` target always wins; otherwise the window comes from
+/// `target`.
+///
+/// # Arguments
+/// * `connect_target` - Hostname from the second instance's `--connect` argument
+/// * `target` - The `single_instance_target` setting
+/// * `last_hidden` - Label of the window hidden most recently
+pub fn single_instance_action(
+    connect_target: Option<String>,
+    target: SingleInstanceTarget,
+    last_hidden: &str,
+) -> SingleInstanceAction {
+    if let Some(hostname) = connect_target {
+        return SingleInstanceAction::Connect(hostname);
+    }
+
+    let label = match target {
+        SingleInstanceTarget::LastHidden => last_hidden,
+        SingleInstanceTarget::Main => "main",
+        SingleInstanceTarget::Login => "login",
+    };
+    SingleInstanceAction::ShowWindow(label.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_hidden_restores_last_hidden_window() {
+        assert_eq!(
+            single_instance_action(None, SingleInstanceTarget::LastHidden, "hosts"),
+            SingleInstanceAction::ShowWindow("hosts".to_string())
+        );
+        assert_eq!(
+            single_instance_action(None, SingleInstanceTarget::LastHidden, "login"),
+            SingleInstanceAction::ShowWindow("login".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fixed_targets_ignore_last_hidden_window() {
+        assert_eq!(
+            single_instance_action(None, SingleInstanceTarget::Main, "hosts"),
+            SingleInstanceAction::ShowWindow("main".to_string())
+        );
+        assert_eq!(
+            single_instance_action(None, SingleInstanceTarget::Login, "main"),
+            SingleInstanceAction::ShowWindow("login".to_string())
+        );
+    }
+
+    #[test]
+    fn test_connect_target_takes_precedence() {
+        for target in [
+            SingleInstanceTarget::LastHidden,
+            SingleInstanceTarget::Main,
+            SingleInstanceTarget::Login,
+        ] {
+            assert_eq!(
+                single_instance_action(Some("server01.domain.com".to_string()), target, "hosts"),
+                SingleInstanceAction::Connect("server01.domain.com".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_single_instance_target_serde_names() {
+        assert_eq!(serde_json::to_string(&SingleInstanceTarget::LastHidden).unwrap(), "\"last_hidden\"");
+        assert_eq!(serde_json::from_str::<SingleInstanceTarget>("\"main\"").unwrap(), SingleInstanceTarget::Main);
+        assert_eq!(serde_json::from_str::<SingleInstanceTarget>("\"login\"").unwrap(), SingleInstanceTarget::Login);
+    }
+}
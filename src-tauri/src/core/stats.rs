@@ -0,0 +1,264 @@
+//! Dashboard statistics
+//!
+//! Aggregates the host list and stored credentials into the figures shown on
+//! the dashboard: host totals, group sizes, credential coverage and usage.
+//!
+//! # Why separate
+//! The aggregation takes the hosts, the credential hostnames and the current
+//! time as parameters, so each figure can be unit tested without the hosts
+//! file, Credential Manager or a clock.
+
+use crate::Host;
+use chrono::{DateTime, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Window used for `connected_last_7_days`
+pub const RECENT_CONNECTION_DAYS: i64 = 7;
+
+/// `last_connected` format written by QuickConnect (UK, local time)
+const UK_TIMESTAMP_FORMAT: &str = "%d/%m/%Y %H:%M:%S";
+
+/// ISO 8601 formats without an offset, accepted from older or hand-edited files
+const ISO_TIMESTAMP_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// The host with the most connections
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MostUsedHost {
+    pub hostname: String,
+    pub connect_count: u32,
+}
+
+/// Aggregate figures for the dashboard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DashboardStats {
+    pub total_hosts: usize,
+    /// Number of hosts in each named group
+    pub hosts_per_group: BTreeMap<String, usize>,
+    /// Hosts with no group
+    pub ungrouped_hosts: usize,
+    /// Hosts with stored `TERMSRV/` credentials
+    pub hosts_with_credentials: usize,
+    /// Hosts whose `last_connected` is within the last `RECENT_CONNECTION_DAYS` days
+    pub connected_last_7_days: usize,
+    /// `None` when no host has been connected to
+    pub most_used_host: Option<MostUsedHost>,
+}
+
+/// Parses a `last_connected` value
+///
+/// Accepts the UK format QuickConnect writes (`13/12/2025 14:30:00`) and ISO
+/// 8601, with or without an offset, so files written during the move between
+/// formats still count. An offset is dropped, keeping the local wall time.
+///
+/// # Returns
+/// * `None` for empty or unrecognised values
+pub fn parse_last_connected(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if let Ok(timestamp) = NaiveDateTime::parse_from_str(value, UK_TIMESTAMP_FORMAT) {
+        return Some(timestamp);
+    }
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.naive_local());
+    }
+    ISO_TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+}
+
+/// Computes the dashboard figures
+///
+/// # Arguments
+/// * `hosts` - All hosts
+/// * `credential_hostnames` - Hostnames with stored `TERMSRV/` credentials (see
+///   `list_credential_targets`); matched case-insensitively, orphans ignored
+/// * `now` - Current local time
+pub fn compute_dashboard_stats(
+    hosts: &[Host],
+    credential_hostnames: &[String],
+    now: NaiveDateTime,
+) -> DashboardStats {
+    let mut hosts_per_group: BTreeMap<String, usize> = BTreeMap::new();
+    let mut ungrouped_hosts = 0;
+    for host in hosts {
+        match host.group.as_deref().map(str::trim).filter(|group| !group.is_empty()) {
+            Some(group) => *hosts_per_group.entry(group.to_string()).or_insert(0) += 1,
+            None => ungrouped_hosts += 1,
+        }
+    }
+
+    let hosts_with_credentials = hosts
+        .iter()
+        .filter(|host| {
+            credential_hostnames
+                .iter()
+                .any(|hostname| hostname.eq_ignore_ascii_case(&host.hostname))
+        })
+        .count();
+
+    let cutoff = now - chrono::Duration::days(RECENT_CONNECTION_DAYS);
+    let connected_last_7_days = hosts
+        .iter()
+        .filter_map(|host| host.last_connected.as_deref().and_then(parse_last_connected))
+        .filter(|connected| *connected >= cutoff && *connected <= now)
+        .count();
+
+    // First host wins a tie, so the result follows the hosts file order
+    let mut most_used_host: Option<MostUsedHost> = None;
+    for host in hosts.iter().filter(|host| host.connect_count > 0) {
+        if most_used_host
+            .as_ref()
+            .is_none_or(|best| host.connect_count > best.connect_count)
+        {
+            most_used_host = Some(MostUsedHost {
+                hostname: host.hostname.clone(),
+                connect_count: host.connect_count,
+            });
+        }
+    }
+
+    DashboardStats {
+        total_hosts: hosts.len(),
+        hosts_per_group,
+        ungrouped_hosts,
+        hosts_with_credentials,
+        connected_last_7_days,
+        most_used_host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(hostname: &str, group: Option<&str>, last_connected: Option<&str>, connect_count: u32) -> Host {
+        Host {
+            hostname: hostname.to_string(),
+            group: group.map(str::to_string),
+            last_connected: last_connected.map(str::to_string),
+            connect_count,
+            ..Default::default()
+        }
+    }
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("15/03/2026 12:00:00", UK_TIMESTAMP_FORMAT).unwrap()
+    }
+
+    fn sample_hosts() -> Vec<Host> {
+        vec![
+            host("web01.domain.com", Some("Web"), Some("14/03/2026 09:00:00"), 5),
+            host("web02.domain.com", Some("Web"), Some("2026-03-10T08:30:00"), 12),
+            host("sql01.domain.com", Some("Database"), Some("01/01/2026 10:00:00"), 12),
+            host("dc01.domain.com", None, Some("2026-03-12T18:00:00+01:00"), 0),
+            host("new01.domain.com", Some("  "), None, 0),
+        ]
+    }
+
+    #[test]
+    fn test_parse_last_connected_formats() {
+        let expected = NaiveDateTime::parse_from_str("13/12/2025 14:30:00", UK_TIMESTAMP_FORMAT).unwrap();
+
+        assert_eq!(parse_last_connected("13/12/2025 14:30:00"), Some(expected));
+        assert_eq!(parse_last_connected("2025-12-13T14:30:00"), Some(expected));
+        assert_eq!(parse_last_connected("2025-12-13 14:30:00"), Some(expected));
+        assert_eq!(parse_last_connected("2025-12-13T14:30:00.250"), Some(expected + chrono::Duration::milliseconds(250)));
+        assert_eq!(parse_last_connected("2025-12-13T14:30:00Z"), Some(expected));
+        assert_eq!(parse_last_connected("2025-12-13T14:30:00+02:00"), Some(expected));
+        assert_eq!(parse_last_connected("  13/12/2025 14:30:00  "), Some(expected));
+    }
+
+    #[test]
+    fn test_parse_last_connected_rejects_invalid() {
+        assert_eq!(parse_last_connected(""), None);
+        assert_eq!(parse_last_connected("   "), None);
+        assert_eq!(parse_last_connected("yesterday"), None);
+        // Month 13 is only valid day-first
+        assert_eq!(parse_last_connected("13/13/2025 14:30:00"), None);
+    }
+
+    #[test]
+    fn test_dashboard_total_hosts() {
+        let stats = compute_dashboard_stats(&sample_hosts(), &[], now());
+        assert_eq!(stats.total_hosts, 5);
+
+        let empty = compute_dashboard_stats(&[], &[], now());
+        assert_eq!(empty.total_hosts, 0);
+        assert!(empty.hosts_per_group.is_empty());
+        assert_eq!(empty.most_used_host, None);
+    }
+
+    #[test]
+    fn test_dashboard_hosts_per_group() {
+        let stats = compute_dashboard_stats(&sample_hosts(), &[], now());
+
+        assert_eq!(stats.hosts_per_group.len(), 2);
+        assert_eq!(stats.hosts_per_group.get("Web"), Some(&2));
+        assert_eq!(stats.hosts_per_group.get("Database"), Some(&1));
+        // No group and a blank group both count as ungrouped
+        assert_eq!(stats.ungrouped_hosts, 2);
+    }
+
+    #[test]
+    fn test_dashboard_hosts_with_credentials() {
+        let credentials = vec![
+            "WEB01.domain.com".to_string(),
+            "sql01.domain.com".to_string(),
+            "removed.domain.com".to_string(),
+        ];
+
+        let stats = compute_dashboard_stats(&sample_hosts(), &credentials, now());
+
+        // Case-insensitive match; the orphaned credential isn't counted
+        assert_eq!(stats.hosts_with_credentials, 2);
+    }
+
+    #[test]
+    fn test_dashboard_connected_last_7_days() {
+        let stats = compute_dashboard_stats(&sample_hosts(), &[], now());
+
+        // web01 (UK), web02 and dc01 (ISO); sql01 is too old, new01 never connected
+        assert_eq!(stats.connected_last_7_days, 3);
+    }
+
+    #[test]
+    fn test_dashboard_connected_window_boundaries() {
+        let hosts = vec![
+            host("edge.domain.com", None, Some("08/03/2026 12:00:00"), 0),
+            host("old.domain.com", None, Some("08/03/2026 11:59:59"), 0),
+            host("future.domain.com", None, Some("16/03/2026 12:00:00"), 0),
+            host("garbage.domain.com", None, Some("not a date"), 0),
+        ];
+
+        let stats = compute_dashboard_stats(&hosts, &[], now());
+
+        assert_eq!(stats.connected_last_7_days, 1);
+    }
+
+    #[test]
+    fn test_dashboard_most_used_host() {
+        let stats = compute_dashboard_stats(&sample_hosts(), &[], now());
+
+        // web02 and sql01 tie on 12; the first in file order wins
+        assert_eq!(
+            stats.most_used_host,
+            Some(MostUsedHost {
+                hostname: "web02.domain.com".to_string(),
+                connect_count: 12,
+            })
+        );
+    }
+
+    #[test]
+    fn test_dashboard_most_used_host_none_when_never_connected() {
+        let hosts = vec![host("a.domain.com", None, None, 0), host("b.domain.com", None, None, 0)];
+
+        let stats = compute_dashboard_stats(&hosts, &[], now());
+
+        assert_eq!(stats.most_used_host, None);
+    }
+}
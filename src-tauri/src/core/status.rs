@@ -0,0 +1,1325 @@
+//! Host status probing
+//!
+//! Determines whether hosts are reachable on the RDP port.
+//!
+//! # Why this exists
+//! Status checks are used by the hosts list, recent connections, and the tray.
+//! Centralizing the probe keeps the "online"/"offline"/"unknown" semantics
+//! identical everywhere.
+//!
+//! # Why separate
+//! Batch probing takes the probe as a parameter so the fan-out and deadline
+//! handling can be unit tested without touching the network.
+
+use crate::core::clock::Clock;
+use crate::infra::debug_log;
+use crate::{Host, RecentConnection, RecentConnectionStatus};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::HashMap;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Standard RDP port
+pub const RDP_PORT: u16 = 3389;
+
+/// Timeout for a single TCP connection attempt
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Status reported when a host could not be probed (DNS failure, deadline hit, ...)
+pub const STATUS_UNKNOWN: &str = "unknown";
+
+/// Delay before the first retry of a failed probe; doubles for each further retry
+pub const RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Most probes `probe_with_retries` makes for one host, whatever was requested
+pub const MAX_PROBE_ATTEMPTS: u8 = 5;
+
+/// Longest pause between retries (the backoff stops doubling here)
+pub const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+/// How long a cached "online"/"offline" result is reused before probing again
+pub const STATUS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Hosts probed at once by a full status refresh
+pub const REFRESH_CONCURRENCY: usize = 16;
+
+/// Checks if a host is online by attempting to connect to the RDP port.
+///
+/// A port in the host entry (`server01:3390`) is used instead of 3389.
+///
+/// # Returns
+/// * `"online"` - The RDP port accepted the connection
+/// * `"offline"` - Hostname resolved but the connection failed or timed out
+/// * `"unknown"` - Hostname could not be resolved
+pub fn probe_host(hostname: &str, timeout: Duration) -> String {
+    probe_host_on_port(hostname, rdp_port_of(hostname), None, timeout)
+}
+
+/// Returns the port a host entry connects to: its explicit port, else `RDP_PORT`
+pub fn rdp_port_of(hostname: &str) -> u16 {
+    crate::core::hosts::split_host_port(hostname)
+        .1
+        .unwrap_or(RDP_PORT)
+}
+
+/// Checks if a host is online on the RDP port, falling back to other ports.
+///
+/// For hosts fronted by a gateway or load balancer on a non-standard port.
+/// The host entry's own port, if it has one, is tried first. See [`probe_ports`] for how the results are combined, and
+/// [`probe_host_on_port`] for `source_address`.
+pub fn probe_host_with_fallbacks(
+    hostname: &str,
+    fallback_ports: &[u16],
+    source_address: Option<IpAddr>,
+    timeout: Duration,
+) -> String {
+    probe_ports(rdp_port_of(hostname), fallback_ports, |port| {
+        probe_host_on_port(hostname, port, source_address, timeout)
+    })
+}
+
+/// Probes the primary port, then each fallback port in order, until one succeeds.
+///
+/// # Arguments
+/// * `primary` - Port tried first (normally `RDP_PORT`)
+/// * `fallback_ports` - Ports tried in order when the primary is offline
+/// * `probe` - Probe for a single port (injectable for tests)
+///
+/// # Returns
+/// * `"online"` - Any port accepted the connection
+/// * `"offline"` - Every port failed
+/// * `"unknown"` - The hostname could not be resolved (other ports aren't tried)
+pub fn probe_ports<F>(primary: u16, fallback_ports: &[u16], probe: F) -> String
+where
+    F: Fn(u16) -> String,
+{
+    let mut status = probe(primary);
+    for &port in fallback_ports.iter().filter(|&&port| port != primary) {
+        if status != "offline" {
+            break;
+        }
+        status = probe(port);
+    }
+    status
+}
+
+/// Checks if a host is online by attempting to connect to the given port.
+///
+/// `port` replaces any port in the host entry; IPv6 literals may be given
+/// with or without brackets.
+///
+/// With a `source_address` the connection is made from that local address
+/// (to a resolved address of the same IP version when there is one);
+/// otherwise the OS picks the interface.
+///
+/// # Returns
+/// * `"online"` - The port accepted the connection
+/// * `"offline"` - Hostname resolved but the connection failed or timed out
+/// * `"unknown"` - Hostname could not be resolved
+pub fn probe_host_on_port(
+    hostname: &str,
+    port: u16,
+    source_address: Option<IpAddr>,
+    timeout: Duration,
+) -> String {
+    debug_log(
+        "DEBUG",
+        "STATUS_CHECK",
+        &format!("Checking status for host: {} (port {})", hostname, port),
+        None,
+    );
+
+    // Resolve hostname to IP address for TCP connection
+    let (host, _) = crate::core::hosts::split_host_port(hostname);
+    let socket_addrs: Vec<_> = match (host, port).to_socket_addrs() {
+        Ok(addrs) => addrs.collect(),
+        Err(e) => {
+            // DNS resolution failed - host doesn't exist or network issue
+            debug_log(
+                "DEBUG",
+                "STATUS_CHECK",
+                &format!("Failed to resolve hostname {}: {}", hostname, e),
+                Some(&e.to_string()),
+            );
+            return STATUS_UNKNOWN.to_string();
+        }
+    };
+
+    if socket_addrs.is_empty() {
+        debug_log(
+            "DEBUG",
+            "STATUS_CHECK",
+            &format!("No addresses resolved for hostname: {}", hostname),
+            None,
+        );
+        return STATUS_UNKNOWN.to_string();
+    }
+
+    let target = source_address
+        .and_then(|source| socket_addrs.iter().find(|addr| addr.is_ipv4() == source.is_ipv4()))
+        .unwrap_or(&socket_addrs[0]);
+
+    // Attempt TCP connection with a timeout so unreachable hosts don't hang the UI
+    match connect_from(*target, source_address, timeout) {
+        Ok(_) => {
+            debug_log(
+                "DEBUG",
+                "STATUS_CHECK",
+                &format!("Host {} is online (port {} open)", hostname, port),
+                None,
+            );
+            "online".to_string()
+        }
+        Err(e) => {
+            debug_log(
+                "DEBUG",
+                "STATUS_CHECK",
+                &format!("Host {} is offline or unreachable: {}", hostname, e),
+                Some(&e.to_string()),
+            );
+            "offline".to_string()
+        }
+    }
+}
+
+/// Opens a TCP connection to `remote`, from `source_address` when given
+///
+/// Without a source address this is `TcpStream::connect_timeout` and the OS
+/// picks the interface.
+pub fn connect_from(
+    remote: SocketAddr,
+    source_address: Option<IpAddr>,
+    timeout: Duration,
+) -> std::io::Result<TcpStream> {
+    let Some(source) = source_address else {
+        return TcpStream::connect_timeout(&remote, timeout);
+    };
+
+    let socket = bound_socket(remote, source)?;
+    socket.connect_timeout(&remote.into(), timeout)?;
+    Ok(socket.into())
+}
+
+/// Creates a TCP socket for reaching `remote`, bound to `source` on any port
+fn bound_socket(remote: SocketAddr, source: IpAddr) -> std::io::Result<Socket> {
+    if remote.is_ipv4() != source.is_ipv4() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("source address {} can't reach {}", source, remote),
+        ));
+    }
+
+    let socket = Socket::new(Domain::for_address(remote), Type::STREAM, Some(Protocol::TCP))?;
+    socket.bind(&SocketAddr::new(source, 0).into())?;
+    Ok(socket)
+}
+
+/// Probes several hosts concurrently, returning within `deadline`.
+///
+/// Each host is probed on its own thread. Results that have not arrived by the
+/// deadline are reported as `"unknown"`; their threads are left to finish in
+/// the background.
+///
+/// # Arguments
+/// * `hostnames` - Hosts to probe
+/// * `probe` - Probe for a single host (normally `probe_host`)
+/// * `deadline` - Maximum time to wait for all results
+///
+/// # Returns
+/// Statuses in the same order as `hostnames`.
+pub fn probe_hosts<F>(hostnames: &[String], probe: F, deadline: Duration) -> Vec<String>
+where
+    F: Fn(&str) -> String + Send + Sync + Clone + 'static,
+{
+    probe_hosts_within(hostnames, probe, deadline)
+        .into_iter()
+        .map(|status| status.unwrap_or_else(|| STATUS_UNKNOWN.to_string()))
+        .collect()
+}
+
+/// Probes several hosts concurrently, like [`probe_hosts`], but reports the
+/// hosts that missed the deadline as `None` instead of `"unknown"`.
+///
+/// # Returns
+/// Statuses in the same order as `hostnames`.
+pub fn probe_hosts_within<F>(hostnames: &[String], probe: F, deadline: Duration) -> Vec<Option<String>>
+where
+    F: Fn(&str) -> String + Send + Sync + Clone + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+
+    for (index, hostname) in hostnames.iter().enumerate() {
+        let tx = tx.clone();
+        let probe = probe.clone();
+        let hostname = hostname.clone();
+        std::thread::spawn(move || {
+            // The receiver may be gone if the deadline passed; that's fine
+            let _ = tx.send((index, probe(&hostname)));
+        });
+    }
+    drop(tx);
+
+    let mut statuses = vec![None; hostnames.len()];
+    let started = Instant::now();
+    let mut received = 0;
+
+    while received < hostnames.len() {
+        let remaining = deadline.saturating_sub(started.elapsed());
+        match rx.recv_timeout(remaining) {
+            Ok((index, status)) => {
+                statuses[index] = Some(status);
+                received += 1;
+            }
+            Err(_) => {
+                debug_log(
+                    "DEBUG",
+                    "STATUS_CHECK",
+                    &format!(
+                        "Status deadline reached with {} of {} hosts probed",
+                        received,
+                        hostnames.len()
+                    ),
+                    None,
+                );
+                break;
+            }
+        }
+    }
+
+    statuses
+}
+
+/// Probes several hosts, at most `concurrency` at a time, returning within `deadline`.
+///
+/// Unlike [`probe_hosts_within`], which starts a thread per host, a fixed
+/// pool of workers takes hosts from a queue, so refreshing a large host list
+/// doesn't open hundreds of connections at once. Hosts not probed by the
+/// deadline are reported as `None`; workers stop taking hosts once it passes.
+///
+/// # Arguments
+/// * `hostnames` - Hosts to probe
+/// * `concurrency` - Maximum probes at once (0 is treated as 1)
+/// * `probe` - Probe for a single host
+/// * `deadline` - Maximum time to wait for all results
+///
+/// # Returns
+/// Statuses in the same order as `hostnames`.
+pub fn probe_hosts_bounded<F>(
+    hostnames: &[String],
+    concurrency: usize,
+    probe: F,
+    deadline: Duration,
+) -> Vec<Option<String>>
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    let mut statuses = vec![None; hostnames.len()];
+    if hostnames.is_empty() {
+        return statuses;
+    }
+
+    let queue: Vec<(usize, String)> = hostnames.iter().cloned().enumerate().collect();
+    let queue = Arc::new(Mutex::new(queue.into_iter()));
+    let probe = Arc::new(probe);
+    let (tx, rx) = mpsc::channel();
+
+    for _ in 0..concurrency.clamp(1, hostnames.len()) {
+        let queue = Arc::clone(&queue);
+        let probe = Arc::clone(&probe);
+        let tx = tx.clone();
+        std::thread::spawn(move || loop {
+            let next = queue.lock().map(|mut queue| queue.next()).unwrap_or(None);
+            let Some((index, hostname)) = next else {
+                break;
+            };
+            // The receiver is gone once the deadline passes; stop taking work
+            if tx.send((index, probe(&hostname))).is_err() {
+                break;
+            }
+        });
+    }
+    drop(tx);
+
+    let started = Instant::now();
+    for _ in 0..hostnames.len() {
+        let remaining = deadline.saturating_sub(started.elapsed());
+        let Ok((index, status)) = rx.recv_timeout(remaining) else {
+            break;
+        };
+        statuses[index] = Some(status);
+    }
+    statuses
+}
+
+/// Probes a host up to `attempts` times, backing off exponentially between failures.
+///
+/// Smooths over servers that intermittently drop connections: the host is
+/// reported online if any attempt succeeds. Resolution failures ("unknown")
+/// are returned immediately since retrying won't help.
+///
+/// # Arguments
+/// * `hostname` - Host to probe
+/// * `attempts` - Maximum number of probes, clamped to 1..=`MAX_PROBE_ATTEMPTS`
+/// * `probe` - Probe for a single attempt
+/// * `sleep` - Waits between attempts (injectable for tests)
+///
+/// # Returns
+/// * `"online"` - Any attempt succeeded
+/// * `"offline"` - Every attempt failed
+/// * `"unknown"` - The hostname could not be resolved
+pub fn probe_with_retries<F, S>(hostname: &str, attempts: u8, probe: F, sleep: S) -> String
+where
+    F: Fn(&str) -> String,
+    S: Fn(Duration),
+{
+    let attempts = attempts.clamp(1, MAX_PROBE_ATTEMPTS);
+    let mut backoff = RETRY_BACKOFF;
+
+    for attempt in 1..=attempts {
+        let status = probe(hostname);
+        if status != "offline" || attempt == attempts {
+            return status;
+        }
+
+        debug_log(
+            "DEBUG",
+            "STATUS_CHECK",
+            &format!(
+                "Probe {}/{} for {} failed, retrying in {:?}",
+                attempt, attempts, hostname, backoff
+            ),
+            None,
+        );
+        sleep(backoff);
+        backoff = backoff.saturating_mul(2).min(MAX_RETRY_BACKOFF);
+    }
+
+    "offline".to_string()
+}
+
+/// Augments recent connections with their current reachability.
+///
+/// # Arguments
+/// * `connections` - Recent connections, in display order
+/// * `probe` - Probe for a single host
+/// * `deadline` - Maximum time to wait for all probes
+pub fn recent_connections_with_status<F>(
+    connections: Vec<RecentConnection>,
+    probe: F,
+    deadline: Duration,
+) -> Vec<RecentConnectionStatus>
+where
+    F: Fn(&str) -> String + Send + Sync + Clone + 'static,
+{
+    let hostnames: Vec<String> = connections.iter().map(|c| c.hostname.clone()).collect();
+    let statuses = probe_hosts(&hostnames, probe, deadline);
+
+    connections
+        .into_iter()
+        .zip(statuses)
+        .map(|(connection, status)| RecentConnectionStatus {
+            hostname: connection.hostname,
+            description: connection.description,
+            timestamp: connection.timestamp,
+            status,
+        })
+        .collect()
+}
+
+/// Recent probe results, reused until they are `ttl` old
+///
+/// Only "online" and "offline" are cached; "unknown" may just mean a probe
+/// missed its deadline, so those hosts are probed again next time.
+#[derive(Debug)]
+pub struct StatusCache {
+    ttl: Duration,
+    entries: HashMap<String, (String, DateTime<Local>)>,
+}
+
+impl StatusCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the cached status for a host if it is still fresh at `now`
+    ///
+    /// A result recorded after `now` (the clock moved back) counts as stale.
+    pub fn get(&self, hostname: &str, now: DateTime<Local>) -> Option<&str> {
+        self.entries
+            .get(&hostname.to_lowercase())
+            .filter(|(_, at)| {
+                now.signed_duration_since(*at)
+                    .to_std()
+                    .is_ok_and(|age| age < self.ttl)
+            })
+            .map(|(status, _)| status.as_str())
+    }
+
+    /// Records a probe result taken at `now`
+    pub fn insert(&mut self, hostname: &str, status: &str, now: DateTime<Local>) {
+        let key = hostname.to_lowercase();
+        if status == STATUS_UNKNOWN {
+            self.entries.remove(&key);
+        } else {
+            self.entries.insert(key, (status.to_string(), now));
+        }
+    }
+}
+
+/// Looks up the status of each host within a time budget
+///
+/// Fresh cached results are used as-is; the other hosts are probed,
+/// `REFRESH_CONCURRENCY` at a time, and the results cached. Hosts whose probe misses `deadline`
+/// are left out, so callers can tell "not checked in time" from `"unknown"`.
+///
+/// # Arguments
+/// * `hostnames` - Hosts to look up
+/// * `cache` - Cached results; updated with the new probes
+/// * `probe` - Probe for a single host
+/// * `deadline` - Maximum time to wait for the probes
+/// * `clock` - Time source for cache freshness, read once before probing
+///
+/// # Returns
+/// Statuses keyed by lowercased hostname.
+pub fn statuses_within<F>(
+    hostnames: &[String],
+    cache: &mut StatusCache,
+    probe: F,
+    deadline: Duration,
+    clock: &dyn Clock,
+) -> HashMap<String, String>
+where
+    F: Fn(&str) -> String + Send + Sync + Clone + 'static,
+{
+    let now = clock.now();
+    let mut statuses = HashMap::new();
+    let mut stale = Vec::new();
+    for hostname in hostnames {
+        match cache.get(hostname, now) {
+            Some(status) => {
+                statuses.insert(hostname.to_lowercase(), status.to_string());
+            }
+            None => stale.push(hostname.clone()),
+        }
+    }
+
+    let probed = probe_hosts_bounded(&stale, REFRESH_CONCURRENCY, probe, deadline);
+    for (hostname, status) in stale.iter().zip(probed) {
+        if let Some(status) = status {
+            cache.insert(hostname, &status, now);
+            statuses.insert(hostname.to_lowercase(), status);
+        }
+    }
+    statuses
+}
+
+/// Hosts bucketed by reachability
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GroupedHosts {
+    pub online: Vec<Host>,
+    pub offline: Vec<Host>,
+    pub unknown: Vec<Host>,
+}
+
+/// Buckets hosts by status, probing only those without a fresh cached result
+///
+/// At most `REFRESH_CONCURRENCY` probes run at once. Hosts keep their
+/// relative order within each bucket; hosts not probed by `deadline` go in
+/// `unknown`.
+///
+/// # Arguments
+/// * `hosts` - Hosts to bucket
+/// * `cache` - Cached results; updated with the new probes
+/// * `probe` - Probe for a single host
+/// * `deadline` - Maximum time to wait for the probes
+/// * `clock` - Time source for cache freshness, read once before probing
+pub fn group_hosts_by_status<F>(
+    hosts: Vec<Host>,
+    cache: &mut StatusCache,
+    probe: F,
+    deadline: Duration,
+    clock: &dyn Clock,
+) -> GroupedHosts
+where
+    F: Fn(&str) -> String + Send + Sync + Clone + 'static,
+{
+    let now = clock.now();
+    let mut statuses: Vec<Option<String>> = hosts
+        .iter()
+        .map(|host| cache.get(&host.hostname, now).map(str::to_string))
+        .collect();
+
+    let stale: Vec<usize> = (0..hosts.len()).filter(|&index| statuses[index].is_none()).collect();
+    let hostnames: Vec<String> = stale.iter().map(|&index| hosts[index].hostname.clone()).collect();
+    let probed = probe_hosts_bounded(&hostnames, REFRESH_CONCURRENCY, probe, deadline);
+    for (index, status) in stale.into_iter().zip(probed) {
+        // A host that missed the deadline is shown as unknown but not cached,
+        // so the next lookup probes it again
+        if let Some(status) = &status {
+            cache.insert(&hosts[index].hostname, status, now);
+        }
+        statuses[index] = status;
+    }
+
+    let mut grouped = GroupedHosts::default();
+    for (host, status) in hosts.into_iter().zip(statuses) {
+        match status.as_deref() {
+            Some("online") => grouped.online.push(host),
+            Some("offline") => grouped.offline.push(host),
+            _ => grouped.unknown.push(host),
+        }
+    }
+    grouped
+}
+
+/// Number of hosts in each status after a refresh
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StatusSummary {
+    pub online: usize,
+    pub offline: usize,
+    pub unknown: usize,
+}
+
+impl StatusSummary {
+    /// Tallies batch probe results; hosts not probed in time count as unknown
+    pub fn from_results(results: &[Option<String>]) -> Self {
+        let mut summary = Self::default();
+        for status in results {
+            match status.as_deref() {
+                Some("online") => summary.online += 1,
+                Some("offline") => summary.offline += 1,
+                _ => summary.unknown += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// Probes every host, ignoring cached results, and caches the new ones
+///
+/// The cache is only locked to store the results, so the tray and other
+/// lookups aren't blocked while the probes run.
+///
+/// # Arguments
+/// * `hostnames` - Hosts to refresh
+/// * `cache` - Shared cache updated with the results
+/// * `batch_probe` - Probes all the hosts, returning statuses in the same order
+///   (`None` for hosts not probed in time); normally [`probe_hosts_bounded`]
+/// * `clock` - Time the results are recorded at
+pub fn refresh_statuses<B>(
+    hostnames: &[String],
+    cache: &Mutex<StatusCache>,
+    batch_probe: B,
+    clock: &dyn Clock,
+) -> StatusSummary
+where
+    B: FnOnce(&[String]) -> Vec<Option<String>>,
+{
+    let results = batch_probe(hostnames);
+    let now = clock.now();
+
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    for (hostname, status) in hostnames.iter().zip(&results) {
+        if let Some(status) = status {
+            cache.insert(hostname, status, now);
+        }
+    }
+    StatusSummary::from_results(&results)
+}
+
+/// Payload of the `host-status-changed` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusChange {
+    pub hostname: String,
+    /// Status from the previous poll
+    pub previous: String,
+    /// Status from this poll
+    pub status: String,
+}
+
+/// Compares a poll's results with the last known statuses.
+///
+/// Only hosts whose status differs from the previous poll produce a change.
+/// Hosts seen for the first time have no previous status to compare against
+/// and produce none; the frontend already checks them when it loads the list.
+///
+/// # Arguments
+/// * `known` - Last known status per hostname; updated to the new results,
+///   with hosts no longer polled removed
+/// * `results` - `(hostname, status)` pairs from this poll
+pub fn diff_statuses(
+    known: &mut HashMap<String, String>,
+    results: Vec<(String, String)>,
+) -> Vec<StatusChange> {
+    let mut changes = Vec::new();
+    let mut current = HashMap::with_capacity(results.len());
+
+    for (hostname, status) in results {
+        if let Some(previous) = known.get(&hostname) {
+            if *previous != status {
+                changes.push(StatusChange {
+                    hostname: hostname.clone(),
+                    previous: previous.clone(),
+                    status: status.clone(),
+                });
+            }
+        }
+        current.insert(hostname, status);
+    }
+
+    *known = current;
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::{MockClock, SystemClock};
+    use crate::RecentConnections;
+    use std::cell::{Cell, RefCell};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn fake_probe(hostname: &str) -> String {
+        match hostname {
+            "up.domain.com" => "online".to_string(),
+            "down.domain.com" => "offline".to_string(),
+            _ => STATUS_UNKNOWN.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_bound_socket_uses_source_address() {
+        let remote: SocketAddr = "127.0.0.1:3389".parse().unwrap();
+
+        let socket = bound_socket(remote, "127.0.0.1".parse().unwrap()).expect("Bind should succeed");
+
+        let local = socket.local_addr().unwrap().as_socket().unwrap();
+        assert_eq!(local.ip(), "127.0.0.1".parse::<IpAddr>().unwrap());
+        assert_ne!(local.port(), 0);
+    }
+
+    #[test]
+    fn test_bound_socket_rejects_other_ip_version() {
+        let remote: SocketAddr = "127.0.0.1:3389".parse().unwrap();
+
+        let error = bound_socket(remote, "::1".parse().unwrap()).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_connect_from_source_and_default() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let remote = listener.local_addr().unwrap();
+
+        let bound = connect_from(remote, Some("127.0.0.1".parse().unwrap()), PROBE_TIMEOUT)
+            .expect("Connecting from the source address should succeed");
+        assert_eq!(bound.local_addr().unwrap().ip(), "127.0.0.1".parse::<IpAddr>().unwrap());
+
+        let default = connect_from(remote, None, PROBE_TIMEOUT).expect("Default connect should succeed");
+        assert_eq!(default.peer_addr().unwrap(), remote);
+    }
+
+    #[test]
+    fn test_rdp_port_of_host_entry() {
+        assert_eq!(rdp_port_of("server01"), RDP_PORT);
+        assert_eq!(rdp_port_of("server01:3390"), 3390);
+        assert_eq!(rdp_port_of("[fe80::1]:3390"), 3390);
+        assert_eq!(rdp_port_of("fe80::1"), RDP_PORT);
+    }
+
+    #[test]
+    fn test_probe_uses_port_from_host_entry() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert_eq!(probe_host(&format!("127.0.0.1:{}", port), PROBE_TIMEOUT), "online");
+        // An explicit port replaces the entry's
+        assert_eq!(probe_host_on_port("127.0.0.1:1", port, None, PROBE_TIMEOUT), "online");
+    }
+
+    #[test]
+    fn test_probe_hosts_preserves_order() {
+        let hostnames = vec![
+            "down.domain.com".to_string(),
+            "up.domain.com".to_string(),
+            "missing.domain.com".to_string(),
+        ];
+
+        let statuses = probe_hosts(&hostnames, fake_probe, Duration::from_secs(5));
+
+        assert_eq!(statuses, vec!["offline", "online", "unknown"]);
+    }
+
+    #[test]
+    fn test_probe_hosts_deadline_reports_unknown() {
+        let hostnames = vec!["up.domain.com".to_string(), "slow.domain.com".to_string()];
+        let probe = |hostname: &str| {
+            if hostname == "slow.domain.com" {
+                std::thread::sleep(Duration::from_secs(2));
+            }
+            "online".to_string()
+        };
+
+        let started = Instant::now();
+        let statuses = probe_hosts(&hostnames, probe, Duration::from_millis(200));
+
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(statuses, vec!["online", "unknown"]);
+    }
+
+    #[test]
+    fn test_probe_hosts_within_leaves_late_results_out() {
+        let hostnames = vec!["missing.domain.com".to_string(), "slow.domain.com".to_string()];
+        let probe = |hostname: &str| {
+            if hostname == "slow.domain.com" {
+                std::thread::sleep(Duration::from_secs(2));
+            }
+            fake_probe(hostname)
+        };
+
+        let statuses = probe_hosts_within(&hostnames, probe, Duration::from_millis(200));
+
+        assert_eq!(statuses, vec![Some("unknown".to_string()), None]);
+    }
+
+    #[test]
+    fn test_statuses_within_uses_cache_and_skips_timeouts() {
+        let mut cache = StatusCache::new(STATUS_CACHE_TTL);
+        let clock = MockClock::at("2026-03-15 12:00:00");
+        cache.insert("Cached.domain.com", "offline", clock.now());
+        let hostnames = vec![
+            "cached.domain.com".to_string(),
+            "UP.domain.com".to_string(),
+            "slow.domain.com".to_string(),
+        ];
+        let probe = |hostname: &str| {
+            assert_ne!(hostname, "cached.domain.com", "cached hosts are not probed");
+            if hostname == "slow.domain.com" {
+                std::thread::sleep(Duration::from_secs(2));
+            }
+            "online".to_string()
+        };
+
+        let statuses = statuses_within(&hostnames, &mut cache, probe, Duration::from_millis(200), &clock);
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses.get("cached.domain.com").map(String::as_str), Some("offline"));
+        assert_eq!(statuses.get("up.domain.com").map(String::as_str), Some("online"));
+        assert_eq!(cache.get("up.domain.com", clock.now()), Some("online"));
+    }
+
+    #[test]
+    fn test_probe_hosts_empty_list() {
+        let statuses = probe_hosts(&[], fake_probe, Duration::from_secs(1));
+        assert!(statuses.is_empty());
+    }
+
+    #[test]
+    fn test_recent_connections_with_status_from_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let path = temp_dir.path().join("recent_connections.json");
+        std::fs::write(
+            &path,
+            r#"{"connections":[
+                {"hostname":"up.domain.com","description":"Web","timestamp":200},
+                {"hostname":"down.domain.com","description":"DB","timestamp":100}
+            ]}"#,
+        )
+        .expect("Failed to write recents file");
+
+        let json = std::fs::read_to_string(&path).expect("Failed to read recents file");
+        let recent: RecentConnections = serde_json::from_str(&json).expect("Failed to parse recents");
+
+        let result =
+            recent_connections_with_status(recent.connections, fake_probe, Duration::from_secs(5));
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].hostname, "up.domain.com");
+        assert_eq!(result[0].description, "Web");
+        assert_eq!(result[0].timestamp, 200);
+        assert_eq!(result[0].status, "online");
+        assert_eq!(result[1].hostname, "down.domain.com");
+        assert_eq!(result[1].status, "offline");
+    }
+
+    #[test]
+    fn test_probe_with_retries_fail_then_succeed_reports_online() {
+        let calls = Cell::new(0);
+        let sleeps = RefCell::new(Vec::new());
+        let probe = |_: &str| {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 { "offline" } else { "online" }.to_string()
+        };
+
+        let status = probe_with_retries("flaky.domain.com", 5, probe, |d| sleeps.borrow_mut().push(d));
+
+        assert_eq!(status, "online");
+        assert_eq!(calls.get(), 3);
+        assert_eq!(*sleeps.borrow(), vec![RETRY_BACKOFF, RETRY_BACKOFF * 2]);
+    }
+
+    #[test]
+    fn test_probe_with_retries_always_fails_reports_offline() {
+        let calls = Cell::new(0);
+        let sleeps = Cell::new(0);
+        let probe = |_: &str| {
+            calls.set(calls.get() + 1);
+            "offline".to_string()
+        };
+
+        let status = probe_with_retries("down.domain.com", 3, probe, |_| sleeps.set(sleeps.get() + 1));
+
+        assert_eq!(status, "offline");
+        assert_eq!(calls.get(), 3);
+        assert_eq!(sleeps.get(), 2);
+    }
+
+    #[test]
+    fn test_probe_with_retries_unknown_is_not_retried() {
+        let calls = Cell::new(0);
+        let probe = |_: &str| {
+            calls.set(calls.get() + 1);
+            STATUS_UNKNOWN.to_string()
+        };
+
+        let status = probe_with_retries("missing.domain.com", 3, probe, |_| {});
+
+        assert_eq!(status, STATUS_UNKNOWN);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_probe_with_retries_single_attempt_by_default() {
+        let calls = Cell::new(0);
+        let probe = |_: &str| {
+            calls.set(calls.get() + 1);
+            "offline".to_string()
+        };
+
+        assert_eq!(probe_with_retries("down.domain.com", 1, probe, |_| {}), "offline");
+        assert_eq!(probe_with_retries("down.domain.com", 0, probe, |_| {}), "offline");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_probe_with_retries_clamps_attempts_and_backoff() {
+        let calls = Cell::new(0);
+        let sleeps = RefCell::new(Vec::new());
+        let probe = |_: &str| {
+            calls.set(calls.get() + 1);
+            "offline".to_string()
+        };
+
+        let status = probe_with_retries("down.domain.com", u8::MAX, probe, |d| sleeps.borrow_mut().push(d));
+
+        assert_eq!(status, "offline");
+        assert_eq!(calls.get(), MAX_PROBE_ATTEMPTS as usize);
+        assert_eq!(
+            *sleeps.borrow(),
+            vec![RETRY_BACKOFF, RETRY_BACKOFF * 2, RETRY_BACKOFF * 4, MAX_RETRY_BACKOFF]
+        );
+    }
+
+    fn poll(results: &[(&str, &str)]) -> Vec<(String, String)> {
+        results
+            .iter()
+            .map(|(hostname, status)| (hostname.to_string(), status.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_statuses_first_poll_reports_nothing() {
+        let mut known = HashMap::new();
+
+        let changes = diff_statuses(&mut known, poll(&[("web01", "online"), ("db01", "offline")]));
+
+        assert!(changes.is_empty());
+        assert_eq!(known.get("web01").map(String::as_str), Some("online"));
+        assert_eq!(known.get("db01").map(String::as_str), Some("offline"));
+    }
+
+    #[test]
+    fn test_diff_statuses_reports_transitions() {
+        let mut known = HashMap::new();
+        diff_statuses(&mut known, poll(&[("web01", "online"), ("db01", "offline")]));
+
+        let changes = diff_statuses(&mut known, poll(&[("web01", "offline"), ("db01", "online")]));
+
+        assert_eq!(
+            changes,
+            vec![
+                StatusChange {
+                    hostname: "web01".to_string(),
+                    previous: "online".to_string(),
+                    status: "offline".to_string(),
+                },
+                StatusChange {
+                    hostname: "db01".to_string(),
+                    previous: "offline".to_string(),
+                    status: "online".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_statuses_unchanged_reports_nothing() {
+        let mut known = HashMap::new();
+        diff_statuses(&mut known, poll(&[("web01", "online"), ("db01", "offline")]));
+
+        let changes = diff_statuses(&mut known, poll(&[("web01", "online"), ("db01", "offline")]));
+
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_diff_statuses_forgets_removed_hosts() {
+        let mut known = HashMap::new();
+        diff_statuses(&mut known, poll(&[("web01", "online"), ("db01", "offline")]));
+
+        diff_statuses(&mut known, poll(&[("web01", "online")]));
+        assert!(!known.contains_key("db01"));
+
+        // A host that comes back is treated as new rather than as a transition
+        let changes = diff_statuses(&mut known, poll(&[("web01", "online"), ("db01", "online")]));
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_probe_ports_succeeds_on_fallback_port() {
+        let tried = RefCell::new(Vec::new());
+        let probe = |port: u16| {
+            tried.borrow_mut().push(port);
+            if port == 8443 { "online" } else { "offline" }.to_string()
+        };
+
+        let status = probe_ports(RDP_PORT, &[3390, 8443, 9000], probe);
+
+        assert_eq!(status, "online");
+        assert_eq!(*tried.borrow(), vec![RDP_PORT, 3390, 8443]);
+    }
+
+    #[test]
+    fn test_probe_ports_primary_online_skips_fallbacks() {
+        let calls = Cell::new(0);
+        let probe = |_: u16| {
+            calls.set(calls.get() + 1);
+            "online".to_string()
+        };
+
+        assert_eq!(probe_ports(RDP_PORT, &[8443], probe), "online");
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_probe_ports_all_offline() {
+        let tried = RefCell::new(Vec::new());
+        let probe = |port: u16| {
+            tried.borrow_mut().push(port);
+            "offline".to_string()
+        };
+
+        // The primary port isn't probed twice when it's also listed as a fallback
+        assert_eq!(probe_ports(RDP_PORT, &[RDP_PORT, 8443], probe), "offline");
+        assert_eq!(*tried.borrow(), vec![RDP_PORT, 8443]);
+    }
+
+    #[test]
+    fn test_probe_ports_unknown_is_kept() {
+        let calls = Cell::new(0);
+        let probe = |_: u16| {
+            calls.set(calls.get() + 1);
+            STATUS_UNKNOWN.to_string()
+        };
+
+        assert_eq!(probe_ports(RDP_PORT, &[8443, 9000], probe), STATUS_UNKNOWN);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_probe_ports_without_fallbacks() {
+        assert_eq!(probe_ports(RDP_PORT, &[], |_| "offline".to_string()), "offline");
+    }
+
+    fn host(hostname: &str) -> Host {
+        Host {
+            hostname: hostname.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn hostnames(hosts: &[Host]) -> Vec<&str> {
+        hosts.iter().map(|host| host.hostname.as_str()).collect()
+    }
+
+    #[test]
+    fn test_group_hosts_by_status_buckets_mixed_results() {
+        let hosts = vec![
+            host("up.domain.com"),
+            host("down.domain.com"),
+            host("missing.domain.com"),
+            host("UP.domain.com"),
+        ];
+        let mut cache = StatusCache::new(STATUS_CACHE_TTL);
+
+        let probe = |hostname: &str| fake_probe(&hostname.to_lowercase());
+        let grouped = group_hosts_by_status(hosts, &mut cache, probe, Duration::from_secs(5), &SystemClock);
+
+        assert_eq!(hostnames(&grouped.online), vec!["up.domain.com", "UP.domain.com"]);
+        assert_eq!(hostnames(&grouped.offline), vec!["down.domain.com"]);
+        assert_eq!(hostnames(&grouped.unknown), vec!["missing.domain.com"]);
+    }
+
+    #[test]
+    fn test_group_hosts_by_status_reuses_fresh_cache() {
+        let probes = Arc::new(AtomicUsize::new(0));
+        let counting_probe = {
+            let probes = Arc::clone(&probes);
+            move |hostname: &str| {
+                probes.fetch_add(1, Ordering::SeqCst);
+                fake_probe(hostname)
+            }
+        };
+        let hosts = vec![host("up.domain.com"), host("down.domain.com"), host("missing.domain.com")];
+        let mut cache = StatusCache::new(Duration::from_secs(30));
+        let clock = MockClock::at("2026-03-15 12:00:00");
+
+        group_hosts_by_status(hosts.clone(), &mut cache, counting_probe.clone(), Duration::from_secs(5), &clock);
+        assert_eq!(probes.load(Ordering::SeqCst), 3);
+
+        // Within the TTL only the unknown host is probed again
+        clock.advance(chrono::Duration::seconds(10));
+        let grouped = group_hosts_by_status(
+            hosts.clone(),
+            &mut cache,
+            counting_probe.clone(),
+            Duration::from_secs(5),
+            &clock,
+        );
+        assert_eq!(probes.load(Ordering::SeqCst), 4);
+        assert_eq!(hostnames(&grouped.online), vec!["up.domain.com"]);
+        assert_eq!(hostnames(&grouped.offline), vec!["down.domain.com"]);
+
+        // 30s after the first probe everything is probed again
+        clock.advance(chrono::Duration::seconds(20));
+        group_hosts_by_status(hosts, &mut cache, counting_probe, Duration::from_secs(5), &clock);
+        assert_eq!(probes.load(Ordering::SeqCst), 7);
+    }
+
+    #[test]
+    fn test_group_hosts_by_status_uses_cached_result_over_probe() {
+        let mut cache = StatusCache::new(STATUS_CACHE_TTL);
+        let clock = MockClock::at("2026-03-15 12:00:00");
+        cache.insert("down.domain.com", "online", clock.now());
+
+        let grouped = group_hosts_by_status(vec![host("down.domain.com")], &mut cache, fake_probe, Duration::from_secs(5), &clock);
+
+        assert_eq!(hostnames(&grouped.online), vec!["down.domain.com"]);
+        assert!(grouped.offline.is_empty());
+    }
+
+    #[test]
+    fn test_status_cache_expiry_and_unknown() {
+        let mut cache = StatusCache::new(Duration::from_secs(30));
+        let clock = MockClock::at("2026-03-15 12:00:00");
+        let recorded = clock.now();
+
+        cache.insert("server01.domain.com", "online", recorded);
+        assert_eq!(cache.get("SERVER01.domain.com", clock.now()), Some("online"));
+
+        // Fresh up to the last millisecond of the TTL, stale exactly at it
+        clock.advance(chrono::Duration::milliseconds(29_999));
+        assert_eq!(cache.get("server01.domain.com", clock.now()), Some("online"));
+        clock.advance(chrono::Duration::milliseconds(1));
+        assert_eq!(cache.get("server01.domain.com", clock.now()), None);
+
+        // An unknown result replaces an older known one
+        cache.insert("server01.domain.com", STATUS_UNKNOWN, recorded);
+        assert_eq!(cache.get("server01.domain.com", recorded), None);
+    }
+
+    #[test]
+    fn test_status_cache_stale_when_clock_moves_back() {
+        let mut cache = StatusCache::new(Duration::from_secs(30));
+        let clock = MockClock::at("2026-03-15 12:00:00");
+
+        cache.insert("server01.domain.com", "online", clock.now());
+        clock.advance(chrono::Duration::seconds(-1));
+
+        assert_eq!(cache.get("server01.domain.com", clock.now()), None);
+    }
+
+    #[test]
+    fn test_group_hosts_by_status_does_not_cache_deadline_misses() {
+        let mut cache = StatusCache::new(STATUS_CACHE_TTL);
+        let clock = MockClock::at("2026-03-15 12:00:00");
+        let probe = |hostname: &str| {
+            if hostname == "slow.domain.com" {
+                std::thread::sleep(Duration::from_secs(2));
+            }
+            "online".to_string()
+        };
+
+        let grouped = group_hosts_by_status(
+            vec![host("up.domain.com"), host("slow.domain.com")],
+            &mut cache,
+            probe,
+            Duration::from_millis(200),
+            &clock,
+        );
+
+        assert_eq!(hostnames(&grouped.online), vec!["up.domain.com"]);
+        assert_eq!(hostnames(&grouped.unknown), vec!["slow.domain.com"]);
+        assert_eq!(cache.get("up.domain.com", clock.now()), Some("online"));
+        assert!(!cache.entries.contains_key("slow.domain.com"));
+    }
+
+    #[test]
+    fn test_group_hosts_by_status_bounds_concurrency() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let probe = {
+            let running = Arc::clone(&running);
+            let peak = Arc::clone(&peak);
+            move |_: &str| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(5));
+                running.fetch_sub(1, Ordering::SeqCst);
+                "online".to_string()
+            }
+        };
+        let hosts: Vec<Host> = (0..REFRESH_CONCURRENCY * 3)
+            .map(|index| host(&format!("host{}.domain.com", index)))
+            .collect();
+        let mut cache = StatusCache::new(STATUS_CACHE_TTL);
+
+        let grouped = group_hosts_by_status(hosts, &mut cache, probe, Duration::from_secs(10), &SystemClock);
+
+        assert_eq!(grouped.online.len(), REFRESH_CONCURRENCY * 3);
+        assert!(peak.load(Ordering::SeqCst) <= REFRESH_CONCURRENCY);
+    }
+
+    #[test]
+    fn test_group_hosts_by_status_empty() {
+        let mut cache = StatusCache::new(STATUS_CACHE_TTL);
+        let grouped = group_hosts_by_status(Vec::new(), &mut cache, fake_probe, Duration::from_secs(1), &SystemClock);
+
+        assert!(grouped.online.is_empty() && grouped.offline.is_empty() && grouped.unknown.is_empty());
+    }
+
+    #[test]
+    fn test_probe_hosts_bounded_limits_concurrency() {
+        let hostnames: Vec<String> = (0..12).map(|i| format!("host{:02}.domain.com", i)).collect();
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let statuses = probe_hosts_bounded(
+            &hostnames,
+            3,
+            {
+                let running = Arc::clone(&running);
+                let peak = Arc::clone(&peak);
+                move |hostname: &str| {
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now_running, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    if hostname.starts_with("host0") { "online" } else { "offline" }.to_string()
+                }
+            },
+            Duration::from_secs(5),
+        );
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert_eq!(statuses[0].as_deref(), Some("online"));
+        assert_eq!(statuses[11].as_deref(), Some("offline"));
+        assert!(statuses.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn test_probe_hosts_bounded_deadline_leaves_none() {
+        let hostnames = vec!["fast.domain.com".to_string(), "slow.domain.com".to_string()];
+
+        let statuses = probe_hosts_bounded(
+            &hostnames,
+            2,
+            |hostname: &str| {
+                if hostname.starts_with("slow") {
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+                "online".to_string()
+            },
+            Duration::from_millis(100),
+        );
+
+        assert_eq!(statuses, vec![Some("online".to_string()), None]);
+        assert!(probe_hosts_bounded(&[], 4, |_: &str| "online".to_string(), Duration::ZERO).is_empty());
+    }
+
+    #[test]
+    fn test_status_summary_tallies_results() {
+        let results = vec![
+            Some("online".to_string()),
+            Some("offline".to_string()),
+            Some("online".to_string()),
+            Some(STATUS_UNKNOWN.to_string()),
+            None,
+        ];
+
+        assert_eq!(
+            StatusSummary::from_results(&results),
+            StatusSummary {
+                online: 2,
+                offline: 1,
+                unknown: 2,
+            }
+        );
+        assert_eq!(StatusSummary::from_results(&[]), StatusSummary::default());
+    }
+
+    #[test]
+    fn test_refresh_statuses_populates_cache() {
+        let clock = MockClock::at("2026-03-01 09:00:00");
+        let cache = Mutex::new(StatusCache::new(STATUS_CACHE_TTL));
+        cache.lock().unwrap().insert("web01.domain.com", "offline", clock.now());
+        let hostnames = vec![
+            "web01.domain.com".to_string(),
+            "sql01.domain.com".to_string(),
+            "gone.domain.com".to_string(),
+        ];
+
+        let summary = refresh_statuses(
+            &hostnames,
+            &cache,
+            |hosts| {
+                assert_eq!(hosts.len(), 3);
+                vec![Some("online".to_string()), Some("offline".to_string()), None]
+            },
+            &clock,
+        );
+
+        assert_eq!(
+            summary,
+            StatusSummary {
+                online: 1,
+                offline: 1,
+                unknown: 1,
+            }
+        );
+        // A fresh cached result is replaced, not reused
+        let cache = cache.lock().unwrap();
+        assert_eq!(cache.get("web01.domain.com", clock.now()), Some("online"));
+        assert_eq!(cache.get("sql01.domain.com", clock.now()), Some("offline"));
+        assert_eq!(cache.get("gone.domain.com", clock.now()), None);
+    }
+}
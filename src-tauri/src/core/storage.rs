@@ -0,0 +1,215 @@
+//! Storage status
+//!
+//! Reports where QuickConnect keeps its data and whether anything is left to
+//! migrate.
+//!
+//! # Why this exists
+//! On roaming profiles, or with `QUICKCONNECT_DATA_DIR` set, users lose track
+//! of which directory is in use and whether an old working-directory
+//! `hosts.csv` was ever moved.
+//!
+//! # Why separate
+//! Takes the data directory, legacy path and free-space query as parameters so
+//! the status can be assembled against temporary directories in tests.
+
+use crate::core::csv_reader::{detect_csv_schema_version, HOSTS_CSV_SCHEMA_VERSION};
+use crate::core::hosts_store::{hosts_path_in, StorageFormat};
+use crate::AppError;
+use serde::Serialize;
+use std::path::Path;
+
+/// Where QuickConnect's data lives and its migration state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageStatus {
+    /// Resolved data directory
+    pub data_dir: String,
+    /// `false` until something has been saved
+    pub data_dir_exists: bool,
+    /// `true` when `QUICKCONNECT_DATA_DIR` chose the directory
+    pub data_dir_overridden: bool,
+    /// Active hosts file (per the `storage_format` setting)
+    pub hosts_file: String,
+    pub storage_format: StorageFormat,
+    /// Path of a `hosts.csv` left in the working directory, if there is one
+    pub legacy_hosts_csv: Option<String>,
+    /// `true` while a legacy `hosts.csv` still exists
+    pub migration_pending: bool,
+    /// hosts.csv layout version this build writes
+    pub schema_version: u32,
+    /// Layout version of the hosts.csv on disk (`None` if absent or using JSON)
+    pub hosts_csv_schema_version: Option<u32>,
+    /// Bytes free on the data volume (`None` if it couldn't be determined)
+    pub free_bytes: Option<u64>,
+}
+
+/// Assembles the storage status
+///
+/// # Arguments
+/// * `data_dir` - Resolved data directory (may not exist yet)
+/// * `data_dir_overridden` - Whether `QUICKCONNECT_DATA_DIR` is set
+/// * `storage_format` - The `storage_format` setting
+/// * `legacy_hosts_csv` - Where a pre-AppData `hosts.csv` would be (ignored when it
+///   is the data directory's own file)
+/// * `free_space` - Free bytes on the volume holding an existing directory
+pub fn storage_status_for<F>(
+    data_dir: &Path,
+    data_dir_overridden: bool,
+    storage_format: StorageFormat,
+    legacy_hosts_csv: &Path,
+    free_space: F,
+) -> Result<StorageStatus, AppError>
+where
+    F: Fn(&Path) -> Option<u64>,
+{
+    let hosts_file = hosts_path_in(data_dir, storage_format);
+    let hosts_csv_schema_version = match storage_format {
+        StorageFormat::Csv => detect_csv_schema_version(&hosts_file)?,
+        StorageFormat::Json => None,
+    };
+
+    // Run from the data directory, the working-directory hosts.csv is the
+    // data directory's own file, not one left to migrate
+    let in_data_dir = same_file(legacy_hosts_csv, &hosts_path_in(data_dir, StorageFormat::Csv));
+    let legacy_exists = legacy_hosts_csv.is_file() && !in_data_dir;
+
+    // Free space is reported for the volume even before the directory is created
+    let free_bytes = data_dir
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .and_then(&free_space);
+
+    Ok(StorageStatus {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        data_dir_exists: data_dir.is_dir(),
+        data_dir_overridden,
+        hosts_file: hosts_file.to_string_lossy().to_string(),
+        storage_format,
+        legacy_hosts_csv: legacy_exists.then(|| legacy_hosts_csv.to_string_lossy().to_string()),
+        migration_pending: legacy_exists,
+        schema_version: HOSTS_CSV_SCHEMA_VERSION,
+        hosts_csv_schema_version,
+        free_bytes,
+    })
+}
+
+/// Whether two paths name the same existing file
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    fn free_space(_: &Path) -> Option<u64> {
+        Some(42 * 1024 * 1024)
+    }
+
+    #[test]
+    fn test_storage_status_without_legacy_file() {
+        let data_dir = TempDir::new().expect("Failed to create temp dir");
+        let work_dir = TempDir::new().expect("Failed to create temp dir");
+        std::fs::write(
+            data_dir.path().join("hosts.csv"),
+            "hostname,description,last_connected,connect_username,group,connect_count,tags,sort_order\n",
+        )
+        .expect("Failed to write CSV");
+
+        let status = storage_status_for(
+            data_dir.path(),
+            false,
+            StorageFormat::Csv,
+            &work_dir.path().join("hosts.csv"),
+            free_space,
+        )
+        .expect("Status should assemble");
+
+        assert_eq!(status.data_dir, data_dir.path().to_string_lossy());
+        assert!(status.data_dir_exists);
+        assert!(!status.data_dir_overridden);
+        assert_eq!(status.hosts_file, data_dir.path().join("hosts.csv").to_string_lossy());
+        assert_eq!(status.legacy_hosts_csv, None);
+        assert!(!status.migration_pending);
+        assert_eq!(status.schema_version, HOSTS_CSV_SCHEMA_VERSION);
+        assert_eq!(status.hosts_csv_schema_version, Some(HOSTS_CSV_SCHEMA_VERSION));
+        assert_eq!(status.free_bytes, Some(42 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_storage_status_with_stray_legacy_file() {
+        let data_dir = TempDir::new().expect("Failed to create temp dir");
+        let work_dir = TempDir::new().expect("Failed to create temp dir");
+        let legacy = work_dir.path().join("hosts.csv");
+        std::fs::write(&legacy, "server01.local,Web Server\n").expect("Failed to write legacy CSV");
+        std::fs::write(
+            data_dir.path().join("hosts.csv"),
+            "hostname,description,last_connected,connect_username\n",
+        )
+        .expect("Failed to write CSV");
+
+        let status = storage_status_for(data_dir.path(), true, StorageFormat::Csv, &legacy, free_space)
+            .expect("Status should assemble");
+
+        assert!(status.data_dir_overridden);
+        assert!(status.migration_pending);
+        assert_eq!(status.legacy_hosts_csv, Some(legacy.to_string_lossy().to_string()));
+        // An older layout is reported until the next write upgrades it
+        assert_eq!(status.hosts_csv_schema_version, Some(2));
+    }
+
+    #[test]
+    fn test_storage_status_run_from_data_dir_has_no_legacy_file() {
+        let data_dir = TempDir::new().expect("Failed to create temp dir");
+        std::fs::write(
+            data_dir.path().join("hosts.csv"),
+            "hostname,description,last_connected,connect_username,group,connect_count,tags,sort_order\n",
+        )
+        .expect("Failed to write CSV");
+
+        // The working directory is the data directory itself
+        for format in [StorageFormat::Csv, StorageFormat::Json] {
+            let status = storage_status_for(
+                data_dir.path(),
+                false,
+                format,
+                &data_dir.path().join(".").join("hosts.csv"),
+                free_space,
+            )
+            .expect("Status should assemble");
+
+            assert_eq!(status.legacy_hosts_csv, None);
+            assert!(!status.migration_pending);
+        }
+    }
+
+    #[test]
+    fn test_storage_status_before_first_save() {
+        let parent = TempDir::new().expect("Failed to create temp dir");
+        let data_dir = parent.path().join("QuickConnect");
+        let queried = RefCell::new(Vec::new());
+
+        let status = storage_status_for(
+            &data_dir,
+            false,
+            StorageFormat::Json,
+            &parent.path().join("hosts.csv"),
+            |dir: &Path| {
+                queried.borrow_mut().push(dir.to_path_buf());
+                None
+            },
+        )
+        .expect("Status should assemble");
+
+        assert!(!status.data_dir_exists);
+        assert_eq!(status.hosts_file, data_dir.join("hosts.json").to_string_lossy());
+        assert_eq!(status.hosts_csv_schema_version, None);
+        assert_eq!(status.free_bytes, None);
+        // Free space is asked for the nearest existing directory
+        assert_eq!(queried.into_inner(), vec![parent.path().to_path_buf()]);
+    }
+}
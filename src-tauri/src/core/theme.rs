@@ -0,0 +1,105 @@
+//! Theme resolution
+//!
+//! Turns the saved theme preference into the theme that is actually shown.
+//! `"light"` and `"dark"` are used as-is; `"auto_time"` picks one from the
+//! current hour and the configured switchover hours.
+//!
+//! # Why separate
+//! The time-of-day rule is a pure function of the hour, so the boundary and
+//! overnight cases can be unit tested without a clock.
+
+use std::time::Duration;
+
+/// Theme preference that follows the time of day
+pub const THEME_AUTO_TIME: &str = "auto_time";
+
+/// Theme preferences that can be saved
+pub const THEME_PREFERENCES: [&str; 3] = ["light", "dark", THEME_AUTO_TIME];
+
+/// How often the time-of-day theme is re-evaluated
+pub const AUTO_THEME_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Returns the theme for an hour of the day (0-23)
+///
+/// Dark from `dark_start_hour` until `light_start_hour`, light otherwise. The
+/// dark period may wrap past midnight (e.g. dark 18 to light 7). Equal hours
+/// mean there is no dark period.
+pub fn resolve_time_theme(hour: u32, dark_start_hour: u8, light_start_hour: u8) -> &'static str {
+    let dark_start = u32::from(dark_start_hour);
+    let light_start = u32::from(light_start_hour);
+
+    let is_dark = if dark_start < light_start {
+        hour >= dark_start && hour < light_start
+    } else if dark_start > light_start {
+        // Overnight: dark through midnight
+        hour >= dark_start || hour < light_start
+    } else {
+        false
+    };
+
+    if is_dark {
+        "dark"
+    } else {
+        "light"
+    }
+}
+
+/// Returns the theme to show for a saved preference
+///
+/// `"auto_time"` resolves through `resolve_time_theme`; any other preference
+/// is returned unchanged.
+pub fn effective_theme(preference: &str, hour: u32, dark_start_hour: u8, light_start_hour: u8) -> String {
+    if preference == THEME_AUTO_TIME {
+        resolve_time_theme(hour, dark_start_hour, light_start_hour).to_string()
+    } else {
+        preference.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_time_theme_overnight_range() {
+        // Dark 18:00 - 07:00
+        assert_eq!(resolve_time_theme(18, 18, 7), "dark");
+        assert_eq!(resolve_time_theme(23, 18, 7), "dark");
+        assert_eq!(resolve_time_theme(0, 18, 7), "dark");
+        assert_eq!(resolve_time_theme(6, 18, 7), "dark");
+        assert_eq!(resolve_time_theme(7, 18, 7), "light");
+        assert_eq!(resolve_time_theme(12, 18, 7), "light");
+        assert_eq!(resolve_time_theme(17, 18, 7), "light");
+    }
+
+    #[test]
+    fn test_resolve_time_theme_same_day_range() {
+        // Dark 01:00 - 09:00 (night shift sleeping hours)
+        assert_eq!(resolve_time_theme(0, 1, 9), "light");
+        assert_eq!(resolve_time_theme(1, 1, 9), "dark");
+        assert_eq!(resolve_time_theme(8, 1, 9), "dark");
+        assert_eq!(resolve_time_theme(9, 1, 9), "light");
+        assert_eq!(resolve_time_theme(23, 1, 9), "light");
+    }
+
+    #[test]
+    fn test_resolve_time_theme_equal_hours_never_dark() {
+        for hour in 0..24 {
+            assert_eq!(resolve_time_theme(hour, 8, 8), "light");
+        }
+    }
+
+    #[test]
+    fn test_resolve_time_theme_dark_from_midnight() {
+        assert_eq!(resolve_time_theme(0, 0, 6), "dark");
+        assert_eq!(resolve_time_theme(6, 0, 6), "light");
+    }
+
+    #[test]
+    fn test_effective_theme() {
+        assert_eq!(effective_theme("auto_time", 20, 18, 7), "dark");
+        assert_eq!(effective_theme("auto_time", 10, 18, 7), "light");
+        assert_eq!(effective_theme("light", 20, 18, 7), "light");
+        assert_eq!(effective_theme("dark", 10, 18, 7), "dark");
+    }
+}
@@ -0,0 +1,418 @@
+//! Tray menu layout
+//!
+//! Decides which items the system tray menu shows and how they are labelled.
+//!
+//! # Why separate
+//! Building real menu items needs a running Tauri app. Computing the layout
+//! from plain inputs (recent connections, theme, autostart state) keeps it
+//! testable; the command layer only turns it into menu items.
+
+use crate::RecentConnection;
+use std::collections::HashMap;
+
+/// A single tray menu entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayItem {
+    /// Menu event id
+    pub id: String,
+    /// Text shown in the menu
+    pub label: String,
+    pub enabled: bool,
+}
+
+impl TrayItem {
+    fn new(id: &str, label: &str, enabled: bool) -> Self {
+        Self {
+            id: id.to_string(),
+            label: label.to_string(),
+            enabled,
+        }
+    }
+}
+
+/// Contents of the tray menu, top to bottom
+///
+/// About, a separator and Quit always follow `autostart`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrayMenuLayout {
+    /// Items of the "Recent Connections" submenu
+    pub recent: Vec<TrayItem>,
+    /// Items of the "Theme" submenu
+    pub theme: Vec<TrayItem>,
+    pub autostart: TrayItem,
+}
+
+/// Prefix of the menu id used for recent connection entries
+pub const RECENT_ITEM_PREFIX: &str = "recent_";
+
+/// Default `recent_label_format`, matching the historical "hostname - description" labels
+pub const DEFAULT_RECENT_LABEL_FORMAT: &str = "{hostname} - {description}";
+
+/// Tokens recognised in `recent_label_format`; other `{...}` text is kept literally
+const LABEL_TOKENS: [&str; 3] = ["hostname", "description", "alias"];
+
+const OPENING_BRACKETS: [char; 4] = ['(', '[', '{', '<'];
+const CLOSING_BRACKETS: [char; 4] = [')', ']', '}', '>'];
+
+/// Short name of a host: the hostname without its domain ("server01"), or the
+/// whole entry for an IPv4 or IPv6 literal, which has no domain to drop
+fn host_alias(hostname: &str) -> &str {
+    let hostname = hostname.trim();
+    let (host, _) = crate::core::hosts::split_host_port(hostname);
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return hostname;
+    }
+    hostname.split('.').next().unwrap_or(hostname)
+}
+
+/// Renders a recent connection label from a format string
+///
+/// Tokens: `{hostname}`, `{description}` and `{alias}` (the hostname without
+/// its domain, e.g. "server01"; IP addresses are kept whole).
+///
+/// Empty fields are dropped together with the separator text before them,
+/// so `{hostname} - {description}` renders as just the hostname when there
+/// is no description. Brackets directly around a token belong to it, and are
+/// dropped too when it is the only field left: `{description} ({hostname})`
+/// renders as the bare hostname. A format that renders nothing falls back to
+/// the hostname.
+pub fn render_recent_label(format: &str, hostname: &str, description: &str) -> String {
+    let alias = host_alias(hostname);
+    let value_of = |token: &str| match token {
+        "hostname" => hostname.trim(),
+        "description" => description.trim(),
+        "alias" => alias.trim(),
+        _ => "",
+    };
+
+    // literals[i] is the text before tokens[i]; the last literal follows the last token
+    let mut literals = vec![String::new()];
+    let mut tokens: Vec<&str> = Vec::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) if LABEL_TOKENS.contains(&&after[..end]) => {
+                if let Some(literal) = literals.last_mut() {
+                    literal.push_str(&rest[..start]);
+                }
+                tokens.push(&after[..end]);
+                literals.push(String::new());
+                rest = &after[end + 1..];
+            }
+            _ => {
+                if let Some(literal) = literals.last_mut() {
+                    literal.push_str(&rest[..=start]);
+                }
+                rest = after;
+            }
+        }
+    }
+    if let Some(literal) = literals.last_mut() {
+        literal.push_str(rest);
+    }
+
+    if tokens.is_empty() {
+        return hostname.to_string();
+    }
+
+    // Split each literal into: closing brackets of the previous token,
+    // separator text, opening brackets of the next token
+    let count = tokens.len();
+    let mut prefixes = vec![""; count];
+    let mut suffixes = vec![""; count];
+    let mut separators = vec![""; count];
+
+    let lead = literals[0].trim_end_matches(OPENING_BRACKETS);
+    prefixes[0] = &literals[0][lead.len()..];
+    for index in 1..count {
+        let literal = literals[index].as_str();
+        let after_close = literal.trim_start_matches(CLOSING_BRACKETS);
+        suffixes[index - 1] = &literal[..literal.len() - after_close.len()];
+        let separator = after_close.trim_end_matches(OPENING_BRACKETS);
+        separators[index] = separator;
+        prefixes[index] = &after_close[separator.len()..];
+    }
+    let last = literals[count].as_str();
+    let trail = last.trim_start_matches(CLOSING_BRACKETS);
+    suffixes[count - 1] = &last[..last.len() - trail.len()];
+
+    let visible: Vec<usize> = (0..count).filter(|&i| !value_of(tokens[i]).is_empty()).collect();
+    if visible.is_empty() {
+        return hostname.to_string();
+    }
+
+    let unwrap_single = visible.len() == 1 && count > 1;
+    let mut label = lead.to_string();
+    for (position, &index) in visible.iter().enumerate() {
+        if position > 0 {
+            label.push_str(separators[index]);
+        }
+        if !unwrap_single {
+            label.push_str(prefixes[index]);
+        }
+        label.push_str(value_of(tokens[index]));
+        if !unwrap_single {
+            label.push_str(suffixes[index]);
+        }
+    }
+    label.push_str(trail);
+
+    label.trim().to_string()
+}
+
+/// Returns the glyph shown before a recent connection for its status
+///
+/// `None` (not checked, or not checked in time) and unrecognised statuses get
+/// no glyph.
+pub fn status_glyph(status: Option<&str>) -> Option<&'static str> {
+    match status? {
+        "online" => Some("●"),
+        "offline" => Some("○"),
+        crate::core::status::STATUS_UNKNOWN => Some("?"),
+        _ => None,
+    }
+}
+
+/// Prefixes a menu label with the glyph for `status`, if it has one
+pub fn decorate_label(label: &str, status: Option<&str>) -> String {
+    match status_glyph(status) {
+        Some(glyph) => format!("{} {}", glyph, label),
+        None => label.to_string(),
+    }
+}
+
+/// Computes the tray menu layout
+///
+/// # Arguments
+/// * `recent` - Recent connections, most recent first
+/// * `label_format` - Format for recent connection labels (see `render_recent_label`)
+/// * `statuses` - Statuses keyed by lowercased hostname; recent labels get a
+///   status glyph when present (empty when `tray_status_indicators` is off)
+/// * `current_theme` - Saved preference ("light", "dark" or "auto_time"); the matching theme item is checked
+/// * `autostart_enabled` - Whether QuickConnect starts with Windows
+pub fn tray_menu_layout(
+    recent: &[RecentConnection],
+    label_format: &str,
+    statuses: &HashMap<String, String>,
+    current_theme: &str,
+    autostart_enabled: bool,
+) -> TrayMenuLayout {
+    let recent = if recent.is_empty() {
+        vec![TrayItem::new("no_recent", "No recent connections", false)]
+    } else {
+        recent
+            .iter()
+            .map(|conn| TrayItem {
+                id: format!("{}{}", RECENT_ITEM_PREFIX, conn.hostname),
+                label: decorate_label(
+                    &render_recent_label(label_format, &conn.hostname, &conn.description),
+                    statuses.get(&conn.hostname.to_lowercase()).map(String::as_str),
+                ),
+                enabled: true,
+            })
+            .collect()
+    };
+
+    let check = |selected: bool, text: &str| {
+        format!("{} {}", if selected { "✓" } else { "✗" }, text)
+    };
+
+    let theme = vec![
+        TrayItem::new("theme_light", &check(current_theme == "light", "Light"), true),
+        TrayItem::new("theme_dark", &check(current_theme == "dark", "Dark"), true),
+        TrayItem::new(
+            "theme_auto_time",
+            &check(current_theme == crate::core::theme::THEME_AUTO_TIME, "Auto (Time of Day)"),
+            true,
+        ),
+    ];
+
+    TrayMenuLayout {
+        recent,
+        theme,
+        autostart: TrayItem::new(
+            "toggle_autostart",
+            &check(autostart_enabled, "Autostart with Windows"),
+            true,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recent(hostname: &str, description: &str) -> RecentConnection {
+        RecentConnection {
+            hostname: hostname.to_string(),
+            description: description.to_string(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_tray_menu_layout_populated() {
+        let layout = tray_menu_layout(
+            &[recent("web01.domain.com", "Web"), recent("db01.domain.com", "")],
+            DEFAULT_RECENT_LABEL_FORMAT,
+            &HashMap::new(),
+            "light",
+            true,
+        );
+
+        assert_eq!(
+            layout.recent,
+            vec![
+                TrayItem::new("recent_web01.domain.com", "web01.domain.com - Web", true),
+                TrayItem::new("recent_db01.domain.com", "db01.domain.com", true),
+            ]
+        );
+        assert_eq!(
+            layout.theme,
+            vec![
+                TrayItem::new("theme_light", "✓ Light", true),
+                TrayItem::new("theme_dark", "✗ Dark", true),
+                TrayItem::new("theme_auto_time", "✗ Auto (Time of Day)", true),
+            ]
+        );
+        assert_eq!(
+            layout.autostart,
+            TrayItem::new("toggle_autostart", "✓ Autostart with Windows", true)
+        );
+    }
+
+    #[test]
+    fn test_tray_menu_layout_empty_recents() {
+        let layout = tray_menu_layout(&[], DEFAULT_RECENT_LABEL_FORMAT, &HashMap::new(), "dark", false);
+
+        assert_eq!(
+            layout.recent,
+            vec![TrayItem::new("no_recent", "No recent connections", false)]
+        );
+        assert_eq!(layout.theme[1].label, "✓ Dark");
+        assert_eq!(layout.autostart.label, "✗ Autostart with Windows");
+    }
+
+    #[test]
+    fn test_tray_menu_layout_auto_time_theme() {
+        let layout = tray_menu_layout(&[], DEFAULT_RECENT_LABEL_FORMAT, &HashMap::new(), "auto_time", false);
+
+        let labels: Vec<&str> = layout.theme.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["✗ Light", "✗ Dark", "✓ Auto (Time of Day)"]);
+    }
+
+    const HOST: &str = "server01.domain.com";
+
+    #[test]
+    fn test_render_recent_label_default_format() {
+        assert_eq!(
+            render_recent_label(DEFAULT_RECENT_LABEL_FORMAT, HOST, "Web"),
+            "server01.domain.com - Web"
+        );
+        assert_eq!(render_recent_label(DEFAULT_RECENT_LABEL_FORMAT, HOST, ""), HOST);
+        assert_eq!(render_recent_label(DEFAULT_RECENT_LABEL_FORMAT, HOST, "   "), HOST);
+    }
+
+    #[test]
+    fn test_render_recent_label_description_first() {
+        let format = "{description} ({hostname})";
+        assert_eq!(render_recent_label(format, HOST, "Web"), "Web (server01.domain.com)");
+        assert_eq!(render_recent_label(format, HOST, ""), HOST);
+    }
+
+    #[test]
+    fn test_render_recent_label_hostname_only() {
+        assert_eq!(render_recent_label("{hostname}", HOST, "Web"), HOST);
+        assert_eq!(render_recent_label("[{hostname}]", HOST, "Web"), "[server01.domain.com]");
+    }
+
+    #[test]
+    fn test_render_recent_label_alias() {
+        assert_eq!(render_recent_label("{alias}: {description}", HOST, "Web"), "server01: Web");
+        assert_eq!(render_recent_label("{alias}: {description}", HOST, ""), "server01");
+        assert_eq!(render_recent_label("{alias}", "server01.domain.com:3390", ""), "server01");
+        // IP literals have no domain to drop
+        assert_eq!(render_recent_label("{alias}", "10.0.0.5", ""), "10.0.0.5");
+        assert_eq!(render_recent_label("{alias}", "10.0.0.5:3390", ""), "10.0.0.5:3390");
+        assert_eq!(render_recent_label("{alias}", "fe80::1", ""), "fe80::1");
+        assert_eq!(render_recent_label("{alias}", "[2001:db8::5]:3390", ""), "[2001:db8::5]:3390");
+    }
+
+    #[test]
+    fn test_render_recent_label_skips_empty_middle_field() {
+        let format = "{alias} | {description} | {hostname}";
+        assert_eq!(
+            render_recent_label(format, HOST, "Web"),
+            "server01 | Web | server01.domain.com"
+        );
+        assert_eq!(
+            render_recent_label(format, HOST, ""),
+            "server01 | server01.domain.com"
+        );
+    }
+
+    #[test]
+    fn test_render_recent_label_falls_back_to_hostname() {
+        assert_eq!(render_recent_label("", HOST, "Web"), HOST);
+        assert_eq!(render_recent_label("Server", HOST, "Web"), HOST);
+        assert_eq!(render_recent_label("{description}", HOST, ""), HOST);
+    }
+
+    #[test]
+    fn test_render_recent_label_keeps_unknown_braces() {
+        assert_eq!(
+            render_recent_label("{hostname} {env}", HOST, ""),
+            "server01.domain.com {env}"
+        );
+    }
+
+    #[test]
+    fn test_tray_menu_layout_uses_label_format() {
+        let layout = tray_menu_layout(
+            &[recent("web01.domain.com", "Web")],
+            "{description} ({hostname})",
+            &HashMap::new(),
+            "dark",
+            false,
+        );
+        assert_eq!(layout.recent[0].label, "Web (web01.domain.com)");
+        assert_eq!(layout.recent[0].id, "recent_web01.domain.com");
+    }
+
+    #[test]
+    fn test_decorate_label_for_each_status() {
+        assert_eq!(decorate_label("web01 - Web", Some("online")), "● web01 - Web");
+        assert_eq!(decorate_label("web01 - Web", Some("offline")), "○ web01 - Web");
+        assert_eq!(decorate_label("web01 - Web", Some("unknown")), "? web01 - Web");
+        assert_eq!(decorate_label("web01 - Web", Some("checking")), "web01 - Web");
+        assert_eq!(decorate_label("web01 - Web", None), "web01 - Web");
+    }
+
+    #[test]
+    fn test_tray_menu_layout_decorates_recents_with_status() {
+        let statuses = HashMap::from([
+            ("web01.domain.com".to_string(), "online".to_string()),
+            ("db01.domain.com".to_string(), "unknown".to_string()),
+        ]);
+
+        let layout = tray_menu_layout(
+            &[
+                recent("WEB01.domain.com", "Web"),
+                recent("db01.domain.com", ""),
+                recent("new01.domain.com", ""),
+            ],
+            DEFAULT_RECENT_LABEL_FORMAT,
+            &statuses,
+            "dark",
+            false,
+        );
+
+        let labels: Vec<&str> = layout.recent.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["● WEB01.domain.com - Web", "? db01.domain.com", "new01.domain.com"]
+        );
+        assert_eq!(layout.recent[0].id, "recent_WEB01.domain.com");
+    }
+}
@@ -1,9 +1,10 @@
 //! Core domain types for QuickConnect
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// RDP Host structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Host {
     /// Fully Qualified Domain Name (e.g., "server.domain.com")
     pub hostname: String,
@@ -11,6 +12,30 @@ pub struct Host {
     pub description: String,
     /// ISO 8601 formatted timestamp of last successful connection (optional)
     pub last_connected: Option<String>,
+    /// Username to connect as instead of the stored credential's username (optional)
+    ///
+    /// The stored password is still used; only `username:s:`/`domain:s:` in the
+    /// RDP file change. Accepts the same formats as stored usernames.
+    #[serde(default)]
+    pub connect_username: Option<String>,
+    /// Group the host belongs to, e.g. "Patch Tuesday" (optional)
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Number of connections launched to this host
+    #[serde(default)]
+    pub connect_count: u32,
+    /// Lowercase tags such as "prod" or "sql" (CSV: semicolon-delimited)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Position in the user's manual ordering (see `reorder_hosts`); unset hosts
+    /// follow the ordered ones in file order
+    #[serde(default)]
+    pub sort_order: Option<u32>,
+    /// hosts.csv columns QuickConnect doesn't recognise, keyed by header name
+    ///
+    /// Kept so that columns added by newer versions or by hand survive a rewrite.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_columns: BTreeMap<String, String>,
 }
 
 /// Stored credentials
@@ -42,6 +67,22 @@ pub struct RecentConnection {
     pub timestamp: u64,
 }
 
+/// Recent connection entry with its current reachability
+#[derive(Debug, Serialize, Clone)]
+pub struct RecentConnectionStatus {
+    /// Hostname of the connected server
+    pub hostname: String,
+    /// Description of the server
+    pub description: String,
+    /// Unix timestamp (seconds since epoch) of the connection
+    pub timestamp: u64,
+    /// "online", "offline", or "unknown"
+    pub status: String,
+}
+
+/// Maximum number of entries kept in the recent connections list
+pub const MAX_RECENT_CONNECTIONS: usize = 5;
+
 /// Collection of recent connections
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RecentConnections {
@@ -61,12 +102,18 @@ impl RecentConnections {
     ///
     /// Removes duplicates, keeps only 5 most recent
     pub fn add_connection(&mut self, hostname: String, description: String) {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        self.add_connection_with_clock(hostname, description, &crate::core::clock::SystemClock);
+    }
+
+    /// Adds a new connection, timestamped by `clock`
+    pub fn add_connection_with_clock(
+        &mut self,
+        hostname: String,
+        description: String,
+        clock: &dyn crate::core::clock::Clock,
+    ) {
+        // Seconds since the Unix epoch; a clock before 1970 records 0
+        let timestamp = u64::try_from(clock.now().timestamp()).unwrap_or_default();
 
         // Remove existing entry for this hostname
         self.connections.retain(|c| c.hostname != hostname);
@@ -82,9 +129,35 @@ impl RecentConnections {
         );
 
         // Keep only the 5 most recent
-        if self.connections.len() > 5 {
-            self.connections.truncate(5);
+        if self.connections.len() > MAX_RECENT_CONNECTIONS {
+            self.connections.truncate(MAX_RECENT_CONNECTIONS);
+        }
+    }
+
+    /// Merges another recent connections list into this one
+    ///
+    /// Entries are deduplicated by hostname, keeping whichever entry has the
+    /// newer timestamp. The merged list is re-sorted most recent first and
+    /// truncated to `MAX_RECENT_CONNECTIONS`.
+    pub fn merge(&mut self, other: RecentConnections) {
+        for incoming in other.connections {
+            match self
+                .connections
+                .iter_mut()
+                .find(|c| c.hostname == incoming.hostname)
+            {
+                Some(existing) => {
+                    if incoming.timestamp > existing.timestamp {
+                        *existing = incoming;
+                    }
+                }
+                None => self.connections.push(incoming),
+            }
         }
+
+        // Stable sort keeps the original relative order for equal timestamps
+        self.connections.sort_by_key(|c| std::cmp::Reverse(c.timestamp));
+        self.connections.truncate(MAX_RECENT_CONNECTIONS);
     }
 }
 
@@ -94,6 +167,71 @@ impl Default for RecentConnections {
     }
 }
 
+/// Most recent launch failure recorded for a host
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HostLastError {
+    /// Error category (e.g. "CREDENTIALS", "RDP")
+    pub category: String,
+    /// User-facing error message
+    pub message: String,
+    /// Unix timestamp (seconds since epoch) of the failure
+    pub timestamp: u64,
+}
+
+/// Tracks the last launch error for each host
+///
+/// Lets the hosts list show which hosts recently failed to connect. An entry
+/// is replaced by newer failures and removed on the next successful launch.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HostErrorTracker {
+    /// Last error keyed by hostname
+    pub errors: HashMap<String, HostLastError>,
+}
+
+impl HostErrorTracker {
+    /// Creates an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a launch failure for a host
+    ///
+    /// An existing entry is only replaced when the new failure is not older,
+    /// so out-of-order reports cannot hide the most recent error.
+    pub fn record_failure(
+        &mut self,
+        hostname: &str,
+        category: &str,
+        message: &str,
+        timestamp: u64,
+    ) {
+        if let Some(existing) = self.errors.get(hostname) {
+            if existing.timestamp > timestamp {
+                return;
+            }
+        }
+
+        self.errors.insert(
+            hostname.to_string(),
+            HostLastError {
+                category: category.to_string(),
+                message: message.to_string(),
+                timestamp,
+            },
+        );
+    }
+
+    /// Clears the recorded error for a host after a successful launch
+    pub fn record_success(&mut self, hostname: &str) {
+        self.errors.remove(hostname);
+    }
+
+    /// Returns the last recorded error for a host, if any
+    pub fn last_error(&self, hostname: &str) -> Option<&HostLastError> {
+        self.errors.get(hostname)
+    }
+}
+
 /// Error payload for the error window
 #[derive(Clone, Serialize)]
 pub struct ErrorPayload {
@@ -0,0 +1,221 @@
+//! Rolling per-host usage
+//!
+//! Keeps the most recent connection times for each host in a sidecar file so
+//! usage can be counted over a recent window instead of the lifetime
+//! `connect_count`.
+//!
+//! # Why this exists
+//! A server used heavily a year ago keeps a high `connect_count` forever;
+//! counting only the last `USAGE_WINDOW_DAYS` days reflects current usage.
+//!
+//! # Why separate
+//! Takes the sidecar path and a `Clock` as parameters so the window and
+//! pruning can be unit tested with `MockClock` against temporary files.
+
+use crate::core::clock::Clock;
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Days counted by `recent_count`
+pub const USAGE_WINDOW_DAYS: i64 = 30;
+
+/// Connection times kept per host; older ones are dropped first
+pub const MAX_USAGE_TIMESTAMPS: usize = 50;
+
+/// Recent connection times per host
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageLog {
+    /// Unix timestamps (seconds), oldest first, keyed by lowercase hostname
+    pub hosts: BTreeMap<String, Vec<i64>>,
+}
+
+impl UsageLog {
+    /// Records a connection to `hostname` at `now` (Unix seconds)
+    ///
+    /// Keeps at most `MAX_USAGE_TIMESTAMPS` entries for the host.
+    pub fn record(&mut self, hostname: &str, now: i64) {
+        let timestamps = self.hosts.entry(hostname.to_lowercase()).or_default();
+        timestamps.push(now);
+        timestamps.sort_unstable();
+        let excess = timestamps.len().saturating_sub(MAX_USAGE_TIMESTAMPS);
+        timestamps.drain(..excess);
+    }
+
+    /// Number of connections to `hostname` within the window ending at `now`
+    ///
+    /// Times after `now` (the clock went backwards) aren't counted.
+    pub fn recent_count(&self, hostname: &str, now: i64) -> u32 {
+        let cutoff = window_start(now);
+        self.hosts.get(&hostname.to_lowercase()).map_or(0, |timestamps| {
+            timestamps
+                .iter()
+                .filter(|timestamp| (cutoff..=now).contains(*timestamp))
+                .count() as u32
+        })
+    }
+
+    /// Drops times older than the window ending at `now`, and hosts left with none
+    ///
+    /// # Returns
+    /// * Number of times removed
+    pub fn prune(&mut self, now: i64) -> usize {
+        let cutoff = window_start(now);
+        let mut removed = 0;
+        self.hosts.retain(|_, timestamps| {
+            let before = timestamps.len();
+            timestamps.retain(|timestamp| *timestamp >= cutoff);
+            removed += before - timestamps.len();
+            !timestamps.is_empty()
+        });
+        removed
+    }
+}
+
+fn window_start(now: i64) -> i64 {
+    now - USAGE_WINDOW_DAYS * 24 * 60 * 60
+}
+
+/// Loads the usage log from the given file
+///
+/// # Returns
+/// * `Ok(UsageLog)` - The stored log (empty if the file doesn't exist yet)
+/// * `Err(AppError)` - Failed to read or parse the file
+pub fn load_usage_from(path: &Path) -> Result<UsageLog, AppError> {
+    if !path.exists() {
+        return Ok(UsageLog::default());
+    }
+
+    let json = std::fs::read_to_string(path).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })?;
+
+    serde_json::from_str(&json).map_err(|e| AppError::JsonError {
+        context: format!("Failed to parse usage log {:?}", path),
+        source: e,
+    })
+}
+
+/// Saves the usage log to the given file
+pub fn save_usage_to(path: &Path, log: &UsageLog) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(log).map_err(|e| AppError::JsonError {
+        context: "Failed to serialize usage log".to_string(),
+        source: e,
+    })?;
+
+    std::fs::write(path, json).map_err(|e| AppError::IoError {
+        path: path.to_string_lossy().to_string(),
+        source: e,
+    })
+}
+
+/// Records a connection to `hostname` in the given file, pruning expired times
+pub fn record_usage_at(path: &Path, hostname: &str, clock: &dyn Clock) -> Result<(), AppError> {
+    let now = clock.now().timestamp();
+    let mut log = load_usage_from(path)?;
+    log.prune(now);
+    log.record(hostname, now);
+    save_usage_to(path, &log)
+}
+
+/// Number of connections to `hostname` in the last `USAGE_WINDOW_DAYS` days,
+/// read from the given file
+///
+/// Expired times are pruned from the file when there are any.
+pub fn recent_usage_at(path: &Path, hostname: &str, clock: &dyn Clock) -> Result<u32, AppError> {
+    let now = clock.now().timestamp();
+    let mut log = load_usage_from(path)?;
+    if log.prune(now) > 0 {
+        save_usage_to(path, &log)?;
+    }
+    Ok(log.recent_count(hostname, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::MockClock;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_recent_count_only_counts_inside_window() {
+        let now = 1_800_000_000;
+        let mut log = UsageLog::default();
+        for days_ago in [0, 1, 29, 30, 31, 365] {
+            log.record("web01.domain.com", now - days_ago * DAY);
+        }
+        // Recorded later than now, e.g. before the clock was corrected
+        log.record("web01.domain.com", now + DAY);
+
+        assert_eq!(log.recent_count("web01.domain.com", now), 4);
+        assert_eq!(log.recent_count("WEB01.domain.com", now), 4);
+        assert_eq!(log.recent_count("sql01.domain.com", now), 0);
+    }
+
+    #[test]
+    fn test_prune_drops_expired_times_and_hosts() {
+        let now = 1_800_000_000;
+        let mut log = UsageLog::default();
+        log.record("web01.domain.com", now - 31 * DAY);
+        log.record("web01.domain.com", now - 2 * DAY);
+        log.record("old01.domain.com", now - 90 * DAY);
+
+        assert_eq!(log.prune(now), 2);
+
+        assert_eq!(log.hosts.len(), 1);
+        assert_eq!(log.hosts["web01.domain.com"], vec![now - 2 * DAY]);
+        assert_eq!(log.prune(now), 0);
+    }
+
+    #[test]
+    fn test_record_caps_timestamps_per_host() {
+        let mut log = UsageLog::default();
+        for second in 0..(MAX_USAGE_TIMESTAMPS as i64 + 10) {
+            log.record("web01.domain.com", 1_800_000_000 + second);
+        }
+
+        let timestamps = &log.hosts["web01.domain.com"];
+        assert_eq!(timestamps.len(), MAX_USAGE_TIMESTAMPS);
+        assert_eq!(timestamps[0], 1_800_000_010);
+    }
+
+    #[test]
+    fn test_recent_usage_with_mock_clock() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("connection_usage.json");
+        let clock = MockClock::at("2026-03-01 09:00:00");
+
+        record_usage_at(&path, "web01.domain.com", &clock).unwrap();
+        clock.advance(Duration::days(10));
+        record_usage_at(&path, "web01.domain.com", &clock).unwrap();
+        record_usage_at(&path, "sql01.domain.com", &clock).unwrap();
+
+        assert_eq!(recent_usage_at(&path, "web01.domain.com", &clock).unwrap(), 2);
+
+        // The first connection falls out of the window 30 days after it was made
+        clock.advance(Duration::days(21));
+        assert_eq!(recent_usage_at(&path, "web01.domain.com", &clock).unwrap(), 1);
+        assert_eq!(load_usage_from(&path).unwrap().hosts["web01.domain.com"].len(), 1);
+
+        clock.advance(Duration::days(30));
+        assert_eq!(recent_usage_at(&path, "web01.domain.com", &clock).unwrap(), 0);
+        assert_eq!(recent_usage_at(&path, "sql01.domain.com", &clock).unwrap(), 0);
+        assert!(load_usage_from(&path).unwrap().hosts.is_empty());
+    }
+
+    #[test]
+    fn test_recent_usage_without_sidecar() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("connection_usage.json");
+        let clock = MockClock::at("2026-03-01 09:00:00");
+
+        assert_eq!(recent_usage_at(&path, "web01.domain.com", &clock).unwrap(), 0);
+        assert!(!path.exists());
+    }
+}
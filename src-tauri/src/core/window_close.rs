@@ -0,0 +1,76 @@
+//! Window close behavior
+//!
+//! Decides whether closing a window hides it to the tray or quits QuickConnect.
+//!
+//! # Why this exists
+//! Closing a window has always hidden it, leaving QuickConnect running in the
+//! tray. Users who expect the X to quit can choose that instead; secondary
+//! windows (hosts management, About, error) still just hide, since closing
+//! them returns to the window they were opened from.
+//!
+//! # Why separate
+//! The decision only depends on the setting and the window label, so it is
+//! unit tested here; the close handlers in `run()` apply it.
+
+use serde::{Deserialize, Serialize};
+
+/// What the X on the login and main windows does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseBehavior {
+    /// Hide the window and keep running in the tray
+    #[default]
+    HideToTray,
+    /// Quit QuickConnect
+    Exit,
+}
+
+/// What a close request does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseAction {
+    /// Hide the window; it can be restored from the tray
+    Hide,
+    /// Quit the application
+    Exit,
+}
+
+/// Windows whose X follows the `close_behavior` setting
+const PRIMARY_WINDOWS: [&str; 2] = ["login", "main"];
+
+/// Chooses what closing the window labelled `window_label` does
+pub fn close_action(behavior: CloseBehavior, window_label: &str) -> CloseAction {
+    match behavior {
+        CloseBehavior::Exit if PRIMARY_WINDOWS.contains(&window_label) => CloseAction::Exit,
+        _ => CloseAction::Hide,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hide_to_tray_hides_every_window() {
+        for label in ["login", "main", "hosts", "about", "error"] {
+            assert_eq!(close_action(CloseBehavior::HideToTray, label), CloseAction::Hide);
+        }
+    }
+
+    #[test]
+    fn test_exit_quits_from_primary_windows_only() {
+        assert_eq!(close_action(CloseBehavior::Exit, "login"), CloseAction::Exit);
+        assert_eq!(close_action(CloseBehavior::Exit, "main"), CloseAction::Exit);
+        for label in ["hosts", "about", "error", "unknown"] {
+            assert_eq!(close_action(CloseBehavior::Exit, label), CloseAction::Hide);
+        }
+    }
+
+    #[test]
+    fn test_close_behavior_serializes_as_snake_case() {
+        assert_eq!(serde_json::to_string(&CloseBehavior::HideToTray).unwrap(), "\"hide_to_tray\"");
+        assert_eq!(
+            serde_json::from_str::<CloseBehavior>("\"exit\"").unwrap(),
+            CloseBehavior::Exit
+        );
+    }
+}
@@ -94,7 +94,7 @@ pub enum AppError {
     },
 
     /// LDAP search operation failed
-    #[error("LDAP search failed in base DN '{base_dn}'")]
+    #[error("LDAP search failed in base DN '{base_dn}': {source}")]
     LdapSearchError {
         base_dn: String,
         #[source]
@@ -109,7 +109,7 @@ pub enum AppError {
     },
 
     /// Failed to launch RDP client (mstsc.exe)
-    #[error("Failed to launch RDP client")]
+    #[error("Failed to launch RDP client: {source}")]
     RdpLaunchError {
         #[source]
         source: io::Error,
@@ -330,6 +330,114 @@ impl From<serde_json::Error> for AppError {
     }
 }
 
+/// Default LDAP port, used when an ldap3 error doesn't say which port failed
+const LDAP_DEFAULT_PORT: u16 = 389;
+
+/// Placeholder for context (server, user, base DN) an ldap3 error doesn't carry
+///
+/// Callers that know the real value replace it; see `core::ldap`.
+pub const LDAP_UNKNOWN_CONTEXT: &str = "unknown";
+
+/// Describes an LDAP result code (RFC 4511 appendix A)
+fn ldap_result_code_name(rc: u32) -> &'static str {
+    match rc {
+        3 => "time limit exceeded",
+        4 => "size limit exceeded",
+        8 => "stronger authentication required",
+        10 => "referral",
+        32 => "no such object",
+        34 => "invalid DN syntax",
+        48 => "inappropriate authentication",
+        49 => "invalid credentials",
+        50 => "insufficient access rights",
+        51 => "server busy",
+        52 => "server unavailable",
+        53 => "server unwilling to perform",
+        81 => "server down",
+        _ => "LDAP error",
+    }
+}
+
+/// Explains the Active Directory sub-code in an invalid-credentials diagnostic
+///
+/// AD puts the real reason in the diagnostic text, e.g.
+/// `80090308: LdapErr: DSID-0C09044E, comment: AcceptSecurityContext error, data 52e, v4563`.
+fn ad_bind_failure_reason(text: &str) -> Option<&'static str> {
+    let code = text.split("data ").nth(1)?.split([',', ' ']).next()?;
+    let reason = match code.to_ascii_lowercase().as_str() {
+        "525" => "user not found",
+        "52e" => "wrong username or password",
+        "530" => "logon not permitted at this time",
+        "531" => "logon not permitted from this workstation",
+        "532" => "password expired",
+        "533" => "account disabled",
+        "701" => "account expired",
+        "773" => "password must be reset",
+        "775" => "account locked out",
+        _ => return None,
+    };
+    Some(reason)
+}
+
+/// Maps ldap3 errors to the LDAP variant that matches their cause
+///
+/// Transport failures become `LdapConnectionError`, rejected binds (result
+/// codes 8, 48, 49) `LdapBindError`, and other result codes `LdapSearchError`.
+/// Fields the error doesn't carry are set to `LDAP_UNKNOWN_CONTEXT`.
+impl From<ldap3::LdapError> for AppError {
+    fn from(err: ldap3::LdapError) -> Self {
+        use ldap3::LdapError;
+
+        let connection_error = |err: LdapError| AppError::LdapConnectionError {
+            server: LDAP_UNKNOWN_CONTEXT.to_string(),
+            port: LDAP_DEFAULT_PORT,
+            source: err.into(),
+        };
+
+        match err {
+            LdapError::LdapResult { result } => {
+                let mut reason = format!("{} (result code {})", ldap_result_code_name(result.rc), result.rc);
+                if let Some(ad_reason) = ad_bind_failure_reason(&result.text) {
+                    reason.push_str(&format!(": {}", ad_reason));
+                } else if !result.text.trim().is_empty() {
+                    reason.push_str(&format!(": {}", result.text.trim()));
+                }
+
+                match result.rc {
+                    8 | 48 | 49 => AppError::LdapBindError {
+                        username: LDAP_UNKNOWN_CONTEXT.to_string(),
+                        source: anyhow::anyhow!(reason),
+                    },
+                    51 | 52 | 81 => AppError::LdapConnectionError {
+                        server: LDAP_UNKNOWN_CONTEXT.to_string(),
+                        port: LDAP_DEFAULT_PORT,
+                        source: anyhow::anyhow!(reason),
+                    },
+                    _ => AppError::LdapSearchError {
+                        base_dn: if result.matched.is_empty() {
+                            LDAP_UNKNOWN_CONTEXT.to_string()
+                        } else {
+                            result.matched
+                        },
+                        source: anyhow::anyhow!(reason),
+                    },
+                }
+            }
+            err @ (LdapError::Io { .. }
+            | LdapError::Timeout { .. }
+            | LdapError::OpSend { .. }
+            | LdapError::ResultRecv { .. }
+            | LdapError::IdScrubSend { .. }
+            | LdapError::MiscSend { .. }
+            | LdapError::EndOfStream) => connection_error(err),
+            err => AppError::Other {
+                message: format!("LDAP error: {}", err),
+                source: Some(err.into()),
+            },
+        }
+    }
+}
+
 impl From<tauri::Error> for AppError {
     fn from(err: tauri::Error) -> Self {
         AppError::WindowOperationError {
@@ -338,3 +446,112 @@ impl From<tauri::Error> for AppError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ldap3::{LdapError, LdapResult};
+
+    fn ldap_result(rc: u32, matched: &str, text: &str) -> LdapError {
+        LdapError::LdapResult {
+            result: LdapResult {
+                rc,
+                matched: matched.to_string(),
+                text: text.to_string(),
+                refs: Vec::new(),
+                ctrls: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_ldap_invalid_credentials_maps_to_bind_error() {
+        let err = AppError::from(ldap_result(
+            49,
+            "",
+            "80090308: LdapErr: DSID-0C09044E, comment: AcceptSecurityContext error, data 52e, v4563",
+        ));
+
+        assert!(matches!(err, AppError::LdapBindError { .. }));
+        assert_eq!(err.code(), "LDAP_BIND");
+        let source = std::error::Error::source(&err).expect("Source should be kept").to_string();
+        assert_eq!(source, "invalid credentials (result code 49): wrong username or password");
+    }
+
+    #[test]
+    fn test_ldap_ad_bind_sub_codes() {
+        for (data, reason) in [("775", "account locked out"), ("532", "password expired"), ("533", "account disabled")] {
+            let err = AppError::from(ldap_result(49, "", &format!("AcceptSecurityContext error, data {}, v4563", data)));
+            let source = std::error::Error::source(&err).unwrap().to_string();
+            assert!(source.ends_with(reason), "data {}: {}", data, source);
+        }
+    }
+
+    #[test]
+    fn test_ldap_stronger_auth_required_maps_to_bind_error() {
+        let err = AppError::from(ldap_result(8, "", "The server requires binds to turn on integrity checking"));
+
+        assert_eq!(err.code(), "LDAP_BIND");
+        let source = std::error::Error::source(&err).unwrap().to_string();
+        assert!(source.starts_with("stronger authentication required (result code 8)"));
+    }
+
+    #[test]
+    fn test_ldap_io_error_maps_to_connection_error() {
+        let err = AppError::from(LdapError::Io {
+            source: io::Error::new(io::ErrorKind::ConnectionRefused, "connection refused"),
+        });
+
+        match &err {
+            AppError::LdapConnectionError { server, port, .. } => {
+                assert_eq!(server, LDAP_UNKNOWN_CONTEXT);
+                assert_eq!(*port, 389);
+            }
+            other => panic!("Expected LdapConnectionError, got {:?}", other),
+        }
+        assert_eq!(err.code(), "LDAP_CONNECTION");
+    }
+
+    #[test]
+    fn test_ldap_server_unavailable_maps_to_connection_error() {
+        let err = AppError::from(ldap_result(52, "", ""));
+
+        assert_eq!(err.code(), "LDAP_CONNECTION");
+        let source = std::error::Error::source(&err).unwrap().to_string();
+        assert_eq!(source, "server unavailable (result code 52)");
+    }
+
+    #[tokio::test]
+    async fn test_ldap_timeout_maps_to_connection_error() {
+        let elapsed = tokio::time::timeout(std::time::Duration::from_millis(1), std::future::pending::<()>())
+            .await
+            .expect_err("Pending future should time out");
+
+        let err = AppError::from(LdapError::Timeout { elapsed });
+
+        assert_eq!(err.code(), "LDAP_CONNECTION");
+    }
+
+    #[test]
+    fn test_ldap_other_result_codes_map_to_search_error() {
+        let err = AppError::from(ldap_result(32, "DC=contoso,DC=com", "0000208D: NameErr"));
+
+        match &err {
+            AppError::LdapSearchError { base_dn, .. } => assert_eq!(base_dn, "DC=contoso,DC=com"),
+            other => panic!("Expected LdapSearchError, got {:?}", other),
+        }
+        assert_eq!(err.code(), "LDAP_SEARCH");
+        assert!(err.to_string().contains("no such object (result code 32): 0000208D: NameErr"));
+
+        let access = AppError::from(ldap_result(50, "", ""));
+        assert_eq!(access.code(), "LDAP_SEARCH");
+    }
+
+    #[test]
+    fn test_ldap_client_side_errors_map_to_other() {
+        let err = AppError::from(LdapError::FilterParsing);
+
+        assert_eq!(err.code(), "GENERAL");
+        assert_eq!(err.to_string(), "LDAP error: filter parse error");
+    }
+}
@@ -3,10 +3,12 @@
 //! Provides structured logging using the tracing ecosystem with file output.
 //! Maintains backward compatibility with the legacy debug_log interface.
 
+use super::paths::DEBUG_LOG_FILE_NAME;
 use chrono::Local;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 /// Global flag indicating whether debug logging is enabled.
@@ -24,6 +26,98 @@ pub fn set_debug_mode(enabled: bool) {
     }
 }
 
+/// Severity of a log entry, least severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Parses "DEBUG", "INFO", "WARN"/"WARNING" or "ERROR" (case-insensitive)
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_ascii_uppercase().as_str() {
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "ERROR" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Per-category log thresholds, keyed by category or prefix pattern (`LDAP_*`).
+///
+/// Categories listed here are logged at their own level even when debug mode
+/// is off; all others follow debug mode.
+static CATEGORY_LEVELS: Mutex<BTreeMap<String, LogLevel>> = Mutex::new(BTreeMap::new());
+
+/// Sets the log threshold for a category, or clears it
+///
+/// `category` is an exact category name (`LDAP_SEARCH`) or a prefix pattern
+/// ending in `*` (`LDAP_*`). A `level` of "DEFAULT" (or blank) removes the
+/// override so the category follows debug mode again.
+///
+/// # Returns
+/// * `Ok(())` - Threshold updated
+/// * `Err(String)` - Blank category or unrecognised level
+pub fn set_category_log_level(category: &str, level: &str) -> Result<(), String> {
+    let category = category.trim().to_ascii_uppercase();
+    if category.is_empty() || category == "*" {
+        return Err("Log category cannot be empty".to_string());
+    }
+
+    let mut levels = CATEGORY_LEVELS.lock().unwrap_or_else(|e| e.into_inner());
+    if level.trim().is_empty() || level.trim().eq_ignore_ascii_case("DEFAULT") {
+        levels.remove(&category);
+        return Ok(());
+    }
+
+    let level = LogLevel::parse(level).ok_or_else(|| {
+        format!("Unknown log level '{}' (expected DEBUG, INFO, WARN, ERROR or DEFAULT)", level)
+    })?;
+    levels.insert(category, level);
+    Ok(())
+}
+
+/// Returns the threshold configured for a category
+///
+/// An exact entry wins over patterns; among patterns the longest prefix wins.
+fn category_threshold(category: &str, levels: &BTreeMap<String, LogLevel>) -> Option<LogLevel> {
+    let category = category.to_ascii_uppercase();
+    if let Some(level) = levels.get(&category) {
+        return Some(*level);
+    }
+
+    levels
+        .iter()
+        .filter_map(|(pattern, level)| {
+            let prefix = pattern.strip_suffix('*')?;
+            category.starts_with(prefix).then_some((prefix.len(), *level))
+        })
+        .max_by_key(|(length, _)| *length)
+        .map(|(_, level)| level)
+}
+
+/// Decides whether an entry is written
+///
+/// A category with its own threshold logs entries at or above it. Other
+/// categories log everything in debug mode and nothing otherwise. An
+/// unrecognised `level` counts as DEBUG.
+fn should_log(
+    level: &str,
+    category: &str,
+    debug_enabled: bool,
+    levels: &BTreeMap<String, LogLevel>,
+) -> bool {
+    match category_threshold(category, levels) {
+        Some(threshold) => LogLevel::parse(level).unwrap_or(LogLevel::Debug) >= threshold,
+        None => debug_enabled,
+    }
+}
+
 /// Initializes the tracing subscriber for structured logging
 ///
 /// Sets up tracing with file output to the same location as debug_log.
@@ -36,19 +130,14 @@ pub fn init_tracing() -> Result<(), String> {
     use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
     // Determine log file path (same as debug_log)
-    let log_dir = if let Ok(appdata_dir) = std::env::var("APPDATA") {
-        let quick_connect_dir = PathBuf::from(appdata_dir).join("QuickConnect");
-        std::fs::create_dir_all(&quick_connect_dir)
-            .map_err(|e| format!("Failed to create log directory: {}", e))?;
-        quick_connect_dir
-    } else {
-        PathBuf::from(".")
-    };
+    let log_dir = super::paths::current_data_dir()?;
+    std::fs::create_dir_all(&log_dir)
+        .map_err(|e| format!("Failed to create log directory: {}", e))?;
 
-    let log_file = log_dir.join("QuickConnect_Debug.log");
+    let log_file = log_dir.join(DEBUG_LOG_FILE_NAME);
 
     // Create file appender
-    let file_appender = tracing_appender::rolling::never(&log_dir, "QuickConnect_Debug.log");
+    let file_appender = tracing_appender::rolling::never(&log_dir, DEBUG_LOG_FILE_NAME);
 
     // Configure formatting
     let file_layer = fmt::layer()
@@ -83,6 +172,9 @@ pub fn init_tracing() -> Result<(), String> {
 /// Legacy function maintained for backward compatibility.
 /// Now delegates to tracing macros for structured logging.
 ///
+/// Entries are written in debug mode, or when the category has its own
+/// threshold (see `set_category_log_level`) and `level` meets it.
+///
 /// # Arguments
 /// * `level` - Log level: "INFO", "WARN", "ERROR", "DEBUG"
 /// * `category` - Log category for grouping related messages
@@ -92,8 +184,12 @@ pub fn debug_log(level: &str, category: &str, message: &str, error_details: Opti
     use tracing::{debug, error, info, warn};
 
     let debug_enabled = DEBUG_MODE.lock().map(|flag| *flag).unwrap_or(false);
+    let enabled = CATEGORY_LEVELS
+        .lock()
+        .map(|levels| should_log(level, category, debug_enabled, &levels))
+        .unwrap_or(debug_enabled);
 
-    if !debug_enabled {
+    if !enabled {
         return;
     }
 
@@ -107,16 +203,14 @@ pub fn debug_log(level: &str, category: &str, message: &str, error_details: Opti
     }
 
     // Use AppData\Roaming\QuickConnect for reliable write permissions
-    // This location works even when app is installed in Program Files (read-only)
-    let log_file = if let Ok(appdata_dir) = std::env::var("APPDATA") {
-        let quick_connect_dir = PathBuf::from(appdata_dir).join("QuickConnect");
+    // This location works even when app is installed in Program Files (read-only);
+    // infra::paths falls back to other per-user locations if APPDATA is unset
+    let log_file = if let Ok(quick_connect_dir) = super::paths::current_data_dir() {
         // Ensure directory exists (idempotent operation)
         let _ = std::fs::create_dir_all(&quick_connect_dir);
-        quick_connect_dir.join("QuickConnect_Debug.log")
+        quick_connect_dir.join(DEBUG_LOG_FILE_NAME)
     } else {
-        // Fallback to current directory if APPDATA not available
-        // This should never happen on Windows, but provides safety
-        PathBuf::from("QuickConnect_Debug.log")
+        PathBuf::from(DEBUG_LOG_FILE_NAME)
     };
 
     // Check if file is new (to add header)
@@ -164,6 +258,58 @@ pub fn debug_log(level: &str, category: &str, message: &str, error_details: Opti
     }
 }
 
+/// Size of the blocks read backwards from the end of the log by `read_log_tail`
+const TAIL_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads the last `lines` lines of a log file, oldest first
+///
+/// Reads backwards from the end in fixed-size blocks until enough line breaks
+/// have been seen, so only the tail of a large log is loaded. A trailing line
+/// break doesn't count as an empty last line; invalid UTF-8 is replaced.
+///
+/// # Returns
+/// * `Ok(Vec<String>)` - Up to `lines` lines (empty if the file doesn't exist)
+/// * `Err(std::io::Error)` - The file exists but couldn't be read
+pub fn read_log_tail(path: &Path, lines: usize) -> std::io::Result<Vec<String>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let len = file.metadata()?.len();
+    if lines == 0 || len == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut start = 0;
+    let mut pos = len;
+    let mut newlines = 0;
+    let mut buf = vec![0u8; TAIL_CHUNK_SIZE];
+    'search: while pos > 0 {
+        let read = TAIL_CHUNK_SIZE.min(pos as usize);
+        pos -= read as u64;
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut buf[..read])?;
+
+        for (offset, byte) in buf[..read].iter().enumerate().rev() {
+            let at = pos + offset as u64;
+            if *byte == b'\n' && at != len - 1 {
+                newlines += 1;
+                if newlines == lines {
+                    start = at + 1;
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    file.seek(SeekFrom::Start(start))?;
+    let mut tail = Vec::with_capacity((len - start) as usize);
+    file.read_to_end(&mut tail)?;
+
+    Ok(String::from_utf8_lossy(&tail).lines().map(str::to_string).collect())
+}
+
 fn write_log_header(file: &mut std::fs::File) -> std::io::Result<()> {
     writeln!(file, "{}", "=".repeat(80))?;
     writeln!(file, "QuickConnect Debug Log")?;
@@ -202,10 +348,8 @@ fn add_category_context(log_entry: &mut String, category: &str) {
     match category {
         "RDP_LAUNCH" => {
             // Add RDP temporary files location for troubleshooting
-            if let Ok(appdata_dir) = std::env::var("APPDATA") {
-                let connections_dir = PathBuf::from(appdata_dir)
-                    .join("QuickConnect")
-                    .join("Connections");
+            if let Ok(data_dir) = super::paths::current_data_dir() {
+                let connections_dir = super::paths::connections_dir_in(&data_dir);
                 log_entry.push_str(&format!("RDP Files Directory: {:?}\n", connections_dir));
             }
         }
@@ -305,3 +449,142 @@ fn add_error_troubleshooting(log_entry: &mut String, category: &str) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_log(contents: &str) -> (TempDir, PathBuf) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join(DEBUG_LOG_FILE_NAME);
+        std::fs::write(&path, contents).expect("Failed to write log");
+        (temp_dir, path)
+    }
+
+    fn levels(entries: &[(&str, LogLevel)]) -> BTreeMap<String, LogLevel> {
+        entries
+            .iter()
+            .map(|(category, level)| (category.to_string(), *level))
+            .collect()
+    }
+
+    #[test]
+    fn test_category_at_debug_logs_while_others_suppress_info() {
+        let levels = levels(&[("LDAP_*", LogLevel::Debug), ("HOST_OPERATIONS", LogLevel::Warn)]);
+
+        // LDAP categories log everything, even with debug mode off
+        assert!(should_log("DEBUG", "LDAP_SEARCH", false, &levels));
+        assert!(should_log("INFO", "LDAP_BIND", false, &levels));
+
+        // A category at WARN drops INFO and DEBUG but keeps WARN and ERROR
+        assert!(!should_log("INFO", "HOST_OPERATIONS", true, &levels));
+        assert!(!should_log("DEBUG", "HOST_OPERATIONS", true, &levels));
+        assert!(should_log("WARN", "HOST_OPERATIONS", false, &levels));
+        assert!(should_log("ERROR", "HOST_OPERATIONS", false, &levels));
+    }
+
+    #[test]
+    fn test_unlisted_category_follows_debug_mode() {
+        let levels = levels(&[("LDAP_*", LogLevel::Debug)]);
+
+        assert!(!should_log("ERROR", "RDP_LAUNCH", false, &levels));
+        assert!(should_log("DEBUG", "RDP_LAUNCH", true, &levels));
+    }
+
+    #[test]
+    fn test_exact_and_longest_pattern_win() {
+        let levels = levels(&[
+            ("LDAP_*", LogLevel::Debug),
+            ("LDAP_SEARCH*", LogLevel::Warn),
+            ("LDAP_BIND", LogLevel::Error),
+        ]);
+
+        assert_eq!(category_threshold("ldap_connection", &levels), Some(LogLevel::Debug));
+        assert_eq!(category_threshold("LDAP_SEARCH", &levels), Some(LogLevel::Warn));
+        assert_eq!(category_threshold("LDAP_BIND", &levels), Some(LogLevel::Error));
+        assert_eq!(category_threshold("CREDENTIALS", &levels), None);
+    }
+
+    #[test]
+    fn test_unknown_entry_level_counts_as_debug() {
+        let levels = levels(&[("TRAY", LogLevel::Info)]);
+
+        assert!(!should_log("TRACE", "TRAY", true, &levels));
+    }
+
+    #[test]
+    fn test_log_level_parse() {
+        assert_eq!(LogLevel::parse("debug"), Some(LogLevel::Debug));
+        assert_eq!(LogLevel::parse(" Warning "), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse("ERROR"), Some(LogLevel::Error));
+        assert_eq!(LogLevel::parse("verbose"), None);
+    }
+
+    #[test]
+    fn test_set_category_log_level_validates_input() {
+        assert!(set_category_log_level("", "DEBUG").is_err());
+        assert!(set_category_log_level("*", "DEBUG").is_err());
+        assert!(set_category_log_level("TEST_CATEGORY_LEVEL", "loud").is_err());
+
+        set_category_log_level("test_category_level", "debug").expect("Valid level");
+        assert_eq!(
+            category_threshold("TEST_CATEGORY_LEVEL", &CATEGORY_LEVELS.lock().unwrap()),
+            Some(LogLevel::Debug)
+        );
+
+        set_category_log_level("TEST_CATEGORY_LEVEL", "default").expect("Clearing is valid");
+        assert_eq!(
+            category_threshold("TEST_CATEGORY_LEVEL", &CATEGORY_LEVELS.lock().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_log_tail_returns_last_lines_in_order() {
+        let (_temp_dir, path) = write_log("one\ntwo\nthree\nfour\nfive\n");
+
+        assert_eq!(read_log_tail(&path, 2).unwrap(), vec!["four", "five"]);
+        assert_eq!(read_log_tail(&path, 5).unwrap(), vec!["one", "two", "three", "four", "five"]);
+    }
+
+    #[test]
+    fn test_read_log_tail_more_lines_than_file() {
+        let (_temp_dir, path) = write_log("one\ntwo");
+
+        assert_eq!(read_log_tail(&path, 10).unwrap(), vec!["one", "two"]);
+        assert_eq!(read_log_tail(&path, 1).unwrap(), vec!["two"]);
+    }
+
+    #[test]
+    fn test_read_log_tail_spans_chunks() {
+        let contents: String = (0..5000).map(|i| format!("line {:04} {}\r\n", i, "x".repeat(20))).collect();
+        assert!(contents.len() > TAIL_CHUNK_SIZE * 10);
+        let (_temp_dir, path) = write_log(&contents);
+
+        let tail = read_log_tail(&path, 1200).unwrap();
+
+        assert_eq!(tail.len(), 1200);
+        assert_eq!(tail[0], format!("line 3800 {}", "x".repeat(20)));
+        assert_eq!(tail[1199], format!("line 4999 {}", "x".repeat(20)));
+    }
+
+    #[test]
+    fn test_read_log_tail_keeps_blank_lines() {
+        let (_temp_dir, path) = write_log("header\n\nentry\n");
+
+        assert_eq!(read_log_tail(&path, 2).unwrap(), vec!["", "entry"]);
+    }
+
+    #[test]
+    fn test_read_log_tail_missing_or_empty() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        assert!(read_log_tail(&temp_dir.path().join("missing.log"), 10).unwrap().is_empty());
+
+        let (_temp_dir, path) = write_log("");
+        assert!(read_log_tail(&path, 10).unwrap().is_empty());
+
+        let (_temp_dir, path) = write_log("one\n");
+        assert!(read_log_tail(&path, 0).unwrap().is_empty());
+    }
+}
@@ -3,5 +3,7 @@
 pub mod logging;
 pub mod paths;
 
-pub use logging::{debug_log, init_tracing, set_debug_mode};
-pub use paths::{get_hosts_csv_path, get_recent_connections_path};
+pub use logging::{debug_log, init_tracing, read_log_tail, set_category_log_level, set_debug_mode};
+pub use paths::{
+    get_connection_history_path, get_connection_usage_path, get_connections_dir, get_credential_meta_path, get_debug_log_path, get_hosts_csv_path, get_hosts_json_path, get_migrated_credentials_path, get_recent_connections_path, get_settings_path,
+};
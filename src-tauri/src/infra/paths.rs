@@ -12,7 +12,119 @@
 //! logic. Keeping them in infra/ makes dependencies clear and enables future changes
 //! (e.g., supporting custom data directories) without touching core or command layers.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable that relocates all QuickConnect data.
+///
+/// Used for portable installs and for users whose roaming profile has a size
+/// limit. When set, data is stored directly in this directory (no `QuickConnect`
+/// subfolder is appended).
+pub const DATA_DIR_ENV_VAR: &str = "QUICKCONNECT_DATA_DIR";
+
+/// Where the per-user base directory was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseDirSource {
+    /// `%APPDATA%` (the normal case)
+    AppData,
+    /// `%LOCALAPPDATA%`
+    LocalAppData,
+    /// `%USERPROFILE%\AppData\Roaming`
+    UserProfile,
+    /// The system temp directory (last resort, data may not persist)
+    TempDir,
+}
+
+/// Set once a fallback base directory has been logged, so it is reported only once
+static FALLBACK_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Resolves the per-user base directory that holds the QuickConnect folder.
+///
+/// Some service accounts run without `%APPDATA%`, so this falls back to
+/// `%LOCALAPPDATA%`, then `%USERPROFILE%\AppData\Roaming`, then the temp
+/// directory. Empty variables are treated as unset.
+///
+/// # Arguments
+/// * `env` - Environment lookup (`std::env::var` in production)
+pub fn resolve_base_dir<F>(env: F) -> (PathBuf, BaseDirSource)
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let var = |name: &str| env(name).filter(|value| !value.trim().is_empty());
+
+    if let Some(appdata) = var("APPDATA") {
+        (PathBuf::from(appdata), BaseDirSource::AppData)
+    } else if let Some(local_appdata) = var("LOCALAPPDATA") {
+        (PathBuf::from(local_appdata), BaseDirSource::LocalAppData)
+    } else if let Some(profile) = var("USERPROFILE") {
+        (
+            PathBuf::from(profile).join("AppData").join("Roaming"),
+            BaseDirSource::UserProfile,
+        )
+    } else {
+        (std::env::temp_dir(), BaseDirSource::TempDir)
+    }
+}
+
+/// Resolves the per-user base directory from the current environment.
+///
+/// # Side Effects
+/// - Logs (once per run) when a fallback location is used instead of `%APPDATA%`
+pub fn base_dir() -> PathBuf {
+    let (dir, source) = resolve_base_dir(|name| std::env::var(name).ok());
+
+    // swap() also guards against recursion, since debug_log resolves paths itself
+    if source != BaseDirSource::AppData && !FALLBACK_LOGGED.swap(true, Ordering::SeqCst) {
+        super::debug_log(
+            "WARN",
+            "PATHS",
+            &format!("APPDATA is not set, using {:?} from {:?}", dir, source),
+            None,
+        );
+    }
+
+    dir
+}
+
+/// Resolves the application data directory without touching the filesystem.
+///
+/// # Arguments
+/// * `data_dir_override` - Value of `QUICKCONNECT_DATA_DIR`, if set (blank is ignored)
+/// * `appdata` - Value of `APPDATA`, if set
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The override directory, or `{appdata}\QuickConnect`
+/// * `Err(String)` - Neither an override nor APPDATA is available
+pub fn resolve_data_dir(
+    data_dir_override: Option<String>,
+    appdata: Option<String>,
+) -> Result<PathBuf, String> {
+    if let Some(dir) = data_dir_override.filter(|dir| !dir.trim().is_empty()) {
+        return Ok(PathBuf::from(dir.trim()));
+    }
+
+    let appdata_dir = appdata.ok_or_else(|| "Failed to get APPDATA directory".to_string())?;
+    Ok(PathBuf::from(appdata_dir).join("QuickConnect"))
+}
+
+/// Resolves the application data directory from the current environment.
+///
+/// Like `get_quick_connect_dir` but never creates the directory. Uses
+/// `base_dir()` (with its fallbacks) when no override is set.
+pub fn current_data_dir() -> Result<PathBuf, String> {
+    if let Ok(dir) = std::env::var(DATA_DIR_ENV_VAR) {
+        if !dir.trim().is_empty() {
+            return resolve_data_dir(Some(dir), None);
+        }
+    }
+
+    resolve_data_dir(None, Some(base_dir().to_string_lossy().into_owned()))
+}
+
+/// Returns the RDP connections directory beneath a data directory.
+pub fn connections_dir_in(data_dir: &Path) -> PathBuf {
+    data_dir.join("Connections")
+}
 
 /// Gets the QuickConnect application data directory.
 ///
@@ -21,21 +133,18 @@ use std::path::PathBuf;
 /// All file operations should use this function to ensure consistency.
 ///
 /// # Returns
-/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect`
-/// * `Err(String)` - If APPDATA environment variable is not set
+/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect`, or `QUICKCONNECT_DATA_DIR` if set
+/// * `Err(String)` - If the directory cannot be created
 ///
 /// # Side Effects
 /// - Creates the QuickConnect directory if it doesn't exist
 /// - Creates parent directories as needed
 ///
 /// # Failure Modes
-/// - APPDATA environment variable not set (rare on Windows)
 /// - Permission denied when creating directory
 /// - Disk full
 pub fn get_quick_connect_dir() -> Result<PathBuf, String> {
-    let appdata_dir =
-        std::env::var("APPDATA").map_err(|_| "Failed to get APPDATA directory".to_string())?;
-    let quick_connect_dir = PathBuf::from(appdata_dir).join("QuickConnect");
+    let quick_connect_dir = current_data_dir()?;
     std::fs::create_dir_all(&quick_connect_dir)
         .map_err(|e| format!("Failed to create QuickConnect directory: {}", e))?;
     Ok(quick_connect_dir)
@@ -58,6 +167,18 @@ pub fn get_hosts_csv_path() -> Result<PathBuf, String> {
     Ok(quick_connect_dir.join("hosts.csv"))
 }
 
+/// Gets the full path to the hosts JSON file.
+///
+/// Used instead of hosts.csv when the `storage_format` setting is `"json"`.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect\hosts.json`
+/// * `Err(String)` - If application directory cannot be accessed
+pub fn get_hosts_json_path() -> Result<PathBuf, String> {
+    let quick_connect_dir = get_quick_connect_dir()?;
+    Ok(quick_connect_dir.join("hosts.json"))
+}
+
 /// Gets the full path to the recent connections JSON file.
 ///
 /// # Why this exists
@@ -74,24 +195,286 @@ pub fn get_recent_connections_path() -> Result<PathBuf, String> {
     Ok(quick_connect_dir.join("recent_connections.json"))
 }
 
+/// Gets the full path to the connection history file.
+///
+/// # Why this exists
+/// Centralizes the connection history file location for consistent access.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect\connection_history.jsonl`
+/// * `Err(String)` - If application directory cannot be accessed
+///
+/// # Side Effects
+/// - Creates the QuickConnect directory if it doesn't exist (via get_quick_connect_dir)
+pub fn get_connection_history_path() -> Result<PathBuf, String> {
+    let quick_connect_dir = get_quick_connect_dir()?;
+    Ok(quick_connect_dir.join("connection_history.jsonl"))
+}
+
+/// Gets the full path to the rolling connection usage JSON file.
+///
+/// # Why this exists
+/// Recent connection times per host are kept in a sidecar rather than in
+/// hosts.csv, which only has the lifetime count.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect\connection_usage.json`
+/// * `Err(String)` - If application directory cannot be accessed
+///
+/// # Side Effects
+/// - Creates the QuickConnect directory if it doesn't exist (via get_quick_connect_dir)
+pub fn get_connection_usage_path() -> Result<PathBuf, String> {
+    let quick_connect_dir = get_quick_connect_dir()?;
+    Ok(quick_connect_dir.join("connection_usage.json"))
+}
+
+/// Gets the full path to the global credential metadata JSON file.
+///
+/// # Why this exists
+/// When the global credentials were saved is kept next to, not inside, the
+/// encrypted Credential Manager entry.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect\credential_meta.json`
+/// * `Err(String)` - If application directory cannot be accessed
+///
+/// # Side Effects
+/// - Creates the QuickConnect directory if it doesn't exist (via get_quick_connect_dir)
+pub fn get_credential_meta_path() -> Result<PathBuf, String> {
+    let quick_connect_dir = get_quick_connect_dir()?;
+    Ok(quick_connect_dir.join("credential_meta.json"))
+}
+
+/// File name of the record of credentials migrated to the domain type
+pub const MIGRATED_CREDENTIALS_FILE_NAME: &str = "migrated_credentials.json";
+
+/// Gets the full path to the record of credentials migrated to the domain type.
+///
+/// # Why this exists
+/// Credential Manager can't tell a migrated `TERMSRV/{hostname}` credential
+/// from the domain copy a launch writes, so migrations are recorded here.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect\migrated_credentials.json`
+/// * `Err(String)` - If application directory cannot be accessed
+///
+/// # Side Effects
+/// - Creates the QuickConnect directory if it doesn't exist (via get_quick_connect_dir)
+pub fn get_migrated_credentials_path() -> Result<PathBuf, String> {
+    let quick_connect_dir = get_quick_connect_dir()?;
+    Ok(quick_connect_dir.join(MIGRATED_CREDENTIALS_FILE_NAME))
+}
+
+/// Gets the full path to the settings JSON file.
+///
+/// # Why this exists
+/// Centralizes the settings file location (RDP options and other preferences).
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect\settings.json`
+/// * `Err(String)` - If application directory cannot be accessed
+///
+/// # Side Effects
+/// - Creates the QuickConnect directory if it doesn't exist (via get_quick_connect_dir)
+pub fn get_settings_path() -> Result<PathBuf, String> {
+    let quick_connect_dir = get_quick_connect_dir()?;
+    Ok(quick_connect_dir.join("settings.json"))
+}
+
+/// File name of the debug log in the data directory
+pub const DEBUG_LOG_FILE_NAME: &str = "QuickConnect_Debug.log";
+
+/// Gets the full path to the debug log file.
+///
+/// # Why this exists
+/// The log follows the data directory, so viewers must resolve it the same
+/// way the logger does.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Path to `{data dir}\QuickConnect_Debug.log` (may not exist yet)
+/// * `Err(String)` - If the data directory cannot be resolved
+pub fn get_debug_log_path() -> Result<PathBuf, String> {
+    Ok(current_data_dir()?.join(DEBUG_LOG_FILE_NAME))
+}
+
 /// Gets the full path to the RDP connections directory.
 ///
 /// # Why this exists
 /// Centralizes the RDP files storage location. All RDP file operations
-/// should use this path for consistency.
+/// should use this path for consistency, so relocating the data directory
+/// also relocates the RDP files.
 ///
 /// # Returns
-/// * `Ok(PathBuf)` - Path to `%APPDATA%\Roaming\QuickConnect\Connections`
+/// * `Ok(PathBuf)` - Path to `{data dir}\Connections`
 /// * `Err(String)` - If application directory cannot be accessed
 ///
 /// # Side Effects
 /// - Creates the QuickConnect directory if it doesn't exist (via get_quick_connect_dir)
 /// - Creates the Connections subdirectory if it doesn't exist
-#[allow(dead_code)]
 pub fn get_connections_dir() -> Result<PathBuf, String> {
     let quick_connect_dir = get_quick_connect_dir()?;
-    let connections_dir = quick_connect_dir.join("Connections");
+    let connections_dir = connections_dir_in(&quick_connect_dir);
     std::fs::create_dir_all(&connections_dir)
         .map_err(|e| format!("Failed to create Connections directory: {}", e))?;
     Ok(connections_dir)
 }
+
+/// Test helper that serializes changes to the environment variables data
+/// paths are resolved from, and restores them when dropped
+///
+/// Every test in the crate that sets these variables, or that reads paths
+/// which depend on them, must hold one of these; a lock per module would
+/// still let tests in different modules see each other's values.
+#[cfg(test)]
+pub(crate) struct EnvGuard {
+    saved: Vec<(&'static str, Option<std::ffi::OsString>)>,
+    _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl EnvGuard {
+    /// Variables restored on drop
+    const VARS: [&'static str; 4] = ["APPDATA", "LOCALAPPDATA", "USERPROFILE", DATA_DIR_ENV_VAR];
+
+    /// Waits for other tests using the environment, then records its current values
+    pub(crate) fn lock() -> Self {
+        static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+        let lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        Self {
+            saved: Self::VARS.iter().map(|&var| (var, std::env::var_os(var))).collect(),
+            _lock: lock,
+        }
+    }
+
+    pub(crate) fn set(&self, var: &str, value: impl AsRef<std::ffi::OsStr>) {
+        debug_assert!(Self::VARS.contains(&var), "{} is not restored by EnvGuard", var);
+        std::env::set_var(var, value);
+    }
+
+    pub(crate) fn remove(&self, var: &str) {
+        debug_assert!(Self::VARS.contains(&var), "{} is not restored by EnvGuard", var);
+        std::env::remove_var(var);
+    }
+}
+
+#[cfg(test)]
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        // Runs before `_lock` is released
+        for (var, value) in &self.saved {
+            match value {
+                Some(value) => std::env::set_var(var, value),
+                None => std::env::remove_var(var),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_data_dir_defaults_to_appdata() {
+        let dir = resolve_data_dir(None, Some("C:\\Users\\me\\AppData\\Roaming".to_string()))
+            .expect("APPDATA should resolve");
+        assert_eq!(
+            dir,
+            PathBuf::from("C:\\Users\\me\\AppData\\Roaming").join("QuickConnect")
+        );
+    }
+
+    #[test]
+    fn test_resolve_data_dir_prefers_override() {
+        let dir = resolve_data_dir(
+            Some("D:\\QuickConnectData".to_string()),
+            Some("C:\\Users\\me\\AppData\\Roaming".to_string()),
+        )
+        .expect("Override should resolve");
+        assert_eq!(dir, PathBuf::from("D:\\QuickConnectData"));
+    }
+
+    #[test]
+    fn test_resolve_data_dir_ignores_blank_override() {
+        let dir = resolve_data_dir(Some("  ".to_string()), Some("C:\\AppData".to_string()))
+            .expect("APPDATA should resolve");
+        assert_eq!(dir, PathBuf::from("C:\\AppData").join("QuickConnect"));
+    }
+
+    #[test]
+    fn test_resolve_data_dir_fails_without_override_or_appdata() {
+        assert!(resolve_data_dir(None, None).is_err());
+    }
+
+    #[test]
+    fn test_connections_dir_resolves_beneath_custom_data_dir() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let data_dir = resolve_data_dir(
+            Some(temp_dir.path().to_string_lossy().to_string()),
+            None,
+        )
+        .expect("Override should resolve");
+
+        let connections_dir = connections_dir_in(&data_dir);
+
+        assert!(connections_dir.starts_with(temp_dir.path()));
+        assert_eq!(connections_dir, temp_dir.path().join("Connections"));
+    }
+
+    fn env_with(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: std::collections::HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_resolve_base_dir_prefers_appdata() {
+        let env = env_with(&[
+            ("APPDATA", "C:\\Users\\me\\AppData\\Roaming"),
+            ("LOCALAPPDATA", "C:\\Users\\me\\AppData\\Local"),
+            ("USERPROFILE", "C:\\Users\\me"),
+        ]);
+
+        let (dir, source) = resolve_base_dir(env);
+
+        assert_eq!(source, BaseDirSource::AppData);
+        assert_eq!(dir, PathBuf::from("C:\\Users\\me\\AppData\\Roaming"));
+    }
+
+    #[test]
+    fn test_resolve_base_dir_falls_back_to_local_appdata() {
+        let env = env_with(&[
+            ("LOCALAPPDATA", "C:\\Users\\me\\AppData\\Local"),
+            ("USERPROFILE", "C:\\Users\\me"),
+        ]);
+
+        let (dir, source) = resolve_base_dir(env);
+
+        assert_eq!(source, BaseDirSource::LocalAppData);
+        assert_eq!(dir, PathBuf::from("C:\\Users\\me\\AppData\\Local"));
+    }
+
+    #[test]
+    fn test_resolve_base_dir_falls_back_to_user_profile() {
+        let env = env_with(&[("APPDATA", ""), ("USERPROFILE", "C:\\Users\\svc")]);
+
+        let (dir, source) = resolve_base_dir(env);
+
+        assert_eq!(source, BaseDirSource::UserProfile);
+        assert_eq!(
+            dir,
+            PathBuf::from("C:\\Users\\svc").join("AppData").join("Roaming")
+        );
+    }
+
+    #[test]
+    fn test_resolve_base_dir_falls_back_to_temp_dir() {
+        let (dir, source) = resolve_base_dir(env_with(&[]));
+
+        assert_eq!(source, BaseDirSource::TempDir);
+        assert_eq!(dir, std::env::temp_dir());
+    }
+}
@@ -47,7 +47,6 @@ pub use adapters::{CredentialManager, RegistryAdapter, WindowsCredentialManager,
 //
 // Run tests with: `cargo test`
 
-use std::path::PathBuf;
 use tauri::{
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     Emitter, Manager,
@@ -58,9 +57,74 @@ use tauri::{
 
 // Import functions from command modules
 use commands::windows::{LAST_HIDDEN_WINDOW, show_about};
-use commands::theme::{get_theme, set_theme};
+use commands::theme::set_theme;
+use core::single_instance::{single_instance_action, SingleInstanceAction};
+use core::window_close::{close_action, CloseAction};
 use commands::system::{build_tray_menu, launch_rdp, toggle_autostart};
 
+/// Extracts the hostname from a `--connect <hostname>` or `--connect=<hostname>` argument.
+///
+/// Returns `None` when the argument is absent or has no (non-blank) hostname.
+fn parse_connect_arg(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let target = if arg == "--connect" {
+            iter.next().map(|s| s.as_str())
+        } else {
+            arg.strip_prefix("--connect=")
+        };
+
+        if let Some(target) = target {
+            let target = target.trim();
+            return if target.is_empty() || target.starts_with("--") {
+                None
+            } else {
+                Some(target.to_string())
+            };
+        }
+    }
+    None
+}
+
+/// What closing the window labelled `window_label` does under the `close_behavior` setting.
+fn close_action_for(window_label: &str) -> CloseAction {
+    let behavior = core::settings::load_settings()
+        .map(|settings| settings.close_behavior)
+        .unwrap_or_default();
+    close_action(behavior, window_label)
+}
+
+/// Launches an RDP connection requested via `--connect` on the command line.
+///
+/// Uses the matching entry from hosts.csv (case-insensitive) when there is one so
+/// per-host settings apply; otherwise connects using a transient host entry.
+fn launch_from_command_line(app: tauri::AppHandle, hostname: String) {
+    debug_log(
+        "INFO",
+        "SYSTEM",
+        &format!("Launching connection from command line: {}", hostname),
+        None,
+    );
+
+    tauri::async_runtime::spawn(async move {
+        let host = commands::hosts::get_hosts()
+            .ok()
+            .and_then(|hosts| {
+                hosts
+                    .into_iter()
+                    .find(|h| h.hostname.eq_ignore_ascii_case(&hostname))
+            })
+            .unwrap_or_else(|| Host {
+                hostname: hostname.clone(),
+                ..Default::default()
+            });
+
+        if let Err(e) = launch_rdp(app, host, None).await {
+            eprintln!("Failed to launch RDP to {}: {}", hostname, e);
+        }
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Check for --debug or --debug-log command line argument
@@ -68,19 +132,27 @@ pub fn run() {
     let debug_enabled = args
         .iter()
         .any(|arg| arg == "--debug" || arg == "--debug-log");
+    let connect_target = parse_connect_arg(&args);
+
+    // A data directory set by IT in the registry applies unless
+    // QUICKCONNECT_DATA_DIR is already set
+    if std::env::var_os(infra::paths::DATA_DIR_ENV_VAR).is_none() {
+        if let Some(data_dir) = core::registry_defaults::registry_defaults().data_dir.clone() {
+            std::env::set_var(infra::paths::DATA_DIR_ENV_VAR, data_dir);
+        }
+    }
 
     if debug_enabled {
         eprintln!("[QuickConnect] Debug mode enabled");
         eprintln!("[QuickConnect] Args: {:?}", args);
 
-        // Show where log file will be written
-        if let Ok(appdata_dir) = std::env::var("APPDATA") {
-            let log_file = PathBuf::from(appdata_dir)
-                .join("QuickConnect")
-                .join("QuickConnect_Debug.log");
-            eprintln!("[QuickConnect] Log file will be written to: {:?}", log_file);
-        } else {
-            eprintln!("[QuickConnect] WARNING: APPDATA not found, using current directory for log");
+        // Show where log file will be written (resolved the same way as debug_log)
+        match infra::get_debug_log_path() {
+            Ok(log_file) => eprintln!("[QuickConnect] Log file will be written to: {:?}", log_file),
+            Err(e) => eprintln!(
+                "[QuickConnect] WARNING: No data directory ({}), using current directory for log",
+                e
+            ),
         }
 
         set_debug_mode(true);
@@ -122,18 +194,32 @@ pub fn run() {
     }
 
     tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            // When a second instance is launched, show the last hidden window
-            let _ = app.emit("single-instance", ());
-
-            if let Ok(window_label) = LAST_HIDDEN_WINDOW.lock() {
-                if let Some(window) = app.get_webview_window(&window_label) {
-                    let _ = window.unminimize();
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                    // Emit focus-search event if main window is shown
-                    if window_label.as_str() == "main" {
-                        let _ = window.emit("focus-search", ());
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // A second instance started with --connect forwards the connection
+            // to this instance instead of showing a window
+            let target = core::settings::load_settings()
+                .map(|settings| settings.single_instance_target)
+                .unwrap_or_default();
+            let last_hidden = LAST_HIDDEN_WINDOW
+                .lock()
+                .map(|label| label.clone())
+                .unwrap_or_else(|_| "login".to_string());
+
+            match single_instance_action(parse_connect_arg(&args), target, &last_hidden) {
+                SingleInstanceAction::Connect(hostname) => {
+                    launch_from_command_line(app.clone(), hostname);
+                }
+                SingleInstanceAction::ShowWindow(window_label) => {
+                    let _ = app.emit("single-instance", ());
+
+                    if let Some(window) = app.get_webview_window(&window_label) {
+                        let _ = window.unminimize();
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        // Emit focus-search event if main window is shown
+                        if window_label == "main" {
+                            let _ = window.emit("focus-search", ());
+                        }
                     }
                 }
             }
@@ -153,9 +239,8 @@ pub fn run() {
                 *last_hidden = "login".to_string();
             }
 
-            // Get current theme for tray menu
-            let current_theme =
-                get_theme(app.app_handle().clone()).unwrap_or_else(|_| "dark".to_string());
+            // Get current theme preference for tray menu
+            let current_theme = commands::theme::get_theme_or_default(app.app_handle().clone());
 
             // Build the tray menu with theme awareness
             let menu = build_tray_menu(app.app_handle(), &current_theme)?;
@@ -166,6 +251,10 @@ pub fn run() {
                 login_window.on_window_event(move |event| {
                     if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                         println!("Close requested for login window");
+                        if close_action_for("login") == CloseAction::Exit {
+                            app_handle.exit(0);
+                            return;
+                        }
                         if let Ok(mut last_hidden) = LAST_HIDDEN_WINDOW.lock() {
                             *last_hidden = "login".to_string();
                         }
@@ -181,8 +270,16 @@ pub fn run() {
             let app_handle = app.app_handle().clone();
             if let Some(main_window) = app.get_webview_window("main") {
                 main_window.on_window_event(move |event| {
+                    // Focusing the main window counts as activity for idle auto-hide
+                    if let tauri::WindowEvent::Focused(true) = event {
+                        commands::windows::touch_activity();
+                    }
                     if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                         println!("Close requested for main window");
+                        if close_action_for("main") == CloseAction::Exit {
+                            app_handle.exit(0);
+                            return;
+                        }
                         if let Ok(mut last_hidden) = LAST_HIDDEN_WINDOW.lock() {
                             *last_hidden = "main".to_string();
                         }
@@ -212,6 +309,22 @@ pub fn run() {
                 });
             }
 
+            // Hide the main window to the tray after the configured idle time
+            commands::windows::start_idle_auto_hide(app.app_handle().clone());
+
+            // Push host status changes to the windows when polling is enabled
+            commands::spawn_status_poller(app.app_handle().clone());
+
+            // Keep the Connections directory within the configured size, or
+            // empty it while high-security mode is on
+            commands::system::spawn_connections_cleanup();
+
+            // Remove temporary RDP files a crash left behind in high-security mode
+            core::rdp_launcher::sweep_ephemeral_rdp_files(&std::env::temp_dir());
+
+            // Follow the time of day when the theme is "auto_time"
+            commands::theme::spawn_auto_theme_timer(app.app_handle().clone());
+
             // Set up close handler for about window (just hide it)
             let app_handle = app.app_handle().clone();
             if let Some(about_window) = app.get_webview_window("about") {
@@ -361,7 +474,7 @@ pub fn run() {
                                             hosts.into_iter().find(|h| h.hostname == hostname)
                                         {
                                             if let Err(e) =
-                                                launch_rdp(app_clone.clone(), host).await
+                                                launch_rdp(app_clone.clone(), host, None).await
                                             {
                                                 eprintln!(
                                                     "Failed to launch RDP to {}: {}",
@@ -374,8 +487,9 @@ pub fn run() {
                                                 hostname: hostname.clone(),
                                                 description: String::new(),
                                                 last_connected: None,
+                                                ..Default::default()
                                             };
-                                            if let Err(e) = launch_rdp(app_clone, host).await {
+                                            if let Err(e) = launch_rdp(app_clone, host, None).await {
                                                 eprintln!(
                                                     "Failed to launch RDP to {}: {}",
                                                     hostname, e
@@ -398,13 +512,7 @@ pub fn run() {
                             match toggle_autostart() {
                                 Ok(_enabled) => {
                                     // Rebuild the entire menu with updated autostart status and current theme
-                                    if let Some(tray) = app.tray_by_id("main") {
-                                        let current_theme = get_theme(app.clone())
-                                            .unwrap_or_else(|_| "dark".to_string());
-                                        if let Ok(new_menu) = build_tray_menu(app, &current_theme) {
-                                            let _ = tray.set_menu(Some(new_menu));
-                                        }
-                                    }
+                                    commands::system::refresh_tray_menu(app);
                                 }
                                 Err(e) => {
                                     eprintln!("Failed to toggle autostart: {}", e);
@@ -421,6 +529,19 @@ pub fn run() {
                                 eprintln!("Failed to set theme to dark: {}", e);
                             }
                         }
+                        id if id == "theme_auto_time" => {
+                            if let Err(e) = set_theme(app.clone(), "auto_time".to_string()) {
+                                eprintln!("Failed to set theme to auto_time: {}", e);
+                            }
+                        }
+                        id if id == "refresh_status" => {
+                            let app_clone = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = commands::refresh_all_status(app_clone).await {
+                                    eprintln!("Failed to refresh host status: {}", e);
+                                }
+                            });
+                        }
                         id if id == "about" => {
                             if let Err(e) = show_about(app.clone()) {
                                 eprintln!("Failed to show about window: {}", e);
@@ -461,18 +582,20 @@ pub fn run() {
 
             // Register global hotkey Ctrl+Shift+R to show the main window
             // Note: We don't fail the app if hotkey registration fails
+            use crate::core::shortcuts::{SHOW_MAIN_SHORTCUT, TOGGLE_ERROR_SHORTCUT};
             use tauri_plugin_global_shortcut::GlobalShortcutExt;
             let app_handle_for_hotkey = app.app_handle().clone();
             let app_handle_for_error_hotkey = app.app_handle().clone();
             let shortcut_manager = app.handle().global_shortcut();
 
             // Try to unregister first in case it was registered by a previous instance
-            let _ = shortcut_manager.unregister("Ctrl+Shift+R");
-            let _ = shortcut_manager.unregister("Ctrl+Shift+E");
+            let _ = shortcut_manager.unregister(SHOW_MAIN_SHORTCUT);
+            let _ = shortcut_manager.unregister(TOGGLE_ERROR_SHORTCUT);
 
-            // Set up the handler for Ctrl+Shift+R BEFORE registering (per Tauri docs)
-            match shortcut_manager.on_shortcut(
-                "Ctrl+Shift+R",
+            // on_shortcut installs the handler and registers the hotkey in one step;
+            // the outcome is recorded for get_shortcut_status
+            let main_hotkey_result = shortcut_manager.on_shortcut(
+                SHOW_MAIN_SHORTCUT,
                 move |_app_handle, _shortcut, event| {
                     // Only trigger on key press (Down), not on release (Up) to prevent double-toggle
                     use tauri_plugin_global_shortcut::ShortcutState;
@@ -513,31 +636,22 @@ pub fn run() {
                         });
                     }
                 },
-            ) {
-                Ok(_) => {
-                    println!("Global hotkey handler for Ctrl+Shift+R registered");
-
-                    // Now register the actual shortcut
-                    match shortcut_manager.register("Ctrl+Shift+R") {
-                        Ok(_) => println!("Global hotkey Ctrl+Shift+R activated successfully"),
-                        Err(e) => {
-                            eprintln!(
-                                "Warning: Failed to register global hotkey Ctrl+Shift+R: {:?}",
-                                e
-                            );
-                            eprintln!("The hotkey may be in use by another application.");
-                        }
-                    }
-                }
+            );
+            match &main_hotkey_result {
+                Ok(_) => println!("Global hotkey Ctrl+Shift+R activated successfully"),
                 Err(e) => {
-                    eprintln!("Warning: Failed to set up hotkey handler: {:?}", e);
-                    eprintln!("The application will continue without the global hotkey.");
+                    eprintln!(
+                        "Warning: Failed to register global hotkey Ctrl+Shift+R: {:?}",
+                        e
+                    );
+                    eprintln!("The hotkey may be in use by another application.");
                 }
             }
+            commands::windows::record_shortcut_result(SHOW_MAIN_SHORTCUT, main_hotkey_result);
 
-            // Set up the handler for Ctrl+Shift+E to toggle error window
-            match shortcut_manager.on_shortcut(
-                "Ctrl+Shift+E",
+            // Ctrl+Shift+E toggles the error window
+            let error_hotkey_result = shortcut_manager.on_shortcut(
+                TOGGLE_ERROR_SHORTCUT,
                 move |_app_handle, _shortcut, event| {
                     // Only trigger on key press (Down), not on release (Up) to prevent double-toggle
                     use tauri_plugin_global_shortcut::ShortcutState;
@@ -570,30 +684,23 @@ pub fn run() {
                         });
                     }
                 },
-            ) {
-                Ok(_) => {
-                    println!("Global hotkey handler for Ctrl+Shift+E registered");
-
-                    // Now register the actual shortcut
-                    match shortcut_manager.register("Ctrl+Shift+E") {
-                        Ok(_) => println!("Global hotkey Ctrl+Shift+E activated successfully"),
-                        Err(e) => {
-                            eprintln!(
-                                "Warning: Failed to register global hotkey Ctrl+Shift+E: {:?}",
-                                e
-                            );
-                            eprintln!("The hotkey may be in use by another application.");
-                        }
-                    }
-                }
+            );
+            match &error_hotkey_result {
+                Ok(_) => println!("Global hotkey Ctrl+Shift+E activated successfully"),
                 Err(e) => {
                     eprintln!(
-                        "Warning: Failed to set up Ctrl+Shift+E hotkey handler: {:?}",
+                        "Warning: Failed to register global hotkey Ctrl+Shift+E: {:?}",
                         e
                     );
-                    eprintln!("The application will continue without this global hotkey.");
+                    eprintln!("The hotkey may be in use by another application.");
                 }
             }
+            commands::windows::record_shortcut_result(TOGGLE_ERROR_SHORTCUT, error_hotkey_result);
+
+            // Launch a connection requested with --connect <hostname>
+            if let Some(hostname) = connect_target.clone() {
+                launch_from_command_line(app.app_handle().clone(), hostname);
+            }
 
             Ok(())
         })
@@ -603,6 +710,7 @@ pub fn run() {
             commands::windows::show_about,
             commands::windows::show_error,
             commands::windows::toggle_error_window,
+            commands::windows::get_shortcut_status,
             commands::windows::toggle_visible_window,
             commands::windows::close_login_window,
             commands::windows::close_login_and_prepare_main,
@@ -612,29 +720,104 @@ pub fn run() {
             commands::windows::hide_main_window,
             commands::windows::show_hosts_window,
             commands::windows::hide_hosts_window,
+            commands::windows::record_activity,
             // Credentials (from commands::credentials)
             commands::save_credentials,
             commands::get_stored_credentials,
+            commands::get_credential_info,
             commands::delete_credentials,
+            commands::get_credential_age,
+            commands::check_credential_age_warning,
             commands::save_host_credentials,
+            commands::set_group_credentials,
             commands::get_host_credentials,
             commands::delete_host_credentials,
+            commands::save_gateway_credentials,
+            commands::delete_gateway_credentials,
             commands::list_hosts_with_credentials,
+            commands::validate_username,
+            commands::list_credential_targets,
+            commands::audit_credential_targets,
+            commands::get_dashboard_stats,
+            commands::test_all_credentials,
+            commands::explain_credential_resolution,
+            commands::clear_host_credential,
+            commands::migrate_credentials_to_domain_type,
             // Hosts (from commands::hosts)
             commands::get_hosts,
+            commands::get_hosts_version,
             commands::get_all_hosts,
             commands::save_host,
+            commands::add_hosts_from_cidr,
+            commands::add_hosts_from_pattern,
+            commands::duplicate_host,
+            commands::set_hosts_group,
+            commands::set_host_tags,
+            commands::deduplicate_hosts,
+            commands::reorder_hosts,
+            commands::import_rdp_directory,
+            commands::validate_hosts_csv,
+            commands::convert_hosts_to_json,
+            commands::convert_hosts_to_csv,
             commands::delete_host,
             commands::search_hosts,
+            commands::search_hosts_by_tags,
+            commands::get_never_connected_hosts,
+            commands::search_hosts_regex,
+            commands::get_recent_usage,
+            commands::request_delete_all_token,
             commands::delete_all_hosts,
             commands::check_host_status,
+            commands::probe_rdp_security,
+            commands::get_hosts_grouped_by_status,
+            commands::refresh_all_status,
+            commands::start_status_polling,
+            commands::stop_status_polling,
+            // Settings (from commands::settings)
+            commands::settings::get_settings,
+            commands::settings::get_registry_defaults,
+            commands::settings::set_settings,
+            commands::settings::get_rdp_options,
+            commands::settings::set_rdp_options,
+            commands::settings::suggest_rdp_resolution,
+            commands::settings::export_ui_preferences,
+            commands::settings::import_ui_preferences,
+            commands::settings::get_auto_hide_minutes,
+            commands::settings::set_auto_hide_minutes,
+            commands::settings::get_preserve_rdp_edits,
+            commands::settings::set_preserve_rdp_edits,
+            commands::settings::set_host_stable,
             // System operations (from commands::system)
             commands::system::launch_rdp,
+            commands::system::connect_group,
+            commands::system::get_host_last_error,
+            commands::system::edit_rdp_file,
+            commands::system::clear_rdp_file,
+            commands::system::preview_rdp_content,
+            commands::system::validate_generated_rdp,
             commands::system::scan_domain,
+            commands::system::test_ldap_connection,
+            commands::system::preview_ldap_bind,
+            commands::system::scan_domain_preview,
+            commands::system::scan_domain_detailed,
+            commands::system::diff_scan_result,
+            commands::system::commit_scanned_hosts,
             commands::system::reset_application,
+            commands::system::backup_data,
+            commands::system::restore_data,
+            commands::system::refresh_tray,
             commands::system::check_autostart,
             commands::system::toggle_autostart,
+            commands::system::audit_autostart,
+            commands::system::clean_autostart_duplicates,
             commands::system::get_recent_connections,
+            commands::system::get_recent_connections_since,
+            commands::system::get_recent_connections_with_status,
+            commands::system::search_connection_history,
+            commands::system::save_recent_as_host,
+            commands::system::get_log_tail,
+            commands::system::set_category_log_level,
+            commands::system::get_storage_status,
             // Theme management (from commands::theme)
             commands::theme::get_windows_theme,
             commands::theme::set_theme,
@@ -739,6 +922,26 @@ mod tests {
             assert!(recent.connections[0].timestamp <= after);
         }
 
+        #[test]
+        fn test_add_connection_with_clock_records_exact_timestamp() {
+            use crate::core::clock::{Clock, MockClock};
+
+            let clock = MockClock::new(
+                chrono::DateTime::parse_from_rfc3339("2025-12-13T14:30:00Z")
+                    .unwrap()
+                    .with_timezone(&chrono::Local),
+            );
+            let mut recent = RecentConnections::new();
+
+            recent.add_connection_with_clock("server01.domain.com".to_string(), "First".to_string(), &clock);
+            clock.advance(chrono::Duration::seconds(5));
+            recent.add_connection_with_clock("server02.domain.com".to_string(), "Second".to_string(), &clock);
+
+            assert_eq!(recent.connections[0].timestamp, 1_765_636_205);
+            assert_eq!(recent.connections[1].timestamp, 1_765_636_200);
+            assert_eq!(recent.connections[0].timestamp as i64, clock.now().timestamp());
+        }
+
         #[test]
         fn test_add_connection_with_empty_description() {
             let mut recent = RecentConnections::new();
@@ -762,6 +965,101 @@ mod tests {
             assert_eq!(recent.connections[0].hostname, "server01.domain.com");
             assert_eq!(recent.connections[0].description, "First Again");
         }
+
+        fn recent_with(entries: &[(&str, &str, u64)]) -> RecentConnections {
+            RecentConnections {
+                connections: entries
+                    .iter()
+                    .map(|(hostname, description, timestamp)| RecentConnection {
+                        hostname: hostname.to_string(),
+                        description: description.to_string(),
+                        timestamp: *timestamp,
+                    })
+                    .collect(),
+            }
+        }
+
+        #[test]
+        fn test_merge_overlapping_keeps_newer_timestamp() {
+            let mut recent = recent_with(&[
+                ("server01.domain.com", "Local newer", 2000),
+                ("server02.domain.com", "Local older", 1000),
+            ]);
+            let other = recent_with(&[
+                ("server01.domain.com", "Remote older", 1500),
+                ("server02.domain.com", "Remote newer", 2500),
+            ]);
+
+            recent.merge(other);
+
+            assert_eq!(recent.connections.len(), 2);
+            assert_eq!(recent.connections[0].hostname, "server02.domain.com");
+            assert_eq!(recent.connections[0].description, "Remote newer");
+            assert_eq!(recent.connections[0].timestamp, 2500);
+            assert_eq!(recent.connections[1].hostname, "server01.domain.com");
+            assert_eq!(recent.connections[1].description, "Local newer");
+            assert_eq!(recent.connections[1].timestamp, 2000);
+        }
+
+        #[test]
+        fn test_merge_disjoint_sets_sorted_by_timestamp() {
+            let mut recent = recent_with(&[
+                ("server01.domain.com", "One", 1000),
+                ("server03.domain.com", "Three", 3000),
+            ]);
+            let other = recent_with(&[
+                ("server02.domain.com", "Two", 2000),
+                ("server04.domain.com", "Four", 4000),
+            ]);
+
+            recent.merge(other);
+
+            let hostnames: Vec<&str> = recent
+                .connections
+                .iter()
+                .map(|c| c.hostname.as_str())
+                .collect();
+            assert_eq!(
+                hostnames,
+                vec![
+                    "server04.domain.com",
+                    "server03.domain.com",
+                    "server02.domain.com",
+                    "server01.domain.com",
+                ]
+            );
+        }
+
+        #[test]
+        fn test_merge_truncates_to_max() {
+            let mut recent = recent_with(&[
+                ("server01.domain.com", "", 1),
+                ("server02.domain.com", "", 2),
+                ("server03.domain.com", "", 3),
+                ("server04.domain.com", "", 4),
+            ]);
+            let other = recent_with(&[
+                ("server05.domain.com", "", 5),
+                ("server06.domain.com", "", 6),
+                ("server07.domain.com", "", 7),
+            ]);
+
+            recent.merge(other);
+
+            assert_eq!(recent.connections.len(), MAX_RECENT_CONNECTIONS);
+            assert_eq!(recent.connections[0].hostname, "server07.domain.com");
+            assert_eq!(recent.connections[4].hostname, "server03.domain.com");
+        }
+
+        #[test]
+        fn test_merge_with_empty_other_is_noop() {
+            let mut recent = recent_with(&[("server01.domain.com", "One", 1000)]);
+
+            recent.merge(RecentConnections::new());
+
+            assert_eq!(recent.connections.len(), 1);
+            assert_eq!(recent.connections[0].hostname, "server01.domain.com");
+        }
     }
 
     // ========================================================================
@@ -777,6 +1075,7 @@ mod tests {
                 hostname: "server.domain.com".to_string(),
                 description: "Test Server".to_string(),
                 last_connected: Some("15/01/2024 10:30:00".to_string()),
+                ..Default::default()
             };
 
             let json = serde_json::to_string(&host).expect("Host serialization should succeed");
@@ -817,6 +1116,7 @@ mod tests {
                 hostname: "server.domain.com".to_string(),
                 description: "Test".to_string(),
                 last_connected: None,
+                ..Default::default()
             };
 
             let cloned = host.clone();
@@ -873,6 +1173,146 @@ mod tests {
         }
     }
 
+    // ========================================================================
+    // Tests for command-line argument parsing
+    // ========================================================================
+
+    mod command_line_tests {
+        use super::*;
+
+        fn args(values: &[&str]) -> Vec<String> {
+            values.iter().map(|v| v.to_string()).collect()
+        }
+
+        #[test]
+        fn test_parse_connect_arg_separate_value() {
+            let argv = args(&["QuickConnect.exe", "--connect", "server01.domain.com"]);
+            assert_eq!(parse_connect_arg(&argv), Some("server01.domain.com".to_string()));
+        }
+
+        #[test]
+        fn test_parse_connect_arg_equals_form() {
+            let argv = args(&["QuickConnect.exe", "--connect=server01.domain.com"]);
+            assert_eq!(parse_connect_arg(&argv), Some("server01.domain.com".to_string()));
+        }
+
+        #[test]
+        fn test_parse_connect_arg_with_other_flags() {
+            let argv = args(&["QuickConnect.exe", "--debug", "--connect", "server01", "--debug-log"]);
+            assert_eq!(parse_connect_arg(&argv), Some("server01".to_string()));
+        }
+
+        #[test]
+        fn test_parse_connect_arg_absent() {
+            assert_eq!(parse_connect_arg(&args(&["QuickConnect.exe"])), None);
+            assert_eq!(parse_connect_arg(&args(&["QuickConnect.exe", "--debug"])), None);
+            assert_eq!(parse_connect_arg(&[]), None);
+        }
+
+        #[test]
+        fn test_parse_connect_arg_missing_value() {
+            assert_eq!(parse_connect_arg(&args(&["QuickConnect.exe", "--connect"])), None);
+            assert_eq!(parse_connect_arg(&args(&["QuickConnect.exe", "--connect="])), None);
+            assert_eq!(
+                parse_connect_arg(&args(&["QuickConnect.exe", "--connect", "--debug"])),
+                None
+            );
+        }
+
+        #[test]
+        fn test_parse_connect_arg_trims_whitespace() {
+            let argv = args(&["QuickConnect.exe", "--connect", "  server01  "]);
+            assert_eq!(parse_connect_arg(&argv), Some("server01".to_string()));
+        }
+
+        #[test]
+        fn test_single_instance_forwarded_connect_launches() {
+            use crate::core::single_instance::{single_instance_action, SingleInstanceAction, SingleInstanceTarget};
+
+            let argv = args(&["QuickConnect.exe", "--connect=server01.domain.com"]);
+            assert_eq!(
+                single_instance_action(parse_connect_arg(&argv), SingleInstanceTarget::Main, "hosts"),
+                SingleInstanceAction::Connect("server01.domain.com".to_string())
+            );
+        }
+
+        #[test]
+        fn test_single_instance_without_connect_shows_configured_window() {
+            use crate::core::single_instance::{single_instance_action, SingleInstanceAction, SingleInstanceTarget};
+
+            let argv = args(&["QuickConnect.exe", "--debug"]);
+            assert_eq!(
+                single_instance_action(parse_connect_arg(&argv), SingleInstanceTarget::Main, "hosts"),
+                SingleInstanceAction::ShowWindow("main".to_string())
+            );
+            assert_eq!(
+                single_instance_action(parse_connect_arg(&argv), SingleInstanceTarget::LastHidden, "hosts"),
+                SingleInstanceAction::ShowWindow("hosts".to_string())
+            );
+        }
+    }
+
+    // ========================================================================
+    // Tests for HostErrorTracker
+    // ========================================================================
+
+    mod host_error_tracker_tests {
+        use super::*;
+
+        #[test]
+        fn test_record_failure_stores_error() {
+            let mut tracker = HostErrorTracker::new();
+            tracker.record_failure("server01.domain.com", "RDP", "Failed to launch", 1705312200);
+
+            let error = tracker
+                .last_error("server01.domain.com")
+                .expect("Failure should be recorded");
+            assert_eq!(error.category, "RDP");
+            assert_eq!(error.message, "Failed to launch");
+            assert_eq!(error.timestamp, 1705312200);
+            assert!(tracker.last_error("server02.domain.com").is_none());
+        }
+
+        #[test]
+        fn test_newer_failure_overwrites_previous() {
+            let mut tracker = HostErrorTracker::new();
+            tracker.record_failure("server01.domain.com", "RDP", "First failure", 100);
+            tracker.record_failure("server01.domain.com", "CREDENTIALS", "Second failure", 200);
+
+            let error = tracker
+                .last_error("server01.domain.com")
+                .expect("Failure should be recorded");
+            assert_eq!(error.category, "CREDENTIALS");
+            assert_eq!(error.message, "Second failure");
+            assert_eq!(error.timestamp, 200);
+            assert_eq!(tracker.errors.len(), 1);
+        }
+
+        #[test]
+        fn test_older_failure_does_not_overwrite() {
+            let mut tracker = HostErrorTracker::new();
+            tracker.record_failure("server01.domain.com", "RDP", "Newer failure", 200);
+            tracker.record_failure("server01.domain.com", "RDP", "Older failure", 100);
+
+            let error = tracker
+                .last_error("server01.domain.com")
+                .expect("Failure should be recorded");
+            assert_eq!(error.message, "Newer failure");
+        }
+
+        #[test]
+        fn test_success_clears_error() {
+            let mut tracker = HostErrorTracker::new();
+            tracker.record_failure("server01.domain.com", "RDP", "Failed to launch", 100);
+            tracker.record_failure("server02.domain.com", "RDP", "Failed to launch", 100);
+
+            tracker.record_success("server01.domain.com");
+
+            assert!(tracker.last_error("server01.domain.com").is_none());
+            assert!(tracker.last_error("server02.domain.com").is_some());
+        }
+    }
+
     // ========================================================================
     // Tests for Credentials struct
     // ========================================================================
@@ -989,6 +1429,7 @@ mod tests {
         fn test_hosts_csv_path_format() {
             // This test verifies the path format, not the actual directory
             // Since infra::get_hosts_csv_path() depends on APPDATA, we test the logic
+            let _env = infra::paths::EnvGuard::lock();
             let result = infra::get_hosts_csv_path();
             if let Ok(path) = result {
                 assert!(path.ends_with("hosts.csv"));
@@ -999,6 +1440,7 @@ mod tests {
 
         #[test]
         fn test_recent_connections_file_path_format() {
+            let _env = infra::paths::EnvGuard::lock();
             let result = infra::get_recent_connections_path();
             if let Ok(path) = result {
                 assert!(path.ends_with("recent_connections.json"));
@@ -1048,6 +1490,7 @@ mod tests {
                         hostname: record[0].to_string(),
                         description: record[1].to_string(),
                         last_connected,
+                        ..Default::default()
                     });
                 }
             }
@@ -1082,6 +1525,7 @@ mod tests {
                         hostname: record[0].to_string(),
                         description: record[1].to_string(),
                         last_connected: None,
+                        ..Default::default()
                     });
                 }
             }
@@ -1110,6 +1554,7 @@ mod tests {
                     hostname: r[0].to_string(),
                     description: r[1].to_string(),
                     last_connected: None,
+                    ..Default::default()
                 })
                 .collect();
 
@@ -1213,6 +1658,110 @@ mod tests {
         }
     }
 
+    // ========================================================================
+    // Tests for username validation (validate_username command)
+    // ========================================================================
+
+    mod username_validation_tests {
+        use crate::core::rdp::{analyze_username, UsernameFormat};
+
+        #[test]
+        fn test_validate_domain_backslash_format() {
+            let info = analyze_username("CONTOSO\\admin");
+            assert_eq!(info.format, UsernameFormat::Netbios);
+            assert_eq!(info.domain, "CONTOSO");
+            assert_eq!(info.username, "admin");
+            assert!(info.warnings.is_empty());
+        }
+
+        #[test]
+        fn test_validate_upn_format() {
+            let info = analyze_username("admin@contoso.com");
+            assert_eq!(info.format, UsernameFormat::Upn);
+            assert_eq!(info.domain, "contoso.com");
+            assert_eq!(info.username, "admin");
+            assert!(info.warnings.is_empty());
+        }
+
+        #[test]
+        fn test_validate_plain_username() {
+            let info = analyze_username("localadmin");
+            assert_eq!(info.format, UsernameFormat::Plain);
+            assert_eq!(info.domain, "");
+            assert_eq!(info.username, "localadmin");
+            assert!(info.warnings.is_empty());
+        }
+
+        #[test]
+        fn test_validate_domain_with_subdomain() {
+            let info = analyze_username("user@corp.contoso.com");
+            assert_eq!(info.format, UsernameFormat::Upn);
+            assert_eq!(info.domain, "corp.contoso.com");
+            assert!(info.warnings.is_empty());
+        }
+
+        #[test]
+        fn test_validate_username_with_dots() {
+            let info = analyze_username("DOMAIN\\john.doe");
+            assert_eq!(info.format, UsernameFormat::Netbios);
+            assert_eq!(info.username, "john.doe");
+            assert!(info.warnings.is_empty());
+        }
+
+        #[test]
+        fn test_validate_empty_username() {
+            let info = analyze_username("");
+            assert_eq!(info.format, UsernameFormat::Plain);
+            assert_eq!(info.warnings, vec!["Username is empty".to_string()]);
+
+            let info = analyze_username("   ");
+            assert_eq!(info.warnings, vec!["Username is empty".to_string()]);
+        }
+
+        #[test]
+        fn test_validate_double_backslash_warns() {
+            let info = analyze_username("contoso\\\\admin");
+            assert_eq!(info.format, UsernameFormat::Netbios);
+            assert_eq!(info.domain, "contoso");
+            assert!(info.warnings.iter().any(|w| w.contains("double backslash")));
+        }
+
+        #[test]
+        fn test_validate_surrounding_whitespace_warns() {
+            let info = analyze_username("CONTOSO\\admin ");
+            assert_eq!(info.format, UsernameFormat::Netbios);
+            assert!(info
+                .warnings
+                .iter()
+                .any(|w| w.contains("leading or trailing whitespace")));
+        }
+
+        #[test]
+        fn test_validate_inner_whitespace_warns() {
+            let info = analyze_username("john doe@contoso.com");
+            assert_eq!(info.format, UsernameFormat::Upn);
+            assert!(info.warnings.iter().any(|w| w.contains("contains whitespace")));
+        }
+
+        #[test]
+        fn test_validate_missing_parts_warn() {
+            let info = analyze_username("CONTOSO\\");
+            assert!(info.warnings.iter().any(|w| w.contains("User part is empty")));
+
+            let info = analyze_username("@contoso.com");
+            assert!(info.warnings.iter().any(|w| w.contains("User part is empty")));
+        }
+
+        #[test]
+        fn test_validate_multiple_at_signs() {
+            // Classified as UPN; only the first @ separates user and domain
+            let info = analyze_username("user@first@second.com");
+            assert_eq!(info.format, UsernameFormat::Upn);
+            assert_eq!(info.domain, "first@second.com");
+            assert_eq!(info.username, "user");
+        }
+    }
+
     // ========================================================================
     // Tests for search/filter logic
     // ========================================================================
@@ -1226,21 +1775,25 @@ mod tests {
                     hostname: "web01.domain.com".to_string(),
                     description: "Production Web Server".to_string(),
                     last_connected: None,
+                    ..Default::default()
                 },
                 Host {
                     hostname: "web02.domain.com".to_string(),
                     description: "Staging Web Server".to_string(),
                     last_connected: None,
+                    ..Default::default()
                 },
                 Host {
                     hostname: "db01.domain.com".to_string(),
                     description: "MySQL Database".to_string(),
                     last_connected: None,
+                    ..Default::default()
                 },
                 Host {
                     hostname: "dc01.contoso.local".to_string(),
                     description: "Domain Controller".to_string(),
                     last_connected: None,
+                    ..Default::default()
                 },
             ]
         }
@@ -1432,6 +1985,7 @@ mod tests {
                 hostname: "server01.domain.com".to_string(),
                 description: "First Server".to_string(),
                 last_connected: None,
+                ..Default::default()
             };
             hosts.push(host1);
             assert_eq!(hosts.len(), 1);
@@ -1494,7 +2048,7 @@ mod tests {
         fn test_check_host_status_invalid_hostname_returns_unknown() {
             // Test with completely invalid hostname (no dots, special chars)
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("!!!invalid!!!".to_string()));
+            let result = rt.block_on(commands::check_host_status("!!!invalid!!!".to_string(), None));
             assert!(result.is_ok());
             assert_eq!(result.unwrap(), "unknown");
         }
@@ -1503,7 +2057,7 @@ mod tests {
         fn test_check_host_status_empty_hostname() {
             // Test with empty hostname - returns offline (connection refused on empty string:3389)
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("".to_string()));
+            let result = rt.block_on(commands::check_host_status("".to_string(), None));
             assert!(result.is_ok());
             // Empty hostname resolves but connection fails
             assert_eq!(result.unwrap(), "offline");
@@ -1513,7 +2067,7 @@ mod tests {
         fn test_check_host_status_malformed_hostname_returns_unknown() {
             // Test with hostname that can't be resolved
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("nonexistent.invalid.test.local".to_string()));
+            let result = rt.block_on(commands::check_host_status("nonexistent.invalid.test.local".to_string(), None));
             assert!(result.is_ok());
             assert_eq!(result.unwrap(), "unknown");
         }
@@ -1523,7 +2077,7 @@ mod tests {
             // Test with valid hostname format but unreachable host
             // Using a reserved IP that should timeout/fail
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("192.0.2.1".to_string())); // TEST-NET-1 (RFC 5737)
+            let result = rt.block_on(commands::check_host_status("192.0.2.1".to_string(), None)); // TEST-NET-1 (RFC 5737)
             assert!(result.is_ok());
             let status = result.unwrap();
             // Should be offline (connection timeout/refused)
@@ -1534,7 +2088,7 @@ mod tests {
         fn test_check_host_status_localhost_may_vary() {
             // Test with localhost - result depends on whether RDP is running
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("127.0.0.1".to_string()));
+            let result = rt.block_on(commands::check_host_status("127.0.0.1".to_string(), None));
             assert!(result.is_ok());
             let status = result.unwrap();
             // Status can be online, offline, or unknown depending on system
@@ -1545,7 +2099,7 @@ mod tests {
         fn test_check_host_status_with_spaces_returns_unknown() {
             // Test with hostname containing spaces (invalid)
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("server with spaces.com".to_string()));
+            let result = rt.block_on(commands::check_host_status("server with spaces.com".to_string(), None));
             assert!(result.is_ok());
             assert_eq!(result.unwrap(), "unknown");
         }
@@ -1554,7 +2108,7 @@ mod tests {
         fn test_check_host_status_with_unicode_returns_unknown() {
             // Test with Unicode characters in hostname
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("服务器.domain.com".to_string()));
+            let result = rt.block_on(commands::check_host_status("服务器.domain.com".to_string(), None));
             assert!(result.is_ok());
             // May return unknown due to DNS resolution failure
             let status = result.unwrap();
@@ -1566,7 +2120,7 @@ mod tests {
             // Test with extremely long hostname (exceeds DNS limits)
             let long_hostname = "a".repeat(300) + ".domain.com";
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status(long_hostname));
+            let result = rt.block_on(commands::check_host_status(long_hostname, None));
             assert!(result.is_ok());
             assert_eq!(result.unwrap(), "unknown");
         }
@@ -1575,7 +2129,7 @@ mod tests {
         fn test_check_host_status_null_byte_in_hostname() {
             // Test with null byte (should be handled safely)
             let rt = Runtime::new().unwrap();
-            let result = rt.block_on(commands::check_host_status("server\0.domain.com".to_string()));
+            let result = rt.block_on(commands::check_host_status("server\0.domain.com".to_string(), None));
             assert!(result.is_ok());
             assert_eq!(result.unwrap(), "unknown");
         }
@@ -1592,7 +2146,7 @@ mod tests {
 
             let mut handles = vec![];
             for host in hosts {
-                let handle = rt.spawn(async move { commands::check_host_status(host).await });
+                let handle = rt.spawn(async move { commands::check_host_status(host, None).await });
                 handles.push(handle);
             }
 
@@ -1608,7 +2162,7 @@ mod tests {
         fn test_check_host_status_ipv6_localhost() {
             // Test with IPv6 localhost
             let rt = Runtime::new().expect("Failed to create tokio runtime");
-            let result = rt.block_on(commands::check_host_status("::1".to_string()));
+            let result = rt.block_on(commands::check_host_status("::1".to_string(), None));
             assert!(result.is_ok());
             let status = result.expect("check_host_status should return Ok");
             // Status depends on whether RDP is running on IPv6
@@ -1619,7 +2173,7 @@ mod tests {
         fn test_check_host_status_returns_result_not_error() {
             // Verify function returns Result, not panic
             let rt = Runtime::new().expect("Failed to create tokio runtime");
-            let result = rt.block_on(commands::check_host_status("invalid".to_string()));
+            let result = rt.block_on(commands::check_host_status("invalid".to_string(), None));
             // Should always return Ok, never Err
             assert!(result.is_ok());
         }
@@ -47,6 +47,9 @@ export const invoke = vi.fn(
       case "search_hosts":
         return [] as T;
 
+      case "request_delete_all_token":
+        return "test-token" as T;
+
       case "get_recent_connections":
         return [] as T;
 
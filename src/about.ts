@@ -98,12 +98,12 @@ document.addEventListener("DOMContentLoaded", async () => {
       }
       
       try {
-        const result = await invoke<string>("reset_application");
+        const result = await invoke<{ complete: boolean; message: string }>("reset_application");
         await showCustomDialog({
           title: 'Application Reset',
-          message: result,
+          message: result.message,
           type: 'alert',
-          icon: 'success'
+          icon: result.complete ? 'success' : 'warning'
         });
 
         // Return to the initial credentials screen
@@ -36,8 +36,16 @@ interface StoredCredentials {
   password: string;
 }
 
+// Result of save_host; loopback hosts are held back until confirmed
+interface SaveHostOutcome {
+  saved: boolean;
+  loopback_warning: boolean;
+}
+
 let hosts: Host[] = [];
 let filteredHosts: Host[] = [];
+// Host list version last loaded (see get_hosts_version)
+let hostsVersion: number | null = null;
 
 /**
  * Displays an error in the dedicated error window.
@@ -85,7 +93,9 @@ async function initializeTheme() {
   });
 
   // Listen for hosts-updated events to refresh the hosts list
-  await listen("hosts-updated", async () => {
+  // (skipped when this window already has the version the event carries)
+  await listen<number>("hosts-updated", async (event) => {
+    if (event.payload === hostsVersion) return;
     await loadHosts();
   });
 }
@@ -128,11 +138,11 @@ async function handleHostFormSubmit(e: Event): Promise<void> {
     return;
   }
 
-  // Validate hostname format
-  if (!isValidFQDN(hostname)) {
+  // Validate hostname format; an explicit RDP port (server.domain.com:3390) is allowed
+  if (!isValidFQDN(hostname.replace(/:\d{1,5}$/, ''))) {
     await showCustomDialog({
       title: 'Invalid Hostname',
-      message: 'Please enter a valid hostname in the format: server.domain.com',
+      message: 'Please enter a valid hostname in the format: server.domain.com (optionally with :port)',
       type: 'alert',
       icon: 'error'
     });
@@ -370,6 +380,8 @@ function filterHosts(searchTerm: string) {
  */
 async function loadHosts() {
   try {
+    // Read the version first so a write landing mid-load triggers another reload
+    hostsVersion = await invoke<number>("get_hosts_version");
     hosts = await invoke<Host[]>("get_hosts");
     filteredHosts = [...hosts];
     renderHosts();
@@ -465,7 +477,19 @@ function renderHosts() {
  */
 async function saveHost(host: Host) {
   try {
-    await invoke("save_host", { host });
+    const outcome = await invoke<SaveHostOutcome | undefined>("save_host", { host });
+    if (outcome && !outcome.saved && outcome.loopback_warning) {
+      const confirmed = await showCustomDialog({
+        title: 'Loopback Host',
+        message: `${host.hostname} points back at this computer, so connecting to it will not reach another server. Save it anyway?`,
+        type: 'confirm',
+        icon: 'warning',
+        confirmText: 'Save',
+        cancelText: 'Cancel'
+      });
+      if (!confirmed) return;
+      await invoke("save_host", { host, confirmLoopback: true });
+    }
     await loadHosts();
   } catch (error) {
     throw error;
@@ -637,7 +661,8 @@ async function deleteAllHosts() {
   if (!confirmed) return;
 
   try {
-    await invoke("delete_all_hosts");
+    const token = await invoke<string>("request_delete_all_token");
+    await invoke("delete_all_hosts", { token });
     await loadHosts();
     showToast("All hosts deleted successfully", "success");
   } catch (error) {
@@ -719,12 +744,12 @@ document.addEventListener("DOMContentLoaded", async () => {
       }
 
       try {
-        const result = await invoke<string>("reset_application");
+        const result = await invoke<{ complete: boolean; message: string }>("reset_application");
         await showCustomDialog({
           title: 'Application Reset',
-          message: result,
+          message: result.message,
           type: 'alert',
-          icon: 'success'
+          icon: result.complete ? 'success' : 'warning'
         });
 
         // Return to the initial credentials screen
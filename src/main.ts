@@ -256,10 +256,26 @@ async function initializeTheme() {
   });
 
   // Listen for hosts-updated events to refresh the hosts list
-  await listen("hosts-updated", async () => {
+  // (skipped when this window already has the version the event carries)
+  await listen<number>("hosts-updated", async (event) => {
+    if (event.payload === hostsVersion) return;
     await loadAllHosts();
     await checkHostsStatus();
   });
+
+  // Listen for status changes pushed by the background poller
+  await listen<{ hostname: string; previous: string; status: string }>(
+    "host-status-changed",
+    (event) => {
+      const host = allHosts.find((h) => h.hostname === event.payload.hostname);
+      if (!host) return;
+      host.status = event.payload.status as Host["status"];
+
+      const searchInput = document.querySelector("#search-input") as HTMLInputElement;
+      const query = searchInput?.value || "";
+      renderHostsList(filterHosts(query), query);
+    },
+  );
 }
 
 // Declare this once at the top of the file
@@ -278,6 +294,8 @@ let isIntentionalReturn = false;
 
 // Store all hosts globally for client-side filtering
 let allHosts: Host[] = [];
+// Host list version last loaded (see get_hosts_version)
+let hostsVersion: number | null = null;
 
 /**
  * Highlights matching characters in text based on the search query.
@@ -415,6 +433,8 @@ async function handleSearch() {
  */
 async function loadAllHosts() {
   try {
+    // Read the version first so a write landing mid-load triggers another reload
+    hostsVersion = await invoke<number>("get_hosts_version");
     allHosts = await invoke<Host[]>("get_all_hosts");
     renderHostsList(allHosts);
   } catch (err) {
@@ -442,6 +462,8 @@ function initializeSearch() {
   if (searchInput) {
     // Handle input changes with debounce for smoother performance
     searchInput.addEventListener("input", () => {
+      // Searching counts as activity for the idle auto-hide timer
+      invoke("record_activity").catch(() => {});
       clearTimeout(searchTimeout);
       searchTimeout = setTimeout(() => {
         handleSearch();
@@ -450,6 +472,31 @@ function initializeSearch() {
   }
 }
 
+/**
+ * Warns when the stored credentials are past their configured age.
+ * Only the main window shows the warning, once per load.
+ */
+async function checkCredentialAge() {
+  if (!document.querySelector("#server-list")) {
+    return;
+  }
+
+  try {
+    const ageDays = await invoke<number | null>("check_credential_age_warning");
+    if (ageDays !== null) {
+      showNotificationUtil({
+        message: `Stored credentials are ${ageDays} day(s) old - consider updating them`,
+        type: "warning",
+        duration: 5000,
+        position: "top",
+      });
+    }
+  } catch (err) {
+    // The warning is advisory; a missing metadata file must not block the list
+    console.error("Error checking credential age:", err);
+  }
+}
+
 /**
  * Initializes the server list and sets up real-time event listeners.
  * Loads all hosts and listens for connection events to update last-connected timestamps.
@@ -461,6 +508,9 @@ async function initializeServerList() {
   // Automatically check host status after loading
   await checkHostsStatus();
 
+  // Warn about stale credentials now that the page can show it
+  await checkCredentialAge();
+
   // Listen for host-connected events to refresh the list in real-time
   await listen("host-connected", async () => {
     await loadAllHosts();
@@ -469,6 +519,7 @@ async function initializeServerList() {
 
   // Listen for focus-search event to focus the search input when window is shown via hotkey
   await listen("focus-search", () => {
+    invoke("record_activity").catch(() => {});
     const searchInput = document.querySelector(
       "#search-input",
     ) as HTMLInputElement;
@@ -687,12 +738,12 @@ document.addEventListener("DOMContentLoaded", async () => {
       }
 
       try {
-        const result = await invoke<string>("reset_application");
+        const result = await invoke<{ complete: boolean; message: string }>("reset_application");
         await showCustomDialog({
           title: 'Application Reset',
-          message: result,
+          message: result.message,
           type: 'alert',
-          icon: 'success'
+          icon: result.complete ? 'success' : 'warning'
         });
 
         // Return to the initial credentials screen
@@ -25,6 +25,11 @@ export interface Host {
   hostname: string;
   description: string;
   last_connected?: string;
+  connect_username?: string;
+  group?: string;
+  connect_count?: number;
+  tags?: string[];
+  sort_order?: number;
   status?: "online" | "offline" | "unknown" | "checking";
 }
 